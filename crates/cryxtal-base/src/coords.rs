@@ -0,0 +1,183 @@
+use crate::{Error, Result};
+
+/// A typed point: absolute `x,y,z` or relative `@dx,dy,dz`. Each component is an arithmetic
+/// expression (`+ - * /` and parentheses); a missing Z component is 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CoordinateInput {
+    Absolute([f64; 3]),
+    Relative([f64; 3]),
+}
+
+impl CoordinateInput {
+    /// The point this input names, with relative offsets taken from `reference`.
+    pub fn resolve(self, reference: [f64; 3]) -> [f64; 3] {
+        match self {
+            CoordinateInput::Absolute(point) => point,
+            CoordinateInput::Relative([dx, dy, dz]) => {
+                [reference[0] + dx, reference[1] + dy, reference[2] + dz]
+            }
+        }
+    }
+}
+
+pub fn parse_coordinate(text: &str) -> Result<CoordinateInput> {
+    let text = text.trim();
+    let (relative, body) = match text.strip_prefix('@') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let components = body
+        .split(',')
+        .map(evaluate_expression)
+        .collect::<Result<Vec<_>>>()?;
+    let point = match components.as_slice() {
+        [x, y] => [*x, *y, 0.0],
+        [x, y, z] => [*x, *y, *z],
+        _ => {
+            return Err(invalid(format!(
+                "expected 2 or 3 comma-separated values, got {}",
+                components.len()
+            )));
+        }
+    };
+    Ok(if relative {
+        CoordinateInput::Relative(point)
+    } else {
+        CoordinateInput::Absolute(point)
+    })
+}
+
+/// Evaluates a single arithmetic expression such as `1200 + 2*450` or `-(3000/4)`.
+pub fn evaluate_expression(text: &str) -> Result<f64> {
+    let mut parser = ExprParser {
+        chars: text.chars().filter(|c| !c.is_whitespace()).collect(),
+        pos: 0,
+        source: text.trim(),
+    };
+    if parser.chars.is_empty() {
+        return Err(invalid("empty value".to_string()));
+    }
+    let value = parser.expr()?;
+    if parser.pos != parser.chars.len() {
+        return Err(parser.unexpected());
+    }
+    if !value.is_finite() {
+        return Err(invalid(format!(
+            "`{}` is not a finite number",
+            parser.source
+        )));
+    }
+    Ok(value)
+}
+
+struct ExprParser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    source: &'a str,
+}
+
+impl ExprParser<'_> {
+    fn expr(&mut self) -> Result<f64> {
+        let mut value = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<f64> {
+        let mut value = self.factor()?;
+        while let Some(op @ ('*' | '/')) = self.peek() {
+            self.pos += 1;
+            let rhs = self.factor()?;
+            if op == '/' && rhs == 0.0 {
+                return Err(invalid(format!("division by zero in `{}`", self.source)));
+            }
+            value = if op == '*' { value * rhs } else { value / rhs };
+        }
+        Ok(value)
+    }
+
+    fn factor(&mut self) -> Result<f64> {
+        match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                Ok(-self.factor()?)
+            }
+            Some('+') => {
+                self.pos += 1;
+                self.factor()
+            }
+            Some('(') => {
+                self.pos += 1;
+                let value = self.expr()?;
+                if self.peek() != Some(')') {
+                    return Err(self.unexpected());
+                }
+                self.pos += 1;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            let exponent_sign = (c == '+' || c == '-')
+                && matches!(self.chars.get(self.pos.wrapping_sub(1)), Some('e' | 'E'));
+            if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        let literal: String = self.chars[start..self.pos].iter().collect();
+        literal
+            .parse()
+            .map_err(|_| invalid(format!("invalid number `{literal}` in `{}`", self.source)))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn unexpected(&self) -> Error {
+        match self.peek() {
+            Some(c) => invalid(format!("unexpected `{c}` in `{}`", self.source)),
+            None => invalid(format!("unexpected end of `{}`", self.source)),
+        }
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::InvalidParameter(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_absolute_and_relative_points() {
+        assert_eq!(
+            parse_coordinate("1000, 2000, 300").unwrap(),
+            CoordinateInput::Absolute([1000.0, 2000.0, 300.0])
+        );
+        let relative = parse_coordinate("@ 500*2, -(100+50)").unwrap();
+        assert_eq!(relative, CoordinateInput::Relative([1000.0, -150.0, 0.0]));
+        assert_eq!(relative.resolve([10.0, 20.0, 30.0]), [1010.0, -130.0, 30.0]);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(parse_coordinate("1,2,3,4").is_err());
+        assert!(parse_coordinate("1,,3").is_err());
+        assert!(parse_coordinate("1/0,2").is_err());
+        assert!(evaluate_expression("2*(3+4").is_err());
+        assert_eq!(evaluate_expression("1.5e3").unwrap(), 1500.0);
+    }
+}
@@ -1,7 +1,16 @@
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
 use thiserror::Error;
 use uuid::Uuid;
 
+mod coords;
+mod units;
+
+pub use coords::{CoordinateInput, evaluate_expression, parse_coordinate};
+pub use units::{
+    convert_angle, convert_length, format_feet_inches, format_fractional_inches, format_length,
+};
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Guid(Uuid);
 
@@ -31,17 +40,107 @@ impl std::fmt::Display for Guid {
     }
 }
 
+impl std::str::FromStr for Guid {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Uuid::parse_str(text.trim())
+            .map(Self)
+            .map_err(|err| Error::InvalidParameter(format!("invalid guid {text:?}: {err}")))
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum LengthUnit {
     Millimeter,
     Meter,
+    Inch,
+    Foot,
+}
+
+impl LengthUnit {
+    /// Whether the unit is displayed as feet and inches rather than decimals.
+    pub const fn is_imperial(self) -> bool {
+        matches!(self, LengthUnit::Inch | LengthUnit::Foot)
+    }
+
+    /// Size of one unit in metres.
+    pub const fn in_meters(self) -> f64 {
+        match self {
+            LengthUnit::Millimeter => 0.001,
+            LengthUnit::Meter => 1.0,
+            LengthUnit::Inch => 0.0254,
+            LengthUnit::Foot => 0.3048,
+        }
+    }
+
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            LengthUnit::Millimeter => "mm",
+            LengthUnit::Meter => "m",
+            LengthUnit::Inch => "in",
+            LengthUnit::Foot => "ft",
+        }
+    }
+
+    /// Factor taking lengths in this unit to `to`.
+    pub fn factor_to(self, to: LengthUnit) -> f64 {
+        if self == to {
+            1.0
+        } else {
+            self.in_meters() / to.in_meters()
+        }
+    }
+
+    /// `value` in this unit, expressed in `to`.
+    pub fn convert(self, value: f64, to: LengthUnit) -> f64 {
+        value * self.factor_to(to)
+    }
+}
+
+impl std::str::FromStr for LengthUnit {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        match text.trim() {
+            "mm" => Ok(LengthUnit::Millimeter),
+            "m" => Ok(LengthUnit::Meter),
+            "in" | "\"" => Ok(LengthUnit::Inch),
+            "ft" | "'" => Ok(LengthUnit::Foot),
+            other => Err(Error::InvalidParameter(format!(
+                "unknown length unit {other:?} (expected mm, m, in or ft)"
+            ))),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum AngleUnit {
     Radian,
+    Degree,
 }
 
+impl AngleUnit {
+    /// Size of one unit in radians.
+    pub const fn in_radians(self) -> f64 {
+        match self {
+            AngleUnit::Radian => 1.0,
+            AngleUnit::Degree => std::f64::consts::PI / 180.0,
+        }
+    }
+
+    /// `value` in this unit, expressed in `to`.
+    pub fn convert(self, value: f64, to: AngleUnit) -> f64 {
+        if self == to {
+            value
+        } else {
+            value * self.in_radians() / to.in_radians()
+        }
+    }
+}
+
+/// Units a model is measured in. Angle parameters such as `Roll` and `Slope` are stored in
+/// degrees whatever the length unit.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Units {
     pub length: LengthUnit,
@@ -52,7 +151,7 @@ impl Default for Units {
     fn default() -> Self {
         Self {
             length: LengthUnit::Millimeter,
-            angle: AngleUnit::Radian,
+            angle: AngleUnit::Degree,
         }
     }
 }
@@ -61,7 +160,22 @@ impl Units {
     pub const fn metric_mm() -> Self {
         Self {
             length: LengthUnit::Millimeter,
-            angle: AngleUnit::Radian,
+            angle: AngleUnit::Degree,
+        }
+    }
+
+    pub const fn metric_m() -> Self {
+        Self {
+            length: LengthUnit::Meter,
+            angle: AngleUnit::Degree,
+        }
+    }
+
+    /// Inches for lengths and degrees for angles, as US drawings use.
+    pub const fn imperial() -> Self {
+        Self {
+            length: LengthUnit::Inch,
+            angle: AngleUnit::Degree,
         }
     }
 }
@@ -81,12 +195,77 @@ impl Default for Tolerance {
     }
 }
 
+/// Progress and cancellation hooks for long-running geometry operations.
+#[derive(Clone, Copy, Default)]
+pub struct OpContext<'a> {
+    progress: Option<&'a (dyn Fn(f32) + Sync)>,
+    cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> OpContext<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_progress(mut self, progress: &'a (dyn Fn(f32) + Sync)) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn with_cancel(mut self, cancel: &'a AtomicBool) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Reports completion in `[0, 1]`.
+    pub fn report(&self, fraction: f32) {
+        if let Some(progress) = self.progress {
+            progress(fraction.clamp(0.0, 1.0));
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    /// Whether the operation can be cancelled at all.
+    pub fn is_cancellable(&self) -> bool {
+        self.cancel.is_some()
+    }
+
+    /// Runs `op` as the stretch of the operation from `start` to `end`: the fractions it
+    /// reports are mapped into that stretch, and it is cancelled along with the whole.
+    pub fn span<T>(&self, start: f32, end: f32, op: impl FnOnce(&OpContext) -> T) -> T {
+        let report = |fraction: f32| self.report(start + (end - start) * fraction.clamp(0.0, 1.0));
+        op(&self.with_progress(&report))
+    }
+
+    pub fn checkpoint(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for OpContext<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpContext")
+            .field("progress", &self.progress.is_some())
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("not implemented: {0}")]
     NotImplemented(&'static str),
     #[error("invalid parameter: {0}")]
     InvalidParameter(String),
+    #[error("operation cancelled")]
+    Cancelled,
     #[error(transparent)]
     Io(#[from] std::io::Error),
 }
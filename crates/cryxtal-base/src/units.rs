@@ -0,0 +1,115 @@
+use crate::{AngleUnit, LengthUnit};
+
+/// Finest fraction of an inch [`format_length`] rounds imperial lengths to.
+const INCH_DENOMINATOR: u32 = 16;
+
+/// `value` in `from`, expressed in `to`.
+pub fn convert_length(value: f64, from: LengthUnit, to: LengthUnit) -> f64 {
+    from.convert(value, to)
+}
+
+/// `value` in `from`, expressed in `to`.
+pub fn convert_angle(value: f64, from: AngleUnit, to: AngleUnit) -> f64 {
+    from.convert(value, to)
+}
+
+/// `value` in `unit` as drawings label it: `1200 mm`, `1.200 m`, `6 1/2"` or `3'-6 1/2"`,
+/// imperial lengths rounded to the nearest sixteenth of an inch.
+pub fn format_length(value: f64, unit: LengthUnit) -> String {
+    match unit {
+        LengthUnit::Millimeter => format!("{} mm", trim_decimals(value, 1)),
+        LengthUnit::Meter => format!("{value:.3} m"),
+        LengthUnit::Inch => format_fractional_inches(value, INCH_DENOMINATOR),
+        LengthUnit::Foot => format_feet_inches(value, INCH_DENOMINATOR),
+    }
+}
+
+/// `inches` rounded to the nearest `1/denominator` inch, with the fraction reduced:
+/// `6 1/2"`, `3/8"`, `12"`.
+pub fn format_fractional_inches(inches: f64, denominator: u32) -> String {
+    let (negative, whole, fraction) = split_inches(inches, denominator);
+    let sign = if negative { "-" } else { "" };
+    match (whole, fraction) {
+        (_, None) => format!("{sign}{whole}\""),
+        (0, Some((numerator, denominator))) => format!("{sign}{numerator}/{denominator}\""),
+        (_, Some((numerator, denominator))) => {
+            format!("{sign}{whole} {numerator}/{denominator}\"")
+        }
+    }
+}
+
+/// `feet` as feet and inches, the inches rounded to the nearest `1/denominator`:
+/// `3'-6 1/2"`, `0'-0 3/4"`, `10'-0"`. The whole inches are always written.
+pub fn format_feet_inches(feet: f64, denominator: u32) -> String {
+    let (negative, whole_inches, fraction) = split_inches(feet * 12.0, denominator);
+    let sign = if negative { "-" } else { "" };
+    let (feet, inches) = (whole_inches / 12, whole_inches % 12);
+    match fraction {
+        None => format!("{sign}{feet}'-{inches}\""),
+        Some((numerator, denominator)) => {
+            format!("{sign}{feet}'-{inches} {numerator}/{denominator}\"")
+        }
+    }
+}
+
+/// Sign, whole inches and reduced remaining fraction of `inches` rounded to `1/denominator`.
+fn split_inches(inches: f64, denominator: u32) -> (bool, u64, Option<(u64, u64)>) {
+    let denominator = u64::from(denominator.max(1));
+    let steps = (inches.abs() * denominator as f64).round() as u64;
+    let (whole, numerator) = (steps / denominator, steps % denominator);
+    let fraction = (numerator != 0).then(|| {
+        let divisor = gcd(numerator, denominator);
+        (numerator / divisor, denominator / divisor)
+    });
+    (inches < 0.0 && steps != 0, whole, fraction)
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn trim_decimals(value: f64, decimals: usize) -> String {
+    let text = format!("{value:.decimals$}");
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_metric_and_imperial() {
+        assert!((convert_length(1.0, LengthUnit::Foot, LengthUnit::Inch) - 12.0).abs() < 1.0e-12);
+        assert!(
+            (convert_length(25.4, LengthUnit::Millimeter, LengthUnit::Inch) - 1.0).abs() < 1.0e-12
+        );
+        assert!(
+            (convert_angle(180.0, AngleUnit::Degree, AngleUnit::Radian) - std::f64::consts::PI)
+                .abs()
+                < 1.0e-12
+        );
+    }
+
+    #[test]
+    fn formats_feet_and_fractional_inches() {
+        assert_eq!(format_feet_inches(3.0 + 6.5 / 12.0, 16), "3'-6 1/2\"");
+        assert_eq!(format_feet_inches(11.999 / 12.0, 8), "1'-0\"");
+        assert_eq!(format_feet_inches(-2.0, 16), "-2'-0\"");
+        assert_eq!(format_fractional_inches(6.0 + 3.0 / 8.0, 16), "6 3/8\"");
+        assert_eq!(format_length(1200.0, LengthUnit::Millimeter), "1200 mm");
+    }
+
+    #[test]
+    fn inches_under_one_keep_their_zero() {
+        assert_eq!(format_feet_inches(0.75 / 12.0, 16), "0'-0 3/4\"");
+        assert_eq!(format_feet_inches(5.0 + 0.5 / 12.0, 16), "5'-0 1/2\"");
+        assert_eq!(format_feet_inches(-1.0 / 192.0, 16), "-0'-0 1/16\"");
+    }
+}
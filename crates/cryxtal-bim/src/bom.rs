@@ -0,0 +1,197 @@
+use cryxtal_base::Guid;
+use cryxtal_topology::mass_properties;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{BimCategory, BimElement, BimModel, GroupKind, MaterialClass, ParameterValue};
+
+/// Parameter holding an element's density in kg/m³, which takes precedence over the typical
+/// density of its [`MaterialClass`].
+pub const DENSITY_KEY: &str = "Density";
+
+/// Line of a bill of materials; see [`bill_of_materials`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BomRow {
+    /// Position in the tree: `1`, `2`, … at the top, `1.1`, `1.2`, … for the parts of item `1`.
+    pub item: String,
+    /// 0 for assemblies, groups and loose parts; 1 for the parts of an assembly or group.
+    pub level: usize,
+    /// Type name of the parts, or the element name for parts without a type.
+    pub name: String,
+    /// `Assembly` or `Group` for parents, otherwise the category of the parts.
+    pub kind: String,
+    /// Empty for parents and parts without a material.
+    pub material: String,
+    pub quantity: usize,
+    /// Total volume of the row, in m³.
+    pub volume: f64,
+    /// Total weight of the row, in kg; `None` when the density of a part is unknown.
+    pub weight: Option<f64>,
+}
+
+/// Identical parts, rolled up into one row.
+#[derive(Default)]
+struct Parts {
+    quantity: usize,
+    volume: f64,
+    weight: Option<f64>,
+}
+
+impl Parts {
+    fn add(&mut self, volume: f64, weight: Option<f64>) {
+        self.weight = match (self.quantity, self.weight, weight) {
+            (0, _, weight) => weight,
+            (_, Some(total), Some(weight)) => Some(total + weight),
+            _ => None,
+        };
+        self.quantity += 1;
+        self.volume += volume;
+    }
+
+    /// Adds the parts of `other` to these.
+    fn merge(&mut self, other: &Parts) {
+        self.weight = match (self.weight, other.weight) {
+            (Some(total), Some(weight)) => Some(total + weight),
+            _ => None,
+        };
+        self.quantity += other.quantity;
+        self.volume += other.volume;
+    }
+}
+
+type Key = (String, String, String);
+
+/// Whether `parts` are those of one more copy of the `count` assemblies rolled up in `rolled`.
+fn is_copy(rolled: &BTreeMap<Key, Parts>, count: usize, parts: &BTreeMap<Key, Parts>) -> bool {
+    rolled.len() == parts.len()
+        && rolled
+            .iter()
+            .zip(parts)
+            .all(|((key, rolled), (other, part))| {
+                key == other && rolled.quantity == part.quantity * count
+            })
+}
+
+/// Bill of materials of the model: each assembly, then each group, as a row followed by its
+/// parts, then the parts in neither. Parts of the same name, category and material are rolled
+/// up into one row with their quantity, sorted by name, and so are assemblies of the same name
+/// and parts, their part rows then counting the parts of all of them. Unlike [`takeoff`](crate::takeoff),
+/// every element is counted once: under the first assembly holding it, else the first group,
+/// with the openings and elements it hosts. Openings are voids and reference meshes context,
+/// and both are left out.
+pub fn bill_of_materials(model: &BimModel) -> Vec<BomRow> {
+    let parents: Vec<_> = model
+        .groups
+        .iter()
+        .filter(|group| group.kind == GroupKind::Assembly)
+        .chain(
+            model
+                .groups
+                .iter()
+                .filter(|group| group.kind != GroupKind::Assembly),
+        )
+        .collect();
+    let mut owners: HashMap<Guid, usize> = HashMap::new();
+    for (idx, group) in parents.iter().enumerate() {
+        for guid in model.group_elements(group.guid).unwrap_or_default() {
+            owners.entry(guid).or_insert(idx);
+        }
+    }
+
+    let mut children: Vec<BTreeMap<Key, Parts>> = parents.iter().map(|_| BTreeMap::new()).collect();
+    let mut loose: BTreeMap<Key, Parts> = BTreeMap::new();
+    let cubic = model.units.length.in_meters().powi(3);
+    for element in model.elements() {
+        if element.category == BimCategory::Opening || element.is_reference_mesh() {
+            continue;
+        }
+        let name = element
+            .type_id
+            .and_then(|guid| model.element_type(guid))
+            .map_or_else(
+                || element.name.clone(),
+                |element_type| element_type.name.clone(),
+            );
+        let material = model.element_material(element);
+        let key = (
+            name,
+            format!("{:?}", element.category),
+            material.unwrap_or_default().to_string(),
+        );
+        let volume = mass_properties(&element.geometry).volume.abs() * cubic;
+        let weight = density(element, material).map(|density| density * volume);
+        let rows = match owners.get(&element.guid) {
+            Some(&idx) => &mut children[idx],
+            None => &mut loose,
+        };
+        rows.entry(key).or_default().add(volume, weight);
+    }
+
+    let row = |item: String, level, (name, kind, material): Key, parts: &Parts| BomRow {
+        item,
+        level,
+        name,
+        kind,
+        material,
+        quantity: parts.quantity,
+        volume: parts.volume,
+        weight: parts.weight,
+    };
+    let mut rolled: Vec<(_, usize, BTreeMap<Key, Parts>)> = Vec::new();
+    for (group, parts) in parents.iter().zip(children) {
+        if parts.is_empty() {
+            continue;
+        }
+        let copy_of = rolled.iter_mut().find(|(other, count, other_parts)| {
+            group.kind == GroupKind::Assembly
+                && other.kind == GroupKind::Assembly
+                && other.name == group.name
+                && is_copy(other_parts, *count, &parts)
+        });
+        match copy_of {
+            Some((_, count, other_parts)) => {
+                *count += 1;
+                for (other, part) in other_parts.values_mut().zip(parts.values()) {
+                    other.merge(part);
+                }
+            }
+            None => rolled.push((group, 1, parts)),
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut item = 0;
+    for (group, count, parts) in rolled {
+        item += 1;
+        let total = Parts {
+            quantity: count,
+            volume: parts.values().map(|part| part.volume).sum(),
+            weight: parts.values().map(|part| part.weight).sum(),
+        };
+        let key = (
+            group.name.clone(),
+            group.kind.label().to_string(),
+            String::new(),
+        );
+        rows.push(row(item.to_string(), 0, key, &total));
+        for (child, (key, part)) in parts.into_iter().enumerate() {
+            rows.push(row(format!("{item}.{}", child + 1), 1, key, &part));
+        }
+    }
+    for (key, parts) in loose {
+        item += 1;
+        rows.push(row(item.to_string(), 0, key, &parts));
+    }
+    rows
+}
+
+/// Density of `element` in kg/m³: its [`DENSITY_KEY`] parameter, else that of its material.
+fn density(element: &BimElement, material: Option<&str>) -> Option<f64> {
+    match element.parameters.get(DENSITY_KEY) {
+        Some(ParameterValue::Number(density)) if *density > 0.0 => Some(*density),
+        Some(ParameterValue::Integer(density)) if *density > 0 => Some(*density as f64),
+        _ => material
+            .and_then(MaterialClass::of)
+            .map(MaterialClass::density),
+    }
+}
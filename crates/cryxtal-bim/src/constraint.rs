@@ -0,0 +1,184 @@
+use cryxtal_base::{Error, Guid, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::{BimCategory, BimElement, BimModel, ParameterValue};
+
+/// How far a solved value may drift before the element counts as changed.
+const SOLVE_TOLERANCE: f64 = 1.0e-9;
+
+/// A relation an element keeps to a storey or its host, re-solved by
+/// [`BimModel::solve_constraints`] whenever what it refers to moves or changes size.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Constraint {
+    /// The wall's top stays `offset` above `storey`: its `Height` is the storey elevation plus
+    /// `offset`, less the wall's base (`StartZ`).
+    TopAttached { storey: Guid, offset: f64 },
+    /// The opening stays centered along its host wall, at half the host's `Length`.
+    CenteredInHost,
+    /// The hosted element runs along the host face labeled `face`, `offset` inside it, such as
+    /// a rebar set's `Face` and `Cover`.
+    FaceOffset { face: String, offset: f64 },
+}
+
+impl Constraint {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Constraint::TopAttached { .. } => "Top attached",
+            Constraint::CenteredInHost => "Centered in host",
+            Constraint::FaceOffset { .. } => "Face offset",
+        }
+    }
+
+    /// Whether the constraint on `element` reads any of `changed`: the element itself, or the
+    /// storey or host it refers to.
+    pub fn depends_on(&self, element: &BimElement, changed: &BTreeSet<Guid>) -> bool {
+        if changed.contains(&element.guid) {
+            return true;
+        }
+        let referred = match self {
+            Constraint::TopAttached { storey, .. } => Some(*storey),
+            Constraint::CenteredInHost => element.opening.map(|opening| opening.host),
+            Constraint::FaceOffset { .. } => element.host,
+        };
+        referred.is_some_and(|guid| changed.contains(&guid))
+    }
+
+    /// Updates `element` so the constraint holds in `model`; returns whether anything changed.
+    /// Constraints whose storey or host is gone are left alone, like orphaned elements.
+    fn solve(&self, model: &BimModel, element: &mut BimElement) -> Result<bool> {
+        match self {
+            Constraint::TopAttached { storey, offset } => {
+                if element.category != BimCategory::Wall {
+                    return Err(invalid(element, "only walls have their top attached"));
+                }
+                let Some(storey) = model.storey(*storey) else {
+                    return Ok(false);
+                };
+                let base = number(element, "StartZ")?;
+                let height = storey.elevation + offset - base;
+                if height <= 0.0 {
+                    return Err(invalid(
+                        element,
+                        &format!("top at {} is below its base", storey.name),
+                    ));
+                }
+                set_number(element, "Height", height)
+            }
+            Constraint::CenteredInHost => {
+                let Some(mut opening) = element.opening else {
+                    return Err(invalid(element, "only openings are centered in a host"));
+                };
+                let Some(host) = model.opening_host(&opening) else {
+                    return Ok(false);
+                };
+                if host.category != BimCategory::Wall {
+                    return Err(invalid(element, "openings are centered in walls only"));
+                }
+                let center_x = number(host, "Length")? * 0.5;
+                if (opening.placement.center_x - center_x).abs() <= SOLVE_TOLERANCE {
+                    return Ok(false);
+                }
+                opening.placement.center_x = center_x;
+                element.opening = Some(opening);
+                Ok(true)
+            }
+            Constraint::FaceOffset { face, offset } => {
+                if element
+                    .host
+                    .is_none_or(|host| model.element(host).is_none())
+                {
+                    return Ok(false);
+                }
+                let face_changed = !matches!(
+                    element.parameters.get("Face"),
+                    Some(ParameterValue::Text(current)) if current == face
+                );
+                if face_changed {
+                    element.insert_parameter("Face", ParameterValue::Text(face.clone()));
+                }
+                Ok(set_number(element, "Cover", *offset)? || face_changed)
+            }
+        }
+    }
+}
+
+impl BimModel {
+    /// Constrained elements that no longer satisfy their [`Constraint`]s, updated so they do,
+    /// for a [`ModelCommand::ReplaceElements`](crate::ModelCommand::ReplaceElements). Only
+    /// parameters and opening placements are solved; the caller regenerates geometry, as after
+    /// storey elevation changes. Constraints read storeys and hosts as they are in the model.
+    pub fn solve_constraints(&self) -> Result<Vec<BimElement>> {
+        let mut solved = Vec::new();
+        for element in self.elements() {
+            solved.extend(self.solve_element(element)?);
+        }
+        Ok(solved)
+    }
+
+    /// Like [`BimModel::solve_constraints`] after an edit of `changed`, solving only the
+    /// constraints that read one of them (see [`Constraint::depends_on`]). An element whose
+    /// constraints cannot hold is left as it is and its error returned alongside the others.
+    pub fn solve_constraints_touching(
+        &self,
+        changed: &BTreeSet<Guid>,
+    ) -> (Vec<BimElement>, Vec<Error>) {
+        let mut solved = Vec::new();
+        let mut failures = Vec::new();
+        for element in self.elements() {
+            let touched = element
+                .constraints
+                .iter()
+                .any(|constraint| constraint.depends_on(element, changed));
+            if !touched {
+                continue;
+            }
+            match self.solve_element(element) {
+                Ok(updated) => solved.extend(updated),
+                Err(err) => failures.push(err),
+            }
+        }
+        (solved, failures)
+    }
+
+    /// `element` updated so its constraints hold, or `None` when they already do.
+    fn solve_element(&self, element: &BimElement) -> Result<Option<BimElement>> {
+        if element.constraints.is_empty() {
+            return Ok(None);
+        }
+        let mut updated = element.clone();
+        let mut changed = false;
+        for constraint in &element.constraints {
+            changed |= constraint.solve(self, &mut updated)?;
+        }
+        Ok(changed.then_some(updated))
+    }
+}
+
+fn number(element: &BimElement, key: &str) -> Result<f64> {
+    match element.parameters.get(key) {
+        Some(ParameterValue::Number(value)) => Ok(*value),
+        _ => Err(invalid(
+            element,
+            &format!("missing or invalid parameter {key}"),
+        )),
+    }
+}
+
+/// Sets a solved number unless it already holds; a formula-driven parameter cannot be solved.
+fn set_number(element: &mut BimElement, key: &str, value: f64) -> Result<bool> {
+    if let Some(ParameterValue::Number(current)) = element.parameters.get(key)
+        && (current - value).abs() <= SOLVE_TOLERANCE
+    {
+        return Ok(false);
+    }
+    if element.formula(key).is_some() {
+        return Err(invalid(element, &format!("{key} is driven by a formula")));
+    }
+    element.insert_parameter(key, ParameterValue::Number(value));
+    Ok(true)
+}
+
+fn invalid(element: &BimElement, message: &str) -> Error {
+    Error::InvalidParameter(format!("constraint on {}: {message}", element.name))
+}
@@ -0,0 +1,158 @@
+use cryxtal_base::{Error, Result};
+use cryxtal_topology::{Matrix4, Vector3, transform_solid};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{transform_points, transform_vector};
+use crate::{BimCategory, BimModel, DirtyFlags, ReferenceKind};
+
+/// Rigid placement of a model in another coordinate system, such as the shared site
+/// coordinates of a federated project: a rotation about the Z axis through the origin,
+/// followed by a translation. Lengths are in the model's units.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CoordinateTransform {
+    #[serde(default)]
+    pub offset: [f64; 3],
+    /// Degrees, counter-clockwise seen from above.
+    #[serde(default)]
+    pub rotation: f64,
+}
+
+impl CoordinateTransform {
+    pub fn new(offset: [f64; 3], rotation: f64) -> Self {
+        Self { offset, rotation }
+    }
+
+    /// Parses an `x,y,z` offset (missing components are 0) and a rotation in degrees.
+    pub fn parse(offset: &str, rotation: f64) -> Result<Self> {
+        let mut components = [0.0; 3];
+        let parts: Vec<&str> = offset.split(',').map(str::trim).collect();
+        if parts.len() > 3 || !rotation.is_finite() {
+            return Err(Error::InvalidParameter(format!(
+                "invalid offset `{offset}` (expected x,y,z)"
+            )));
+        }
+        for (component, part) in components.iter_mut().zip(parts) {
+            if part.is_empty() {
+                continue;
+            }
+            *component = part.parse().map_err(|_| {
+                Error::InvalidParameter(format!("invalid offset `{offset}` (expected x,y,z)"))
+            })?;
+        }
+        Ok(Self::new(components, rotation))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.offset == [0.0; 3] && self.rotation.rem_euclid(360.0) == 0.0
+    }
+
+    pub fn matrix(&self) -> Matrix4 {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let [x, y, z] = self.offset;
+        Matrix4::new(
+            cos, sin, 0.0, 0.0, //
+            -sin, cos, 0.0, 0.0, //
+            0.0, 0.0, 1.0, 0.0, //
+            x, y, z, 1.0,
+        )
+    }
+
+    /// The transform taking placed coordinates back to the model's own.
+    pub fn inverse(&self) -> Self {
+        let (sin, cos) = (-self.rotation).to_radians().sin_cos();
+        let [x, y, z] = self.offset;
+        Self::new(
+            [-(cos * x - sin * y), -(sin * x + cos * y), -z],
+            -self.rotation,
+        )
+    }
+
+    pub fn apply(&self, point: [f64; 3]) -> [f64; 3] {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let [x, y, z] = point;
+        [
+            cos * x - sin * y + self.offset[0],
+            sin * x + cos * y + self.offset[1],
+            z + self.offset[2],
+        ]
+    }
+}
+
+impl BimModel {
+    /// Moves the whole model by `transform`: element geometry and point parameters, slab
+    /// opening placements, storey elevations, reference geometry and grids. The saved
+    /// [`Site::shared_coordinates`](crate::Site) are left alone.
+    pub fn transform(&mut self, transform: &CoordinateTransform) {
+        if transform.is_identity() {
+            return;
+        }
+        let matrix = transform.matrix();
+        let slabs: Vec<_> = self
+            .elements()
+            .iter()
+            .filter(|element| element.category == BimCategory::Slab)
+            .map(|element| element.guid)
+            .collect();
+        let mut moved = Vec::with_capacity(self.elements().len());
+        for element in self.elements_mut() {
+            element.geometry = transform_solid(&element.geometry, matrix);
+            transform_points(&mut element.parameters, &matrix);
+            // Wall openings are placed along their wall and move with it; slab openings are
+            // placed in plan, as in `BimModel::map_elements`.
+            if let Some(opening) = element
+                .opening
+                .as_mut()
+                .filter(|opening| slabs.contains(&opening.host))
+            {
+                let offset = transform_vector(
+                    &matrix,
+                    Vector3::new(opening.placement.center_x, opening.placement.center_y, 0.0),
+                );
+                opening.placement.center_x = offset.x;
+                opening.placement.center_y = offset.y;
+            }
+            moved.push(element.guid);
+        }
+        for guid in moved {
+            self.mark_dirty(guid, DirtyFlags::ALL);
+        }
+        for storey in self
+            .site
+            .buildings
+            .iter_mut()
+            .flat_map(|building| building.storeys.iter_mut())
+        {
+            storey.elevation += transform.offset[2];
+        }
+        for reference in &mut self.references {
+            match &mut reference.kind {
+                ReferenceKind::Point { position } => *position = transform.apply(*position),
+                ReferenceKind::Line { origin, direction }
+                | ReferenceKind::Plane {
+                    origin,
+                    normal: direction,
+                } => {
+                    *origin = transform.apply(*origin);
+                    let [x, y, z] = *direction;
+                    let rotated = transform_vector(&matrix, Vector3::new(x, y, z));
+                    *direction = [rotated.x, rotated.y, rotated.z];
+                }
+            }
+        }
+        for grid in &mut self.grids {
+            grid.curve.place(transform);
+        }
+    }
+
+    /// Copy of the model moved by `transform`; see [`BimModel::transform`].
+    pub fn transformed(&self, transform: &CoordinateTransform) -> BimModel {
+        let mut model = self.clone();
+        model.transform(transform);
+        model
+    }
+
+    /// Copy of the model in its shared coordinates, for exports to federated models.
+    pub fn in_shared_coordinates(&self) -> BimModel {
+        self.transformed(&self.site.shared_coordinates)
+    }
+}
@@ -0,0 +1,150 @@
+use cryxtal_base::Guid;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{BimElement, BimModel, ParameterValue};
+
+/// A parameter that was added (`old` is `None`), removed (`new` is `None`) or changed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterChange {
+    pub name: String,
+    pub old: Option<ParameterValue>,
+    pub new: Option<ParameterValue>,
+}
+
+/// How an element present in both models differs between them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElementChange {
+    pub guid: Guid,
+    /// Name in the newer model.
+    pub name: String,
+    pub parameters: Vec<ParameterChange>,
+    /// The solids differ by [`BimElement::geometry_hash`], which samples curved edges too.
+    pub geometry: bool,
+    /// Other fields that changed, by name: `Name`, `Category`, `Opening`, `Cuts`, `Host`,
+    /// `Type`, `Formulas`, `Constraints`, `Welds`, `Phase` and `Storey`.
+    pub fields: Vec<&'static str>,
+}
+
+/// Elements added, removed and modified going from one model to another; see [`diff`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelDiff {
+    pub added: Vec<Guid>,
+    pub removed: Vec<Guid>,
+    pub modified: Vec<ElementChange>,
+}
+
+impl ModelDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Changes from `old` to `new`, matching elements by guid. Added elements are listed in `new`'s
+/// order, removed and modified ones in `old`'s. Geometry is compared by content, so the same
+/// project loaded twice has no differences.
+pub fn diff(old: &BimModel, new: &BimModel) -> ModelDiff {
+    let old_elements = elements_by_guid(old);
+    let new_elements = elements_by_guid(new);
+    let (old_storeys, new_storeys) = (storeys_by_element(old), storeys_by_element(new));
+    let mut result = ModelDiff {
+        added: new
+            .elements()
+            .iter()
+            .filter(|element| !old_elements.contains_key(&element.guid))
+            .map(|element| element.guid)
+            .collect(),
+        ..ModelDiff::default()
+    };
+    for before in old.elements() {
+        let Some(after) = new_elements.get(&before.guid) else {
+            result.removed.push(before.guid);
+            continue;
+        };
+        let mut fields = element_fields(before, after);
+        if old_storeys.get(&before.guid) != new_storeys.get(&before.guid) {
+            fields.push("Storey");
+        }
+        let change = ElementChange {
+            guid: before.guid,
+            name: after.name.clone(),
+            parameters: parameter_changes(before, after),
+            geometry: before.geometry_hash() != after.geometry_hash(),
+            fields,
+        };
+        if !change.parameters.is_empty() || change.geometry || !change.fields.is_empty() {
+            result.modified.push(change);
+        }
+    }
+    result
+}
+
+fn elements_by_guid(model: &BimModel) -> HashMap<Guid, &BimElement> {
+    model
+        .elements()
+        .iter()
+        .map(|element| (element.guid, element))
+        .collect()
+}
+
+/// Storey guid of each element placed on a storey.
+fn storeys_by_element(model: &BimModel) -> HashMap<Guid, Guid> {
+    model
+        .storeys()
+        .flat_map(|storey| {
+            storey
+                .elements
+                .iter()
+                .map(|&element| (element, storey.guid))
+        })
+        .collect()
+}
+
+/// Whether `after` differs from `before` in its parameters or a field [`diff`] reports; the
+/// geometry is left to the caller.
+pub(crate) fn element_changed(before: &BimElement, after: &BimElement) -> bool {
+    before.parameters != after.parameters || !element_fields(before, after).is_empty()
+}
+
+fn element_fields(before: &BimElement, after: &BimElement) -> Vec<&'static str> {
+    let checks = [
+        ("Name", before.name != after.name),
+        ("Category", before.category != after.category),
+        ("Opening", before.opening != after.opening),
+        ("Cuts", before.cuts != after.cuts),
+        ("Host", before.host != after.host),
+        ("Type", before.type_id != after.type_id),
+        ("Formulas", before.formulas != after.formulas),
+        ("Constraints", before.constraints != after.constraints),
+        ("Welds", before.welds != after.welds),
+        (
+            "Phase",
+            before.phase_created != after.phase_created
+                || before.phase_demolished != after.phase_demolished,
+        ),
+    ];
+    checks
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(field, _)| field)
+        .collect()
+}
+
+fn parameter_changes(before: &BimElement, after: &BimElement) -> Vec<ParameterChange> {
+    let names: BTreeSet<&String> = before
+        .parameters
+        .keys()
+        .chain(after.parameters.keys())
+        .collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let old = before.parameters.get(name);
+            let new = after.parameters.get(name);
+            (old != new).then(|| ParameterChange {
+                name: name.clone(),
+                old: old.cloned(),
+                new: new.cloned(),
+            })
+        })
+        .collect()
+}
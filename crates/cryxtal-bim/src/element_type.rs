@@ -0,0 +1,61 @@
+use cryxtal_base::Guid;
+use serde::{Deserialize, Serialize};
+
+use crate::{BimCategory, BimElement, ParameterSet, ParameterValue};
+
+/// Definition shared by every instance that refers to it through [`BimElement::type_id`], such
+/// as a wall type ("200mm CIP concrete") or a rebar bar size. Type parameters are copied onto
+/// instances by [`BimElementType::apply_to`], so geometry builders only read instance values.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BimElementType {
+    pub guid: Guid,
+    pub name: String,
+    pub category: BimCategory,
+    pub family: String,
+    #[serde(default)]
+    pub material: Option<String>,
+    #[serde(default)]
+    pub parameters: ParameterSet,
+}
+
+impl BimElementType {
+    pub fn new(name: impl Into<String>, category: BimCategory, family: impl Into<String>) -> Self {
+        Self {
+            guid: Guid::new(),
+            name: name.into(),
+            category,
+            family: family.into(),
+            material: None,
+            parameters: ParameterSet::new(),
+        }
+    }
+
+    pub fn with_material(mut self, material: impl Into<String>) -> Self {
+        self.material = Some(material.into());
+        self
+    }
+
+    pub fn with_parameter(mut self, key: impl Into<String>, value: ParameterValue) -> Self {
+        self.parameters.insert(key.into(), value);
+        self
+    }
+
+    pub fn number(&self, key: &str) -> Option<f64> {
+        match self.parameters.get(key) {
+            Some(ParameterValue::Number(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Links `element` to this type and overwrites its type-driven parameters. Geometry is left
+    /// for the caller to regenerate.
+    pub fn apply_to(&self, element: &mut BimElement) {
+        element.type_id = Some(self.guid);
+        for (key, value) in &self.parameters {
+            element.insert_parameter(key.clone(), value.clone());
+        }
+        if let Some(material) = &self.material {
+            element.insert_parameter("Material", ParameterValue::Text(material.clone()));
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use cryxtal_base::{Error, Result};
+
+use crate::{BimElement, ParameterValue};
+
+/// Parsed parameter formula, such as `Length * Height / 1000000`.
+#[derive(Clone, Debug)]
+enum Expr {
+    Number(f64),
+    Parameter(String),
+    Negate(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+impl Expr {
+    fn parameters(&self, names: &mut BTreeSet<String>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Parameter(name) => {
+                names.insert(name.clone());
+            }
+            Expr::Negate(operand) => operand.parameters(names),
+            Expr::Binary(_, left, right) => {
+                left.parameters(names);
+                right.parameters(names);
+            }
+            Expr::Call(_, arguments) => {
+                for argument in arguments {
+                    argument.parameters(names);
+                }
+            }
+        }
+    }
+
+    fn evaluate(&self, value: &mut impl FnMut(&str) -> Result<f64>) -> Result<f64> {
+        Ok(match self {
+            Expr::Number(number) => *number,
+            Expr::Parameter(name) => value(name)?,
+            Expr::Negate(operand) => -operand.evaluate(value)?,
+            Expr::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(value)?, right.evaluate(value)?);
+                match operator {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    _ if right == 0.0 => {
+                        return Err(Error::InvalidParameter("division by zero".to_string()));
+                    }
+                    _ => left / right,
+                }
+            }
+            Expr::Call(function, arguments) => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate(value))
+                    .collect::<Result<Vec<_>>>()?;
+                match (function.as_str(), arguments.as_slice()) {
+                    ("abs", [x]) => x.abs(),
+                    ("sqrt", [x]) if *x >= 0.0 => x.sqrt(),
+                    ("round", [x]) => x.round(),
+                    ("min", [first, rest @ ..]) => rest.iter().fold(*first, |a, b| a.min(*b)),
+                    ("max", [first, rest @ ..]) => rest.iter().fold(*first, |a, b| a.max(*b)),
+                    _ => {
+                        return Err(Error::InvalidParameter(format!(
+                            "invalid call {function}({})",
+                            arguments.len()
+                        )));
+                    }
+                }
+            }
+        })
+    }
+}
+
+const FUNCTIONS: [&str; 5] = ["abs", "sqrt", "round", "min", "max"];
+
+/// Recursive-descent parser over `+ - * /`, parentheses, numbers, parameter names and
+/// [`FUNCTIONS`].
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn parse(text: &str) -> Result<Expr> {
+        let mut parser = Parser { text, position: 0 };
+        let expr = parser.sum()?;
+        parser.skip_space();
+        match parser.peek() {
+            None => Ok(expr),
+            Some(c) => Err(parser.error(&format!("unexpected `{c}`"))),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr> {
+        let mut expr = self.product()?;
+        while let Some(operator @ ('+' | '-')) = self.next_operator() {
+            expr = Expr::Binary(operator, Box::new(expr), Box::new(self.product()?));
+        }
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
+        while let Some(operator @ ('*' | '/')) = self.next_operator() {
+            expr = Expr::Binary(operator, Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        self.skip_space();
+        match self.peek() {
+            Some('-') => {
+                self.position += 1;
+                Ok(Expr::Negate(Box::new(self.unary()?)))
+            }
+            Some('(') => {
+                self.position += 1;
+                let expr = self.sum()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let number = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                number
+                    .parse()
+                    .map(Expr::Number)
+                    .map_err(|_| self.error(&format!("invalid number `{number}`")))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self.take_while(|c| c.is_alphanumeric() || c == '_');
+                self.skip_space();
+                if self.peek() != Some('(') {
+                    return Ok(Expr::Parameter(name));
+                }
+                if !FUNCTIONS.contains(&name.as_str()) {
+                    return Err(self.error(&format!("unknown function `{name}`")));
+                }
+                self.position += 1;
+                let mut arguments = vec![self.sum()?];
+                while self.next_operator() == Some(',') {
+                    arguments.push(self.sum()?);
+                }
+                self.expect(')')?;
+                Ok(Expr::Call(name, arguments))
+            }
+            Some(c) => Err(self.error(&format!("unexpected `{c}`"))),
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    /// Consumes the next character if it is an operator or `,`.
+    fn next_operator(&mut self) -> Option<char> {
+        self.skip_space();
+        let c = self.peek().filter(|c| "+-*/,".contains(*c))?;
+        self.position += 1;
+        Some(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_space();
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected `{expected}`")));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn take_while(&mut self, accept: impl Fn(char) -> bool) -> String {
+        let rest = &self.text[self.position..];
+        let end = rest.find(|c| !accept(c)).unwrap_or(rest.len());
+        self.position += end;
+        rest[..end].to_string()
+    }
+
+    fn skip_space(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::InvalidParameter(format!(
+            "formula `{}`: {message} at {}",
+            self.text, self.position
+        ))
+    }
+}
+
+/// Parameters `expression` reads, or why it does not parse.
+pub fn formula_parameters(expression: &str) -> Result<BTreeSet<String>> {
+    let mut names = BTreeSet::new();
+    Parser::parse(expression)?.parameters(&mut names);
+    Ok(names)
+}
+
+/// `expression` with the parameters it reads renamed by `mapping`; function names and the
+/// rest of the text are kept as written.
+pub(crate) fn rename_formula_parameters(
+    expression: &str,
+    mapping: &BTreeMap<&str, &str>,
+) -> Result<String> {
+    Parser::parse(expression)?;
+    let mut renamed = String::with_capacity(expression.len());
+    let mut rest = expression;
+    while let Some(c) = rest.chars().next() {
+        let name = c.is_alphabetic() || c == '_';
+        let token = if name {
+            rest.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        } else if c.is_ascii_digit() || c == '.' {
+            rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        } else {
+            Some(c.len_utf8())
+        }
+        .unwrap_or(rest.len());
+        let (text, after) = rest.split_at(token);
+        let parameter = name && !after.trim_start().starts_with('(');
+        match mapping.get(text) {
+            Some(to) if parameter => renamed.push_str(to),
+            _ => renamed.push_str(text),
+        }
+        rest = after;
+    }
+    // New names that are no identifiers, such as ones with spaces, cannot be read by formulas.
+    Parser::parse(&renamed)?;
+    Ok(renamed)
+}
+
+impl BimElement {
+    /// Formula driving parameter `key`, if any.
+    pub fn formula(&self, key: &str) -> Option<&str> {
+        self.formulas.get(key).map(String::as_str)
+    }
+
+    /// Drives parameter `key` by `expression`, a formula over other numeric parameters such as
+    /// `Length * Height`, and stores its value. Fails, leaving the element as it was, when the
+    /// formula does not parse, depends on `key` itself or cannot be evaluated yet, or when a
+    /// formula reading `key` can no longer be evaluated with its new value.
+    pub fn set_formula(
+        &mut self,
+        key: impl Into<String>,
+        expression: impl Into<String>,
+    ) -> Result<()> {
+        let (key, expression) = (key.into(), expression.into());
+        let inputs = formula_parameters(&expression)?;
+        if inputs.contains(&key) || inputs.iter().any(|input| self.depends_on(input, &key)) {
+            return Err(Error::InvalidParameter(format!(
+                "formula for {key} depends on {key} itself"
+            )));
+        }
+        let (formulas, parameters) = (self.formulas.clone(), self.parameters.clone());
+        self.formulas.insert(key.clone(), expression);
+        let result = self
+            .evaluate(&key)
+            .and_then(|_| self.refresh_formulas(Some(&key)));
+        if result.is_err() {
+            self.formulas = formulas;
+            self.parameters = parameters;
+        }
+        result
+    }
+
+    /// Stops driving `key` by a formula; its last value stays as a plain parameter.
+    pub fn remove_formula(&mut self, key: &str) -> Option<String> {
+        self.formulas.remove(key)
+    }
+
+    /// Current value of parameter `key`: formulas are evaluated on demand from the parameters
+    /// they read, themselves evaluated when formula-driven, so the result is never stale.
+    pub fn evaluate(&self, key: &str) -> Result<ParameterValue> {
+        self.evaluate_in(key, &mut Vec::new())
+    }
+
+    /// Formula-driven parameters that change with `key`, directly or through other formulas,
+    /// sorted by name.
+    pub fn driven_parameters(&self, key: &str) -> Vec<String> {
+        self.formulas
+            .keys()
+            .filter(|driven| self.depends_on(driven, key))
+            .cloned()
+            .collect()
+    }
+
+    /// Writes the current value of every formula-driven parameter, or of those driven by
+    /// `changed` only, into `parameters`. A formula that cannot be evaluated, such as one
+    /// reading a removed parameter, leaves its parameter unset rather than stale; the first
+    /// such failure is returned.
+    pub fn refresh_formulas(&mut self, changed: Option<&str>) -> Result<()> {
+        let keys: Vec<String> = match changed {
+            Some(changed) => {
+                let mut keys = self.driven_parameters(changed);
+                if self.formulas.contains_key(changed) {
+                    keys.push(changed.to_string());
+                }
+                keys
+            }
+            None => self.formulas.keys().cloned().collect(),
+        };
+        let mut result = Ok(());
+        for key in keys {
+            match self.evaluate(&key) {
+                Ok(value) => {
+                    self.parameters.insert(key, value);
+                }
+                Err(err) => {
+                    self.parameters.remove(&key);
+                    if result.is_ok() {
+                        result = Err(err);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Whether the formula for `key` reads `input`, directly or through other formulas.
+    fn depends_on(&self, key: &str, input: &str) -> bool {
+        let mut pending = vec![key.to_string()];
+        let mut seen = BTreeSet::new();
+        while let Some(key) = pending.pop() {
+            let Some(inputs) = self
+                .formulas
+                .get(&key)
+                .and_then(|expression| formula_parameters(expression).ok())
+            else {
+                continue;
+            };
+            if inputs.contains(input) {
+                return true;
+            }
+            pending.extend(
+                inputs
+                    .into_iter()
+                    .filter(|input| seen.insert(input.clone())),
+            );
+        }
+        false
+    }
+
+    fn evaluate_in(&self, key: &str, stack: &mut Vec<String>) -> Result<ParameterValue> {
+        let Some(expression) = self.formulas.get(key) else {
+            return self
+                .parameters
+                .get(key)
+                .cloned()
+                .ok_or_else(|| Error::InvalidParameter(format!("unknown parameter {key}")));
+        };
+        if stack.iter().any(|visiting| visiting == key) {
+            return Err(Error::InvalidParameter(format!(
+                "circular formula through {key}"
+            )));
+        }
+        stack.push(key.to_string());
+        let expr = Parser::parse(expression)?;
+        let mut number = |name: &str| match self.evaluate_in(name, stack)? {
+            ParameterValue::Number(value) => Ok(value),
+            ParameterValue::Integer(value) => Ok(value as f64),
+            _ => Err(Error::InvalidParameter(format!("{name} is not a number"))),
+        };
+        let value = expr.evaluate(&mut number)?;
+        stack.pop();
+        Ok(ParameterValue::Number(value))
+    }
+}
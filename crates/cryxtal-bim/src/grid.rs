@@ -0,0 +1,153 @@
+use cryxtal_base::{Error, Guid, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{BimModel, CoordinateTransform};
+
+/// Plan curve of a grid line. Grids are vertical datums: they run through every level.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GridCurve {
+    Line {
+        start: [f64; 2],
+        end: [f64; 2],
+    },
+    /// Counter-clockwise from `start_angle` to `end_angle`, both in degrees.
+    Arc {
+        center: [f64; 2],
+        radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+    },
+}
+
+impl GridCurve {
+    /// Points along the curve from its start to its end; arcs are split into `segments` spans.
+    pub fn points(&self, segments: usize) -> Vec<[f64; 2]> {
+        match *self {
+            GridCurve::Line { start, end } => vec![start, end],
+            GridCurve::Arc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            } => {
+                let segments = segments.max(1);
+                let sweep = (end_angle - start_angle).rem_euclid(360.0);
+                let sweep = if sweep == 0.0 { 360.0 } else { sweep };
+                (0..=segments)
+                    .map(|step| {
+                        let angle =
+                            (start_angle + sweep * step as f64 / segments as f64).to_radians();
+                        [
+                            center[0] + radius * angle.cos(),
+                            center[1] + radius * angle.sin(),
+                        ]
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// The end the grid bubble is drawn at.
+    pub fn start(&self) -> [f64; 2] {
+        self.points(1)[0]
+    }
+
+    pub(crate) fn scale(&mut self, factor: f64) {
+        match self {
+            GridCurve::Line { start, end } => {
+                for point in [start, end] {
+                    point[0] *= factor;
+                    point[1] *= factor;
+                }
+            }
+            GridCurve::Arc { center, radius, .. } => {
+                center[0] *= factor;
+                center[1] *= factor;
+                *radius *= factor;
+            }
+        }
+    }
+
+    pub(crate) fn place(&mut self, transform: &CoordinateTransform) {
+        let place = |point: &mut [f64; 2]| {
+            let [x, y, _] = transform.apply([point[0], point[1], 0.0]);
+            *point = [x, y];
+        };
+        match self {
+            GridCurve::Line { start, end } => {
+                place(start);
+                place(end);
+            }
+            GridCurve::Arc {
+                center,
+                start_angle,
+                end_angle,
+                ..
+            } => {
+                place(center);
+                *start_angle += transform.rotation;
+                *end_angle += transform.rotation;
+            }
+        }
+    }
+}
+
+/// A labelled setting-out line, such as grid `A` or `3`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Grid {
+    pub guid: Guid,
+    pub label: String,
+    pub curve: GridCurve,
+}
+
+impl Grid {
+    /// Straight grid between two distinct plan points.
+    pub fn line(label: impl Into<String>, start: [f64; 2], end: [f64; 2]) -> Result<Self> {
+        if (end[0] - start[0]).hypot(end[1] - start[1]) <= 1.0e-9 {
+            return Err(Error::InvalidParameter("grid points coincide".to_string()));
+        }
+        Ok(Self::new(label, GridCurve::Line { start, end }))
+    }
+
+    /// Arc grid with a positive radius; angles are in degrees.
+    pub fn arc(
+        label: impl Into<String>,
+        center: [f64; 2],
+        radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+    ) -> Result<Self> {
+        if !radius.is_finite()
+            || radius <= 0.0
+            || !start_angle.is_finite()
+            || !end_angle.is_finite()
+        {
+            return Err(Error::InvalidParameter(format!(
+                "invalid grid arc (radius {radius})"
+            )));
+        }
+        Ok(Self::new(
+            label,
+            GridCurve::Arc {
+                center,
+                radius,
+                start_angle,
+                end_angle,
+            },
+        ))
+    }
+
+    fn new(label: impl Into<String>, curve: GridCurve) -> Self {
+        Self {
+            guid: Guid::new(),
+            label: label.into(),
+            curve,
+        }
+    }
+}
+
+impl BimModel {
+    pub fn grid(&self, guid: Guid) -> Option<&Grid> {
+        self.grids.iter().find(|grid| grid.guid == guid)
+    }
+}
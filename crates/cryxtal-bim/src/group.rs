@@ -0,0 +1,129 @@
+use cryxtal_base::{Error, Guid, Result};
+use cryxtal_topology::Matrix4;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{BimElement, BimModel};
+
+/// What an [`ElementGroup`] stands for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GroupKind {
+    /// A loose collection picked for convenience, such as "Core walls".
+    #[default]
+    Group,
+    /// A unit that is made and delivered as one piece, such as a precast panel with its
+    /// openings and rebar.
+    Assembly,
+}
+
+impl GroupKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            GroupKind::Group => "Group",
+            GroupKind::Assembly => "Assembly",
+        }
+    }
+}
+
+/// Named set of elements that is selected, moved, copied and exported as one unit. Members are
+/// referred to by guid; their openings and hosted elements belong to the group with them, and
+/// members no longer in the model are skipped.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ElementGroup {
+    pub guid: Guid,
+    pub name: String,
+    #[serde(default)]
+    pub kind: GroupKind,
+    pub members: Vec<Guid>,
+}
+
+impl ElementGroup {
+    pub fn new(name: impl Into<String>, kind: GroupKind) -> Self {
+        Self {
+            guid: Guid::new(),
+            name: name.into(),
+            kind,
+            members: Vec::new(),
+        }
+    }
+
+    pub fn with_members(mut self, members: impl IntoIterator<Item = Guid>) -> Self {
+        for member in members {
+            if !self.members.contains(&member) {
+                self.members.push(member);
+            }
+        }
+        self
+    }
+}
+
+impl BimModel {
+    pub fn group(&self, guid: Guid) -> Option<&ElementGroup> {
+        self.groups.iter().find(|group| group.guid == guid)
+    }
+
+    /// Groups `element` belongs to, directly or through the element it is hosted by.
+    pub fn groups_of(&self, element: Guid) -> Vec<&ElementGroup> {
+        self.groups
+            .iter()
+            .filter(|group| {
+                group.members.contains(&element) || self.has_ancestor_in(element, &group.members)
+            })
+            .collect()
+    }
+
+    /// Guids of every element in group `guid`: the members still in the model, each followed by
+    /// its openings and the elements it hosts.
+    pub fn group_elements(&self, guid: Guid) -> Result<Vec<Guid>> {
+        let roots = self.group_roots(guid)?;
+        Ok(self.hosted_tree(&roots))
+    }
+
+    /// Copies of every element in group `guid`, mapped by `transform` with new guids as in
+    /// [`BimModel::deep_clone`], and a group of the same name and kind holding them. Neither is
+    /// added to the model.
+    pub fn copy_group(
+        &self,
+        guid: Guid,
+        transform: Matrix4,
+    ) -> Result<(ElementGroup, Vec<BimElement>)> {
+        let Some(group) = self.group(guid) else {
+            return Err(Error::InvalidParameter(format!("unknown group {guid}")));
+        };
+        let roots = self.group_roots(guid)?;
+        let order = self.hosted_tree(&roots);
+        let guids: HashMap<Guid, Guid> = order.iter().map(|old| (*old, Guid::new())).collect();
+        let copies = self.map_elements(&order, &roots, &guids, &transform);
+        let copied = ElementGroup {
+            guid: Guid::new(),
+            name: group.name.clone(),
+            kind: group.kind,
+            members: roots.iter().map(|root| guids[root]).collect(),
+        };
+        Ok((copied, copies))
+    }
+
+    /// Every element in group `guid` mapped by `transform`, keeping its guid, for a
+    /// [`ModelCommand::ReplaceElements`](crate::ModelCommand) that moves the group.
+    pub fn moved_group(&self, guid: Guid, transform: Matrix4) -> Result<Vec<BimElement>> {
+        let roots = self.group_roots(guid)?;
+        let order = self.hosted_tree(&roots);
+        let guids: HashMap<Guid, Guid> = order.iter().map(|guid| (*guid, *guid)).collect();
+        Ok(self.map_elements(&order, &roots, &guids, &transform))
+    }
+
+    /// Members of group `guid` still in the model that are not hosted by another member.
+    fn group_roots(&self, guid: Guid) -> Result<Vec<Guid>> {
+        let Some(group) = self.group(guid) else {
+            return Err(Error::InvalidParameter(format!("unknown group {guid}")));
+        };
+        Ok(group
+            .members
+            .iter()
+            .copied()
+            .filter(|member| {
+                self.element(*member).is_some() && !self.has_ancestor_in(*member, &group.members)
+            })
+            .collect())
+    }
+}
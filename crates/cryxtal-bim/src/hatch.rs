@@ -0,0 +1,94 @@
+use crate::{BimCategory, BimElement, BimModel, MaterialClass};
+
+/// Fill pattern an element's cut is drawn with in 2D exports, so elements stay apart on black
+/// and white prints and for readers who cannot tell the layer colors apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HatchPattern {
+    /// Outline only.
+    None,
+    /// Lines at 45°.
+    Diagonal,
+    /// Lines at 135°.
+    ReverseDiagonal,
+    /// Pairs of lines at 45°.
+    Concrete,
+    /// Dense lines at 45°.
+    Steel,
+    /// Lines along X.
+    Timber,
+    /// Lines crossing at 45° and 135°.
+    Masonry,
+    /// Square grid along X and Y.
+    Insulation,
+}
+
+/// Family of parallel hatch lines at `angle_deg` from X, `spacing` apart and shifted across by
+/// `offset`, both in multiples of the drawing's hatch spacing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HatchLines {
+    pub angle_deg: f64,
+    pub spacing: f64,
+    pub offset: f64,
+}
+
+const fn lines(angle_deg: f64, spacing: f64, offset: f64) -> HatchLines {
+    HatchLines {
+        angle_deg,
+        spacing,
+        offset,
+    }
+}
+
+impl HatchPattern {
+    /// Line families the pattern is drawn with; empty for [`HatchPattern::None`].
+    pub fn lines(self) -> &'static [HatchLines] {
+        match self {
+            HatchPattern::None => &[],
+            HatchPattern::Diagonal => &[lines(45.0, 1.0, 0.0)],
+            HatchPattern::ReverseDiagonal => &[lines(135.0, 1.0, 0.0)],
+            HatchPattern::Concrete => &[lines(45.0, 1.5, 0.0), lines(45.0, 1.5, 0.2)],
+            HatchPattern::Steel => &[lines(45.0, 0.4, 0.0)],
+            HatchPattern::Timber => &[lines(0.0, 0.6, 0.0)],
+            HatchPattern::Masonry => &[lines(45.0, 1.0, 0.0), lines(135.0, 1.0, 0.0)],
+            HatchPattern::Insulation => &[lines(0.0, 1.0, 0.0), lines(90.0, 1.0, 0.0)],
+        }
+    }
+
+    /// Pattern for a material name such as `Concrete C30/37`, `S355` or `GL24h`; `None` when
+    /// the material is not recognized by [`MaterialClass::of`].
+    pub fn for_material(material: &str) -> Option<Self> {
+        Some(match MaterialClass::of(material)? {
+            MaterialClass::Concrete => HatchPattern::Concrete,
+            MaterialClass::Steel => HatchPattern::Steel,
+            MaterialClass::Timber => HatchPattern::Timber,
+            MaterialClass::Masonry => HatchPattern::Masonry,
+            MaterialClass::Insulation => HatchPattern::Insulation,
+        })
+    }
+
+    /// Pattern for elements of `category` whose material is not recognized.
+    pub fn for_category(category: BimCategory) -> Self {
+        match category {
+            BimCategory::Wall | BimCategory::Column | BimCategory::Beam => HatchPattern::Diagonal,
+            BimCategory::Slab
+            | BimCategory::Roof
+            | BimCategory::Ramp
+            | BimCategory::Stair
+            | BimCategory::Foundation => HatchPattern::ReverseDiagonal,
+            _ => HatchPattern::None,
+        }
+    }
+}
+
+impl BimModel {
+    /// Hatch pattern of `element`: by its [material](BimModel::element_material), else by its
+    /// category. Openings are never hatched.
+    pub fn hatch_pattern(&self, element: &BimElement) -> HatchPattern {
+        if element.category == BimCategory::Opening {
+            return HatchPattern::None;
+        }
+        self.element_material(element)
+            .and_then(HatchPattern::for_material)
+            .unwrap_or_else(|| HatchPattern::for_category(element.category))
+    }
+}
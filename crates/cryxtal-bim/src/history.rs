@@ -0,0 +1,897 @@
+use cryxtal_base::{Error, Guid, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::mem::Discriminant;
+
+use crate::diff::element_changed;
+use crate::{
+    BimElement, BimElementType, BimModel, DirtyFlags, ElementGroup, Grid, Issue, Layer, NamedView,
+    ParameterValue, Phase, ReferenceGeometry, SessionJournal, WeldAnnotation,
+};
+
+/// Undo depth kept by [`History`]; the oldest steps are dropped beyond it.
+const HISTORY_LIMIT: usize = 200;
+
+/// Kind of a command, with the parameter it writes for parameter and formula edits.
+type EditKey = (Discriminant<ModelCommand>, Option<String>);
+
+/// An undoable model edit. Applying a command returns its inverse, so undo and redo are both
+/// plain [`ModelCommand::apply`] calls. A failed command leaves the model unchanged.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ModelCommand {
+    AddElements {
+        storey: Guid,
+        elements: Vec<BimElement>,
+    },
+    /// Removes an element together with the openings it hosts.
+    DeleteElement {
+        guid: Guid,
+    },
+    /// Sets a parameter, or removes it when `value` is `None`. Formulas reading it are
+    /// refreshed; parameters driven by a formula cannot be set.
+    SetParameter {
+        guid: Guid,
+        name: String,
+        value: Option<ParameterValue>,
+    },
+    /// Drives a parameter by a formula (see [`BimElement::set_formula`]), or stops driving it
+    /// when `expression` is `None`.
+    SetFormula {
+        guid: Guid,
+        name: String,
+        expression: Option<String>,
+    },
+    /// Swaps elements (matched by guid) for edited versions, for edits that regenerate
+    /// geometry such as parameter or opening changes and the host walls they rebuild.
+    ReplaceElements {
+        elements: Vec<BimElement>,
+    },
+    AddReference(ReferenceGeometry),
+    DeleteReference {
+        guid: Guid,
+    },
+    AddGrid(Grid),
+    DeleteGrid {
+        guid: Guid,
+    },
+    /// Moves a storey (level) to a new elevation. Elements bound to it are not touched; batch
+    /// this with a [`ModelCommand::ReplaceElements`] carrying them regenerated.
+    SetStoreyElevation {
+        storey: Guid,
+        elevation: f64,
+    },
+    /// Moves an element onto a storey, or off every storey with `None`. Its geometry is not
+    /// touched; see [`BimModel::storey_assignments`].
+    MoveToStorey {
+        element: Guid,
+        storey: Option<Guid>,
+    },
+    AddType(BimElementType),
+    /// Removes a type that no element is an instance of.
+    DeleteType {
+        guid: Guid,
+    },
+    /// Swaps a type (matched by guid) for an edited version. Instances are not touched; batch
+    /// this with a [`ModelCommand::ReplaceElements`] carrying the regenerated instances.
+    ReplaceType(BimElementType),
+    AddGroup(ElementGroup),
+    /// Removes a group; its members stay in the model.
+    DeleteGroup {
+        guid: Guid,
+    },
+    /// Swaps a group (matched by guid) for an edited version, such as one with members added.
+    ReplaceGroup(ElementGroup),
+    /// Adds a layer under a name the model does not use yet.
+    AddLayer(Layer),
+    /// Removes a layer; elements on it keep their `Layer` parameter.
+    DeleteLayer {
+        name: String,
+    },
+    /// Inserts a phase at `index` in build order, with later phases moving up one.
+    AddPhase {
+        index: usize,
+        phase: Phase,
+    },
+    /// Removes a phase no element is created or demolished in.
+    DeletePhase {
+        guid: Guid,
+    },
+    /// Adds a named view under a name the model does not use yet.
+    AddView(NamedView),
+    DeleteView {
+        name: String,
+    },
+    /// Swaps a named view (matched by name) for an edited version, such as one with a filter
+    /// added or switched off.
+    ReplaceView(NamedView),
+    /// Replaces the welds annotated on an element's edges. Its geometry is not touched.
+    SetWelds {
+        guid: Guid,
+        welds: Vec<WeldAnnotation>,
+    },
+    AddIssue(Issue),
+    DeleteIssue {
+        guid: Guid,
+    },
+    /// Swaps an issue (matched by guid) for an edited version, such as one with a comment
+    /// added or closed.
+    ReplaceIssue(Issue),
+    /// Several commands applied as one step.
+    Batch(Vec<ModelCommand>),
+}
+
+impl ModelCommand {
+    pub fn set_layer(guid: Guid, layer: impl Into<String>) -> Self {
+        Self::SetParameter {
+            guid,
+            name: "Layer".to_string(),
+            value: Some(ParameterValue::Text(layer.into())),
+        }
+    }
+
+    /// Whether the command only edits annotations: reference geometry, named views, welds and
+    /// review issues, leaving geometry, parameters, levels, grids, types, groups, layers and
+    /// phases alone; read-only sessions still allow these.
+    pub fn is_annotation(&self) -> bool {
+        match self {
+            ModelCommand::AddReference(_)
+            | ModelCommand::DeleteReference { .. }
+            | ModelCommand::AddView(_)
+            | ModelCommand::DeleteView { .. }
+            | ModelCommand::ReplaceView(_)
+            | ModelCommand::SetWelds { .. }
+            | ModelCommand::AddIssue(_)
+            | ModelCommand::DeleteIssue { .. }
+            | ModelCommand::ReplaceIssue(_) => true,
+            ModelCommand::Batch(commands) => commands.iter().all(ModelCommand::is_annotation),
+            _ => false,
+        }
+    }
+
+    /// Guids of the elements, levels, grids, references, types, groups and phases the command
+    /// adds, changes or removes. Openings removed along with their host are not listed, nor are
+    /// layers and named views, which have no guid.
+    pub fn targets(&self) -> BTreeSet<Guid> {
+        let mut targets = BTreeSet::new();
+        self.collect_targets(&mut targets);
+        targets
+    }
+
+    /// [`EditKey`]s of the command, one per part of a batch.
+    fn edit_keys(&self) -> Vec<EditKey> {
+        match self {
+            ModelCommand::SetParameter { name, .. } | ModelCommand::SetFormula { name, .. } => {
+                vec![(std::mem::discriminant(self), Some(name.clone()))]
+            }
+            ModelCommand::Batch(commands) => {
+                commands.iter().flat_map(ModelCommand::edit_keys).collect()
+            }
+            _ => vec![(std::mem::discriminant(self), None)],
+        }
+    }
+
+    fn collect_targets(&self, targets: &mut BTreeSet<Guid>) {
+        match self {
+            ModelCommand::AddElements { elements, .. }
+            | ModelCommand::ReplaceElements { elements } => {
+                targets.extend(elements.iter().map(|element| element.guid));
+            }
+            ModelCommand::DeleteElement { guid }
+            | ModelCommand::SetParameter { guid, .. }
+            | ModelCommand::SetFormula { guid, .. }
+            | ModelCommand::DeleteReference { guid }
+            | ModelCommand::DeleteGrid { guid }
+            | ModelCommand::DeleteType { guid }
+            | ModelCommand::DeleteGroup { guid }
+            | ModelCommand::DeletePhase { guid }
+            | ModelCommand::SetWelds { guid, .. }
+            | ModelCommand::DeleteIssue { guid } => {
+                targets.insert(*guid);
+            }
+            ModelCommand::AddReference(reference) => {
+                targets.insert(reference.guid);
+            }
+            ModelCommand::AddGrid(grid) => {
+                targets.insert(grid.guid);
+            }
+            ModelCommand::SetStoreyElevation { storey, .. } => {
+                targets.insert(*storey);
+            }
+            ModelCommand::MoveToStorey { element, .. } => {
+                targets.insert(*element);
+            }
+            ModelCommand::AddType(element_type) | ModelCommand::ReplaceType(element_type) => {
+                targets.insert(element_type.guid);
+            }
+            ModelCommand::AddGroup(group) | ModelCommand::ReplaceGroup(group) => {
+                targets.insert(group.guid);
+            }
+            ModelCommand::AddIssue(issue) | ModelCommand::ReplaceIssue(issue) => {
+                targets.insert(issue.guid);
+            }
+            ModelCommand::AddPhase { phase, .. } => {
+                targets.insert(phase.guid);
+            }
+            ModelCommand::AddLayer(_)
+            | ModelCommand::DeleteLayer { .. }
+            | ModelCommand::AddView(_)
+            | ModelCommand::DeleteView { .. }
+            | ModelCommand::ReplaceView(_) => {}
+            ModelCommand::Batch(commands) => {
+                for command in commands {
+                    command.collect_targets(targets);
+                }
+            }
+        }
+    }
+
+    /// Applies the command as the model's next revision, stamping the elements it adds or
+    /// changes with it (see [`BimModel::changed_since`]).
+    pub fn apply(self, model: &mut BimModel) -> Result<ModelCommand> {
+        let revision = model.revision() + 1;
+        let inverse = self.apply_at(model, revision)?;
+        model.revision = revision;
+        Ok(inverse)
+    }
+
+    fn apply_at(self, model: &mut BimModel, revision: u64) -> Result<ModelCommand> {
+        match self {
+            ModelCommand::AddElements { storey, elements } => {
+                if model.storey(storey).is_none() {
+                    return Err(Error::InvalidParameter(format!("unknown storey {storey}")));
+                }
+                for (index, element) in elements.iter().enumerate() {
+                    let duplicate = elements[..index]
+                        .iter()
+                        .any(|other| other.guid == element.guid);
+                    if duplicate || model.element_index(element.guid).is_some() {
+                        return Err(Error::InvalidParameter(format!(
+                            "element {} is already in the model",
+                            element.guid
+                        )));
+                    }
+                }
+                let mut inverse = Vec::with_capacity(elements.len());
+                for element in elements {
+                    let guid = element.guid;
+                    inverse.push(ModelCommand::DeleteElement { guid });
+                    model.add_element(element, storey)?;
+                    model.stamp(guid, revision);
+                }
+                inverse.reverse();
+                Ok(ModelCommand::Batch(inverse))
+            }
+            ModelCommand::DeleteElement { guid } => {
+                let storeys: Vec<_> = std::iter::once(guid)
+                    .chain(model.dependents(guid))
+                    .map(|member| (member, storey_guid(model, member)))
+                    .collect();
+                let removed = model.remove_element(guid);
+                if removed.is_empty() {
+                    return Err(Error::InvalidParameter(format!("unknown element {guid}")));
+                }
+                for element in &removed {
+                    model.record_removal(element.guid, revision);
+                }
+                let fallback = model.default_storey();
+                let inverse = removed
+                    .into_iter()
+                    .filter_map(|element| {
+                        let storey = storeys
+                            .iter()
+                            .find(|(member, _)| *member == element.guid)
+                            .and_then(|(_, storey)| *storey)
+                            .or(fallback)?;
+                        Some(ModelCommand::AddElements {
+                            storey,
+                            elements: vec![element],
+                        })
+                    })
+                    .collect();
+                Ok(ModelCommand::Batch(inverse))
+            }
+            ModelCommand::SetParameter { guid, name, value } => {
+                let Some(index) = model.element_index(guid) else {
+                    return Err(Error::InvalidParameter(format!("unknown element {guid}")));
+                };
+                ensure_editable(&model.elements()[index])?;
+                let element = &mut model.elements_mut()[index];
+                if element.formula(&name).is_some() {
+                    return Err(Error::InvalidParameter(format!(
+                        "{name} is driven by a formula"
+                    )));
+                }
+                let before = element.parameter_hash();
+                let before_parameters = element.parameters.clone();
+                let previous = match value {
+                    Some(value) => element.parameters.insert(name.clone(), value),
+                    None => element.parameters.remove(&name),
+                };
+                // Formulas left without a value are reported by `evaluate`.
+                let _ = element.refresh_formulas(Some(&name));
+                let parameters = element.parameter_hash() != before;
+                let changed = element.parameters != before_parameters;
+                model.mark_dirty(
+                    guid,
+                    DirtyFlags {
+                        parameters,
+                        geometry: false,
+                    },
+                );
+                // Setting a parameter to the value it has is no edit of the element.
+                if changed {
+                    model.stamp(guid, revision);
+                    model.log_parameters(guid, &before_parameters, revision);
+                }
+                Ok(ModelCommand::SetParameter {
+                    guid,
+                    name,
+                    value: previous,
+                })
+            }
+            ModelCommand::SetFormula {
+                guid,
+                name,
+                expression,
+            } => {
+                let Some(index) = model.element_index(guid) else {
+                    return Err(Error::InvalidParameter(format!("unknown element {guid}")));
+                };
+                ensure_editable(&model.elements()[index])?;
+                let element = &mut model.elements_mut()[index];
+                let before = element.parameter_hash();
+                let before_parameters = element.parameters.clone();
+                let previous_formula = element.formula(&name).map(str::to_string);
+                let previous_value = before_parameters.get(&name).cloned();
+                match expression {
+                    Some(expression) => element.set_formula(name.clone(), expression)?,
+                    None => {
+                        element.remove_formula(&name);
+                    }
+                }
+                let parameters = element.parameter_hash() != before;
+                model.mark_dirty(
+                    guid,
+                    DirtyFlags {
+                        parameters,
+                        geometry: false,
+                    },
+                );
+                model.stamp(guid, revision);
+                model.log_parameters(guid, &before_parameters, revision);
+                // A parameter set by hand before gets its value back with the formula gone.
+                Ok(match previous_formula {
+                    Some(expression) => ModelCommand::SetFormula {
+                        guid,
+                        name,
+                        expression: Some(expression),
+                    },
+                    None => ModelCommand::Batch(vec![
+                        ModelCommand::SetFormula {
+                            guid,
+                            name: name.clone(),
+                            expression: None,
+                        },
+                        ModelCommand::SetParameter {
+                            guid,
+                            name,
+                            value: previous_value,
+                        },
+                    ]),
+                })
+            }
+            ModelCommand::ReplaceElements { elements } => {
+                let indices = elements
+                    .iter()
+                    .map(|element| {
+                        let index = model.element_index(element.guid).ok_or_else(|| {
+                            Error::InvalidParameter(format!("unknown element {}", element.guid))
+                        })?;
+                        ensure_editable(&model.elements()[index])?;
+                        Ok(index)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let previous = indices
+                    .into_iter()
+                    .zip(elements)
+                    .map(|(index, element)| {
+                        let previous = std::mem::replace(&mut model.elements_mut()[index], element);
+                        let current = &model.elements()[index];
+                        let (guid, flags) = (current.guid, DirtyFlags::between(&previous, current));
+                        // Elements handed back as they were, such as walls re-joined around a
+                        // new one, keep their stamp.
+                        let changed = !flags.is_clean() || element_changed(&previous, current);
+                        model.mark_dirty(guid, flags);
+                        if changed {
+                            model.stamp(guid, revision);
+                            model.log_parameters(guid, &previous.parameters, revision);
+                        }
+                        previous
+                    })
+                    .collect();
+                Ok(ModelCommand::ReplaceElements { elements: previous })
+            }
+            ModelCommand::AddReference(reference) => {
+                if model
+                    .references
+                    .iter()
+                    .any(|existing| existing.guid == reference.guid)
+                {
+                    return Err(Error::InvalidParameter(format!(
+                        "reference {} is already in the model",
+                        reference.guid
+                    )));
+                }
+                let guid = reference.guid;
+                model.references.push(reference);
+                Ok(ModelCommand::DeleteReference { guid })
+            }
+            ModelCommand::DeleteReference { guid } => {
+                let Some(index) = model
+                    .references
+                    .iter()
+                    .position(|reference| reference.guid == guid)
+                else {
+                    return Err(Error::InvalidParameter(format!("unknown reference {guid}")));
+                };
+                Ok(ModelCommand::AddReference(model.references.remove(index)))
+            }
+            ModelCommand::AddGrid(grid) => {
+                if model.grid(grid.guid).is_some() {
+                    return Err(Error::InvalidParameter(format!(
+                        "grid {} is already in the model",
+                        grid.guid
+                    )));
+                }
+                let guid = grid.guid;
+                model.grids.push(grid);
+                Ok(ModelCommand::DeleteGrid { guid })
+            }
+            ModelCommand::DeleteGrid { guid } => {
+                let Some(index) = model.grids.iter().position(|grid| grid.guid == guid) else {
+                    return Err(Error::InvalidParameter(format!("unknown grid {guid}")));
+                };
+                Ok(ModelCommand::AddGrid(model.grids.remove(index)))
+            }
+            ModelCommand::SetStoreyElevation { storey, elevation } => {
+                if !elevation.is_finite() {
+                    return Err(Error::InvalidParameter(format!(
+                        "invalid elevation {elevation}"
+                    )));
+                }
+                let Some(target) = model.storey_mut(storey) else {
+                    return Err(Error::InvalidParameter(format!("unknown storey {storey}")));
+                };
+                let previous = std::mem::replace(&mut target.elevation, elevation);
+                Ok(ModelCommand::SetStoreyElevation {
+                    storey,
+                    elevation: previous,
+                })
+            }
+            ModelCommand::MoveToStorey { element, storey } => {
+                let previous = storey_guid(model, element);
+                match storey {
+                    Some(storey) => model.move_to_storey(element, storey)?,
+                    None if model.element_index(element).is_none() => {
+                        return Err(Error::InvalidParameter(format!(
+                            "unknown element {element}"
+                        )));
+                    }
+                    None => model.leave_storeys(element),
+                }
+                model.stamp(element, revision);
+                Ok(ModelCommand::MoveToStorey {
+                    element,
+                    storey: previous,
+                })
+            }
+            ModelCommand::AddType(element_type) => {
+                if model.element_type(element_type.guid).is_some() {
+                    return Err(Error::InvalidParameter(format!(
+                        "type {} is already in the model",
+                        element_type.guid
+                    )));
+                }
+                let guid = element_type.guid;
+                model.types.push(element_type);
+                Ok(ModelCommand::DeleteType { guid })
+            }
+            ModelCommand::DeleteType { guid } => {
+                let Some(index) = model
+                    .types
+                    .iter()
+                    .position(|candidate| candidate.guid == guid)
+                else {
+                    return Err(Error::InvalidParameter(format!("unknown type {guid}")));
+                };
+                if !model.instances_of(guid).is_empty() {
+                    return Err(Error::InvalidParameter(format!(
+                        "type {} still has instances",
+                        model.types[index].name
+                    )));
+                }
+                Ok(ModelCommand::AddType(model.types.remove(index)))
+            }
+            ModelCommand::ReplaceType(element_type) => {
+                let Some(slot) = model
+                    .types
+                    .iter_mut()
+                    .find(|candidate| candidate.guid == element_type.guid)
+                else {
+                    return Err(Error::InvalidParameter(format!(
+                        "unknown type {}",
+                        element_type.guid
+                    )));
+                };
+                Ok(ModelCommand::ReplaceType(std::mem::replace(
+                    slot,
+                    element_type,
+                )))
+            }
+            ModelCommand::AddGroup(group) => {
+                if model.group(group.guid).is_some() {
+                    return Err(Error::InvalidParameter(format!(
+                        "group {} is already in the model",
+                        group.guid
+                    )));
+                }
+                let guid = group.guid;
+                model.groups.push(group);
+                Ok(ModelCommand::DeleteGroup { guid })
+            }
+            ModelCommand::DeleteGroup { guid } => {
+                let Some(index) = model
+                    .groups
+                    .iter()
+                    .position(|candidate| candidate.guid == guid)
+                else {
+                    return Err(Error::InvalidParameter(format!("unknown group {guid}")));
+                };
+                Ok(ModelCommand::AddGroup(model.groups.remove(index)))
+            }
+            ModelCommand::ReplaceGroup(group) => {
+                let Some(slot) = model
+                    .groups
+                    .iter_mut()
+                    .find(|candidate| candidate.guid == group.guid)
+                else {
+                    return Err(Error::InvalidParameter(format!(
+                        "unknown group {}",
+                        group.guid
+                    )));
+                };
+                Ok(ModelCommand::ReplaceGroup(std::mem::replace(slot, group)))
+            }
+            ModelCommand::AddLayer(layer) => {
+                if model
+                    .layers
+                    .iter()
+                    .any(|existing| existing.name == layer.name)
+                {
+                    return Err(Error::InvalidParameter(format!(
+                        "layer {} is already in the model",
+                        layer.name
+                    )));
+                }
+                let name = layer.name.clone();
+                model.layers.push(layer);
+                Ok(ModelCommand::DeleteLayer { name })
+            }
+            ModelCommand::DeleteLayer { name } => {
+                let Some(index) = model.layers.iter().position(|layer| layer.name == name) else {
+                    return Err(Error::InvalidParameter(format!("unknown layer {name}")));
+                };
+                Ok(ModelCommand::AddLayer(model.layers.remove(index)))
+            }
+            ModelCommand::AddPhase { index, phase } => {
+                if model.phase(phase.guid).is_some() {
+                    return Err(Error::InvalidParameter(format!(
+                        "phase {} is already in the model",
+                        phase.guid
+                    )));
+                }
+                if index > model.phases.len() {
+                    return Err(Error::InvalidParameter(format!(
+                        "phase index {index} is past the last phase"
+                    )));
+                }
+                let guid = phase.guid;
+                model.phases.insert(index, phase);
+                Ok(ModelCommand::DeletePhase { guid })
+            }
+            ModelCommand::DeletePhase { guid } => {
+                let Some(index) = model.phases.iter().position(|phase| phase.guid == guid) else {
+                    return Err(Error::InvalidParameter(format!("unknown phase {guid}")));
+                };
+                let phase = &model.phases[index];
+                if model.elements().iter().any(|element| {
+                    element.phase_created == Some(guid) || element.phase_demolished == Some(guid)
+                }) {
+                    return Err(Error::InvalidParameter(format!(
+                        "phase {} still has elements",
+                        phase.name
+                    )));
+                }
+                let phase = model.phases.remove(index);
+                Ok(ModelCommand::AddPhase { index, phase })
+            }
+            ModelCommand::AddView(view) => {
+                if model
+                    .views
+                    .iter()
+                    .any(|existing| existing.name == view.name)
+                {
+                    return Err(Error::InvalidParameter(format!(
+                        "view {} is already in the model",
+                        view.name
+                    )));
+                }
+                let name = view.name.clone();
+                model.views.push(view);
+                Ok(ModelCommand::DeleteView { name })
+            }
+            ModelCommand::DeleteView { name } => {
+                let Some(index) = model.views.iter().position(|view| view.name == name) else {
+                    return Err(Error::InvalidParameter(format!("unknown view {name}")));
+                };
+                Ok(ModelCommand::AddView(model.views.remove(index)))
+            }
+            ModelCommand::ReplaceView(view) => {
+                let Some(slot) = model
+                    .views
+                    .iter_mut()
+                    .find(|candidate| candidate.name == view.name)
+                else {
+                    return Err(Error::InvalidParameter(format!(
+                        "unknown view {}",
+                        view.name
+                    )));
+                };
+                Ok(ModelCommand::ReplaceView(std::mem::replace(slot, view)))
+            }
+            ModelCommand::SetWelds { guid, welds } => {
+                let Some(index) = model.element_index(guid) else {
+                    return Err(Error::InvalidParameter(format!("unknown element {guid}")));
+                };
+                let element = &model.elements()[index];
+                ensure_editable(element)?;
+                for weld in &welds {
+                    element.check_weld(weld)?;
+                }
+                let previous = std::mem::replace(&mut model.elements_mut()[index].welds, welds);
+                model.stamp(guid, revision);
+                Ok(ModelCommand::SetWelds {
+                    guid,
+                    welds: previous,
+                })
+            }
+            ModelCommand::AddIssue(issue) => {
+                if model.issue(issue.guid).is_some() {
+                    return Err(Error::InvalidParameter(format!(
+                        "issue {} is already in the model",
+                        issue.guid
+                    )));
+                }
+                let guid = issue.guid;
+                model.issues.push(issue);
+                Ok(ModelCommand::DeleteIssue { guid })
+            }
+            ModelCommand::DeleteIssue { guid } => {
+                let Some(index) = model
+                    .issues
+                    .iter()
+                    .position(|candidate| candidate.guid == guid)
+                else {
+                    return Err(Error::InvalidParameter(format!("unknown issue {guid}")));
+                };
+                Ok(ModelCommand::AddIssue(model.issues.remove(index)))
+            }
+            ModelCommand::ReplaceIssue(issue) => {
+                let Some(slot) = model
+                    .issues
+                    .iter_mut()
+                    .find(|candidate| candidate.guid == issue.guid)
+                else {
+                    return Err(Error::InvalidParameter(format!(
+                        "unknown issue {}",
+                        issue.guid
+                    )));
+                };
+                Ok(ModelCommand::ReplaceIssue(std::mem::replace(slot, issue)))
+            }
+            ModelCommand::Batch(commands) => {
+                let mut inverses = Vec::with_capacity(commands.len());
+                for command in commands {
+                    match command.apply_at(model, revision) {
+                        Ok(inverse) => inverses.push(inverse),
+                        Err(err) => {
+                            for inverse in inverses.into_iter().rev() {
+                                let _ = inverse.apply_at(model, revision);
+                            }
+                            return Err(err);
+                        }
+                    }
+                }
+                inverses.reverse();
+                Ok(ModelCommand::Batch(inverses))
+            }
+        }
+    }
+}
+
+fn storey_guid(model: &BimModel, element: Guid) -> Option<Guid> {
+    model.storey_of(element).map(|storey| storey.guid)
+}
+
+/// Reference meshes are context: they are added and deleted, but never edited.
+fn ensure_editable(element: &BimElement) -> Result<()> {
+    if element.is_reference_mesh() {
+        return Err(Error::InvalidParameter(format!(
+            "{} is a reference mesh and cannot be edited",
+            element.name
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+struct HistoryEntry {
+    label: String,
+    command: ModelCommand,
+    /// [`ModelCommand::targets`] of the edit the entry undoes, which its inverse restores.
+    targets: BTreeSet<Guid>,
+    /// [`ModelCommand::edit_keys`] of that edit.
+    keys: Vec<EditKey>,
+}
+
+/// Undo and redo stacks over a [`BimModel`]. Edits go through [`History::apply`]; each stack
+/// entry holds the command that steps the model back (or forward) again. Everything applied is
+/// also recorded in the [`History::journal`].
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    journal: SessionJournal,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn apply(
+        &mut self,
+        model: &mut BimModel,
+        label: impl Into<String>,
+        command: ModelCommand,
+    ) -> Result<()> {
+        let label = label.into();
+        let applied = command.clone();
+        let targets = command.targets();
+        let keys = command.edit_keys();
+        self.journal.begin(model);
+        let inverse = command.apply(model)?;
+        self.journal.push(label.clone(), applied);
+        self.undo.push(HistoryEntry {
+            label,
+            command: inverse,
+            targets,
+            keys,
+        });
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+        Ok(())
+    }
+
+    /// Like [`History::apply`], but folds into the previous step when it has the same label,
+    /// makes the same kind of edit (to the same parameter, for parameter and formula edits) and
+    /// touches nothing beyond that step's [`ModelCommand::targets`], so a continuous edit
+    /// (dragging a value) undoes in one go while the same edit on another element or another
+    /// parameter stays its own step. The earlier inverse is kept, as it restores everything the
+    /// new command touches.
+    pub fn apply_merged(
+        &mut self,
+        model: &mut BimModel,
+        label: impl Into<String>,
+        command: ModelCommand,
+    ) -> Result<()> {
+        let label = label.into();
+        let mergeable = self.undo.last().is_some_and(|entry| {
+            entry.label == label
+                && entry.keys == command.edit_keys()
+                && command.targets().is_subset(&entry.targets)
+        });
+        if self.redo.is_empty() && mergeable {
+            let applied = command.clone();
+            self.journal.begin(model);
+            command.apply(model)?;
+            self.journal.push(label, applied);
+            return Ok(());
+        }
+        self.apply(model, label, command)
+    }
+
+    /// Reverts the last step and returns its label, or `None` when there is nothing to undo.
+    pub fn undo(&mut self, model: &mut BimModel) -> Result<Option<String>> {
+        step(
+            &mut self.undo,
+            &mut self.redo,
+            &mut self.journal,
+            "Undo",
+            model,
+        )
+    }
+
+    /// Re-applies the last undone step and returns its label.
+    pub fn redo(&mut self, model: &mut BimModel) -> Result<Option<String>> {
+        step(
+            &mut self.redo,
+            &mut self.undo,
+            &mut self.journal,
+            "Redo",
+            model,
+        )
+    }
+
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|entry| entry.label.as_str())
+    }
+
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|entry| entry.label.as_str())
+    }
+
+    /// Command the next undo applies.
+    pub fn undo_command(&self) -> Option<&ModelCommand> {
+        self.undo.last().map(|entry| &entry.command)
+    }
+
+    /// Command the next redo applies.
+    pub fn redo_command(&self) -> Option<&ModelCommand> {
+        self.redo.last().map(|entry| &entry.command)
+    }
+
+    /// Commands applied since the history was last cleared, for export and replay.
+    pub fn journal(&self) -> &SessionJournal {
+        &self.journal
+    }
+
+    /// Empties both stacks and starts a new journal.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.journal = SessionJournal::default();
+    }
+}
+
+fn step(
+    from: &mut Vec<HistoryEntry>,
+    to: &mut Vec<HistoryEntry>,
+    journal: &mut SessionJournal,
+    verb: &str,
+    model: &mut BimModel,
+) -> Result<Option<String>> {
+    let Some(entry) = from.pop() else {
+        return Ok(None);
+    };
+    journal.begin(model);
+    match entry.command.clone().apply(model) {
+        Ok(inverse) => {
+            let label = entry.label.clone();
+            journal.push(format!("{verb} {label}"), entry.command);
+            to.push(HistoryEntry {
+                label: entry.label,
+                command: inverse,
+                targets: entry.targets,
+                keys: entry.keys,
+            });
+            Ok(Some(label))
+        }
+        Err(err) => {
+            from.push(entry);
+            Err(err)
+        }
+    }
+}
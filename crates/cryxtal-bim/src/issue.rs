@@ -0,0 +1,61 @@
+use cryxtal_base::Guid;
+use serde::{Deserialize, Serialize};
+
+use crate::BimModel;
+
+/// A review issue raised on the model, such as a clash or a missing opening, with the elements
+/// it concerns and the comments made on it. Issues are annotations, so read-only sessions still
+/// raise, discuss and close them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Issue {
+    pub guid: Guid,
+    pub title: String,
+    /// Elements the issue is about; elements no longer in the model are skipped.
+    #[serde(default)]
+    pub elements: Vec<Guid>,
+    /// Comments in the order they were made.
+    #[serde(default)]
+    pub comments: Vec<IssueComment>,
+    #[serde(default)]
+    pub closed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IssueComment {
+    pub author: String,
+    pub text: String,
+}
+
+impl Issue {
+    pub fn new(title: impl Into<String>, elements: impl IntoIterator<Item = Guid>) -> Self {
+        Self {
+            guid: Guid::new(),
+            title: title.into(),
+            elements: elements.into_iter().collect(),
+            comments: Vec::new(),
+            closed: false,
+        }
+    }
+
+    pub fn with_comment(mut self, author: impl Into<String>, text: impl Into<String>) -> Self {
+        self.comments.push(IssueComment {
+            author: author.into(),
+            text: text.into(),
+        });
+        self
+    }
+}
+
+impl BimModel {
+    pub fn issue(&self, guid: Guid) -> Option<&Issue> {
+        self.issues.iter().find(|issue| issue.guid == guid)
+    }
+
+    /// Open issues concerning `element`.
+    pub fn open_issues_of(&self, element: Guid) -> Vec<&Issue> {
+        self.issues
+            .iter()
+            .filter(|issue| !issue.closed && issue.elements.contains(&element))
+            .collect()
+    }
+}
@@ -0,0 +1,65 @@
+use cryxtal_base::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{BimModel, ModelCommand};
+
+/// One step of a session: the command that was applied, with the label it had in the history.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub label: String,
+    pub command: ModelCommand,
+}
+
+/// Every command a [`History`](crate::History) applied since it was cleared, undo and redo
+/// included as the commands they applied, in order. Replaying it against the model the session
+/// started from reproduces the session exactly, for bug reports and reproducing issues.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SessionJournal {
+    /// Name of the model the session started from.
+    pub model: String,
+    /// [`BimModel::revision`] the session started from.
+    pub base_revision: u64,
+    pub entries: Vec<JournalEntry>,
+}
+
+impl SessionJournal {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Notes `model` as the one the session starts from, unless entries were recorded already.
+    pub(crate) fn begin(&mut self, model: &BimModel) {
+        if self.entries.is_empty() {
+            self.model = model.name.clone();
+            self.base_revision = model.revision();
+        }
+    }
+
+    pub(crate) fn push(&mut self, label: String, command: ModelCommand) {
+        self.entries.push(JournalEntry { label, command });
+    }
+
+    /// Applies the entries to `model`, which must be at the revision the session started from.
+    /// Stops at the first entry that fails, naming it; the entries before it stay applied.
+    /// Returns the number of entries applied.
+    pub fn replay(&self, model: &mut BimModel) -> Result<usize> {
+        if model.revision() != self.base_revision {
+            return Err(Error::InvalidParameter(format!(
+                "the journal starts from revision {} of {}, the model is at revision {}",
+                self.base_revision,
+                self.model,
+                model.revision()
+            )));
+        }
+        for (index, entry) in self.entries.iter().enumerate() {
+            entry.command.clone().apply(model).map_err(|err| {
+                Error::InvalidParameter(format!(
+                    "journal entry {} ({}) failed: {err}",
+                    index + 1,
+                    entry.label
+                ))
+            })?;
+        }
+        Ok(self.entries.len())
+    }
+}
@@ -1,19 +1,97 @@
 use cryxtal_base::Guid;
-use cryxtal_topology::Solid;
+use cryxtal_topology::{ShapeTags, Solid};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::fmt;
+
+mod bom;
+mod constraint;
+mod coordinates;
+mod diff;
+mod element_type;
+mod formula;
+mod grid;
+mod group;
+mod hatch;
+mod history;
+mod issue;
+mod journal;
+mod material;
+mod model;
+mod opening;
+mod phase;
+mod query;
+mod rating;
+mod reference;
+mod regen;
+mod rename;
+mod revision;
+mod schema;
+mod similar;
+mod storey_assign;
+mod storey_copy;
+mod takeoff;
+mod units;
+mod view_filter;
+mod weld;
+
+pub use bom::{BomRow, DENSITY_KEY, bill_of_materials};
+pub use constraint::Constraint;
+pub use coordinates::CoordinateTransform;
+pub use diff::{ElementChange, ModelDiff, ParameterChange, diff};
+pub use element_type::BimElementType;
+pub use formula::formula_parameters;
+pub use grid::{Grid, GridCurve};
+pub use group::{ElementGroup, GroupKind};
+pub use hatch::{HatchLines, HatchPattern};
+pub use history::{History, ModelCommand};
+pub use issue::{Issue, IssueComment};
+pub use journal::{JournalEntry, SessionJournal};
+pub use material::MaterialClass;
+pub use model::{BimModel, Building, Layer, Site, Storey};
+pub use opening::{
+    Opening, OpeningPlacement, OpeningProfile, cutting_openings, embedded_members, hosted_openings,
+    opening_host,
+};
+pub use phase::{Phase, PhaseStatus};
+pub use query::ElementQuery;
+pub use rating::{ACOUSTIC_RATING_KEY, FIRE_RATING_KEY, Rating, RatingShortfall};
+pub use reference::{REFERENCE_MESH_PARAMETER, ReferenceGeometry, ReferenceKind};
+pub use regen::DirtyFlags;
+pub use rename::{ParameterRename, RenameConflict, RenamedKey};
+pub use revision::{ChangeSource, ElementRevision, ParameterLogEntry};
+pub use schema::{
+    ParameterKind, ParameterSpec, ParameterUnit, SchemaViolation, is_computed_parameter,
+    parameter_schema, parameter_unit, validate_parameters,
+};
+pub use similar::SIMILARITY_TOLERANCE;
+pub use storey_assign::{STOREY_LOCKED_KEY, StoreyAssignment};
+pub use takeoff::{TakeoffReport, TakeoffRow, takeoff};
+pub use view_filter::{
+    FilterOperator, FilterRule, GraphicOverride, LegendEntry, NamedView, ViewFilter,
+};
+pub use weld::{WeldAnnotation, WeldKind, WeldSide};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BimCategory {
     Wall,
     Slab,
     Beam,
+    Column,
+    Foundation,
+    Roof,
+    Stair,
+    Ramp,
+    Door,
+    Window,
     Opening,
     Rebar,
+    Pipe,
+    Duct,
     Generic,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ParameterValue {
     Integer(i64),
     Number(f64),
@@ -21,6 +99,19 @@ pub enum ParameterValue {
     Text(String),
 }
 
+/// The value as typed into a cell: numbers in full precision, booleans as `true`/`false` and
+/// text unquoted.
+impl fmt::Display for ParameterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterValue::Integer(value) => write!(f, "{value}"),
+            ParameterValue::Number(value) => write!(f, "{value}"),
+            ParameterValue::Bool(value) => write!(f, "{value}"),
+            ParameterValue::Text(text) => f.write_str(text),
+        }
+    }
+}
+
 pub type ParameterSet = BTreeMap<String, ParameterValue>;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -29,7 +120,55 @@ pub struct BimElement {
     pub name: String,
     pub category: BimCategory,
     pub parameters: ParameterSet,
+    /// Units of parameters the schema and builders do not know, such as a user's own length
+    /// parameters, by name; see [`BimElement::parameter_unit`].
+    #[serde(default)]
+    pub parameter_units: BTreeMap<String, ParameterUnit>,
+    /// Formulas driving parameters, by parameter name; their values are kept in `parameters`
+    /// as the inputs change. See [`BimElement::set_formula`].
+    #[serde(default)]
+    pub formulas: BTreeMap<String, String>,
+    /// Relations to a storey or the host kept up by [`BimModel::solve_constraints`].
+    #[serde(default)]
+    pub constraints: Vec<Constraint>,
     pub geometry: Solid,
+    #[serde(default)]
+    pub opening: Option<Opening>,
+    /// Hosts besides `opening.host` the opening also cuts through, such as the slab beside the
+    /// wall a duct opening is placed in; see [`BimModel::cut_hosts`]. For elements that are not
+    /// openings, the hosts they are set into, such as the wall around a lintel.
+    #[serde(default)]
+    pub cuts: Vec<Guid>,
+    /// Element this one is placed in, such as the wall holding a rebar. Unlike openings, hosted
+    /// elements outlive their host and are reported by [`BimModel::orphans`].
+    #[serde(default)]
+    pub host: Option<Guid>,
+    /// The element was generated for its host, such as the lintel over an opening, and is
+    /// removed along with it instead of being left an orphan.
+    #[serde(default)]
+    pub removed_with_host: bool,
+    /// Shared [`BimElementType`] this element is an instance of.
+    #[serde(default)]
+    pub type_id: Option<Guid>,
+    /// [`Phase`] the element is built in; `None` predates the first phase.
+    #[serde(default)]
+    pub phase_created: Option<Guid>,
+    /// [`Phase`] the element is torn down in, if any.
+    #[serde(default)]
+    pub phase_demolished: Option<Guid>,
+    /// Face and edge tags on `geometry`, written to STEP as face and edge names.
+    #[serde(default)]
+    pub tags: ShapeTags,
+    /// Welds along edges of `geometry`.
+    #[serde(default)]
+    pub welds: Vec<WeldAnnotation>,
+    /// [`BimElement::regen_key`] of the inputs `geometry` was last built from; rebuilds are
+    /// skipped while it still matches. Elements start out matching their own parameters.
+    #[serde(default)]
+    pub regen_hash: Option<u64>,
+    /// Last edit of the element; see [`BimModel::changed_since`].
+    #[serde(default)]
+    pub revision: ElementRevision,
 }
 
 impl BimElement {
@@ -40,20 +179,82 @@ impl BimElement {
         parameters: ParameterSet,
         geometry: Solid,
     ) -> Self {
-        Self {
+        let mut element = Self {
             guid,
             name: name.into(),
             category,
             parameters,
+            parameter_units: BTreeMap::new(),
+            formulas: BTreeMap::new(),
+            constraints: Vec::new(),
             geometry,
-        }
+            opening: None,
+            cuts: Vec::new(),
+            host: None,
+            removed_with_host: false,
+            type_id: None,
+            phase_created: None,
+            phase_demolished: None,
+            tags: ShapeTags::new(),
+            welds: Vec::new(),
+            regen_hash: None,
+            revision: ElementRevision::default(),
+        };
+        element.regen_hash = Some(element.parameter_hash());
+        element
+    }
+
+    /// Builds an opening element; its guid is the opening's own.
+    pub fn opening(
+        opening: Opening,
+        name: impl Into<String>,
+        parameters: ParameterSet,
+        geometry: Solid,
+    ) -> Self {
+        let mut element = Self {
+            opening: Some(opening),
+            ..Self::new(
+                opening.guid,
+                name,
+                BimCategory::Opening,
+                parameters,
+                geometry,
+            )
+        };
+        element.regen_hash = Some(element.parameter_hash());
+        element
     }
 
+    /// Sets a parameter and refreshes the formulas reading it. A formula-driven parameter keeps
+    /// its formula's value.
     pub fn insert_parameter(&mut self, key: impl Into<String>, value: ParameterValue) {
-        self.parameters.insert(key.into(), value);
+        let key = key.into();
+        self.parameters.insert(key.clone(), value);
+        // Formulas left without a value are reported by `evaluate`.
+        let _ = self.refresh_formulas(Some(&key));
+    }
+
+    /// Unit of parameter `key`: the one declared in `parameter_units`, else the unit
+    /// [`parameter_unit`] knows for the category.
+    pub fn parameter_unit(&self, key: &str) -> ParameterUnit {
+        self.parameter_units
+            .get(key)
+            .copied()
+            .unwrap_or_else(|| parameter_unit(self.category, key))
     }
 
     pub fn geometry(&self) -> &Solid {
         &self.geometry
     }
+
+    /// Whether the element is an imported reference mesh, marked with
+    /// [`REFERENCE_MESH_PARAMETER`]. Reference meshes are modeling context: they are drawn and
+    /// snapped to, but left out of quantities, schedules, IFC and clash checks, and commands
+    /// other than deleting them cannot edit them.
+    pub fn is_reference_mesh(&self) -> bool {
+        matches!(
+            self.parameters.get(REFERENCE_MESH_PARAMETER),
+            Some(ParameterValue::Bool(true))
+        )
+    }
 }
@@ -0,0 +1,63 @@
+use crate::{BimElement, BimModel, ParameterValue};
+
+/// Broad kind of construction material, recognized from a material name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MaterialClass {
+    Concrete,
+    Steel,
+    Timber,
+    Masonry,
+    Insulation,
+}
+
+impl MaterialClass {
+    /// Class of a material name such as `Concrete C30/37`, `S355` or `GL24h`; `None` when the
+    /// name is not recognized.
+    pub fn of(material: &str) -> Option<Self> {
+        let name = material.trim().to_ascii_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|word| name.contains(word));
+        // Strength classes: a prefix followed by a digit.
+        let class = |prefix: &str| {
+            name.strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        };
+        if mentions(&["concrete", "beton"]) || (class("c") && name.contains('/')) {
+            Some(MaterialClass::Concrete)
+        } else if mentions(&["steel", "stahl"]) || class("s") {
+            Some(MaterialClass::Steel)
+        } else if mentions(&["timber", "wood", "glulam"]) || class("gl") || class("c") {
+            Some(MaterialClass::Timber)
+        } else if mentions(&["brick", "masonry", "block"]) {
+            Some(MaterialClass::Masonry)
+        } else if mentions(&["insulation", "mineral wool"]) {
+            Some(MaterialClass::Insulation)
+        } else {
+            None
+        }
+    }
+
+    /// Typical density in kg/m³: reinforced concrete, structural steel, softwood, clay
+    /// brickwork and mineral wool.
+    pub fn density(self) -> f64 {
+        match self {
+            MaterialClass::Concrete => 2500.0,
+            MaterialClass::Steel => 7850.0,
+            MaterialClass::Timber => 500.0,
+            MaterialClass::Masonry => 1800.0,
+            MaterialClass::Insulation => 50.0,
+        }
+    }
+}
+
+impl BimModel {
+    /// Material of `element`: its `Material` parameter, else its type's material.
+    pub fn element_material<'a>(&'a self, element: &'a BimElement) -> Option<&'a str> {
+        match element.parameters.get("Material") {
+            Some(ParameterValue::Text(material)) => Some(material),
+            _ => element
+                .type_id
+                .and_then(|guid| self.element_type(guid))
+                .and_then(|element_type| element_type.material.as_deref()),
+        }
+    }
+}
@@ -0,0 +1,654 @@
+use cryxtal_base::{Error, Guid, Result, Units};
+use cryxtal_topology::{Matrix4, Point3, Vector3, solid_bounds, transform_solid};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+use crate::revision::{ChangeSource, ParameterLogEntry, Removal};
+use crate::{
+    BimCategory, BimElement, BimElementType, CoordinateTransform, DirtyFlags, ElementGroup, Grid,
+    Issue, ModelCommand, NamedView, Opening, ParameterSet, ParameterValue, Phase,
+    ReferenceGeometry, cutting_openings, embedded_members, hosted_openings, opening_host,
+};
+
+/// How far [`BimModel::cut_hosts`] shrinks an opening's box, so hosts that merely touch it,
+/// like the slab under a door, are left out.
+const CUT_INSET: f64 = 1.0e-6;
+
+/// A named presentation layer; elements refer to it through their `Layer` parameter.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Layer {
+    pub name: String,
+    /// Unmultiplied sRGBA.
+    pub color: [u8; 4],
+}
+
+impl Layer {
+    pub fn new(name: impl Into<String>, color: [u8; 4]) -> Self {
+        Self {
+            name: name.into(),
+            color,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Storey {
+    pub guid: Guid,
+    pub name: String,
+    pub elevation: f64,
+    elements: Vec<Guid>,
+}
+
+impl Storey {
+    pub fn new(name: impl Into<String>, elevation: f64) -> Self {
+        Self {
+            guid: Guid::new(),
+            name: name.into(),
+            elevation,
+            elements: Vec::new(),
+        }
+    }
+
+    /// Guids of the elements contained in this storey.
+    pub fn elements(&self) -> &[Guid] {
+        &self.elements
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Building {
+    pub guid: Guid,
+    pub name: String,
+    pub storeys: Vec<Storey>,
+}
+
+impl Building {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            guid: Guid::new(),
+            name: name.into(),
+            storeys: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Site {
+    pub guid: Guid,
+    pub name: String,
+    pub buildings: Vec<Building>,
+    /// Placement of the model in the shared coordinates of the federated project it belongs
+    /// to; exports can write the model there (see [`BimModel::in_shared_coordinates`]).
+    #[serde(default)]
+    pub shared_coordinates: CoordinateTransform,
+}
+
+impl Site {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            guid: Guid::new(),
+            name: name.into(),
+            buildings: Vec::new(),
+            shared_coordinates: CoordinateTransform::default(),
+        }
+    }
+}
+
+/// The project document: elements, layers, units, the Site → Building → Storey structure
+/// every element is contained in, the element types instances share, the groups elements are
+/// handled in, and the reference geometry used to set them out.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BimModel {
+    pub name: String,
+    pub units: Units,
+    pub layers: Vec<Layer>,
+    pub site: Site,
+    #[serde(default)]
+    pub references: Vec<ReferenceGeometry>,
+    #[serde(default)]
+    pub types: Vec<BimElementType>,
+    #[serde(default)]
+    pub groups: Vec<ElementGroup>,
+    /// Named views with the filters that recolor elements in them.
+    #[serde(default)]
+    pub views: Vec<NamedView>,
+    /// Review issues raised on the model.
+    #[serde(default)]
+    pub issues: Vec<Issue>,
+    /// Construction phases in build order; see [`BimModel::phase_status`].
+    #[serde(default)]
+    pub phases: Vec<Phase>,
+    /// Plan grids for setting out; storeys are the model's levels.
+    #[serde(default)]
+    pub grids: Vec<Grid>,
+    /// Name stamped on the elements edited through [`ModelCommand`]s;
+    /// set by the application for each session and not saved.
+    #[serde(skip)]
+    pub author: String,
+    /// Where the edits of this session come from, logged with each parameter change; set by the
+    /// application and not saved.
+    #[serde(skip)]
+    pub source: ChangeSource,
+    /// See [`BimModel::revision`].
+    #[serde(default)]
+    pub(crate) revision: u64,
+    #[serde(default)]
+    pub(crate) removals: Vec<Removal>,
+    /// See [`BimModel::parameter_log`].
+    #[serde(default)]
+    pub(crate) parameter_log: Vec<ParameterLogEntry>,
+    elements: Vec<BimElement>,
+    /// Changes since [`BimModel::take_dirty`] was last called.
+    #[serde(skip)]
+    dirty: HashMap<Guid, DirtyFlags>,
+}
+
+impl Default for BimModel {
+    fn default() -> Self {
+        Self::new("Untitled")
+    }
+}
+
+impl BimModel {
+    /// Creates a model with one building, a ground storey at elevation 0 and a `Default` layer.
+    pub fn new(name: impl Into<String>) -> Self {
+        let mut building = Building::new("Building");
+        building.storeys.push(Storey::new("Level 0", 0.0));
+        let mut site = Site::new("Site");
+        site.buildings.push(building);
+        Self {
+            name: name.into(),
+            units: Units::default(),
+            layers: vec![Layer::new("Default", [180, 190, 200, 255])],
+            site,
+            references: Vec::new(),
+            types: Vec::new(),
+            groups: Vec::new(),
+            views: Vec::new(),
+            issues: Vec::new(),
+            phases: Vec::new(),
+            grids: Vec::new(),
+            author: String::new(),
+            source: ChangeSource::default(),
+            revision: 0,
+            removals: Vec::new(),
+            parameter_log: Vec::new(),
+            elements: Vec::new(),
+            dirty: HashMap::new(),
+        }
+    }
+
+    pub fn add_building(&mut self, name: impl Into<String>) -> Guid {
+        let building = Building::new(name);
+        let guid = building.guid;
+        self.site.buildings.push(building);
+        guid
+    }
+
+    pub fn add_storey(
+        &mut self,
+        building: Guid,
+        name: impl Into<String>,
+        elevation: f64,
+    ) -> Result<Guid> {
+        let Some(building) = self
+            .site
+            .buildings
+            .iter_mut()
+            .find(|candidate| candidate.guid == building)
+        else {
+            return Err(Error::InvalidParameter(format!(
+                "unknown building {building}"
+            )));
+        };
+        let storey = Storey::new(name, elevation);
+        let guid = storey.guid;
+        building.storeys.push(storey);
+        building
+            .storeys
+            .sort_by(|a, b| a.elevation.total_cmp(&b.elevation));
+        Ok(guid)
+    }
+
+    pub fn storeys(&self) -> impl Iterator<Item = &Storey> {
+        self.site
+            .buildings
+            .iter()
+            .flat_map(|building| building.storeys.iter())
+    }
+
+    pub fn storey(&self, guid: Guid) -> Option<&Storey> {
+        self.storeys().find(|storey| storey.guid == guid)
+    }
+
+    /// The lowest storey of the first building, used when no storey is chosen explicitly.
+    pub fn default_storey(&self) -> Option<Guid> {
+        self.storeys().next().map(|storey| storey.guid)
+    }
+
+    pub fn add_element(&mut self, element: BimElement, storey: Guid) -> Result<()> {
+        if self.element_index(element.guid).is_some() {
+            return Err(Error::InvalidParameter(format!(
+                "element {} is already in the model",
+                element.guid
+            )));
+        }
+        let Some(storey) = self.storey_mut(storey) else {
+            return Err(Error::InvalidParameter(format!("unknown storey {storey}")));
+        };
+        storey.elements.push(element.guid);
+        self.dirty.insert(element.guid, DirtyFlags::ALL);
+        self.elements.push(element);
+        Ok(())
+    }
+
+    /// Removes an element together with its [`BimModel::dependents`]; the element comes first
+    /// in the returned list.
+    pub fn remove_element(&mut self, guid: Guid) -> Vec<BimElement> {
+        let Some(index) = self.element_index(guid) else {
+            return Vec::new();
+        };
+        let dependents = self.dependents(guid);
+        let mut removed = vec![self.elements.remove(index)];
+        let (hosted, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.elements)
+            .into_iter()
+            .partition(|element| dependents.contains(&element.guid));
+        self.elements = kept;
+        removed.extend(hosted);
+        for storey in self.storeys_mut() {
+            storey
+                .elements
+                .retain(|member| removed.iter().all(|element| element.guid != *member));
+        }
+        for element in &removed {
+            self.dirty.remove(&element.guid);
+        }
+        removed
+    }
+
+    /// Elements that go when `guid` is removed: the openings it hosts and the elements
+    /// generated for it (see [`BimElement::removed_with_host`]), and theirs in turn.
+    pub fn dependents(&self, guid: Guid) -> Vec<Guid> {
+        let mut order = vec![guid];
+        let mut queue = VecDeque::from([guid]);
+        while let Some(parent) = queue.pop_front() {
+            for element in &self.elements {
+                let host = element
+                    .opening
+                    .map(|opening| opening.host)
+                    .or(element.host.filter(|_| element.removed_with_host));
+                if host == Some(parent) && !order.contains(&element.guid) {
+                    order.push(element.guid);
+                    queue.push_back(element.guid);
+                }
+            }
+        }
+        order.remove(0);
+        order
+    }
+
+    pub fn clear_elements(&mut self) {
+        self.elements.clear();
+        self.dirty.clear();
+        for storey in self.storeys_mut() {
+            storey.elements.clear();
+        }
+    }
+
+    pub fn move_to_storey(&mut self, element: Guid, storey: Guid) -> Result<()> {
+        if self.element_index(element).is_none() {
+            return Err(Error::InvalidParameter(format!(
+                "unknown element {element}"
+            )));
+        }
+        if self.storey(storey).is_none() {
+            return Err(Error::InvalidParameter(format!("unknown storey {storey}")));
+        }
+        for candidate in self.storeys_mut() {
+            candidate.elements.retain(|member| *member != element);
+            if candidate.guid == storey {
+                candidate.elements.push(element);
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes `element` off whichever storey holds it.
+    pub(crate) fn leave_storeys(&mut self, element: Guid) {
+        for storey in self.storeys_mut() {
+            storey.elements.retain(|member| *member != element);
+        }
+    }
+
+    pub fn elements(&self) -> &[BimElement] {
+        &self.elements
+    }
+
+    /// Mutable access for editing elements in place; adding and removing goes through the model
+    /// so storey containment stays in sync. In-place edits are not tracked as dirty; see
+    /// [`BimModel::mark_dirty`].
+    pub fn elements_mut(&mut self) -> &mut [BimElement] {
+        &mut self.elements
+    }
+
+    /// Records a change to element `guid`; flags accumulate until they are taken.
+    pub fn mark_dirty(&mut self, guid: Guid, flags: DirtyFlags) {
+        if !flags.is_clean() {
+            self.dirty.entry(guid).or_default().merge(flags);
+        }
+    }
+
+    /// Elements added or changed since the last call, with what changed about them. Removed
+    /// elements are not listed. Consumers such as the viewer use this to redo only the work
+    /// the changes call for.
+    pub fn take_dirty(&mut self) -> HashMap<Guid, DirtyFlags> {
+        std::mem::take(&mut self.dirty)
+    }
+
+    pub fn element(&self, guid: Guid) -> Option<&BimElement> {
+        self.elements.iter().find(|element| element.guid == guid)
+    }
+
+    pub fn element_index(&self, guid: Guid) -> Option<usize> {
+        self.elements
+            .iter()
+            .position(|element| element.guid == guid)
+    }
+
+    pub fn elements_on_storey(&self, storey: Guid) -> Vec<&BimElement> {
+        let Some(storey) = self.storey(storey) else {
+            return Vec::new();
+        };
+        storey
+            .elements
+            .iter()
+            .filter_map(|guid| self.element(*guid))
+            .collect()
+    }
+
+    pub fn storey_of(&self, element: Guid) -> Option<&Storey> {
+        self.storeys()
+            .find(|storey| storey.elements.contains(&element))
+    }
+
+    pub fn hosted_openings(&self, host: Guid) -> Vec<(usize, Opening)> {
+        hosted_openings(&self.elements, host)
+    }
+
+    pub fn cutting_openings(&self, host: Guid) -> Vec<usize> {
+        cutting_openings(&self.elements, host)
+    }
+
+    pub fn embedded_members(&self, host: Guid) -> Vec<usize> {
+        embedded_members(&self.elements, host)
+    }
+
+    /// Walls and slabs other than its own host that `opening` passes through, found by
+    /// overlapping bounding boxes. Storing them in [`BimElement::cuts`] has them cut as well.
+    pub fn cut_hosts(&self, opening: &BimElement) -> Vec<Guid> {
+        let Some((min, max)) = solid_bounds(opening.geometry()) else {
+            return Vec::new();
+        };
+        let own_host = opening.opening.map(|opening| opening.host);
+        let inset = Vector3::new(CUT_INSET, CUT_INSET, CUT_INSET);
+        self.query()
+            .categories(&[BimCategory::Wall, BimCategory::Slab])
+            .intersecting(min + inset, max - inset)
+            .map(|host| host.guid)
+            .filter(|guid| Some(*guid) != own_host && *guid != opening.guid)
+            .collect()
+    }
+
+    pub fn opening_host(&self, opening: &Opening) -> Option<&BimElement> {
+        opening_host(&self.elements, opening).map(|index| &self.elements[index])
+    }
+
+    /// Elements placed in `host` through [`BimElement::host`]; openings are not included.
+    pub fn hosted_elements(&self, host: Guid) -> Vec<&BimElement> {
+        self.elements
+            .iter()
+            .filter(|element| element.host == Some(host))
+            .collect()
+    }
+
+    pub fn element_type(&self, guid: Guid) -> Option<&BimElementType> {
+        self.types
+            .iter()
+            .find(|element_type| element_type.guid == guid)
+    }
+
+    pub fn types_for(&self, category: BimCategory) -> Vec<&BimElementType> {
+        self.types
+            .iter()
+            .filter(|element_type| element_type.category == category)
+            .collect()
+    }
+
+    /// Elements that are instances of the type `guid`.
+    pub fn instances_of(&self, guid: Guid) -> Vec<&BimElement> {
+        self.elements
+            .iter()
+            .filter(|element| element.type_id == Some(guid))
+            .collect()
+    }
+
+    /// Elements whose host is no longer part of the model.
+    pub fn orphans(&self) -> Vec<&BimElement> {
+        self.elements
+            .iter()
+            .filter(|element| {
+                let host = element.opening.map(|opening| opening.host).or(element.host);
+                host.is_some_and(|host| self.element(host).is_none())
+            })
+            .collect()
+    }
+
+    /// Copies of element `guid` and everything hanging off it — its openings, the elements it
+    /// hosts, and theirs in turn — mapped by `transform`, with new guids and the host links
+    /// rewired among the copies. The copy of `guid` comes first; nothing is added to the model.
+    ///
+    /// Geometry and point parameters (`{Name}X/Y/Z` number triples such as `StartX`) are
+    /// transformed; wall openings keep their host-local placement, slab openings have their plan
+    /// offset turned with the slab. A mirroring transform swaps `Left`/`Right` rebar faces, since
+    /// it swaps the sides of the wall. `transform` should preserve distances and keep Z up, as
+    /// parametric hosts are not rescaled or tilted.
+    pub fn deep_clone(&self, guid: Guid, transform: Matrix4) -> Result<Vec<BimElement>> {
+        if self.element(guid).is_none() {
+            return Err(Error::InvalidParameter(format!("unknown element {guid}")));
+        }
+        let order = self.hosted_tree(&[guid]);
+        let guids: HashMap<Guid, Guid> = order.iter().map(|old| (*old, Guid::new())).collect();
+        Ok(self.map_elements(&order, &[guid], &guids, &transform))
+    }
+
+    /// Command adding copies of the elements `guids`, with everything hanging off them, moved
+    /// by `offset` onto the storeys the originals are on. Applied, it adds them all or none,
+    /// and its inverse removes them again; the copies are its [`ModelCommand::targets`].
+    ///
+    /// Copies get new guids, and opening hosts, hosted elements and cuts within the copied set
+    /// are remapped to them as in [`BimModel::deep_clone`]. An element listed alongside its host
+    /// is copied once, with the host.
+    pub fn duplicate(&self, guids: &[Guid], offset: Vector3) -> Result<ModelCommand> {
+        let mut roots = Vec::new();
+        for &guid in guids {
+            if self.element(guid).is_none() {
+                return Err(Error::InvalidParameter(format!("unknown element {guid}")));
+            }
+            if !roots.contains(&guid) && !self.has_ancestor_in(guid, guids) {
+                roots.push(guid);
+            }
+        }
+        let order = self.hosted_tree(&roots);
+        let new_guids: HashMap<Guid, Guid> = order.iter().map(|old| (*old, Guid::new())).collect();
+        let copies = self.map_elements(
+            &order,
+            &roots,
+            &new_guids,
+            &Matrix4::from_translation(offset),
+        );
+        let Some(fallback) = self.default_storey() else {
+            return Err(Error::InvalidParameter(
+                "model has no storey to place copies on".to_string(),
+            ));
+        };
+        let storeys: Vec<Guid> = order
+            .iter()
+            .map(|old| self.storey_of(*old).map_or(fallback, |storey| storey.guid))
+            .collect();
+        // Copies go in host-first order, one `AddElements` per run of copies on a storey.
+        let mut commands = Vec::new();
+        for (copy, storey) in copies.into_iter().zip(storeys) {
+            match commands.last_mut() {
+                Some(ModelCommand::AddElements {
+                    storey: last,
+                    elements,
+                }) if *last == storey => elements.push(copy),
+                _ => commands.push(ModelCommand::AddElements {
+                    storey,
+                    elements: vec![copy],
+                }),
+            }
+        }
+        Ok(ModelCommand::Batch(commands))
+    }
+
+    /// `roots` followed by everything hanging off them, breadth first.
+    pub(crate) fn hosted_tree(&self, roots: &[Guid]) -> Vec<Guid> {
+        let mut order = roots.to_vec();
+        let mut queue: VecDeque<Guid> = roots.iter().copied().collect();
+        while let Some(parent) = queue.pop_front() {
+            for element in &self.elements {
+                let host = element.opening.map(|opening| opening.host).or(element.host);
+                if host == Some(parent) && !order.contains(&element.guid) {
+                    order.push(element.guid);
+                    queue.push_back(element.guid);
+                }
+            }
+        }
+        order
+    }
+
+    /// Elements `order` mapped by `transform` and renamed through `guids`, which holds every
+    /// guid in `order`. Hosts of `roots` are kept; other host links only survive within the set.
+    pub(crate) fn map_elements(
+        &self,
+        order: &[Guid],
+        roots: &[Guid],
+        guids: &HashMap<Guid, Guid>,
+        transform: &Matrix4,
+    ) -> Vec<BimElement> {
+        let mirrored = is_mirroring(transform);
+        let mut copies = Vec::with_capacity(order.len());
+        for old in order {
+            let Some(source) = self.element(*old) else {
+                continue;
+            };
+            let mut copy = source.clone();
+            copy.guid = guids[old];
+            copy.geometry = transform_solid(&source.geometry, *transform);
+            transform_points(&mut copy.parameters, transform);
+            if let Some(opening) = copy.opening.as_mut() {
+                let host = self.element(opening.host);
+                if host.is_some_and(|host| host.category == BimCategory::Slab) {
+                    let offset = transform_vector(
+                        transform,
+                        Vector3::new(opening.placement.center_x, opening.placement.center_y, 0.0),
+                    );
+                    opening.placement.center_x = offset.x;
+                    opening.placement.center_y = offset.y;
+                }
+                opening.guid = copy.guid;
+                opening.host = guids.get(&opening.host).copied().unwrap_or(opening.host);
+            }
+            if !roots.contains(old) {
+                copy.host = copy.host.and_then(|host| guids.get(&host).copied());
+            }
+            // Other hosts the copy would cut are registered again where it lands.
+            copy.cuts.retain(|host| guids.contains_key(host));
+            for host in &mut copy.cuts {
+                *host = guids[host];
+            }
+            if mirrored && copy.host.is_some() {
+                swap_face(&mut copy.parameters);
+            }
+            copies.push(copy);
+        }
+        copies
+    }
+
+    fn storeys_mut(&mut self) -> impl Iterator<Item = &mut Storey> {
+        self.site
+            .buildings
+            .iter_mut()
+            .flat_map(|building| building.storeys.iter_mut())
+    }
+
+    pub(crate) fn storey_mut(&mut self, guid: Guid) -> Option<&mut Storey> {
+        self.storeys_mut().find(|storey| storey.guid == guid)
+    }
+}
+
+fn transform_point(transform: &Matrix4, point: Point3) -> Point3 {
+    let moved = transform_vector(transform, Vector3::new(point.x, point.y, point.z));
+    Point3::new(
+        moved.x + transform.w.x,
+        moved.y + transform.w.y,
+        moved.z + transform.w.z,
+    )
+}
+
+pub(crate) fn transform_vector(transform: &Matrix4, vector: Vector3) -> Vector3 {
+    let (x, y, z) = (transform.x, transform.y, transform.z);
+    Vector3::new(
+        x.x * vector.x + y.x * vector.y + z.x * vector.z,
+        x.y * vector.x + y.y * vector.y + z.y * vector.z,
+        x.z * vector.x + y.z * vector.y + z.z * vector.z,
+    )
+}
+
+/// Whether `transform` reverses handedness, i.e. its linear part has a negative determinant.
+fn is_mirroring(transform: &Matrix4) -> bool {
+    let (x, y, z) = (transform.x, transform.y, transform.z);
+    let determinant = x.x * (y.y * z.z - z.y * y.z) - y.x * (x.y * z.z - z.y * x.z)
+        + z.x * (x.y * y.z - y.y * x.z);
+    determinant < 0.0
+}
+
+/// Maps every `{Name}X/Y/Z` triple of number parameters as a point.
+pub(crate) fn transform_points(parameters: &mut ParameterSet, transform: &Matrix4) {
+    let stems: Vec<String> = parameters
+        .keys()
+        .filter_map(|key| key.strip_suffix('X'))
+        .map(str::to_string)
+        .collect();
+    for stem in stems {
+        let keys = [format!("{stem}X"), format!("{stem}Y"), format!("{stem}Z")];
+        let coordinate = |key: &String| match parameters.get(key) {
+            Some(ParameterValue::Number(value)) => Some(*value),
+            _ => None,
+        };
+        let (Some(x), Some(y), Some(z)) = (
+            coordinate(&keys[0]),
+            coordinate(&keys[1]),
+            coordinate(&keys[2]),
+        ) else {
+            continue;
+        };
+        let point = transform_point(transform, Point3::new(x, y, z));
+        for (key, value) in keys.into_iter().zip([point.x, point.y, point.z]) {
+            parameters.insert(key, ParameterValue::Number(value));
+        }
+    }
+}
+
+/// Swaps a `Left`/`Right` `Face` parameter, the wall side a hosted element sits on.
+fn swap_face(parameters: &mut ParameterSet) {
+    if let Some(ParameterValue::Text(face)) = parameters.get_mut("Face") {
+        let swapped = match face.as_str() {
+            "Left" => "Right",
+            "Right" => "Left",
+            _ => return,
+        };
+        *face = swapped.to_string();
+    }
+}
@@ -0,0 +1,90 @@
+use cryxtal_base::Guid;
+use serde::{Deserialize, Serialize};
+
+use crate::{BimCategory, BimElement};
+
+/// Rectangular cut-out size in the host's elevation plane.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OpeningProfile {
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Opening center in host-local coordinates: `center_x` runs along the host axis from its
+/// start point and `center_z` up from its base. Slab openings are plan offsets from the first
+/// outline point instead (`center_x` along X, `center_y` along Y), with the profile's `height`
+/// measured along Y. The coordinate a host does not use is zero.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OpeningPlacement {
+    pub center_x: f64,
+    #[serde(default)]
+    pub center_y: f64,
+    pub center_z: f64,
+}
+
+/// A void cut into a host element. Hosts keep no list of their openings; the relationship is
+/// resolved from the opening elements that point at them.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Opening {
+    pub guid: Guid,
+    pub host: Guid,
+    pub profile: OpeningProfile,
+    pub placement: OpeningPlacement,
+}
+
+impl Opening {
+    pub fn new(host: Guid, profile: OpeningProfile, placement: OpeningPlacement) -> Self {
+        Self {
+            guid: Guid::new(),
+            host,
+            profile,
+            placement,
+        }
+    }
+}
+
+/// Index of the element hosting `opening`, if it is still part of `elements`.
+pub fn opening_host(elements: &[BimElement], opening: &Opening) -> Option<usize> {
+    elements.iter().position(|element| {
+        element.guid == opening.host && element.category != BimCategory::Opening
+    })
+}
+
+/// Indices of the openings hosted elsewhere that also cut through `host` (see
+/// [`BimElement::cuts`]).
+pub fn cutting_openings(elements: &[BimElement], host: Guid) -> Vec<usize> {
+    elements
+        .iter()
+        .enumerate()
+        .filter(|(_, element)| {
+            element.cuts.contains(&host)
+                && element.opening.is_some_and(|opening| opening.host != host)
+        })
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Indices of the elements other than openings set into `host`, such as the lintels over its
+/// openings, whose extent is cut from it along with the openings (see [`BimElement::cuts`]).
+pub fn embedded_members(elements: &[BimElement], host: Guid) -> Vec<usize> {
+    elements
+        .iter()
+        .enumerate()
+        .filter(|(_, element)| element.opening.is_none() && element.cuts.contains(&host))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Openings cut into `host`, paired with the index of the element that carries each one.
+pub fn hosted_openings(elements: &[BimElement], host: Guid) -> Vec<(usize, Opening)> {
+    elements
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, element)| {
+            element
+                .opening
+                .filter(|opening| opening.host == host)
+                .map(|opening| (idx, opening))
+        })
+        .collect()
+}
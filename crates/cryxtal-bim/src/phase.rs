@@ -0,0 +1,76 @@
+use cryxtal_base::Guid;
+use serde::{Deserialize, Serialize};
+
+use crate::{BimElement, BimModel};
+
+/// A construction phase, such as "Existing" or "New Construction". [`BimModel::phases`] lists
+/// them in the order they are built.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Phase {
+    pub guid: Guid,
+    pub name: String,
+}
+
+impl Phase {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            guid: Guid::new(),
+            name: name.into(),
+        }
+    }
+}
+
+/// How an element stands in a phase; see [`BimModel::phase_status`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhaseStatus {
+    /// Built in an earlier phase and still standing.
+    Existing,
+    /// Built in this phase.
+    New,
+    /// Torn down in this phase.
+    Demolished,
+}
+
+impl PhaseStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            PhaseStatus::Existing => "Existing",
+            PhaseStatus::New => "New",
+            PhaseStatus::Demolished => "Demolished",
+        }
+    }
+}
+
+impl BimModel {
+    pub fn phase(&self, guid: Guid) -> Option<&Phase> {
+        self.phases.iter().find(|phase| phase.guid == guid)
+    }
+
+    /// How `element` stands in `phase`, or `None` when it is not built yet or already gone.
+    /// Elements without a created phase predate the first phase; a created or demolished phase
+    /// missing from the model counts as unset. Every element is existing in an unknown phase.
+    pub fn phase_status(&self, element: &BimElement, phase: Guid) -> Option<PhaseStatus> {
+        let Some(current) = self.phase_index(Some(phase)) else {
+            return Some(PhaseStatus::Existing);
+        };
+        let created = self.phase_index(element.phase_created);
+        let demolished = self.phase_index(element.phase_demolished);
+        if created.is_some_and(|created| created > current)
+            || demolished.is_some_and(|demolished| demolished < current)
+        {
+            return None;
+        }
+        Some(if demolished == Some(current) {
+            PhaseStatus::Demolished
+        } else if created == Some(current) {
+            PhaseStatus::New
+        } else {
+            PhaseStatus::Existing
+        })
+    }
+
+    fn phase_index(&self, guid: Option<Guid>) -> Option<usize> {
+        let guid = guid?;
+        self.phases.iter().position(|phase| phase.guid == guid)
+    }
+}
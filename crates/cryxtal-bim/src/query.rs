@@ -0,0 +1,156 @@
+use cryxtal_base::Guid;
+use cryxtal_topology::{Point3, solid_bounds};
+
+use crate::{BimCategory, BimElement, BimModel, ParameterValue, PhaseStatus};
+
+type Filter<'a> = Box<dyn Fn(&BimElement) -> bool + 'a>;
+
+/// Elements of a [`BimModel`] matching every filter added to it, in model order; see
+/// [`BimModel::query`].
+pub struct ElementQuery<'a> {
+    model: &'a BimModel,
+    elements: std::iter::Enumerate<std::slice::Iter<'a, BimElement>>,
+    filters: Vec<Filter<'a>>,
+}
+
+impl BimModel {
+    /// Starts a query over the model's elements:
+    /// `model.query().category(BimCategory::Wall).where_num("Height", |h| h > 3000.0)`.
+    pub fn query(&self) -> ElementQuery<'_> {
+        ElementQuery {
+            model: self,
+            elements: self.elements().iter().enumerate(),
+            filters: Vec::new(),
+        }
+    }
+}
+
+impl<'a> ElementQuery<'a> {
+    pub fn category(self, category: BimCategory) -> Self {
+        self.matching(move |element| element.category == category)
+    }
+
+    /// Elements in any of `categories`.
+    pub fn categories(self, categories: &'a [BimCategory]) -> Self {
+        self.matching(move |element| categories.contains(&element.category))
+    }
+
+    /// Elements on layer `name`; elements without a `Layer` parameter are on the model's
+    /// first layer.
+    pub fn layer(self, name: &'a str) -> Self {
+        let model = self.model;
+        let default = model.layers.first().map(|layer| layer.name.as_str());
+        self.matching(move |element| match element.parameters.get("Layer") {
+            Some(ParameterValue::Text(layer)) => layer == name,
+            _ => default == Some(name),
+        })
+    }
+
+    /// Elements with a parameter `key`, of any type.
+    pub fn with_parameter(self, key: &'a str) -> Self {
+        self.matching(move |element| element.parameters.contains_key(key))
+    }
+
+    /// Elements whose number (or integer) parameter `key` satisfies `predicate`.
+    pub fn where_num(self, key: &'a str, predicate: impl Fn(f64) -> bool + 'a) -> Self {
+        self.matching(move |element| match element.parameters.get(key) {
+            Some(ParameterValue::Number(value)) => predicate(*value),
+            Some(ParameterValue::Integer(value)) => predicate(*value as f64),
+            _ => false,
+        })
+    }
+
+    /// Elements whose text parameter `key` satisfies `predicate`.
+    pub fn where_text(self, key: &'a str, predicate: impl Fn(&str) -> bool + 'a) -> Self {
+        self.matching(move |element| match element.parameters.get(key) {
+            Some(ParameterValue::Text(value)) => predicate(value),
+            _ => false,
+        })
+    }
+
+    /// Elements whose name, guid or a parameter value contains `text`, ignoring case.
+    pub fn text(self, text: &str) -> Self {
+        let needle = text.trim().to_lowercase();
+        self.matching(move |element| {
+            let contains = |value: &str| value.to_lowercase().contains(&needle);
+            contains(&element.name)
+                || contains(&element.guid.to_string())
+                || element.parameters.values().any(|value| match value {
+                    ParameterValue::Text(value) => contains(value),
+                    ParameterValue::Number(value) => contains(&value.to_string()),
+                    ParameterValue::Integer(value) => contains(&value.to_string()),
+                    ParameterValue::Bool(value) => contains(&value.to_string()),
+                })
+        })
+    }
+
+    /// Elements standing in `phase`: built in it or earlier and not torn down before it.
+    pub fn phase(self, phase: Guid) -> Self {
+        let model = self.model;
+        self.matching(move |element| model.phase_status(element, phase).is_some())
+    }
+
+    /// Elements with `status` in `phase`, such as the ones demolished in it.
+    pub fn phase_status(self, phase: Guid, status: PhaseStatus) -> Self {
+        let model = self.model;
+        self.matching(move |element| model.phase_status(element, phase) == Some(status))
+    }
+
+    /// Elements whose bounding box overlaps the box from `min` to `max`.
+    pub fn intersecting(self, min: Point3, max: Point3) -> Self {
+        self.matching(move |element| {
+            solid_bounds(element.geometry()).is_some_and(|(low, high)| {
+                low.x <= max.x
+                    && high.x >= min.x
+                    && low.y <= max.y
+                    && high.y >= min.y
+                    && low.z <= max.z
+                    && high.z >= min.z
+            })
+        })
+    }
+
+    /// Elements whose bounding box lies inside the box from `min` to `max`.
+    pub fn inside(self, min: Point3, max: Point3) -> Self {
+        self.matching(move |element| {
+            solid_bounds(element.geometry()).is_some_and(|(low, high)| {
+                low.x >= min.x
+                    && high.x <= max.x
+                    && low.y >= min.y
+                    && high.y <= max.y
+                    && low.z >= min.z
+                    && high.z <= max.z
+            })
+        })
+    }
+
+    /// Elements satisfying `predicate`.
+    pub fn matching(mut self, predicate: impl Fn(&BimElement) -> bool + 'a) -> Self {
+        self.filters.push(Box::new(predicate));
+        self
+    }
+
+    /// Guids of the matching elements.
+    pub fn guids(self) -> Vec<Guid> {
+        self.map(|element| element.guid).collect()
+    }
+
+    /// Positions of the matching elements in [`BimModel::elements`].
+    pub fn indices(mut self) -> Vec<usize> {
+        std::iter::from_fn(|| self.next_match().map(|(idx, _)| idx)).collect()
+    }
+
+    fn next_match(&mut self) -> Option<(usize, &'a BimElement)> {
+        let filters = &self.filters;
+        self.elements
+            .find(|(_, element)| filters.iter().all(|filter| filter(element)))
+    }
+}
+
+impl<'a> Iterator for ElementQuery<'a> {
+    type Item = &'a BimElement;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_match().map(|(_, element)| element)
+    }
+}
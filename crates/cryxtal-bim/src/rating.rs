@@ -0,0 +1,112 @@
+use cryxtal_base::Guid;
+use std::fmt;
+
+use crate::{BimElement, BimModel, ParameterValue};
+
+/// Parameter holding the fire resistance class of a wall or opening, such as `EI60` or
+/// `REI 90`; its minutes are what counts. On a wall it is also what its openings must reach.
+pub const FIRE_RATING_KEY: &str = "FireRating";
+/// Parameter holding the sound reduction index `Rw` of a wall or opening, in dB. On a wall it
+/// is also what its openings must reach.
+pub const ACOUSTIC_RATING_KEY: &str = "AcousticRating";
+
+/// Performance rating walls and openings carry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rating {
+    Fire,
+    Acoustic,
+}
+
+impl Rating {
+    pub const ALL: [Rating; 2] = [Rating::Fire, Rating::Acoustic];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Rating::Fire => FIRE_RATING_KEY,
+            Rating::Acoustic => ACOUSTIC_RATING_KEY,
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            Rating::Fire => "min",
+            Rating::Acoustic => "dB",
+        }
+    }
+
+    /// Rating of `element` in [`Rating::unit`]: a number, or the number a class such as `EI60`
+    /// or `Rw 45` ends in. `None` for text that does not end in one.
+    pub fn of(self, element: &BimElement) -> Option<f64> {
+        match element.parameters.get(self.key())? {
+            ParameterValue::Integer(value) => Some(*value as f64),
+            ParameterValue::Number(value) => Some(*value),
+            ParameterValue::Text(class) => class
+                .trim()
+                .trim_start_matches(|c: char| c.is_ascii_alphabetic() || c.is_whitespace())
+                .parse()
+                .ok(),
+            ParameterValue::Bool(_) => None,
+        }
+    }
+}
+
+/// Opening rated lower than its host wall requires, found by [`BimModel::rating_shortfalls`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RatingShortfall {
+    pub opening: Guid,
+    pub host: Guid,
+    pub rating: Rating,
+    pub required: f64,
+    /// `None` when the opening carries no rating at all.
+    pub actual: Option<f64>,
+}
+
+impl fmt::Display for RatingShortfall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (key, unit) = (self.rating.key(), self.rating.unit());
+        match self.actual {
+            Some(actual) => write!(
+                f,
+                "{key} {actual} {unit} is below the {} {unit} its host requires",
+                self.required
+            ),
+            None => write!(
+                f,
+                "{key} is missing; its host requires {} {unit}",
+                self.required
+            ),
+        }
+    }
+}
+
+impl BimModel {
+    /// Openings whose fire or acoustic rating is lower than the one set on their host, or
+    /// missing where the host sets one, in element order.
+    pub fn rating_shortfalls(&self) -> Vec<RatingShortfall> {
+        let mut shortfalls = Vec::new();
+        for element in self.elements() {
+            let Some(opening) = element.opening else {
+                continue;
+            };
+            let Some(host) = self.element(opening.host) else {
+                continue;
+            };
+            for rating in Rating::ALL {
+                let Some(required) = rating.of(host) else {
+                    continue;
+                };
+                let actual = rating.of(element);
+                if actual.is_none_or(|actual| actual < required) {
+                    shortfalls.push(RatingShortfall {
+                        opening: element.guid,
+                        host: host.guid,
+                        rating,
+                        required,
+                        actual,
+                    });
+                }
+            }
+        }
+        shortfalls
+    }
+}
@@ -0,0 +1,85 @@
+use cryxtal_base::{Error, Guid, Result};
+use serde::{Deserialize, Serialize};
+
+/// Parameter marking an element as an imported reference mesh, such as a surveyor's terrain;
+/// see [`BimElement::is_reference_mesh`](crate::BimElement::is_reference_mesh).
+pub const REFERENCE_MESH_PARAMETER: &str = "ReferenceMesh";
+
+/// Shape of a reference object. Lines and planes are unbounded.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReferenceKind {
+    Point {
+        position: [f64; 3],
+    },
+    /// Unit `direction`.
+    Line {
+        origin: [f64; 3],
+        direction: [f64; 3],
+    },
+    /// Unit `normal`.
+    Plane {
+        origin: [f64; 3],
+        normal: [f64; 3],
+    },
+}
+
+/// Construction geometry used to set out a design: it takes part in snapping but is never
+/// exported and never part of an element.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReferenceGeometry {
+    pub guid: Guid,
+    pub name: String,
+    pub kind: ReferenceKind,
+}
+
+impl ReferenceGeometry {
+    pub fn point(name: impl Into<String>, position: [f64; 3]) -> Self {
+        Self::new(name, ReferenceKind::Point { position })
+    }
+
+    /// Line through two distinct points.
+    pub fn line(name: impl Into<String>, start: [f64; 3], through: [f64; 3]) -> Result<Self> {
+        let direction = unit(sub(through, start))
+            .ok_or_else(|| Error::InvalidParameter("line points coincide".to_string()))?;
+        Ok(Self::new(
+            name,
+            ReferenceKind::Line {
+                origin: start,
+                direction,
+            },
+        ))
+    }
+
+    /// Plane through three points that are not collinear.
+    pub fn plane(name: impl Into<String>, points: [[f64; 3]; 3]) -> Result<Self> {
+        let [a, b, c] = points;
+        let normal = unit(cross(sub(b, a), sub(c, a)))
+            .ok_or_else(|| Error::InvalidParameter("plane points are collinear".to_string()))?;
+        Ok(Self::new(name, ReferenceKind::Plane { origin: a, normal }))
+    }
+
+    fn new(name: impl Into<String>, kind: ReferenceKind) -> Self {
+        Self {
+            guid: Guid::new(),
+            name: name.into(),
+            kind,
+        }
+    }
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn unit(v: [f64; 3]) -> Option<[f64; 3]> {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    (length > 1.0e-9).then(|| [v[0] / length, v[1] / length, v[2] / length])
+}
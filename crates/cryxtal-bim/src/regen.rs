@@ -0,0 +1,149 @@
+use cryxtal_topology::{same_geometry, sample_edge, solid_edges};
+
+use crate::{BimElement, ParameterValue};
+
+/// Parameters that only affect presentation, left out of [`BimElement::parameter_hash`].
+const PRESENTATION_KEYS: [&str; 2] = ["Layer", "Material"];
+
+/// Segments each edge is sampled with by [`BimElement::geometry_hash`], so an arc that bulges
+/// differently between the same vertices changes the hash.
+const HASH_EDGE_SEGMENTS: usize = 4;
+
+/// What changed about an element since the model's dirty flags were last taken with
+/// [`BimModel::take_dirty`](crate::BimModel::take_dirty). Added elements are dirty in both.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DirtyFlags {
+    /// Inputs the geometry is built from, as covered by [`BimElement::parameter_hash`].
+    pub parameters: bool,
+    /// The solid itself; meshes of elements without this flag are still valid.
+    pub geometry: bool,
+}
+
+impl DirtyFlags {
+    pub const ALL: DirtyFlags = DirtyFlags {
+        parameters: true,
+        geometry: true,
+    };
+
+    /// Flags for `before` being replaced by `after`.
+    pub fn between(before: &BimElement, after: &BimElement) -> Self {
+        Self {
+            parameters: before.parameter_hash() != after.parameter_hash(),
+            geometry: !same_geometry(&before.geometry, &after.geometry),
+        }
+    }
+
+    pub fn is_clean(self) -> bool {
+        !self.parameters && !self.geometry
+    }
+
+    pub fn merge(&mut self, other: DirtyFlags) {
+        self.parameters |= other.parameters;
+        self.geometry |= other.geometry;
+    }
+}
+
+impl BimElement {
+    /// Fingerprint (FNV-1a) of what the element's geometry is built from: its category, its
+    /// parameters apart from presentation ones such as `Layer`, and its opening. Equal hashes
+    /// mean regenerating would rebuild the same solid.
+    pub fn parameter_hash(&self) -> u64 {
+        let mut hash = Fnv::default();
+        hash.write(format!("{:?}", self.category).as_bytes());
+        for (key, value) in &self.parameters {
+            if PRESENTATION_KEYS.contains(&key.as_str()) {
+                continue;
+            }
+            hash.write(key.as_bytes());
+            match value {
+                ParameterValue::Integer(value) => {
+                    hash.write(&[0]);
+                    hash.write(&value.to_le_bytes());
+                }
+                ParameterValue::Number(value) => {
+                    hash.write(&[1]);
+                    hash.write(&value.to_bits().to_le_bytes());
+                }
+                ParameterValue::Bool(value) => hash.write(&[2, u8::from(*value)]),
+                ParameterValue::Text(value) => {
+                    hash.write(&[3]);
+                    hash.write(value.as_bytes());
+                }
+            }
+        }
+        if let Some(opening) = &self.opening {
+            let numbers = [
+                opening.profile.width,
+                opening.profile.height,
+                opening.placement.center_x,
+                opening.placement.center_y,
+                opening.placement.center_z,
+            ];
+            for number in numbers {
+                hash.write(&number.to_bits().to_le_bytes());
+            }
+        }
+        hash.finish()
+    }
+
+    /// Fingerprint (FNV-1a) of the element's solid from its vertex positions, points sampled
+    /// along each edge, and its face and edge counts. Unlike [`same_geometry`], it is equal for
+    /// identical solids built or loaded separately, so it compares geometry across models.
+    pub fn geometry_hash(&self) -> u64 {
+        let mut hash = Fnv::default();
+        let faces = self.geometry.face_iter().count() as u64;
+        let edges = solid_edges(&self.geometry);
+        hash.write(&faces.to_le_bytes());
+        hash.write(&(edges.len() as u64).to_le_bytes());
+        let vertices = self.geometry.vertex_iter().map(|vertex| vertex.point());
+        let samples = edges
+            .iter()
+            .flat_map(|edge| sample_edge(edge, HASH_EDGE_SEGMENTS));
+        for point in vertices.chain(samples) {
+            for coordinate in [point.x, point.y, point.z] {
+                hash.write(&coordinate.to_bits().to_le_bytes());
+            }
+        }
+        hash.finish()
+    }
+
+    /// Hash of the element's own inputs combined with those of the elements it is built
+    /// around, such as a wall's openings; store it in [`BimElement::regen_hash`] after a rebuild.
+    pub fn regen_key<'a>(&self, dependencies: impl IntoIterator<Item = &'a BimElement>) -> u64 {
+        let mut hash = Fnv(self.parameter_hash());
+        for dependency in dependencies {
+            hash.write(&dependency.parameter_hash().to_le_bytes());
+        }
+        hash.finish()
+    }
+
+    /// Whether the geometry was built from exactly these inputs, so rebuilding can be skipped.
+    pub fn is_up_to_date<'a>(
+        &self,
+        dependencies: impl IntoIterator<Item = &'a BimElement>,
+    ) -> bool {
+        self.regen_hash == Some(self.regen_key(dependencies))
+    }
+}
+
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        // Separates consecutive fields, so "ab" + "c" and "a" + "bc" differ.
+        self.0 = (self.0 ^ 0xff).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use cryxtal_base::{Error, Guid, Result};
+
+use crate::formula::rename_formula_parameters;
+use crate::{BimElement, BimElementType, BimModel, ModelCommand, ParameterSet, ParameterValue};
+
+/// Elements and types carrying one parameter key of a mapping; see
+/// [`BimModel::rename_parameters`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenamedKey {
+    pub from: String,
+    pub to: String,
+    pub elements: usize,
+    pub types: usize,
+}
+
+/// Two different values landing on the same key. The value already under the target key is
+/// kept, else the one mapped first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenameConflict {
+    /// Element or type the values belong to.
+    pub guid: Guid,
+    pub name: String,
+    pub key: String,
+    pub kept: ParameterValue,
+    pub dropped: ParameterValue,
+}
+
+/// Preview of a parameter rename: what it renames and merges, and the edited elements and types
+/// to apply with [`ParameterRename::command`].
+#[derive(Clone, Debug, Default)]
+pub struct ParameterRename {
+    pub renamed: Vec<RenamedKey>,
+    pub conflicts: Vec<RenameConflict>,
+    elements: Vec<BimElement>,
+    types: Vec<BimElementType>,
+}
+
+impl ParameterRename {
+    /// Whether the mapping matches no parameter in the model.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty() && self.types.is_empty()
+    }
+
+    /// The undoable step applying the rename.
+    pub fn command(self) -> ModelCommand {
+        let mut commands = vec![ModelCommand::ReplaceElements {
+            elements: self.elements,
+        }];
+        commands.extend(self.types.into_iter().map(ModelCommand::ReplaceType));
+        ModelCommand::Batch(commands)
+    }
+}
+
+impl BimModel {
+    /// Every parameter key on the model's elements and types, with how many of them carry it.
+    pub fn parameter_keys(&self) -> BTreeMap<String, usize> {
+        let mut keys = BTreeMap::new();
+        let sets = self
+            .elements()
+            .iter()
+            .map(|element| &element.parameters)
+            .chain(
+                self.types
+                    .iter()
+                    .map(|element_type| &element_type.parameters),
+            );
+        for key in sets.flat_map(|parameters| parameters.keys()) {
+            *keys.entry(key.clone()).or_default() += 1;
+        }
+        keys
+    }
+
+    /// Renames parameter keys across every element and type by `mapping` (old key to new key),
+    /// as a preview to apply with [`ParameterRename::command`]. Keys are renamed all at once, so
+    /// `A → B, B → C` moves each value one step; keys mapped onto a key that is already there
+    /// merge into it. Formulas follow their parameters and the parameters they read.
+    pub fn rename_parameters(&self, mapping: &BTreeMap<String, String>) -> Result<ParameterRename> {
+        let mapping: BTreeMap<&str, &str> = mapping
+            .iter()
+            .map(|(from, to)| (from.trim(), to.trim()))
+            .filter(|(from, to)| from != to)
+            .collect();
+        if let Some((from, _)) = mapping
+            .iter()
+            .find(|(from, to)| from.is_empty() || to.is_empty())
+        {
+            return Err(Error::InvalidParameter(format!(
+                "parameter `{from}` needs an old and a new name"
+            )));
+        }
+        let mut rename = ParameterRename {
+            renamed: mapping
+                .iter()
+                .map(|(from, to)| RenamedKey {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    elements: 0,
+                    types: 0,
+                })
+                .collect(),
+            ..ParameterRename::default()
+        };
+        for element in self.elements() {
+            if !element
+                .parameters
+                .keys()
+                .any(|key| mapping.contains_key(key.as_str()))
+            {
+                continue;
+            }
+            let mut renamed = element.clone();
+            renamed.parameters =
+                rename_keys(&element.parameters, &mapping, &mut |key, kept, dropped| {
+                    rename.conflicts.push(RenameConflict {
+                        guid: element.guid,
+                        name: element.name.clone(),
+                        key: key.to_string(),
+                        kept: kept.clone(),
+                        dropped: dropped.clone(),
+                    })
+                });
+            renamed.formulas = BTreeMap::new();
+            for (key, expression) in &element.formulas {
+                let key = mapping.get(key.as_str()).copied().unwrap_or(key);
+                let expression = rename_formula_parameters(expression, &mapping)?;
+                renamed
+                    .formulas
+                    .entry(key.to_string())
+                    .or_insert(expression);
+            }
+            // Formulas that now read a merged-away value are reported by `evaluate`.
+            let _ = renamed.refresh_formulas(None);
+            count(&mut rename.renamed, &element.parameters, |row| {
+                row.elements += 1
+            });
+            rename.elements.push(renamed);
+        }
+        for element_type in &self.types {
+            if !element_type
+                .parameters
+                .keys()
+                .any(|key| mapping.contains_key(key.as_str()))
+            {
+                continue;
+            }
+            let mut renamed = element_type.clone();
+            renamed.parameters = rename_keys(
+                &element_type.parameters,
+                &mapping,
+                &mut |key, kept, dropped| {
+                    rename.conflicts.push(RenameConflict {
+                        guid: element_type.guid,
+                        name: element_type.name.clone(),
+                        key: key.to_string(),
+                        kept: kept.clone(),
+                        dropped: dropped.clone(),
+                    })
+                },
+            );
+            count(&mut rename.renamed, &element_type.parameters, |row| {
+                row.types += 1
+            });
+            rename.types.push(renamed);
+        }
+        Ok(rename)
+    }
+}
+
+/// `parameters` with their keys mapped. Unmapped keys are placed first so they win merges;
+/// `conflict` hears of every differing value that is dropped.
+fn rename_keys(
+    parameters: &ParameterSet,
+    mapping: &BTreeMap<&str, &str>,
+    conflict: &mut impl FnMut(&str, &ParameterValue, &ParameterValue),
+) -> ParameterSet {
+    let (mapped, unmapped): (Vec<_>, Vec<_>) = parameters
+        .iter()
+        .partition(|(key, _)| mapping.contains_key(key.as_str()));
+    let mut renamed = ParameterSet::new();
+    for (key, value) in unmapped.into_iter().chain(mapped) {
+        let key = mapping.get(key.as_str()).copied().unwrap_or(key);
+        match renamed.get(key) {
+            Some(kept) if kept != value => conflict(key, kept, value),
+            Some(_) => {}
+            None => {
+                renamed.insert(key.to_string(), value.clone());
+            }
+        }
+    }
+    renamed
+}
+
+fn count(rows: &mut [RenamedKey], parameters: &ParameterSet, add: impl Fn(&mut RenamedKey)) {
+    for row in rows {
+        if parameters.contains_key(&row.from) {
+            add(row);
+        }
+    }
+}
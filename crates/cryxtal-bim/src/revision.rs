@@ -0,0 +1,148 @@
+use cryxtal_base::Guid;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{BimElement, BimModel, ParameterSet, ParameterValue};
+
+/// When and by whom an element was last changed through a [`ModelCommand`](crate::ModelCommand).
+/// Elements that were never edited have revision 0.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElementRevision {
+    /// [`BimModel::revision`] of the edit.
+    pub revision: u64,
+    /// Seconds since the Unix epoch.
+    pub modified: u64,
+    pub author: String,
+}
+
+/// Where an edit was made, as recorded in the [`BimModel::parameter_log`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeSource {
+    #[default]
+    Gui,
+    Cli,
+    Script,
+}
+
+impl ChangeSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            ChangeSource::Gui => "GUI",
+            ChangeSource::Cli => "CLI",
+            ChangeSource::Script => "Script",
+        }
+    }
+}
+
+/// A parameter of an element added (`old` is `None`), removed (`new` is `None`) or changed by
+/// a [`ModelCommand`](crate::ModelCommand), including the values formulas took on as a result.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParameterLogEntry {
+    pub element: Guid,
+    pub name: String,
+    pub old: Option<ParameterValue>,
+    pub new: Option<ParameterValue>,
+    /// [`BimModel::revision`] of the edit.
+    pub revision: u64,
+    /// Seconds since the Unix epoch.
+    pub modified: u64,
+    pub author: String,
+    pub source: ChangeSource,
+}
+
+/// An element removed from the model, kept so [`BimModel::removed_since`] can report it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Removal {
+    pub(crate) guid: Guid,
+    pub(crate) revision: u64,
+}
+
+impl BimModel {
+    /// Number of commands applied to the model over its lifetime, saved with the project.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Elements added or changed after `revision`, in model order.
+    pub fn changed_since(&self, revision: u64) -> Vec<&BimElement> {
+        self.elements()
+            .iter()
+            .filter(|element| element.revision.revision > revision)
+            .collect()
+    }
+
+    /// Elements removed after `revision` and not added back since.
+    pub fn removed_since(&self, revision: u64) -> Vec<Guid> {
+        self.removals
+            .iter()
+            .filter(|removal| removal.revision > revision)
+            .map(|removal| removal.guid)
+            .collect()
+    }
+
+    /// Every parameter change made through [`ModelCommand`](crate::ModelCommand)s, oldest first
+    /// and saved with the project, for auditing who changed what. Undo and redo are logged as
+    /// the changes they make; entries of removed elements are kept.
+    pub fn parameter_log(&self) -> &[ParameterLogEntry] {
+        &self.parameter_log
+    }
+
+    /// The [`BimModel::parameter_log`] entries of one element, oldest first.
+    pub fn parameter_history(&self, guid: Guid) -> Vec<&ParameterLogEntry> {
+        self.parameter_log
+            .iter()
+            .filter(|entry| entry.element == guid)
+            .collect()
+    }
+
+    /// Logs how the parameters of `guid` differ from `before`, as changed in `revision`.
+    pub(crate) fn log_parameters(&mut self, guid: Guid, before: &ParameterSet, revision: u64) {
+        let Some(after) = self.element(guid).map(|element| &element.parameters) else {
+            return;
+        };
+        let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+        let modified = now();
+        let entries: Vec<ParameterLogEntry> = names
+            .into_iter()
+            .filter_map(|name| {
+                let (old, new) = (before.get(name), after.get(name));
+                (old != new).then(|| ParameterLogEntry {
+                    element: guid,
+                    name: name.clone(),
+                    old: old.cloned(),
+                    new: new.cloned(),
+                    revision,
+                    modified,
+                    author: self.author.clone(),
+                    source: self.source,
+                })
+            })
+            .collect();
+        self.parameter_log.extend(entries);
+    }
+
+    /// Marks `guid` as changed in `revision` by [`BimModel::author`].
+    pub(crate) fn stamp(&mut self, guid: Guid, revision: u64) {
+        self.removals.retain(|removal| removal.guid != guid);
+        let stamp = ElementRevision {
+            revision,
+            modified: now(),
+            author: self.author.clone(),
+        };
+        if let Some(index) = self.element_index(guid) {
+            self.elements_mut()[index].revision = stamp;
+        }
+    }
+
+    pub(crate) fn record_removal(&mut self, guid: Guid, revision: u64) {
+        self.removals.retain(|removal| removal.guid != guid);
+        self.removals.push(Removal { guid, revision });
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
@@ -0,0 +1,416 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::rating::{ACOUSTIC_RATING_KEY, FIRE_RATING_KEY};
+use crate::{BimCategory, BimElement, ParameterValue};
+
+/// Value type a schema expects for a parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterKind {
+    Integer,
+    Number,
+    Bool,
+    Text,
+}
+
+impl ParameterKind {
+    pub fn of(value: &ParameterValue) -> Self {
+        match value {
+            ParameterValue::Integer(_) => ParameterKind::Integer,
+            ParameterValue::Number(_) => ParameterKind::Number,
+            ParameterValue::Bool(_) => ParameterKind::Bool,
+            ParameterValue::Text(_) => ParameterKind::Text,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ParameterKind::Integer => "integer",
+            ParameterKind::Number => "number",
+            ParameterKind::Bool => "bool",
+            ParameterKind::Text => "text",
+        }
+    }
+}
+
+/// Unit a number parameter is measured in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParameterUnit {
+    /// The model's length unit.
+    Length,
+    Degrees,
+    /// Counts and other plain numbers.
+    None,
+}
+
+/// One entry of a category's parameter schema.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParameterSpec {
+    pub key: &'static str,
+    pub kind: ParameterKind,
+    pub unit: ParameterUnit,
+    pub required: bool,
+    /// Numbers must be strictly greater than zero.
+    pub positive: bool,
+}
+
+const fn spec(
+    key: &'static str,
+    unit: ParameterUnit,
+    required: bool,
+    positive: bool,
+) -> ParameterSpec {
+    ParameterSpec {
+        key,
+        kind: ParameterKind::Number,
+        unit,
+        required,
+        positive,
+    }
+}
+
+const fn dimension(key: &'static str) -> ParameterSpec {
+    spec(key, ParameterUnit::Length, true, true)
+}
+
+const fn coordinate(key: &'static str) -> ParameterSpec {
+    spec(key, ParameterUnit::Length, true, false)
+}
+
+const POINT_COUNT: ParameterSpec = ParameterSpec {
+    key: "PointCount",
+    kind: ParameterKind::Integer,
+    unit: ParameterUnit::None,
+    required: false,
+    positive: true,
+};
+
+const AXIS: [ParameterSpec; 6] = [
+    coordinate("StartX"),
+    coordinate("StartY"),
+    coordinate("StartZ"),
+    coordinate("EndX"),
+    coordinate("EndY"),
+    coordinate("EndZ"),
+];
+
+/// Fire and acoustic ratings of walls and openings, optional; see
+/// [`BimModel::rating_shortfalls`](crate::BimModel::rating_shortfalls).
+const RATINGS: [ParameterSpec; 2] = [
+    ParameterSpec {
+        key: FIRE_RATING_KEY,
+        kind: ParameterKind::Text,
+        unit: ParameterUnit::None,
+        required: false,
+        positive: false,
+    },
+    spec(ACOUSTIC_RATING_KEY, ParameterUnit::None, false, true),
+];
+
+const WALL: [ParameterSpec; 11] = [
+    dimension("Length"),
+    dimension("Thickness"),
+    dimension("Height"),
+    AXIS[0],
+    AXIS[1],
+    AXIS[2],
+    AXIS[3],
+    AXIS[4],
+    AXIS[5],
+    RATINGS[0],
+    RATINGS[1],
+];
+
+const SLAB: [ParameterSpec; 2] = [dimension("Thickness"), POINT_COUNT];
+
+const ROOF: [ParameterSpec; 3] = [
+    dimension("Thickness"),
+    spec("Slope", ParameterUnit::Degrees, false, false),
+    POINT_COUNT,
+];
+
+/// `SlopePercent` is driven by a formula over `Height` and `Length`, so it cannot be set.
+const RAMP: [ParameterSpec; 5] = [
+    dimension("Thickness"),
+    dimension("Width"),
+    dimension("Length"),
+    spec("SlopePercent", ParameterUnit::None, false, false),
+    POINT_COUNT,
+];
+
+const BEAM: [ParameterSpec; 10] = [
+    dimension("Length"),
+    spec("Roll", ParameterUnit::Degrees, false, false),
+    spec("StartOffset", ParameterUnit::Length, false, false),
+    spec("EndOffset", ParameterUnit::Length, false, false),
+    AXIS[0],
+    AXIS[1],
+    AXIS[2],
+    AXIS[3],
+    AXIS[4],
+    AXIS[5],
+];
+
+const COLUMN: [ParameterSpec; 7] = [
+    dimension("Height"),
+    AXIS[0],
+    AXIS[1],
+    AXIS[2],
+    AXIS[3],
+    AXIS[4],
+    AXIS[5],
+];
+
+const REBAR: [ParameterSpec; 3] = [dimension("Diameter"), dimension("Length"), POINT_COUNT];
+
+const RUN: [ParameterSpec; 1] = [dimension("Length")];
+
+/// Parameters the geometry builders read back for `category`; categories built from free
+/// geometry have an empty schema. Outline points (`Point{n}X/Y/Z`) are checked separately
+/// against `PointCount`.
+pub fn parameter_schema(category: BimCategory) -> &'static [ParameterSpec] {
+    match category {
+        BimCategory::Wall => &WALL,
+        BimCategory::Opening => &RATINGS,
+        BimCategory::Slab => &SLAB,
+        BimCategory::Roof => &ROOF,
+        BimCategory::Ramp => &RAMP,
+        BimCategory::Beam => &BEAM,
+        BimCategory::Column => &COLUMN,
+        BimCategory::Rebar => &REBAR,
+        BimCategory::Pipe | BimCategory::Duct => &RUN,
+        _ => &[],
+    }
+}
+
+/// Quantities the geometry builders compute from the other parameters.
+const COMPUTED_KEYS: [&str; 6] = [
+    "Area",
+    "BarCorners",
+    "CutLengths",
+    "FormworkArea",
+    "MaxCutLength",
+    "Volume",
+];
+
+/// Whether parameter `key` is a quantity computed with the geometry, such as a slab's `Area`
+/// or a footing's `Volume`, rather than an input; setting one would only go stale.
+pub fn is_computed_parameter(key: &str) -> bool {
+    COMPUTED_KEYS.contains(&key)
+}
+
+/// Units of the number parameters the element builders write, by key, for keys outside the
+/// category schemas. Other keys are plain numbers unless the element declares a unit for them
+/// in [`BimElement::parameter_units`]. Sorted by key.
+const BUILDER_UNITS: [(&str, ParameterUnit); 42] = [
+    ("BaseOffset", ParameterUnit::Length),
+    ("BoltDiameter", ParameterUnit::Length),
+    ("BoltLength", ParameterUnit::Length),
+    ("Cover", ParameterUnit::Length),
+    ("Depth", ParameterUnit::Length),
+    ("Diameter", ParameterUnit::Length),
+    ("EdgeDistance", ParameterUnit::Length),
+    ("EndExtensionLeft", ParameterUnit::Length),
+    ("EndExtensionRight", ParameterUnit::Length),
+    ("EndOffset", ParameterUnit::Length),
+    ("EndX", ParameterUnit::Length),
+    ("EndY", ParameterUnit::Length),
+    ("EndZ", ParameterUnit::Length),
+    ("FlangeThickness", ParameterUnit::Length),
+    ("Gauge", ParameterUnit::Length),
+    ("Height", ParameterUnit::Length),
+    ("HoleDiameter", ParameterUnit::Length),
+    ("Leg", ParameterUnit::Length),
+    ("Length", ParameterUnit::Length),
+    ("MaxCutLength", ParameterUnit::Length),
+    ("Pitch", ParameterUnit::Length),
+    ("PlateHeight", ParameterUnit::Length),
+    ("PlateThickness", ParameterUnit::Length),
+    ("PlateWidth", ParameterUnit::Length),
+    ("ProfileDepth", ParameterUnit::Length),
+    ("ProfileWidth", ParameterUnit::Length),
+    ("RebarDiameter", ParameterUnit::Length),
+    ("RebarSpacing", ParameterUnit::Length),
+    ("Roll", ParameterUnit::Degrees),
+    ("Slope", ParameterUnit::Degrees),
+    ("Spacing", ParameterUnit::Length),
+    ("StartExtensionLeft", ParameterUnit::Length),
+    ("StartExtensionRight", ParameterUnit::Length),
+    ("StartOffset", ParameterUnit::Length),
+    ("StartX", ParameterUnit::Length),
+    ("StartY", ParameterUnit::Length),
+    ("StartZ", ParameterUnit::Length),
+    ("Thickness", ParameterUnit::Length),
+    ("TieDepth", ParameterUnit::Length),
+    ("TopOffset", ParameterUnit::Length),
+    ("WebThickness", ParameterUnit::Length),
+    ("Width", ParameterUnit::Length),
+];
+
+/// Unit of parameter `key` on an element of `category`: the schema's when the key is listed,
+/// else the builders' unit for it. Outline points (`Point{n}X/Y/Z`) are lengths; any other key
+/// is [`ParameterUnit::None`]. See [`BimElement::parameter_unit`] for units declared per element.
+pub fn parameter_unit(category: BimCategory, key: &str) -> ParameterUnit {
+    if let Some(spec) = parameter_schema(category)
+        .iter()
+        .find(|spec| spec.key == key)
+    {
+        return spec.unit;
+    }
+    if let Ok(idx) = BUILDER_UNITS.binary_search_by(|(name, _)| (*name).cmp(key)) {
+        return BUILDER_UNITS[idx].1;
+    }
+    if point_coordinate(key) {
+        return ParameterUnit::Length;
+    }
+    ParameterUnit::None
+}
+
+/// Whether `key` is a coordinate of an outline point, `Point{n}X/Y/Z`.
+fn point_coordinate(key: &str) -> bool {
+    key.strip_prefix("Point")
+        .and_then(|rest| rest.strip_suffix(['X', 'Y', 'Z']))
+        .is_some_and(|idx| !idx.is_empty() && idx.bytes().all(|byte| byte.is_ascii_digit()))
+}
+
+/// A way an element's parameters break its category schema.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaViolation {
+    Missing {
+        key: String,
+    },
+    WrongKind {
+        key: String,
+        expected: ParameterKind,
+        found: ParameterKind,
+    },
+    NotPositive {
+        key: String,
+        value: f64,
+    },
+    /// A key outside the schema that looks like a typo of a missing one.
+    Misspelled {
+        key: String,
+        expected: String,
+    },
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaViolation::Missing { key } => write!(f, "missing parameter {key}"),
+            SchemaViolation::WrongKind {
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "parameter {key} should be a {}, not a {}",
+                expected.label(),
+                found.label()
+            ),
+            SchemaViolation::NotPositive { key, value } => {
+                write!(f, "parameter {key} must be > 0 (is {value})")
+            }
+            SchemaViolation::Misspelled { key, expected } => {
+                write!(f, "parameter {key} looks like a misspelling of {expected}")
+            }
+        }
+    }
+}
+
+/// Checks `element` against the schema of its category. Keys outside the schema are allowed,
+/// unless they are one edit away from a schema key the element lacks.
+pub fn validate_parameters(element: &BimElement) -> Vec<SchemaViolation> {
+    let mut specs: Vec<(String, ParameterSpec)> = parameter_schema(element.category)
+        .iter()
+        .map(|spec| (spec.key.to_string(), *spec))
+        .collect();
+    if let Some(ParameterValue::Integer(count)) = element.parameters.get(POINT_COUNT.key) {
+        for idx in 1..=*count {
+            for axis in ["X", "Y", "Z"] {
+                specs.push((format!("Point{idx}{axis}"), coordinate("Point")));
+            }
+        }
+    }
+
+    let mut violations = Vec::new();
+    for (key, spec) in &specs {
+        let Some(value) = element.parameters.get(key) else {
+            if spec.required {
+                violations.push(SchemaViolation::Missing { key: key.clone() });
+            }
+            continue;
+        };
+        let found = ParameterKind::of(value);
+        if found != spec.kind {
+            violations.push(SchemaViolation::WrongKind {
+                key: key.clone(),
+                expected: spec.kind,
+                found,
+            });
+            continue;
+        }
+        let number = match value {
+            ParameterValue::Number(number) => *number,
+            ParameterValue::Integer(number) => *number as f64,
+            _ => continue,
+        };
+        if spec.positive && number <= 0.0 {
+            violations.push(SchemaViolation::NotPositive {
+                key: key.clone(),
+                value: number,
+            });
+        }
+    }
+
+    for key in element.parameters.keys() {
+        if specs.iter().any(|(expected, _)| expected == key) {
+            continue;
+        }
+        let expected = specs.iter().find(|(expected, _)| {
+            !element.parameters.contains_key(expected) && is_typo(key, expected)
+        });
+        if let Some((expected, _)) = expected {
+            violations.push(SchemaViolation::Misspelled {
+                key: key.clone(),
+                expected: expected.clone(),
+            });
+        }
+    }
+    violations
+}
+
+/// Whether `key` differs from `expected` only in letter case or by one inserted, deleted,
+/// substituted or swapped character.
+fn is_typo(key: &str, expected: &str) -> bool {
+    if key.eq_ignore_ascii_case(expected) {
+        return true;
+    }
+    let key: Vec<char> = key.chars().collect();
+    let expected: Vec<char> = expected.chars().collect();
+    let (shorter, longer) = if key.len() <= expected.len() {
+        (&key, &expected)
+    } else {
+        (&expected, &key)
+    };
+    if longer.len() - shorter.len() > 1 {
+        return false;
+    }
+    let prefix = shorter
+        .iter()
+        .zip(longer.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if prefix == longer.len() {
+        return true;
+    }
+    if shorter.len() < longer.len() {
+        return shorter[prefix..] == longer[prefix + 1..];
+    }
+    let swapped = prefix + 1 < shorter.len()
+        && shorter[prefix] == longer[prefix + 1]
+        && shorter[prefix + 1] == longer[prefix]
+        && shorter[prefix + 2..] == longer[prefix + 2..];
+    swapped || shorter[prefix + 1..] == longer[prefix + 1..]
+}
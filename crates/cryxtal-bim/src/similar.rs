@@ -0,0 +1,122 @@
+use cryxtal_base::{Error, Guid, Result};
+
+use crate::{BimElement, BimModel, ParameterValue};
+
+/// Tolerance [`BimModel::similar_elements`] is usually called with, in model units.
+pub const SIMILARITY_TOLERANCE: f64 = 1.0;
+
+/// Parameters that differ between copies of one element, left out of the comparison.
+const PRESENTATION_KEYS: [&str; 2] = ["Layer", "Material"];
+
+impl BimModel {
+    /// Elements other than `guid` that are geometrically similar to it: of the same category,
+    /// with the same parameters apart from placement (`StartX`, `EndY`, `Point2Z`, ...) and
+    /// presentation (`Layer`, `Material`), numbers and opening sizes within `tolerance`. Elements
+    /// whose solids match vertex for vertex within `tolerance` after a translation count as well,
+    /// which covers elements without parameters such as imported ones.
+    pub fn similar_elements(&self, guid: Guid, tolerance: f64) -> Result<Vec<Guid>> {
+        if tolerance.is_nan() || tolerance <= 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "similarity tolerance must be > 0, got {tolerance}"
+            )));
+        }
+        let Some(element) = self.element(guid) else {
+            return Err(Error::InvalidParameter(format!("unknown element {guid}")));
+        };
+        let shape = ShapeKey::of(element, tolerance);
+        Ok(self
+            .elements()
+            .iter()
+            .filter(|other| other.guid != guid && other.category == element.category)
+            .filter(|other| {
+                same_parameters(element, other, tolerance)
+                    || ShapeKey::of(other, tolerance) == shape
+            })
+            .map(|other| other.guid)
+            .collect())
+    }
+}
+
+/// Whether `a` and `b` carry the same shape parameters; elements without any never match this
+/// way, so they are compared by their solids alone.
+fn same_parameters(a: &BimElement, b: &BimElement, tolerance: f64) -> bool {
+    let a_parameters: Vec<_> = shape_parameters(a).collect();
+    let b_parameters: Vec<_> = shape_parameters(b).collect();
+    if a_parameters.is_empty() && a.opening.is_none() {
+        return false;
+    }
+    let same_opening = match (a.opening, b.opening) {
+        (Some(a), Some(b)) => {
+            (a.profile.width - b.profile.width).abs() <= tolerance
+                && (a.profile.height - b.profile.height).abs() <= tolerance
+        }
+        (None, None) => true,
+        _ => false,
+    };
+    same_opening
+        && a_parameters.len() == b_parameters.len()
+        && a_parameters
+            .iter()
+            .zip(&b_parameters)
+            .all(|((a_key, a_value), (b_key, b_value))| {
+                a_key == b_key && same_value(a_value, b_value, tolerance)
+            })
+}
+
+fn shape_parameters(element: &BimElement) -> impl Iterator<Item = (&String, &ParameterValue)> {
+    element
+        .parameters
+        .iter()
+        .filter(|(key, _)| !PRESENTATION_KEYS.contains(&key.as_str()) && !is_placement(key))
+}
+
+fn same_value(a: &ParameterValue, b: &ParameterValue, tolerance: f64) -> bool {
+    match (a, b) {
+        (ParameterValue::Number(a), ParameterValue::Number(b)) => (a - b).abs() <= tolerance,
+        _ => a == b,
+    }
+}
+
+/// `Start`, `End` or `Point{n}` followed by an axis: where the element is, not what it is.
+fn is_placement(key: &str) -> bool {
+    let Some(stem) = key.strip_suffix(['X', 'Y', 'Z']) else {
+        return false;
+    };
+    stem == "Start"
+        || stem == "End"
+        || stem
+            .strip_prefix("Point")
+            .is_some_and(|number| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Face count of a solid and its vertices relative to the first one, rounded to the tolerance,
+/// so copies of a solid moved anywhere compare equal.
+#[derive(PartialEq)]
+struct ShapeKey {
+    faces: usize,
+    vertices: Vec<[i64; 3]>,
+}
+
+impl ShapeKey {
+    fn of(element: &BimElement, tolerance: f64) -> Self {
+        let points: Vec<_> = element
+            .geometry
+            .vertex_iter()
+            .map(|vertex| vertex.point())
+            .collect();
+        let vertices = match points.first() {
+            Some(&origin) => points
+                .iter()
+                .map(|point| {
+                    let offset = point - origin;
+                    [offset.x, offset.y, offset.z].map(|value| (value / tolerance).round() as i64)
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        Self {
+            faces: element.geometry.face_iter().count(),
+            vertices,
+        }
+    }
+}
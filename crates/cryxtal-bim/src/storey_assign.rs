@@ -0,0 +1,117 @@
+use cryxtal_base::Guid;
+use cryxtal_topology::solid_bounds;
+use std::collections::HashMap;
+
+use crate::{BimElement, BimModel, ParameterValue};
+
+/// Parameter that, when true, keeps an element on its storey through
+/// [`BimModel::storey_assignments`].
+pub const STOREY_LOCKED_KEY: &str = "StoreyLocked";
+/// Parameter binding a wall's or column's base to a storey, by guid.
+const BASE_STOREY_KEY: &str = "BaseStorey";
+
+/// An element to move to another storey, as found by [`BimModel::storey_assignments`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StoreyAssignment {
+    pub element: Guid,
+    /// Storey the element is on now, if any.
+    pub from: Option<Guid>,
+    pub to: Guid,
+}
+
+impl BimModel {
+    /// Elements standing on another storey than their geometry suggests, with the storey each
+    /// belongs on: the one whose elevation is nearest the bottom of the element's solid, or the
+    /// storey its base is bound to (`BaseStorey`). Openings and hosted elements follow their
+    /// host. Elements with [`STOREY_LOCKED_KEY`] set are left where they are, as are elements
+    /// without geometry. Meant for imports that come without level data, and safe to run again
+    /// as a cleanup; apply the result with [`ModelCommand::MoveToStorey`](crate::ModelCommand).
+    pub fn storey_assignments(&self) -> Vec<StoreyAssignment> {
+        let storeys: Vec<(Guid, f64)> = self
+            .storeys()
+            .map(|storey| (storey.guid, storey.elevation))
+            .collect();
+        if storeys.is_empty() {
+            return Vec::new();
+        }
+        let current = |guid: Guid| self.storey_of(guid).map(|storey| storey.guid);
+        let locked = |element: &BimElement| {
+            matches!(
+                element.parameters.get(STOREY_LOCKED_KEY),
+                Some(ParameterValue::Bool(true))
+            )
+        };
+
+        // Where each element belongs by itself, before hosts are followed.
+        let mut own: HashMap<Guid, Option<Guid>> = HashMap::new();
+        for element in self.elements() {
+            let bound = match element.parameters.get(BASE_STOREY_KEY) {
+                Some(ParameterValue::Text(storey)) => storey
+                    .parse::<Guid>()
+                    .ok()
+                    .filter(|storey| self.storey(*storey).is_some()),
+                _ => None,
+            };
+            let target = if locked(element) {
+                current(element.guid)
+            } else if let Some(storey) = bound {
+                Some(storey)
+            } else {
+                solid_bounds(&element.geometry)
+                    .map(|(min, _)| nearest_storey(&storeys, min.z))
+                    .or_else(|| current(element.guid))
+            };
+            own.insert(element.guid, target);
+        }
+
+        let host_of = |guid: Guid| {
+            let element = self.element(guid)?;
+            element
+                .opening
+                .map(|opening| opening.host)
+                .or(element.host)
+                .filter(|host| self.element(*host).is_some())
+        };
+        let mut assignments = Vec::new();
+        for element in self.elements() {
+            // Follow hosts to the element that stands by itself; a host cycle stays put.
+            let mut source = element.guid;
+            if !locked(element) {
+                let mut seen = vec![element.guid];
+                while let Some(host) = host_of(source) {
+                    if seen.contains(&host) {
+                        source = element.guid;
+                        break;
+                    }
+                    seen.push(host);
+                    source = host;
+                }
+            }
+            let from = current(element.guid);
+            if let Some(to) = own.get(&source).copied().flatten()
+                && from != Some(to)
+            {
+                assignments.push(StoreyAssignment {
+                    element: element.guid,
+                    from,
+                    to,
+                });
+            }
+        }
+        assignments
+    }
+}
+
+/// Storey with the elevation nearest `z`; the lower one on a tie.
+fn nearest_storey(storeys: &[(Guid, f64)], z: f64) -> Guid {
+    storeys
+        .iter()
+        .min_by(|a, b| {
+            (a.1 - z)
+                .abs()
+                .total_cmp(&(b.1 - z).abs())
+                .then(a.1.total_cmp(&b.1))
+        })
+        .map(|(guid, _)| *guid)
+        .expect("at least one storey")
+}
@@ -0,0 +1,129 @@
+use cryxtal_base::{Error, Guid, Result};
+use cryxtal_topology::{Matrix4, Vector3};
+
+use crate::{BimElement, BimModel, ParameterSet, ParameterValue, Storey};
+
+impl BimModel {
+    /// Copies of the elements `guids`, with everything hanging off them, for each storey in
+    /// `storeys`, paired with that storey for a [`ModelCommand::AddElements`](crate::ModelCommand).
+    ///
+    /// Each element moves by the difference in elevation between the storey it is on and the
+    /// target, and storey references in its parameters (such as a column's `TopStorey`) move up
+    /// or down by as many storeys; ends that would leave the model stay at their copied height.
+    /// Host links within the copied set are rewired to the copies, as with
+    /// [`BimModel::deep_clone`]; an element hosted outside the set is copied without a host.
+    /// Elements are not copied onto the storey they are already on.
+    pub fn copy_to_storeys(
+        &self,
+        guids: &[Guid],
+        storeys: &[Guid],
+    ) -> Result<Vec<(Guid, Vec<BimElement>)>> {
+        let mut ordered: Vec<&Storey> = self.storeys().collect();
+        ordered.sort_by(|a, b| a.elevation.total_cmp(&b.elevation));
+        let position = |guid: Guid| ordered.iter().position(|storey| storey.guid == guid);
+
+        let mut roots: Vec<(Guid, usize)> = Vec::new();
+        for &guid in guids {
+            let Some(element) = self.element(guid) else {
+                return Err(Error::InvalidParameter(format!("unknown element {guid}")));
+            };
+            if self.has_ancestor_in(guid, guids) || roots.iter().any(|(root, _)| *root == guid) {
+                continue;
+            }
+            if element.opening.is_some() {
+                return Err(Error::InvalidParameter(format!(
+                    "opening {} can only be copied with its host",
+                    element.name
+                )));
+            }
+            let Some(source) = self
+                .storey_of(guid)
+                .and_then(|storey| position(storey.guid))
+            else {
+                return Err(Error::InvalidParameter(format!(
+                    "element {} is not on a storey",
+                    element.name
+                )));
+            };
+            roots.push((guid, source));
+        }
+
+        let mut copies_per_storey = Vec::with_capacity(storeys.len());
+        for &target in storeys {
+            let Some(target_index) = position(target) else {
+                return Err(Error::InvalidParameter(format!("unknown storey {target}")));
+            };
+            let mut copies = Vec::new();
+            for &(guid, source_index) in &roots {
+                if source_index == target_index {
+                    continue;
+                }
+                let rise = ordered[target_index].elevation - ordered[source_index].elevation;
+                let transform = Matrix4::from_translation(Vector3::new(0.0, 0.0, rise));
+                let mut cloned = self.deep_clone(guid, transform)?;
+                cloned[0].host = None;
+                let shift = target_index as isize - source_index as isize;
+                for copy in &mut cloned {
+                    shift_storey_references(&mut copy.parameters, &ordered, shift);
+                }
+                copies.extend(cloned);
+            }
+            if !copies.is_empty() {
+                copies_per_storey.push((target, copies));
+            }
+        }
+        Ok(copies_per_storey)
+    }
+
+    /// Whether an element `guid` is hosted, directly or further up, by one of `guids`.
+    pub(crate) fn has_ancestor_in(&self, guid: Guid, guids: &[Guid]) -> bool {
+        let mut current = self.element(guid);
+        let mut visited = vec![guid];
+        while let Some(element) = current {
+            let Some(host) = element.opening.map(|opening| opening.host).or(element.host) else {
+                return false;
+            };
+            if guids.contains(&host) {
+                return true;
+            }
+            if visited.contains(&host) {
+                return false;
+            }
+            visited.push(host);
+            current = self.element(host);
+        }
+        false
+    }
+}
+
+/// Moves `{Prefix}Storey` references `shift` storeys along `ordered`. A reference with no storey
+/// that far away is dropped with its `{Prefix}Offset`, leaving that end at a fixed elevation.
+fn shift_storey_references(parameters: &mut ParameterSet, ordered: &[&Storey], shift: isize) {
+    let mut dropped = Vec::new();
+    for (key, value) in parameters.iter_mut() {
+        let Some(prefix) = key.strip_suffix("Storey") else {
+            continue;
+        };
+        let ParameterValue::Text(text) = value else {
+            continue;
+        };
+        let Some(index) = text
+            .parse::<Guid>()
+            .ok()
+            .and_then(|guid| ordered.iter().position(|storey| storey.guid == guid))
+        else {
+            continue;
+        };
+        match index
+            .checked_add_signed(shift)
+            .and_then(|index| ordered.get(index))
+        {
+            Some(storey) => *text = storey.guid.to_string(),
+            None => dropped.push(prefix.to_string()),
+        }
+    }
+    for prefix in dropped {
+        parameters.remove(&format!("{prefix}Storey"));
+        parameters.remove(&format!("{prefix}Offset"));
+    }
+}
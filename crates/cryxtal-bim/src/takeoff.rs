@@ -0,0 +1,123 @@
+use cryxtal_base::Units;
+use cryxtal_topology::mass_properties;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{BimCategory, BimElement, BimModel, ParameterValue};
+
+/// Quantities of a group of elements, in the model's units.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TakeoffRow {
+    /// Category, type or material name the row sums up.
+    pub name: String,
+    pub count: usize,
+    /// Sum of element lengths: the `Length` parameter, or the distance from the `Start` to
+    /// the `End` point for elements placed along an axis such as columns.
+    pub length: f64,
+    /// Sum of surface areas, as measured for formwork and finishes.
+    pub area: f64,
+    pub volume: f64,
+}
+
+impl TakeoffRow {
+    fn add(&mut self, quantities: &Quantities) {
+        self.count += 1;
+        self.length += quantities.length;
+        self.area += quantities.area;
+        self.volume += quantities.volume;
+    }
+}
+
+/// Element quantities of a model grouped three ways; see [`takeoff`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TakeoffReport {
+    pub units: Units,
+    pub by_category: Vec<TakeoffRow>,
+    /// Elements that are no type's instance are grouped under `No type`.
+    pub by_type: Vec<TakeoffRow>,
+    /// The element's `Material`, else its type's, else `Unspecified`.
+    pub by_material: Vec<TakeoffRow>,
+    pub total: TakeoffRow,
+}
+
+struct Quantities {
+    length: f64,
+    area: f64,
+    volume: f64,
+}
+
+/// Counts, lengths, areas and volumes of the model's elements by category, type and material,
+/// with rows sorted by name. Openings are voids and reference meshes context, and both are left
+/// out.
+pub fn takeoff(model: &BimModel) -> TakeoffReport {
+    let mut by_category = BTreeMap::new();
+    let mut by_type = BTreeMap::new();
+    let mut by_material = BTreeMap::new();
+    let mut total = TakeoffRow {
+        name: "Total".to_string(),
+        ..TakeoffRow::default()
+    };
+    for element in model.elements() {
+        if element.category == BimCategory::Opening || element.is_reference_mesh() {
+            continue;
+        }
+        let mass = mass_properties(&element.geometry);
+        let quantities = Quantities {
+            length: element_length(element),
+            area: mass.area,
+            volume: mass.volume,
+        };
+        let element_type = element.type_id.and_then(|guid| model.element_type(guid));
+        let material = match element.parameters.get("Material") {
+            Some(ParameterValue::Text(material)) => Some(material.clone()),
+            _ => element_type.and_then(|element_type| element_type.material.clone()),
+        };
+        for (rows, name) in [
+            (&mut by_category, format!("{:?}", element.category)),
+            (
+                &mut by_type,
+                element_type.map_or_else(
+                    || "No type".to_string(),
+                    |element_type| element_type.name.clone(),
+                ),
+            ),
+            (
+                &mut by_material,
+                material.unwrap_or_else(|| "Unspecified".to_string()),
+            ),
+        ] {
+            rows.entry(name.clone())
+                .or_insert_with(|| TakeoffRow {
+                    name,
+                    ..TakeoffRow::default()
+                })
+                .add(&quantities);
+        }
+        total.add(&quantities);
+    }
+    TakeoffReport {
+        units: model.units,
+        by_category: by_category.into_values().collect(),
+        by_type: by_type.into_values().collect(),
+        by_material: by_material.into_values().collect(),
+        total,
+    }
+}
+
+fn element_length(element: &BimElement) -> f64 {
+    let number = |key: &str| match element.parameters.get(key) {
+        Some(ParameterValue::Number(value)) => Some(*value),
+        _ => None,
+    };
+    if let Some(length) = number("Length") {
+        return length;
+    }
+    let axis = ["X", "Y", "Z"]
+        .map(|axis| number(&format!("Start{axis}")).zip(number(&format!("End{axis}"))));
+    match axis {
+        [Some(x), Some(y), Some(z)] => {
+            ((x.1 - x.0).powi(2) + (y.1 - y.0).powi(2) + (z.1 - z.0).powi(2)).sqrt()
+        }
+        _ => 0.0,
+    }
+}
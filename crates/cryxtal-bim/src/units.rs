@@ -0,0 +1,121 @@
+use cryxtal_base::{LengthUnit, Units};
+use cryxtal_topology::{Matrix4, transform_solid};
+
+use crate::{
+    BimElement, BimElementType, BimModel, Constraint, DirtyFlags, ParameterSet, ParameterUnit,
+    ParameterValue, ReferenceKind, parameter_unit,
+};
+
+impl BimElement {
+    /// Re-expresses the element in `to` after it was built in `from`: the geometry, every
+    /// length parameter (see [`BimElement::parameter_unit`]), the opening and weld sizes and
+    /// constraint offsets are scaled, and formula-driven parameters are re-evaluated.
+    pub fn convert_length(&mut self, from: LengthUnit, to: LengthUnit) {
+        let factor = from.factor_to(to);
+        if factor == 1.0 {
+            return;
+        }
+        let up_to_date = self.regen_hash == Some(self.parameter_hash());
+        self.geometry = transform_solid(&self.geometry, Matrix4::from_scale(factor));
+        let (category, units) = (self.category, &self.parameter_units);
+        scale_lengths(&mut self.parameters, factor, |key| {
+            units
+                .get(key)
+                .copied()
+                .unwrap_or_else(|| parameter_unit(category, key))
+        });
+        // Driven values follow their scaled inputs; constants in formulas are not converted.
+        let _ = self.refresh_formulas(None);
+        if let Some(opening) = self.opening.as_mut() {
+            opening.profile.width *= factor;
+            opening.profile.height *= factor;
+            opening.placement.center_x *= factor;
+            opening.placement.center_y *= factor;
+            opening.placement.center_z *= factor;
+        }
+        for weld in &mut self.welds {
+            weld.size *= factor;
+        }
+        for constraint in &mut self.constraints {
+            if let Constraint::TopAttached { offset, .. } | Constraint::FaceOffset { offset, .. } =
+                constraint
+            {
+                *offset *= factor;
+            }
+        }
+        // Hosts keyed on their openings too are left to rebuild once.
+        self.regen_hash = up_to_date.then(|| self.parameter_hash());
+    }
+}
+
+impl BimModel {
+    /// Switches the model to `units`, converting elements, types, storey elevations and
+    /// reference geometry so the model stays the same size. Parameters in degrees are left
+    /// as they are.
+    pub fn convert_units(&mut self, units: Units) {
+        let (from, to) = (self.units.length, units.length);
+        self.units = units;
+        let factor = from.factor_to(to);
+        if factor == 1.0 {
+            return;
+        }
+        let mut converted = Vec::with_capacity(self.elements().len());
+        for element in self.elements_mut() {
+            element.convert_length(from, to);
+            converted.push(element.guid);
+        }
+        for guid in converted {
+            self.mark_dirty(guid, DirtyFlags::ALL);
+        }
+        for element_type in &mut self.types {
+            convert_type(element_type, factor);
+        }
+        for storey in self
+            .site
+            .buildings
+            .iter_mut()
+            .flat_map(|building| building.storeys.iter_mut())
+        {
+            storey.elevation *= factor;
+        }
+        for coordinate in &mut self.site.shared_coordinates.offset {
+            *coordinate *= factor;
+        }
+        for reference in &mut self.references {
+            let point = match &mut reference.kind {
+                ReferenceKind::Point { position } => position,
+                ReferenceKind::Line { origin, .. } | ReferenceKind::Plane { origin, .. } => origin,
+            };
+            for coordinate in point {
+                *coordinate *= factor;
+            }
+        }
+        for grid in &mut self.grids {
+            grid.curve.scale(factor);
+        }
+    }
+
+    /// Copy of the model in `units`; exporters that expect millimetres call this first.
+    pub fn converted(&self, units: Units) -> BimModel {
+        let mut model = self.clone();
+        model.convert_units(units);
+        model
+    }
+}
+
+fn convert_type(element_type: &mut BimElementType, factor: f64) {
+    let category = element_type.category;
+    scale_lengths(&mut element_type.parameters, factor, |key| {
+        parameter_unit(category, key)
+    });
+}
+
+fn scale_lengths(parameters: &mut ParameterSet, factor: f64, unit: impl Fn(&str) -> ParameterUnit) {
+    for (key, value) in parameters.iter_mut() {
+        if let ParameterValue::Number(number) = value
+            && unit(key) == ParameterUnit::Length
+        {
+            *number *= factor;
+        }
+    }
+}
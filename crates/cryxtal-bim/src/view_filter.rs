@@ -0,0 +1,299 @@
+use cryxtal_base::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::{BimElement, BimModel, ParameterValue};
+
+/// Color of elements whose layer is missing from a model without layers.
+const FALLBACK_COLOR: [u8; 4] = [180, 190, 200, 255];
+
+/// How a [`FilterRule`] compares a parameter with its value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilterOperator {
+    Equals,
+    NotEquals,
+    /// Text containing the value, ignoring case.
+    Contains,
+    Greater,
+    Less,
+    GreaterOrEqual,
+    LessOrEqual,
+}
+
+impl FilterOperator {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            FilterOperator::Equals => "=",
+            FilterOperator::NotEquals => "!=",
+            FilterOperator::Contains => "~",
+            FilterOperator::Greater => ">",
+            FilterOperator::Less => "<",
+            FilterOperator::GreaterOrEqual => ">=",
+            FilterOperator::LessOrEqual => "<=",
+        }
+    }
+}
+
+/// Condition on one element parameter, such as `FireRating=EI60` or `Height>3000`. The
+/// parameter `Category` also matches the element category when there is no such parameter.
+/// Numbers, including text parameters holding one, compare numerically with a numeric value.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FilterRule {
+    pub parameter: String,
+    pub operator: FilterOperator,
+    pub value: String,
+}
+
+impl FilterRule {
+    pub fn new(
+        parameter: impl Into<String>,
+        operator: FilterOperator,
+        value: impl Into<String>,
+    ) -> Self {
+        Self {
+            parameter: parameter.into(),
+            operator,
+            value: value.into(),
+        }
+    }
+
+    pub fn matches(&self, element: &BimElement) -> bool {
+        match element.parameters.get(&self.parameter) {
+            Some(ParameterValue::Number(value)) => self.compare(&value.to_string(), Some(*value)),
+            Some(ParameterValue::Integer(value)) => {
+                self.compare(&value.to_string(), Some(*value as f64))
+            }
+            Some(ParameterValue::Bool(value)) => self.compare(&value.to_string(), None),
+            Some(ParameterValue::Text(value)) => self.compare(value, value.trim().parse().ok()),
+            None if self.parameter == "Category" => {
+                self.compare(&format!("{:?}", element.category), None)
+            }
+            None => self.operator == FilterOperator::NotEquals,
+        }
+    }
+
+    /// Compares `number` when both it and the rule's value are numbers, `text` otherwise.
+    fn compare(&self, text: &str, number: Option<f64>) -> bool {
+        let expected = self.value.trim();
+        if let (Some(value), Ok(expected)) = (number, expected.parse::<f64>()) {
+            let equal = (value - expected).abs() <= 1.0e-9;
+            return match self.operator {
+                FilterOperator::Equals => equal,
+                FilterOperator::NotEquals => !equal,
+                FilterOperator::Contains => text.contains(self.value.trim()),
+                FilterOperator::Greater => value > expected && !equal,
+                FilterOperator::Less => value < expected && !equal,
+                FilterOperator::GreaterOrEqual => value > expected || equal,
+                FilterOperator::LessOrEqual => value < expected || equal,
+            };
+        }
+        match self.operator {
+            FilterOperator::Equals => text == expected,
+            FilterOperator::NotEquals => text != expected,
+            FilterOperator::Contains => text.to_lowercase().contains(&expected.to_lowercase()),
+            FilterOperator::Greater => text > expected,
+            FilterOperator::Less => text < expected,
+            FilterOperator::GreaterOrEqual => text >= expected,
+            FilterOperator::LessOrEqual => text <= expected,
+        }
+    }
+}
+
+impl std::fmt::Display for FilterRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.parameter,
+            self.operator.symbol(),
+            self.value
+        )
+    }
+}
+
+impl FromStr for FilterRule {
+    type Err = Error;
+
+    /// Parses `Parameter<op>Value` with `op` one of `!=`, `>=`, `<=`, `=`, `~`, `>` or `<`.
+    fn from_str(text: &str) -> Result<Self> {
+        // Two-character operators come first, so `>=` wins over the `>` found at the same place.
+        const OPERATORS: [FilterOperator; 7] = [
+            FilterOperator::NotEquals,
+            FilterOperator::GreaterOrEqual,
+            FilterOperator::LessOrEqual,
+            FilterOperator::Equals,
+            FilterOperator::Contains,
+            FilterOperator::Greater,
+            FilterOperator::Less,
+        ];
+        OPERATORS
+            .iter()
+            .filter_map(|operator| {
+                let (parameter, value) = text.split_once(operator.symbol())?;
+                Some((parameter.len(), *operator, parameter, value))
+            })
+            .min_by_key(|(position, ..)| *position)
+            .filter(|(_, _, parameter, _)| !parameter.trim().is_empty())
+            .map(|(_, operator, parameter, value)| {
+                Self::new(parameter.trim(), operator, value.trim())
+            })
+            .ok_or_else(|| Error::InvalidParameter(format!("invalid filter rule `{text}`")))
+    }
+}
+
+/// Display changes for the elements a [`ViewFilter`] matches; unset fields keep the layer's.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphicOverride {
+    /// Unmultiplied sRGB.
+    #[serde(default)]
+    pub color: Option<[u8; 3]>,
+    /// 0 is opaque, 1 fully transparent.
+    #[serde(default)]
+    pub transparency: Option<f32>,
+    /// Washes the color out, to push elements into the background.
+    #[serde(default)]
+    pub halftone: bool,
+}
+
+impl GraphicOverride {
+    /// `color` (unmultiplied sRGBA, like [`Layer::color`](crate::Layer)) with this override
+    /// applied.
+    pub fn apply(&self, color: [u8; 4]) -> [u8; 4] {
+        let [mut r, mut g, mut b, mut a] = color;
+        if let Some([or, og, ob]) = self.color {
+            (r, g, b) = (or, og, ob);
+        }
+        if let Some(transparency) = self.transparency {
+            a = ((1.0 - transparency.clamp(0.0, 1.0)) * 255.0).round() as u8;
+        }
+        if self.halftone {
+            let wash = |channel: u8| channel / 2 + 128;
+            (r, g, b) = (wash(r), wash(g), wash(b));
+        }
+        [r, g, b, a]
+    }
+
+    /// `other` laid over this override: fields `other` sets replace these.
+    fn merge(&mut self, other: &GraphicOverride) {
+        self.color = other.color.or(self.color);
+        self.transparency = other.transparency.or(self.transparency);
+        self.halftone |= other.halftone;
+    }
+}
+
+/// A rule and the override given to the elements it matches.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ViewFilter {
+    pub name: String,
+    pub rule: FilterRule,
+    #[serde(default)]
+    pub graphics: GraphicOverride,
+    /// Disabled filters are kept with the view but not applied.
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+impl ViewFilter {
+    pub fn new(name: impl Into<String>, rule: FilterRule, graphics: GraphicOverride) -> Self {
+        Self {
+            name: name.into(),
+            rule,
+            graphics,
+            enabled: true,
+        }
+    }
+}
+
+/// A named set of view filters, such as "Fire compartments"; the viewer applies one at a time.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NamedView {
+    pub name: String,
+    #[serde(default)]
+    pub filters: Vec<ViewFilter>,
+}
+
+impl NamedView {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            filters: Vec::new(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: ViewFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Combined override of the enabled filters matching `element`, later filters winning
+    /// where they disagree; `None` when no filter matches.
+    pub fn override_for(&self, element: &BimElement) -> Option<GraphicOverride> {
+        self.filters
+            .iter()
+            .filter(|filter| filter.enabled && filter.rule.matches(element))
+            .fold(None, |combined, filter| {
+                let mut combined: GraphicOverride = combined.unwrap_or_default();
+                combined.merge(&filter.graphics);
+                Some(combined)
+            })
+    }
+}
+
+/// One row of a view's legend: a color and what it stands for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LegendEntry {
+    pub label: String,
+    /// Unmultiplied sRGBA.
+    pub color: [u8; 4],
+}
+
+impl BimModel {
+    /// Color of `element`'s layer; elements on no known layer take the first layer's.
+    pub fn layer_color(&self, element: &BimElement) -> [u8; 4] {
+        let layer = match element.parameters.get("Layer") {
+            Some(ParameterValue::Text(name)) => {
+                self.layers.iter().find(|layer| layer.name == *name)
+            }
+            _ => None,
+        };
+        layer
+            .or(self.layers.first())
+            .map_or(FALLBACK_COLOR, |layer| layer.color)
+    }
+
+    /// Color `element` is shown in: its layer color with the filters of `view` applied.
+    pub fn display_color(&self, element: &BimElement, view: Option<&NamedView>) -> [u8; 4] {
+        let color = self.layer_color(element);
+        match view.and_then(|view| view.override_for(element)) {
+            Some(graphics) => graphics.apply(color),
+            None => color,
+        }
+    }
+
+    /// Legend for `view`: a row per enabled filter matching at least one element, labelled
+    /// with the filter name and colored as its override turns the default layer's color.
+    pub fn legend(&self, view: &NamedView) -> Vec<LegendEntry> {
+        let base = self
+            .layers
+            .first()
+            .map_or(FALLBACK_COLOR, |layer| layer.color);
+        view.filters
+            .iter()
+            .filter(|filter| {
+                filter.enabled
+                    && self
+                        .elements()
+                        .iter()
+                        .any(|element| filter.rule.matches(element))
+            })
+            .map(|filter| LegendEntry {
+                label: filter.name.clone(),
+                color: filter.graphics.apply(base),
+            })
+            .collect()
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
@@ -0,0 +1,93 @@
+use cryxtal_base::{Error, Result};
+use cryxtal_topology::solid_edges;
+use serde::{Deserialize, Serialize};
+
+use crate::BimElement;
+
+/// Weld types with a standard elementary symbol (ISO 2553 / AWS A2.4).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum WeldKind {
+    Fillet,
+    SquareButt,
+    SingleV,
+    SingleBevel,
+    Plug,
+}
+
+impl WeldKind {
+    pub const ALL: [WeldKind; 5] = [
+        WeldKind::Fillet,
+        WeldKind::SquareButt,
+        WeldKind::SingleV,
+        WeldKind::SingleBevel,
+        WeldKind::Plug,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WeldKind::Fillet => "Fillet",
+            WeldKind::SquareButt => "Square butt",
+            WeldKind::SingleV => "Single V",
+            WeldKind::SingleBevel => "Single bevel",
+            WeldKind::Plug => "Plug",
+        }
+    }
+}
+
+/// Side of the joint the weld is on, relative to the symbol's arrow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum WeldSide {
+    Arrow,
+    Other,
+    Both,
+}
+
+impl WeldSide {
+    pub fn label(self) -> &'static str {
+        match self {
+            WeldSide::Arrow => "Arrow side",
+            WeldSide::Other => "Other side",
+            WeldSide::Both => "Both sides",
+        }
+    }
+}
+
+/// Weld along one edge of an element's solid. Edges are addressed like edge tags, by their
+/// position in [`solid_edges`]. `size` is the fillet leg or groove depth, in model length units.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WeldAnnotation {
+    pub edge: usize,
+    pub kind: WeldKind,
+    pub size: f64,
+    pub side: WeldSide,
+    /// Welded all around the joint, drawn as a circle at the arrow's knee.
+    #[serde(default)]
+    pub all_around: bool,
+    /// Made on site rather than in the shop, drawn as a flag.
+    #[serde(default)]
+    pub field: bool,
+}
+
+impl BimElement {
+    /// Attaches `weld`, checking its edge against the element's geometry.
+    pub fn add_weld(&mut self, weld: WeldAnnotation) -> Result<()> {
+        self.check_weld(&weld)?;
+        self.welds.push(weld);
+        Ok(())
+    }
+
+    /// Checks that `weld` is on an edge of the element's solid and has a size.
+    pub(crate) fn check_weld(&self, weld: &WeldAnnotation) -> Result<()> {
+        let edges = solid_edges(&self.geometry).len();
+        if weld.edge >= edges {
+            return Err(Error::InvalidParameter(format!(
+                "edge {} out of range (solid has {edges})",
+                weld.edge
+            )));
+        }
+        if weld.size <= 0.0 {
+            return Err(Error::InvalidParameter("weld size must be > 0".to_string()));
+        }
+        Ok(())
+    }
+}
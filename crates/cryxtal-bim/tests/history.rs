@@ -0,0 +1,1030 @@
+use cryxtal_base::{Guid, Units};
+use cryxtal_bim::{
+    BimCategory, BimElement, BimElementType, BimModel, ChangeSource, DirtyFlags, Grid, GridCurve,
+    History, Issue, Layer, ModelCommand, NamedView, Opening, OpeningPlacement, OpeningProfile,
+    ParameterSet, ParameterValue, Phase, ReferenceGeometry, ReferenceKind, WeldAnnotation,
+    WeldKind, WeldSide,
+};
+use cryxtal_topology::{Result, SolidBuilder};
+
+fn element(category: BimCategory) -> Result<BimElement> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    Ok(BimElement::new(
+        Guid::new(),
+        "Element",
+        category,
+        ParameterSet::new(),
+        solid,
+    ))
+}
+
+fn layer(model: &BimModel, guid: Guid) -> Option<String> {
+    match model.element(guid)?.parameters.get("Layer") {
+        Some(ParameterValue::Text(value)) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+#[test]
+fn undo_and_redo_step_through_edits() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut history = History::new();
+
+    let wall = element(BimCategory::Wall)?;
+    let wall_guid = wall.guid;
+    let opening = Opening::new(
+        wall_guid,
+        OpeningProfile {
+            width: 900.0,
+            height: 2100.0,
+        },
+        OpeningPlacement {
+            center_x: 500.0,
+            center_y: 0.0,
+            center_z: 1050.0,
+        },
+    );
+    let opening_element = BimElement::opening(
+        opening,
+        "Opening 1",
+        ParameterSet::new(),
+        SolidBuilder::box_solid(900.0, 200.0, 2100.0)?,
+    );
+    history
+        .apply(
+            &mut model,
+            "Add wall",
+            ModelCommand::AddElements {
+                storey,
+                elements: vec![wall, opening_element],
+            },
+        )
+        .expect("add");
+    history
+        .apply(
+            &mut model,
+            "Layer",
+            ModelCommand::set_layer(wall_guid, "Walls"),
+        )
+        .expect("layer");
+    history
+        .apply(
+            &mut model,
+            "Delete wall",
+            ModelCommand::DeleteElement { guid: wall_guid },
+        )
+        .expect("delete");
+    assert!(model.elements().is_empty());
+
+    assert_eq!(
+        history.undo(&mut model).expect("undo").as_deref(),
+        Some("Delete wall")
+    );
+    assert_eq!(model.elements().len(), 2);
+    assert_eq!(model.hosted_openings(wall_guid).len(), 1);
+    assert_eq!(
+        model.storey_of(wall_guid).map(|storey| storey.guid),
+        Some(storey)
+    );
+    assert_eq!(layer(&model, wall_guid).as_deref(), Some("Walls"));
+
+    history.undo(&mut model).expect("undo");
+    assert_eq!(layer(&model, wall_guid), None);
+    history.undo(&mut model).expect("undo");
+    assert!(model.elements().is_empty());
+    assert!(history.undo(&mut model).expect("undo").is_none());
+
+    history.redo(&mut model).expect("redo");
+    history.redo(&mut model).expect("redo");
+    assert_eq!(layer(&model, wall_guid).as_deref(), Some("Walls"));
+    assert_eq!(history.redo_label(), Some("Delete wall"));
+    Ok(())
+}
+
+#[test]
+fn generated_elements_go_with_their_opening() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut history = History::new();
+
+    let wall = element(BimCategory::Wall)?;
+    let wall_guid = wall.guid;
+    let opening = Opening::new(
+        wall_guid,
+        OpeningProfile {
+            width: 1200.0,
+            height: 1200.0,
+        },
+        OpeningPlacement {
+            center_x: 1000.0,
+            center_y: 0.0,
+            center_z: 1500.0,
+        },
+    );
+    let opening_element = BimElement::opening(
+        opening,
+        "Opening 1",
+        ParameterSet::new(),
+        SolidBuilder::box_solid(1200.0, 200.0, 1200.0)?,
+    );
+    let mut lintel = element(BimCategory::Beam)?;
+    lintel.host = Some(opening.guid);
+    lintel.removed_with_host = true;
+    lintel.cuts = vec![wall_guid];
+    let lintel_guid = lintel.guid;
+    // A rebar placed in the opening's surround outlives it, as hosted elements do.
+    let mut rebar = element(BimCategory::Rebar)?;
+    rebar.host = Some(opening.guid);
+    let rebar_guid = rebar.guid;
+    history
+        .apply(
+            &mut model,
+            "Add wall",
+            ModelCommand::AddElements {
+                storey,
+                elements: vec![wall, opening_element, lintel, rebar],
+            },
+        )
+        .expect("add");
+    assert_eq!(model.dependents(wall_guid), vec![opening.guid, lintel_guid]);
+    let embedded: Vec<Guid> = model
+        .embedded_members(wall_guid)
+        .into_iter()
+        .map(|index| model.elements()[index].guid)
+        .collect();
+    assert_eq!(embedded, vec![lintel_guid]);
+
+    history
+        .apply(
+            &mut model,
+            "Delete opening",
+            ModelCommand::DeleteElement { guid: opening.guid },
+        )
+        .expect("delete");
+    assert!(model.element(lintel_guid).is_none());
+    assert!(model.embedded_members(wall_guid).is_empty());
+    assert!(model.element(rebar_guid).is_some());
+    assert_eq!(model.orphans().len(), 1);
+
+    history.undo(&mut model).expect("undo");
+    assert_eq!(
+        model.storey_of(lintel_guid).map(|storey| storey.guid),
+        Some(storey)
+    );
+
+    history
+        .apply(
+            &mut model,
+            "Delete wall",
+            ModelCommand::DeleteElement { guid: wall_guid },
+        )
+        .expect("delete");
+    assert_eq!(model.elements().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn failed_batch_leaves_model_unchanged() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut history = History::new();
+    let slab = element(BimCategory::Slab)?;
+
+    let result = history.apply(
+        &mut model,
+        "Broken",
+        ModelCommand::Batch(vec![
+            ModelCommand::AddElements {
+                storey,
+                elements: vec![slab],
+            },
+            ModelCommand::DeleteElement { guid: Guid::new() },
+        ]),
+    );
+
+    assert!(result.is_err());
+    assert!(model.elements().is_empty());
+    assert!(history.undo_label().is_none());
+    Ok(())
+}
+
+#[test]
+fn references_are_added_and_undone() -> cryxtal_base::Result<()> {
+    let mut model = BimModel::new("Test");
+    let mut history = History::new();
+
+    assert!(ReferenceGeometry::line("Line", [1.0, 2.0, 3.0], [1.0, 2.0, 3.0]).is_err());
+    assert!(
+        ReferenceGeometry::plane("Plane", [[0.0; 3], [1.0, 0.0, 0.0], [2.0, 0.0, 0.0]]).is_err()
+    );
+
+    let plane = ReferenceGeometry::plane(
+        "Plane",
+        [
+            [0.0, 0.0, 500.0],
+            [1000.0, 0.0, 500.0],
+            [0.0, 1000.0, 500.0],
+        ],
+    )?;
+    assert!(matches!(
+        plane.kind,
+        ReferenceKind::Plane { normal, .. } if normal == [0.0, 0.0, 1.0]
+    ));
+    history.apply(
+        &mut model,
+        "Add reference",
+        ModelCommand::AddReference(plane.clone()),
+    )?;
+    assert_eq!(model.references, vec![plane.clone()]);
+    // References are annotations, which read-only sessions may add and undo.
+    assert!(
+        history
+            .undo_command()
+            .is_some_and(ModelCommand::is_annotation)
+    );
+    assert!(!ModelCommand::set_layer(plane.guid, "A").is_annotation());
+
+    history.undo(&mut model)?;
+    assert!(model.references.is_empty());
+    history.redo(&mut model)?;
+    assert_eq!(model.references, vec![plane]);
+    Ok(())
+}
+
+#[test]
+fn types_are_shared_and_guarded_while_in_use() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut history = History::new();
+
+    let wall_type = BimElementType::new("200mm CIP concrete", BimCategory::Wall, "Basic Wall")
+        .with_material("Concrete")
+        .with_parameter("Thickness", ParameterValue::Number(200.0));
+    let type_guid = wall_type.guid;
+    history
+        .apply(
+            &mut model,
+            "Add type",
+            ModelCommand::AddType(wall_type.clone()),
+        )
+        .expect("add type");
+
+    let mut wall = element(BimCategory::Wall)?;
+    wall_type.apply_to(&mut wall);
+    assert_eq!(wall.type_id, Some(type_guid));
+    assert!(matches!(
+        wall.parameters.get("Material"),
+        Some(ParameterValue::Text(material)) if material == "Concrete"
+    ));
+    model.add_element(wall, storey).expect("add wall");
+    assert_eq!(model.instances_of(type_guid).len(), 1);
+    assert!(
+        history
+            .apply(
+                &mut model,
+                "Delete type",
+                ModelCommand::DeleteType { guid: type_guid }
+            )
+            .is_err()
+    );
+
+    let thicker = wall_type.with_parameter("Thickness", ParameterValue::Number(300.0));
+    history
+        .apply(&mut model, "Edit type", ModelCommand::ReplaceType(thicker))
+        .expect("edit type");
+    assert_eq!(
+        model
+            .element_type(type_guid)
+            .and_then(|t| t.number("Thickness")),
+        Some(300.0)
+    );
+    history.undo(&mut model).expect("undo");
+    assert_eq!(
+        model
+            .element_type(type_guid)
+            .and_then(|t| t.number("Thickness")),
+        Some(200.0)
+    );
+    Ok(())
+}
+
+#[test]
+fn commands_report_what_they_dirty() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut wall = element(BimCategory::Wall)?;
+    wall.insert_parameter("Thickness", ParameterValue::Number(200.0));
+    let guid = wall.guid;
+    model.add_element(wall.clone(), storey).expect("add wall");
+    assert_eq!(model.take_dirty().get(&guid), Some(&DirtyFlags::ALL));
+    assert!(model.take_dirty().is_empty());
+
+    // Presentation parameters leave the geometry inputs alone.
+    ModelCommand::set_layer(guid, "Walls")
+        .apply(&mut model)
+        .expect("set layer");
+    assert!(model.take_dirty().is_empty());
+
+    // Replacing a wall with an unchanged copy is a no-op for regeneration and meshing.
+    let same = model.element(guid).expect("wall").clone();
+    ModelCommand::ReplaceElements {
+        elements: vec![same.clone()],
+    }
+    .apply(&mut model)
+    .expect("replace");
+    assert!(model.take_dirty().is_empty());
+    assert!(same.is_up_to_date([]));
+
+    let mut thicker = same;
+    thicker.insert_parameter("Thickness", ParameterValue::Number(250.0));
+    assert!(!thicker.is_up_to_date([]));
+    thicker.geometry = SolidBuilder::box_solid(100.0, 250.0, 300.0)?;
+    ModelCommand::ReplaceElements {
+        elements: vec![thicker],
+    }
+    .apply(&mut model)
+    .expect("replace");
+    assert_eq!(model.take_dirty().get(&guid), Some(&DirtyFlags::ALL));
+    Ok(())
+}
+
+#[test]
+fn commands_stamp_element_revisions() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    model.author = "alice".to_string();
+    let storey = model.default_storey().expect("default storey");
+    let mut history = History::new();
+    let wall = element(BimCategory::Wall)?;
+    let slab = element(BimCategory::Slab)?;
+    let (wall_guid, slab_guid) = (wall.guid, slab.guid);
+    history
+        .apply(
+            &mut model,
+            "Add elements",
+            ModelCommand::AddElements {
+                storey,
+                elements: vec![wall, slab],
+            },
+        )
+        .expect("add");
+    assert_eq!(model.revision(), 1);
+    let stamp = &model.element(wall_guid).expect("wall").revision;
+    assert_eq!((stamp.revision, stamp.author.as_str()), (1, "alice"));
+    assert!(stamp.modified > 0);
+
+    model.author = "bob".to_string();
+    history
+        .apply(
+            &mut model,
+            "Set layer",
+            ModelCommand::set_layer(wall_guid, "Walls"),
+        )
+        .expect("set layer");
+    history
+        .apply(
+            &mut model,
+            "Delete slab",
+            ModelCommand::DeleteElement { guid: slab_guid },
+        )
+        .expect("delete");
+    assert_eq!(model.revision(), 3);
+    let changed: Vec<Guid> = model
+        .changed_since(1)
+        .iter()
+        .map(|element| element.guid)
+        .collect();
+    assert_eq!(changed, vec![wall_guid]);
+    assert_eq!(
+        model.element(wall_guid).expect("wall").revision.author,
+        "bob"
+    );
+    assert_eq!(model.removed_since(1), vec![slab_guid]);
+    assert!(model.removed_since(3).is_empty());
+
+    // Undoing is an edit of its own: the slab comes back as changed, no longer removed.
+    history.undo(&mut model).expect("undo");
+    assert_eq!(model.revision(), 4);
+    assert!(model.removed_since(1).is_empty());
+    let changed: Vec<Guid> = model
+        .changed_since(3)
+        .iter()
+        .map(|element| element.guid)
+        .collect();
+    assert_eq!(changed, vec![slab_guid]);
+    Ok(())
+}
+
+#[test]
+fn unchanged_elements_keep_their_stamp() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut history = History::new();
+    let wall = element(BimCategory::Wall)?;
+    let wall_guid = wall.guid;
+    history
+        .apply(
+            &mut model,
+            "Add wall",
+            ModelCommand::AddElements {
+                storey,
+                elements: vec![wall],
+            },
+        )
+        .expect("add");
+    history
+        .apply(
+            &mut model,
+            "Set layer",
+            ModelCommand::set_layer(wall_guid, "Walls"),
+        )
+        .expect("set layer");
+    let stamped = model.element(wall_guid).expect("wall").revision.revision;
+
+    // The same value again, and the wall handed back as it is.
+    history
+        .apply(
+            &mut model,
+            "Set layer",
+            ModelCommand::set_layer(wall_guid, "Walls"),
+        )
+        .expect("set layer again");
+    let unchanged = model.element(wall_guid).expect("wall").clone();
+    history
+        .apply(
+            &mut model,
+            "Re-join",
+            ModelCommand::ReplaceElements {
+                elements: vec![unchanged],
+            },
+        )
+        .expect("replace");
+    assert_eq!(
+        model.element(wall_guid).expect("wall").revision.revision,
+        stamped
+    );
+    assert!(model.changed_since(stamped).is_empty());
+    Ok(())
+}
+
+#[test]
+fn levels_move_and_grids_are_undoable() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let ground = model.default_storey().expect("default storey");
+    let mut history = History::new();
+
+    history
+        .apply(
+            &mut model,
+            "Move level",
+            ModelCommand::SetStoreyElevation {
+                storey: ground,
+                elevation: -150.0,
+            },
+        )
+        .expect("move level");
+    assert_eq!(model.storey(ground).expect("storey").elevation, -150.0);
+    assert!(
+        ModelCommand::SetStoreyElevation {
+            storey: ground,
+            elevation: f64::NAN,
+        }
+        .apply(&mut model)
+        .is_err()
+    );
+    history.undo(&mut model).expect("undo");
+    assert_eq!(model.storey(ground).expect("storey").elevation, 0.0);
+
+    assert!(Grid::line("A", [0.0, 0.0], [0.0, 0.0]).is_err());
+    assert!(Grid::arc("R", [0.0, 0.0], -1.0, 0.0, 90.0).is_err());
+    let grid = Grid::arc("R", [0.0, 0.0], 1000.0, 0.0, 90.0)?;
+    let guid = grid.guid;
+    let points = grid.curve.points(2);
+    assert_eq!(points.len(), 3);
+    assert!(points[2][0].abs() < 1.0e-9 && (points[2][1] - 1000.0).abs() < 1.0e-9);
+    history
+        .apply(&mut model, "Add grid", ModelCommand::AddGrid(grid))
+        .expect("add grid");
+    assert_eq!(model.grid(guid).map(|grid| grid.label.as_str()), Some("R"));
+    history.undo(&mut model).expect("undo");
+    assert!(model.grids.is_empty());
+    history.redo(&mut model).expect("redo");
+
+    // Grids follow unit conversion.
+    let converted = model.converted(Units::metric_m());
+    let Some(GridCurve::Arc { radius, .. }) = converted.grid(guid).map(|grid| grid.curve) else {
+        panic!("arc grid");
+    };
+    assert!((radius - 1.0).abs() < 1.0e-9);
+    Ok(())
+}
+
+#[test]
+fn formulas_keep_driven_parameters_in_sync() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut wall = element(BimCategory::Wall)?;
+    let guid = wall.guid;
+    wall.insert_parameter("Length", ParameterValue::Number(4000.0));
+    wall.insert_parameter("Height", ParameterValue::Integer(3000));
+    wall.insert_parameter("Area", ParameterValue::Number(1.0));
+    assert!(wall.set_formula("Area", "Length * (Height - ").is_err());
+    assert!(wall.set_formula("Area", "Width * Height").is_err());
+    wall.set_formula("Area", "Length * Height / 1000000")
+        .expect("area formula");
+    wall.set_formula("Cost", "max(Area * 85, 500)")
+        .expect("cost formula");
+    assert!(wall.set_formula("Length", "Cost / 2").is_err());
+    // A formula that breaks one reading it is rolled back along with the values it changed.
+    wall.insert_parameter("Gap", ParameterValue::Number(100.0));
+    wall.set_formula("Ratio", "Height / Gap")
+        .expect("ratio formula");
+    assert!(wall.set_formula("Gap", "Length - 4000").is_err());
+    assert_eq!(wall.formula("Gap"), None);
+    assert_eq!(
+        wall.parameters.get("Gap"),
+        Some(&ParameterValue::Number(100.0))
+    );
+    assert_eq!(
+        wall.parameters.get("Ratio"),
+        Some(&ParameterValue::Number(30.0))
+    );
+    wall.remove_formula("Ratio");
+    assert_eq!(
+        wall.driven_parameters("Length"),
+        vec!["Area".to_string(), "Cost".to_string()]
+    );
+    let area = |model: &BimModel| {
+        model
+            .element(guid)
+            .and_then(|wall| wall.parameters.get("Area").cloned())
+    };
+    model.add_element(wall, storey).expect("add wall");
+    assert_eq!(area(&model), Some(ParameterValue::Number(12.0)));
+
+    let mut history = History::new();
+    let set_length = ModelCommand::SetParameter {
+        guid,
+        name: "Length".to_string(),
+        value: Some(ParameterValue::Number(5000.0)),
+    };
+    history
+        .apply(&mut model, "Set length", set_length)
+        .expect("set length");
+    let wall = model.element(guid).expect("wall");
+    assert_eq!(area(&model), Some(ParameterValue::Number(15.0)));
+    assert_eq!(
+        wall.evaluate("Cost").ok(),
+        Some(ParameterValue::Number(1275.0))
+    );
+    let set_area = ModelCommand::SetParameter {
+        guid,
+        name: "Area".to_string(),
+        value: Some(ParameterValue::Number(1.0)),
+    };
+    assert!(set_area.apply(&mut model).is_err());
+
+    // Removing the formula leaves the last value; undo brings the formula back.
+    history
+        .apply(
+            &mut model,
+            "Remove formula",
+            ModelCommand::SetFormula {
+                guid,
+                name: "Area".to_string(),
+                expression: None,
+            },
+        )
+        .expect("remove formula");
+    assert_eq!(
+        model.element(guid).and_then(|wall| wall.formula("Area")),
+        None
+    );
+    assert_eq!(area(&model), Some(ParameterValue::Number(15.0)));
+    history.undo(&mut model).expect("undo");
+    history.undo(&mut model).expect("undo");
+    let wall = model.element(guid).expect("wall");
+    assert_eq!(wall.formula("Area"), Some("Length * Height / 1000000"));
+    assert_eq!(area(&model), Some(ParameterValue::Number(12.0)));
+    Ok(())
+}
+
+#[test]
+fn parameter_changes_are_logged_with_their_source() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    model.author = "alice".to_string();
+    model.source = ChangeSource::Cli;
+    let storey = model.default_storey().expect("default storey");
+    let mut history = History::new();
+    let mut wall = element(BimCategory::Wall)?;
+    wall.insert_parameter("Width", ParameterValue::Number(200.0));
+    let guid = wall.guid;
+    history
+        .apply(
+            &mut model,
+            "Add wall",
+            ModelCommand::AddElements {
+                storey,
+                elements: vec![wall],
+            },
+        )
+        .expect("add");
+    history
+        .apply(
+            &mut model,
+            "Drive area",
+            ModelCommand::SetFormula {
+                guid,
+                name: "Double".to_string(),
+                expression: Some("Width * 2".to_string()),
+            },
+        )
+        .expect("formula");
+    model.source = ChangeSource::Script;
+    history
+        .apply(
+            &mut model,
+            "Widen",
+            ModelCommand::SetParameter {
+                guid,
+                name: "Width".to_string(),
+                value: Some(ParameterValue::Number(250.0)),
+            },
+        )
+        .expect("set");
+
+    // Adding the wall logs nothing; the formula's value and its refresh are logged.
+    let log = model.parameter_history(guid);
+    let changes: Vec<(&str, Option<&ParameterValue>, u64, ChangeSource)> = log
+        .iter()
+        .map(|entry| {
+            (
+                entry.name.as_str(),
+                entry.new.as_ref(),
+                entry.revision,
+                entry.source,
+            )
+        })
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (
+                "Double",
+                Some(&ParameterValue::Number(400.0)),
+                2,
+                ChangeSource::Cli
+            ),
+            (
+                "Double",
+                Some(&ParameterValue::Number(500.0)),
+                3,
+                ChangeSource::Script
+            ),
+            (
+                "Width",
+                Some(&ParameterValue::Number(250.0)),
+                3,
+                ChangeSource::Script
+            ),
+        ]
+    );
+    assert_eq!(log[2].old, Some(ParameterValue::Number(200.0)));
+    assert_eq!(log[2].author, "alice");
+
+    // Undo is logged as the change back.
+    history.undo(&mut model).expect("undo");
+    let last = model.parameter_log().last().expect("undo entry");
+    assert_eq!(
+        (last.name.as_str(), last.new.clone(), last.revision),
+        ("Width", Some(ParameterValue::Number(200.0)), 4)
+    );
+    assert!(model.parameter_history(Guid::new()).is_empty());
+    Ok(())
+}
+
+#[test]
+fn journal_replays_the_session() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let base = model.clone();
+    let mut history = History::new();
+    let wall = element(BimCategory::Wall)?;
+    let guid = wall.guid;
+    history
+        .apply(
+            &mut model,
+            "Add wall",
+            ModelCommand::AddElements {
+                storey,
+                elements: vec![wall],
+            },
+        )
+        .expect("add");
+    history
+        .apply(&mut model, "Layer", ModelCommand::set_layer(guid, "Walls"))
+        .expect("layer");
+    history.undo(&mut model).expect("undo");
+    history.redo(&mut model).expect("redo");
+    history
+        .apply(&mut model, "Layer", ModelCommand::set_layer(guid, "Cores"))
+        .expect("layer");
+
+    let journal = history.journal();
+    let labels: Vec<&str> = journal
+        .entries
+        .iter()
+        .map(|entry| entry.label.as_str())
+        .collect();
+    assert_eq!(
+        labels,
+        vec!["Add wall", "Layer", "Undo Layer", "Redo Layer", "Layer"]
+    );
+    assert_eq!(journal.model, "Test");
+    assert_eq!(journal.base_revision, base.revision());
+
+    let mut replayed = base.clone();
+    assert_eq!(journal.replay(&mut replayed).expect("replay"), 5);
+    assert_eq!(replayed.revision(), model.revision());
+    assert_eq!(layer(&replayed, guid).as_deref(), Some("Cores"));
+
+    // Only the model the session started from takes the journal.
+    assert!(journal.replay(&mut replayed).is_err());
+    history.clear();
+    assert!(history.journal().is_empty());
+    Ok(())
+}
+
+#[test]
+fn layers_and_views_are_journaled() {
+    let mut model = BimModel::new("Test");
+    let base = model.clone();
+    let mut history = History::new();
+    let walls = Layer::new("Walls", [200, 0, 0, 255]);
+    history
+        .apply(
+            &mut model,
+            "Add layer",
+            ModelCommand::AddLayer(walls.clone()),
+        )
+        .expect("add layer");
+    assert!(
+        history
+            .apply(&mut model, "Add layer", ModelCommand::AddLayer(walls))
+            .is_err()
+    );
+    history
+        .apply(
+            &mut model,
+            "Add view",
+            ModelCommand::AddView(NamedView::new("Fire")),
+        )
+        .expect("add view");
+    // Named views are presentation, which read-only sessions may change.
+    assert!(
+        history
+            .undo_command()
+            .is_some_and(ModelCommand::is_annotation)
+    );
+    let missing = NamedView::new("Acoustic");
+    assert!(
+        history
+            .apply(&mut model, "Edit view", ModelCommand::ReplaceView(missing))
+            .is_err()
+    );
+    history.undo(&mut model).expect("undo");
+    assert!(model.views.is_empty());
+    history.redo(&mut model).expect("redo");
+
+    let mut replayed = base.clone();
+    history.journal().replay(&mut replayed).expect("replay");
+    assert_eq!(replayed.layers, model.layers);
+    assert_eq!(replayed.views, model.views);
+    assert!(replayed.layers.iter().any(|layer| layer.name == "Walls"));
+}
+
+#[test]
+fn phases_are_undoable_and_journaled() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let base = model.clone();
+    let mut history = History::new();
+    let existing = Phase::new("Existing");
+    let new = Phase::new("New Construction");
+    let (existing_guid, new_guid) = (existing.guid, new.guid);
+    history
+        .apply(
+            &mut model,
+            "Add phase",
+            ModelCommand::AddPhase {
+                index: 0,
+                phase: new,
+            },
+        )
+        .expect("add phase");
+    history
+        .apply(
+            &mut model,
+            "Add phase",
+            ModelCommand::AddPhase {
+                index: 0,
+                phase: existing,
+            },
+        )
+        .expect("add phase");
+    let order: Vec<Guid> = model.phases.iter().map(|phase| phase.guid).collect();
+    assert_eq!(order, [existing_guid, new_guid]);
+    assert!(
+        history
+            .undo_command()
+            .is_some_and(|command| !command.is_annotation())
+    );
+
+    let mut wall = element(BimCategory::Wall)?;
+    wall.phase_created = Some(new_guid);
+    history
+        .apply(
+            &mut model,
+            "Add wall",
+            ModelCommand::AddElements {
+                storey,
+                elements: vec![wall],
+            },
+        )
+        .expect("add wall");
+    // A phase elements are built in cannot go.
+    assert!(
+        history
+            .apply(
+                &mut model,
+                "Delete phase",
+                ModelCommand::DeletePhase { guid: new_guid }
+            )
+            .is_err()
+    );
+    history
+        .apply(
+            &mut model,
+            "Delete phase",
+            ModelCommand::DeletePhase {
+                guid: existing_guid,
+            },
+        )
+        .expect("delete phase");
+    assert_eq!(model.phases.len(), 1);
+    // Undoing the delete puts the phase back in its place in build order.
+    history.undo(&mut model).expect("undo");
+    assert_eq!(model.phases[0].guid, existing_guid);
+
+    let mut replayed = base.clone();
+    history.journal().replay(&mut replayed).expect("replay");
+    assert_eq!(replayed.phases, model.phases);
+    Ok(())
+}
+
+#[test]
+fn merged_edits_stay_apart_across_elements() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut history = History::new();
+    // Both walls carry the default name, so the label alone cannot tell them apart.
+    let first = element(BimCategory::Wall)?;
+    let second = element(BimCategory::Wall)?;
+    let (first_guid, second_guid) = (first.guid, second.guid);
+    history.apply(
+        &mut model,
+        "Add walls",
+        ModelCommand::AddElements {
+            storey,
+            elements: vec![first, second],
+        },
+    )?;
+
+    for layer_name in ["A", "B"] {
+        history.apply_merged(
+            &mut model,
+            "Edit Element",
+            ModelCommand::set_layer(first_guid, layer_name),
+        )?;
+    }
+    history.apply_merged(
+        &mut model,
+        "Edit Element",
+        ModelCommand::set_layer(second_guid, "C"),
+    )?;
+
+    history.undo(&mut model)?;
+    assert_eq!(layer(&model, first_guid).as_deref(), Some("B"));
+    assert_eq!(layer(&model, second_guid), None);
+    history.undo(&mut model)?;
+    assert_eq!(layer(&model, first_guid), None);
+    assert_eq!(history.undo_label(), Some("Add walls"));
+    Ok(())
+}
+
+#[test]
+fn merged_edits_stay_apart_across_parameters() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut history = History::new();
+    let wall = element(BimCategory::Wall)?;
+    let guid = wall.guid;
+    history.apply(
+        &mut model,
+        "Add wall",
+        ModelCommand::AddElements {
+            storey,
+            elements: vec![wall],
+        },
+    )?;
+
+    let set = |name: &str, value: f64| ModelCommand::SetParameter {
+        guid,
+        name: name.to_string(),
+        value: Some(ParameterValue::Number(value)),
+    };
+    let number = |model: &BimModel, name: &str| model.element(guid)?.parameters.get(name).cloned();
+    for height in [2800.0, 3000.0] {
+        history.apply_merged(&mut model, "Edit Element", set("Height", height))?;
+    }
+    history.apply_merged(&mut model, "Edit Element", set("Width", 250.0))?;
+    history.apply_merged(&mut model, "Edit Element", set("Width", 300.0))?;
+
+    // Each parameter undoes on its own, the drags on one parameter in one step.
+    history.undo(&mut model)?;
+    assert_eq!(number(&model, "Width"), None);
+    assert_eq!(
+        number(&model, "Height"),
+        Some(ParameterValue::Number(3000.0))
+    );
+    history.undo(&mut model)?;
+    assert_eq!(number(&model, "Height"), None);
+    assert_eq!(history.undo_label(), Some("Add wall"));
+    Ok(())
+}
+
+#[test]
+fn welds_and_issues_are_annotations() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut history = History::new();
+    let plate = element(BimCategory::Generic)?;
+    let guid = plate.guid;
+    history.apply(
+        &mut model,
+        "Add plate",
+        ModelCommand::AddElements {
+            storey,
+            elements: vec![plate],
+        },
+    )?;
+
+    let weld = WeldAnnotation {
+        edge: 0,
+        kind: WeldKind::Fillet,
+        size: 6.0,
+        side: WeldSide::Arrow,
+        all_around: false,
+        field: false,
+    };
+    let welds = ModelCommand::SetWelds {
+        guid,
+        welds: vec![weld],
+    };
+    assert!(welds.is_annotation());
+    history.apply(&mut model, "Add weld", welds)?;
+    assert_eq!(model.element(guid).expect("plate").welds, vec![weld]);
+    let off_edge = ModelCommand::SetWelds {
+        guid,
+        welds: vec![WeldAnnotation { edge: 99, ..weld }],
+    };
+    assert!(history.apply(&mut model, "Add weld", off_edge).is_err());
+    history.undo(&mut model)?;
+    assert!(model.element(guid).expect("plate").welds.is_empty());
+
+    let issue = Issue::new("Plate clashes with duct", [guid]).with_comment("Reviewer", "Move it");
+    let issue_guid = issue.guid;
+    let raise = ModelCommand::AddIssue(issue.clone());
+    assert!(raise.is_annotation());
+    history.apply(&mut model, "Raise issue", raise)?;
+    assert_eq!(model.open_issues_of(guid).len(), 1);
+
+    let mut closed = issue.with_comment("Modeler", "Moved");
+    closed.closed = true;
+    let close = ModelCommand::ReplaceIssue(closed);
+    assert!(close.is_annotation());
+    history.apply(&mut model, "Close issue", close)?;
+    let stored = model.issue(issue_guid).expect("issue");
+    assert_eq!(stored.comments.len(), 2);
+    assert!(model.open_issues_of(guid).is_empty());
+
+    history.undo(&mut model)?;
+    assert_eq!(model.open_issues_of(guid).len(), 1);
+    history.apply(
+        &mut model,
+        "Delete issue",
+        ModelCommand::DeleteIssue { guid: issue_guid },
+    )?;
+    assert!(model.issues.is_empty());
+    Ok(())
+}
@@ -0,0 +1,1138 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use cryxtal_base::{AngleUnit, Guid, LengthUnit, Units};
+use cryxtal_bim::{
+    BimCategory, BimElement, BimElementType, BimModel, Constraint, CoordinateTransform,
+    DENSITY_KEY, ElementGroup, FilterOperator, FilterRule, GraphicOverride, GroupKind,
+    HatchPattern, History, ModelCommand, NamedView, Opening, OpeningPlacement, OpeningProfile,
+    ParameterSet, ParameterUnit, ParameterValue, Phase, PhaseStatus, ViewFilter, bill_of_materials,
+    diff, takeoff,
+};
+use cryxtal_topology::{
+    Matrix4, Point3, Result, SolidBuilder, Vector3, solid_bounds, solid_edges, transform_solid,
+};
+use truck_modeling::builder;
+
+fn element(category: BimCategory) -> Result<BimElement> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    Ok(BimElement::new(
+        Guid::new(),
+        "Element",
+        category,
+        ParameterSet::new(),
+        solid,
+    ))
+}
+
+#[test]
+fn elements_are_queried_by_storey() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let ground = model.default_storey().expect("default storey");
+    let building = model.site.buildings[0].guid;
+    let upper = model
+        .add_storey(building, "Level 1", 3000.0)
+        .expect("storey");
+
+    let wall = element(BimCategory::Wall)?;
+    let slab = element(BimCategory::Slab)?;
+    let (wall_guid, slab_guid) = (wall.guid, slab.guid);
+    model.add_element(wall, ground).expect("add wall");
+    model.add_element(slab, upper).expect("add slab");
+
+    let on_ground: Vec<Guid> = model
+        .elements_on_storey(ground)
+        .iter()
+        .map(|element| element.guid)
+        .collect();
+    assert_eq!(on_ground, vec![wall_guid]);
+    assert_eq!(
+        model.storey_of(slab_guid).map(|storey| storey.guid),
+        Some(upper)
+    );
+
+    model.move_to_storey(wall_guid, upper).expect("move");
+    assert!(model.elements_on_storey(ground).is_empty());
+    assert_eq!(model.elements_on_storey(upper).len(), 2);
+    Ok(())
+}
+
+#[test]
+fn elements_are_filtered_with_queries() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut low = element(BimCategory::Wall)?;
+    low.insert_parameter("Height", ParameterValue::Number(2800.0));
+    let mut high = element(BimCategory::Wall)?;
+    high.insert_parameter("Height", ParameterValue::Number(3500.0));
+    high.insert_parameter("Layer", ParameterValue::Text("Structure".to_string()));
+    high.geometry = transform_solid(
+        &high.geometry,
+        Matrix4::from_translation(Vector3::new(5000.0, 0.0, 0.0)),
+    );
+    let slab = element(BimCategory::Slab)?;
+    let (low_guid, high_guid) = (low.guid, high.guid);
+    for element in [low, high, slab] {
+        model.add_element(element, storey).expect("add element");
+    }
+
+    let tall = model
+        .query()
+        .category(BimCategory::Wall)
+        .where_num("Height", |height| height > 3000.0)
+        .guids();
+    assert_eq!(tall, vec![high_guid]);
+    assert_eq!(model.query().layer("Default").count(), 2);
+    assert_eq!(model.query().layer("Structure").guids(), vec![high_guid]);
+    assert_eq!(model.query().text("STRUCT").guids(), vec![high_guid]);
+    let guid_prefix = low_guid.to_string()[..8].to_string();
+    assert_eq!(model.query().text(&guid_prefix).guids(), vec![low_guid]);
+    let near_origin = model
+        .query()
+        .category(BimCategory::Wall)
+        .intersecting(
+            Point3::new(-10.0, -10.0, -10.0),
+            Point3::new(50.0, 50.0, 50.0),
+        )
+        .guids();
+    assert_eq!(near_origin, vec![low_guid]);
+    assert_eq!(
+        model
+            .query()
+            .inside(
+                Point3::new(4000.0, -1.0, -1.0),
+                Point3::new(6000.0, 300.0, 400.0)
+            )
+            .indices(),
+        vec![1]
+    );
+    Ok(())
+}
+
+#[test]
+fn removing_a_host_removes_its_openings() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let wall = element(BimCategory::Wall)?;
+    let wall_guid = wall.guid;
+    let opening = Opening::new(
+        wall_guid,
+        OpeningProfile {
+            width: 900.0,
+            height: 2100.0,
+        },
+        OpeningPlacement {
+            center_x: 500.0,
+            center_y: 0.0,
+            center_z: 1050.0,
+        },
+    );
+    let opening_element = BimElement::opening(
+        opening,
+        "Opening 1",
+        ParameterSet::new(),
+        SolidBuilder::box_solid(900.0, 200.0, 2100.0)?,
+    );
+    model.add_element(wall, storey).expect("add wall");
+    model
+        .add_element(opening_element, storey)
+        .expect("add opening");
+    assert_eq!(model.hosted_openings(wall_guid).len(), 1);
+
+    let removed = model.remove_element(wall_guid);
+    assert_eq!(removed.len(), 2);
+    assert_eq!(removed[0].guid, wall_guid);
+    assert!(model.elements().is_empty());
+    assert!(model.elements_on_storey(storey).is_empty());
+    Ok(())
+}
+
+#[test]
+fn openings_register_cuts_on_other_hosts_they_pass_through() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let wall = element(BimCategory::Wall)?;
+    let mut slab = element(BimCategory::Slab)?;
+    slab.geometry = transform_solid(
+        &slab.geometry,
+        Matrix4::from_translation(Vector3::new(0.0, 200.0, 0.0)),
+    );
+    let mut far = element(BimCategory::Slab)?;
+    far.geometry = transform_solid(
+        &far.geometry,
+        Matrix4::from_translation(Vector3::new(0.0, 0.0, 300.0)),
+    );
+    let (wall_guid, slab_guid) = (wall.guid, slab.guid);
+    for element in [wall, slab, far] {
+        model.add_element(element, storey).expect("add element");
+    }
+
+    // Spans the wall and reaches into the slab beside it; the far slab only touches it.
+    let profile = OpeningProfile {
+        width: 50.0,
+        height: 100.0,
+    };
+    let placement = OpeningPlacement {
+        center_x: 50.0,
+        center_y: 0.0,
+        center_z: 100.0,
+    };
+    let mut opening = BimElement::opening(
+        Opening::new(wall_guid, profile, placement),
+        "Duct",
+        ParameterSet::new(),
+        SolidBuilder::box_solid(50.0, 300.0, 300.0)?,
+    );
+    assert_eq!(model.cut_hosts(&opening), vec![slab_guid]);
+
+    opening.cuts = model.cut_hosts(&opening);
+    model.add_element(opening, storey).expect("add opening");
+    assert_eq!(model.cutting_openings(slab_guid), vec![3]);
+    assert!(model.cutting_openings(wall_guid).is_empty());
+    Ok(())
+}
+
+#[test]
+fn hosted_rebar_outlives_its_host_as_an_orphan() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let wall = element(BimCategory::Wall)?;
+    let wall_guid = wall.guid;
+    let mut rebar = element(BimCategory::Rebar)?;
+    rebar.host = Some(wall_guid);
+    let rebar_guid = rebar.guid;
+    model.add_element(wall.clone(), storey).expect("add wall");
+    model.add_element(rebar, storey).expect("add rebar");
+    assert_eq!(model.hosted_elements(wall_guid).len(), 1);
+    assert!(model.orphans().is_empty());
+
+    let removed = model.remove_element(wall_guid);
+    assert_eq!(removed.len(), 1);
+    let orphans = model.orphans();
+    assert_eq!(orphans.len(), 1);
+    assert_eq!(orphans[0].guid, rebar_guid);
+
+    model.add_element(wall, storey).expect("re-add wall");
+    assert!(model.orphans().is_empty());
+    Ok(())
+}
+
+#[test]
+fn deep_clone_mirrors_openings_and_hosted_rebar() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut wall = element(BimCategory::Wall)?;
+    wall.insert_parameter("StartX", ParameterValue::Number(1000.0));
+    wall.insert_parameter("StartY", ParameterValue::Number(0.0));
+    wall.insert_parameter("StartZ", ParameterValue::Number(0.0));
+    let wall_guid = wall.guid;
+    let opening = Opening::new(
+        wall_guid,
+        OpeningProfile {
+            width: 50.0,
+            height: 100.0,
+        },
+        OpeningPlacement {
+            center_x: 50.0,
+            center_y: 0.0,
+            center_z: 150.0,
+        },
+    );
+    let opening = BimElement::opening(
+        opening,
+        "Opening 1",
+        ParameterSet::new(),
+        SolidBuilder::box_solid(50.0, 200.0, 100.0)?,
+    );
+    let mut rebar = element(BimCategory::Rebar)?;
+    rebar.host = Some(wall_guid);
+    rebar.insert_parameter("Face", ParameterValue::Text("Left".to_string()));
+    model.add_element(wall, storey).expect("add wall");
+    model.add_element(opening, storey).expect("add opening");
+    model.add_element(rebar, storey).expect("add rebar");
+
+    let copies = model
+        .deep_clone(wall_guid, Matrix4::from_nonuniform_scale(-1.0, 1.0, 1.0))
+        .expect("deep clone");
+    assert_eq!(copies.len(), 3);
+    let copy = &copies[0];
+    assert_ne!(copy.guid, wall_guid);
+    assert_eq!(
+        copy.parameters.get("StartX"),
+        Some(&ParameterValue::Number(-1000.0))
+    );
+    assert!(
+        copy.geometry
+            .vertex_iter()
+            .all(|vertex| vertex.point().x <= 0.0)
+    );
+    let opening = copies[1].opening.expect("copied opening");
+    assert_eq!((opening.guid, opening.host), (copies[1].guid, copy.guid));
+    assert_eq!(opening.placement.center_x, 50.0);
+    assert_eq!(copies[2].host, Some(copy.guid));
+    assert_eq!(
+        copies[2].parameters.get("Face"),
+        Some(&ParameterValue::Text("Right".to_string()))
+    );
+
+    for copy in copies {
+        model.add_element(copy, storey).expect("add copy");
+    }
+    assert_eq!(model.elements().len(), 6);
+    assert!(model.orphans().is_empty());
+    Ok(())
+}
+
+#[test]
+fn duplicating_a_wall_remaps_its_openings_and_hosted_rebar() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let wall = element(BimCategory::Wall)?;
+    let wall_guid = wall.guid;
+    let opening = Opening::new(
+        wall_guid,
+        OpeningProfile {
+            width: 900.0,
+            height: 2100.0,
+        },
+        OpeningPlacement {
+            center_x: 500.0,
+            center_y: 0.0,
+            center_z: 1050.0,
+        },
+    );
+    let opening_element = BimElement::opening(
+        opening,
+        "Opening 1",
+        ParameterSet::new(),
+        SolidBuilder::box_solid(900.0, 200.0, 2100.0)?,
+    );
+    let mut rebar = element(BimCategory::Rebar)?;
+    rebar.host = Some(wall_guid);
+    model.add_element(wall, storey).expect("add wall");
+    model
+        .add_element(opening_element, storey)
+        .expect("add opening");
+    model.add_element(rebar, storey).expect("add rebar");
+
+    // The opening is listed with its host, so it is copied once.
+    let command = model
+        .duplicate(&[wall_guid, opening.guid], Vector3::new(0.0, 3000.0, 0.0))
+        .expect("duplicate");
+    assert_eq!(model.elements().len(), 3);
+    let added = command.targets();
+    assert_eq!(added.len(), 3);
+    let inverse = command.apply(&mut model).expect("apply");
+    assert_eq!(model.elements().len(), 6);
+    assert_eq!(model.elements_on_storey(storey).len(), 6);
+    let copy = *added
+        .iter()
+        .find(|guid| model.element(**guid).expect("copy").category == BimCategory::Wall)
+        .expect("wall copy");
+    assert_ne!(copy, wall_guid);
+    assert_eq!(model.hosted_openings(copy).len(), 1);
+    assert_eq!(model.hosted_elements(copy).len(), 1);
+    assert_eq!(model.hosted_openings(wall_guid).len(), 1);
+    let min_y = model
+        .element(copy)
+        .expect("copy")
+        .geometry
+        .vertex_iter()
+        .map(|vertex| vertex.point().y)
+        .fold(f64::INFINITY, f64::min);
+    assert!((min_y - 3000.0).abs() < 1.0e-9);
+
+    // Undoing removes every copy again.
+    inverse.apply(&mut model).expect("undo");
+    assert_eq!(model.elements().len(), 3);
+    assert!(added.iter().all(|guid| model.element(*guid).is_none()));
+    Ok(())
+}
+
+#[test]
+fn elements_are_copied_to_other_storeys_with_their_hosted_elements() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let ground = model.default_storey().expect("default storey");
+    let building = model.site.buildings[0].guid;
+    let upper = model
+        .add_storey(building, "Level 1", 3000.0)
+        .expect("storey");
+    let mut column = element(BimCategory::Column)?;
+    for key in ["StartX", "StartY", "StartZ"] {
+        column.insert_parameter(key, ParameterValue::Number(0.0));
+    }
+    column.insert_parameter("BaseStorey", ParameterValue::Text(ground.to_string()));
+    column.insert_parameter("BaseOffset", ParameterValue::Number(0.0));
+    column.insert_parameter("TopStorey", ParameterValue::Text(upper.to_string()));
+    column.insert_parameter("TopOffset", ParameterValue::Number(0.0));
+    let mut rebar = element(BimCategory::Rebar)?;
+    rebar.host = Some(column.guid);
+    let column_guid = column.guid;
+    model.add_element(column, ground).expect("add column");
+    model.add_element(rebar, ground).expect("add rebar");
+
+    let copies = model
+        .copy_to_storeys(&[column_guid], &[ground, upper])
+        .expect("copy");
+    assert_eq!(copies.len(), 1);
+    let (storey, elements) = &copies[0];
+    assert_eq!(*storey, upper);
+    assert_eq!(elements.len(), 2);
+    let column = &elements[0];
+    assert_ne!(column.guid, column_guid);
+    assert_eq!(elements[1].host, Some(column.guid));
+    assert!(matches!(
+        column.parameters.get("StartZ"),
+        Some(ParameterValue::Number(z)) if (*z - 3000.0).abs() < 1.0e-9
+    ));
+    assert!(matches!(
+        column.parameters.get("BaseStorey"),
+        Some(ParameterValue::Text(guid)) if *guid == upper.to_string()
+    ));
+    // There is no storey above the top one to follow.
+    assert!(!column.parameters.contains_key("TopStorey"));
+    assert!(!column.parameters.contains_key("TopOffset"));
+    Ok(())
+}
+
+#[test]
+fn assemblies_are_copied_and_moved_with_their_hosted_elements() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let wall = element(BimCategory::Wall)?;
+    let wall_guid = wall.guid;
+    let mut rebar = element(BimCategory::Rebar)?;
+    rebar.host = Some(wall_guid);
+    let rebar_guid = rebar.guid;
+    model.add_element(wall, storey).expect("add wall");
+    model.add_element(rebar, storey).expect("add rebar");
+    let panel = ElementGroup::new("Panel P1", GroupKind::Assembly).with_members([wall_guid]);
+    let panel_guid = panel.guid;
+    ModelCommand::AddGroup(panel)
+        .apply(&mut model)
+        .expect("add group");
+
+    assert_eq!(
+        model.group_elements(panel_guid).expect("members"),
+        vec![wall_guid, rebar_guid]
+    );
+    assert_eq!(model.groups_of(rebar_guid).len(), 1);
+
+    let shift = Matrix4::from_translation(Vector3::new(5000.0, 0.0, 0.0));
+    let (copy, elements) = model.copy_group(panel_guid, shift).expect("copy");
+    assert_ne!(copy.guid, panel_guid);
+    assert_eq!(copy.kind, GroupKind::Assembly);
+    assert_eq!(copy.members, vec![elements[0].guid]);
+    assert_eq!(elements[1].host, Some(elements[0].guid));
+
+    let moved = model.moved_group(panel_guid, shift).expect("move");
+    assert_eq!(moved[0].guid, wall_guid);
+    assert_eq!(moved[1].host, Some(wall_guid));
+    let min_x = moved[0]
+        .geometry
+        .vertex_iter()
+        .map(|vertex| vertex.point().x)
+        .fold(f64::INFINITY, f64::min);
+    assert!((min_x - 5000.0).abs() < 1.0e-9);
+
+    // Deleting a member leaves the group in place, so undoing the delete restores it.
+    let undo = ModelCommand::DeleteElement { guid: wall_guid }
+        .apply(&mut model)
+        .expect("delete");
+    assert!(
+        model
+            .group_elements(panel_guid)
+            .expect("members")
+            .is_empty()
+    );
+    undo.apply(&mut model).expect("undo");
+    assert_eq!(model.group_elements(panel_guid).expect("members").len(), 2);
+    Ok(())
+}
+
+#[test]
+fn view_filters_override_matching_element_colors() -> Result<()> {
+    let rule: FilterRule = "FireRating=EI60".parse().expect("rule");
+    assert_eq!(rule.operator, FilterOperator::Equals);
+    assert_eq!(rule.to_string(), "FireRating=EI60");
+    assert_eq!(
+        "Height!=3000".parse::<FilterRule>().expect("rule").operator,
+        FilterOperator::NotEquals
+    );
+    assert!("=EI60".parse::<FilterRule>().is_err());
+    let at_least: FilterRule = "Height>=3000".parse().expect("rule");
+    assert_eq!(at_least.operator, FilterOperator::GreaterOrEqual);
+    assert_eq!(at_least.value, "3000");
+    assert_eq!(at_least.to_string(), "Height>=3000");
+    assert_eq!(
+        "Height<=3000".parse::<FilterRule>().expect("rule").operator,
+        FilterOperator::LessOrEqual
+    );
+
+    let red = GraphicOverride {
+        color: Some([255, 0, 0]),
+        ..GraphicOverride::default()
+    };
+    let ghosted = GraphicOverride {
+        transparency: Some(0.5),
+        ..GraphicOverride::default()
+    };
+    let view = NamedView::new("Fire")
+        .with_filter(ViewFilter::new("EI60", rule, red))
+        .with_filter(ViewFilter::new(
+            "Tall",
+            "Height>3000".parse().expect("rule"),
+            ghosted,
+        ));
+
+    let mut wall = element(BimCategory::Wall)?;
+    wall.insert_parameter("FireRating", ParameterValue::Text("EI60".to_string()));
+    wall.insert_parameter("Height", ParameterValue::Number(3500.0));
+    let graphics = view.override_for(&wall).expect("override");
+    assert_eq!(graphics.apply([180, 190, 200, 255]), [255, 0, 0, 128]);
+    let slab = element(BimCategory::Slab)?;
+    assert!(view.override_for(&slab).is_none());
+
+    // Numbers compare by value, also when stored as text: 900 < 3000 though "900" > "3000".
+    let mut door = element(BimCategory::Door)?;
+    door.insert_parameter("Height", ParameterValue::Text("900".to_string()));
+    assert!(!at_least.matches(&door));
+    assert!(
+        "Height<3000"
+            .parse::<FilterRule>()
+            .expect("rule")
+            .matches(&door)
+    );
+    door.insert_parameter("Height", ParameterValue::Integer(3000));
+    assert!(at_least.matches(&door));
+    assert!(
+        !"Height>3000"
+            .parse::<FilterRule>()
+            .expect("rule")
+            .matches(&door)
+    );
+    Ok(())
+}
+
+#[test]
+fn model_diff_lists_added_removed_and_modified_elements() -> Result<()> {
+    let mut old = BimModel::new("Test");
+    let storey = old.default_storey().expect("default storey");
+    let mut wall = element(BimCategory::Wall)?;
+    wall.insert_parameter("Height", ParameterValue::Number(3000.0));
+    let wall_guid = wall.guid;
+    let slab = element(BimCategory::Slab)?;
+    let slab_guid = slab.guid;
+    let beam = element(BimCategory::Beam)?;
+    let beam_guid = beam.guid;
+    old.add_element(wall, storey).expect("add wall");
+    old.add_element(slab, storey).expect("add slab");
+    old.add_element(beam, storey).expect("add beam");
+    assert!(diff(&old, &old.clone()).is_empty());
+
+    let mut new = old.clone();
+    new.remove_element(slab_guid);
+    let column = element(BimCategory::Column)?;
+    let column_guid = column.guid;
+    new.add_element(column, storey).expect("add column");
+    let index = new.element_index(wall_guid).expect("wall");
+    let wall = &mut new.elements_mut()[index];
+    wall.insert_parameter("Height", ParameterValue::Number(3500.0));
+    wall.geometry = SolidBuilder::box_solid(100.0, 200.0, 3500.0)?;
+    let index = new.element_index(beam_guid).expect("beam");
+    new.elements_mut()[index].name = "Beam B1".to_string();
+
+    let changes = diff(&old, &new);
+    assert_eq!(changes.added, vec![column_guid]);
+    assert_eq!(changes.removed, vec![slab_guid]);
+    assert_eq!(changes.modified.len(), 2);
+    let wall = &changes.modified[0];
+    assert_eq!(wall.guid, wall_guid);
+    assert!(wall.geometry);
+    assert_eq!(wall.parameters.len(), 1);
+    assert_eq!(wall.parameters[0].old, Some(ParameterValue::Number(3000.0)));
+    assert_eq!(wall.parameters[0].new, Some(ParameterValue::Number(3500.0)));
+    let beam = &changes.modified[1];
+    assert_eq!(beam.fields, vec!["Name"]);
+    assert!(!beam.geometry);
+    Ok(())
+}
+
+#[test]
+fn model_diff_compares_curved_edges() -> Result<()> {
+    let mut old = BimModel::new("Test");
+    let storey = old.default_storey().expect("default storey");
+    let wall = element(BimCategory::Wall)?;
+    let wall_guid = wall.guid;
+    old.add_element(wall, storey).expect("add wall");
+
+    // A box built again has new topology but the same content, and its edges are not shared
+    // with the old model's.
+    let mut new = old.clone();
+    let index = new.element_index(wall_guid).expect("wall");
+    new.elements_mut()[index].geometry = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    assert!(diff(&old, &new).is_empty());
+
+    // Bending an edge into an arc keeps every vertex where it was.
+    let edge = solid_edges(&new.elements()[index].geometry)
+        .into_iter()
+        .find(|edge| {
+            let ends = [edge.absolute_front().point(), edge.absolute_back().point()];
+            ends.contains(&Point3::new(0.0, 0.0, 0.0))
+                && ends.contains(&Point3::new(100.0, 0.0, 0.0))
+        })
+        .expect("edge along x");
+    let arc = builder::circle_arc(
+        edge.absolute_front(),
+        edge.absolute_back(),
+        Point3::new(50.0, -20.0, 0.0),
+    );
+    edge.set_curve(arc.curve());
+    let changes = diff(&old, &new);
+    assert_eq!(changes.modified.len(), 1);
+    assert!(changes.modified[0].geometry);
+    assert!(changes.modified[0].parameters.is_empty());
+    Ok(())
+}
+
+#[test]
+fn converting_units_scales_lengths_and_geometry() -> Result<()> {
+    assert!((LengthUnit::Meter.convert(2.5, LengthUnit::Millimeter) - 2500.0).abs() < 1.0e-9);
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut beam = element(BimCategory::Beam)?;
+    beam.insert_parameter("StartX", ParameterValue::Number(1000.0));
+    beam.insert_parameter("ProfileWidth", ParameterValue::Number(200.0));
+    beam.insert_parameter("Roll", ParameterValue::Number(90.0));
+    // Units come from the key itself, not its ending.
+    beam.insert_parameter("CamberSize", ParameterValue::Number(20.0));
+    beam.insert_parameter("Point2X", ParameterValue::Number(500.0));
+    beam.insert_parameter("ClearHeight", ParameterValue::Number(2500.0));
+    beam.parameter_units
+        .insert("ClearHeight".to_string(), ParameterUnit::Length);
+    let guid = beam.guid;
+    model.add_element(beam, storey).expect("add beam");
+
+    model.convert_units(Units::metric_m());
+    assert_eq!(model.units.length, LengthUnit::Meter);
+    let beam = model.element(guid).expect("beam");
+    let number = |key: &str| match beam.parameters.get(key) {
+        Some(ParameterValue::Number(value)) => *value,
+        _ => panic!("missing {key}"),
+    };
+    assert!((number("StartX") - 1.0).abs() < 1.0e-9);
+    assert!((number("ProfileWidth") - 0.2).abs() < 1.0e-9);
+    assert_eq!(number("Roll"), 90.0);
+    assert_eq!(number("CamberSize"), 20.0);
+    assert!((number("Point2X") - 0.5).abs() < 1.0e-9);
+    assert!((number("ClearHeight") - 2.5).abs() < 1.0e-9);
+    assert_eq!(beam.parameter_unit("Slope"), ParameterUnit::Degrees);
+    assert_eq!(Units::metric_m().angle, AngleUnit::Degree);
+    let top = beam
+        .geometry
+        .vertex_iter()
+        .map(|vertex| vertex.point().z)
+        .fold(f64::MIN, f64::max);
+    assert!((top - 0.3).abs() < 1.0e-9);
+    Ok(())
+}
+
+#[test]
+fn phases_decide_which_elements_stand_and_how() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let existing = Phase::new("Existing");
+    let new_work = Phase::new("New Construction");
+    let (existing_guid, new_guid) = (existing.guid, new_work.guid);
+    model.phases = vec![existing, new_work];
+
+    let old_wall = element(BimCategory::Wall)?;
+    let mut torn_down = element(BimCategory::Wall)?;
+    torn_down.phase_demolished = Some(new_guid);
+    let mut added = element(BimCategory::Wall)?;
+    added.phase_created = Some(new_guid);
+    let (old_guid, torn_guid, added_guid) = (old_wall.guid, torn_down.guid, added.guid);
+    for element in [old_wall, torn_down, added] {
+        model.add_element(element, storey).expect("add element");
+    }
+
+    assert_eq!(
+        model.query().phase(existing_guid).guids(),
+        vec![old_guid, torn_guid]
+    );
+    assert_eq!(model.query().phase(new_guid).count(), 3);
+    assert_eq!(
+        model
+            .query()
+            .phase_status(new_guid, PhaseStatus::Demolished)
+            .guids(),
+        vec![torn_guid]
+    );
+    let added = model.element(added_guid).expect("added wall");
+    assert_eq!(model.phase_status(added, new_guid), Some(PhaseStatus::New));
+    assert_eq!(model.phase_status(added, existing_guid), None);
+    Ok(())
+}
+
+#[test]
+fn models_are_placed_in_shared_coordinates() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut wall = element(BimCategory::Wall)?;
+    wall.insert_parameter("StartX", ParameterValue::Number(1000.0));
+    wall.insert_parameter("StartY", ParameterValue::Number(0.0));
+    wall.insert_parameter("StartZ", ParameterValue::Number(0.0));
+    let guid = wall.guid;
+    model.add_element(wall, storey).expect("add wall");
+    model.site.shared_coordinates =
+        CoordinateTransform::parse("500000, 200000, 35", 90.0).expect("transform");
+
+    let shared = model.in_shared_coordinates();
+    let number = |key: &str| match shared
+        .element(guid)
+        .and_then(|wall| wall.parameters.get(key))
+    {
+        Some(ParameterValue::Number(value)) => *value,
+        _ => f64::NAN,
+    };
+    assert!((number("StartX") - 500000.0).abs() < 1.0e-6);
+    assert!((number("StartY") - 201000.0).abs() < 1.0e-6);
+    assert!((number("StartZ") - 35.0).abs() < 1.0e-9);
+    let (min, max) = solid_bounds(&shared.element(guid).expect("wall").geometry).expect("bounds");
+    // The 100 x 200 box is turned a quarter: 200 along X, 100 along Y.
+    assert!((max.x - min.x - 200.0).abs() < 1.0e-6);
+    assert!((max.y - min.y - 100.0).abs() < 1.0e-6);
+    assert_eq!(
+        shared.storeys().next().map(|storey| storey.elevation),
+        Some(35.0)
+    );
+
+    let back = shared.transformed(&model.site.shared_coordinates.inverse());
+    let back_wall = back.element(guid).expect("wall");
+    assert_eq!(
+        back_wall.parameters.get("StartX").map(|value| match value {
+            ParameterValue::Number(x) => (x - 1000.0).abs() < 1.0e-6,
+            _ => false,
+        }),
+        Some(true)
+    );
+    assert!(CoordinateTransform::parse("1,2,3,4", 0.0).is_err());
+    Ok(())
+}
+
+#[test]
+fn takeoff_sums_quantities_by_category_type_and_material() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let wall_type =
+        BimElementType::new("Wall 200", BimCategory::Wall, "Basic Wall").with_material("Concrete");
+    let mut walls = Vec::new();
+    for _ in 0..2 {
+        let mut wall = element(BimCategory::Wall)?;
+        wall_type.apply_to(&mut wall);
+        wall.insert_parameter("Length", ParameterValue::Number(100.0));
+        walls.push(wall);
+    }
+    let mut column = element(BimCategory::Column)?;
+    for (key, value) in [("StartZ", 0.0), ("EndZ", 300.0)] {
+        column.insert_parameter(key, ParameterValue::Number(value));
+    }
+    for key in ["StartX", "StartY", "EndX", "EndY"] {
+        column.insert_parameter(key, ParameterValue::Number(50.0));
+    }
+    let opening = element(BimCategory::Opening)?;
+    model.types.push(wall_type);
+    for element in walls.into_iter().chain([column, opening]) {
+        model.add_element(element, storey).expect("add element");
+    }
+
+    let report = takeoff(&model);
+    let volume = 100.0 * 200.0 * 300.0;
+    let area = 2.0 * (100.0 * 200.0 + 200.0 * 300.0 + 100.0 * 300.0);
+    assert_eq!(report.total.count, 3);
+    assert!((report.total.volume - 3.0 * volume).abs() < 1.0e-3);
+    assert!((report.total.area - 3.0 * area).abs() < 1.0e-3);
+    assert!((report.total.length - 500.0).abs() < 1.0e-9);
+
+    let names = |rows: &[cryxtal_bim::TakeoffRow]| -> Vec<(String, usize)> {
+        rows.iter()
+            .map(|row| (row.name.clone(), row.count))
+            .collect()
+    };
+    assert_eq!(
+        names(&report.by_category),
+        [("Column".to_string(), 1), ("Wall".to_string(), 2)]
+    );
+    assert_eq!(
+        names(&report.by_type),
+        [("No type".to_string(), 1), ("Wall 200".to_string(), 2)]
+    );
+    assert_eq!(
+        names(&report.by_material),
+        [("Concrete".to_string(), 2), ("Unspecified".to_string(), 1)]
+    );
+    Ok(())
+}
+
+#[test]
+fn constraints_are_solved_against_levels_and_hosts() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let ground = model.default_storey().expect("default storey");
+    let mut wall = element(BimCategory::Wall)?;
+    wall.insert_parameter("StartZ", ParameterValue::Number(0.0));
+    wall.insert_parameter("Height", ParameterValue::Number(3000.0));
+    wall.insert_parameter("Length", ParameterValue::Number(4000.0));
+    wall.constraints.push(Constraint::TopAttached {
+        storey: ground,
+        offset: 3000.0,
+    });
+    let wall_guid = wall.guid;
+    let mut opening = BimElement::opening(
+        Opening::new(
+            wall_guid,
+            OpeningProfile {
+                width: 900.0,
+                height: 2100.0,
+            },
+            OpeningPlacement {
+                center_x: 500.0,
+                center_y: 0.0,
+                center_z: 1050.0,
+            },
+        ),
+        "Opening 1",
+        ParameterSet::new(),
+        SolidBuilder::box_solid(900.0, 200.0, 2100.0)?,
+    );
+    opening.constraints.push(Constraint::CenteredInHost);
+    let opening_guid = opening.guid;
+    model.add_element(wall, ground).expect("add wall");
+    model.add_element(opening, ground).expect("add opening");
+
+    // The wall already reaches its level; only the opening moves.
+    let solved = model.solve_constraints().expect("solve");
+    assert_eq!(solved.len(), 1);
+    assert_eq!(solved[0].guid, opening_guid);
+    let placement = solved[0].opening.expect("opening").placement;
+    assert_eq!(placement.center_x, 2000.0);
+    ModelCommand::ReplaceElements { elements: solved }
+        .apply(&mut model)
+        .expect("replace");
+    assert!(model.solve_constraints().expect("solve").is_empty());
+
+    // Raising the level raises the wall top.
+    ModelCommand::SetStoreyElevation {
+        storey: ground,
+        elevation: 500.0,
+    }
+    .apply(&mut model)
+    .expect("move level");
+    let solved = model.solve_constraints().expect("solve");
+    assert_eq!(solved.len(), 1);
+    assert_eq!(
+        solved[0].parameters.get("Height"),
+        Some(&ParameterValue::Number(3500.0))
+    );
+    // Only constraints reading what changed are solved.
+    let (touching, failures) = model.solve_constraints_touching(&BTreeSet::from([ground]));
+    assert_eq!((touching.len(), failures.len()), (1, 0));
+    let (untouched, _) = model.solve_constraints_touching(&BTreeSet::from([opening_guid]));
+    assert!(untouched.is_empty());
+
+    // A top below the base cannot hold.
+    ModelCommand::SetStoreyElevation {
+        storey: ground,
+        elevation: -3000.0,
+    }
+    .apply(&mut model)
+    .expect("move level");
+    assert!(model.solve_constraints().is_err());
+    let (solved, failures) = model.solve_constraints_touching(&BTreeSet::from([ground]));
+    assert!(solved.is_empty());
+    assert_eq!(failures.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn parameter_keys_are_renamed_and_merged_across_the_model() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut imported = element(BimCategory::Wall)?;
+    imported.insert_parameter("Thk", ParameterValue::Number(200.0));
+    imported.insert_parameter("Length", ParameterValue::Number(4000.0));
+    imported
+        .set_formula("Area", "Length * Thk")
+        .expect("formula");
+    let mut both = element(BimCategory::Wall)?;
+    both.insert_parameter("Thk", ParameterValue::Number(250.0));
+    both.insert_parameter("Thickness", ParameterValue::Number(300.0));
+    let mut wall_type = BimElementType::new("Imported", BimCategory::Wall, "Basic Wall");
+    wall_type
+        .parameters
+        .insert("Thk".to_string(), ParameterValue::Number(200.0));
+    let (imported_guid, both_guid, type_guid) = (imported.guid, both.guid, wall_type.guid);
+    model.types.push(wall_type);
+    model.add_element(imported, storey).expect("add element");
+    model.add_element(both, storey).expect("add element");
+    assert_eq!(model.parameter_keys().get("Thk"), Some(&3));
+
+    let mapping = BTreeMap::from([("Thk".to_string(), "Thickness".to_string())]);
+    let rename = model.rename_parameters(&mapping).expect("preview");
+    assert_eq!(rename.renamed[0].elements, 2);
+    assert_eq!(rename.renamed[0].types, 1);
+    // The value already under the new key wins the merge.
+    assert_eq!(rename.conflicts.len(), 1);
+    assert_eq!(rename.conflicts[0].guid, both_guid);
+    assert_eq!(rename.conflicts[0].kept, ParameterValue::Number(300.0));
+    // Previewing leaves the model alone.
+    assert!(model.parameter_keys().contains_key("Thk"));
+
+    let mut history = History::new();
+    history
+        .apply(&mut model, "Rename parameters", rename.command())
+        .expect("rename");
+    assert!(!model.parameter_keys().contains_key("Thk"));
+    let imported = model.element(imported_guid).expect("element");
+    assert_eq!(imported.formula("Area"), Some("Length * Thickness"));
+    assert_eq!(
+        imported.parameters.get("Area"),
+        Some(&ParameterValue::Number(800000.0))
+    );
+    let wall_type = model.element_type(type_guid).expect("type");
+    assert!(wall_type.parameters.contains_key("Thickness"));
+
+    history.undo(&mut model).expect("undo");
+    assert_eq!(model.parameter_keys().get("Thk"), Some(&3));
+
+    // Formulas can only read names without spaces.
+    let spaced = BTreeMap::from([("Thk".to_string(), "Wall thickness".to_string())]);
+    assert!(model.rename_parameters(&spaced).is_err());
+    Ok(())
+}
+
+#[test]
+fn similar_elements_match_parameters_or_moved_geometry() -> Result<()> {
+    let mut model = BimModel::new("Similar");
+    let storey = model.default_storey().expect("default storey");
+    let wall = |length: f64, start_x: f64, layer: &str| -> Result<BimElement> {
+        let mut wall = element(BimCategory::Wall)?;
+        wall.geometry = SolidBuilder::box_solid(length, 200.0, 2800.0)?;
+        for (key, value) in [
+            ("Length", length),
+            ("Thickness", 200.0),
+            ("Height", 2800.0),
+            ("StartX", start_x),
+            ("EndX", start_x + length),
+        ] {
+            wall.insert_parameter(key, ParameterValue::Number(value));
+        }
+        wall.insert_parameter("Layer", ParameterValue::Text(layer.to_string()));
+        Ok(wall)
+    };
+    let source = wall(3000.0, 0.0, "Walls")?;
+    let twin = wall(3000.4, 8000.0, "Other")?;
+    let longer = wall(4000.0, 0.0, "Walls")?;
+    let (source_guid, twin_guid) = (source.guid, twin.guid);
+    for wall in [source, twin, longer] {
+        model.add_element(wall, storey).expect("add wall");
+    }
+
+    let plain = element(BimCategory::Generic)?;
+    let mut moved = element(BimCategory::Generic)?;
+    moved.geometry = transform_solid(
+        &moved.geometry,
+        Matrix4::from_translation(Vector3::new(500.0, -200.0, 3000.0)),
+    );
+    let mut larger = element(BimCategory::Generic)?;
+    larger.geometry = SolidBuilder::box_solid(100.0, 200.0, 400.0)?;
+    let (plain_guid, moved_guid) = (plain.guid, moved.guid);
+    for element in [plain, moved, larger] {
+        model.add_element(element, storey).expect("add element");
+    }
+
+    assert_eq!(
+        model
+            .similar_elements(source_guid, 1.0)
+            .expect("similar walls"),
+        vec![twin_guid]
+    );
+    assert!(
+        model
+            .similar_elements(source_guid, 0.1)
+            .expect("similar walls")
+            .is_empty()
+    );
+    assert_eq!(
+        model
+            .similar_elements(plain_guid, 1.0)
+            .expect("similar solids"),
+        vec![moved_guid]
+    );
+    assert!(model.similar_elements(source_guid, 0.0).is_err());
+    Ok(())
+}
+
+#[test]
+fn storeys_are_assigned_from_base_elevation() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let ground = model.default_storey().expect("default storey");
+    let building = model.site.buildings[0].guid;
+    let upper = model
+        .add_storey(building, "Level 1", 3000.0)
+        .expect("storey");
+    let raised = |category| -> Result<BimElement> {
+        let mut element = element(category)?;
+        element.geometry = transform_solid(
+            &element.geometry,
+            Matrix4::from_translation(Vector3::new(0.0, 0.0, 2900.0)),
+        );
+        Ok(element)
+    };
+
+    // Imported without level data: everything on the ground storey.
+    let column = raised(BimCategory::Column)?;
+    let mut bar = element(BimCategory::Rebar)?;
+    bar.host = Some(column.guid);
+    let mut locked = raised(BimCategory::Beam)?;
+    locked.insert_parameter(cryxtal_bim::STOREY_LOCKED_KEY, ParameterValue::Bool(true));
+    let footing = element(BimCategory::Slab)?;
+    let guids = [column.guid, bar.guid, locked.guid, footing.guid];
+    for element in [column, bar, locked, footing] {
+        model.add_element(element, ground).expect("add element");
+    }
+
+    let assignments = model.storey_assignments();
+    let moved: Vec<Guid> = assignments
+        .iter()
+        .map(|assignment| assignment.element)
+        .collect();
+    // The bar follows its column although it sits at the ground storey.
+    assert_eq!(moved, vec![guids[0], guids[1]]);
+    assert!(
+        assignments
+            .iter()
+            .all(|assignment| assignment.from == Some(ground) && assignment.to == upper)
+    );
+
+    let command = ModelCommand::Batch(
+        assignments
+            .iter()
+            .map(|assignment| ModelCommand::MoveToStorey {
+                element: assignment.element,
+                storey: Some(assignment.to),
+            })
+            .collect(),
+    );
+    let inverse = command.apply(&mut model).expect("assign storeys");
+    assert_eq!(
+        model.storey_of(guids[1]).map(|storey| storey.guid),
+        Some(upper)
+    );
+    assert!(model.storey_assignments().is_empty());
+    inverse.apply(&mut model).expect("undo");
+    assert_eq!(model.storey_assignments().len(), 2);
+    Ok(())
+}
+
+#[test]
+fn hatch_patterns_follow_material_then_category() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let beam_type =
+        BimElementType::new("GL 200", BimCategory::Beam, "Glulam Beam").with_material("GL24h");
+    let mut beam = element(BimCategory::Beam)?;
+    beam.type_id = Some(beam_type.guid);
+    model.types.push(beam_type);
+    assert_eq!(model.hatch_pattern(&beam), HatchPattern::Timber);
+    beam.insert_parameter("Material", ParameterValue::Text("S355".to_string()));
+    assert_eq!(model.hatch_pattern(&beam), HatchPattern::Steel);
+    beam.insert_parameter("Material", ParameterValue::Text("Unobtainium".to_string()));
+    assert_eq!(model.hatch_pattern(&beam), HatchPattern::Diagonal);
+
+    for (material, pattern) in [
+        ("Concrete", HatchPattern::Concrete),
+        ("C30/37", HatchPattern::Concrete),
+        ("C24", HatchPattern::Timber),
+        ("Clay brick", HatchPattern::Masonry),
+        ("Mineral wool insulation", HatchPattern::Insulation),
+    ] {
+        assert_eq!(HatchPattern::for_material(material), Some(pattern));
+    }
+    assert_eq!(
+        model.hatch_pattern(&element(BimCategory::Slab)?),
+        HatchPattern::ReverseDiagonal
+    );
+    let mut opening = element(BimCategory::Opening)?;
+    opening.insert_parameter("Material", ParameterValue::Text("Concrete".to_string()));
+    assert_eq!(model.hatch_pattern(&opening), HatchPattern::None);
+    assert!(HatchPattern::None.lines().is_empty());
+    Ok(())
+}
+
+#[test]
+fn bill_of_materials_rolls_up_assemblies_then_groups() -> Result<()> {
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    let mut parts = Vec::new();
+    for category in [
+        BimCategory::Wall,
+        BimCategory::Wall,
+        BimCategory::Column,
+        BimCategory::Beam,
+        BimCategory::Wall,
+        BimCategory::Wall,
+    ] {
+        let mut part = element(category)?;
+        part.name = format!("{category:?}");
+        if category != BimCategory::Beam {
+            part.insert_parameter("Material", ParameterValue::Text("C30/37".to_string()));
+        }
+        parts.push(part);
+    }
+    parts[2].insert_parameter(DENSITY_KEY, ParameterValue::Number(2000.0));
+    let guids: Vec<Guid> = parts.iter().map(|part| part.guid).collect();
+    for part in parts {
+        model.add_element(part, storey).expect("add element");
+    }
+    // The second wall is in both; the assembly claims it.
+    model
+        .groups
+        .push(ElementGroup::new("Core", GroupKind::Group).with_members([guids[1], guids[2]]));
+    model
+        .groups
+        .push(ElementGroup::new("Panel", GroupKind::Assembly).with_members([guids[0], guids[1]]));
+    // An identical panel shares its row.
+    model
+        .groups
+        .push(ElementGroup::new("Panel", GroupKind::Assembly).with_members([guids[4], guids[5]]));
+
+    let rows = bill_of_materials(&model);
+    let tree: Vec<_> = rows
+        .iter()
+        .map(|row| {
+            (
+                row.item.as_str(),
+                row.level,
+                row.name.as_str(),
+                row.quantity,
+            )
+        })
+        .collect();
+    assert_eq!(
+        tree,
+        [
+            ("1", 0, "Panel", 2),
+            ("1.1", 1, "Wall", 4),
+            ("2", 0, "Core", 1),
+            ("2.1", 1, "Column", 1),
+            ("3", 0, "Beam", 1),
+        ]
+    );
+    let volume = 100.0 * 200.0 * 300.0 * 1.0e-9;
+    assert_eq!(rows[0].kind, "Assembly");
+    assert_eq!(rows[1].material, "C30/37");
+    assert!((rows[1].volume - 4.0 * volume).abs() < 1.0e-9);
+    let weight = |idx: usize| rows[idx].weight.expect("known density");
+    assert!((weight(0) - 4.0 * volume * 2500.0).abs() < 1.0e-6);
+    assert!((weight(3) - volume * 2000.0).abs() < 1.0e-6);
+    assert_eq!(rows[4].weight, None);
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::{
+    ACOUSTIC_RATING_KEY, BimCategory, BimElement, BimModel, FIRE_RATING_KEY, Opening,
+    OpeningPlacement, OpeningProfile, ParameterKind, ParameterSet, ParameterValue, Rating,
+    SchemaViolation, validate_parameters,
+};
+use cryxtal_topology::{Result, SolidBuilder};
+
+fn wall(parameters: &[(&str, ParameterValue)]) -> Result<BimElement> {
+    let mut set = ParameterSet::new();
+    for key in ["StartX", "StartY", "StartZ", "EndY", "EndZ"] {
+        set.insert(key.to_string(), ParameterValue::Number(0.0));
+    }
+    set.insert("EndX".to_string(), ParameterValue::Number(4000.0));
+    for (key, value) in parameters {
+        set.insert(key.to_string(), value.clone());
+    }
+    Ok(BimElement::new(
+        Guid::new(),
+        "Wall",
+        BimCategory::Wall,
+        set,
+        SolidBuilder::box_solid(4000.0, 200.0, 3000.0)?,
+    ))
+}
+
+#[test]
+fn wall_parameters_are_checked_against_the_schema() -> Result<()> {
+    let valid = wall(&[
+        ("Length", ParameterValue::Number(4000.0)),
+        ("Thickness", ParameterValue::Number(200.0)),
+        ("Height", ParameterValue::Number(3000.0)),
+        ("Material", ParameterValue::Text("Concrete".to_string())),
+    ])?;
+    assert!(validate_parameters(&valid).is_empty());
+
+    let broken = wall(&[
+        ("Length", ParameterValue::Number(0.0)),
+        ("Thicknes", ParameterValue::Number(200.0)),
+        ("Height", ParameterValue::Text("3000".to_string())),
+    ])?;
+    let violations = validate_parameters(&broken);
+    assert_eq!(
+        violations,
+        vec![
+            SchemaViolation::NotPositive {
+                key: "Length".to_string(),
+                value: 0.0,
+            },
+            SchemaViolation::Missing {
+                key: "Thickness".to_string(),
+            },
+            SchemaViolation::WrongKind {
+                key: "Height".to_string(),
+                expected: ParameterKind::Number,
+                found: ParameterKind::Text,
+            },
+            SchemaViolation::Misspelled {
+                key: "Thicknes".to_string(),
+                expected: "Thickness".to_string(),
+            },
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn openings_rated_below_their_wall_are_flagged() -> Result<()> {
+    let wall = wall(&[
+        ("Length", ParameterValue::Number(4000.0)),
+        ("Thickness", ParameterValue::Number(200.0)),
+        ("Height", ParameterValue::Number(3000.0)),
+        (FIRE_RATING_KEY, ParameterValue::Text("EI60".to_string())),
+        (ACOUSTIC_RATING_KEY, ParameterValue::Number(45.0)),
+    ])?;
+    assert!(validate_parameters(&wall).is_empty());
+    let wall_guid = wall.guid;
+    let mut model = BimModel::new("Test");
+    let storey = model.default_storey().expect("default storey");
+    model.add_element(wall, storey).expect("add wall");
+
+    let opening = |name: &str, ratings: &[(&str, ParameterValue)]| -> Result<BimElement> {
+        let mut parameters = ParameterSet::new();
+        for (key, value) in ratings {
+            parameters.insert(key.to_string(), value.clone());
+        }
+        Ok(BimElement::opening(
+            Opening::new(
+                wall_guid,
+                OpeningProfile {
+                    width: 900.0,
+                    height: 2100.0,
+                },
+                OpeningPlacement {
+                    center_x: 1000.0,
+                    center_y: 0.0,
+                    center_z: 1050.0,
+                },
+            ),
+            name,
+            parameters,
+            SolidBuilder::box_solid(900.0, 200.0, 2100.0)?,
+        ))
+    };
+    let door = opening(
+        "Door",
+        &[
+            (FIRE_RATING_KEY, ParameterValue::Text("EI30".to_string())),
+            (ACOUSTIC_RATING_KEY, ParameterValue::Number(48.0)),
+        ],
+    )?;
+    let bare = opening("Bare", &[])?;
+    let (door_guid, bare_guid) = (door.guid, bare.guid);
+    assert!(validate_parameters(&door).is_empty());
+    for element in [door, bare] {
+        model.add_element(element, storey).expect("add opening");
+    }
+
+    let shortfalls = model.rating_shortfalls();
+    assert_eq!(
+        shortfalls
+            .iter()
+            .map(|shortfall| (shortfall.opening, shortfall.rating, shortfall.actual))
+            .collect::<Vec<_>>(),
+        vec![
+            (door_guid, Rating::Fire, Some(30.0)),
+            (bare_guid, Rating::Fire, None),
+            (bare_guid, Rating::Acoustic, None),
+        ]
+    );
+    assert!(
+        shortfalls
+            .iter()
+            .all(|shortfall| shortfall.host == wall_guid)
+    );
+    assert_eq!(
+        shortfalls[0].to_string(),
+        "FireRating 30 min is below the 60 min its host requires"
+    );
+
+    for guid in [door_guid, bare_guid] {
+        let index = model.element_index(guid).expect("opening");
+        let opening = &mut model.elements_mut()[index];
+        opening.insert_parameter(FIRE_RATING_KEY, ParameterValue::Text("EI 90".to_string()));
+        opening.insert_parameter(ACOUSTIC_RATING_KEY, ParameterValue::Number(45.0));
+    }
+    assert!(model.rating_shortfalls().is_empty());
+    Ok(())
+}
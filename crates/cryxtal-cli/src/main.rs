@@ -1,12 +1,29 @@
 use anyhow::{Context, Result, bail};
 use clap::{Args, Parser, Subcommand};
-use cryxtal_base::Guid;
-use cryxtal_bim::{BimCategory, BimElement, ParameterSet, ParameterValue};
-use cryxtal_io::{DEFAULT_TESSELLATION_TOLERANCE, export_obj, export_step};
+use cryxtal_base::{CoordinateInput, Guid, LengthUnit, Units, parse_coordinate};
+use cryxtal_bim::{
+    BimCategory, BimElement, BimModel, ChangeSource, CoordinateTransform, ModelCommand,
+    ParameterSet, ParameterValue, WeldAnnotation, WeldKind, WeldSide, bill_of_materials, diff,
+    parameter_schema, takeoff, validate_parameters,
+};
+use cryxtal_io::{
+    BINARY_PROJECT_EXTENSION, BREP_EXTENSION, BvbsOptions, DEFAULT_ANGULAR_TOLERANCE,
+    DEFAULT_TESSELLATION_TOLERANCE, GltfOptions, PROJECT_EXTENSION, Project, SplitBy,
+    TessellationOptions, bvbs_bars, export_bom, export_brep, export_bvbs, export_deviation_csv,
+    export_dxf_plan, export_glb_streaming, export_gltf, export_ifc, export_model_mesh,
+    export_model_mesh_streaming, export_obj, export_parameter_log_csv, export_parameters_csv,
+    export_parameters_json, export_plan_svg, export_pxml, export_rebar_schedule_csv, export_step,
+    export_step_model, export_weld_drawing_svg, export_weld_schedule_csv, import_brep, import_ids,
+    import_journal, import_obj, import_parameters, import_scan_points, import_step,
+    import_step_shells, load_project, rebar_schedule, recognize_holes, reference_mesh_element,
+    save_project, scan_deviation, split_elements, validate_ids, weld_schedule, write_bom_csv,
+    write_bvbs, write_deviation_csv, write_parameter_log_csv, write_pxml, write_rebar_schedule_csv,
+    write_weld_schedule_csv,
+};
 use cryxtal_shapeops::{DEFAULT_SHAPEOPS_TOLERANCE, plate_with_hole};
-use cryxtal_topology::SolidBuilder;
-use std::path::PathBuf;
-use tracing::info;
+use cryxtal_topology::{SolidBuilder, heal_shells, heal_solid};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
 
 #[derive(Parser)]
 #[command(name = "cryxtal")]
@@ -23,6 +40,31 @@ enum Command {
         command: GenerateCommand,
     },
     Triangulate(TriangulateArgs),
+    Export(ExportArgs),
+    Convert(ConvertArgs),
+    Holes(HolesArgs),
+    Schedule(ScheduleArgs),
+    Bom(BomArgs),
+    Deviation(DeviationArgs),
+    Bvbs(BvbsArgs),
+    Pxml(PxmlArgs),
+    Weld(WeldArgs),
+    Welds(WeldsArgs),
+    Plan(PlanArgs),
+    Section(SectionArgs),
+    Validate(ValidateArgs),
+    Units(UnitsArgs),
+    Diff(DiffArgs),
+    Place(PlaceArgs),
+    Heal(HealArgs),
+    Levels(LevelsArgs),
+    Set(SetArgs),
+    ParameterLog(ParameterLogArgs),
+    Replay(ReplayArgs),
+    ExportParameters(ExportParametersArgs),
+    ImportParameters(ImportParametersArgs),
+    Takeoff(TakeoffArgs),
+    Ids(IdsArgs),
 }
 
 #[derive(Subcommand)]
@@ -67,6 +109,479 @@ struct TriangulateArgs {
     out: PathBuf,
 }
 
+/// Exports a project's elements as a triangle mesh or an IFC4 model; the format follows the
+/// `--out` extension (`.obj`, `.gltf`, `.glb`, `.cxmesh` or `.ifc`).
+#[derive(Args)]
+struct ExportArgs {
+    #[arg(long)]
+    project: PathBuf,
+    #[arg(long)]
+    out: PathBuf,
+    #[command(flatten)]
+    output: MeshOutputArgs,
+    /// Only export the elements of the group or assembly with this name.
+    #[arg(long)]
+    group: Option<String>,
+    /// Write one file per level, layer or category into the `--out` directory.
+    #[arg(long)]
+    split_by: Option<String>,
+    /// Format of the split files: obj, gltf, glb, cxmesh or ifc.
+    #[arg(long, default_value = "obj", requires = "split_by")]
+    format: String,
+    #[command(flatten)]
+    placement: PlacementArgs,
+}
+
+/// Converts a model between formats, each picked by its extension: projects (`.cxp`, `.cxpb`),
+/// STEP (`.step`, `.stp`), B-rep (`.cxbrep`) and OBJ meshes in; projects, STEP, B-rep and the
+/// formats of `export` out.
+/// A single solid or mesh is read as one generic element of a new model. The mesh flags only
+/// apply to mesh and IFC outputs.
+#[derive(Args)]
+struct ConvertArgs {
+    #[arg(long = "in")]
+    input: PathBuf,
+    #[arg(long)]
+    out: PathBuf,
+    /// Length unit of inputs that carry none (STEP, B-rep and OBJ): mm, m, in or ft. Defaults to
+    /// mm; projects carry their own.
+    #[arg(long)]
+    units: Option<LengthUnit>,
+    #[command(flatten)]
+    output: MeshOutputArgs,
+}
+
+/// How `export` and `convert` write mesh and IFC files.
+#[derive(Args)]
+struct MeshOutputArgs {
+    #[command(flatten)]
+    tessellation: TessellationArgs,
+    /// Tessellate and write element by element instead of merging the whole model first, so
+    /// memory stays bounded by the largest element. IFC is always written this way.
+    #[arg(long)]
+    stream: bool,
+    /// Store `.glb` positions as 16-bit integers (KHR_mesh_quantization).
+    #[arg(long)]
+    quantize: bool,
+}
+
+/// How finely solids are tessellated for export.
+#[derive(Args)]
+struct TessellationArgs {
+    /// Largest distance between a triangle and the surface it approximates.
+    #[arg(long, visible_alias = "tol", default_value_t = DEFAULT_TESSELLATION_TOLERANCE)]
+    tolerance: f64,
+    /// Largest angle between neighbouring normals on curved faces with `--adaptive`, in
+    /// degrees.
+    #[arg(long, default_value_t = DEFAULT_ANGULAR_TOLERANCE.to_degrees())]
+    angular_tol: f64,
+    /// Split triangles until no edge is longer than this.
+    #[arg(long)]
+    max_edge: Option<f64>,
+    /// Tighten the tolerance of each solid to its most sharply curved face.
+    #[arg(long)]
+    adaptive: bool,
+    /// Weld vertices closer than this, drop slivers thinner than it and wind the triangles
+    /// consistently.
+    #[arg(long)]
+    repair: Option<f64>,
+    /// Decimate the mesh of each element to at most this many triangles.
+    #[arg(long)]
+    max_triangles: Option<usize>,
+    /// Refuse to export elements whose mesh is open or inconsistently wound, listing where
+    /// the holes are.
+    #[arg(long)]
+    check_watertight: bool,
+}
+
+impl MeshOutputArgs {
+    /// Flags given away from their defaults, for outputs that write no mesh.
+    fn given(&self) -> Vec<&'static str> {
+        let tessellation = &self.tessellation;
+        [
+            (
+                "--tolerance",
+                tessellation.tolerance != DEFAULT_TESSELLATION_TOLERANCE,
+            ),
+            (
+                "--angular-tol",
+                tessellation.angular_tol != DEFAULT_ANGULAR_TOLERANCE.to_degrees(),
+            ),
+            ("--max-edge", tessellation.max_edge.is_some()),
+            ("--adaptive", tessellation.adaptive),
+            ("--repair", tessellation.repair.is_some()),
+            ("--max-triangles", tessellation.max_triangles.is_some()),
+            ("--check-watertight", tessellation.check_watertight),
+            ("--stream", self.stream),
+            ("--quantize", self.quantize),
+        ]
+        .into_iter()
+        .filter_map(|(flag, given)| given.then_some(flag))
+        .collect()
+    }
+}
+
+impl TessellationArgs {
+    fn options(&self) -> TessellationOptions {
+        TessellationOptions {
+            chordal_tol: self.tolerance,
+            angular_tol: self.angular_tol.to_radians(),
+            max_edge_length: self.max_edge,
+            curvature_adaptive: self.adaptive,
+            repair_tol: self.repair,
+            max_triangles: self.max_triangles,
+            check_watertight: self.check_watertight,
+        }
+    }
+}
+
+/// Offset and rotation about Z applied to the model, in project units.
+#[derive(Args)]
+struct PlacementArgs {
+    /// Start from the project's saved shared coordinates.
+    #[arg(long)]
+    shared: bool,
+    /// `x,y,z` translation, applied after the rotation.
+    #[arg(long, default_value = "0,0,0", allow_hyphen_values = true)]
+    offset: String,
+    /// Degrees about Z, counter-clockwise.
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    rotate: f64,
+}
+
+impl PlacementArgs {
+    /// `model` moved to the shared coordinates if asked for, then by the offset and rotation.
+    fn place(&self, model: BimModel) -> Result<BimModel> {
+        let mut model = if self.shared {
+            model.in_shared_coordinates()
+        } else {
+            model
+        };
+        model.transform(&CoordinateTransform::parse(&self.offset, self.rotate)?);
+        Ok(model)
+    }
+}
+
+/// Lists the cylindrical holes recognized in each element of a project, or in the solid of a
+/// STEP or B-rep file.
+#[derive(Args)]
+struct HolesArgs {
+    #[arg(long, required_unless_present = "input")]
+    project: Option<PathBuf>,
+    /// Solid to search instead of a project, read as by `convert`.
+    #[arg(long = "in", conflicts_with = "project")]
+    input: Option<PathBuf>,
+    #[arg(long, default_value_t = DEFAULT_TESSELLATION_TOLERANCE)]
+    tolerance: f64,
+}
+
+/// Writes the bar bending schedule of a project's rebar as CSV.
+#[derive(Args)]
+struct ScheduleArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// CSV file to write; prints to stdout when omitted.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+/// Writes the bill of materials of a project: each assembly and group followed by its parts,
+/// then the loose parts, with quantities, volumes and weights.
+#[derive(Args)]
+struct BomArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// `.csv` or `.xlsx` file to write; prints CSV to stdout when omitted.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+/// Compares a laser scan with a project's elements and writes the deviation of each face as
+/// CSV.
+#[derive(Args)]
+struct DeviationArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// Point cloud (`.xyz`, `.pts`, `.csv`) or scan mesh (`.obj`) in millimetres, registered
+    /// to the project's coordinates.
+    #[arg(long)]
+    scan: PathBuf,
+    /// CSV file to write; prints to stdout when omitted.
+    #[arg(long)]
+    out: Option<PathBuf>,
+    /// Scan points further than this from every face (mm) are ignored.
+    #[arg(long, default_value_t = 50.0)]
+    max_distance: f64,
+    #[arg(long, default_value_t = DEFAULT_TESSELLATION_TOLERANCE)]
+    tolerance: f64,
+}
+
+/// Writes a project's rebar as BVBS lines for bending machines.
+#[derive(Args)]
+struct BvbsArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// BVBS file to write; prints to stdout when omitted.
+    #[arg(long)]
+    out: Option<PathBuf>,
+    /// Project number written into every bar.
+    #[arg(long, default_value = "")]
+    project_number: String,
+    /// Plan number written into every bar.
+    #[arg(long, default_value = "")]
+    plan: String,
+    #[arg(long, default_value = "B500B")]
+    grade: String,
+}
+
+/// Writes a project's walls and slabs as precast panels in PXML.
+#[derive(Args)]
+struct PxmlArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// PXML file to write; prints to stdout when omitted.
+    #[arg(long)]
+    out: Option<PathBuf>,
+    /// Order number of the panels.
+    #[arg(long, default_value = "")]
+    order: String,
+}
+
+/// Attaches a weld to an edge of a project element; edges are numbered as for edge tags.
+#[derive(Args)]
+struct WeldArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// Element name.
+    #[arg(long)]
+    element: String,
+    #[arg(long)]
+    edge: usize,
+    /// fillet, square-butt, single-v, single-bevel or plug.
+    #[arg(long, default_value = "fillet")]
+    kind: String,
+    /// Fillet leg or groove depth.
+    #[arg(long)]
+    size: f64,
+    /// arrow, other or both.
+    #[arg(long, default_value = "arrow")]
+    side: String,
+    #[arg(long)]
+    all_around: bool,
+    #[arg(long)]
+    field: bool,
+}
+
+/// Writes the weld schedule of a project as CSV and optionally a plan drawing with weld
+/// symbols as SVG.
+#[derive(Args)]
+struct WeldsArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// CSV file to write; prints to stdout when omitted.
+    #[arg(long)]
+    out: Option<PathBuf>,
+    #[arg(long)]
+    drawing: Option<PathBuf>,
+}
+
+/// Draws a project in plan as SVG, colored by a named view with its legend.
+#[derive(Args)]
+struct PlanArgs {
+    #[arg(long)]
+    project: PathBuf,
+    #[arg(long)]
+    out: PathBuf,
+    /// Named view whose filters color the elements; layer colors when omitted.
+    #[arg(long)]
+    view: Option<String>,
+    /// Largest distance between a hatched cut and the curved faces it follows.
+    #[arg(long, visible_alias = "tol", default_value_t = DEFAULT_TESSELLATION_TOLERANCE)]
+    tolerance: f64,
+    /// Tighten the tolerance of each solid to its most sharply curved face.
+    #[arg(long)]
+    adaptive: bool,
+}
+
+/// Cuts a project with a horizontal plane and writes the cut outlines as 2D DXF, on layers
+/// named after the project's layers.
+#[derive(Args)]
+struct SectionArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// Height of the cutting plane.
+    #[arg(long, allow_hyphen_values = true)]
+    elevation: f64,
+    #[arg(long)]
+    out: PathBuf,
+    /// Largest distance between a cut outline and the curved faces it follows.
+    #[arg(long, visible_alias = "tol", default_value_t = DEFAULT_TESSELLATION_TOLERANCE)]
+    tolerance: f64,
+    /// Tighten the tolerance of each solid to its most sharply curved face.
+    #[arg(long)]
+    adaptive: bool,
+}
+
+/// Checks every element's parameters against its category schema, and openings' fire and
+/// acoustic ratings against their host wall's, and fails if any break them.
+#[derive(Args)]
+struct ValidateArgs {
+    #[arg(long)]
+    project: PathBuf,
+}
+
+/// Converts a project to another length unit, scaling geometry and length parameters.
+#[derive(Args)]
+struct UnitsArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// mm, m, in or ft.
+    #[arg(long)]
+    to: LengthUnit,
+}
+
+/// Moves a project by an offset and a rotation about Z, such as a model authored at a local
+/// origin onto site coordinates, or with `--save-shared` stores them as the project's shared
+/// coordinates instead, leaving the geometry in place.
+#[derive(Args)]
+struct PlaceArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// `x,y,z` translation in project units, applied after the rotation.
+    #[arg(long, default_value = "0,0,0", allow_hyphen_values = true)]
+    offset: String,
+    /// Degrees about Z, counter-clockwise.
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    rotate: f64,
+    #[arg(long)]
+    save_shared: bool,
+}
+
+/// Heals element geometry that does not quite close, such as imported solids: merges vertices
+/// and edges closer than `--tolerance`, closes small gaps and orients faces outward, printing
+/// what was fixed per element. `--in` heals the shells of a STEP file instead and writes the
+/// solid to `--out` (STEP or B-rep).
+#[derive(Args)]
+struct HealArgs {
+    #[arg(long, required_unless_present = "input")]
+    project: Option<PathBuf>,
+    #[arg(long = "in", conflicts_with = "project", requires = "out")]
+    input: Option<PathBuf>,
+    #[arg(long, requires = "input")]
+    out: Option<PathBuf>,
+    /// Widest gap closed, in project units.
+    #[arg(long, default_value_t = 0.01)]
+    tolerance: f64,
+}
+
+/// Moves each element onto the storey nearest the bottom of its geometry, as after an import
+/// without level data, printing the moves. Hosted elements follow their host; elements with
+/// `StoreyLocked` set stay put. `--dry-run` only prints.
+#[derive(Args)]
+struct LevelsArgs {
+    #[arg(long)]
+    project: PathBuf,
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Sets a parameter of an element, or removes it without `--value`. The change is logged in
+/// the project's parameter log with the CLI as its source.
+#[derive(Args)]
+struct SetArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// Element name.
+    #[arg(long)]
+    element: String,
+    /// Parameter name.
+    #[arg(long)]
+    name: String,
+    /// Integers, numbers and `true`/`false` are stored as such, anything else as text.
+    #[arg(long, allow_hyphen_values = true)]
+    value: Option<String>,
+}
+
+/// Writes a project's parameter log as CSV for audits: who changed which parameter when, from
+/// the GUI, the CLI or a script.
+#[derive(Args)]
+struct ParameterLogArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// Only this element's changes, by name.
+    #[arg(long)]
+    element: Option<String>,
+    /// Written to stdout when omitted.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+/// Replays a session journal exported from the viewer on the project it started from, to
+/// reproduce the session.
+#[derive(Args)]
+struct ReplayArgs {
+    #[arg(long)]
+    project: PathBuf,
+    #[arg(long)]
+    journal: PathBuf,
+    /// Where to save the result; the project is left untouched when omitted.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+/// Writes every element's parameters for editing in a spreadsheet: JSON for a `.json` output,
+/// CSV otherwise.
+#[derive(Args)]
+struct ExportParametersArgs {
+    #[arg(long)]
+    project: PathBuf,
+    #[arg(long)]
+    out: PathBuf,
+}
+
+/// Updates parameters from an edited export, matching elements by GUID, and saves the project.
+#[derive(Args)]
+struct ImportParametersArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// CSV, or JSON for a `.json` file.
+    #[arg(long)]
+    from: PathBuf,
+    /// Lists the changes without saving them.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Prints element counts, lengths, surface areas and volumes, one tab-separated row per
+/// category, type or material, followed by the total.
+#[derive(Args)]
+struct TakeoffArgs {
+    #[arg(long)]
+    project: PathBuf,
+    /// category, type or material.
+    #[arg(long, default_value = "category")]
+    by: String,
+}
+
+/// Checks the project against an IDS (Information Delivery Specification) file, printing a
+/// pass or fail row per applicable element and failing if any requirement is broken.
+#[derive(Args)]
+struct IdsArgs {
+    #[arg(long)]
+    project: PathBuf,
+    #[arg(long)]
+    ids: PathBuf,
+}
+
+/// Lists the elements added, removed and modified between two versions of a project.
+#[derive(Args)]
+struct DiffArgs {
+    #[arg(long)]
+    old: PathBuf,
+    #[arg(long)]
+    new: PathBuf,
+}
+
 fn main() -> Result<()> {
     init_tracing();
     let cli = Cli::parse();
@@ -79,6 +594,31 @@ fn main() -> Result<()> {
             command: GenerateCommand::Plate(args),
         } => generate_plate(args),
         Command::Triangulate(args) => triangulate(args),
+        Command::Export(args) => export(args),
+        Command::Convert(args) => convert(args),
+        Command::Holes(args) => holes(args),
+        Command::Schedule(args) => schedule(args),
+        Command::Bom(args) => bom(args),
+        Command::Deviation(args) => deviation(args),
+        Command::Bvbs(args) => bvbs(args),
+        Command::Pxml(args) => pxml(args),
+        Command::Weld(args) => weld(args),
+        Command::Welds(args) => welds(args),
+        Command::Plan(args) => plan(args),
+        Command::Section(args) => section(args),
+        Command::Validate(args) => validate(args),
+        Command::Units(args) => units(args),
+        Command::Diff(args) => model_diff(args),
+        Command::Place(args) => place(args),
+        Command::Heal(args) => heal(args),
+        Command::Levels(args) => assign_levels(args),
+        Command::Set(args) => set_parameter(args),
+        Command::ParameterLog(args) => parameter_log(args),
+        Command::Replay(args) => replay(args),
+        Command::ExportParameters(args) => export_parameters(args),
+        Command::ImportParameters(args) => import_parameter_values(args),
+        Command::Takeoff(args) => quantity_takeoff(args),
+        Command::Ids(args) => check_ids(args),
     }
 }
 
@@ -131,7 +671,7 @@ fn generate_plate(args: PlateArgs) -> Result<()> {
     export_obj(
         element.geometry(),
         &args.out,
-        DEFAULT_TESSELLATION_TOLERANCE,
+        &TessellationOptions::default(),
     )
     .context("OBJ export failed")?;
     info!(path = %args.out.display(), "OBJ export complete");
@@ -139,23 +679,811 @@ fn generate_plate(args: PlateArgs) -> Result<()> {
 }
 
 fn triangulate(args: TriangulateArgs) -> Result<()> {
-    let _ = args.out;
-    bail!(
-        "STEP import is not implemented yet (requested input: {})",
-        args.input.display()
+    let solid = import_step(&args.input)?;
+    export_obj(&solid, &args.out, &TessellationOptions::default()).context("OBJ export failed")?;
+    info!(path = %args.out.display(), "OBJ export complete");
+    Ok(())
+}
+
+fn export(args: ExportArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    // Mesh exports expect millimetres.
+    let model = args
+        .placement
+        .place(project.model)?
+        .converted(Units::metric_mm());
+    let grouped: Vec<BimElement>;
+    let elements = match &args.group {
+        Some(name) => {
+            let group = model
+                .groups
+                .iter()
+                .find(|group| group.name == *name)
+                .with_context(|| format!("unknown group `{name}`"))?;
+            grouped = model
+                .group_elements(group.guid)?
+                .into_iter()
+                .filter_map(|guid| model.element(guid).cloned())
+                .collect();
+            &grouped[..]
+        }
+        None => model.elements(),
+    };
+    let Some(split_by) = &args.split_by else {
+        return export_file(&model, elements, &args.out, &args.output);
+    };
+    let split_by: SplitBy = split_by.parse()?;
+    let extension = args.format.to_ascii_lowercase();
+    std::fs::create_dir_all(&args.out)
+        .with_context(|| format!("failed to create {}", args.out.display()))?;
+    let parts = split_elements(&model, elements, split_by);
+    for part in &parts {
+        let path = args.out.join(format!("{}.{extension}", part.file_stem));
+        export_file(&model, part.elements.iter().copied(), &path, &args.output)
+            .with_context(|| format!("export of {} failed", part.name))?;
+    }
+    info!(dir = %args.out.display(), files = parts.len(), "split export complete");
+    Ok(())
+}
+
+/// Writes `elements` to `out` in the format of its extension.
+fn export_file<'a>(
+    model: &BimModel,
+    elements: impl IntoIterator<Item = &'a BimElement>,
+    out: &Path,
+    args: &MeshOutputArgs,
+) -> Result<()> {
+    let extension = out
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("ifc") => {
+            export_ifc(model, elements, &args.tessellation.options(), out)
+                .context("IFC export failed")?;
+            info!(path = %out.display(), "IFC export complete");
+        }
+        Some("glb") => {
+            let options = GltfOptions {
+                tessellation: args.tessellation.options(),
+                layers: model.layers.clone(),
+                quantize: args.quantize,
+            };
+            if args.stream {
+                let written =
+                    export_glb_streaming(elements, out, &options).context("GLB export failed")?;
+                info!(path = %out.display(), elements = written, "streaming GLB export complete");
+            } else {
+                export_gltf(elements, out, &options).context("GLB export failed")?;
+                info!(path = %out.display(), "GLB export complete");
+            }
+        }
+        _ => export_mesh(elements, out, args)?,
+    }
+    Ok(())
+}
+
+fn export_mesh<'a>(
+    elements: impl IntoIterator<Item = &'a BimElement>,
+    out: &Path,
+    args: &MeshOutputArgs,
+) -> Result<()> {
+    if args.stream {
+        let written = export_model_mesh_streaming(elements, out, &args.tessellation.options())
+            .context("mesh export failed")?;
+        info!(path = %out.display(), elements = written, "streaming mesh export complete");
+    } else {
+        export_model_mesh(elements, out, &args.tessellation.options())
+            .context("mesh export failed")?;
+        info!(path = %out.display(), "mesh export complete");
+    }
+    Ok(())
+}
+
+fn convert(args: ConvertArgs) -> Result<()> {
+    let extension = args
+        .out
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    if let Some(PROJECT_EXTENSION | BINARY_PROJECT_EXTENSION | BREP_EXTENSION | "step" | "stp") =
+        extension.as_deref()
+    {
+        let given = args.output.given();
+        if !given.is_empty() {
+            bail!(
+                "{} only apply to mesh and IFC outputs, not {}",
+                given.join(", "),
+                args.out.display()
+            );
+        }
+    }
+    let mut model = read_model(&args.input, args.units)?;
+    if let Some(PROJECT_EXTENSION | BINARY_PROJECT_EXTENSION) = extension.as_deref() {
+        let project = Project {
+            model,
+            camera: None,
+        };
+        save_project(&project, &args.out)?;
+        info!(path = %args.out.display(), "project saved");
+        return Ok(());
+    }
+    // Every other writer expects millimetres.
+    model.convert_units(Units::metric_mm());
+    match extension.as_deref() {
+        Some("step" | "stp") => {
+            let project = Project {
+                model,
+                camera: None,
+            };
+            export_step_model(&project, &args.out).context("STEP export failed")?;
+            info!(path = %args.out.display(), "STEP export complete");
+        }
+        Some(BREP_EXTENSION) => {
+            let [element] = model.elements() else {
+                bail!(
+                    "{} has {} elements, but a B-rep file holds a single solid",
+                    args.input.display(),
+                    model.elements().len()
+                );
+            };
+            export_brep(element.geometry(), &args.out).context("B-rep export failed")?;
+            info!(path = %args.out.display(), "B-rep export complete");
+        }
+        _ => export_file(&model, model.elements(), &args.out, &args.output)?,
+    }
+    Ok(())
+}
+
+/// Reads `path` as a model by its extension: a project as is, or a STEP or B-rep solid or OBJ
+/// mesh, in `units` (mm by default), as one generic element on the ground storey of a new model
+/// named after the file.
+fn read_model(path: &Path, units: Option<LengthUnit>) -> Result<BimModel> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    if let Some(PROJECT_EXTENSION | BINARY_PROJECT_EXTENSION) = extension.as_deref() {
+        if units.is_some() {
+            bail!("projects carry their own units; `--units` only applies to STEP, B-rep and OBJ");
+        }
+        return Ok(load_project(path)?.model);
+    }
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("Imported")
+        .to_string();
+    let element = if extension.as_deref() == Some("obj") {
+        reference_mesh_element(name.clone(), &import_obj(path)?)?
+    } else {
+        let solid = match extension.as_deref() {
+            Some(BREP_EXTENSION) => import_brep(path)?,
+            Some("step" | "stp") => import_step(path)?,
+            _ => bail!(
+                "unsupported input format for {} (expected .{PROJECT_EXTENSION}, \
+                 .{BINARY_PROJECT_EXTENSION}, .step, .stp, .{BREP_EXTENSION} or .obj)",
+                path.display()
+            ),
+        };
+        BimElement::new(
+            Guid::new(),
+            name.clone(),
+            BimCategory::Generic,
+            ParameterSet::new(),
+            solid,
+        )
+    };
+    let mut model = BimModel::new(name);
+    model.units.length = units.unwrap_or(LengthUnit::Millimeter);
+    let storey = model.default_storey().context("new model has no storey")?;
+    model.add_element(element, storey)?;
+    Ok(model)
+}
+
+fn holes(args: HolesArgs) -> Result<()> {
+    let model = match (&args.project, &args.input) {
+        (Some(project), _) => load_project(project)?.model,
+        (None, Some(input)) => read_model(input, None)?,
+        (None, None) => bail!("give a --project or an --in file"),
+    };
+    println!("element\thole\tdiameter\tdepth\tkind\tentry\tdirection");
+    let mut total = 0;
+    for element in model.elements() {
+        for (idx, hole) in recognize_holes(element.geometry(), args.tolerance)
+            .iter()
+            .enumerate()
+        {
+            let (entry, dir) = (hole.entry, hole.direction);
+            println!(
+                "{}\t{}\t{:.2}\t{:.2}\t{}\t{:.2},{:.2},{:.2}\t{:.4},{:.4},{:.4}",
+                element.name,
+                idx + 1,
+                hole.diameter,
+                hole.depth,
+                if hole.through { "through" } else { "blind" },
+                entry.x,
+                entry.y,
+                entry.z,
+                dir.x,
+                dir.y,
+                dir.z,
+            );
+            total += 1;
+        }
+    }
+    info!(holes = total, "hole recognition complete");
+    Ok(())
+}
+
+fn schedule(args: ScheduleArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let model = project.model.converted(Units::metric_mm());
+    let rows = rebar_schedule(model.elements());
+    match &args.out {
+        Some(out) => {
+            export_rebar_schedule_csv(&rows, out).context("schedule export failed")?;
+            info!(path = %out.display(), rows = rows.len(), "schedule export complete");
+        }
+        None => write_rebar_schedule_csv(&rows, std::io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+fn bom(args: BomArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let rows = bill_of_materials(&project.model);
+    match &args.out {
+        Some(out) => {
+            export_bom(&rows, out).context("BOM export failed")?;
+            info!(path = %out.display(), rows = rows.len(), "BOM export complete");
+        }
+        None => write_bom_csv(&rows, std::io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+fn deviation(args: DeviationArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let model = project.model.converted(Units::metric_mm());
+    let points = import_scan_points(&args.scan)?;
+    let rows = scan_deviation(model.elements(), &points, args.max_distance, args.tolerance)
+        .context("deviation analysis failed")?;
+    let measured: usize = rows.iter().map(|row| row.points).sum();
+    info!(
+        points = points.len(),
+        measured,
+        faces = rows.len(),
+        "deviation analysis complete"
     );
+    match &args.out {
+        Some(out) => {
+            export_deviation_csv(&rows, model.elements(), out)
+                .context("deviation export failed")?;
+            info!(path = %out.display(), rows = rows.len(), "deviation export complete");
+        }
+        None => write_deviation_csv(&rows, model.elements(), std::io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+fn bvbs(args: BvbsArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let model = project.model.converted(Units::metric_mm());
+    let bars = bvbs_bars(model.elements())?;
+    let options = BvbsOptions {
+        project: args.project_number,
+        plan: args.plan,
+        grade: args.grade,
+    };
+    match &args.out {
+        Some(out) => {
+            export_bvbs(&bars, &options, out).context("BVBS export failed")?;
+            info!(path = %out.display(), bars = bars.len(), "BVBS export complete");
+        }
+        None => write_bvbs(&bars, &options, std::io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+fn pxml(args: PxmlArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let model = project.model.converted(Units::metric_mm());
+    match &args.out {
+        Some(out) => {
+            export_pxml(&model, &args.order, out).context("PXML export failed")?;
+            info!(path = %out.display(), "PXML export complete");
+        }
+        None => write_pxml(&model, &args.order, std::io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+fn weld(args: WeldArgs) -> Result<()> {
+    let kind = match args.kind.as_str() {
+        "fillet" => WeldKind::Fillet,
+        "square-butt" => WeldKind::SquareButt,
+        "single-v" => WeldKind::SingleV,
+        "single-bevel" => WeldKind::SingleBevel,
+        "plug" => WeldKind::Plug,
+        other => bail!("unknown weld kind `{other}`"),
+    };
+    let side = match args.side.as_str() {
+        "arrow" => WeldSide::Arrow,
+        "other" => WeldSide::Other,
+        "both" => WeldSide::Both,
+        other => bail!("unknown weld side `{other}`"),
+    };
+    let mut project = load_project(&args.project)?;
+    let element = project
+        .model
+        .elements_mut()
+        .iter_mut()
+        .find(|element| element.name == args.element)
+        .with_context(|| format!("unknown element `{}`", args.element))?;
+    element.add_weld(WeldAnnotation {
+        edge: args.edge,
+        kind,
+        size: args.size,
+        side,
+        all_around: args.all_around,
+        field: args.field,
+    })?;
+    save_project(&project, &args.project)?;
+    info!(element = %args.element, edge = args.edge, "weld added");
+    Ok(())
+}
+
+fn welds(args: WeldsArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let model = project.model.converted(Units::metric_mm());
+    let elements = model.elements();
+    let rows = weld_schedule(elements);
+    match &args.out {
+        Some(out) => {
+            export_weld_schedule_csv(&rows, out).context("weld schedule export failed")?;
+            info!(path = %out.display(), rows = rows.len(), "weld schedule export complete");
+        }
+        None => write_weld_schedule_csv(&rows, std::io::stdout().lock())?,
+    }
+    if let Some(drawing) = &args.drawing {
+        export_weld_drawing_svg(elements, drawing).context("weld drawing export failed")?;
+        info!(path = %drawing.display(), "weld drawing export complete");
+    }
+    Ok(())
+}
+
+fn plan(args: PlanArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let model = &project.model;
+    let view = match &args.view {
+        Some(name) => Some(
+            model
+                .views
+                .iter()
+                .find(|view| view.name == *name)
+                .with_context(|| format!("unknown view `{name}`"))?,
+        ),
+        None => None,
+    };
+    let tessellation = TessellationOptions {
+        curvature_adaptive: args.adaptive,
+        ..TessellationOptions::chordal(args.tolerance)
+    };
+    export_plan_svg(model, view, &tessellation, &args.out).context("plan export failed")?;
+    info!(path = %args.out.display(), "plan export complete");
+    Ok(())
+}
+
+fn section(args: SectionArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let tessellation = TessellationOptions {
+        curvature_adaptive: args.adaptive,
+        ..TessellationOptions::chordal(args.tolerance)
+    };
+    export_dxf_plan(&project.model, args.elevation, &tessellation, &args.out)
+        .context("section export failed")?;
+    info!(path = %args.out.display(), elevation = args.elevation, "section export complete");
+    Ok(())
+}
+
+fn validate(args: ValidateArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let mut total = 0;
+    for element in project.model.elements() {
+        for violation in validate_parameters(element) {
+            println!("{}\t{violation}", element.name);
+            total += 1;
+        }
+    }
+    for shortfall in project.model.rating_shortfalls() {
+        let name = project
+            .model
+            .element(shortfall.opening)
+            .map_or("", |opening| opening.name.as_str());
+        println!("{name}\t{shortfall}");
+        total += 1;
+    }
+    if total > 0 {
+        bail!("{total} parameter schema or rating violation(s)");
+    }
+    info!("all parameters match their schemas and ratings");
+    Ok(())
+}
+
+fn units(args: UnitsArgs) -> Result<()> {
+    let mut project = load_project(&args.project)?;
+    let from = project.model.units.length;
+    let units = Units {
+        length: args.to,
+        ..project.model.units
+    };
+    project.model.convert_units(units);
+    save_project(&project, &args.project)?;
+    info!(
+        from = from.symbol(),
+        to = args.to.symbol(),
+        "project units converted"
+    );
+    Ok(())
+}
+
+fn place(args: PlaceArgs) -> Result<()> {
+    let mut project = load_project(&args.project)?;
+    let transform = CoordinateTransform::parse(&args.offset, args.rotate)?;
+    if args.save_shared {
+        project.model.site.shared_coordinates = transform;
+    } else {
+        project.model.transform(&transform);
+    }
+    save_project(&project, &args.project)?;
+    let [x, y, z] = transform.offset;
+    info!(
+        offset = %format!("{x},{y},{z}"),
+        rotation = transform.rotation,
+        shared = args.save_shared,
+        "project placed"
+    );
+    Ok(())
+}
+
+fn heal(args: HealArgs) -> Result<()> {
+    let path = match (&args.project, &args.input, &args.out) {
+        (Some(project), _, _) => project,
+        (None, Some(input), Some(out)) => return heal_step(input, out, args.tolerance),
+        _ => bail!("give a --project, or an --in STEP file and an --out file"),
+    };
+    let mut project = load_project(path)?;
+    let mut healed = Vec::new();
+    for element in project.model.elements() {
+        let (geometry, report) = heal_solid(&element.geometry, args.tolerance)
+            .with_context(|| format!("failed to heal {}", element.name))?;
+        if report.is_clean() {
+            continue;
+        }
+        println!("{}\t{report}", element.name);
+        let mut element = element.clone();
+        element.geometry = geometry;
+        element.tags.retain_valid(&element.geometry);
+        healed.push(element);
+    }
+    let count = healed.len();
+    if count > 0 {
+        ModelCommand::ReplaceElements { elements: healed }.apply(&mut project.model)?;
+        save_project(&project, path)?;
+    }
+    info!(healed = count, "element geometry healed");
+    Ok(())
+}
+
+/// Heals the shells of the STEP file `input` into one solid, printing what was fixed, and
+/// writes it to `out` as STEP or B-rep by its extension.
+fn heal_step(input: &Path, out: &Path, tolerance: f64) -> Result<()> {
+    let shells = import_step_shells(input)?;
+    let (solid, report) = heal_shells(&shells, tolerance)
+        .with_context(|| format!("failed to heal {}", input.display()))?;
+    println!("{}\t{report}", input.display());
+    let extension = out
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("step" | "stp") => export_step(&solid, out).context("STEP export failed")?,
+        Some(BREP_EXTENSION) => export_brep(&solid, out).context("B-rep export failed")?,
+        _ => bail!(
+            "unsupported output format for {} (expected .step, .stp or .{BREP_EXTENSION})",
+            out.display()
+        ),
+    }
+    info!(path = %out.display(), "healed solid written");
+    Ok(())
+}
+
+fn assign_levels(args: LevelsArgs) -> Result<()> {
+    let mut project = load_project(&args.project)?;
+    let model = &project.model;
+    let storey_name = |guid: Option<Guid>| {
+        guid.and_then(|guid| model.storey(guid))
+            .map_or_else(|| "-".to_string(), |storey| storey.name.clone())
+    };
+    let assignments = model.storey_assignments();
+    for assignment in &assignments {
+        let name = model.element(assignment.element).map_or_else(
+            || assignment.element.to_string(),
+            |element| element.name.clone(),
+        );
+        println!(
+            "{name}\t{}\t{}",
+            storey_name(assignment.from),
+            storey_name(Some(assignment.to))
+        );
+    }
+    let count = assignments.len();
+    if count > 0 && !args.dry_run {
+        let commands = assignments
+            .into_iter()
+            .map(|assignment| ModelCommand::MoveToStorey {
+                element: assignment.element,
+                storey: Some(assignment.to),
+            })
+            .collect();
+        ModelCommand::Batch(commands).apply(&mut project.model)?;
+        save_project(&project, &args.project)?;
+    }
+    info!(moved = count, "levels assigned");
+    Ok(())
+}
+
+fn set_parameter(args: SetArgs) -> Result<()> {
+    let mut project = load_project(&args.project)?;
+    let model = &mut project.model;
+    model.author = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    model.source = ChangeSource::Cli;
+    let guid = model
+        .elements()
+        .iter()
+        .find(|element| element.name == args.element)
+        .map(|element| element.guid)
+        .with_context(|| format!("unknown element `{}`", args.element))?;
+    let value = args.value.map(|value| {
+        if let Ok(value) = value.parse::<i64>() {
+            ParameterValue::Integer(value)
+        } else if let Ok(value) = value.parse::<f64>() {
+            ParameterValue::Number(value)
+        } else if let Ok(value) = value.parse::<bool>() {
+            ParameterValue::Bool(value)
+        } else {
+            ParameterValue::Text(value)
+        }
+    });
+    ModelCommand::SetParameter {
+        guid,
+        name: args.name.clone(),
+        value,
+    }
+    .apply(model)?;
+    save_project(&project, &args.project)?;
+    info!(element = %args.element, parameter = %args.name, "parameter set");
+    Ok(())
+}
+
+fn parameter_log(args: ParameterLogArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let model = &project.model;
+    let element = match &args.element {
+        Some(name) => Some(
+            model
+                .elements()
+                .iter()
+                .find(|element| element.name == *name)
+                .map(|element| element.guid)
+                .with_context(|| format!("unknown element `{name}`"))?,
+        ),
+        None => None,
+    };
+    match &args.out {
+        Some(out) => {
+            export_parameter_log_csv(model, element, out).context("parameter log export failed")?;
+            info!(path = %out.display(), "parameter log export complete");
+        }
+        None => write_parameter_log_csv(model, element, std::io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+fn replay(args: ReplayArgs) -> Result<()> {
+    let mut project = load_project(&args.project)?;
+    let journal = import_journal(&args.journal)?;
+    for (index, entry) in journal.entries.iter().enumerate() {
+        println!("{}\t{}", index + 1, entry.label);
+    }
+    let steps = journal
+        .replay(&mut project.model)
+        .context("journal replay failed")?;
+    if let Some(out) = &args.out {
+        save_project(&project, out)?;
+    }
+    info!(
+        steps,
+        revision = project.model.revision(),
+        "journal replayed"
+    );
+    Ok(())
+}
+
+fn export_parameters(args: ExportParametersArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let is_json = args
+        .out
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    if is_json {
+        export_parameters_json(&project.model, &args.out)
+    } else {
+        export_parameters_csv(&project.model, &args.out)
+    }
+    .context("parameter export failed")?;
+    info!(path = %args.out.display(), "parameter export complete");
+    Ok(())
+}
+
+fn import_parameter_values(args: ImportParametersArgs) -> Result<()> {
+    let mut project = load_project(&args.project)?;
+    let model = &mut project.model;
+    model.author = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+    model.source = ChangeSource::Cli;
+    let import = import_parameters(model, &args.from)?;
+    let mut driving = 0;
+    if let ModelCommand::Batch(commands) = &import.command {
+        for command in commands {
+            if let ModelCommand::SetParameter { guid, name, value } = command {
+                let element = model.element(*guid);
+                if element.is_some_and(|element| {
+                    parameter_schema(element.category)
+                        .iter()
+                        .any(|spec| spec.key == name)
+                }) {
+                    driving += 1;
+                }
+                let element =
+                    element.map_or_else(|| guid.to_string(), |element| element.name.clone());
+                let value = value
+                    .as_ref()
+                    .map_or_else(|| "-".to_string(), ToString::to_string);
+                println!("{element}\t{name}\t{value}");
+            }
+        }
+    }
+    if driving > 0 {
+        warn!(
+            driving,
+            "geometry is not rebuilt here; import in the viewer to regenerate the elements"
+        );
+    }
+    for guid in &import.unknown {
+        warn!(%guid, "unknown element skipped");
+    }
+    if import.changed > 0 && !args.dry_run {
+        import.command.apply(model)?;
+        save_project(&project, &args.project)?;
+    }
+    info!(changed = import.changed, "parameters imported");
+    Ok(())
+}
+
+fn check_ids(args: IdsArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let ids = import_ids(&args.ids)?;
+    let report = validate_ids(&project.model, &ids);
+    let mut failed = 0;
+    for specification in &report.specifications {
+        if specification.missing {
+            println!("{}\t-\tfail\tno applicable elements", specification.name);
+            failed += 1;
+        }
+        for element in &specification.elements {
+            if element.passed() {
+                println!("{}\t{}\tpass", specification.name, element.name);
+            } else {
+                println!(
+                    "{}\t{}\tfail\t{}",
+                    specification.name,
+                    element.name,
+                    element.failures.join("; ")
+                );
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{failed} IDS check(s) failed");
+    }
+    info!(
+        specifications = report.specifications.len(),
+        "model meets the IDS"
+    );
+    Ok(())
+}
+
+fn quantity_takeoff(args: TakeoffArgs) -> Result<()> {
+    let project = load_project(&args.project)?;
+    let report = takeoff(&project.model);
+    let rows = match args.by.to_ascii_lowercase().as_str() {
+        "category" => &report.by_category,
+        "type" => &report.by_type,
+        "material" => &report.by_material,
+        _ => bail!(
+            "unknown grouping `{}` (expected category, type or material)",
+            args.by
+        ),
+    };
+    let unit = report.units.length.symbol();
+    println!(
+        "{}\tcount\tlength ({unit})\tarea ({unit}²)\tvolume ({unit}³)",
+        args.by
+    );
+    for row in rows.iter().chain([&report.total]) {
+        println!(
+            "{}\t{}\t{:.1}\t{:.1}\t{:.1}",
+            row.name, row.count, row.length, row.area, row.volume
+        );
+    }
+    Ok(())
+}
+
+fn model_diff(args: DiffArgs) -> Result<()> {
+    let old = load_project(&args.old)?.model;
+    let new = load_project(&args.new)?.model;
+    let changes = diff(&old, &new);
+    let name = |model: &BimModel, guid| {
+        model
+            .element(guid)
+            .map_or_else(String::new, |element| element.name.clone())
+    };
+    for guid in &changes.added {
+        println!("added\t{}\t{guid}", name(&new, *guid));
+    }
+    for guid in &changes.removed {
+        println!("removed\t{}\t{guid}", name(&old, *guid));
+    }
+    for change in &changes.modified {
+        let mut details: Vec<String> = change
+            .parameters
+            .iter()
+            .map(|parameter| parameter.name.clone())
+            .collect();
+        details.extend(change.fields.iter().map(|field| field.to_string()));
+        if change.geometry {
+            details.push("geometry".to_string());
+        }
+        println!(
+            "modified\t{}\t{}\t{}",
+            change.name,
+            change.guid,
+            details.join(",")
+        );
+    }
+    info!(
+        added = changes.added.len(),
+        removed = changes.removed.len(),
+        modified = changes.modified.len(),
+        "model diff complete"
+    );
+    Ok(())
 }
 
 fn parse_size(text: &str) -> Result<(f64, f64, f64)> {
-    let parts: Vec<&str> = text.split(',').collect();
-    if parts.len() != 3 {
+    if text.split(',').count() != 3 {
         bail!("--size expects three comma-separated numbers, e.g. 100,200,300");
     }
-
-    let width: f64 = parts[0].trim().parse().context("invalid width")?;
-    let height: f64 = parts[1].trim().parse().context("invalid height")?;
-    let depth: f64 = parts[2].trim().parse().context("invalid depth")?;
-    Ok((width, height, depth))
+    match parse_coordinate(text).context("invalid --size")? {
+        CoordinateInput::Absolute([width, height, depth]) => Ok((width, height, depth)),
+        CoordinateInput::Relative(_) => bail!("--size does not accept a relative `@` value"),
+    }
 }
 
 fn init_tracing() {
@@ -0,0 +1,121 @@
+use anyhow::{Result, ensure};
+use cryxtal_io::{export_step, import_step};
+use cryxtal_shapeops::{DEFAULT_SHAPEOPS_TOLERANCE, plate_with_hole};
+use cryxtal_topology::{Edge, Face, Shell, Solid, SolidBuilder, Vertex, Wire};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn temp_path(file_name: &str) -> PathBuf {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    std::env::temp_dir().join(format!("cryxtal-cli-{stamp}-{file_name}"))
+}
+
+/// Runs the CLI with `args`, failing with its standard error when it does.
+fn cryxtal(args: &[&str]) -> Result<String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_cryxtal-cli"))
+        .args(args)
+        .output()?;
+    ensure!(
+        output.status.success(),
+        "cryxtal {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+fn path_arg(path: &Path) -> &str {
+    path.to_str().expect("temp paths are UTF-8")
+}
+
+/// `solid` with every face on vertices and edges of its own, as files that do not share
+/// topology between faces arrive.
+fn loose_faces(solid: &Solid) -> Solid {
+    let faces = solid.face_iter().map(|face| {
+        let mut vertices = HashMap::new();
+        let wires = face
+            .absolute_boundaries()
+            .iter()
+            .map(|wire| {
+                wire.edge_iter()
+                    .map(|edge| {
+                        let mut vertex = |vertex: &Vertex| {
+                            vertices
+                                .entry(vertex.id())
+                                .or_insert_with(|| Vertex::new(vertex.point()))
+                                .clone()
+                        };
+                        let front = vertex(edge.front());
+                        let back = vertex(edge.back());
+                        Edge::new(&front, &back, edge.oriented_curve())
+                    })
+                    .collect::<Wire>()
+            })
+            .collect();
+        let mut loose = Face::new(wires, face.surface());
+        if !face.orientation() {
+            loose.invert();
+        }
+        loose
+    });
+    Solid::new_unchecked(vec![faces.collect::<Shell>()])
+}
+
+#[test]
+fn convert_reads_step_into_glb() -> Result<()> {
+    let step = temp_path("box.step");
+    let glb = temp_path("box.glb");
+    export_step(&SolidBuilder::box_solid(100.0, 200.0, 300.0)?, &step)?;
+
+    let converted = cryxtal(&["convert", "--in", path_arg(&step), "--out", path_arg(&glb)]);
+    let bytes = fs::read(&glb);
+    let _ = fs::remove_file(&step);
+    let _ = fs::remove_file(&glb);
+    converted?;
+    let bytes = bytes?;
+    assert_eq!(&bytes[..4], b"glTF");
+    assert!(bytes.len() > 100);
+    Ok(())
+}
+
+#[test]
+fn holes_lists_the_holes_of_a_step_file() -> Result<()> {
+    let step = temp_path("plate.step");
+    let plate = plate_with_hole(200.0, 200.0, 20.0, 30.0, DEFAULT_SHAPEOPS_TOLERANCE)?;
+    export_step(&plate, &step)?;
+
+    let listed = cryxtal(&["holes", "--in", path_arg(&step)]);
+    let _ = fs::remove_file(&step);
+    let listed = listed?;
+    let rows: Vec<Vec<&str>> = listed
+        .lines()
+        .skip(1)
+        .map(|row| row.split('\t').collect())
+        .collect();
+    assert_eq!(rows.len(), 1);
+    let diameter: f64 = rows[0][2].parse()?;
+    assert!((diameter - 30.0).abs() < 0.5);
+    assert_eq!(rows[0][4], "through");
+    Ok(())
+}
+
+#[test]
+fn heal_sews_a_step_file_and_writes_it_out() -> Result<()> {
+    let step = temp_path("loose.step");
+    let healed = temp_path("healed.step");
+    let loose = loose_faces(&SolidBuilder::box_solid(100.0, 200.0, 300.0)?);
+    export_step(&loose, &step)?;
+
+    let printed = cryxtal(&["heal", "--in", path_arg(&step), "--out", path_arg(&healed)]);
+    let solid = import_step(&healed);
+    let _ = fs::remove_file(&step);
+    let _ = fs::remove_file(&healed);
+    assert!(printed?.contains("16 vertices merged, 12 edges merged"));
+    assert_eq!(solid?.face_iter().count(), 6);
+    Ok(())
+}
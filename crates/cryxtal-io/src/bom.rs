@@ -0,0 +1,85 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_bim::BomRow;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::schedule::csv_field;
+use crate::xlsx::{Cell, write_xlsx};
+
+const BOM_HEADER: [&str; 8] = [
+    "Item", "Level", "Name", "Kind", "Material", "Quantity", "Volume", "Weight",
+];
+
+/// Writes `rows` as CSV with a header line; volumes in m³, weights in kg, left empty where
+/// unknown.
+pub fn write_bom_csv(rows: &[BomRow], mut writer: impl Write) -> Result<()> {
+    writeln!(writer, "{}", BOM_HEADER.join(","))?;
+    for row in rows {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{:.4},{}",
+            row.item,
+            row.level,
+            csv_field(&row.name),
+            row.kind,
+            csv_field(&row.material),
+            row.quantity,
+            row.volume,
+            row.weight
+                .map_or_else(String::new, |weight| format!("{weight:.1}"))
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes `rows` as an Excel workbook with one `BOM` sheet, the columns of
+/// [`write_bom_csv`] and numbers kept as numbers.
+pub fn write_bom_xlsx(rows: &[BomRow], writer: impl Write) -> Result<()> {
+    let mut cells = vec![
+        BOM_HEADER
+            .iter()
+            .map(|title| Cell::Text(title.to_string()))
+            .collect::<Vec<_>>(),
+    ];
+    cells.extend(rows.iter().map(|row| {
+        vec![
+            Cell::Text(row.item.clone()),
+            Cell::Number(row.level as f64),
+            Cell::Text(row.name.clone()),
+            Cell::Text(row.kind.clone()),
+            Cell::Text(row.material.clone()),
+            Cell::Number(row.quantity as f64),
+            Cell::Number(row.volume),
+            row.weight.map_or(Cell::Empty, Cell::Number),
+        ]
+    }));
+    write_xlsx("BOM", &cells, writer)
+}
+
+/// Writes `rows` to `path` as CSV or Excel, by its `.csv` or `.xlsx` extension.
+pub fn export_bom(rows: &[BomRow], path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    let xlsx = match extension.as_deref() {
+        Some("csv") => false,
+        Some("xlsx") => true,
+        _ => bail!(
+            "unsupported BOM format for {} (expected .csv or .xlsx)",
+            path.display()
+        ),
+    };
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    if xlsx {
+        write_bom_xlsx(rows, &mut writer)?;
+    } else {
+        write_bom_csv(rows, &mut writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
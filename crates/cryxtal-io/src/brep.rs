@@ -0,0 +1,452 @@
+use anyhow::{Context, Result, anyhow, bail};
+use cryxtal_topology::{Curve, Solid, Surface};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use truck_modeling::{
+    BSplineCurve, BSplineSurface, IntersectionCurve, KnotVec, Line, Matrix4, NurbsCurve,
+    NurbsSurface, Plane, Point3, Processor, RevolutedCurve, Vector3,
+};
+use truck_topology::compress::{
+    CompressedEdge, CompressedEdgeIndex, CompressedFace, CompressedShell, CompressedSolid,
+};
+
+/// Bumped whenever the B-rep layout changes; every earlier version keeps loading.
+pub const BREP_FORMAT_VERSION: u32 = 1;
+pub const BREP_EXTENSION: &str = "cxbrep";
+
+const FORMAT_NAME: &str = "cryxtal-brep";
+
+/// Boundary representation of a solid in a layout owned by this crate rather than by truck, so
+/// files written today still load after the geometry kernel changes its own serialization.
+///
+/// Each shell lists its vertices as points, its edges as a pair of vertex indices and a curve,
+/// and its faces as a surface with boundary loops of edge indices. Curves run from their first
+/// vertex to their second; an edge used backwards in a loop has `forward: false`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrepSolid {
+    pub shells: Vec<BrepShell>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrepShell {
+    pub vertices: Vec<[f64; 3]>,
+    pub edges: Vec<BrepEdge>,
+    pub faces: Vec<BrepFace>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrepEdge {
+    pub vertices: [usize; 2],
+    pub curve: BrepCurve,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrepFace {
+    /// Outer loop first, then the holes.
+    pub boundaries: Vec<Vec<BrepEdgeUse>>,
+    /// Whether the face normal is the surface normal (`u × v`) rather than its opposite.
+    pub forward: bool,
+    pub surface: BrepSurface,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrepEdgeUse {
+    pub edge: usize,
+    pub forward: bool,
+}
+
+/// Edge curves. Knot vectors are stored in full; the degree follows from the knot and control
+/// point counts. Rational control points are homogeneous: `[x·w, y·w, z·w, w]`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BrepCurve {
+    Line {
+        from: [f64; 3],
+        to: [f64; 3],
+    },
+    BSpline {
+        knots: Vec<f64>,
+        control_points: Vec<[f64; 3]>,
+    },
+    Nurbs {
+        knots: Vec<f64>,
+        control_points: Vec<[f64; 4]>,
+    },
+    /// Intersection of two surfaces, traced near the B-spline `knots`/`control_points`, as
+    /// boolean operations leave behind.
+    Intersection {
+        surfaces: [Box<BrepSurface>; 2],
+        knots: Vec<f64>,
+        control_points: Vec<[f64; 3]>,
+    },
+}
+
+/// Face surfaces, with the same knot and control point conventions as [`BrepCurve`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BrepSurface {
+    Plane {
+        origin: [f64; 3],
+        u_axis: [f64; 3],
+        v_axis: [f64; 3],
+    },
+    BSpline {
+        u_knots: Vec<f64>,
+        v_knots: Vec<f64>,
+        control_points: Vec<Vec<[f64; 3]>>,
+    },
+    Nurbs {
+        u_knots: Vec<f64>,
+        v_knots: Vec<f64>,
+        control_points: Vec<Vec<[f64; 4]>>,
+    },
+    /// `curve` revolved a full turn about the axis through `origin`, then mapped by the
+    /// column-major `transform`; `inverted` swaps the surface's orientation.
+    Revolution {
+        curve: Box<BrepCurve>,
+        origin: [f64; 3],
+        axis: [f64; 3],
+        transform: [[f64; 4]; 4],
+        inverted: bool,
+    },
+}
+
+#[derive(Serialize)]
+struct BrepFileRef<'a> {
+    format: &'a str,
+    version: u32,
+    solid: &'a BrepSolid,
+}
+
+#[derive(Deserialize)]
+struct BrepHeader {
+    format: String,
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct BrepFile {
+    solid: BrepSolid,
+}
+
+impl BrepSolid {
+    pub fn from_solid(solid: &Solid) -> Self {
+        let compressed = solid.compress();
+        Self {
+            shells: compressed
+                .boundaries
+                .iter()
+                .map(|shell| BrepShell {
+                    vertices: shell.vertices.iter().map(|&point| point.into()).collect(),
+                    edges: shell
+                        .edges
+                        .iter()
+                        .map(|edge| BrepEdge {
+                            vertices: [edge.vertices.0, edge.vertices.1],
+                            curve: BrepCurve::from_curve(&edge.curve),
+                        })
+                        .collect(),
+                    faces: shell
+                        .faces
+                        .iter()
+                        .map(|face| BrepFace {
+                            boundaries: face
+                                .boundaries
+                                .iter()
+                                .map(|wire| {
+                                    wire.iter()
+                                        .map(|edge| BrepEdgeUse {
+                                            edge: edge.index,
+                                            forward: edge.orientation,
+                                        })
+                                        .collect()
+                                })
+                                .collect(),
+                            forward: face.orientation,
+                            surface: BrepSurface::from_surface(&face.surface),
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Rebuilds the solid, checking that every index is in range and every shell is closed.
+    pub fn to_solid(&self) -> Result<Solid> {
+        let mut boundaries = Vec::with_capacity(self.shells.len());
+        for (index, shell) in self.shells.iter().enumerate() {
+            let vertex_count = shell.vertices.len();
+            let mut edges = Vec::with_capacity(shell.edges.len());
+            for edge in &shell.edges {
+                if let Some(vertex) = edge.vertices.iter().find(|&&vertex| vertex >= vertex_count) {
+                    bail!("shell {index}: edge vertex {vertex} out of range ({vertex_count})");
+                }
+                edges.push(CompressedEdge {
+                    vertices: (edge.vertices[0], edge.vertices[1]),
+                    curve: edge.curve.to_curve()?,
+                });
+            }
+            let mut faces = Vec::with_capacity(shell.faces.len());
+            for face in &shell.faces {
+                let mut wires = Vec::with_capacity(face.boundaries.len());
+                for wire in &face.boundaries {
+                    if let Some(edge) = wire.iter().find(|edge| edge.edge >= edges.len()) {
+                        bail!(
+                            "shell {index}: face edge {} out of range ({})",
+                            edge.edge,
+                            edges.len()
+                        );
+                    }
+                    wires.push(
+                        wire.iter()
+                            .map(|edge| CompressedEdgeIndex {
+                                index: edge.edge,
+                                orientation: edge.forward,
+                            })
+                            .collect(),
+                    );
+                }
+                faces.push(CompressedFace {
+                    boundaries: wires,
+                    orientation: face.forward,
+                    surface: face.surface.to_surface()?,
+                });
+            }
+            boundaries.push(CompressedShell {
+                vertices: shell.vertices.iter().map(|&point| point.into()).collect(),
+                edges,
+                faces,
+            });
+        }
+        Solid::extract(CompressedSolid { boundaries })
+            .map_err(|err| anyhow!("invalid B-rep topology: {err}"))
+    }
+}
+
+impl BrepCurve {
+    fn from_curve(curve: &Curve) -> Self {
+        match curve {
+            Curve::Line(line) => BrepCurve::Line {
+                from: line.0.into(),
+                to: line.1.into(),
+            },
+            Curve::BSplineCurve(curve) => BrepCurve::BSpline {
+                knots: curve.knot_vec().to_vec(),
+                control_points: points(curve.control_points()),
+            },
+            Curve::NurbsCurve(curve) => BrepCurve::Nurbs {
+                knots: curve.non_rationalized().knot_vec().to_vec(),
+                control_points: points(curve.non_rationalized().control_points()),
+            },
+            Curve::IntersectionCurve(curve) => BrepCurve::Intersection {
+                surfaces: [
+                    Box::new(BrepSurface::from_surface(curve.surface0())),
+                    Box::new(BrepSurface::from_surface(curve.surface1())),
+                ],
+                knots: curve.leader().knot_vec().to_vec(),
+                control_points: points(curve.leader().control_points()),
+            },
+        }
+    }
+
+    fn to_curve(&self) -> Result<Curve> {
+        Ok(match self {
+            BrepCurve::Line { from, to } => {
+                Curve::Line(Line(Point3::from(*from), Point3::from(*to)))
+            }
+            BrepCurve::BSpline {
+                knots,
+                control_points,
+            } => Curve::BSplineCurve(bspline_curve(knots, control_points)?),
+            BrepCurve::Nurbs {
+                knots,
+                control_points,
+            } => Curve::NurbsCurve(NurbsCurve::new(bspline_curve(knots, control_points)?)),
+            BrepCurve::Intersection {
+                surfaces: [surface0, surface1],
+                knots,
+                control_points,
+            } => Curve::IntersectionCurve(IntersectionCurve::new_unchecked(
+                Box::new(surface0.to_surface()?),
+                Box::new(surface1.to_surface()?),
+                bspline_curve(knots, control_points)?,
+            )),
+        })
+    }
+}
+
+impl BrepSurface {
+    fn from_surface(surface: &Surface) -> Self {
+        match surface {
+            Surface::Plane(plane) => BrepSurface::Plane {
+                origin: plane.origin().into(),
+                u_axis: plane.u_axis().into(),
+                v_axis: plane.v_axis().into(),
+            },
+            Surface::BSplineSurface(surface) => {
+                let (u_knots, v_knots) = surface.knot_vecs();
+                BrepSurface::BSpline {
+                    u_knots: u_knots.to_vec(),
+                    v_knots: v_knots.to_vec(),
+                    control_points: surface
+                        .control_points()
+                        .iter()
+                        .map(|row| points(row))
+                        .collect(),
+                }
+            }
+            Surface::NurbsSurface(surface) => {
+                let surface = surface.non_rationalized();
+                let (u_knots, v_knots) = surface.knot_vecs();
+                BrepSurface::Nurbs {
+                    u_knots: u_knots.to_vec(),
+                    v_knots: v_knots.to_vec(),
+                    control_points: surface
+                        .control_points()
+                        .iter()
+                        .map(|row| points(row))
+                        .collect(),
+                }
+            }
+            Surface::RevolutedCurve(processor) => {
+                let revolution = processor.entity();
+                BrepSurface::Revolution {
+                    curve: Box::new(BrepCurve::from_curve(revolution.entity_curve())),
+                    origin: revolution.origin().into(),
+                    axis: revolution.axis().into(),
+                    transform: (*processor.transform()).into(),
+                    inverted: !processor.orientation(),
+                }
+            }
+        }
+    }
+
+    fn to_surface(&self) -> Result<Surface> {
+        Ok(match self {
+            BrepSurface::Plane {
+                origin,
+                u_axis,
+                v_axis,
+            } => {
+                let origin = Point3::from(*origin);
+                Surface::Plane(Plane::new(
+                    origin,
+                    origin + Vector3::from(*u_axis),
+                    origin + Vector3::from(*v_axis),
+                ))
+            }
+            BrepSurface::BSpline {
+                u_knots,
+                v_knots,
+                control_points,
+            } => Surface::BSplineSurface(bspline_surface(u_knots, v_knots, control_points)?),
+            BrepSurface::Nurbs {
+                u_knots,
+                v_knots,
+                control_points,
+            } => Surface::NurbsSurface(NurbsSurface::new(bspline_surface(
+                u_knots,
+                v_knots,
+                control_points,
+            )?)),
+            BrepSurface::Revolution {
+                curve,
+                origin,
+                axis,
+                transform,
+                inverted,
+            } => {
+                let revolution = RevolutedCurve::by_revolution(
+                    curve.to_curve()?,
+                    Point3::from(*origin),
+                    Vector3::from(*axis),
+                );
+                let mut processor = Processor::new(revolution);
+                processor.transform_by(Matrix4::from(*transform));
+                if *inverted {
+                    processor.invert();
+                }
+                Surface::RevolutedCurve(processor)
+            }
+        })
+    }
+}
+
+fn points<P: Copy + Into<[f64; N]>, const N: usize>(points: &[P]) -> Vec<[f64; N]> {
+    points.iter().map(|&point| point.into()).collect()
+}
+
+fn bspline_curve<P: From<[f64; N]>, const N: usize>(
+    knots: &[f64],
+    control_points: &[[f64; N]],
+) -> Result<BSplineCurve<P>> {
+    BSplineCurve::try_new(
+        KnotVec::from(knots.to_vec()),
+        control_points.iter().map(|&point| P::from(point)).collect(),
+    )
+    .context("invalid B-spline curve")
+}
+
+fn bspline_surface<P: From<[f64; N]>, const N: usize>(
+    u_knots: &[f64],
+    v_knots: &[f64],
+    control_points: &[Vec<[f64; N]>],
+) -> Result<BSplineSurface<P>> {
+    BSplineSurface::try_new(
+        (
+            KnotVec::from(u_knots.to_vec()),
+            KnotVec::from(v_knots.to_vec()),
+        ),
+        control_points
+            .iter()
+            .map(|row| row.iter().map(|&point| P::from(point)).collect())
+            .collect(),
+    )
+    .context("invalid B-spline surface")
+}
+
+/// Writes `solid` as pretty-printed JSON in the [`BrepSolid`] layout, tagged with the format
+/// name and [`BREP_FORMAT_VERSION`].
+pub fn export_brep(solid: &Solid, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("create output directory {}", parent.display()))?;
+    }
+    let text = brep_to_string(solid)?;
+    std::fs::write(path, text).with_context(|| format!("write B-rep file {}", path.display()))?;
+    Ok(())
+}
+
+pub fn import_brep(path: impl AsRef<Path>) -> Result<Solid> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("read B-rep file {}", path.display()))?;
+    brep_from_str(&text).with_context(|| format!("decode B-rep file {}", path.display()))
+}
+
+/// The text [`export_brep`] writes.
+pub fn brep_to_string(solid: &Solid) -> Result<String> {
+    serde_json::to_string_pretty(&BrepFileRef {
+        format: FORMAT_NAME,
+        version: BREP_FORMAT_VERSION,
+        solid: &BrepSolid::from_solid(solid),
+    })
+    .context("serialize B-rep")
+}
+
+/// Reads any version up to [`BREP_FORMAT_VERSION`].
+pub fn brep_from_str(text: &str) -> Result<Solid> {
+    let header: BrepHeader = serde_json::from_str(text).context("parse B-rep")?;
+    if header.format != FORMAT_NAME {
+        bail!("not a cryxtal B-rep (format `{}`)", header.format);
+    }
+    if header.version == 0 || header.version > BREP_FORMAT_VERSION {
+        bail!(
+            "unsupported B-rep format version {} (this build reads up to {BREP_FORMAT_VERSION})",
+            header.version
+        );
+    }
+    let file: BrepFile = serde_json::from_str(text).context("decode B-rep")?;
+    file.solid.to_solid()
+}
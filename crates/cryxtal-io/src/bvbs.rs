@@ -0,0 +1,303 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_bim::{BimCategory, BimElement, ParameterValue};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use truck_modeling::{InnerSpace, Point3, Vector3};
+
+use crate::schedule::{bar_mass_per_metre, bend_radius, hook_extension, set_cut_lengths};
+
+/// Distance in millimetres a bar corner may lie off the plane of the others and still be bent
+/// as a 2D bar.
+const PLANARITY_TOLERANCE: f64 = 0.5;
+
+/// Header fields of a BVBS file shared by every bar.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BvbsOptions {
+    /// Project number (`j`).
+    pub project: String,
+    /// Plan number (`r`).
+    pub plan: String,
+    /// Steel grade (`g`).
+    pub grade: String,
+}
+
+impl Default for BvbsOptions {
+    fn default() -> Self {
+        Self {
+            project: String::new(),
+            plan: String::new(),
+            grade: "B500B".to_string(),
+        }
+    }
+}
+
+/// One bar mark for the bending machine: identical bars bent in one plane. Legs are measured
+/// between the intersection points of the straight center lines, as BVBS expects; the bends
+/// between them are rounded at `mandrel`. Lengths are in millimetres, weights in kilograms.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BvbsBar {
+    /// Bar mark, numbering the bars from 1.
+    pub mark: usize,
+    pub diameter: f64,
+    pub count: usize,
+    /// Cut length of one bar, bends deducted.
+    pub cut_length: f64,
+    /// Weight of all `count` bars.
+    pub weight: f64,
+    /// Mandrel diameter the bar is bent around.
+    pub mandrel: f64,
+    pub legs: Vec<f64>,
+    /// Bend after each leg but the last in degrees, positive to the left.
+    pub angles: Vec<f64>,
+}
+
+/// Converts the rebar elements among `elements` into bending machine bars, sorted by
+/// diameter and shape. Single bars are bent at the corners of their polyline, which must lie
+/// in one plane; rebar sets give `BarCount` bars of their `Shape` at the cut lengths of
+/// [`CUT_LENGTHS_KEY`](crate::CUT_LENGTHS_KEY). Identical bars share a mark. Bends use the
+/// mandrel of [`bend_radius`]. Other elements and rebar missing `Diameter` are skipped.
+/// Elements are read as millimetres.
+pub fn bvbs_bars(elements: &[BimElement]) -> Result<Vec<BvbsBar>> {
+    // Keyed by tenths of a millimetre of diameter, then whole millimetres and degrees.
+    let mut groups: BTreeMap<(i64, Vec<i64>, Vec<i64>), (usize, f64)> = BTreeMap::new();
+    for element in elements {
+        if element.category != BimCategory::Rebar {
+            continue;
+        }
+        let Some(diameter) = number(element, "Diameter") else {
+            continue;
+        };
+        let shapes = if element.parameters.contains_key("BarCount") {
+            set_shapes(element, diameter)
+        } else {
+            polyline_shape(element).map(|shape| vec![shape])
+        }
+        .with_context(|| format!("{} cannot be bent", element.name))?;
+        for (count, legs, angles) in shapes {
+            let key = (
+                (diameter * 10.0).round() as i64,
+                legs.iter().map(|leg| leg.round() as i64).collect(),
+                angles.iter().map(|angle| angle.round() as i64).collect(),
+            );
+            let entry = groups.entry(key).or_insert((0, diameter));
+            entry.0 += count;
+        }
+    }
+
+    Ok(groups
+        .into_iter()
+        .enumerate()
+        .map(|(idx, ((_, legs, angles), (count, diameter)))| {
+            let legs: Vec<f64> = legs.into_iter().map(|leg| leg as f64).collect();
+            let angles: Vec<f64> = angles.into_iter().map(|angle| angle as f64).collect();
+            let cut_length = legs.iter().sum::<f64>()
+                - angles
+                    .iter()
+                    .map(|angle| bend_deduction(diameter, angle.abs().to_radians()))
+                    .sum::<f64>();
+            BvbsBar {
+                mark: idx + 1,
+                diameter,
+                count,
+                cut_length,
+                weight: count as f64 * cut_length * 1.0e-3 * bar_mass_per_metre(diameter),
+                mandrel: bend_radius(diameter) * 2.0,
+                legs,
+                angles,
+            }
+        })
+        .collect())
+}
+
+/// Writes `bars` as BVBS 2.0 `BF2D` lines, one per mark, each closed by its checksum.
+pub fn write_bvbs(bars: &[BvbsBar], options: &BvbsOptions, mut writer: impl Write) -> Result<()> {
+    for bar in bars {
+        let mut line = format!(
+            "BF2D@Hj{}@r{}@i@p{}@l{:.0}@n{}@e{:.3}@d{:.0}@g{}@s{:.0}@v@G",
+            header_field(&options.project),
+            header_field(&options.plan),
+            bar.mark,
+            bar.cut_length,
+            bar.count,
+            bar.weight,
+            bar.diameter,
+            header_field(&options.grade),
+            bar.mandrel,
+        );
+        for (index, leg) in bar.legs.iter().enumerate() {
+            let angle = bar.angles.get(index).copied().unwrap_or(0.0);
+            line.push_str(&format!("l{leg:.0}@w{angle:.0}@"));
+        }
+        line.push('C');
+        line.push_str(&format!("{}@", bvbs_checksum(&line)));
+        write!(writer, "{line}\r\n")?;
+    }
+    Ok(())
+}
+
+pub fn export_bvbs(bars: &[BvbsBar], options: &BvbsOptions, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_bvbs(bars, options, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Checksum of a BVBS line up to and including its `@C`: 96 less the sum of its bytes
+/// modulo 32.
+pub fn bvbs_checksum(line: &str) -> u32 {
+    let sum: u32 = line.bytes().map(u32::from).sum();
+    96 - sum % 32
+}
+
+/// Legs and signed bends between the corners of a single bar.
+fn polyline_shape(element: &BimElement) -> Result<(usize, Vec<f64>, Vec<f64>)> {
+    let points = corner_points(element)?;
+    let directions: Vec<Vector3> = points.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    if directions
+        .iter()
+        .any(|direction| direction.magnitude() <= 1.0e-6)
+    {
+        bail!("rebar segment is too short");
+    }
+    let normal = directions
+        .windows(2)
+        .map(|pair| pair[0].cross(pair[1]))
+        .find(|normal| normal.magnitude() > 1.0e-9)
+        .map(|normal| normal.normalize());
+    if let Some(normal) = normal
+        && points
+            .iter()
+            .any(|point| (point - points[0]).dot(normal).abs() > PLANARITY_TOLERANCE)
+    {
+        bail!("its corners do not lie in one plane");
+    }
+    let legs = directions
+        .iter()
+        .map(|direction| direction.magnitude())
+        .collect();
+    let angles = directions
+        .windows(2)
+        .map(|pair| {
+            let angle = pair[0].angle(pair[1]).0.to_degrees();
+            match normal {
+                Some(normal) if pair[0].cross(pair[1]).dot(normal) < 0.0 => -angle,
+                _ => angle,
+            }
+        })
+        .collect();
+    Ok((1, legs, angles))
+}
+
+/// Corners of a polyline element: `Point{n}` up to `PointCount`, else `Start` and `End`.
+pub(crate) fn corner_points(element: &BimElement) -> Result<Vec<Point3>> {
+    let read = |prefix: &str| -> Result<Point3> {
+        let [x, y, z] =
+            ["X", "Y", "Z"].map(|axis| read_number(element, &format!("{prefix}{axis}")));
+        Ok(Point3::new(x?, y?, z?))
+    };
+    match element.parameters.get("PointCount") {
+        Some(ParameterValue::Integer(count)) if *count >= 2 => (1..=*count)
+            .map(|idx| read(&format!("Point{idx}")))
+            .collect(),
+        Some(_) => bail!("rebar must have at least 2 points"),
+        None => Ok(vec![read("Start")?, read("End")?]),
+    }
+}
+
+/// Legs of the bars of a rebar set by cut length, from [`set_cut_lengths`]: how many bars
+/// share each whole-millimetre length, and their legs with the run found from that length.
+fn set_shapes(element: &BimElement, diameter: f64) -> Result<Vec<(usize, Vec<f64>, Vec<f64>)>> {
+    let count = match element.parameters.get("BarCount") {
+        Some(ParameterValue::Integer(count)) if *count > 0 => *count as usize,
+        _ => bail!("rebar set has no bars"),
+    };
+    let length = number(element, "Length").context("rebar set has no Length")?;
+    let mut lengths: BTreeMap<i64, usize> = BTreeMap::new();
+    for cut_length in set_cut_lengths(element, count, length) {
+        *lengths.entry(cut_length.round() as i64).or_default() += 1;
+    }
+    lengths
+        .into_iter()
+        .map(|(cut_length, count)| {
+            let (legs, angles) = set_bar_shape(element, diameter, cut_length as f64)?;
+            Ok((count, legs, angles))
+        })
+        .collect()
+}
+
+/// Legs and bends of one bar of a rebar set cut at `cut_length`.
+fn set_bar_shape(
+    element: &BimElement,
+    diameter: f64,
+    cut_length: f64,
+) -> Result<(Vec<f64>, Vec<f64>)> {
+    let shape = match element.parameters.get("Shape") {
+        Some(ParameterValue::Text(shape)) => shape.as_str(),
+        _ => "Straight",
+    };
+    let right = bend_deduction(diameter, 90f64.to_radians());
+    let (legs, angles) = match shape {
+        "Straight" => (vec![cut_length], Vec::new()),
+        "L" => {
+            let leg = number(element, "Leg").context("L bars need a Leg")?;
+            (vec![cut_length - leg + right, leg], vec![90.0])
+        }
+        "U" => {
+            let leg = number(element, "Leg").context("U bars need a Leg")?;
+            let run = cut_length - 2.0 * leg + 2.0 * right;
+            (vec![leg, run, leg], vec![90.0, 90.0])
+        }
+        "Stirrup" => {
+            let depth = number(element, "TieDepth").context("stirrups need a TieDepth")?;
+            let hook_angle = 135f64.to_radians();
+            let center_radius = bend_radius(diameter) + diameter * 0.5;
+            let hook = center_radius * (hook_angle * 0.5).tan() + hook_extension(diameter);
+            let run = (cut_length - 2.0 * hook - 2.0 * depth
+                + 2.0 * bend_deduction(diameter, hook_angle)
+                + 3.0 * right)
+                * 0.5;
+            (
+                vec![hook, run, depth, run, depth, hook],
+                vec![135.0, 90.0, 90.0, 90.0, 135.0],
+            )
+        }
+        other => bail!("unknown rebar shape: {other}"),
+    };
+    if legs.iter().any(|leg| *leg <= 0.0) {
+        bail!("its cut length is too short for a {shape} bar");
+    }
+    Ok((legs, angles))
+}
+
+/// Length saved by bending a corner of `angle` radians at the mandrel instead of running both
+/// legs to their intersection point.
+fn bend_deduction(diameter: f64, angle: f64) -> f64 {
+    let radius = bend_radius(diameter) + diameter * 0.5;
+    2.0 * radius * (angle * 0.5).tan() - radius * angle
+}
+
+fn number(element: &BimElement, key: &str) -> Option<f64> {
+    match element.parameters.get(key) {
+        Some(ParameterValue::Number(value)) if *value > 0.0 => Some(*value),
+        _ => None,
+    }
+}
+
+fn read_number(element: &BimElement, key: &str) -> Result<f64> {
+    match element.parameters.get(key) {
+        Some(ParameterValue::Number(value)) => Ok(*value),
+        _ => bail!("missing or invalid rebar parameter: {key}"),
+    }
+}
+
+/// `text` without the `@` separators and control characters a header field cannot hold.
+fn header_field(text: &str) -> String {
+    text.chars()
+        .filter(|c| *c != '@' && !c.is_control())
+        .collect()
+}
@@ -0,0 +1,220 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement};
+use cryxtal_topology::Point3;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use truck_modeling::InnerSpace;
+use truck_polymesh::obj;
+
+use crate::distance::Bvh;
+use crate::mesh::{TessellationOptions, triangulate_faces};
+use crate::point_cloud::import_point_cloud;
+
+/// Signed deviation of the scan points closest to one face of a designed element. Positive
+/// deviations lie outside the solid, negative ones inside. Lengths are in model units.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaceDeviation {
+    pub element: Guid,
+    /// Index of the face in the element's solid.
+    pub face: usize,
+    /// Scan points assigned to the face.
+    pub points: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl FaceDeviation {
+    /// `min` or `max`, whichever lies further from the design.
+    pub fn worst(&self) -> f64 {
+        if self.min.abs() > self.max.abs() {
+            self.min
+        } else {
+            self.max
+        }
+    }
+}
+
+/// Reads scan points from `path`: the vertices of a `.obj` mesh or a `.ply` cloud, otherwise a
+/// point cloud as text with the first three numbers of each line as X, Y and Z (`.xyz`, `.pts`,
+/// `.csv`).
+pub fn import_scan_points(path: impl AsRef<Path>) -> Result<Vec<Point3>> {
+    let path = path.as_ref();
+    let is_ply = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ply"));
+    if is_ply {
+        return Ok(import_point_cloud(path, None)?.points);
+    }
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let is_obj = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("obj"));
+    let points = if is_obj {
+        obj::read(BufReader::new(file))
+            .with_context(|| format!("failed to read OBJ file {}", path.display()))?
+            .positions()
+            .to_vec()
+    } else {
+        read_scan_points(BufReader::new(file))
+            .with_context(|| format!("failed to read point cloud {}", path.display()))?
+    };
+    if points.is_empty() {
+        bail!("{} holds no points", path.display());
+    }
+    Ok(points)
+}
+
+/// Points of a text point cloud, one per line. Separators may be spaces, tabs, commas or
+/// semicolons; lines without three leading numbers, such as headers, counts and comments, are
+/// skipped, and numbers after the third (intensity, color) are ignored.
+pub fn read_scan_points(reader: impl BufRead) -> Result<Vec<Point3>> {
+    let mut points = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let mut numbers = line
+            .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+            .filter(|field| !field.is_empty())
+            .map(|field| field.parse::<f64>());
+        if let (Some(Ok(x)), Some(Ok(y)), Some(Ok(z))) =
+            (numbers.next(), numbers.next(), numbers.next())
+        {
+            points.push(Point3::new(x, y, z));
+        }
+    }
+    Ok(points)
+}
+
+/// Compares `points` of an as-built scan with the designed `elements`: each point is assigned
+/// to the nearest element face within `max_distance` and measured from it, signed by the side
+/// of the face it lies on. Points further from every face, such as scanned furniture or
+/// neighbouring buildings, are ignored. Returns one entry per face that received points, in
+/// element and face order. Openings are left out. `tolerance` is the tessellation tolerance
+/// the faces are measured on. The triangles are indexed by a bounding volume hierarchy, so a
+/// large `max_distance` does not slow the search down.
+pub fn scan_deviation<'a>(
+    elements: impl IntoIterator<Item = &'a BimElement>,
+    points: &[Point3],
+    max_distance: f64,
+    tolerance: f64,
+) -> Result<Vec<FaceDeviation>> {
+    if max_distance.is_nan() || max_distance <= 0.0 {
+        bail!("maximum deviation distance must be > 0, got {max_distance}");
+    }
+    let mut faces = Vec::new();
+    let mut slots = Vec::new();
+    let mut triangles = Vec::new();
+    for element in elements {
+        if element.category == BimCategory::Opening {
+            continue;
+        }
+        for (face, face_triangles) in
+            triangulate_faces(&element.geometry, &TessellationOptions::chordal(tolerance))
+                .into_iter()
+                .enumerate()
+        {
+            let slot = faces.len();
+            faces.push(FaceSum::new(element.guid, face));
+            slots.extend(std::iter::repeat_n(slot, face_triangles.len()));
+            triangles.extend(face_triangles);
+        }
+    }
+
+    let bvh = Bvh::new(triangles);
+    for point in points {
+        let Some((index, [a, b, c], closest, distance)) = bvh.nearest(*point, max_distance) else {
+            continue;
+        };
+        let normal = (b - a).cross(c - a);
+        let deviation = if (point - closest).dot(normal) < 0.0 {
+            -distance
+        } else {
+            distance
+        };
+        faces[slots[index]].add(deviation);
+    }
+
+    Ok(faces
+        .into_iter()
+        .filter(|face| face.points > 0)
+        .map(|face| FaceDeviation {
+            element: face.element,
+            face: face.face,
+            points: face.points,
+            min: face.min,
+            max: face.max,
+            mean: face.sum / face.points as f64,
+        })
+        .collect())
+}
+
+/// Writes `rows` as CSV, naming elements from `elements` where they are found there.
+pub fn write_deviation_csv(
+    rows: &[FaceDeviation],
+    elements: &[BimElement],
+    mut writer: impl Write,
+) -> Result<()> {
+    writeln!(writer, "Element,Face,Points,Min,Max,Mean")?;
+    for row in rows {
+        let name = elements
+            .iter()
+            .find(|element| element.guid == row.element)
+            .map(|element| element.name.replace('"', "\"\""))
+            .unwrap_or_else(|| row.element.to_string());
+        writeln!(
+            writer,
+            "\"{name}\",{},{},{:.2},{:.2},{:.2}",
+            row.face + 1,
+            row.points,
+            row.min,
+            row.max,
+            row.mean
+        )?;
+    }
+    Ok(())
+}
+
+pub fn export_deviation_csv(
+    rows: &[FaceDeviation],
+    elements: &[BimElement],
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_deviation_csv(rows, elements, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+struct FaceSum {
+    element: Guid,
+    face: usize,
+    points: usize,
+    min: f64,
+    max: f64,
+    sum: f64,
+}
+
+impl FaceSum {
+    fn new(element: Guid, face: usize) -> Self {
+        Self {
+            element,
+            face,
+            points: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.0,
+        }
+    }
+
+    fn add(&mut self, deviation: f64) {
+        self.points += 1;
+        self.min = self.min.min(deviation);
+        self.max = self.max.max(deviation);
+        self.sum += deviation;
+    }
+}
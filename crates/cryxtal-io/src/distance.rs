@@ -0,0 +1,239 @@
+use anyhow::{Result, bail};
+use cryxtal_bim::BimElement;
+use cryxtal_topology::{Point3, Vector3, closest_point_on_triangle, triangle_distance};
+use truck_modeling::InnerSpace;
+
+use crate::mesh::{DEFAULT_TESSELLATION_TOLERANCE, TessellationOptions, triangulate_faces};
+
+/// Triangles per BVH leaf.
+const LEAF_SIZE: usize = 4;
+
+/// Shortest distance between the surfaces of two elements, with the closest point on each,
+/// tessellated at [`DEFAULT_TESSELLATION_TOLERANCE`] (so suited to millimetre models); see
+/// [`distance_with_tolerance`].
+pub fn distance(a: &BimElement, b: &BimElement) -> Result<(f64, Point3, Point3)> {
+    distance_with_tolerance(a, b, DEFAULT_TESSELLATION_TOLERANCE)
+}
+
+/// Shortest distance between the surfaces of `a` and `b` tessellated at `tolerance`, with the
+/// closest point on `a` and on `b`. Touching or intersecting surfaces give 0 at a common point.
+/// Surfaces are compared, not volumes: an element entirely inside another is as far from it as
+/// from its boundary. Each tessellation is indexed by a bounding volume hierarchy, so only
+/// triangle pairs whose boxes can still beat the best distance found are compared.
+pub fn distance_with_tolerance(
+    a: &BimElement,
+    b: &BimElement,
+    tolerance: f64,
+) -> Result<(f64, Point3, Point3)> {
+    let bvh_a = Bvh::of(a, tolerance)?;
+    let bvh_b = Bvh::of(b, tolerance)?;
+    let mut best = (
+        f64::INFINITY,
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(0.0, 0.0, 0.0),
+    );
+    let mut stack = vec![(0, 0)];
+    while let Some((node_a, node_b)) = stack.pop() {
+        let (box_a, box_b) = (&bvh_a.nodes[node_a], &bvh_b.nodes[node_b]);
+        if box_distance(box_a, box_b) >= best.0 {
+            continue;
+        }
+        match (&box_a.content, &box_b.content) {
+            (Content::Leaf(range_a), Content::Leaf(range_b)) => {
+                for (_, triangle_a) in &bvh_a.triangles[range_a.clone()] {
+                    for (_, triangle_b) in &bvh_b.triangles[range_b.clone()] {
+                        let (gap, pa, pb) = triangle_distance(triangle_a, triangle_b);
+                        if gap < best.0 {
+                            best = (gap, pa, pb);
+                        }
+                    }
+                }
+            }
+            // Descend the larger box first, keeping the nearer child on top of the stack.
+            (Content::Split(left, right), _)
+                if matches!(box_b.content, Content::Leaf(_)) || box_a.size() >= box_b.size() =>
+            {
+                let mut pairs = [(*left, node_b), (*right, node_b)];
+                pairs.sort_by(|x, y| {
+                    box_distance(&bvh_a.nodes[y.0], box_b)
+                        .total_cmp(&box_distance(&bvh_a.nodes[x.0], box_b))
+                });
+                stack.extend(pairs);
+            }
+            (_, Content::Split(left, right)) => {
+                let mut pairs = [(node_a, *left), (node_a, *right)];
+                pairs.sort_by(|x, y| {
+                    box_distance(box_a, &bvh_b.nodes[y.1])
+                        .total_cmp(&box_distance(box_a, &bvh_b.nodes[x.1]))
+                });
+                stack.extend(pairs);
+            }
+            (Content::Split(..), Content::Leaf(_)) => unreachable!("handled by the guard above"),
+        }
+    }
+    Ok(best)
+}
+
+/// Axis-aligned bounding volume hierarchy over the triangles of one tessellation.
+pub(crate) struct Bvh {
+    /// The root is node 0; there are no nodes over no triangles.
+    nodes: Vec<Node>,
+    /// Triangles with their index in the list the hierarchy was built from, reordered so each
+    /// leaf covers a contiguous range.
+    triangles: Vec<(usize, [Point3; 3])>,
+}
+
+struct Node {
+    min: Point3,
+    max: Point3,
+    content: Content,
+}
+
+enum Content {
+    Leaf(std::ops::Range<usize>),
+    Split(usize, usize),
+}
+
+impl Node {
+    /// Length of the box diagonal.
+    fn size(&self) -> f64 {
+        (self.max - self.min).magnitude()
+    }
+}
+
+impl Bvh {
+    fn of(element: &BimElement, tolerance: f64) -> Result<Self> {
+        let triangles: Vec<[Point3; 3]> =
+            triangulate_faces(&element.geometry, &TessellationOptions::chordal(tolerance))
+                .into_iter()
+                .flatten()
+                .collect();
+        if triangles.is_empty() {
+            bail!("{} has no faces to measure from", element.name);
+        }
+        Ok(Self::new(triangles))
+    }
+
+    pub(crate) fn new(triangles: Vec<[Point3; 3]>) -> Self {
+        let mut bvh = Self {
+            nodes: Vec::new(),
+            triangles: triangles.into_iter().enumerate().collect(),
+        };
+        if !bvh.triangles.is_empty() {
+            bvh.build(0..bvh.triangles.len());
+        }
+        bvh
+    }
+
+    /// Triangle nearest to `point` no further than `max_distance`, as its index in the list
+    /// the hierarchy was built from, the triangle, the closest point on it and the distance.
+    pub(crate) fn nearest(
+        &self,
+        point: Point3,
+        max_distance: f64,
+    ) -> Option<(usize, &[Point3; 3], Point3, f64)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let mut best: Option<(usize, &[Point3; 3], Point3, f64)> = None;
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            let limit = best.map_or(max_distance, |best| best.3);
+            if point_box_distance(point, node) > limit {
+                continue;
+            }
+            match &node.content {
+                Content::Leaf(range) => {
+                    for (index, triangle) in &self.triangles[range.clone()] {
+                        let closest = closest_point_on_triangle(triangle, point);
+                        let distance = (point - closest).magnitude();
+                        if distance <= max_distance && best.is_none_or(|best| distance < best.3) {
+                            best = Some((*index, triangle, closest, distance));
+                        }
+                    }
+                }
+                // The nearer child goes on top of the stack.
+                Content::Split(left, right) => {
+                    let mut children = [*left, *right];
+                    children.sort_by(|a, b| {
+                        point_box_distance(point, &self.nodes[*b])
+                            .total_cmp(&point_box_distance(point, &self.nodes[*a]))
+                    });
+                    stack.extend(children);
+                }
+            }
+        }
+        best
+    }
+
+    /// Adds the node over `range` and its descendants, splitting at the median centroid along
+    /// the longest axis. Returns the node's index.
+    fn build(&mut self, range: std::ops::Range<usize>) -> usize {
+        let triangles = &mut self.triangles[range.clone()];
+        let (min, max) = bounds(triangles.iter().flat_map(|(_, triangle)| triangle));
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            min,
+            max,
+            content: Content::Leaf(range.clone()),
+        });
+        if triangles.len() <= LEAF_SIZE {
+            return index;
+        }
+        let extent = max - min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let centroid = |(_, triangle): &(usize, [Point3; 3])| {
+            triangle
+                .iter()
+                .map(|point| [point.x, point.y, point.z][axis])
+                .sum::<f64>()
+        };
+        let middle = triangles.len() / 2;
+        triangles.select_nth_unstable_by(middle, |a, b| centroid(a).total_cmp(&centroid(b)));
+        let left = self.build(range.start..range.start + middle);
+        let right = self.build(range.start + middle..range.end);
+        self.nodes[index].content = Content::Split(left, right);
+        index
+    }
+}
+
+fn bounds<'a>(mut points: impl Iterator<Item = &'a Point3>) -> (Point3, Point3) {
+    let first = *points.next().expect("bounds of no points");
+    points.fold((first, first), |(min, max), p| {
+        (
+            Point3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+            Point3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+        )
+    })
+}
+
+/// Distance from `point` to the box of `node`, 0 inside it.
+fn point_box_distance(point: Point3, node: &Node) -> f64 {
+    let gap = |value: f64, min: f64, max: f64| (min - value).max(value - max).max(0.0);
+    Vector3::new(
+        gap(point.x, node.min.x, node.max.x),
+        gap(point.y, node.min.y, node.max.y),
+        gap(point.z, node.min.z, node.max.z),
+    )
+    .magnitude()
+}
+
+/// Gap between two boxes, 0 where they overlap.
+fn box_distance(a: &Node, b: &Node) -> f64 {
+    let gap = |a_min: f64, a_max: f64, b_min: f64, b_max: f64| {
+        (b_min - a_max).max(a_min - b_max).max(0.0)
+    };
+    Vector3::new(
+        gap(a.min.x, a.max.x, b.min.x, b.max.x),
+        gap(a.min.y, a.max.y, b.min.y, b.max.y),
+        gap(a.min.z, a.max.z, b.min.z, b.max.z),
+    )
+    .magnitude()
+}
@@ -0,0 +1,209 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_bim::{BimCategory, BimElement, BimModel, HatchPattern, ParameterValue};
+use cryxtal_topology::{Plane, Point3, Vector3, plane_section};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::hatch::hatch_segments;
+use crate::mesh::TessellationOptions;
+
+/// Layer for elements when the model has no layers.
+const DEFAULT_LAYER: &str = "0";
+/// Spacing of hatch lines, in metres.
+const HATCH_SPACING_M: f64 = 0.1;
+/// Standard AutoCAD color indices 1–9 and their colors; layer colors snap to the nearest.
+const ACI_COLORS: [(u8, [u8; 3]); 9] = [
+    (1, [255, 0, 0]),
+    (2, [255, 255, 0]),
+    (3, [0, 255, 0]),
+    (4, [0, 255, 255]),
+    (5, [0, 0, 255]),
+    (6, [255, 0, 255]),
+    (7, [255, 255, 255]),
+    (8, [128, 128, 128]),
+    (9, [192, 192, 192]),
+];
+
+/// Plan section of the model cut at `elevation`, as 2D DXF (R12): each element's cut outline
+/// as polylines on the DXF layer named after its layer, closed where the cut goes around the
+/// element, and hatched with lines after [`BimModel::hatch_pattern`] so the drawing reads in
+/// black and white. The model's layers become DXF layers with the nearest standard color.
+/// Openings are left out, as their hosts are already cut around them; elements the plane
+/// misses are too. Curved outlines follow `tessellation`.
+pub fn write_dxf_plan(
+    model: &BimModel,
+    elevation: f64,
+    tessellation: &TessellationOptions,
+    mut writer: impl Write,
+) -> Result<()> {
+    if !elevation.is_finite() {
+        bail!("elevation must be finite");
+    }
+    let plane = Plane {
+        origin: Point3::new(0.0, 0.0, elevation),
+        normal: Vector3::new(0.0, 0.0, 1.0),
+    };
+    let sections: Vec<(String, HatchPattern, Vec<Vec<Point3>>)> = model
+        .elements()
+        .iter()
+        .filter(|element| element.category != BimCategory::Opening)
+        .map(|element| {
+            (
+                element_layer(model, element),
+                model.hatch_pattern(element),
+                plane_section(
+                    &element.geometry,
+                    &plane,
+                    tessellation.tolerance_for(&element.geometry),
+                ),
+            )
+        })
+        .filter(|(_, _, polylines)| !polylines.is_empty())
+        .collect();
+    if sections.is_empty() {
+        bail!("nothing to draw at elevation {elevation}");
+    }
+
+    let mut dxf = DxfWriter { out: &mut writer };
+    dxf.pair(0, "SECTION")?;
+    dxf.pair(2, "HEADER")?;
+    dxf.pair(9, "$ACADVER")?;
+    dxf.pair(1, "AC1009")?;
+    dxf.pair(0, "ENDSEC")?;
+
+    dxf.pair(0, "SECTION")?;
+    dxf.pair(2, "TABLES")?;
+    dxf.pair(0, "TABLE")?;
+    dxf.pair(2, "LAYER")?;
+    let layers: Vec<(String, u8)> = if model.layers.is_empty() {
+        vec![(DEFAULT_LAYER.to_string(), 7)]
+    } else {
+        model
+            .layers
+            .iter()
+            .map(|layer| (layer_name(&layer.name), aci_color(layer.color)))
+            .collect()
+    };
+    dxf.pair(70, layers.len())?;
+    for (name, color) in &layers {
+        dxf.pair(0, "LAYER")?;
+        dxf.pair(2, name)?;
+        dxf.pair(70, 0)?;
+        dxf.pair(62, color)?;
+        dxf.pair(6, "CONTINUOUS")?;
+    }
+    dxf.pair(0, "ENDTAB")?;
+    dxf.pair(0, "ENDSEC")?;
+
+    dxf.pair(0, "SECTION")?;
+    dxf.pair(2, "ENTITIES")?;
+    let hatch_spacing = HATCH_SPACING_M / model.units.length.in_meters();
+    for (layer, pattern, polylines) in &sections {
+        // R12 has no HATCH entity, so hatches are plain lines.
+        for [start, end] in hatch_segments(polylines, *pattern, hatch_spacing) {
+            dxf.pair(0, "LINE")?;
+            dxf.pair(8, layer)?;
+            dxf.pair(10, format!("{:.6}", start.x))?;
+            dxf.pair(20, format!("{:.6}", start.y))?;
+            dxf.pair(30, "0.0")?;
+            dxf.pair(11, format!("{:.6}", end.x))?;
+            dxf.pair(21, format!("{:.6}", end.y))?;
+            dxf.pair(31, "0.0")?;
+        }
+        for polyline in polylines {
+            let closed = polyline.len() > 2 && polyline.first() == polyline.last();
+            let points = if closed {
+                &polyline[..polyline.len() - 1]
+            } else {
+                &polyline[..]
+            };
+            dxf.pair(0, "POLYLINE")?;
+            dxf.pair(8, layer)?;
+            dxf.pair(66, 1)?;
+            dxf.pair(70, u8::from(closed))?;
+            for point in points {
+                dxf.pair(0, "VERTEX")?;
+                dxf.pair(8, layer)?;
+                dxf.pair(10, format!("{:.6}", point.x))?;
+                dxf.pair(20, format!("{:.6}", point.y))?;
+                dxf.pair(30, "0.0")?;
+            }
+            dxf.pair(0, "SEQEND")?;
+            dxf.pair(8, layer)?;
+        }
+    }
+    dxf.pair(0, "ENDSEC")?;
+    dxf.pair(0, "EOF")?;
+    Ok(())
+}
+
+pub fn export_dxf_plan(
+    model: &BimModel,
+    elevation: f64,
+    tessellation: &TessellationOptions,
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_dxf_plan(model, elevation, tessellation, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+struct DxfWriter<W> {
+    out: W,
+}
+
+impl<W: Write> DxfWriter<W> {
+    /// One group: its code, then its value, each on a line of its own.
+    fn pair(&mut self, code: u16, value: impl std::fmt::Display) -> std::io::Result<()> {
+        writeln!(self.out, "{code:>3}\n{value}")
+    }
+}
+
+/// DXF layer of `element`: its layer, or the first layer if it names none the model has, as
+/// [`BimModel::layer_color`] does.
+fn element_layer(model: &BimModel, element: &BimElement) -> String {
+    let named = match element.parameters.get("Layer") {
+        Some(ParameterValue::Text(name)) => model.layers.iter().find(|layer| layer.name == *name),
+        _ => None,
+    };
+    named.or(model.layers.first()).map_or_else(
+        || DEFAULT_LAYER.to_string(),
+        |layer| layer_name(&layer.name),
+    )
+}
+
+/// `name` with the characters DXF does not allow in layer names replaced by `_`.
+fn layer_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || "<>/\\\":;?*|=`".contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    if name.trim().is_empty() {
+        DEFAULT_LAYER.to_string()
+    } else {
+        name
+    }
+}
+
+/// Standard color index nearest an sRGBA color.
+fn aci_color([r, g, b, _]: [u8; 4]) -> u8 {
+    let distance = |[cr, cg, cb]: [u8; 3]| {
+        let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).pow(2);
+        d(r, cr) + d(g, cg) + d(b, cb)
+    };
+    ACI_COLORS
+        .iter()
+        .min_by_key(|(_, color)| distance(*color))
+        .map_or(7, |(index, _)| *index)
+}
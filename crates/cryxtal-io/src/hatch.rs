@@ -0,0 +1,63 @@
+use cryxtal_bim::HatchPattern;
+use cryxtal_topology::Point3;
+
+/// Most lines one family may put across an outline; denser patterns are spread out to this.
+const MAX_HATCH_LINES: f64 = 10_000.0;
+
+/// Segments filling the closed polylines of `outlines` in XY (Z is dropped) with `pattern`, its
+/// spacing scaled by `spacing`. Regions inside an even number of outlines, like the hole of a
+/// section around an opening, stay empty; open polylines are ignored.
+pub fn hatch_segments(
+    outlines: &[Vec<Point3>],
+    pattern: HatchPattern,
+    spacing: f64,
+) -> Vec<[Point3; 2]> {
+    let edges: Vec<[[f64; 2]; 2]> = outlines
+        .iter()
+        .filter(|outline| outline.len() > 3 && outline.first() == outline.last())
+        .flat_map(|outline| outline.windows(2))
+        .map(|pair| [[pair[0].x, pair[0].y], [pair[1].x, pair[1].y]])
+        .collect();
+    let mut segments = Vec::new();
+    if edges.is_empty() || spacing.is_nan() || spacing <= 0.0 {
+        return segments;
+    }
+    for family in pattern.lines() {
+        let (sin, cos) = family.angle_deg.to_radians().sin_cos();
+        let along = |[x, y]: [f64; 2]| x * cos + y * sin;
+        let across = |[x, y]: [f64; 2]| y * cos - x * sin;
+        let (low, high) = edges
+            .iter()
+            .map(|[start, _]| across(*start))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), c| {
+                (low.min(c), high.max(c))
+            });
+        let step = (spacing * family.spacing).max((high - low) / MAX_HATCH_LINES);
+        let first = (low / step - family.offset).ceil() as i64;
+        let last = (high / step - family.offset).floor() as i64;
+        for index in first..=last {
+            let c = (index as f64 + family.offset) * step;
+            // Where the line crosses the outlines; an edge counts when its ends lie on
+            // either side, with ends on the line taken as above it so vertices count once.
+            let mut crossings: Vec<f64> = edges
+                .iter()
+                .filter_map(|&[start, end]| {
+                    let (a, b) = (across(start) - c, across(end) - c);
+                    ((a >= 0.0) != (b >= 0.0)).then(|| {
+                        let t = a / (a - b);
+                        along(start) + (along(end) - along(start)) * t
+                    })
+                })
+                .collect();
+            crossings.sort_by(f64::total_cmp);
+            let point = |u: f64| Point3::new(u * cos - c * sin, u * sin + c * cos, 0.0);
+            segments.extend(
+                crossings
+                    .chunks_exact(2)
+                    .filter(|pair| pair[1] > pair[0])
+                    .map(|pair| [point(pair[0]), point(pair[1])]),
+            );
+        }
+    }
+    segments
+}
@@ -0,0 +1,275 @@
+use cryxtal_topology::{Point3, Solid, Vector3};
+use truck_meshalgo::prelude::*;
+
+use crate::mesh::{TessellationOptions, triangulate_faces};
+
+/// Largest `|cos|` between a face normal and the fitted axis for the face to count as
+/// cylindrical.
+const AXIS_TOLERANCE: f64 = 1.0e-2;
+/// Smallest angle spread (as `|sin|`) between a face's normals before it is treated as curved.
+const CURVATURE_TOLERANCE: f64 = 1.0e-3;
+
+/// Cylindrical hole recognized in a solid.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HoleFeature {
+    /// Center of the hole's opening. Through holes enter at the end their axis runs away from,
+    /// with the axis along the positive direction of its dominant component.
+    pub entry: Point3,
+    /// Unit axis pointing from the entry into the material.
+    pub direction: Vector3,
+    pub diameter: f64,
+    pub depth: f64,
+    /// Whether the hole is open at both ends.
+    pub through: bool,
+}
+
+/// Cylindrical segment of one face, before segments of the same hole are merged.
+#[derive(Clone, Copy, Debug)]
+struct Cylinder {
+    /// A point on the axis, at axis coordinate 0.
+    origin: Point3,
+    axis: Vector3,
+    radius: f64,
+    start: f64,
+    end: f64,
+}
+
+/// Finds cylindrical holes in `solid`: faces whose normals are perpendicular to a common axis,
+/// lie on a circle around it and point towards it. Faces split along the circumference are
+/// merged into one hole. `tol` is the tessellation tolerance; the fit tolerance follows from it.
+pub fn recognize_holes(solid: &Solid, tol: f64) -> Vec<HoleFeature> {
+    let faces = triangulate_faces(solid, &TessellationOptions::chordal(tol));
+
+    let fit_tolerance = tol.max(1.0e-3) * 2.0;
+    let mut cylinders: Vec<Cylinder> = Vec::new();
+    for face in &faces {
+        let Some(cylinder) = fit_hole_cylinder(face, fit_tolerance) else {
+            continue;
+        };
+        match cylinders
+            .iter_mut()
+            .find(|other| same_hole(other, &cylinder, fit_tolerance))
+        {
+            Some(other) => {
+                let flip = if other.axis.dot(cylinder.axis) < 0.0 {
+                    -1.0
+                } else {
+                    1.0
+                };
+                let shift = (cylinder.origin - other.origin).dot(other.axis);
+                let (a, b) = (cylinder.start * flip + shift, cylinder.end * flip + shift);
+                other.start = other.start.min(a.min(b));
+                other.end = other.end.max(a.max(b));
+            }
+            None => cylinders.push(cylinder),
+        }
+    }
+
+    let solid_triangles: Vec<[Point3; 3]> = faces.into_iter().flatten().collect();
+    let probe = fit_tolerance.max(1.0) * 2.0;
+    cylinders
+        .into_iter()
+        .map(|cylinder| {
+            let low = cylinder.origin + cylinder.axis * cylinder.start;
+            let high = cylinder.origin + cylinder.axis * cylinder.end;
+            let low_open = !contains(&solid_triangles, low - cylinder.axis * probe);
+            let high_open = !contains(&solid_triangles, high + cylinder.axis * probe);
+            let (entry, direction) = if low_open || !high_open {
+                (low, cylinder.axis)
+            } else {
+                (high, -cylinder.axis)
+            };
+            HoleFeature {
+                entry,
+                direction,
+                diameter: cylinder.radius * 2.0,
+                depth: cylinder.end - cylinder.start,
+                through: low_open && high_open,
+            }
+        })
+        .collect()
+}
+
+fn fit_hole_cylinder(triangles: &[[Point3; 3]], tol: f64) -> Option<Cylinder> {
+    let normals: Vec<(Point3, Vector3)> = triangles
+        .iter()
+        .filter_map(|&[a, b, c]| {
+            let normal = (b - a).cross(c - a);
+            let length = normal.magnitude();
+            let center = Point3::from_vec((a.to_vec() + b.to_vec() + c.to_vec()) / 3.0);
+            (length > 1.0e-12).then(|| (center, normal / length))
+        })
+        .collect();
+    let (_, first) = *normals.first()?;
+    let (_, widest) = normals
+        .iter()
+        .max_by(|(_, a), (_, b)| {
+            first
+                .cross(*a)
+                .magnitude()
+                .total_cmp(&first.cross(*b).magnitude())
+        })
+        .copied()?;
+    let axis = first.cross(widest);
+    if axis.magnitude() < CURVATURE_TOLERANCE {
+        return None;
+    }
+    let axis = axis.normalize();
+    // Point axes along their dominant positive direction so through holes read the same way
+    // however the face was parametrized.
+    let dominant = [axis.x, axis.y, axis.z]
+        .into_iter()
+        .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+        .unwrap_or(1.0);
+    let axis = if dominant < 0.0 { -axis } else { axis };
+    if normals
+        .iter()
+        .any(|(_, normal)| normal.dot(axis).abs() > AXIS_TOLERANCE)
+    {
+        return None;
+    }
+
+    let u = if axis.x.abs() < 0.9 {
+        Vector3::unit_x()
+    } else {
+        Vector3::unit_y()
+    };
+    let u = (u - axis * u.dot(axis)).normalize();
+    let v = axis.cross(u);
+    let points: Vec<Point3> = triangles.iter().flatten().copied().collect();
+    let (cx, cy, radius) = fit_circle(
+        points
+            .iter()
+            .map(|p| (p.to_vec().dot(u), p.to_vec().dot(v))),
+    )?;
+    let origin = Point3::from_vec(u * cx + v * cy);
+    let radial = |p: Point3| {
+        let offset = p - origin;
+        offset - axis * offset.dot(axis)
+    };
+    if points
+        .iter()
+        .any(|p| (radial(*p).magnitude() - radius).abs() > tol)
+    {
+        return None;
+    }
+    // Outward normals of a hole wall point at the axis; on a boss they point away.
+    let facing: f64 = normals
+        .iter()
+        .map(|(center, normal)| radial(*center).dot(*normal))
+        .sum();
+    if facing >= 0.0 {
+        return None;
+    }
+
+    let (start, end) = points
+        .iter()
+        .map(|p| (*p - origin).dot(axis))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), t| {
+            (min.min(t), max.max(t))
+        });
+    Some(Cylinder {
+        origin,
+        axis,
+        radius,
+        start,
+        end,
+    })
+}
+
+/// Least-squares circle through 2D points (Kåsa fit): center and radius.
+fn fit_circle(points: impl Iterator<Item = (f64, f64)>) -> Option<(f64, f64, f64)> {
+    // Solves x² + y² + d·x + e·y + f = 0 through the normal equations.
+    let mut m = [[0.0; 3]; 3];
+    let mut rhs = [0.0; 3];
+    let mut count = 0;
+    for (x, y) in points {
+        let row = [x, y, 1.0];
+        let value = -(x * x + y * y);
+        for ((m_row, rhs), a) in m.iter_mut().zip(&mut rhs).zip(row) {
+            for (cell, b) in m_row.iter_mut().zip(row) {
+                *cell += a * b;
+            }
+            *rhs += a * value;
+        }
+        count += 1;
+    }
+    if count < 3 {
+        return None;
+    }
+    let [d, e, f] = solve3(m, rhs)?;
+    let (cx, cy) = (-d * 0.5, -e * 0.5);
+    let squared = cx * cx + cy * cy - f;
+    (squared > 0.0).then(|| (cx, cy, squared.sqrt()))
+}
+
+fn solve3(m: [[f64; 3]; 3], rhs: [f64; 3]) -> Option<[f64; 3]> {
+    let det = |m: [[f64; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+    let denominator = det(m);
+    if denominator.abs() < 1.0e-12 {
+        return None;
+    }
+    Some(std::array::from_fn(|column| {
+        let mut replaced = m;
+        for (row, value) in replaced.iter_mut().zip(rhs) {
+            row[column] = value;
+        }
+        det(replaced) / denominator
+    }))
+}
+
+/// Whether two cylindrical segments share axis line and radius and touch along the axis.
+fn same_hole(a: &Cylinder, b: &Cylinder, tol: f64) -> bool {
+    if a.axis.dot(b.axis).abs() < 1.0 - AXIS_TOLERANCE * AXIS_TOLERANCE {
+        return false;
+    }
+    let offset = b.origin - a.origin;
+    let along = offset.dot(a.axis);
+    if (offset - a.axis * along).magnitude() > tol || (a.radius - b.radius).abs() > tol {
+        return false;
+    }
+    let flip = a.axis.dot(b.axis).signum();
+    let (b_start, b_end) = {
+        let (s, e) = (b.start * flip + along, b.end * flip + along);
+        (s.min(e), s.max(e))
+    };
+    b_start <= a.end + tol && a.start <= b_end + tol
+}
+
+/// Even-odd ray test against the closed triangle soup of the solid. The ray is slightly skewed
+/// so it does not run along axis-aligned edges.
+fn contains(triangles: &[[Point3; 3]], point: Point3) -> bool {
+    let direction = Vector3::new(0.5773, 0.5779, 0.5768).normalize();
+    triangles
+        .iter()
+        .filter(|triangle| ray_hits(point, direction, triangle))
+        .count()
+        % 2
+        == 1
+}
+
+/// Möller–Trumbore ray/triangle intersection in front of `origin`.
+fn ray_hits(origin: Point3, direction: Vector3, triangle: &[Point3; 3]) -> bool {
+    let [a, b, c] = *triangle;
+    let (ab, ac) = (b - a, c - a);
+    let p = direction.cross(ac);
+    let det = ab.dot(p);
+    if det.abs() < 1.0e-12 {
+        return false;
+    }
+    let t_vec = origin - a;
+    let u = t_vec.dot(p) / det;
+    if !(0.0..=1.0).contains(&u) {
+        return false;
+    }
+    let q = t_vec.cross(ab);
+    let v = direction.dot(q) / det;
+    if v < 0.0 || u + v > 1.0 {
+        return false;
+    }
+    ac.dot(q) / det > 1.0e-9
+}
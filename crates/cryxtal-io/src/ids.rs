@@ -0,0 +1,480 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, BimModel, ParameterValue};
+use regex::Regex;
+use roxmltree::{Document, Node};
+use std::path::Path;
+
+use crate::ifc::{PROPERTY_SET_NAME, entity, ifc_guid};
+
+/// Information Delivery Specification: requirements on the elements a model delivers, read
+/// from IDS XML with [`read_ids`].
+#[derive(Clone, Debug, Default)]
+pub struct Ids {
+    pub title: String,
+    pub specifications: Vec<IdsSpecification>,
+}
+
+/// Requirements on the elements matching every applicability facet.
+#[derive(Clone, Debug)]
+pub struct IdsSpecification {
+    pub name: String,
+    /// At least one element must be applicable (`minOccurs` above 0).
+    pub required: bool,
+    pub applicability: Vec<IdsFacet>,
+    pub requirements: Vec<IdsRequirement>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdsCardinality {
+    Required,
+    /// Need not be there, but must match where it is.
+    Optional,
+    Prohibited,
+}
+
+#[derive(Clone, Debug)]
+pub struct IdsRequirement {
+    pub facet: IdsFacet,
+    pub cardinality: IdsCardinality,
+}
+
+/// What an element is checked for, as mapped by the IFC export: the entity of its category,
+/// its `Name` and `GlobalId`, its parameters as properties and its `Material` parameter.
+#[derive(Clone, Debug)]
+pub enum IdsFacet {
+    /// IFC entity, such as `IFCWALL`.
+    Entity(IdsValue),
+    Attribute {
+        name: IdsValue,
+        value: Option<IdsValue>,
+    },
+    /// A parameter `Set.Name` is property `Name` of property set `Set`; any other parameter
+    /// is in the property set the IFC export writes them to.
+    Property {
+        property_set: IdsValue,
+        base_name: IdsValue,
+        value: Option<IdsValue>,
+    },
+    Material(Option<IdsValue>),
+    /// Facets this checker does not evaluate (classification, part of); they never match.
+    Unsupported(String),
+}
+
+/// Value constraint of a facet: a plain value, or an XML Schema restriction.
+#[derive(Clone, Debug)]
+pub enum IdsValue {
+    Simple(String),
+    Restriction(IdsRestriction),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct IdsRestriction {
+    pub enumeration: Vec<String>,
+    pub pattern: Option<Regex>,
+    /// Lower bound and whether it is inclusive.
+    pub min: Option<(f64, bool)>,
+    /// Upper bound and whether it is inclusive.
+    pub max: Option<(f64, bool)>,
+    pub length: Option<usize>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+}
+
+impl IdsValue {
+    /// Whether `text` satisfies the constraint. Numbers compare as numbers, so `3000` matches
+    /// `3000.0`; `case_insensitive` is for IFC entity names.
+    pub fn matches(&self, text: &str, case_insensitive: bool) -> bool {
+        match self {
+            IdsValue::Simple(expected) => {
+                if case_insensitive {
+                    return expected.eq_ignore_ascii_case(text);
+                }
+                match (expected.parse::<f64>(), text.parse::<f64>()) {
+                    (Ok(expected), Ok(value)) => expected == value,
+                    _ => expected == text,
+                }
+            }
+            IdsValue::Restriction(restriction) => restriction.matches(text, case_insensitive),
+        }
+    }
+}
+
+impl IdsRestriction {
+    fn matches(&self, text: &str, case_insensitive: bool) -> bool {
+        if !self.enumeration.is_empty()
+            && !self
+                .enumeration
+                .iter()
+                .any(|option| IdsValue::Simple(option.clone()).matches(text, case_insensitive))
+        {
+            return false;
+        }
+        if let Some(pattern) = &self.pattern
+            && !pattern.is_match(text)
+        {
+            return false;
+        }
+        if self.min.is_some() || self.max.is_some() {
+            let Ok(value) = text.parse::<f64>() else {
+                return false;
+            };
+            let above = match self.min {
+                Some((min, true)) => value >= min,
+                Some((min, false)) => value > min,
+                None => true,
+            };
+            let below = match self.max {
+                Some((max, true)) => value <= max,
+                Some((max, false)) => value < max,
+                None => true,
+            };
+            if !above || !below {
+                return false;
+            }
+        }
+        let length = text.chars().count();
+        self.length.is_none_or(|expected| length == expected)
+            && self.min_length.is_none_or(|min| length >= min)
+            && self.max_length.is_none_or(|max| length <= max)
+    }
+}
+
+/// Outcome of checking a model against an [`Ids`], per specification.
+#[derive(Clone, Debug, Default)]
+pub struct IdsReport {
+    pub specifications: Vec<IdsSpecificationReport>,
+}
+
+#[derive(Clone, Debug)]
+pub struct IdsSpecificationReport {
+    pub name: String,
+    /// The specification requires applicable elements and the model has none.
+    pub missing: bool,
+    /// Every applicable element, passed or failed.
+    pub elements: Vec<IdsElementResult>,
+}
+
+#[derive(Clone, Debug)]
+pub struct IdsElementResult {
+    pub element: Guid,
+    pub name: String,
+    /// Requirements the element breaks, described; empty when it passes.
+    pub failures: Vec<String>,
+}
+
+impl IdsElementResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl IdsSpecificationReport {
+    pub fn passed(&self) -> bool {
+        !self.missing && self.elements.iter().all(IdsElementResult::passed)
+    }
+}
+
+impl IdsReport {
+    pub fn passed(&self) -> bool {
+        self.specifications
+            .iter()
+            .all(IdsSpecificationReport::passed)
+    }
+}
+
+/// Checks every element of `model` (openings excepted, as the IFC export writes them with
+/// their hosts) against the specifications of `ids` that apply to it. Values compare in the
+/// model's units.
+pub fn validate_ids(model: &BimModel, ids: &Ids) -> IdsReport {
+    let specifications = ids
+        .specifications
+        .iter()
+        .map(|specification| {
+            let elements: Vec<IdsElementResult> = model
+                .elements()
+                .iter()
+                .filter(|element| element.category != BimCategory::Opening)
+                .filter(|element| {
+                    specification
+                        .applicability
+                        .iter()
+                        .all(|facet| facet_matches(element, facet, false))
+                })
+                .map(|element| IdsElementResult {
+                    element: element.guid,
+                    name: element.name.clone(),
+                    failures: specification
+                        .requirements
+                        .iter()
+                        .filter_map(|requirement| requirement_failure(element, requirement))
+                        .collect(),
+                })
+                .collect();
+            IdsSpecificationReport {
+                name: specification.name.clone(),
+                missing: specification.required && elements.is_empty(),
+                elements,
+            }
+        })
+        .collect();
+    IdsReport { specifications }
+}
+
+/// Whether `element` has what `facet` describes; with `present_only`, only whether it has
+/// the attribute, property or material at all, whatever its value.
+fn facet_matches(element: &BimElement, facet: &IdsFacet, present_only: bool) -> bool {
+    let value_matches = |constraint: &Option<IdsValue>, text: &str| {
+        present_only
+            || constraint
+                .as_ref()
+                .is_none_or(|constraint| constraint.matches(text, false))
+    };
+    match facet {
+        IdsFacet::Entity(name) => name.matches(entity(element), true),
+        IdsFacet::Attribute { name, value } => attributes(element)
+            .iter()
+            .any(|(attribute, text)| name.matches(attribute, false) && value_matches(value, text)),
+        IdsFacet::Property {
+            property_set,
+            base_name,
+            value,
+        } => element.parameters.iter().any(|(key, parameter)| {
+            let (set, property) = key.split_once('.').unwrap_or((PROPERTY_SET_NAME, key));
+            property_set.matches(set, false)
+                && base_name.matches(property, false)
+                && value_matches(value, &value_text(parameter))
+        }),
+        IdsFacet::Material(value) => match element.parameters.get("Material") {
+            Some(ParameterValue::Text(material)) => value_matches(value, material),
+            _ => false,
+        },
+        IdsFacet::Unsupported(_) => false,
+    }
+}
+
+fn requirement_failure(element: &BimElement, requirement: &IdsRequirement) -> Option<String> {
+    let facet = &requirement.facet;
+    let failed = match requirement.cardinality {
+        IdsCardinality::Required => !facet_matches(element, facet, false),
+        IdsCardinality::Optional => {
+            facet_matches(element, facet, true) && !facet_matches(element, facet, false)
+        }
+        IdsCardinality::Prohibited => facet_matches(element, facet, false),
+    };
+    failed.then(|| {
+        let what = describe(facet);
+        match requirement.cardinality {
+            IdsCardinality::Prohibited => format!("{what} is prohibited"),
+            _ if facet_matches(element, facet, true) => format!("{what} has another value"),
+            _ => format!("{what} is missing"),
+        }
+    })
+}
+
+fn attributes(element: &BimElement) -> [(&'static str, String); 2] {
+    [
+        ("Name", element.name.clone()),
+        ("GlobalId", ifc_guid(element.guid)),
+    ]
+}
+
+fn describe(facet: &IdsFacet) -> String {
+    match facet {
+        IdsFacet::Entity(name) => format!("entity {}", value_label(name)),
+        IdsFacet::Attribute { name, value } => {
+            format!("attribute {}{}", value_label(name), expected(value))
+        }
+        IdsFacet::Property {
+            property_set,
+            base_name,
+            value,
+        } => format!(
+            "property {}.{}{}",
+            value_label(property_set),
+            value_label(base_name),
+            expected(value)
+        ),
+        IdsFacet::Material(value) => format!("material{}", expected(value)),
+        IdsFacet::Unsupported(name) => format!("{name} facet (not supported)"),
+    }
+}
+
+fn expected(value: &Option<IdsValue>) -> String {
+    value
+        .as_ref()
+        .map_or_else(String::new, |value| format!(" = {}", value_label(value)))
+}
+
+fn value_label(value: &IdsValue) -> String {
+    match value {
+        IdsValue::Simple(text) => text.clone(),
+        IdsValue::Restriction(restriction) if !restriction.enumeration.is_empty() => {
+            restriction.enumeration.join("|")
+        }
+        IdsValue::Restriction(restriction) => match &restriction.pattern {
+            Some(pattern) => format!("/{}/", pattern.as_str()),
+            None => "(restricted)".to_string(),
+        },
+    }
+}
+
+/// IDS spells booleans as `TRUE`/`FALSE`.
+fn value_text(value: &ParameterValue) -> String {
+    match value {
+        ParameterValue::Bool(value) => value.to_string().to_ascii_uppercase(),
+        value => value.to_string(),
+    }
+}
+
+/// Reads an IDS document (version 1.0, namespace prefixes free). Facets other than entity,
+/// attribute, property and material are kept as [`IdsFacet::Unsupported`].
+pub fn read_ids(xml: &str) -> Result<Ids> {
+    let document = Document::parse(xml).context("invalid IDS XML")?;
+    let root = document.root_element();
+    if root.tag_name().name() != "ids" {
+        bail!("not an IDS document (root `{}`)", root.tag_name().name());
+    }
+    let title = child(root, "info")
+        .and_then(|info| child(info, "title"))
+        .and_then(|title| title.text())
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let mut specifications = Vec::new();
+    for specification in child(root, "specifications")
+        .into_iter()
+        .flat_map(|node| children(node, "specification"))
+    {
+        let name = specification
+            .attribute("name")
+            .unwrap_or("Unnamed")
+            .to_string();
+        let context = || format!("specification `{name}`");
+        let applicability = child(specification, "applicability");
+        let required = applicability
+            .and_then(|node| node.attribute("minOccurs"))
+            .is_some_and(|occurs| occurs.trim() != "0");
+        let applicability = applicability
+            .into_iter()
+            .flat_map(|node| node.children().filter(Node::is_element))
+            .map(facet)
+            .collect::<Result<Vec<_>>>()
+            .with_context(context)?;
+        let requirements = child(specification, "requirements")
+            .into_iter()
+            .flat_map(|node| node.children().filter(Node::is_element))
+            .map(|node| {
+                let cardinality = match node.attribute("cardinality").unwrap_or("required") {
+                    "required" => IdsCardinality::Required,
+                    "optional" => IdsCardinality::Optional,
+                    "prohibited" => IdsCardinality::Prohibited,
+                    other => bail!("unknown cardinality `{other}`"),
+                };
+                Ok(IdsRequirement {
+                    facet: facet(node)?,
+                    cardinality,
+                })
+            })
+            .collect::<Result<Vec<_>>>()
+            .with_context(context)?;
+        specifications.push(IdsSpecification {
+            name,
+            required,
+            applicability,
+            requirements,
+        });
+    }
+    Ok(Ids {
+        title,
+        specifications,
+    })
+}
+
+pub fn import_ids(path: impl AsRef<Path>) -> Result<Ids> {
+    let path = path.as_ref();
+    let xml = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    read_ids(&xml).with_context(|| format!("failed to read IDS file {}", path.display()))
+}
+
+fn facet(node: Node) -> Result<IdsFacet> {
+    let required = |name: &str| -> Result<IdsValue> {
+        match child(node, name) {
+            Some(node) => value(node),
+            None => bail!("{} facet has no {name}", node.tag_name().name()),
+        }
+    };
+    let optional = |name: &str| child(node, name).map(value).transpose();
+    Ok(match node.tag_name().name() {
+        "entity" => IdsFacet::Entity(required("name")?),
+        "attribute" => IdsFacet::Attribute {
+            name: required("name")?,
+            value: optional("value")?,
+        },
+        "property" => IdsFacet::Property {
+            property_set: required("propertySet")?,
+            base_name: required("baseName")?,
+            value: optional("value")?,
+        },
+        "material" => IdsFacet::Material(optional("value")?),
+        other => IdsFacet::Unsupported(other.to_string()),
+    })
+}
+
+fn value(node: Node) -> Result<IdsValue> {
+    if let Some(simple) = child(node, "simpleValue") {
+        return Ok(IdsValue::Simple(
+            simple.text().unwrap_or_default().trim().to_string(),
+        ));
+    }
+    let Some(restriction) = child(node, "restriction") else {
+        bail!(
+            "{} has neither a simple value nor a restriction",
+            node.tag_name().name()
+        );
+    };
+    let mut parsed = IdsRestriction::default();
+    for facet in restriction.children().filter(Node::is_element) {
+        let text = facet.attribute("value").unwrap_or_default();
+        let number = || {
+            text.parse::<f64>()
+                .with_context(|| format!("invalid bound `{text}`"))
+        };
+        let count = || {
+            text.parse::<usize>()
+                .with_context(|| format!("invalid length `{text}`"))
+        };
+        match facet.tag_name().name() {
+            "enumeration" => parsed.enumeration.push(text.to_string()),
+            // XML Schema patterns match the whole value.
+            "pattern" => {
+                parsed.pattern = Some(
+                    Regex::new(&format!("^(?:{text})$"))
+                        .with_context(|| format!("invalid pattern `{text}`"))?,
+                )
+            }
+            "minInclusive" => parsed.min = Some((number()?, true)),
+            "minExclusive" => parsed.min = Some((number()?, false)),
+            "maxInclusive" => parsed.max = Some((number()?, true)),
+            "maxExclusive" => parsed.max = Some((number()?, false)),
+            "length" => parsed.length = Some(count()?),
+            "minLength" => parsed.min_length = Some(count()?),
+            "maxLength" => parsed.max_length = Some(count()?),
+            other => bail!("unsupported restriction `{other}`"),
+        }
+    }
+    Ok(IdsValue::Restriction(parsed))
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
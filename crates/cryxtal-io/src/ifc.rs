@@ -1,5 +1,559 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, BimModel, ParameterValue};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 
-pub fn export_ifc_stub(_path: impl AsRef<std::path::Path>) -> Result<()> {
-    Err(cryxtal_base::Error::NotImplemented("IFC export is not implemented").into())
+use crate::mesh::{TessellationOptions, triangulate_solid};
+use crate::model_mesh::triangles;
+use crate::step::step_string_literal;
+
+/// Characters of IFC's compressed GlobalId encoding, six bits each.
+const IFC_GUID_CHARS: &[u8; 64] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz_$";
+/// Property set holding an element's parameters. Custom sets must not use the `Pset_` prefix,
+/// which is reserved for the standard ones.
+pub(crate) const PROPERTY_SET_NAME: &str = "Cryxtal_Parameters";
+/// Bits flipped in the site guid to give the IfcProject its GlobalId, which stays the same
+/// across exports of a model so other tools can match re-exports.
+const PROJECT_GUID_MASK: u128 = 0x5052_4f4a_4543_5400_0000_0000_0000_0000;
+/// Bits flipped in the guid of an element cut from others, such as a lintel, to give the
+/// opening element voiding them its GlobalId.
+const CUT_GUID_MASK: u128 = 0x4355_5400_0000_0000_0000_0000_0000_0000;
+
+/// 22-character IFC GlobalId of `guid`: its 128 bits in base 64, two bits in the first
+/// character.
+pub fn ifc_guid(guid: Guid) -> String {
+    encode_ifc_guid(guid.as_uuid().as_u128())
+}
+
+fn encode_ifc_guid(value: u128) -> String {
+    (0..22)
+        .map(|index| {
+            let shift = 126 - 6 * index;
+            char::from(IFC_GUID_CHARS[((value >> shift) & 0x3f) as usize])
+        })
+        .collect()
+}
+
+/// Writes `elements` of `model` as an IFC4 file: the project, site, buildings and storeys of
+/// the model's spatial structure, each element contained in its storey (or the site) as the
+/// IFC entity of its category, with its parameters in a property set. Walls and slabs are
+/// extruded from their footprint and outline, everything else is triangulated with
+/// `tessellation`. Openings are written with the elements they void, hosted or cut through,
+/// rather than on their own, and elements cut from others, such as lintels and sills, void
+/// those with an opening element of their shape; reference meshes are left out. Elements are
+/// read as millimetres.
+///
+/// Entities are written out after each element, so only the element being written and the
+/// ids of the elements each storey contains are held in memory.
+pub fn write_ifc<'a>(
+    model: &BimModel,
+    elements: impl IntoIterator<Item = &'a BimElement>,
+    tessellation: &TessellationOptions,
+    file_name: &str,
+    mut writer: impl Write,
+) -> Result<()> {
+    write!(
+        writer,
+        "ISO-10303-21;\nHEADER;\nFILE_DESCRIPTION(('ViewDefinition [ReferenceView_V1.2]'),'2;1');\n\
+         FILE_NAME({},'',(''),(''),'cryxtal-castor','cryxtal-castor','');\n\
+         FILE_SCHEMA(('IFC4'));\nENDSEC;\nDATA;\n",
+        step_string_literal(file_name)
+    )?;
+    let mut ifc = IfcWriter::default();
+    let origin = ifc.point3([0.0; 3]);
+    let z_axis = ifc.add("IFCDIRECTION((0.,0.,1.))".to_string());
+    let world = ifc.add(format!("IFCAXIS2PLACEMENT3D(#{origin},$,$)"));
+    let context = ifc.add(format!(
+        "IFCGEOMETRICREPRESENTATIONCONTEXT($,'Model',3,1.E-05,#{world},$)"
+    ));
+    let body = ifc.add(format!(
+        "IFCGEOMETRICREPRESENTATIONSUBCONTEXT('Body','Model',*,*,*,*,#{context},$,.MODEL_VIEW.,$)"
+    ));
+    let units = [
+        ".LENGTHUNIT.,.MILLI.,.METRE.",
+        ".AREAUNIT.,.MILLI.,.SQUARE_METRE.",
+        ".VOLUMEUNIT.,.MILLI.,.CUBIC_METRE.",
+        ".PLANEANGLEUNIT.,$,.RADIAN.",
+    ]
+    .map(|unit| format!("#{}", ifc.add(format!("IFCSIUNIT(*,{unit})"))));
+    let units = ifc.add(format!("IFCUNITASSIGNMENT(({}))", units.join(",")));
+    let project = ifc.add(format!(
+        "IFCPROJECT('{}',$,{},$,$,$,$,(#{context}),#{units})",
+        encode_ifc_guid(model.site.guid.as_uuid().as_u128() ^ PROJECT_GUID_MASK),
+        step_string_literal(&model.name)
+    ));
+
+    let site = &model.site;
+    let site_placement = ifc.add(format!("IFCLOCALPLACEMENT($,#{world})"));
+    let site_id = ifc.add(format!(
+        "IFCSITE('{}',$,{},$,$,#{site_placement},$,$,.ELEMENT.,$,$,$,$,$)",
+        ifc_guid(site.guid),
+        step_string_literal(&site.name)
+    ));
+    ifc.aggregate(project, &[site_id]);
+    // Storey entity and placement of each element, from the first storey listing it.
+    let mut containers: HashMap<Guid, (usize, usize)> = HashMap::new();
+    let mut buildings = Vec::new();
+    for building in &site.buildings {
+        let placement = ifc.add(format!("IFCLOCALPLACEMENT(#{site_placement},#{world})"));
+        let building_id = ifc.add(format!(
+            "IFCBUILDING('{}',$,{},$,$,#{placement},$,$,.ELEMENT.,$,$,$)",
+            ifc_guid(building.guid),
+            step_string_literal(&building.name)
+        ));
+        buildings.push(building_id);
+        let mut storeys = Vec::new();
+        for storey in &building.storeys {
+            // Storeys sit at the origin so element placements stay in model coordinates.
+            let storey_placement = ifc.add(format!("IFCLOCALPLACEMENT(#{placement},#{world})"));
+            let storey_id = ifc.add(format!(
+                "IFCBUILDINGSTOREY('{}',$,{},$,$,#{storey_placement},$,$,.ELEMENT.,{})",
+                ifc_guid(storey.guid),
+                step_string_literal(&storey.name),
+                real(storey.elevation)
+            ));
+            for element in &storey.elements {
+                containers
+                    .entry(*element)
+                    .or_insert((storey_id, storey_placement));
+            }
+            storeys.push(storey_id);
+        }
+        ifc.aggregate(building_id, &storeys);
+    }
+    ifc.aggregate(site_id, &buildings);
+    ifc.flush(&mut writer)?;
+
+    // Openings of each element, hosted ones first, then the other elements cut from it,
+    // indexed once so huge models export in linear time.
+    let mut openings: HashMap<Guid, Vec<usize>> = HashMap::new();
+    for (index, element) in model.elements().iter().enumerate() {
+        if let Some(opening) = element.opening {
+            openings.entry(opening.host).or_default().push(index);
+        }
+    }
+    for (index, element) in model.elements().iter().enumerate() {
+        let hosted = element.opening.map(|opening| opening.host);
+        for host in element.cuts.iter().filter(|host| Some(**host) != hosted) {
+            openings.entry(*host).or_default().push(index);
+        }
+    }
+
+    let mut contained: Vec<(usize, Vec<usize>)> = Vec::new();
+    // Openings and cuts through several elements are written once, with the first.
+    let mut written_openings: HashMap<Guid, usize> = HashMap::new();
+    for element in elements {
+        if element.category == BimCategory::Opening || element.is_reference_mesh() {
+            continue;
+        }
+        let (container, placement) = containers
+            .get(&element.guid)
+            .copied()
+            .unwrap_or((site_id, site_placement));
+        let product = ifc
+            .element(element, placement, body, z_axis, tessellation)
+            .with_context(|| format!("export {} to IFC", element.name))?;
+        match contained.iter_mut().find(|(id, _)| *id == container) {
+            Some((_, products)) => products.push(product),
+            None => contained.push((container, vec![product])),
+        }
+
+        for &index in openings.get(&element.guid).into_iter().flatten() {
+            let opening = &model.elements()[index];
+            let opening_id = match written_openings.get(&opening.guid) {
+                Some(&id) => id,
+                None => {
+                    let id = ifc
+                        .opening(opening, placement, body, z_axis, tessellation)
+                        .with_context(|| format!("export {} to IFC", opening.name))?;
+                    written_openings.insert(opening.guid, id);
+                    id
+                }
+            };
+            ifc.add(format!(
+                "IFCRELVOIDSELEMENT('{}',$,$,$,#{product},#{opening_id})",
+                ifc_guid(Guid::new())
+            ));
+        }
+        ifc.flush(&mut writer)?;
+    }
+    for (container, products) in contained {
+        ifc.add(format!(
+            "IFCRELCONTAINEDINSPATIALSTRUCTURE('{}',$,$,$,({}),#{container})",
+            ifc_guid(Guid::new()),
+            references(&products)
+        ));
+    }
+
+    ifc.flush(&mut writer)?;
+    writer.write_all(b"ENDSEC;\nEND-ISO-10303-21;\n")?;
+    Ok(())
+}
+
+/// Writes `elements` of `model` to `path` with [`write_ifc`].
+pub fn export_ifc<'a>(
+    model: &BimModel,
+    elements: impl IntoIterator<Item = &'a BimElement>,
+    tessellation: &TessellationOptions,
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("model.ifc");
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_ifc(model, elements, tessellation, file_name, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// IFC data section being written, one entity instance per line, buffered until the next
+/// [`IfcWriter::flush`].
+#[derive(Default)]
+struct IfcWriter {
+    data: String,
+    next_id: usize,
+}
+
+impl IfcWriter {
+    fn add(&mut self, entity: String) -> usize {
+        self.next_id += 1;
+        let _ = writeln!(self.data, "#{}={entity};", self.next_id);
+        self.next_id
+    }
+
+    /// Writes the entities added since the last flush to `writer`.
+    fn flush(&mut self, mut writer: impl Write) -> Result<()> {
+        writer.write_all(self.data.as_bytes())?;
+        self.data.clear();
+        Ok(())
+    }
+
+    fn point3(&mut self, [x, y, z]: [f64; 3]) -> usize {
+        self.add(format!(
+            "IFCCARTESIANPOINT(({},{},{}))",
+            real(x),
+            real(y),
+            real(z)
+        ))
+    }
+
+    fn aggregate(&mut self, whole: usize, parts: &[usize]) {
+        if parts.is_empty() {
+            return;
+        }
+        self.add(format!(
+            "IFCRELAGGREGATES('{}',$,$,$,#{whole},({}))",
+            ifc_guid(Guid::new()),
+            references(parts)
+        ));
+    }
+
+    /// `element` as the IFC entity of its category, with its body and property set.
+    fn element(
+        &mut self,
+        element: &BimElement,
+        relative_to: usize,
+        body: usize,
+        z_axis: usize,
+        tessellation: &TessellationOptions,
+    ) -> Result<usize> {
+        let (placement, shape) = self.body(element, relative_to, body, z_axis, tessellation)?;
+        let common = format!(
+            "'{}',$,{},$,$,#{placement},#{shape},$",
+            ifc_guid(element.guid),
+            step_string_literal(&element.name)
+        );
+        let product = self.add(match element.category {
+            BimCategory::Door | BimCategory::Window => {
+                format!(
+                    "{}({common},$,$,.NOTDEFINED.,.NOTDEFINED.,$)",
+                    entity(element)
+                )
+            }
+            // Rebar sets only know the total length of their bars.
+            BimCategory::Rebar => format!(
+                "IFCREINFORCINGBAR({common},$,{},$,{},.NOTDEFINED.,$)",
+                optional_number(element, "Diameter"),
+                if element.parameters.contains_key("BarCount") {
+                    "$".to_string()
+                } else {
+                    optional_number(element, "Length")
+                }
+            ),
+            _ => format!("{}({common},.NOTDEFINED.)", entity(element)),
+        });
+        self.properties(product, element);
+        Ok(product)
+    }
+
+    /// Opening element of `opening`'s shape. An element that is not an opening but is cut from
+    /// others, such as a lintel, gives a bare void named after it, its GlobalId derived from the
+    /// element's own so it stays the same across exports.
+    fn opening(
+        &mut self,
+        opening: &BimElement,
+        relative_to: usize,
+        body: usize,
+        z_axis: usize,
+        tessellation: &TessellationOptions,
+    ) -> Result<usize> {
+        let (placement, shape) = self.body(opening, relative_to, body, z_axis, tessellation)?;
+        let (global_id, name) = match opening.opening {
+            Some(_) => (ifc_guid(opening.guid), opening.name.clone()),
+            None => (
+                encode_ifc_guid(opening.guid.as_uuid().as_u128() ^ CUT_GUID_MASK),
+                format!("{} cut", opening.name),
+            ),
+        };
+        let product = self.add(format!(
+            "IFCOPENINGELEMENT('{global_id}',$,{},$,$,#{placement},#{shape},$,.OPENING.)",
+            step_string_literal(&name)
+        ));
+        if opening.opening.is_some() {
+            self.properties(product, opening);
+        }
+        Ok(product)
+    }
+
+    /// Local placement and product shape of `element`: swept from its parameters where
+    /// [`swept_profile`] knows how, otherwise its solid triangulated in model coordinates.
+    fn body(
+        &mut self,
+        element: &BimElement,
+        relative_to: usize,
+        body: usize,
+        z_axis: usize,
+        tessellation: &TessellationOptions,
+    ) -> Result<(usize, usize)> {
+        let (axes, item, kind) = match swept_profile(element) {
+            Some(swept) => {
+                let location = self.point3(swept.origin);
+                let [x, y] = swept.direction;
+                let direction = self.add(format!("IFCDIRECTION(({},{},0.))", real(x), real(y)));
+                let axes = self.add(format!(
+                    "IFCAXIS2PLACEMENT3D(#{location},#{z_axis},#{direction})"
+                ));
+                let mut points: Vec<usize> = swept
+                    .profile
+                    .iter()
+                    .map(|[x, y]| {
+                        self.add(format!("IFCCARTESIANPOINT(({},{}))", real(*x), real(*y)))
+                    })
+                    .collect();
+                points.push(points[0]);
+                let polyline = self.add(format!("IFCPOLYLINE(({}))", references(&points)));
+                let profile = self.add(format!(
+                    "IFCARBITRARYCLOSEDPROFILEDEF(.AREA.,$,#{polyline})"
+                ));
+                let origin = self.point3([0.0; 3]);
+                let position = self.add(format!("IFCAXIS2PLACEMENT3D(#{origin},$,$)"));
+                let solid = self.add(format!(
+                    "IFCEXTRUDEDAREASOLID(#{profile},#{position},#{z_axis},{})",
+                    real(swept.depth)
+                ));
+                (axes, solid, "SweptSolid")
+            }
+            None => {
+                let mesh = triangulate_solid(&element.geometry, tessellation);
+                if mesh.positions().is_empty() {
+                    anyhow::bail!("solid has no faces to triangulate");
+                }
+                let mut coordinates = String::new();
+                for (index, p) in mesh.positions().iter().enumerate() {
+                    if index > 0 {
+                        coordinates.push(',');
+                    }
+                    let _ = write!(coordinates, "({},{},{})", real(p.x), real(p.y), real(p.z));
+                }
+                let list = self.add(format!("IFCCARTESIANPOINTLIST3D(({coordinates}))"));
+                let mut indices = String::new();
+                for (index, [a, b, c]) in triangles(&mesh).into_iter().enumerate() {
+                    if index > 0 {
+                        indices.push(',');
+                    }
+                    // Coordinate indices count from 1.
+                    let _ = write!(indices, "({},{},{})", a.0 + 1, b.0 + 1, c.0 + 1);
+                }
+                let faces = self.add(format!(
+                    "IFCTRIANGULATEDFACESET(#{list},$,.T.,({indices}),$)"
+                ));
+                let origin = self.point3([0.0; 3]);
+                let axes = self.add(format!("IFCAXIS2PLACEMENT3D(#{origin},$,$)"));
+                (axes, faces, "Tessellation")
+            }
+        };
+        let placement = self.add(format!("IFCLOCALPLACEMENT(#{relative_to},#{axes})"));
+        let representation = self.add(format!(
+            "IFCSHAPEREPRESENTATION(#{body},'Body','{kind}',(#{item}))"
+        ));
+        let shape = self.add(format!(
+            "IFCPRODUCTDEFINITIONSHAPE($,$,(#{representation}))"
+        ));
+        Ok((placement, shape))
+    }
+
+    /// `element`'s parameters as a property set attached to `product`.
+    fn properties(&mut self, product: usize, element: &BimElement) {
+        if element.parameters.is_empty() {
+            return;
+        }
+        let properties: Vec<usize> = element
+            .parameters
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    ParameterValue::Integer(value) => format!("IFCINTEGER({value})"),
+                    ParameterValue::Number(value) => format!("IFCREAL({})", real(*value)),
+                    ParameterValue::Bool(value) => {
+                        format!("IFCBOOLEAN(.{}.)", if *value { 'T' } else { 'F' })
+                    }
+                    ParameterValue::Text(value) => {
+                        format!("IFCLABEL({})", step_string_literal(value))
+                    }
+                };
+                self.add(format!(
+                    "IFCPROPERTYSINGLEVALUE({},$,{value},$)",
+                    step_string_literal(key)
+                ))
+            })
+            .collect();
+        let set = self.add(format!(
+            "IFCPROPERTYSET('{}',$,'{PROPERTY_SET_NAME}',$,({}))",
+            ifc_guid(Guid::new()),
+            references(&properties)
+        ));
+        self.add(format!(
+            "IFCRELDEFINESBYPROPERTIES('{}',$,$,$,(#{product}),#{set})",
+            ifc_guid(Guid::new())
+        ));
+    }
+}
+
+/// Body of a wall or slab as a closed profile extruded up by `depth`, in a placement at
+/// `origin` whose X axis runs along `direction`.
+struct SweptProfile {
+    origin: [f64; 3],
+    direction: [f64; 2],
+    profile: Vec<[f64; 2]>,
+    depth: f64,
+}
+
+/// The wall footprint, trimmed or extended at its ends by its joins, or the slab outline
+/// hanging `Thickness` below it. `None` for other elements and ones missing parameters, which
+/// are triangulated instead.
+fn swept_profile(element: &BimElement) -> Option<SweptProfile> {
+    let number = |key: &str| match element.parameters.get(key) {
+        Some(ParameterValue::Number(value)) => Some(*value),
+        _ => None,
+    };
+    let thickness = number("Thickness").filter(|thickness| *thickness > 0.0)?;
+    match element.category {
+        BimCategory::Wall => {
+            let start = [number("StartX")?, number("StartY")?, number("StartZ")?];
+            let (dx, dy) = (number("EndX")? - start[0], number("EndY")? - start[1]);
+            let length = dx.hypot(dy);
+            let height = number("Height").filter(|height| *height > 0.0)?;
+            if length <= 1.0e-6 {
+                return None;
+            }
+            let extension = |key: &str| number(key).unwrap_or(0.0);
+            let half = thickness * 0.5;
+            let profile = vec![
+                [-extension("StartExtensionRight"), -half],
+                [length + extension("EndExtensionRight"), -half],
+                [length + extension("EndExtensionLeft"), half],
+                [-extension("StartExtensionLeft"), half],
+            ];
+            if profile[1][0] <= profile[0][0] || profile[2][0] <= profile[3][0] {
+                return None;
+            }
+            Some(SweptProfile {
+                origin: start,
+                direction: [dx / length, dy / length],
+                profile,
+                depth: height,
+            })
+        }
+        BimCategory::Slab => {
+            let count = match element.parameters.get("PointCount") {
+                Some(ParameterValue::Integer(count)) if *count >= 3 => *count,
+                _ => return None,
+            };
+            let outline = (1..=count)
+                .map(|idx| {
+                    Some([
+                        number(&format!("Point{idx}X"))?,
+                        number(&format!("Point{idx}Y"))?,
+                        number(&format!("Point{idx}Z"))?,
+                    ])
+                })
+                .collect::<Option<Vec<_>>>()?;
+            let first = outline[0];
+            Some(SweptProfile {
+                origin: [first[0], first[1], first[2] - thickness],
+                direction: [1.0, 0.0],
+                profile: outline
+                    .iter()
+                    .map(|point| [point[0] - first[0], point[1] - first[1]])
+                    .collect(),
+                depth: thickness,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// IFC4 entity `element` is written as. Doors, windows and rebar take extra attributes.
+pub(crate) fn entity(element: &BimElement) -> &'static str {
+    match element.category {
+        BimCategory::Wall => "IFCWALL",
+        BimCategory::Slab => "IFCSLAB",
+        BimCategory::Beam => "IFCBEAM",
+        BimCategory::Column => "IFCCOLUMN",
+        BimCategory::Foundation => "IFCFOOTING",
+        BimCategory::Roof => "IFCROOF",
+        BimCategory::Stair => "IFCSTAIR",
+        BimCategory::Ramp => "IFCRAMP",
+        BimCategory::Door => "IFCDOOR",
+        BimCategory::Window => "IFCWINDOW",
+        BimCategory::Opening => "IFCOPENINGELEMENT",
+        BimCategory::Rebar => "IFCREINFORCINGBAR",
+        BimCategory::Pipe => "IFCPIPESEGMENT",
+        BimCategory::Duct => "IFCDUCTSEGMENT",
+        BimCategory::Generic => "IFCBUILDINGELEMENTPROXY",
+    }
+}
+
+fn optional_number(element: &BimElement, key: &str) -> String {
+    match element.parameters.get(key) {
+        Some(ParameterValue::Number(value)) if *value > 0.0 => real(*value),
+        _ => "$".to_string(),
+    }
+}
+
+/// STEP real: always with a decimal point.
+fn real(value: f64) -> String {
+    let text = format!("{value:.6}");
+    let text = text.trim_end_matches('0');
+    if text == "-0." {
+        "0.".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+fn references(ids: &[usize]) -> String {
+    ids.iter()
+        .map(|id| format!("#{id}"))
+        .collect::<Vec<_>>()
+        .join(",")
 }
@@ -0,0 +1,58 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_bim::SessionJournal;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Bumped whenever the serialized journal layout changes incompatibly.
+pub const JOURNAL_FORMAT_VERSION: u32 = 1;
+pub const JOURNAL_EXTENSION: &str = "cxjournal";
+
+const FORMAT_NAME: &str = "cryxtal-journal";
+
+#[derive(Serialize)]
+struct JournalFileRef<'a> {
+    format: &'a str,
+    version: u32,
+    journal: &'a SessionJournal,
+}
+
+#[derive(Deserialize)]
+struct JournalFile {
+    format: String,
+    version: u32,
+    journal: SessionJournal,
+}
+
+/// Writes the session journal as pretty-printed JSON, so it can be attached to a bug report
+/// next to the project it started from and read by hand.
+pub fn export_journal(journal: &SessionJournal, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let bytes = serde_json::to_vec_pretty(&JournalFileRef {
+        format: FORMAT_NAME,
+        version: JOURNAL_FORMAT_VERSION,
+        journal,
+    })
+    .context("serialize journal")?;
+    std::fs::write(path, bytes).with_context(|| format!("write journal {}", path.display()))
+}
+
+pub fn import_journal(path: impl AsRef<Path>) -> Result<SessionJournal> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).with_context(|| format!("read journal {}", path.display()))?;
+    let file: JournalFile = serde_json::from_slice(&bytes)
+        .with_context(|| format!("decode journal {}", path.display()))?;
+    if file.format != FORMAT_NAME {
+        bail!(
+            "{} is not a cryxtal session journal (format `{}`)",
+            path.display(),
+            file.format
+        );
+    }
+    if file.version == 0 || file.version > JOURNAL_FORMAT_VERSION {
+        bail!(
+            "unsupported journal format version {} (this build reads up to {JOURNAL_FORMAT_VERSION})",
+            file.version
+        );
+    }
+    Ok(file.journal)
+}
@@ -1,7 +1,85 @@
+pub mod bom;
+pub mod brep;
+pub mod bvbs;
+pub mod deviation;
+pub mod distance;
+pub mod dxf;
+pub mod hatch;
+pub mod holes;
+pub mod ids;
 pub mod ifc;
+pub mod journal;
 pub mod mesh;
+pub mod model_mesh;
+pub mod packed_mesh;
+pub mod parameter_log;
+pub mod parameters;
+pub mod plan;
+pub mod point_cloud;
+pub mod project;
+pub mod pxml;
+pub mod reference_mesh;
+pub mod schedule;
+pub mod split;
 pub mod step;
+pub mod weld;
+mod xlsx;
 
-pub use ifc::export_ifc_stub;
-pub use mesh::{DEFAULT_TESSELLATION_TOLERANCE, export_obj, triangulate_solid};
-pub use step::{export_step, import_step};
+pub use bom::{export_bom, write_bom_csv, write_bom_xlsx};
+pub use brep::{
+    BREP_EXTENSION, BREP_FORMAT_VERSION, BrepCurve, BrepEdge, BrepEdgeUse, BrepFace, BrepShell,
+    BrepSolid, BrepSurface, brep_from_str, brep_to_string, export_brep, import_brep,
+};
+pub use bvbs::{BvbsBar, BvbsOptions, bvbs_bars, bvbs_checksum, export_bvbs, write_bvbs};
+pub use deviation::{
+    FaceDeviation, export_deviation_csv, import_scan_points, read_scan_points, scan_deviation,
+    write_deviation_csv,
+};
+pub use distance::{distance, distance_with_tolerance};
+pub use dxf::{export_dxf_plan, write_dxf_plan};
+pub use hatch::hatch_segments;
+pub use holes::{HoleFeature, recognize_holes};
+pub use ids::{
+    Ids, IdsCardinality, IdsElementResult, IdsFacet, IdsReport, IdsRequirement, IdsRestriction,
+    IdsSpecification, IdsSpecificationReport, IdsValue, import_ids, read_ids, validate_ids,
+};
+pub use ifc::{export_ifc, ifc_guid, write_ifc};
+pub use journal::{JOURNAL_EXTENSION, JOURNAL_FORMAT_VERSION, export_journal, import_journal};
+pub use mesh::{
+    DEFAULT_ANGULAR_TOLERANCE, DEFAULT_TESSELLATION_TOLERANCE, GltfOptions, MeshCheck, MeshHole,
+    MeshRepair, TessellationOptions, check_mesh, decimate_mesh, export_glb_streaming, export_gltf,
+    export_obj, fix_normals, import_obj, remove_degenerate_triangles, repair_mesh,
+    triangulate_faces, triangulate_many, triangulate_many_with, triangulate_solid,
+    triangulate_solid_with, weld_vertices, write_glb,
+};
+pub use model_mesh::{MeshFormat, export_model_mesh, export_model_mesh_streaming};
+pub use packed_mesh::{
+    PACKED_MESH_EXTENSION, PACKED_MESH_VERSION, PackedElement, PackedMesh, PackedMeshWriter,
+    geometry_key, load_mesh_cache, mesh_cache_path, read_packed_meshes, save_mesh_cache,
+};
+pub use parameter_log::{export_parameter_log_csv, write_parameter_log_csv};
+pub use parameters::{
+    ParameterImport, export_parameters_csv, export_parameters_json, import_parameters,
+    read_parameters_csv, read_parameters_json, write_parameters_csv, write_parameters_json,
+};
+pub use plan::{export_plan_svg, write_plan_svg};
+pub use point_cloud::{PointCloud, import_point_cloud, read_ply};
+pub use project::{
+    BINARY_PROJECT_EXTENSION, PROJECT_EXTENSION, PROJECT_FORMAT_VERSION, Project, ProjectCamera,
+    ProjectFormat, load_project, read_project, save_project, save_project_as,
+};
+pub use pxml::{export_pxml, write_pxml};
+pub use reference_mesh::{MAX_REFERENCE_TRIANGLES, reference_mesh_element};
+pub use schedule::{
+    BAR_CORNERS_KEY, BbsRow, CUT_LENGTHS_KEY, STEEL_DENSITY, bar_mass_per_metre, bend_radius,
+    export_rebar_schedule_csv, hook_extension, rebar_schedule, write_rebar_schedule_csv,
+};
+pub use split::{ExportPart, SplitBy, split_elements};
+pub use step::{
+    STEP_IMPORT_TOLERANCE, export_step, export_step_model, export_step_tagged, import_step,
+    import_step_shells,
+};
+pub use weld::{
+    WeldRow, export_weld_drawing_svg, export_weld_schedule_csv, weld_schedule,
+    write_weld_drawing_svg, write_weld_schedule_csv,
+};
@@ -1,33 +1,1417 @@
 use anyhow::{Context, Result, bail};
-use cryxtal_topology::Solid;
+use cryxtal_base::OpContext;
+use cryxtal_bim::{BimElement, Layer, ParameterValue};
+use cryxtal_topology::{Face, Point3, Shell, Solid, Vector3, sample_edge, solid_edges};
+use rayon::prelude::*;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap};
+use std::fmt;
 use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use truck_meshalgo::prelude::*;
-use truck_polymesh::{PolygonMesh, obj};
+use truck_polymesh::{Faces, PolygonMesh, StandardAttributes, obj};
 
+use crate::model_mesh::{
+    GLTF_ARRAY_BUFFER, GLTF_ELEMENT_ARRAY_BUFFER, GLTF_FLOAT, GLTF_ROOT_MATRIX, GLTF_UNSIGNED_INT,
+    triangles,
+};
+
+/// Default chordal tolerance, in model units; suited to millimetre models.
 pub const DEFAULT_TESSELLATION_TOLERANCE: f64 = 0.5;
+/// Default angle between neighbouring normals on curved faces when tessellating adaptively:
+/// 15 degrees, 24 segments around a full circle.
+pub const DEFAULT_ANGULAR_TOLERANCE: f64 = std::f64::consts::PI / 12.0;
+
+/// Finest an adaptive tolerance goes, relative to the chordal tolerance, so one tiny fillet
+/// cannot blow up the tessellation of a whole solid.
+const MIN_ADAPTIVE_RATIO: f64 = 1.0e-2;
+/// Times the longest edge of a mesh is bisected at most to meet the maximum edge length; a
+/// finer maximum is raised to match.
+const MAX_EDGE_SPLITS: i32 = 24;
+/// Points each edge is sampled with to estimate its curvature.
+const CURVATURE_SEGMENTS: usize = 8;
+
+/// `glTF` in little-endian, the first word of a GLB file.
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+const GLB_BIN_CHUNK: u32 = 0x004E_4942;
+const GLTF_SHORT: u32 = 5122;
+
+/// How finely solids are tessellated, by [`triangulate_solid`] and the mesh exporters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TessellationOptions {
+    /// Largest distance between a triangle and the surface it approximates, in model units.
+    pub chordal_tol: f64,
+    /// Largest angle, in radians, between the normals of neighbouring vertices on curved faces.
+    /// Only used with `curvature_adaptive`.
+    pub angular_tol: f64,
+    /// Triangles with longer edges are split until none is; `None` leaves them as they are.
+    pub max_edge_length: Option<f64>,
+    /// Tightens the chordal tolerance of each solid to its most sharply curved face, so a small
+    /// cylinder gets as many segments as `angular_tol` asks for while large curves keep
+    /// `chordal_tol`. Never coarser than `chordal_tol`.
+    pub curvature_adaptive: bool,
+    /// Runs [`repair_mesh`] on each mesh with this tolerance; `None` leaves the triangulation
+    /// as it is.
+    pub repair_tol: Option<f64>,
+    /// Runs [`decimate_mesh`] on each mesh to at most this many triangles.
+    pub max_triangles: Option<usize>,
+    /// Mesh exporters refuse to write an element whose mesh [`check_mesh`] finds open or
+    /// inconsistently wound, listing the defects instead. Triangulation itself ignores it.
+    pub check_watertight: bool,
+}
+
+impl TessellationOptions {
+    /// Options with chordal tolerance `tol` and nothing else.
+    pub fn chordal(tol: f64) -> Self {
+        Self {
+            chordal_tol: tol,
+            ..Self::default()
+        }
+    }
+
+    /// Chordal tolerance used for `solid`: `chordal_tol`, tightened to its smallest radius of
+    /// curvature when adaptive.
+    pub fn tolerance_for(&self, solid: &Solid) -> f64 {
+        if !self.curvature_adaptive {
+            return self.chordal_tol;
+        }
+        let Some(radius) = min_curvature_radius(solid) else {
+            return self.chordal_tol;
+        };
+        let angle = self.angular_tol.clamp(1.0e-3, std::f64::consts::PI);
+        let adaptive = radius * (1.0 - (angle * 0.5).cos());
+        adaptive.clamp(self.chordal_tol * MIN_ADAPTIVE_RATIO, self.chordal_tol)
+    }
+}
+
+impl Default for TessellationOptions {
+    fn default() -> Self {
+        Self {
+            chordal_tol: DEFAULT_TESSELLATION_TOLERANCE,
+            angular_tol: DEFAULT_ANGULAR_TOLERANCE,
+            max_edge_length: None,
+            curvature_adaptive: false,
+            repair_tol: None,
+            max_triangles: None,
+            check_watertight: false,
+        }
+    }
+}
+
+pub fn triangulate_solid(solid: &Solid, options: &TessellationOptions) -> PolygonMesh {
+    let mut mesh = solid
+        .triangulation(options.tolerance_for(solid))
+        .to_polygon();
+    limit_edge_length(&mut mesh, options.max_edge_length);
+    post_process(&mut mesh, options);
+    finish_mesh(&mut mesh);
+    mesh
+}
+
+/// Like [`triangulate_solid`], triangulating face by face on the rayon thread pool so progress
+/// is reported per face, and stopping before the next face once `ctx` is cancelled.
+pub fn triangulate_solid_with(
+    solid: &Solid,
+    options: &TessellationOptions,
+    ctx: &OpContext,
+) -> Result<PolygonMesh> {
+    ctx.checkpoint()?;
+    ctx.report(0.0);
+
+    let tol = options.tolerance_for(solid);
+    let faces: Vec<&Face> = solid.face_iter().collect();
+    // The last step, cleaning up the merged mesh, counts as one more face.
+    let total = faces.len() + 1;
+    let finished = AtomicUsize::new(0);
+    let face_meshes = faces
+        .par_iter()
+        .map(|face| {
+            ctx.checkpoint()?;
+            // Edges are divided alike on both faces sharing them, so the pieces still meet.
+            let mesh = Shell::from(vec![(*face).clone()])
+                .triangulation(tol)
+                .to_polygon();
+            let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+            ctx.report(done as f32 / total as f32);
+            Ok(mesh)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut mesh = PolygonMesh::default();
+    for face_mesh in face_meshes {
+        mesh.merge(face_mesh);
+    }
+
+    limit_edge_length(&mut mesh, options.max_edge_length);
+    post_process(&mut mesh, options);
+    finish_mesh(&mut mesh);
+    ctx.report(1.0);
+    Ok(mesh)
+}
 
-pub fn triangulate_solid(solid: &Solid, tol: f64) -> PolygonMesh {
-    let mut mesh = solid.triangulation(tol).to_polygon();
+/// Triangulates `solids` in parallel on the rayon thread pool, whose work stealing keeps the
+/// threads busy when solids differ widely in size; meshes are returned in input order.
+pub fn triangulate_many(solids: &[&Solid], options: &TessellationOptions) -> Vec<PolygonMesh> {
+    solids
+        .par_iter()
+        .map(|solid| triangulate_solid(solid, options))
+        .collect()
+}
+
+/// Like [`triangulate_many`], reporting the fraction of finished solids through `ctx` and
+/// failing once it is cancelled.
+pub fn triangulate_many_with(
+    solids: &[&Solid],
+    options: &TessellationOptions,
+    ctx: &OpContext,
+) -> Result<Vec<PolygonMesh>> {
+    let total = solids.len();
+    let finished = AtomicUsize::new(0);
+    ctx.report(0.0);
+    solids
+        .par_iter()
+        .map(|solid| {
+            ctx.checkpoint()?;
+            let mesh = triangulate_solid(solid, options);
+            let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+            ctx.report(done as f32 / total as f32);
+            Ok(mesh)
+        })
+        .collect()
+}
+
+/// Triangles of each face of `solid`, wound so their normals point out of the solid.
+pub fn triangulate_faces(solid: &Solid, options: &TessellationOptions) -> Vec<Vec<[Point3; 3]>> {
+    let meshed = solid.triangulation(options.tolerance_for(solid));
+    let mut faces = Vec::new();
+    for face in meshed.face_iter() {
+        let Some(mesh) = face.surface() else {
+            continue;
+        };
+        let positions = mesh.positions();
+        let orientation = face.orientation();
+        let face_triangles = triangles(&mesh)
+            .into_iter()
+            .map(|[(a, _), (b, _), (c, _)]| {
+                if orientation {
+                    [positions[a], positions[b], positions[c]]
+                } else {
+                    [positions[a], positions[c], positions[b]]
+                }
+            })
+            .collect();
+        faces.push(face_triangles);
+    }
+    // One limit for all faces, so the edges they share split alike.
+    if let Some(max) = edge_limit(faces.iter().flatten(), options.max_edge_length) {
+        faces = faces
+            .into_iter()
+            .map(|face| split_long_edges(face, max))
+            .collect();
+    }
+    faces
+}
+
+/// Smallest radius of curvature over the curved edges of `solid`, from the circles through
+/// neighbouring points sampled along each. Curved faces are bounded by curved edges, so this
+/// finds them without tessellating. `None` when all edges are straight.
+fn min_curvature_radius(solid: &Solid) -> Option<f64> {
+    let mut radius: Option<f64> = None;
+    for edge in solid_edges(solid) {
+        let points = sample_edge(edge, CURVATURE_SEGMENTS);
+        for window in points.windows(3) {
+            let (a, b, c) = (
+                window[1] - window[0],
+                window[2] - window[1],
+                window[2] - window[0],
+            );
+            let twice_area = a.cross(c).magnitude();
+            if twice_area <= f64::EPSILON * c.magnitude2() {
+                continue;
+            }
+            // The circle through three points has radius `abc / 4A`.
+            let edge_radius = a.magnitude() * b.magnitude() * c.magnitude() / (2.0 * twice_area);
+            if edge_radius.is_finite() {
+                radius = Some(radius.map_or(edge_radius, |radius| radius.min(edge_radius)));
+            }
+        }
+    }
+    radius
+}
+
+/// Splits the triangles of `mesh` until no edge is longer than `max`. Texture coordinates and
+/// normals are dropped; [`finish_mesh`] recomputes the normals.
+fn limit_edge_length(mesh: &mut PolygonMesh, max: Option<f64>) {
+    let positions = mesh.positions();
+    let input: Vec<_> = triangles(mesh)
+        .into_iter()
+        .map(|[(a, _), (b, _), (c, _)]| [positions[a], positions[b], positions[c]])
+        .collect();
+    let Some(max) = edge_limit(&input, max) else {
+        return;
+    };
+    let split = split_long_edges(input, max);
+    let positions = split.iter().flatten().copied().collect();
+    let faces: Vec<[usize; 3]> = (0..split.len())
+        .map(|idx| [idx * 3, idx * 3 + 1, idx * 3 + 2])
+        .collect();
+    *mesh = PolygonMesh::new(
+        StandardAttributes {
+            positions,
+            ..Default::default()
+        },
+        Faces::from_iter(faces),
+    );
+}
+
+/// Edge length `triangles` are split down to for a requested `max`: `None` when nothing is to
+/// be split, raised so the longest edge is bisected at most [`MAX_EDGE_SPLITS`] times.
+fn edge_limit<'a>(
+    triangles: impl IntoIterator<Item = &'a [Point3; 3]>,
+    max: Option<f64>,
+) -> Option<f64> {
+    let max = max.filter(|max| *max > 0.0)?;
+    let longest = triangles
+        .into_iter()
+        .flat_map(|triangle| {
+            (0..3).map(|idx| (triangle[(idx + 1) % 3] - triangle[idx]).magnitude())
+        })
+        .filter(|length| length.is_finite())
+        .fold(0.0, f64::max);
+    let max = max.max(longest / 2f64.powi(MAX_EDGE_SPLITS));
+    (longest > max).then_some(max)
+}
+
+/// Bisects every edge of `triangles` longer than `max` until none is. Whether an edge is split
+/// and where depends only on its two ends, so triangles sharing it split it alike and the mesh
+/// stays free of T-junctions. Each split edge halves and each new inner edge is shorter than
+/// the longest edge of its triangle, so this ends; `max` comes from [`edge_limit`].
+fn split_long_edges(triangles: Vec<[Point3; 3]>, max: f64) -> Vec<[Point3; 3]> {
+    let mut pending = triangles;
+    let mut done = Vec::with_capacity(pending.len());
+    while let Some(triangle) = pending.pop() {
+        let long = [0, 1, 2].map(|idx| {
+            let length = (triangle[(idx + 1) % 3] - triangle[idx]).magnitude();
+            length.is_finite() && length > max
+        });
+        // Rotate so the split edges come first; winding is kept.
+        let Some(first) = (0..3)
+            .find(|&idx| long[idx] && !long[(idx + 2) % 3])
+            .or_else(|| long[0].then_some(0))
+        else {
+            done.push(triangle);
+            continue;
+        };
+        let [a, b, c] = [0, 1, 2].map(|idx| triangle[(first + idx) % 3]);
+        let [_, long_bc, long_ca] = [0, 1, 2].map(|idx| long[(first + idx) % 3]);
+        let mid = |p: Point3, q: Point3| Point3::from_vec((p.to_vec() + q.to_vec()) * 0.5);
+        let (ab, bc, ca) = (mid(a, b), mid(b, c), mid(c, a));
+        match (long_bc, long_ca) {
+            (false, _) => pending.extend([[a, ab, c], [ab, b, c]]),
+            (true, false) => pending.extend([[a, ab, c], [ab, b, bc], [ab, bc, c]]),
+            (true, true) => pending.extend([[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]),
+        }
+    }
+    done
+}
+
+fn finish_mesh(mesh: &mut PolygonMesh) {
     mesh.add_naive_normals(true);
     mesh.put_together_same_attrs(truck_base::tolerance::TOLERANCE);
     mesh.remove_unused_attrs();
-    mesh
 }
 
-pub fn export_obj(solid: &Solid, path: impl AsRef<Path>, tol: f64) -> Result<()> {
+/// Weight of the planes that hold open boundaries in place while decimating, relative to the
+/// planes of the triangles themselves.
+const BOUNDARY_WEIGHT: f64 = 1.0e3;
+
+/// What [`repair_mesh`] changed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MeshRepair {
+    /// Vertices merged into a neighbour within the tolerance.
+    pub welded: usize,
+    /// Triangles removed for having no area or being thinner than the tolerance.
+    pub degenerate: usize,
+    /// Triangles whose winding was reversed.
+    pub flipped: usize,
+}
+
+/// Cleans up `mesh` for tools that reject slivers and cracks, such as what boolean results
+/// leave: runs [`weld_vertices`], [`remove_degenerate_triangles`] and [`fix_normals`] with
+/// `tol`, in that order.
+pub fn repair_mesh(mesh: &mut PolygonMesh, tol: f64) -> MeshRepair {
+    edit_triangles(mesh, |soup| soup.repair(tol))
+}
+
+/// Merges vertices closer than `tol` and returns how many were merged. Triangles the weld
+/// collapses are left for [`remove_degenerate_triangles`].
+pub fn weld_vertices(mesh: &mut PolygonMesh, tol: f64) -> usize {
+    edit_triangles(mesh, |soup| soup.weld(tol))
+}
+
+/// Removes triangles with a repeated corner or thinner than `tol`, measured across their
+/// longest edge, and returns how many were removed.
+pub fn remove_degenerate_triangles(mesh: &mut PolygonMesh, tol: f64) -> usize {
+    edit_triangles(mesh, |soup| soup.remove_degenerate(tol))
+}
+
+/// Winds the triangles of `mesh` consistently across the edges they share and recomputes
+/// the normals. Closed parts face outward; open ones keep the winding most of their
+/// triangles had. Returns the number of triangles flipped.
+pub fn fix_normals(mesh: &mut PolygonMesh) -> usize {
+    edit_triangles(mesh, TriangleSoup::orient)
+}
+
+/// Collapses edges of `mesh`, the one moving the surface least first, until at most
+/// `target_triangles` are left or no edge can collapse without folding the surface over.
+/// Sharp edges and open boundaries go last. Vertices should be welded first. Returns the
+/// number of triangles removed.
+pub fn decimate_mesh(mesh: &mut PolygonMesh, target_triangles: usize) -> usize {
+    edit_triangles(mesh, |soup| soup.decimate(target_triangles))
+}
+
+fn edit_triangles<T>(mesh: &mut PolygonMesh, edit: impl FnOnce(&mut TriangleSoup) -> T) -> T {
+    let mut soup = TriangleSoup::from_mesh(mesh);
+    let result = edit(&mut soup);
+    *mesh = soup.into_mesh();
+    finish_mesh(mesh);
+    result
+}
+
+/// Hole in a mesh: a loop of edges used by one triangle only.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshHole {
+    /// Mean of the vertices around the hole.
+    pub center: Point3,
+    pub edges: usize,
+    pub perimeter: f64,
+}
+
+/// Defects [`check_mesh`] found, which 3D printing and analysis tools reject.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshCheck {
+    pub holes: Vec<MeshHole>,
+    /// Midpoints of the edges shared by more than two triangles.
+    pub non_manifold: Vec<Point3>,
+    /// Midpoints of the edges whose two triangles run them the same way, so one of them faces
+    /// the wrong way.
+    pub misoriented: Vec<Point3>,
+}
+
+/// Defects of each kind listed by location in [`MeshCheck`]'s message; the rest are counted.
+const MAX_LISTED_DEFECTS: usize = 5;
+
+impl MeshCheck {
+    /// Closed: no holes and no edge shared by more than two triangles.
+    pub fn is_watertight(&self) -> bool {
+        self.holes.is_empty() && self.non_manifold.is_empty()
+    }
+
+    /// Closed and consistently wound.
+    pub fn is_ok(&self) -> bool {
+        self.is_watertight() && self.misoriented.is_empty()
+    }
+}
+
+impl fmt::Display for MeshCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "closed and consistently wound");
+        }
+        let at = |point: &Point3| format!("({:.3}, {:.3}, {:.3})", point.x, point.y, point.z);
+        let mut parts = Vec::new();
+        let mut list = |count: usize, what: &str, locations: Vec<String>| {
+            if count == 0 {
+                return;
+            }
+            let more = count.saturating_sub(MAX_LISTED_DEFECTS);
+            let mut text = format!("{count} {what}: {}", locations.join(", "));
+            if more > 0 {
+                text.push_str(&format!(" and {more} more"));
+            }
+            parts.push(text);
+        };
+        list(
+            self.holes.len(),
+            "hole(s)",
+            self.holes
+                .iter()
+                .take(MAX_LISTED_DEFECTS)
+                .map(|hole| {
+                    format!(
+                        "{} edges, {:.3} around, at {}",
+                        hole.edges,
+                        hole.perimeter,
+                        at(&hole.center)
+                    )
+                })
+                .collect(),
+        );
+        for (edges, what) in [
+            (&self.non_manifold, "non-manifold edge(s) at"),
+            (&self.misoriented, "inconsistently wound edge(s) at"),
+        ] {
+            list(
+                edges.len(),
+                what,
+                edges.iter().take(MAX_LISTED_DEFECTS).map(at).collect(),
+            );
+        }
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// Checks that `mesh` is closed and consistently wound, after welding vertices that coincide
+/// within the geometric tolerance, and finds its holes, non-manifold edges and triangles
+/// wound against their neighbours.
+pub fn check_mesh(mesh: &PolygonMesh) -> MeshCheck {
+    let mut soup = TriangleSoup::from_mesh(mesh);
+    soup.weld(truck_base::tolerance::TOLERANCE);
+    soup.check()
+}
+
+/// Fails with what [`check_mesh`] finds in the mesh of `name` when `options` ask for
+/// watertight meshes.
+pub(crate) fn ensure_watertight(
+    mesh: &PolygonMesh,
+    options: &TessellationOptions,
+    name: &str,
+) -> Result<()> {
+    if !options.check_watertight {
+        return Ok(());
+    }
+    let check = check_mesh(mesh);
+    if !check.is_ok() {
+        bail!("mesh of {name} is not watertight: {check}");
+    }
+    Ok(())
+}
+
+/// Repairs and decimates a fresh triangulation as `options` ask, before [`finish_mesh`].
+fn post_process(mesh: &mut PolygonMesh, options: &TessellationOptions) {
+    if options.repair_tol.is_none() && options.max_triangles.is_none() {
+        return;
+    }
+    let mut soup = TriangleSoup::from_mesh(mesh);
+    if let Some(tol) = options.repair_tol {
+        soup.repair(tol);
+    } else {
+        // Faces are triangulated apart; welding joins them along their edges.
+        soup.weld(truck_base::tolerance::TOLERANCE);
+    }
+    if let Some(max) = options.max_triangles {
+        soup.decimate(max);
+    }
+    *mesh = soup.into_mesh();
+}
+
+/// Positions and the triangles indexing them, which the repair functions edit.
+struct TriangleSoup {
+    positions: Vec<Point3>,
+    triangles: Vec<[usize; 3]>,
+}
+
+impl TriangleSoup {
+    fn from_mesh(mesh: &PolygonMesh) -> Self {
+        Self {
+            positions: mesh.positions().to_vec(),
+            triangles: triangles(mesh)
+                .into_iter()
+                .map(|corners| corners.map(|(pos, _)| pos))
+                .collect(),
+        }
+    }
+
+    /// Positions-only mesh; [`finish_mesh`] adds the normals and drops unused positions.
+    fn into_mesh(self) -> PolygonMesh {
+        PolygonMesh::new(
+            StandardAttributes {
+                positions: self.positions,
+                ..Default::default()
+            },
+            Faces::from_iter(self.triangles),
+        )
+    }
+
+    fn repair(&mut self, tol: f64) -> MeshRepair {
+        MeshRepair {
+            welded: self.weld(tol),
+            degenerate: self.remove_degenerate(tol),
+            flipped: self.orient(),
+        }
+    }
+
+    fn weld(&mut self, tol: f64) -> usize {
+        let tol = if tol > 0.0 {
+            tol
+        } else {
+            truck_base::tolerance::TOLERANCE
+        };
+        // Points within `tol` of each other are at most one grid cell apart.
+        let cell = |point: Point3| [point.x, point.y, point.z].map(|v| (v / tol).floor() as i64);
+        let mut grid: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        let mut positions: Vec<Point3> = Vec::new();
+        let mut remap = Vec::with_capacity(self.positions.len());
+        for &point in &self.positions {
+            let [x, y, z] = cell(point);
+            let existing = (-1..=1)
+                .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz))))
+                .filter_map(|(dx, dy, dz)| grid.get(&[x + dx, y + dy, z + dz]))
+                .flatten()
+                .copied()
+                .find(|&idx| (positions[idx] - point).magnitude() <= tol);
+            let idx = match existing {
+                Some(idx) => idx,
+                None => {
+                    positions.push(point);
+                    grid.entry([x, y, z]).or_default().push(positions.len() - 1);
+                    positions.len() - 1
+                }
+            };
+            remap.push(idx);
+        }
+        for triangle in &mut self.triangles {
+            *triangle = triangle.map(|idx| remap[idx]);
+        }
+        let welded = self.positions.len() - positions.len();
+        self.positions = positions;
+        welded
+    }
+
+    fn remove_degenerate(&mut self, tol: f64) -> usize {
+        let before = self.triangles.len();
+        let positions = &self.positions;
+        self.triangles.retain(|&[a, b, c]| {
+            if a == b || b == c || c == a {
+                return false;
+            }
+            let (pa, pb, pc) = (positions[a], positions[b], positions[c]);
+            let longest = [pb - pa, pc - pb, pa - pc]
+                .iter()
+                .map(|edge| edge.magnitude())
+                .fold(0.0, f64::max);
+            // Twice the area over the longest edge is the height across it.
+            longest > 0.0 && (pb - pa).cross(pc - pa).magnitude() / longest > tol
+        });
+        before - self.triangles.len()
+    }
+
+    fn orient(&mut self) -> usize {
+        let count = self.triangles.len();
+        let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (idx, triangle) in self.triangles.iter().enumerate() {
+            for (a, b) in directed_edges(*triangle) {
+                edges.entry((a.min(b), a.max(b))).or_default().push(idx);
+            }
+        }
+
+        let mut flip = vec![false; count];
+        let mut seen = vec![false; count];
+        for start in 0..count {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut component = vec![start];
+            let mut closed = true;
+            let mut next = 0;
+            while next < component.len() {
+                let idx = component[next];
+                next += 1;
+                for (a, b) in directed_edges(self.wound(idx, flip[idx])) {
+                    let shared = &edges[&(a.min(b), a.max(b))];
+                    // Boundary and non-manifold edges do not carry the winding across.
+                    if shared.len() != 2 {
+                        closed = false;
+                        continue;
+                    }
+                    let other = shared[usize::from(shared[0] == idx)];
+                    if other == idx || seen[other] {
+                        continue;
+                    }
+                    seen[other] = true;
+                    // Consistent neighbours run their shared edge the other way.
+                    flip[other] = directed_edges(self.triangles[other]).contains(&(a, b));
+                    component.push(other);
+                }
+            }
+
+            let outward = if closed {
+                let volume: f64 = component
+                    .iter()
+                    .map(|&idx| {
+                        let [a, b, c] = self.wound(idx, flip[idx]).map(|v| self.positions[v]);
+                        a.to_vec().dot(b.to_vec().cross(c.to_vec()))
+                    })
+                    .sum();
+                volume >= 0.0
+            } else {
+                component.iter().filter(|&&idx| flip[idx]).count() * 2 <= component.len()
+            };
+            if !outward {
+                for &idx in &component {
+                    flip[idx] = !flip[idx];
+                }
+            }
+        }
+
+        let mut flipped = 0;
+        for (triangle, flip) in self.triangles.iter_mut().zip(flip) {
+            if flip {
+                triangle.swap(1, 2);
+                flipped += 1;
+            }
+        }
+        flipped
+    }
+
+    fn check(&self) -> MeshCheck {
+        let mut uses: BTreeMap<(usize, usize), Vec<(usize, usize)>> = BTreeMap::new();
+        for &[a, b, c] in &self.triangles {
+            if a == b || b == c || c == a {
+                continue;
+            }
+            for (p, q) in directed_edges([a, b, c]) {
+                uses.entry((p.min(q), p.max(q))).or_default().push((p, q));
+            }
+        }
+        let midpoint = |(a, b): (usize, usize)| self.positions[a].midpoint(self.positions[b]);
+        let mut check = MeshCheck::default();
+        let mut boundary = Vec::new();
+        for (&edge, directed) in &uses {
+            match directed.as_slice() {
+                [_] => boundary.push(edge),
+                [first, second] if first == second => check.misoriented.push(midpoint(edge)),
+                [_, _] => {}
+                _ => check.non_manifold.push(midpoint(edge)),
+            }
+        }
+
+        // Boundary edges sharing a vertex belong to the same hole.
+        let mut at_vertex: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (idx, &(a, b)) in boundary.iter().enumerate() {
+            at_vertex.entry(a).or_default().push(idx);
+            at_vertex.entry(b).or_default().push(idx);
+        }
+        let mut seen = vec![false; boundary.len()];
+        for start in 0..boundary.len() {
+            if seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut pending = vec![start];
+            let (mut vertices, mut edges, mut perimeter) = (BTreeSet::new(), 0, 0.0);
+            while let Some(idx) = pending.pop() {
+                let (a, b) = boundary[idx];
+                edges += 1;
+                perimeter += (self.positions[b] - self.positions[a]).magnitude();
+                for v in [a, b] {
+                    vertices.insert(v);
+                    for &next in &at_vertex[&v] {
+                        if !seen[next] {
+                            seen[next] = true;
+                            pending.push(next);
+                        }
+                    }
+                }
+            }
+            let sum = vertices
+                .iter()
+                .fold(Vector3::new(0.0, 0.0, 0.0), |sum, &v| {
+                    sum + self.positions[v].to_vec()
+                });
+            check.holes.push(MeshHole {
+                center: Point3::from_vec(sum / vertices.len() as f64),
+                edges,
+                perimeter,
+            });
+        }
+        check
+    }
+
+    /// Triangle `idx`, reversed when `flip` is set.
+    fn wound(&self, idx: usize, flip: bool) -> [usize; 3] {
+        let [a, b, c] = self.triangles[idx];
+        if flip { [a, c, b] } else { [a, b, c] }
+    }
+
+    /// Quadric error edge collapse (Garland and Heckbert): each vertex carries the planes of
+    /// the triangles around it, and collapsing an edge costs the squared distance from those
+    /// planes to where the merged vertex goes.
+    fn decimate(&mut self, target: usize) -> usize {
+        let before = self.triangles.len();
+        if before <= target {
+            return 0;
+        }
+        let count = self.positions.len();
+        let mut quadrics = vec![Quadric::default(); count];
+        let mut incident: Vec<Vec<usize>> = vec![Vec::new(); count];
+        let mut edge_uses: HashMap<(usize, usize), usize> = HashMap::new();
+        for (idx, &triangle) in self.triangles.iter().enumerate() {
+            let [a, b, c] = triangle.map(|v| self.positions[v]);
+            if let Some(plane) = Quadric::plane(a, (b - a).cross(c - a)) {
+                for v in triangle {
+                    quadrics[v] = quadrics[v].add(plane);
+                }
+            }
+            for v in triangle {
+                incident[v].push(idx);
+            }
+            for (p, q) in directed_edges(triangle) {
+                *edge_uses.entry((p.min(q), p.max(q))).or_default() += 1;
+            }
+        }
+        // Open boundaries are held by planes standing on their edges.
+        for &triangle in &self.triangles {
+            let [a, b, c] = triangle.map(|v| self.positions[v]);
+            let normal = (b - a).cross(c - a);
+            for (p, q) in directed_edges(triangle) {
+                if edge_uses[&(p.min(q), p.max(q))] != 1 {
+                    continue;
+                }
+                let along = self.positions[q] - self.positions[p];
+                if let Some(plane) = Quadric::plane(self.positions[p], along.cross(normal)) {
+                    let plane = plane.scaled(BOUNDARY_WEIGHT);
+                    quadrics[p] = quadrics[p].add(plane);
+                    quadrics[q] = quadrics[q].add(plane);
+                }
+            }
+        }
+
+        let mut stamps = vec![0usize; count];
+        let mut edges: Vec<_> = edge_uses.into_keys().collect();
+        edges.sort_unstable();
+        let mut heap: BinaryHeap<Collapse> = edges
+            .into_iter()
+            .map(|(a, b)| self.collapse(a, b, &quadrics, &stamps))
+            .collect();
+        let mut alive = vec![true; before];
+        let mut remaining = before;
+        while remaining > target {
+            let Some(collapse) = heap.pop() else {
+                break;
+            };
+            let (a, b) = (collapse.keep, collapse.remove);
+            if collapse.stamps != [stamps[a], stamps[b]]
+                || !self.can_collapse(&collapse, &incident, &alive)
+            {
+                continue;
+            }
+
+            self.positions[a] = collapse.target;
+            quadrics[a] = quadrics[a].add(quadrics[b]);
+            for idx in std::mem::take(&mut incident[b]) {
+                if !alive[idx] {
+                    continue;
+                }
+                if self.triangles[idx].contains(&a) {
+                    alive[idx] = false;
+                    remaining -= 1;
+                    continue;
+                }
+                for corner in &mut self.triangles[idx] {
+                    if *corner == b {
+                        *corner = a;
+                    }
+                }
+                incident[a].push(idx);
+            }
+            incident[a].retain(|&idx| alive[idx]);
+            stamps[a] += 1;
+            stamps[b] += 1;
+
+            let neighbours: BTreeSet<usize> = incident[a]
+                .iter()
+                .flat_map(|&idx| self.triangles[idx])
+                .filter(|&v| v != a)
+                .collect();
+            for v in neighbours {
+                heap.push(self.collapse(a, v, &quadrics, &stamps));
+            }
+        }
+
+        let mut alive = alive.into_iter();
+        self.triangles.retain(|_| alive.next().unwrap_or(false));
+        before - self.triangles.len()
+    }
+
+    /// Cheapest way to merge `b` into `a`: the point of least error of their combined
+    /// quadric when it is near the edge, otherwise the better end or the midpoint.
+    fn collapse(&self, a: usize, b: usize, quadrics: &[Quadric], stamps: &[usize]) -> Collapse {
+        let quadric = quadrics[a].add(quadrics[b]);
+        let (pa, pb) = (self.positions[a], self.positions[b]);
+        let mid = Point3::from_vec((pa.to_vec() + pb.to_vec()) * 0.5);
+        let optimum = quadric
+            .optimum()
+            .filter(|point| (*point - mid).magnitude() <= (pb - pa).magnitude());
+        let (cost, target) = optimum
+            .into_iter()
+            .chain([pa, pb, mid])
+            .map(|point| (quadric.error(point), point))
+            .min_by(|x, y| x.0.total_cmp(&y.0))
+            .unwrap_or((0.0, pa));
+        Collapse {
+            cost,
+            keep: a,
+            remove: b,
+            stamps: [stamps[a], stamps[b]],
+            target,
+        }
+    }
+
+    /// Whether `collapse` keeps the surface manifold and folds no triangle over.
+    fn can_collapse(&self, collapse: &Collapse, incident: &[Vec<usize>], alive: &[bool]) -> bool {
+        let (a, b) = (collapse.keep, collapse.remove);
+        let around = |v: usize| -> BTreeSet<usize> {
+            incident[v]
+                .iter()
+                .filter(|&&idx| alive[idx])
+                .flat_map(|&idx| self.triangles[idx])
+                .filter(|&w| w != v)
+                .collect()
+        };
+        let shared_faces = incident[a]
+            .iter()
+            .filter(|&&idx| alive[idx] && self.triangles[idx].contains(&b))
+            .count();
+        if shared_faces == 0 || around(a).intersection(&around(b)).count() > shared_faces {
+            return false;
+        }
+        incident[a].iter().chain(&incident[b]).all(|&idx| {
+            let triangle = self.triangles[idx];
+            if !alive[idx] || (triangle.contains(&a) && triangle.contains(&b)) {
+                return true;
+            }
+            let normal = |moved: bool| {
+                let [p, q, r] = triangle.map(|v| {
+                    if moved && (v == a || v == b) {
+                        collapse.target
+                    } else {
+                        self.positions[v]
+                    }
+                });
+                (q - p).cross(r - p)
+            };
+            normal(false).dot(normal(true)) > 0.0
+        })
+    }
+}
+
+/// Edges of `triangle` in its winding order.
+fn directed_edges([a, b, c]: [usize; 3]) -> [(usize, usize); 3] {
+    [(a, b), (b, c), (c, a)]
+}
+
+/// Candidate edge collapse, ordered cheapest first in a [`BinaryHeap`]. It is stale once
+/// either vertex has changed since, which `stamps` tells.
+struct Collapse {
+    cost: f64,
+    keep: usize,
+    remove: usize,
+    stamps: [usize; 2],
+    target: Point3,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| (other.keep, other.remove).cmp(&(self.keep, self.remove)))
+    }
+}
+
+/// Sum of squared distances to a set of planes: the upper triangle of a symmetric 4×4
+/// matrix, row by row.
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// Plane through `point` facing `normal`, `None` when `normal` has no direction.
+    fn plane(point: Point3, normal: Vector3) -> Option<Self> {
+        let length = normal.magnitude();
+        if length <= f64::MIN_POSITIVE {
+            return None;
+        }
+        let n = normal / length;
+        let d = -n.dot(point.to_vec());
+        Some(Self([
+            n.x * n.x,
+            n.x * n.y,
+            n.x * n.z,
+            n.x * d,
+            n.y * n.y,
+            n.y * n.z,
+            n.y * d,
+            n.z * n.z,
+            n.z * d,
+            d * d,
+        ]))
+    }
+
+    fn add(self, other: Self) -> Self {
+        let mut sum = self.0;
+        for (value, other) in sum.iter_mut().zip(other.0) {
+            *value += other;
+        }
+        Self(sum)
+    }
+
+    fn scaled(self, factor: f64) -> Self {
+        Self(self.0.map(|value| value * factor))
+    }
+
+    fn error(&self, p: Point3) -> f64 {
+        let [a, b, c, d, e, f, g, h, i, j] = self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        let squares = a * x * x + e * y * y + h * z * z + j;
+        squares + 2.0 * (b * x * y + c * x * z + f * y * z + d * x + g * y + i * z)
+    }
+
+    /// Point of least error by Cramer's rule, `None` when the planes leave it undetermined,
+    /// as on a flat or singly curved patch.
+    fn optimum(&self) -> Option<Point3> {
+        let [a, b, c, d, e, f, g, h, i, _] = self.0;
+        let det = a * (e * h - f * f) - b * (b * h - f * c) + c * (b * f - e * c);
+        if det.abs() <= 1.0e-9 {
+            return None;
+        }
+        let (r0, r1, r2) = (-d, -g, -i);
+        let x = r0 * (e * h - f * f) - b * (r1 * h - f * r2) + c * (r1 * f - e * r2);
+        let y = a * (r1 * h - f * r2) - r0 * (b * h - f * c) + c * (b * r2 - r1 * c);
+        let z = a * (e * r2 - r1 * f) - b * (b * r2 - r1 * c) + r0 * (b * f - e * c);
+        Some(Point3::new(x / det, y / det, z / det))
+    }
+}
+
+pub fn export_obj(
+    solid: &Solid,
+    path: impl AsRef<Path>,
+    options: &TessellationOptions,
+) -> Result<()> {
     let path = path.as_ref();
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)
             .with_context(|| format!("create output directory {}", parent.display()))?;
     }
 
-    let mesh = triangulate_solid(solid, tol);
+    let mesh = triangulate_solid(solid, options);
     if mesh.positions().is_empty() {
         bail!("triangulation produced empty mesh");
     }
+    ensure_watertight(&mesh, options, "the solid")?;
 
     let file = File::create(path).with_context(|| format!("create OBJ file {}", path.display()))?;
     obj::write(&mesh, file).with_context(|| format!("write OBJ file {}", path.display()))?;
     Ok(())
 }
+
+/// Reads a polygon mesh from an OBJ file, such as a surveyor's terrain or as-built mesh. See
+/// [`reference_mesh_element`](crate::reference_mesh_element) to place it in a model.
+pub fn import_obj(path: impl AsRef<Path>) -> Result<PolygonMesh> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("open OBJ file {}", path.display()))?;
+    let mesh = obj::read(BufReader::new(file))
+        .with_context(|| format!("read OBJ file {}", path.display()))?;
+    if mesh.positions().is_empty() {
+        bail!("OBJ file {} has no vertices", path.display());
+    }
+    Ok(mesh)
+}
+
+/// Options of [`export_gltf`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfOptions {
+    /// How finely each element is tessellated.
+    pub tessellation: TessellationOptions,
+    /// Layers whose colors become materials: each element takes the color of the layer named by
+    /// its `Layer` parameter, else the first layer's, as
+    /// [`BimModel::layer_color`](cryxtal_bim::BimModel::layer_color) does. Without layers the
+    /// meshes carry no material.
+    pub layers: Vec<Layer>,
+    /// Store positions as 16-bit integers (`KHR_mesh_quantization`), each element scaled to its
+    /// own bounds by its node transform. Halves the position data at a precision of 1/65534 of
+    /// the element's extent.
+    pub quantize: bool,
+}
+
+impl Default for GltfOptions {
+    fn default() -> Self {
+        Self {
+            tessellation: TessellationOptions::default(),
+            layers: Vec::new(),
+            quantize: false,
+        }
+    }
+}
+
+/// Writes `elements` to `path` as a binary glTF (`.glb`) with [`write_glb`].
+pub fn export_gltf<'a>(
+    elements: impl IntoIterator<Item = &'a BimElement>,
+    path: impl AsRef<Path>,
+    options: &GltfOptions,
+) -> Result<()> {
+    let path = path.as_ref();
+    let file = File::create(path).with_context(|| format!("create GLB file {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_glb(elements, options, &mut writer)?;
+    writer
+        .flush()
+        .with_context(|| format!("write GLB file {}", path.display()))
+}
+
+/// Tessellates `elements` into a binary glTF for web viewers: one node per element, named after
+/// it with its guid and name in the node `extras`, under a root node mapping model millimetres
+/// (Z up) to glTF metres (Y up). Positions only, like the `.gltf` export. Elements without
+/// faces are skipped.
+pub fn write_glb<'a>(
+    elements: impl IntoIterator<Item = &'a BimElement>,
+    options: &GltfOptions,
+    mut writer: impl Write,
+) -> Result<()> {
+    let mut scene = GlbScene::new(options);
+    let mut bin: Vec<u8> = Vec::new();
+    let elements: Vec<_> = elements.into_iter().collect();
+    let solids: Vec<_> = elements.iter().map(|element| element.geometry()).collect();
+    let element_meshes = triangulate_many(&solids, &options.tessellation);
+    for (element, mesh) in elements.into_iter().zip(element_meshes) {
+        if mesh.positions().is_empty() {
+            continue;
+        }
+        scene.add(element, &mesh, &mut bin)?;
+    }
+    scene.pad(&mut bin)?;
+    scene.write_head(&mut writer)?;
+    writer.write_all(&bin)?;
+    Ok(())
+}
+
+/// Like [`export_gltf`], but tessellates and writes one element at a time, so memory stays
+/// bounded by the largest element rather than the model. The binary chunk is spooled to a
+/// `.glb.part` file next to `path`, copied in behind the JSON chunk at the end and removed.
+/// Returns the number of elements written.
+pub fn export_glb_streaming<'a>(
+    elements: impl IntoIterator<Item = &'a BimElement>,
+    path: impl AsRef<Path>,
+    options: &GltfOptions,
+) -> Result<usize> {
+    let path = path.as_ref();
+    let spool_path = path.with_extension("glb.part");
+    let written = spool_glb(elements, path, &spool_path, options);
+    let _ = std::fs::remove_file(&spool_path);
+    written
+}
+
+fn spool_glb<'a>(
+    elements: impl IntoIterator<Item = &'a BimElement>,
+    path: &Path,
+    spool_path: &Path,
+    options: &GltfOptions,
+) -> Result<usize> {
+    let spool = File::create(spool_path)
+        .with_context(|| format!("create GLB spool file {}", spool_path.display()))?;
+    let mut bin = BufWriter::new(spool);
+    let mut scene = GlbScene::new(options);
+    let mut written = 0;
+    for element in elements {
+        let mesh = triangulate_solid(element.geometry(), &options.tessellation);
+        if mesh.positions().is_empty() {
+            continue;
+        }
+        scene
+            .add(element, &mesh, &mut bin)
+            .with_context(|| format!("write element {}", element.name))?;
+        written += 1;
+    }
+    scene.pad(&mut bin)?;
+    bin.flush()
+        .with_context(|| format!("write GLB spool file {}", spool_path.display()))?;
+    drop(bin);
+
+    let file = File::create(path).with_context(|| format!("create GLB file {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    scene.write_head(&mut writer)?;
+    let mut spool = File::open(spool_path)
+        .with_context(|| format!("open GLB spool file {}", spool_path.display()))?;
+    std::io::copy(&mut spool, &mut writer)?;
+    writer
+        .flush()
+        .with_context(|| format!("write GLB file {}", path.display()))?;
+    Ok(written)
+}
+
+/// JSON side of a GLB being written. Vertex data goes to the binary chunk's sink as elements
+/// are added; only the small per-element records are kept until [`GlbScene::write_head`].
+struct GlbScene<'a> {
+    options: &'a GltfOptions,
+    /// Bytes written to the binary chunk so far.
+    bin_len: usize,
+    accessors: Vec<Value>,
+    buffer_views: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    materials: Vec<Value>,
+    material_colors: Vec<[u8; 4]>,
+}
+
+impl<'a> GlbScene<'a> {
+    fn new(options: &'a GltfOptions) -> Self {
+        Self {
+            options,
+            bin_len: 0,
+            accessors: Vec::new(),
+            buffer_views: Vec::new(),
+            meshes: Vec::new(),
+            nodes: Vec::new(),
+            materials: Vec::new(),
+            material_colors: Vec::new(),
+        }
+    }
+
+    /// Adds `element` as a node with `mesh`, which must have vertices, writing its vertex data
+    /// to `bin`.
+    fn add(
+        &mut self,
+        element: &BimElement,
+        mesh: &PolygonMesh,
+        bin: &mut impl Write,
+    ) -> Result<()> {
+        let positions = mesh.positions();
+        if u32::try_from(positions.len()).is_err() {
+            bail!("{} has too many vertices for glTF", element.name);
+        }
+        let mut low = [f64::INFINITY; 3];
+        let mut high = [f64::NEG_INFINITY; 3];
+        for p in positions {
+            for (axis, value) in [p.x, p.y, p.z].into_iter().enumerate() {
+                low[axis] = low[axis].min(value);
+                high[axis] = high[axis].max(value);
+            }
+        }
+        let mut node = json!({
+            "name": element.name,
+            "mesh": self.meshes.len(),
+            "extras": { "guid": element.guid, "name": element.name },
+        });
+
+        let mut bytes = Vec::new();
+        let position = if self.options.quantize {
+            // Integers from -32767 to 32767 across each axis, mapped back by the node.
+            let center = [0, 1, 2].map(|axis| (low[axis] + high[axis]) * 0.5);
+            let step = [0, 1, 2].map(|axis| match (high[axis] - low[axis]) * 0.5 / 32767.0 {
+                step if step > 0.0 => step,
+                _ => 1.0,
+            });
+            let mut min = [i16::MAX; 3];
+            let mut max = [i16::MIN; 3];
+            for p in positions {
+                for (axis, value) in [p.x, p.y, p.z].into_iter().enumerate() {
+                    let value = ((value - center[axis]) / step[axis])
+                        .round()
+                        .clamp(-32767.0, 32767.0) as i16;
+                    min[axis] = min[axis].min(value);
+                    max[axis] = max[axis].max(value);
+                    bytes.extend_from_slice(&value.to_le_bytes());
+                }
+                // Pad each vertex to 8 bytes, as attributes must be 4-byte aligned.
+                bytes.extend_from_slice(&[0, 0]);
+            }
+            node["translation"] = json!(center);
+            node["scale"] = json!(step);
+            json!({
+                "bufferView": self.push_view(bin, &bytes, GLTF_ARRAY_BUFFER, Some(8))?,
+                "componentType": GLTF_SHORT,
+                "count": positions.len(),
+                "type": "VEC3",
+                "min": min,
+                "max": max,
+            })
+        } else {
+            for p in positions {
+                for value in [p.x, p.y, p.z] {
+                    bytes.extend_from_slice(&(value as f32).to_le_bytes());
+                }
+            }
+            json!({
+                "bufferView": self.push_view(bin, &bytes, GLTF_ARRAY_BUFFER, None)?,
+                "componentType": GLTF_FLOAT,
+                "count": positions.len(),
+                "type": "VEC3",
+                "min": low.map(|value| value as f32),
+                "max": high.map(|value| value as f32),
+            })
+        };
+        self.accessors.push(position);
+
+        let indices: Vec<u8> = triangles(mesh)
+            .into_iter()
+            .flatten()
+            .flat_map(|(pos, _)| (pos as u32).to_le_bytes())
+            .collect();
+        let index_view = self.push_view(bin, &indices, GLTF_ELEMENT_ARRAY_BUFFER, None)?;
+        self.accessors.push(json!({
+            "count": indices.len() / 4,
+            "bufferView": index_view,
+            "componentType": GLTF_UNSIGNED_INT,
+            "type": "SCALAR",
+        }));
+
+        let mut primitive = json!({
+            "attributes": { "POSITION": self.accessors.len() - 2 },
+            "indices": self.accessors.len() - 1,
+        });
+        if let Some(layer) = element_layer(element, &self.options.layers) {
+            let material = match self
+                .material_colors
+                .iter()
+                .position(|color| *color == layer.color)
+            {
+                Some(index) => index,
+                None => {
+                    self.material_colors.push(layer.color);
+                    self.materials.push(layer_material(layer));
+                    self.materials.len() - 1
+                }
+            };
+            primitive["material"] = json!(material);
+        }
+        self.meshes
+            .push(json!({ "name": element.name, "primitives": [primitive] }));
+        self.nodes.push(node);
+        Ok(())
+    }
+
+    /// Writes `bytes` to `bin` as a buffer view and returns its index.
+    fn push_view(
+        &mut self,
+        bin: &mut impl Write,
+        bytes: &[u8],
+        target: u32,
+        stride: Option<usize>,
+    ) -> Result<usize> {
+        // Every view starts 4-byte aligned.
+        self.pad(bin)?;
+        // Stop before spooling data a GLB cannot address; the JSON chunk only adds to it.
+        if u32::try_from(self.bin_len + bytes.len()).is_err() {
+            bail!("GLB exceeds 4 GiB");
+        }
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.bin_len,
+            "byteLength": bytes.len(),
+            "target": target,
+        });
+        if let Some(stride) = stride {
+            view["byteStride"] = json!(stride);
+        }
+        bin.write_all(bytes)?;
+        self.bin_len += bytes.len();
+        self.buffer_views.push(view);
+        Ok(self.buffer_views.len() - 1)
+    }
+
+    /// Pads the binary chunk with zeros to a multiple of 4 bytes.
+    fn pad(&mut self, bin: &mut impl Write) -> Result<()> {
+        let padded = self.bin_len.next_multiple_of(4);
+        bin.write_all(&[0; 3][..padded - self.bin_len])?;
+        self.bin_len = padded;
+        Ok(())
+    }
+
+    /// Writes the GLB header, the JSON chunk and the header of the binary chunk, whose padded
+    /// data is to follow.
+    fn write_head(mut self, writer: &mut impl Write) -> Result<()> {
+        if self.nodes.is_empty() {
+            bail!("triangulation produced empty mesh");
+        }
+        let children: Vec<usize> = (0..self.nodes.len()).collect();
+        let root = self.nodes.len();
+        self.nodes.push(json!({
+            "name": "model",
+            "matrix": GLTF_ROOT_MATRIX,
+            "children": children,
+        }));
+        let mut document = json!({
+            "asset": { "version": "2.0", "generator": "cryxtal" },
+            "scene": 0,
+            "scenes": [{ "nodes": [root] }],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+            "buffers": [{ "byteLength": self.bin_len }],
+        });
+        if !self.materials.is_empty() {
+            document["materials"] = json!(self.materials);
+        }
+        if self.options.quantize {
+            document["extensionsUsed"] = json!(["KHR_mesh_quantization"]);
+            document["extensionsRequired"] = json!(["KHR_mesh_quantization"]);
+        }
+
+        let mut json_chunk = serde_json::to_vec(&document)?;
+        json_chunk.resize(json_chunk.len().next_multiple_of(4), b' ');
+        let length = 12 + 8 + json_chunk.len() + 8 + self.bin_len;
+        let length = u32::try_from(length).context("GLB exceeds 4 GiB")?;
+        writer.write_all(&GLB_MAGIC.to_le_bytes())?;
+        writer.write_all(&2u32.to_le_bytes())?;
+        writer.write_all(&length.to_le_bytes())?;
+        writer.write_all(&(json_chunk.len() as u32).to_le_bytes())?;
+        writer.write_all(&GLB_JSON_CHUNK.to_le_bytes())?;
+        writer.write_all(&json_chunk)?;
+        writer.write_all(&(self.bin_len as u32).to_le_bytes())?;
+        writer.write_all(&GLB_BIN_CHUNK.to_le_bytes())?;
+        Ok(())
+    }
+}
+
+/// Layer `element` is drawn with: the one its `Layer` parameter names, else the first.
+fn element_layer<'a>(element: &BimElement, layers: &'a [Layer]) -> Option<&'a Layer> {
+    let named = match element.parameters.get("Layer") {
+        Some(ParameterValue::Text(name)) => layers.iter().find(|layer| layer.name == *name),
+        _ => None,
+    };
+    named.or(layers.first())
+}
+
+/// Unlit-looking material of a layer color: glTF base colors are linear, layer colors sRGB.
+fn layer_material(layer: &Layer) -> Value {
+    let [r, g, b, a] = layer.color;
+    let linear = |channel: u8| {
+        let value = channel as f64 / 255.0;
+        if value <= 0.04045 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let mut material = json!({
+        "name": layer.name,
+        "pbrMetallicRoughness": {
+            "baseColorFactor": [linear(r), linear(g), linear(b), a as f64 / 255.0],
+            "metallicFactor": 0.0,
+            "roughnessFactor": 1.0,
+        },
+        "doubleSided": true,
+    });
+    if a < u8::MAX {
+        material["alphaMode"] = json!("BLEND");
+    }
+    material
+}
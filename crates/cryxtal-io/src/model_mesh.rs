@@ -0,0 +1,394 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_base::Guid;
+use cryxtal_bim::BimElement;
+use serde_json::{Value, json};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use truck_polymesh::PolygonMesh;
+
+use crate::mesh::{TessellationOptions, ensure_watertight, triangulate_many, triangulate_solid};
+use crate::packed_mesh::{PACKED_MESH_EXTENSION, PackedElement, PackedMesh, PackedMeshWriter};
+
+/// Maps model millimetres (Z up) to glTF metres (Y up), column-major.
+pub(crate) const GLTF_ROOT_MATRIX: [f64; 16] = [
+    0.001, 0.0, 0.0, 0.0, //
+    0.0, 0.0, -0.001, 0.0, //
+    0.0, 0.001, 0.0, 0.0, //
+    0.0, 0.0, 0.0, 1.0,
+];
+
+pub(crate) const GLTF_FLOAT: u32 = 5126;
+pub(crate) const GLTF_UNSIGNED_INT: u32 = 5125;
+pub(crate) const GLTF_ARRAY_BUFFER: u32 = 34962;
+pub(crate) const GLTF_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Triangle mesh formats a whole model can be exported to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MeshFormat {
+    Obj,
+    /// `.gltf` JSON with the vertex data in a `.bin` file next to it.
+    Gltf,
+    /// Compressed `.cxmesh` payload, see [`crate::packed_mesh`].
+    Packed,
+}
+
+impl MeshFormat {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("obj") => Ok(MeshFormat::Obj),
+            Some("gltf") => Ok(MeshFormat::Gltf),
+            Some(PACKED_MESH_EXTENSION) => Ok(MeshFormat::Packed),
+            _ => bail!(
+                "unsupported mesh format for {} (expected .obj, .gltf or .{PACKED_MESH_EXTENSION})",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Tessellates every element, merges the meshes and writes them as one object.
+pub fn export_model_mesh<'a>(
+    elements: impl IntoIterator<Item = &'a BimElement>,
+    path: impl AsRef<Path>,
+    options: &TessellationOptions,
+) -> Result<()> {
+    let path = path.as_ref();
+    let elements: Vec<_> = elements.into_iter().collect();
+    let solids: Vec<_> = elements.iter().map(|element| element.geometry()).collect();
+    let mut merged = PolygonMesh::default();
+    // Elements are checked one by one, as touching elements share edges once merged.
+    for (element, mesh) in elements.iter().zip(triangulate_many(&solids, options)) {
+        ensure_watertight(&mesh, options, &element.name)?;
+        merged.merge(mesh);
+    }
+    if merged.positions().is_empty() {
+        bail!("triangulation produced empty mesh");
+    }
+    let mut writer = MeshWriter::create(path)?;
+    writer.write_mesh("model", None, &merged)?;
+    writer.finish()
+}
+
+/// Tessellates and writes one element at a time, so memory stays bounded by the largest
+/// element instead of the whole model. Each element becomes an OBJ object or glTF node named
+/// after it. Returns the number of elements written.
+pub fn export_model_mesh_streaming<'a>(
+    elements: impl IntoIterator<Item = &'a BimElement>,
+    path: impl AsRef<Path>,
+    options: &TessellationOptions,
+) -> Result<usize> {
+    let mut writer = MeshWriter::create(path.as_ref())?;
+    let mut written = 0;
+    for element in elements {
+        let mesh = triangulate_solid(element.geometry(), options);
+        if mesh.positions().is_empty() {
+            continue;
+        }
+        ensure_watertight(&mesh, options, &element.name)?;
+        writer
+            .write_mesh(&element.name, Some(element.guid), &mesh)
+            .with_context(|| format!("write element {}", element.name))?;
+        written += 1;
+    }
+    if written == 0 {
+        bail!("triangulation produced empty mesh");
+    }
+    writer.finish()?;
+    Ok(written)
+}
+
+enum MeshWriter {
+    Obj(ObjWriter),
+    Gltf(GltfWriter),
+    Packed(PathBuf, PackedMeshWriter<BufWriter<File>>),
+}
+
+impl MeshWriter {
+    fn create(path: &Path) -> Result<Self> {
+        let format = MeshFormat::from_path(path)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("create output directory {}", parent.display()))?;
+        }
+        Ok(match format {
+            MeshFormat::Obj => MeshWriter::Obj(ObjWriter::create(path)?),
+            MeshFormat::Gltf => MeshWriter::Gltf(GltfWriter::create(path)?),
+            MeshFormat::Packed => {
+                let file = File::create(path)
+                    .with_context(|| format!("create mesh file {}", path.display()))?;
+                MeshWriter::Packed(
+                    path.to_path_buf(),
+                    PackedMeshWriter::new(BufWriter::new(file))?,
+                )
+            }
+        })
+    }
+
+    /// `guid` is `None` for the merged model mesh.
+    fn write_mesh(&mut self, name: &str, guid: Option<Guid>, mesh: &PolygonMesh) -> Result<()> {
+        match self {
+            MeshWriter::Obj(writer) => writer.write_mesh(name, mesh),
+            MeshWriter::Gltf(writer) => writer.write_mesh(name, guid, mesh),
+            MeshWriter::Packed(_, writer) => writer.write(&PackedElement {
+                guid: guid.unwrap_or_default(),
+                name: name.to_string(),
+                key: None,
+                mesh: PackedMesh::from_mesh(mesh),
+            }),
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            MeshWriter::Obj(writer) => writer.finish(),
+            MeshWriter::Gltf(writer) => writer.finish(),
+            MeshWriter::Packed(path, writer) => writer
+                .finish()?
+                .flush()
+                .with_context(|| format!("write mesh file {}", path.display())),
+        }
+    }
+}
+
+struct ObjWriter {
+    path: PathBuf,
+    out: BufWriter<File>,
+    positions: usize,
+    normals: usize,
+}
+
+impl ObjWriter {
+    fn create(path: &Path) -> Result<Self> {
+        let file =
+            File::create(path).with_context(|| format!("create OBJ file {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            out: BufWriter::new(file),
+            positions: 0,
+            normals: 0,
+        })
+    }
+
+    /// Indices continue across objects, as OBJ numbers vertices file-wide.
+    fn write_mesh(&mut self, name: &str, mesh: &PolygonMesh) -> Result<()> {
+        let out = &mut self.out;
+        writeln!(
+            out,
+            "o {}",
+            name.split_whitespace().collect::<Vec<_>>().join("_")
+        )?;
+        for p in mesh.positions() {
+            writeln!(out, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+        for n in mesh.normals() {
+            writeln!(out, "vn {} {} {}", n.x, n.y, n.z)?;
+        }
+        for triangle in triangles(mesh) {
+            write!(out, "f")?;
+            for (pos, nor) in triangle {
+                match nor {
+                    Some(nor) => write!(
+                        out,
+                        " {}//{}",
+                        self.positions + pos + 1,
+                        self.normals + nor + 1
+                    )?,
+                    None => write!(out, " {}", self.positions + pos + 1)?,
+                }
+            }
+            writeln!(out)?;
+        }
+        self.positions += mesh.positions().len();
+        self.normals += mesh.normals().len();
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<()> {
+        self.out
+            .flush()
+            .with_context(|| format!("write OBJ file {}", self.path.display()))
+    }
+}
+
+/// Vertex data is appended to the `.bin` file as it is written; only the small per-element
+/// accessor records are kept until the `.gltf` JSON is written at the end.
+struct GltfWriter {
+    path: PathBuf,
+    bin_name: String,
+    bin: BufWriter<File>,
+    byte_length: usize,
+    accessors: Vec<Value>,
+    buffer_views: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+}
+
+impl GltfWriter {
+    fn create(path: &Path) -> Result<Self> {
+        let bin_path = path.with_extension("bin");
+        let bin_name = bin_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .context("glTF output needs a file name")?
+            .to_string();
+        let file = File::create(&bin_path)
+            .with_context(|| format!("create glTF buffer {}", bin_path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            bin_name,
+            bin: BufWriter::new(file),
+            byte_length: 0,
+            accessors: Vec::new(),
+            buffer_views: Vec::new(),
+            meshes: Vec::new(),
+            nodes: Vec::new(),
+        })
+    }
+
+    /// Positions only; viewers shade primitives without normals flat. Element guids go in the
+    /// node `extras`.
+    fn write_mesh(&mut self, name: &str, guid: Option<Guid>, mesh: &PolygonMesh) -> Result<()> {
+        let positions = mesh.positions();
+        if u32::try_from(positions.len()).is_err() {
+            bail!("mesh has too many vertices for glTF");
+        }
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        let position_offset = self.byte_length;
+        for p in positions {
+            for (axis, value) in [p.x, p.y, p.z].into_iter().enumerate() {
+                let value = value as f32;
+                min[axis] = min[axis].min(value);
+                max[axis] = max[axis].max(value);
+                self.bin.write_all(&value.to_le_bytes())?;
+            }
+        }
+        self.byte_length += positions.len() * 12;
+
+        let index_offset = self.byte_length;
+        let mut index_count = 0;
+        for triangle in triangles(mesh) {
+            for (pos, _) in triangle {
+                self.bin.write_all(&(pos as u32).to_le_bytes())?;
+            }
+            index_count += 3;
+        }
+        self.byte_length += index_count * 4;
+
+        let position_accessor = self.push_accessor(
+            position_offset,
+            positions.len() * 12,
+            GLTF_ARRAY_BUFFER,
+            json!({
+                "componentType": GLTF_FLOAT,
+                "count": positions.len(),
+                "type": "VEC3",
+                "min": min,
+                "max": max,
+            }),
+        );
+        let index_accessor = self.push_accessor(
+            index_offset,
+            index_count * 4,
+            GLTF_ELEMENT_ARRAY_BUFFER,
+            json!({
+                "componentType": GLTF_UNSIGNED_INT,
+                "count": index_count,
+                "type": "SCALAR",
+            }),
+        );
+        let mut node = json!({ "name": name, "mesh": self.meshes.len() });
+        if let Some(guid) = guid {
+            node["extras"] = json!({ "guid": guid });
+        }
+        self.nodes.push(node);
+        self.meshes.push(json!({
+            "name": name,
+            "primitives": [{
+                "attributes": { "POSITION": position_accessor },
+                "indices": index_accessor,
+            }],
+        }));
+        Ok(())
+    }
+
+    fn push_accessor(
+        &mut self,
+        byte_offset: usize,
+        byte_length: usize,
+        target: u32,
+        mut accessor: Value,
+    ) -> usize {
+        accessor["bufferView"] = json!(self.buffer_views.len());
+        self.buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": byte_offset,
+            "byteLength": byte_length,
+            "target": target,
+        }));
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn finish(mut self) -> Result<()> {
+        self.bin
+            .flush()
+            .with_context(|| format!("write glTF buffer {}", self.bin_name))?;
+        let children: Vec<usize> = (0..self.nodes.len()).collect();
+        let root = self.nodes.len();
+        self.nodes.push(json!({
+            "name": "model",
+            "matrix": GLTF_ROOT_MATRIX,
+            "children": children,
+        }));
+        let document = json!({
+            "asset": { "version": "2.0", "generator": "cryxtal" },
+            "scene": 0,
+            "scenes": [{ "nodes": [root] }],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+            "buffers": [{ "uri": self.bin_name, "byteLength": self.byte_length }],
+        });
+        let file = File::create(&self.path)
+            .with_context(|| format!("create glTF file {}", self.path.display()))?;
+        let mut out = BufWriter::new(file);
+        serde_json::to_writer(&mut out, &document)
+            .with_context(|| format!("write glTF file {}", self.path.display()))?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Faces as triangles of `(position, normal)` indices; quads and polygons are fanned.
+pub(crate) fn triangles(mesh: &PolygonMesh) -> Vec<[(usize, Option<usize>); 3]> {
+    let mut triangles = Vec::new();
+    for tri in mesh.tri_faces() {
+        triangles.push([
+            (tri[0].pos, tri[0].nor),
+            (tri[1].pos, tri[1].nor),
+            (tri[2].pos, tri[2].nor),
+        ]);
+    }
+    for quad in mesh.quad_faces() {
+        let corner = |idx: usize| (quad[idx].pos, quad[idx].nor);
+        triangles.push([corner(0), corner(1), corner(2)]);
+        triangles.push([corner(0), corner(2), corner(3)]);
+    }
+    for face in mesh.faces().other_faces() {
+        for idx in 1..face.len().saturating_sub(1) {
+            triangles.push([
+                (face[0].pos, face[0].nor),
+                (face[idx].pos, face[idx].nor),
+                (face[idx + 1].pos, face[idx + 1].nor),
+            ]);
+        }
+    }
+    triangles
+}
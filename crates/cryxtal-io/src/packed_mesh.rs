@@ -0,0 +1,281 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_base::Guid;
+use cryxtal_topology::{Point3, Solid, Vector3};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use truck_meshalgo::prelude::*;
+use truck_polymesh::{Faces, PolygonMesh, StandardAttributes};
+
+use crate::model_mesh::triangles;
+
+/// Bumped whenever the packed layout changes; older files are rejected and rebuilt.
+pub const PACKED_MESH_VERSION: u32 = 2;
+pub const PACKED_MESH_EXTENSION: &str = "cxmesh";
+
+const PACKED_MESH_MAGIC: &[u8; 8] = b"CRYXMSH\0";
+const ZSTD_LEVEL: i32 = 9;
+const QUANTIZATION_STEPS: f64 = u16::MAX as f64;
+
+/// Triangle mesh with positions quantized to 16 bits over its bounding box and indices stored
+/// as zigzag deltas, both of which compress well. Normals are kept as they are, so curved faces
+/// shade as smoothly as when tessellated; a mesh with corners lacking a normal gets flat normals
+/// on unpacking.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PackedMesh {
+    origin: [f64; 3],
+    step: [f64; 3],
+    positions: Vec<u16>,
+    indices: Vec<u32>,
+    normals: Vec<[f32; 3]>,
+    /// Normal of each corner, as zigzag deltas like `indices`; empty without normals.
+    normal_indices: Vec<u32>,
+}
+
+impl PackedMesh {
+    pub fn from_mesh(mesh: &PolygonMesh) -> Self {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        for p in mesh.positions() {
+            for (axis, value) in [p.x, p.y, p.z].into_iter().enumerate() {
+                min[axis] = min[axis].min(value);
+                max[axis] = max[axis].max(value);
+            }
+        }
+        let origin = if mesh.positions().is_empty() {
+            [0.0; 3]
+        } else {
+            min
+        };
+        let step = std::array::from_fn(|axis| {
+            let extent = max[axis] - origin[axis];
+            if extent > 0.0 {
+                extent / QUANTIZATION_STEPS
+            } else {
+                0.0
+            }
+        });
+        let quantize = |value: f64, axis: usize| -> u16 {
+            if step[axis] > 0.0 {
+                ((value - origin[axis]) / step[axis]).round() as u16
+            } else {
+                0
+            }
+        };
+        let positions = mesh
+            .positions()
+            .iter()
+            .flat_map(|p| [quantize(p.x, 0), quantize(p.y, 1), quantize(p.z, 2)])
+            .collect();
+
+        let corners: Vec<(usize, Option<usize>)> = triangles(mesh).into_iter().flatten().collect();
+        let indices = encode_deltas(corners.iter().map(|(pos, _)| *pos));
+        let corner_normals: Option<Vec<usize>> = corners.iter().map(|(_, nor)| *nor).collect();
+        let (normals, normal_indices) = match corner_normals {
+            Some(corner_normals) if !mesh.normals().is_empty() => (
+                mesh.normals()
+                    .iter()
+                    .map(|n| [n.x as f32, n.y as f32, n.z as f32])
+                    .collect(),
+                encode_deltas(corner_normals.into_iter()),
+            ),
+            _ => (Vec::new(), Vec::new()),
+        };
+
+        Self {
+            origin,
+            step,
+            positions,
+            indices,
+            normals,
+            normal_indices,
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len() / 3
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Restores the mesh; positions are within half a quantization step of the original.
+    pub fn to_mesh(&self) -> PolygonMesh {
+        let positions = self
+            .positions
+            .chunks_exact(3)
+            .map(|p| {
+                Point3::new(
+                    self.origin[0] + p[0] as f64 * self.step[0],
+                    self.origin[1] + p[1] as f64 * self.step[1],
+                    self.origin[2] + p[2] as f64 * self.step[2],
+                )
+            })
+            .collect();
+        let indices = decode_deltas(&self.indices);
+        if self.normal_indices.len() != indices.len() {
+            let faces: Faces = Faces::from_iter(indices.chunks_exact(3));
+            let mut mesh = PolygonMesh::new(
+                StandardAttributes {
+                    positions,
+                    ..Default::default()
+                },
+                faces,
+            );
+            mesh.add_naive_normals(true);
+            return mesh;
+        }
+        let normals = self
+            .normals
+            .iter()
+            .map(|n| Vector3::new(n[0] as f64, n[1] as f64, n[2] as f64))
+            .collect();
+        let corners: Vec<(usize, Option<usize>, Option<usize>)> = indices
+            .into_iter()
+            .zip(decode_deltas(&self.normal_indices))
+            .map(|(pos, nor)| (pos, None, Some(nor)))
+            .collect();
+        let faces: Faces = Faces::from_iter(corners.chunks_exact(3));
+        PolygonMesh::new(
+            StandardAttributes {
+                positions,
+                normals,
+                ..Default::default()
+            },
+            faces,
+        )
+    }
+}
+
+/// `values` as zigzag-coded differences from the value before, small for indices that mostly
+/// step through a mesh in order.
+fn encode_deltas(values: impl Iterator<Item = usize>) -> Vec<u32> {
+    let mut previous = 0i64;
+    values
+        .map(|value| {
+            let delta = value as i64 - previous;
+            previous = value as i64;
+            ((delta << 1) ^ (delta >> 63)) as u32
+        })
+        .collect()
+}
+
+fn decode_deltas(deltas: &[u32]) -> Vec<usize> {
+    let mut previous = 0i64;
+    deltas
+        .iter()
+        .map(|&zigzag| {
+            let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+            previous += delta;
+            previous as usize
+        })
+        .collect()
+}
+
+/// One element's mesh in a packed mesh file. `key` is the [`geometry_key`] of the solid it was
+/// tessellated from, when the file is used as a cache.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PackedElement {
+    pub guid: Guid,
+    pub name: String,
+    pub key: Option<u64>,
+    pub mesh: PackedMesh,
+}
+
+/// Writes a packed mesh file: magic header and version, then a zstd stream of MessagePack
+/// entries ended by `nil`. Entries are compressed as they are written.
+pub struct PackedMeshWriter<W: Write> {
+    encoder: zstd::Encoder<'static, W>,
+}
+
+impl<W: Write> PackedMeshWriter<W> {
+    pub fn new(mut writer: W) -> Result<Self> {
+        writer.write_all(PACKED_MESH_MAGIC)?;
+        writer.write_all(&PACKED_MESH_VERSION.to_le_bytes())?;
+        Ok(Self {
+            encoder: zstd::Encoder::new(writer, ZSTD_LEVEL)?,
+        })
+    }
+
+    pub fn write(&mut self, element: &PackedElement) -> Result<()> {
+        rmp_serde::encode::write(&mut self.encoder, &Some(element))
+            .context("encode packed mesh")?;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W> {
+        rmp_serde::encode::write(&mut self.encoder, &None::<PackedElement>)
+            .context("encode packed mesh")?;
+        Ok(self.encoder.finish()?)
+    }
+}
+
+pub fn read_packed_meshes(mut reader: impl Read) -> Result<Vec<PackedElement>> {
+    let mut header = [0u8; 12];
+    reader
+        .read_exact(&mut header)
+        .context("truncated packed mesh file")?;
+    if &header[..8] != PACKED_MESH_MAGIC {
+        bail!("not a packed mesh file");
+    }
+    let version = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+    if version != PACKED_MESH_VERSION {
+        bail!("unsupported packed mesh version {version}");
+    }
+    let mut decoder = zstd::Decoder::new(reader)?;
+    let mut elements = Vec::new();
+    while let Some(element) = rmp_serde::decode::from_read::<_, Option<PackedElement>>(&mut decoder)
+        .context("decode packed mesh")?
+    {
+        elements.push(element);
+    }
+    Ok(elements)
+}
+
+/// Stable fingerprint of a solid's serialized geometry (FNV-1a), used to tell whether a cached
+/// mesh still matches its element.
+pub fn geometry_key(solid: &Solid) -> Result<u64> {
+    let bytes = rmp_serde::to_vec(solid).context("serialize geometry")?;
+    Ok(bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    }))
+}
+
+/// Mesh cache kept next to a project file.
+pub fn mesh_cache_path(project: &Path) -> PathBuf {
+    project.with_extension(PACKED_MESH_EXTENSION)
+}
+
+pub fn save_mesh_cache(
+    path: impl AsRef<Path>,
+    elements: impl IntoIterator<Item = PackedElement>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("create mesh cache {}", path.display()))?;
+    let mut writer = PackedMeshWriter::new(BufWriter::new(file))?;
+    for element in elements {
+        writer.write(&element)?;
+    }
+    writer
+        .finish()?
+        .flush()
+        .with_context(|| format!("write mesh cache {}", path.display()))
+}
+
+/// Cached meshes by the [`geometry_key`] of the solid they were tessellated from, so an
+/// element finds its mesh whatever its guid, and copies share one; entries without a key are
+/// skipped.
+pub fn load_mesh_cache(path: impl AsRef<Path>) -> Result<HashMap<u64, PackedMesh>> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("open mesh cache {}", path.display()))?;
+    let elements = read_packed_meshes(BufReader::new(file))
+        .with_context(|| format!("read mesh cache {}", path.display()))?;
+    Ok(elements
+        .into_iter()
+        .filter_map(|element| Some((element.key?, element.mesh)))
+        .collect())
+}
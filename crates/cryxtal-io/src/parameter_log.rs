@@ -0,0 +1,62 @@
+use anyhow::{Context, Result};
+use cryxtal_base::Guid;
+use cryxtal_bim::BimModel;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::schedule::csv_field;
+
+/// Writes the model's parameter log as CSV for audits, one row per change, oldest first: the
+/// revision, time (seconds since the Unix epoch), author and source of the edit, the element by
+/// guid and current name (empty once removed), the parameter and its old and new value (empty
+/// where it was added or removed). With `element`, only that element's changes are written.
+pub fn write_parameter_log_csv(
+    model: &BimModel,
+    element: Option<Guid>,
+    mut writer: impl Write,
+) -> Result<()> {
+    writeln!(
+        writer,
+        "Revision,Modified,Author,Source,Element,Name,Parameter,Old,New"
+    )?;
+    for entry in model
+        .parameter_log()
+        .iter()
+        .filter(|entry| element.is_none_or(|guid| entry.element == guid))
+    {
+        let name = model
+            .element(entry.element)
+            .map_or("", |element| element.name.as_str());
+        let [old, new] = [&entry.old, &entry.new]
+            .map(|value| value.as_ref().map_or_else(String::new, ToString::to_string));
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{}",
+            entry.revision,
+            entry.modified,
+            csv_field(&entry.author),
+            entry.source.label(),
+            entry.element,
+            csv_field(name),
+            csv_field(&entry.name),
+            csv_field(&old),
+            csv_field(&new),
+        )?;
+    }
+    Ok(())
+}
+
+pub fn export_parameter_log_csv(
+    model: &BimModel,
+    element: Option<Guid>,
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_parameter_log_csv(model, element, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
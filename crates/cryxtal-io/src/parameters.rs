@@ -0,0 +1,361 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_base::Guid;
+use cryxtal_bim::{
+    BimElement, BimModel, ModelCommand, ParameterKind, ParameterValue, is_computed_parameter,
+    parameter_schema,
+};
+use serde_json::{Map, Value, json};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::schedule::csv_field;
+
+/// Columns before the parameters; they identify the element and are not read back.
+const CSV_COLUMNS: [&str; 3] = ["GUID", "Name", "Category"];
+
+/// Parameter edits read back from a spreadsheet or JSON file, to apply as one undo step.
+#[derive(Clone, Debug)]
+pub struct ParameterImport {
+    /// One [`ModelCommand::SetParameter`] per value that differs from the model.
+    pub command: ModelCommand,
+    pub changed: usize,
+    /// Guids in the file that name no element of the model; their rows are skipped.
+    pub unknown: Vec<Guid>,
+}
+
+/// Writes every element's parameters as CSV, one row per element and one column per parameter
+/// key used in the model, sorted; cells of parameters an element does not have are empty.
+/// Reference meshes are left out.
+pub fn write_parameters_csv(model: &BimModel, mut writer: impl Write) -> Result<()> {
+    let keys: BTreeSet<&str> = scheduled(model)
+        .flat_map(|element| element.parameters.keys().map(String::as_str))
+        .collect();
+    let header: Vec<String> = CSV_COLUMNS
+        .into_iter()
+        .chain(keys.iter().copied())
+        .map(csv_field)
+        .collect();
+    writeln!(writer, "{}", header.join(","))?;
+    for element in scheduled(model) {
+        let mut row = vec![
+            element.guid.to_string(),
+            csv_field(&element.name),
+            format!("{:?}", element.category),
+        ];
+        row.extend(keys.iter().map(|key| {
+            csv_field(
+                &element
+                    .parameters
+                    .get(*key)
+                    .map_or_else(String::new, ToString::to_string),
+            )
+        }));
+        writeln!(writer, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+pub fn export_parameters_csv(model: &BimModel, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_parameters_csv(model, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes every element's parameters as a JSON array of `{guid, name, category, parameters}`
+/// objects, with the values as plain JSON numbers, booleans and strings. Reference meshes are
+/// left out.
+pub fn write_parameters_json(model: &BimModel, writer: impl Write) -> Result<()> {
+    let elements: Vec<Value> = scheduled(model)
+        .map(|element| {
+            let parameters: Map<String, Value> = element
+                .parameters
+                .iter()
+                .map(|(key, value)| (key.clone(), json_value(value)))
+                .collect();
+            json!({
+                "guid": element.guid.to_string(),
+                "name": element.name,
+                "category": format!("{:?}", element.category),
+                "parameters": parameters,
+            })
+        })
+        .collect();
+    serde_json::to_writer_pretty(writer, &elements).context("serialize parameters")?;
+    Ok(())
+}
+
+pub fn export_parameters_json(model: &BimModel, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_parameters_json(model, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads parameter values edited in a spreadsheet exported by [`write_parameters_csv`] and
+/// matches its rows to elements by the `GUID` column. An empty cell removes the parameter;
+/// columns left out of the file leave their parameters alone. Values keep the type the
+/// parameter has on the element or in its category's schema; new parameters are read as
+/// integers, numbers, `true`/`false` or text, in that order. Parameters driven by a formula
+/// are skipped; changing a computed quantity such as `Volume` is an error. Geometry is not
+/// rebuilt here: the application regenerates the elements whose driving parameters changed.
+pub fn read_parameters_csv(model: &BimModel, mut reader: impl Read) -> Result<ParameterImport> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    // Spreadsheets often save with a byte order mark.
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+    let mut records = csv_records(text).into_iter();
+    let header = records.next().context("parameter CSV is empty")?;
+    let guid_column = header
+        .iter()
+        .position(|name| name.trim().eq_ignore_ascii_case("GUID"))
+        .context("parameter CSV has no GUID column")?;
+    let keys: Vec<(usize, &str)> = header
+        .iter()
+        .enumerate()
+        .map(|(column, name)| (column, name.trim()))
+        .filter(|(_, name)| {
+            !name.is_empty() && !CSV_COLUMNS.iter().any(|c| c.eq_ignore_ascii_case(name))
+        })
+        .collect();
+
+    let mut updates = Updates::default();
+    for (line, record) in records.enumerate() {
+        let row = line + 2;
+        if record.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        let cell = |column: usize| record.get(column).map_or("", |cell| cell.trim());
+        let guid: Guid = cell(guid_column)
+            .parse()
+            .with_context(|| format!("row {row}"))?;
+        let Some(element) = model.element(guid) else {
+            updates.unknown.push(guid);
+            continue;
+        };
+        for (column, key) in &keys {
+            let value = cell(*column);
+            let value = if value.is_empty() {
+                None
+            } else {
+                Some(
+                    parse_value(value, parameter_kind(element, key))
+                        .with_context(|| format!("row {row}, column {key}"))?,
+                )
+            };
+            updates
+                .set(element, key, value)
+                .with_context(|| format!("row {row}, column {key}"))?;
+        }
+    }
+    Ok(updates.finish())
+}
+
+/// Reads parameter values from JSON written by [`write_parameters_json`], matching objects to
+/// elements by `guid`. Only the parameters listed are changed, `null` removing one; values
+/// keep their parameter's type as in [`read_parameters_csv`], and new ones take the JSON type.
+pub fn read_parameters_json(model: &BimModel, reader: impl Read) -> Result<ParameterImport> {
+    let elements: Vec<Value> = serde_json::from_reader(reader).context("parse parameter JSON")?;
+    let mut updates = Updates::default();
+    for (index, object) in elements.iter().enumerate() {
+        let entry = index + 1;
+        let guid: Guid = object
+            .get("guid")
+            .and_then(Value::as_str)
+            .with_context(|| format!("entry {entry} has no guid"))?
+            .parse()
+            .with_context(|| format!("entry {entry}"))?;
+        let Some(element) = model.element(guid) else {
+            updates.unknown.push(guid);
+            continue;
+        };
+        let Some(parameters) = object.get("parameters") else {
+            continue;
+        };
+        let parameters = parameters
+            .as_object()
+            .with_context(|| format!("entry {entry}: parameters must be an object"))?;
+        for (key, value) in parameters {
+            let value = parameter_value(value, parameter_kind(element, key))
+                .and_then(|value| updates.set(element, key, value))
+                .with_context(|| format!("entry {entry}, parameter {key}"))?;
+        }
+    }
+    Ok(updates.finish())
+}
+
+/// Reads parameter edits from `path`: JSON for `.json` files, CSV otherwise.
+pub fn import_parameters(model: &BimModel, path: impl AsRef<Path>) -> Result<ParameterImport> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    if is_json {
+        read_parameters_json(model, file)
+    } else {
+        read_parameters_csv(model, file)
+    }
+    .with_context(|| format!("failed to read parameters from {}", path.display()))
+}
+
+#[derive(Default)]
+struct Updates {
+    commands: Vec<ModelCommand>,
+    unknown: Vec<Guid>,
+}
+
+impl Updates {
+    fn set(
+        &mut self,
+        element: &BimElement,
+        key: &str,
+        value: Option<ParameterValue>,
+    ) -> Result<()> {
+        if element.formulas.contains_key(key) || element.parameters.get(key) == value.as_ref() {
+            return Ok(());
+        }
+        if is_computed_parameter(key) {
+            bail!("{key} of {} is computed from its geometry", element.name);
+        }
+        if element.is_reference_mesh() {
+            bail!("{} is a reference mesh and cannot be edited", element.name);
+        }
+        self.commands.push(ModelCommand::SetParameter {
+            guid: element.guid,
+            name: key.to_string(),
+            value,
+        });
+        Ok(())
+    }
+
+    fn finish(self) -> ParameterImport {
+        ParameterImport {
+            changed: self.commands.len(),
+            command: ModelCommand::Batch(self.commands),
+            unknown: self.unknown,
+        }
+    }
+}
+
+/// Elements whose parameters are exported: all but reference meshes.
+fn scheduled(model: &BimModel) -> impl Iterator<Item = &BimElement> {
+    model
+        .elements()
+        .iter()
+        .filter(|element| !element.is_reference_mesh())
+}
+
+/// Type parameter `key` of `element` is read as: its current one, else its schema's.
+fn parameter_kind(element: &BimElement, key: &str) -> Option<ParameterKind> {
+    element
+        .parameters
+        .get(key)
+        .map(ParameterKind::of)
+        .or_else(|| {
+            parameter_schema(element.category)
+                .iter()
+                .find(|spec| spec.key == key)
+                .map(|spec| spec.kind)
+        })
+}
+
+fn parse_value(text: &str, kind: Option<ParameterKind>) -> Result<ParameterValue> {
+    let invalid = |kind: ParameterKind| format!("`{text}` is not a valid {}", kind.label());
+    Ok(match kind {
+        Some(ParameterKind::Integer) => ParameterValue::Integer(
+            text.parse()
+                .with_context(|| invalid(ParameterKind::Integer))?,
+        ),
+        Some(ParameterKind::Number) => ParameterValue::Number(
+            text.parse()
+                .with_context(|| invalid(ParameterKind::Number))?,
+        ),
+        Some(ParameterKind::Bool) => ParameterValue::Bool(
+            text.to_ascii_lowercase()
+                .parse()
+                .with_context(|| invalid(ParameterKind::Bool))?,
+        ),
+        Some(ParameterKind::Text) => ParameterValue::Text(text.to_string()),
+        None => {
+            if let Ok(value) = text.parse::<i64>() {
+                ParameterValue::Integer(value)
+            } else if let Ok(value) = text.parse::<f64>() {
+                ParameterValue::Number(value)
+            } else if let Ok(value) = text.to_ascii_lowercase().parse::<bool>() {
+                ParameterValue::Bool(value)
+            } else {
+                ParameterValue::Text(text.to_string())
+            }
+        }
+    })
+}
+
+fn parameter_value(value: &Value, kind: Option<ParameterKind>) -> Result<Option<ParameterValue>> {
+    let text = match value {
+        Value::Null => return Ok(None),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(number) => number.to_string(),
+        Value::String(text) => text.clone(),
+        _ => bail!("values must be numbers, booleans, strings or null"),
+    };
+    let kind = kind.or(match value {
+        Value::Bool(_) => Some(ParameterKind::Bool),
+        Value::Number(number) if number.is_i64() => Some(ParameterKind::Integer),
+        Value::Number(_) => Some(ParameterKind::Number),
+        _ => Some(ParameterKind::Text),
+    });
+    parse_value(&text, kind).map(Some)
+}
+
+fn json_value(value: &ParameterValue) -> Value {
+    match value {
+        ParameterValue::Integer(value) => json!(value),
+        ParameterValue::Number(value) => json!(value),
+        ParameterValue::Bool(value) => json!(value),
+        ParameterValue::Text(text) => json!(text),
+    }
+}
+
+/// Records of CSV text: fields split on commas, with quoted fields holding commas, doubled
+/// quotes and line breaks.
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
@@ -0,0 +1,220 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_bim::{BimCategory, BimModel, HatchPattern, LegendEntry, NamedView};
+use cryxtal_topology::{
+    Edge, Plane, Point3, Solid, Vector3, plane_section, sample_edge, solid_edges,
+};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::hatch::hatch_segments;
+use crate::mesh::TessellationOptions;
+
+/// Segments each edge is drawn with; straight edges come out exact.
+const PLAN_SEGMENTS: usize = 8;
+/// Longer side of the drawn model, in SVG pixels.
+const PLAN_SIZE: f64 = 800.0;
+const PLAN_MARGIN: f64 = 20.0;
+/// Height of a legend row, and the side of its color swatch.
+const LEGEND_ROW: f64 = 18.0;
+const LEGEND_SWATCH: f64 = 12.0;
+/// Spacing of hatch lines, in SVG pixels.
+const HATCH_SPACING: f64 = 6.0;
+
+/// Plan view (looking down Z) of the model's elements as SVG, each drawn in the color it has in
+/// `view` (its layer color with the view's filters applied) and, with a view, the view's
+/// legend below the plan. Each element's cut halfway up its height is hatched after
+/// [`BimModel::hatch_pattern`], so the plan reads in black and white; curved cuts follow
+/// `tessellation`. Openings are left out.
+pub fn write_plan_svg(
+    model: &BimModel,
+    view: Option<&NamedView>,
+    tessellation: &TessellationOptions,
+    mut writer: impl Write,
+) -> Result<()> {
+    let elements: Vec<([u8; 4], HatchPattern, &Solid, Vec<Edge>)> = model
+        .elements()
+        .iter()
+        .filter(|element| element.category != BimCategory::Opening)
+        .map(|element| {
+            (
+                model.display_color(element, view),
+                model.hatch_pattern(element),
+                &element.geometry,
+                solid_edges(&element.geometry),
+            )
+        })
+        .collect();
+    let (mut min, mut max) = ([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]);
+    for point in elements
+        .iter()
+        .flat_map(|(_, _, _, edges)| edges.iter())
+        .flat_map(|edge| sample_edge(edge, PLAN_SEGMENTS))
+    {
+        min = [min[0].min(point.x), min[1].min(point.y)];
+        max = [max[0].max(point.x), max[1].max(point.y)];
+    }
+    if min[0] > max[0] {
+        bail!("nothing to draw");
+    }
+    let extent = (max[0] - min[0]).max(max[1] - min[1]).max(1.0e-6);
+    let scale = PLAN_SIZE / extent;
+    let to_svg = |point: Point3| {
+        (
+            PLAN_MARGIN + (point.x - min[0]) * scale,
+            PLAN_MARGIN + (max[1] - point.y) * scale,
+        )
+    };
+    let legend = view.map(|view| model.legend(view)).unwrap_or_default();
+    let plan_height = (max[1] - min[1]) * scale + PLAN_MARGIN * 2.0;
+    let legend_height = if legend.is_empty() {
+        0.0
+    } else {
+        (legend.len() + 1) as f64 * LEGEND_ROW + PLAN_MARGIN
+    };
+    let width = (max[0] - min[0]) * scale + PLAN_MARGIN * 2.0;
+    let height = plan_height + legend_height;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0}" height="{height:.0}" viewBox="0 0 {width:.1} {height:.1}">"#
+    )?;
+    writeln!(svg, r#"<g fill="none" stroke-width="1">"#)?;
+    for (color, pattern, geometry, edges) in &elements {
+        let (stroke, opacity) = svg_color(*color);
+        let hatch = hatch_path(
+            geometry,
+            edges,
+            *pattern,
+            HATCH_SPACING / scale,
+            tessellation,
+            &to_svg,
+        );
+        if !hatch.is_empty() {
+            writeln!(
+                svg,
+                r#"<path d="{hatch}" stroke="{stroke}" stroke-opacity="{opacity:.2}" stroke-width="0.5"/>"#
+            )?;
+        }
+        for edge in edges {
+            let points: Vec<String> = sample_edge(edge, PLAN_SEGMENTS)
+                .into_iter()
+                .map(|point| {
+                    let (x, y) = to_svg(point);
+                    format!("{x:.1},{y:.1}")
+                })
+                .collect();
+            writeln!(
+                svg,
+                r#"<polyline points="{}" stroke="{stroke}" stroke-opacity="{opacity:.2}"/>"#,
+                points.join(" ")
+            )?;
+        }
+    }
+    writeln!(svg, "</g>")?;
+    if let Some(view) = view.filter(|_| !legend.is_empty()) {
+        write_legend(&mut svg, &view.name, &legend, PLAN_MARGIN, plan_height)?;
+    }
+    writeln!(svg, "</svg>")?;
+    writer.write_all(svg.as_bytes())?;
+    Ok(())
+}
+
+pub fn export_plan_svg(
+    model: &BimModel,
+    view: Option<&NamedView>,
+    tessellation: &TessellationOptions,
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_plan_svg(model, view, tessellation, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// SVG path data of the hatch lines filling the cut through `geometry` halfway between the
+/// lowest and highest points of its `edges`.
+fn hatch_path(
+    geometry: &Solid,
+    edges: &[Edge],
+    pattern: HatchPattern,
+    spacing: f64,
+    tessellation: &TessellationOptions,
+    to_svg: impl Fn(Point3) -> (f64, f64),
+) -> String {
+    if pattern == HatchPattern::None {
+        return String::new();
+    }
+    let (low, high) = edges
+        .iter()
+        .flat_map(|edge| sample_edge(edge, PLAN_SEGMENTS))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), point| {
+            (low.min(point.z), high.max(point.z))
+        });
+    if low > high {
+        return String::new();
+    }
+    let plane = Plane {
+        origin: Point3::new(0.0, 0.0, (low + high) * 0.5),
+        normal: Vector3::new(0.0, 0.0, 1.0),
+    };
+    let outlines = plane_section(geometry, &plane, tessellation.tolerance_for(geometry));
+    let mut path = String::new();
+    for [start, end] in hatch_segments(&outlines, pattern, spacing) {
+        let ((x1, y1), (x2, y2)) = (to_svg(start), to_svg(end));
+        let _ = write!(path, "M{x1:.1} {y1:.1}L{x2:.1} {y2:.1}");
+    }
+    path
+}
+
+/// Legend titled `title` with its top left corner at `(x, y)`.
+fn write_legend(
+    svg: &mut String,
+    title: &str,
+    entries: &[LegendEntry],
+    x: f64,
+    y: f64,
+) -> std::fmt::Result {
+    writeln!(
+        svg,
+        r#"<g font-family="sans-serif" font-size="11" stroke="none">"#
+    )?;
+    writeln!(
+        svg,
+        r#"<text x="{x:.1}" y="{:.1}" font-weight="bold">{}</text>"#,
+        y + LEGEND_SWATCH,
+        escape(title)
+    )?;
+    for (row, entry) in entries.iter().enumerate() {
+        let top = y + (row + 1) as f64 * LEGEND_ROW;
+        let (fill, opacity) = svg_color(entry.color);
+        writeln!(
+            svg,
+            r##"<rect x="{x:.1}" y="{top:.1}" width="{LEGEND_SWATCH}" height="{LEGEND_SWATCH}" fill="{fill}" fill-opacity="{opacity:.2}" stroke="#444" stroke-width="0.5"/>"##
+        )?;
+        writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}">{}</text>"#,
+            x + LEGEND_SWATCH + 6.0,
+            top + LEGEND_SWATCH - 2.0,
+            escape(&entry.label)
+        )?;
+    }
+    writeln!(svg, "</g>")
+}
+
+/// `#rrggbb` and opacity of an unmultiplied sRGBA color.
+fn svg_color([r, g, b, a]: [u8; 4]) -> (String, f64) {
+    (format!("#{r:02x}{g:02x}{b:02x}"), f64::from(a) / 255.0)
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
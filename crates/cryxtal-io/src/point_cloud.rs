@@ -0,0 +1,315 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_topology::Point3;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use crate::deviation::read_scan_points;
+
+/// Points of an as-built scan, with their colors where the file has them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PointCloud {
+    pub points: Vec<Point3>,
+    /// sRGB color of each point, in step with `points`; empty when the file has no colors.
+    pub colors: Vec<[u8; 3]>,
+}
+
+impl PointCloud {
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Thins the cloud to at most one point per cube of side `cell` on a grid from the origin,
+    /// keeping the first point read into each cube, so dense scans become light enough to draw
+    /// and compare. Returns how many points were dropped.
+    pub fn decimate(&mut self, cell: f64) -> Result<usize> {
+        if !cell.is_finite() || cell <= 0.0 {
+            bail!("decimation cell size must be > 0, got {cell}");
+        }
+        let before = self.points.len();
+        let mut occupied = HashSet::new();
+        let keep: Vec<bool> = self
+            .points
+            .iter()
+            .map(|point| {
+                let key = [point.x, point.y, point.z].map(|value| (value / cell).floor() as i64);
+                occupied.insert(key)
+            })
+            .collect();
+        let mut index = 0;
+        self.points.retain(|_| {
+            index += 1;
+            keep[index - 1]
+        });
+        if !self.colors.is_empty() {
+            let mut index = 0;
+            self.colors.retain(|_| {
+                index += 1;
+                keep[index - 1]
+            });
+        }
+        Ok(before - self.points.len())
+    }
+}
+
+/// Reads a point cloud from `path`: a `.ply` file (ASCII or binary, with vertex colors if it
+/// has them), otherwise text with the first three numbers of each line as X, Y and Z (`.xyz`,
+/// `.pts`, `.csv`; see [`read_scan_points`]). With `decimation`, the cloud is thinned to one
+/// point per cube of that size (see [`PointCloud::decimate`]).
+pub fn import_point_cloud(path: impl AsRef<Path>, decimation: Option<f64>) -> Result<PointCloud> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let is_ply = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ply"));
+    let mut cloud = if is_ply {
+        read_ply(BufReader::new(file))
+            .with_context(|| format!("failed to read PLY file {}", path.display()))?
+    } else {
+        PointCloud {
+            points: read_scan_points(BufReader::new(file))
+                .with_context(|| format!("failed to read point cloud {}", path.display()))?,
+            colors: Vec::new(),
+        }
+    };
+    if cloud.is_empty() {
+        bail!("{} holds no points", path.display());
+    }
+    if let Some(cell) = decimation {
+        cloud.decimate(cell)?;
+    }
+    Ok(cloud)
+}
+
+/// Vertices of a PLY file, in any of its three formats. Colors are taken from `red`, `green`
+/// and `blue` vertex properties when all three are present. Faces and other elements are
+/// skipped.
+pub fn read_ply(mut reader: impl BufRead) -> Result<PointCloud> {
+    if header_line(&mut reader)? != "ply" {
+        bail!("not a PLY file");
+    }
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    loop {
+        let line = header_line(&mut reader)?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["end_header"] => break,
+            ["format", kind, _] => {
+                format = Some(match *kind {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::Binary { big_endian: false },
+                    "binary_big_endian" => Format::Binary { big_endian: true },
+                    other => bail!("unknown PLY format `{other}`"),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .with_context(|| format!("invalid element count `{count}`"))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, name] => {
+                let property = Property::List(scalar(count)?, scalar(item)?, name.to_string());
+                elements
+                    .last_mut()
+                    .context("PLY property before any element")?
+                    .properties
+                    .push(property);
+            }
+            ["property", kind, name] => {
+                let property = Property::Scalar(scalar(kind)?, name.to_string());
+                elements
+                    .last_mut()
+                    .context("PLY property before any element")?
+                    .properties
+                    .push(property);
+            }
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => bail!("unexpected PLY header line `{line}`"),
+        }
+    }
+
+    match format.context("PLY header has no format")? {
+        Format::Ascii => {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            let mut tokens = text.split_ascii_whitespace();
+            read_elements(&elements, |_| {
+                let token = tokens.next().context("PLY data ends early")?;
+                token
+                    .parse::<f64>()
+                    .with_context(|| format!("invalid PLY value `{token}`"))
+            })
+        }
+        Format::Binary { big_endian } => read_elements(&elements, |scalar| {
+            let mut bytes = [0u8; 8];
+            let bytes = &mut bytes[..scalar.size()];
+            reader.read_exact(bytes).context("PLY data ends early")?;
+            Ok(scalar.decode(bytes, big_endian))
+        }),
+    }
+}
+
+fn header_line(reader: &mut impl BufRead) -> Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        bail!("PLY header ends early");
+    }
+    Ok(line.trim().to_string())
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Ascii,
+    Binary { big_endian: bool },
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+enum Property {
+    Scalar(Scalar, String),
+    /// Count type, item type and name.
+    List(Scalar, Scalar, String),
+}
+
+#[derive(Clone, Copy)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+fn scalar(name: &str) -> Result<Scalar> {
+    Ok(match name {
+        "char" | "int8" => Scalar::I8,
+        "uchar" | "uint8" => Scalar::U8,
+        "short" | "int16" => Scalar::I16,
+        "ushort" | "uint16" => Scalar::U16,
+        "int" | "int32" => Scalar::I32,
+        "uint" | "uint32" => Scalar::U32,
+        "float" | "float32" => Scalar::F32,
+        "double" | "float64" => Scalar::F64,
+        other => bail!("unknown PLY property type `{other}`"),
+    })
+}
+
+impl Scalar {
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    fn decode(self, bytes: &[u8], big_endian: bool) -> f64 {
+        let mut le = [0u8; 8];
+        le[..bytes.len()].copy_from_slice(bytes);
+        if big_endian {
+            le[..bytes.len()].reverse();
+        }
+        let [a, b, c, d, ..] = le;
+        match self {
+            Scalar::I8 => f64::from(a as i8),
+            Scalar::U8 => f64::from(a),
+            Scalar::I16 => f64::from(i16::from_le_bytes([a, b])),
+            Scalar::U16 => f64::from(u16::from_le_bytes([a, b])),
+            Scalar::I32 => f64::from(i32::from_le_bytes([a, b, c, d])),
+            Scalar::U32 => f64::from(u32::from_le_bytes([a, b, c, d])),
+            Scalar::F32 => f64::from(f32::from_le_bytes([a, b, c, d])),
+            Scalar::F64 => f64::from_le_bytes(le),
+        }
+    }
+}
+
+/// Reads the elements up to and including `vertex`, taking each value from `next`.
+fn read_elements(
+    elements: &[Element],
+    mut next: impl FnMut(Scalar) -> Result<f64>,
+) -> Result<PointCloud> {
+    for element in elements {
+        let position = |name: &str| {
+            element.properties.iter().position(
+                |property| matches!(property, Property::Scalar(_, candidate) if candidate == name),
+            )
+        };
+        let vertex = element.name == "vertex";
+        let (xyz, rgb) = if vertex {
+            let xyz = ["x", "y", "z"].map(position);
+            let [Some(x), Some(y), Some(z)] = xyz else {
+                bail!("PLY vertices have no x, y and z");
+            };
+            let rgb = match ["red", "green", "blue"].map(position) {
+                [Some(r), Some(g), Some(b)] => Some([r, g, b]),
+                _ => None,
+            };
+            (Some([x, y, z]), rgb)
+        } else {
+            (None, None)
+        };
+
+        let mut cloud = PointCloud::default();
+        // A count from the header is not trusted with the allocation up front.
+        cloud.points.reserve(element.count.min(1 << 20));
+        let mut values = Vec::with_capacity(element.properties.len());
+        for _ in 0..element.count {
+            values.clear();
+            for property in &element.properties {
+                match property {
+                    Property::Scalar(scalar, name) => {
+                        let value = next(*scalar)?;
+                        // Float colors run from 0 to 1, integer ones to 255.
+                        let is_float = matches!(scalar, Scalar::F32 | Scalar::F64);
+                        let is_color = matches!(name.as_str(), "red" | "green" | "blue");
+                        values.push(if is_float && is_color {
+                            value * 255.0
+                        } else {
+                            value
+                        });
+                    }
+                    Property::List(count, item, _) => {
+                        let count = next(*count)?;
+                        if count < 0.0 || count.fract() != 0.0 {
+                            bail!("invalid PLY list length {count}");
+                        }
+                        for _ in 0..count as usize {
+                            next(*item)?;
+                        }
+                        values.push(f64::NAN);
+                    }
+                }
+            }
+            if let Some([x, y, z]) = xyz {
+                cloud
+                    .points
+                    .push(Point3::new(values[x], values[y], values[z]));
+            }
+            if let Some(rgb) = rgb {
+                cloud
+                    .colors
+                    .push(rgb.map(|index| values[index].round().clamp(0.0, 255.0) as u8));
+            }
+        }
+        if vertex {
+            return Ok(cloud);
+        }
+    }
+    bail!("PLY file has no vertex element")
+}
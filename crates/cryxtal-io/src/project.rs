@@ -0,0 +1,189 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_bim::BimModel;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+use crate::brep::BrepSolid;
+
+/// Bumped whenever the serialized layout changes incompatibly; older files keep loading.
+/// Version 2 stores element geometry as [`BrepSolid`]; version 1 used the kernel's own layout.
+pub const PROJECT_FORMAT_VERSION: u32 = 2;
+pub const PROJECT_EXTENSION: &str = "cxp";
+pub const BINARY_PROJECT_EXTENSION: &str = "cxpb";
+
+const FORMAT_NAME: &str = "cryxtal-project";
+const BINARY_MAGIC: &[u8; 8] = b"CRYXPRJ\0";
+
+/// Viewer camera saved with the project so it reopens on the same view.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProjectCamera {
+    pub target: [f64; 3],
+    pub position: [f64; 3],
+    pub up: [f64; 3],
+    pub fov_deg: f64,
+}
+
+/// Everything written to a project file: the model (elements with geometry and parameters,
+/// layers, spatial structure) and the viewer camera. Geometry is written in the [`BrepSolid`]
+/// layout, so saved projects do not depend on the geometry kernel's serialization.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Project {
+    pub model: BimModel,
+    #[serde(default)]
+    pub camera: Option<ProjectCamera>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProjectFormat {
+    /// Pretty-printed JSON, diffable and hand-editable.
+    Json,
+    /// Magic header and version followed by a MessagePack payload.
+    Binary,
+}
+
+impl ProjectFormat {
+    /// `.cxpb` selects the binary encoding; any other extension is written as JSON.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case(BINARY_PROJECT_EXTENSION) => Self::Binary,
+            _ => Self::Json,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ProjectFileRef<'a> {
+    format: &'a str,
+    version: u32,
+    project: &'a Value,
+}
+
+#[derive(Deserialize)]
+struct ProjectHeader {
+    format: String,
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct ProjectFile<T> {
+    project: T,
+}
+
+/// Saves in the format implied by the file extension, see [`ProjectFormat::from_path`].
+pub fn save_project(project: &Project, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    save_project_as(project, path, ProjectFormat::from_path(path))
+}
+
+pub fn save_project_as(
+    project: &Project,
+    path: impl AsRef<Path>,
+    format: ProjectFormat,
+) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("create output directory {}", parent.display()))?;
+    }
+
+    let project = project_value(project)?;
+    let bytes = match format {
+        ProjectFormat::Json => serde_json::to_vec_pretty(&ProjectFileRef {
+            format: FORMAT_NAME,
+            version: PROJECT_FORMAT_VERSION,
+            project: &project,
+        })
+        .context("serialize project")?,
+        ProjectFormat::Binary => {
+            let mut bytes = BINARY_MAGIC.to_vec();
+            bytes.extend_from_slice(&PROJECT_FORMAT_VERSION.to_le_bytes());
+            bytes.extend(rmp_serde::to_vec_named(&project).context("serialize project")?);
+            bytes
+        }
+    };
+
+    // Write beside the target and rename, so a failed save never truncates the previous file.
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    std::fs::write(&temp, bytes)
+        .with_context(|| format!("write project file {}", path.display()))?;
+    std::fs::rename(&temp, path)
+        .with_context(|| format!("write project file {}", path.display()))?;
+    Ok(())
+}
+
+/// Loads either encoding; the format is detected from the file contents, not the extension.
+pub fn load_project(path: impl AsRef<Path>) -> Result<Project> {
+    let path = path.as_ref();
+    let bytes =
+        std::fs::read(path).with_context(|| format!("read project file {}", path.display()))?;
+    read_project(&bytes).with_context(|| format!("load project file {}", path.display()))
+}
+
+/// Decodes a project file already in memory, such as one fetched or dropped in the browser.
+pub fn read_project(bytes: &[u8]) -> Result<Project> {
+    if let Some(rest) = bytes.strip_prefix(BINARY_MAGIC) {
+        let Some((version, payload)) = rest.split_first_chunk::<4>() else {
+            bail!("truncated project file");
+        };
+        let version = u32::from_le_bytes(*version);
+        check_version(version)?;
+        if version == 1 {
+            return rmp_serde::from_slice(payload).context("decode project file");
+        }
+        let project = rmp_serde::from_slice(payload).context("decode project file")?;
+        return project_from_value(project);
+    }
+
+    let header: ProjectHeader = serde_json::from_slice(bytes).context("parse project file")?;
+    if header.format != FORMAT_NAME {
+        bail!("not a cryxtal project (format `{}`)", header.format);
+    }
+    check_version(header.version)?;
+    if header.version == 1 {
+        let file: ProjectFile<Project> =
+            serde_json::from_slice(bytes).context("decode project file")?;
+        return Ok(file.project);
+    }
+    let file: ProjectFile<Value> = serde_json::from_slice(bytes).context("decode project file")?;
+    project_from_value(file.project)
+}
+
+/// `project` as written to file, with each element's geometry swapped for its [`BrepSolid`].
+fn project_value(project: &Project) -> Result<Value> {
+    let mut value = serde_json::to_value(project).context("serialize project")?;
+    let stored = model_elements(&mut value)?;
+    for (element, stored) in project.model.elements().iter().zip(stored) {
+        stored["geometry"] = serde_json::to_value(BrepSolid::from_solid(&element.geometry))
+            .with_context(|| format!("serialize geometry of {}", element.name))?;
+    }
+    Ok(value)
+}
+
+/// Reverses [`project_value`], rebuilding each element's solid from its [`BrepSolid`].
+fn project_from_value(mut value: Value) -> Result<Project> {
+    for stored in model_elements(&mut value)? {
+        let brep: BrepSolid =
+            serde_json::from_value(stored["geometry"].take()).context("decode element geometry")?;
+        stored["geometry"] =
+            serde_json::to_value(brep.to_solid()?).context("decode element geometry")?;
+    }
+    serde_json::from_value(value).context("decode project file")
+}
+
+fn model_elements(value: &mut Value) -> Result<&mut Vec<Value>> {
+    value
+        .pointer_mut("/model/elements")
+        .and_then(Value::as_array_mut)
+        .context("project has no element list")
+}
+
+fn check_version(version: u32) -> Result<()> {
+    if version == 0 || version > PROJECT_FORMAT_VERSION {
+        bail!(
+            "unsupported project format version {version} (this build reads up to {PROJECT_FORMAT_VERSION})"
+        );
+    }
+    Ok(())
+}
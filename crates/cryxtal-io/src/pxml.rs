@@ -0,0 +1,266 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_bim::{BimCategory, BimElement, BimModel, ParameterValue};
+use cryxtal_topology::solid_bounds;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use truck_modeling::{InnerSpace, Point3, Vector3};
+
+use crate::bvbs::corner_points;
+use crate::plan::escape;
+use crate::schedule::set_bar_corners;
+
+/// PXML version written into `DocInfo`.
+const PXML_VERSION: &str = "1.3";
+
+/// Walls and slabs of `model` as precast panels in a subset of PXML, the XML format precast
+/// plants read. Each panel is a `Product` with its outline, thickness, openings as cutout
+/// outlines, hosted bars under `Steel` and other hosted elements as `MountPart`s, all in panel
+/// coordinates: X along the panel, Y up a wall or across a slab, Z through the thickness from
+/// the pallet. Rebar sets write each bar from their [`BAR_CORNERS_KEY`](crate::BAR_CORNERS_KEY)
+/// list; sets saved without it are left out until regenerated. Elements are read as
+/// millimetres.
+pub fn write_pxml(model: &BimModel, order: &str, mut writer: impl Write) -> Result<()> {
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(xml, "<PXML_Document>")?;
+    writeln!(xml, "  <DocInfo>")?;
+    writeln!(xml, "    <DocumentVersion>{PXML_VERSION}</DocumentVersion>")?;
+    writeln!(xml, "    <Comment>{}</Comment>", escape(&model.name))?;
+    writeln!(xml, "  </DocInfo>")?;
+    writeln!(xml, "  <Order>")?;
+    writeln!(xml, "    <OrderNo>{}</OrderNo>", escape(order))?;
+    for element in model.elements() {
+        if !matches!(element.category, BimCategory::Wall | BimCategory::Slab) {
+            continue;
+        }
+        write_panel(&mut xml, model, element)
+            .with_context(|| format!("{} cannot be exported as a panel", element.name))?;
+    }
+    writeln!(xml, "  </Order>")?;
+    writeln!(xml, "</PXML_Document>")?;
+    writer.write_all(xml.as_bytes())?;
+    Ok(())
+}
+
+pub fn export_pxml(model: &BimModel, order: &str, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_pxml(model, order, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Panel coordinates of a wall or slab: the outline lies in the XY plane at Z from 0 to the
+/// thickness.
+struct PanelFrame {
+    origin: Point3,
+    x: Vector3,
+    y: Vector3,
+    z: Vector3,
+    thickness: f64,
+    outline: Vec<[f64; 2]>,
+}
+
+impl PanelFrame {
+    fn of(element: &BimElement) -> Result<Self> {
+        let thickness = number(element, "Thickness")?;
+        if thickness <= 0.0 {
+            bail!("panel thickness must be > 0");
+        }
+        if element.category == BimCategory::Wall {
+            let start = point(element, "Start")?;
+            let end = point(element, "End")?;
+            let run = Vector3::new(end.x - start.x, end.y - start.y, 0.0);
+            if run.magnitude() <= 1.0e-6 {
+                bail!("wall length is too small");
+            }
+            let (length, height) = (run.magnitude(), number(element, "Height")?);
+            let x = run.normalize();
+            // Walls are centered on their axis; the left face (+Y in wall-local coordinates)
+            // is the top of the panel.
+            let z = Vector3::new(-x.y, x.x, 0.0);
+            Ok(Self {
+                origin: start - z * (thickness * 0.5),
+                x,
+                y: Vector3::unit_z(),
+                z,
+                thickness,
+                outline: vec![[0.0, 0.0], [length, 0.0], [length, height], [0.0, height]],
+            })
+        } else {
+            let corners = corner_points(element)?;
+            if corners.len() < 3 {
+                bail!("slab outline needs at least 3 points");
+            }
+            // The outline is the slab's top face; it hangs `thickness` below.
+            let first = corners[0];
+            let outline = corners
+                .iter()
+                .map(|corner| [corner.x - first.x, corner.y - first.y])
+                .collect();
+            Ok(Self {
+                origin: first - Vector3::unit_z() * thickness,
+                x: Vector3::unit_x(),
+                y: Vector3::unit_y(),
+                z: Vector3::unit_z(),
+                thickness,
+                outline,
+            })
+        }
+    }
+
+    fn local(&self, point: Point3) -> [f64; 3] {
+        let offset = point - self.origin;
+        [offset.dot(self.x), offset.dot(self.y), offset.dot(self.z)]
+    }
+}
+
+fn write_panel(xml: &mut String, model: &BimModel, element: &BimElement) -> Result<()> {
+    let frame = PanelFrame::of(element)?;
+    let product_type = if element.category == BimCategory::Wall {
+        "Wall"
+    } else {
+        "Slab"
+    };
+    writeln!(xml, "    <Product>")?;
+    writeln!(xml, "      <ProductType>{product_type}</ProductType>")?;
+    writeln!(
+        xml,
+        "      <ElementNo>{}</ElementNo>",
+        escape(&element.name)
+    )?;
+    writeln!(xml, "      <GlobalID>{}</GlobalID>", element.guid)?;
+    writeln!(
+        xml,
+        "      <TotalThickness>{:.1}</TotalThickness>",
+        frame.thickness
+    )?;
+    writeln!(xml, "      <Slab>")?;
+    writeln!(xml, "        <SlabNo>1</SlabNo>")?;
+    writeln!(xml, "        <Thickness>{:.1}</Thickness>", frame.thickness)?;
+    write_outline(xml, None, &frame.outline, frame.thickness)?;
+
+    // Openings are placed in the same wall-local or slab-plan coordinates as the outline.
+    for (index, opening) in model.hosted_openings(element.guid) {
+        let half_width = opening.profile.width * 0.5;
+        let half_height = opening.profile.height * 0.5;
+        let y = match element.category {
+            BimCategory::Wall => opening.placement.center_z,
+            _ => opening.placement.center_y,
+        };
+        let x = opening.placement.center_x;
+        let rectangle = [
+            [x - half_width, y - half_height],
+            [x + half_width, y - half_height],
+            [x + half_width, y + half_height],
+            [x - half_width, y + half_height],
+        ];
+        let name = &model.elements()[index].name;
+        write_outline(xml, Some(name), &rectangle, frame.thickness)?;
+    }
+
+    let hosted: Vec<&BimElement> = model
+        .elements()
+        .iter()
+        .filter(|other| other.host == Some(element.guid))
+        .collect();
+    let mut bars: Vec<(f64, Vec<Point3>)> = Vec::new();
+    for rebar in hosted
+        .iter()
+        .filter(|other| other.category == BimCategory::Rebar)
+    {
+        let diameter = number(rebar, "Diameter")?;
+        if !rebar.parameters.contains_key("BarCount") {
+            bars.push((diameter, corner_points(rebar)?));
+        } else if let Some(set) = set_bar_corners(rebar) {
+            bars.extend(set.into_iter().map(|corners| (diameter, corners)));
+        }
+    }
+    if !bars.is_empty() {
+        writeln!(xml, "        <Steel>")?;
+        for (diameter, points) in bars {
+            writeln!(xml, "          <Bar>")?;
+            writeln!(xml, "            <Diameter>{diameter:.1}</Diameter>")?;
+            writeln!(xml, "            <PieceCount>1</PieceCount>")?;
+            writeln!(xml, "            <Segment>")?;
+            for point in points {
+                let [x, y, z] = frame.local(point);
+                writeln!(
+                    xml,
+                    "              <SVertex><X>{x:.1}</X><Y>{y:.1}</Y><Z>{z:.1}</Z></SVertex>"
+                )?;
+            }
+            writeln!(xml, "            </Segment>")?;
+            writeln!(xml, "          </Bar>")?;
+        }
+        writeln!(xml, "        </Steel>")?;
+    }
+
+    for part in hosted
+        .iter()
+        .filter(|other| !matches!(other.category, BimCategory::Rebar | BimCategory::Opening))
+    {
+        let Some((min, max)) = solid_bounds(&part.geometry) else {
+            continue;
+        };
+        let [x, y, z] = frame.local(min + (max - min) * 0.5);
+        writeln!(xml, "        <MountPart>")?;
+        writeln!(xml, "          <Name>{}</Name>", escape(&part.name))?;
+        writeln!(
+            xml,
+            "          <MountPartType>{:?}</MountPartType>",
+            part.category
+        )?;
+        writeln!(xml, "          <X>{x:.1}</X><Y>{y:.1}</Y><Z>{z:.1}</Z>")?;
+        writeln!(xml, "        </MountPart>")?;
+    }
+    writeln!(xml, "      </Slab>")?;
+    writeln!(xml, "    </Product>")?;
+    Ok(())
+}
+
+/// Outline of `vertices` extruded through `height`; outlines named after an opening are cut out
+/// of the panel.
+fn write_outline(
+    xml: &mut String,
+    opening: Option<&str>,
+    vertices: &[[f64; 2]],
+    height: f64,
+) -> std::fmt::Result {
+    writeln!(xml, "        <Outline>")?;
+    if let Some(name) = opening {
+        writeln!(xml, "          <Name>{}</Name>", escape(name))?;
+    }
+    writeln!(xml, "          <Height>{height:.1}</Height>")?;
+    writeln!(xml, "          <Shape>")?;
+    if opening.is_some() {
+        writeln!(xml, "            <Cutout>true</Cutout>")?;
+    }
+    for [x, y] in vertices {
+        writeln!(
+            xml,
+            "            <SVertex><X>{x:.1}</X><Y>{y:.1}</Y></SVertex>"
+        )?;
+    }
+    writeln!(xml, "          </Shape>")?;
+    writeln!(xml, "        </Outline>")
+}
+
+fn point(element: &BimElement, prefix: &str) -> Result<Point3> {
+    Ok(Point3::new(
+        number(element, &format!("{prefix}X"))?,
+        number(element, &format!("{prefix}Y"))?,
+        number(element, &format!("{prefix}Z"))?,
+    ))
+}
+
+fn number(element: &BimElement, key: &str) -> Result<f64> {
+    match element.parameters.get(key) {
+        Some(ParameterValue::Number(value)) => Ok(*value),
+        _ => bail!("missing or invalid parameter: {key}"),
+    }
+}
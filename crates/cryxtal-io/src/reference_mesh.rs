@@ -0,0 +1,86 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_base::Guid;
+use cryxtal_bim::{
+    BimCategory, BimElement, ParameterSet, ParameterValue, REFERENCE_MESH_PARAMETER,
+};
+use cryxtal_topology::{Edge, Face, Shell, Solid, Vertex, Wire};
+use std::collections::HashMap;
+use truck_modeling::{InnerSpace, builder};
+use truck_polymesh::PolygonMesh;
+
+/// Most triangles [`reference_mesh_element`] turns into faces; denser meshes need decimating
+/// first.
+pub const MAX_REFERENCE_TRIANGLES: usize = 200_000;
+
+/// Wraps an imported mesh, such as a surveyor's terrain or as-built mesh, as a generic element
+/// for modeling context: each triangle becomes a planar face, with neighbouring triangles
+/// sharing edges, and polygons are split into triangles. The surface need not be closed, so the
+/// solid is only a container for the faces. The element is marked with
+/// [`REFERENCE_MESH_PARAMETER`] so tools can tell it from modeled elements; see
+/// [`BimElement::is_reference_mesh`].
+pub fn reference_mesh_element(name: impl Into<String>, mesh: &PolygonMesh) -> Result<BimElement> {
+    let positions = mesh.positions();
+    let mut triangles = Vec::new();
+    for polygon in mesh.face_iter() {
+        for pair in polygon[1..].windows(2) {
+            let triangle = [polygon[0].pos, pair[0].pos, pair[1].pos];
+            let [a, b, c] = triangle.map(|index| positions[index]);
+            // Slivers have no plane to attach.
+            if (b - a).cross(c - a).magnitude() > 1.0e-9 * (b - a).magnitude2().max(1.0) {
+                triangles.push(triangle);
+            }
+        }
+    }
+    if triangles.is_empty() {
+        bail!("mesh has no faces");
+    }
+    if triangles.len() > MAX_REFERENCE_TRIANGLES {
+        bail!(
+            "mesh has {} triangles, more than the {MAX_REFERENCE_TRIANGLES} a reference mesh \
+             may have; decimate it first",
+            triangles.len()
+        );
+    }
+
+    let mut vertices: HashMap<usize, Vertex> = HashMap::new();
+    let mut edges: HashMap<(usize, usize), Edge> = HashMap::new();
+    let mut faces = Vec::with_capacity(triangles.len());
+    for triangle in triangles {
+        let mut wire = Wire::new();
+        for (from, to) in [(0, 1), (1, 2), (2, 0)] {
+            let (from, to) = (triangle[from], triangle[to]);
+            let key = (from.min(to), from.max(to));
+            let edge = edges.entry(key).or_insert_with(|| {
+                let mut vertex = |index: usize| {
+                    vertices
+                        .entry(index)
+                        .or_insert_with(|| builder::vertex(positions[index]))
+                        .clone()
+                };
+                builder::line(&vertex(key.0), &vertex(key.1))
+            });
+            wire.push_back(if from < to {
+                edge.clone()
+            } else {
+                edge.inverse()
+            });
+        }
+        let face: Face =
+            builder::try_attach_plane(&[wire]).context("attach a plane to a mesh triangle")?;
+        faces.push(face);
+    }
+    let solid = Solid::new_unchecked(vec![Shell::from(faces)]);
+
+    let mut parameters = ParameterSet::new();
+    parameters.insert(
+        REFERENCE_MESH_PARAMETER.to_string(),
+        ParameterValue::Bool(true),
+    );
+    Ok(BimElement::new(
+        Guid::new(),
+        name,
+        BimCategory::Generic,
+        parameters,
+        solid,
+    ))
+}
@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use cryxtal_bim::{BimCategory, BimElement, ParameterValue};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use truck_modeling::Point3;
+
+/// Density of reinforcing steel in kg/m³, for diameters missing from [`NOMINAL_MASS`].
+pub const STEEL_DENSITY: f64 = 7850.0;
+
+/// Parameter of a rebar set listing the cut length of each of its bars in millimetres,
+/// separated by spaces; bars clipped to the outline of their host differ in length.
+pub const CUT_LENGTHS_KEY: &str = "CutLengths";
+
+/// Parameter of a rebar set listing the center line corners of each of its bars in
+/// millimetres: bars separated by `;`, corners by spaces and coordinates by commas.
+pub const BAR_CORNERS_KEY: &str = "BarCorners";
+
+/// Nominal mass per metre (kg/m) of standard bar diameters (mm).
+const NOMINAL_MASS: [(f64, f64); 13] = [
+    (6.0, 0.222),
+    (8.0, 0.395),
+    (10.0, 0.617),
+    (12.0, 0.888),
+    (14.0, 1.21),
+    (16.0, 1.58),
+    (20.0, 2.47),
+    (25.0, 3.85),
+    (28.0, 4.83),
+    (32.0, 6.31),
+    (36.0, 7.99),
+    (40.0, 9.86),
+    (50.0, 15.4),
+];
+
+/// One line of a bar bending schedule: bars of the same shape, diameter and cut length.
+/// Lengths are in millimetres, weights in kilograms.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BbsRow {
+    /// Bar mark, numbering the rows from 1.
+    pub mark: usize,
+    pub shape: String,
+    pub diameter: f64,
+    /// Cut length of one bar, bends included.
+    pub cut_length: f64,
+    pub count: usize,
+    pub total_length: f64,
+    pub weight: f64,
+}
+
+/// Mass per metre of a bar of `diameter`: the nominal table value for standard sizes,
+/// otherwise the section area at [`STEEL_DENSITY`].
+pub fn bar_mass_per_metre(diameter: f64) -> f64 {
+    NOMINAL_MASS
+        .iter()
+        .find(|(size, _)| (size - diameter).abs() < 1.0e-3)
+        .map(|(_, mass)| *mass)
+        .unwrap_or_else(|| {
+            let area = std::f64::consts::PI * diameter * diameter * 0.25 * 1.0e-6;
+            area * STEEL_DENSITY
+        })
+}
+
+/// Inside bend radius for a bar of `diameter`: half the minimum mandrel diameter of
+/// EN 1992-1-1, 4Ø up to 16 mm bars and 7Ø above.
+pub fn bend_radius(diameter: f64) -> f64 {
+    if diameter <= 16.0 {
+        diameter * 2.0
+    } else {
+        diameter * 3.5
+    }
+}
+
+/// Straight extension after a 135° stirrup hook: 5Ø, at least 50 mm.
+pub fn hook_extension(diameter: f64) -> f64 {
+    (diameter * 5.0).max(50.0)
+}
+
+/// Aggregates the rebar elements among `elements` into schedule rows, sorted by shape,
+/// diameter and cut length. Rebar sets count `BarCount` bars, each at its own length from
+/// [`CUT_LENGTHS_KEY`] (at the mean length for sets saved without the list); single bars are
+/// `Straight` or `Polyline`. Other elements
+/// and rebar missing `Diameter` or `Length` are skipped. Elements are read as millimetres;
+/// convert models in other units with [`BimModel::converted`](cryxtal_bim::BimModel::converted).
+pub fn rebar_schedule(elements: &[BimElement]) -> Vec<BbsRow> {
+    // Keyed by tenths of a millimetre of diameter and whole millimetres of cut length.
+    let mut groups: BTreeMap<(String, i64, i64), (usize, f64)> = BTreeMap::new();
+    for element in elements {
+        if element.category != BimCategory::Rebar {
+            continue;
+        }
+        let (Some(diameter), Some(length)) =
+            (number(element, "Diameter"), number(element, "Length"))
+        else {
+            continue;
+        };
+        let count = match element.parameters.get("BarCount") {
+            Some(ParameterValue::Integer(count)) if *count > 0 => *count as usize,
+            _ => 1,
+        };
+        let shape = match element.parameters.get("Shape") {
+            Some(ParameterValue::Text(shape)) => shape.clone(),
+            _ => match element.parameters.get("PointCount") {
+                Some(ParameterValue::Integer(points)) if *points > 2 => "Polyline".to_string(),
+                _ => "Straight".to_string(),
+            },
+        };
+        for cut_length in set_cut_lengths(element, count, length) {
+            let key = (
+                shape.clone(),
+                (diameter * 10.0).round() as i64,
+                cut_length.round() as i64,
+            );
+            let entry = groups.entry(key).or_insert((0, 0.0));
+            entry.0 += 1;
+            entry.1 += cut_length;
+        }
+    }
+
+    groups
+        .into_iter()
+        .enumerate()
+        .map(|(idx, ((shape, diameter, _), (count, total_length)))| {
+            let diameter = diameter as f64 / 10.0;
+            BbsRow {
+                mark: idx + 1,
+                shape,
+                diameter,
+                cut_length: total_length / count as f64,
+                count,
+                total_length,
+                weight: total_length * 1.0e-3 * bar_mass_per_metre(diameter),
+            }
+        })
+        .collect()
+}
+
+/// Cut lengths of the `count` bars of rebar set `element`, `length` long in total: its
+/// [`CUT_LENGTHS_KEY`] list, else all at the mean length, as for sets saved without the list.
+pub(crate) fn set_cut_lengths(element: &BimElement, count: usize, length: f64) -> Vec<f64> {
+    if let Some(ParameterValue::Text(list)) = element.parameters.get(CUT_LENGTHS_KEY) {
+        let lengths: Option<Vec<f64>> = list
+            .split_whitespace()
+            .map(|value| value.parse().ok())
+            .collect();
+        if let Some(lengths) = lengths
+            && lengths.len() == count
+        {
+            return lengths;
+        }
+    }
+    vec![length / count as f64; count]
+}
+
+/// Center line corners of each bar of rebar set `element`, from its [`BAR_CORNERS_KEY`] list;
+/// `None` for sets saved without the list or with a malformed one.
+pub(crate) fn set_bar_corners(element: &BimElement) -> Option<Vec<Vec<Point3>>> {
+    let Some(ParameterValue::Text(list)) = element.parameters.get(BAR_CORNERS_KEY) else {
+        return None;
+    };
+    list.split(';')
+        .map(|bar| {
+            bar.split_whitespace()
+                .map(|corner| {
+                    let coordinates: Vec<f64> = corner
+                        .split(',')
+                        .map(|value| value.parse().ok())
+                        .collect::<Option<_>>()?;
+                    match coordinates[..] {
+                        [x, y, z] => Some(Point3::new(x, y, z)),
+                        _ => None,
+                    }
+                })
+                .collect::<Option<Vec<_>>>()
+                .filter(|corners| corners.len() >= 2)
+        })
+        .collect()
+}
+
+/// Writes `rows` as CSV with a header line.
+pub fn write_rebar_schedule_csv(rows: &[BbsRow], mut writer: impl Write) -> Result<()> {
+    writeln!(
+        writer,
+        "Mark,Shape,Diameter,CutLength,Count,TotalLength,Weight"
+    )?;
+    for row in rows {
+        writeln!(
+            writer,
+            "{},{},{:.1},{:.0},{},{:.0},{:.2}",
+            row.mark,
+            csv_field(&row.shape),
+            row.diameter,
+            row.cut_length,
+            row.count,
+            row.total_length,
+            row.weight
+        )?;
+    }
+    Ok(())
+}
+
+pub fn export_rebar_schedule_csv(rows: &[BbsRow], path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_rebar_schedule_csv(rows, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn number(element: &BimElement, key: &str) -> Option<f64> {
+    match element.parameters.get(key) {
+        Some(ParameterValue::Number(value)) if *value > 0.0 => Some(*value),
+        _ => None,
+    }
+}
+
+pub(crate) fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
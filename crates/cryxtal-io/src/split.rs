@@ -0,0 +1,128 @@
+use anyhow::{Result, bail};
+use cryxtal_bim::{BimElement, BimModel, ParameterValue};
+use std::str::FromStr;
+
+/// What a split export writes one file per.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitBy {
+    /// Storey, lowest first.
+    Level,
+    Layer,
+    Category,
+}
+
+impl FromStr for SplitBy {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        match text.to_ascii_lowercase().as_str() {
+            "level" | "storey" => Ok(SplitBy::Level),
+            "layer" => Ok(SplitBy::Layer),
+            "category" => Ok(SplitBy::Category),
+            _ => bail!("unknown split `{text}` (expected level, layer or category)"),
+        }
+    }
+}
+
+/// A named share of a split export.
+#[derive(Clone, Debug)]
+pub struct ExportPart<'a> {
+    pub name: String,
+    /// File stem for the part, unique among the parts and safe on every platform.
+    pub file_stem: String,
+    pub elements: Vec<&'a BimElement>,
+}
+
+/// `elements` of `model` grouped by level, layer or category. Parts without elements are left
+/// out; levels come lowest first, layers and categories in the order they first appear.
+/// Elements on no level go to an `Unassigned` part.
+pub fn split_elements<'a>(
+    model: &BimModel,
+    elements: impl IntoIterator<Item = &'a BimElement>,
+    by: SplitBy,
+) -> Vec<ExportPart<'a>> {
+    // Keyed by storey guid for levels, so same-named levels of two buildings stay apart.
+    let mut parts: Vec<(String, Option<f64>, String, Vec<&'a BimElement>)> = Vec::new();
+    for element in elements {
+        let (key, elevation, name) = match by {
+            SplitBy::Level => match model.storey_of(element.guid) {
+                Some(storey) => (
+                    storey.guid.to_string(),
+                    Some(storey.elevation),
+                    storey.name.clone(),
+                ),
+                None => (String::new(), None, "Unassigned".to_string()),
+            },
+            SplitBy::Layer => {
+                let name = layer_name(model, element);
+                (name.clone(), None, name)
+            }
+            SplitBy::Category => {
+                let name = format!("{:?}", element.category);
+                (name.clone(), None, name)
+            }
+        };
+        match parts.iter_mut().find(|(other, ..)| *other == key) {
+            Some((.., members)) => members.push(element),
+            None => parts.push((key, elevation, name, vec![element])),
+        }
+    }
+    if by == SplitBy::Level {
+        // Unassigned elements go last.
+        parts.sort_by(|(_, a, ..), (_, b, ..)| match (a, b) {
+            (Some(a), Some(b)) => a.total_cmp(b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+    }
+
+    let mut stems: Vec<String> = Vec::with_capacity(parts.len());
+    parts
+        .into_iter()
+        .map(|(_, _, name, elements)| {
+            let base = file_stem(&name);
+            let mut stem = base.clone();
+            let mut suffix = 2;
+            while stems.contains(&stem) {
+                stem = format!("{base}-{suffix}");
+                suffix += 1;
+            }
+            stems.push(stem.clone());
+            ExportPart {
+                name,
+                file_stem: stem,
+                elements,
+            }
+        })
+        .collect()
+}
+
+/// The element's `Layer`, or the model's first layer when it has none.
+fn layer_name(model: &BimModel, element: &BimElement) -> String {
+    match element.parameters.get("Layer") {
+        Some(ParameterValue::Text(name)) => name.clone(),
+        _ => model
+            .layers
+            .first()
+            .map_or_else(|| "Default".to_string(), |layer| layer.name.clone()),
+    }
+}
+
+/// `name` with anything but letters, digits, `-` and `_` replaced by `_`.
+fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if stem.is_empty() {
+        "part".to_string()
+    } else {
+        stem
+    }
+}
@@ -1,34 +1,844 @@
-use anyhow::{Context, Result};
-use cryxtal_topology::Solid;
+use anyhow::{Context, Result, anyhow, bail};
+use cryxtal_bim::{BimCategory, BimElement, BimModel, ParameterValue};
+use cryxtal_topology::{Curve, ShapeTags, Shell, Solid, Surface, heal_shells};
+use std::collections::{BTreeSet, HashMap};
+use std::f64::consts::FRAC_PI_2;
+use std::fmt;
+use std::ops::Range;
 use std::path::Path;
-use truck_stepio::out;
+use truck_modeling::{
+    BSplineCurve, BSplineSurface, BoundedCurve, EuclideanSpace, InnerSpace, KnotVec, Matrix4,
+    NurbsCurve, NurbsSurface, ParametricCurve, Plane, Point3, Processor, RevolutedCurve, Vector3,
+    Vector4,
+};
+use truck_stepio::{r#in, out};
+use truck_topology::compress::{CompressedEdge, CompressedFace, CompressedShell};
+
+use crate::project::Project;
+
+/// Tolerance [`import_step`] heals shells with, in model units: the gaps STEP writers leave
+/// between faces, well below any real feature.
+pub const STEP_IMPORT_TOLERANCE: f64 = 1.0e-3;
+
+/// Ids of the contexts [`StepAssembly`] writes first, shared by its assembly products.
+const APPLICATION_CONTEXT: usize = 1;
+const PRODUCT_CONTEXT: usize = 3;
+const PRODUCT_DEFINITION_CONTEXT: usize = 4;
 
 pub fn export_step(solid: &Solid, path: impl AsRef<Path>) -> Result<()> {
+    export_step_tagged(solid, &ShapeTags::new(), path)
+}
+
+/// Exports `solid` with each tagged face's tags as the name of its `ADVANCED_FACE` and each
+/// tagged edge's as the name of its `EDGE_CURVE`, joined by `"; "`.
+pub fn export_step_tagged(solid: &Solid, tags: &ShapeTags, path: impl AsRef<Path>) -> Result<()> {
     let path = path.as_ref();
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)
             .with_context(|| format!("create output directory {}", parent.display()))?;
     }
 
-    let compressed = solid.compress();
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("model.step");
+    let step_string = tagged_step_text(solid, tags, file_name)?;
+
+    std::fs::write(path, step_string)
+        .with_context(|| format!("write STEP file {}", path.display()))?;
+    Ok(())
+}
+
+/// STEP file text of `solid`, its tagged faces and edges named as in [`export_step_tagged`].
+fn tagged_step_text(solid: &Solid, tags: &ShapeTags, file_name: &str) -> Result<String> {
     let header = out::StepHeaderDescriptor {
-        file_name: path
-            .file_name()
-            .and_then(|name| name.to_str())
-            .unwrap_or("model.step")
-            .to_string(),
+        file_name: file_name.to_string(),
         organization_system: "cryxtal-castor".to_string(),
         ..Default::default()
     };
+    let display = out::CompleteStepDisplay::new(out::StepModel::from(&solid.compress()), header);
+    let mut writer = NamingWriter {
+        text: String::new(),
+        line_start: 0,
+        faces: EntityNames::new("ADVANCED_FACE", tags.tagged_faces()),
+        edges: EntityNames::new("EDGE_CURVE", tags.tagged_edges()),
+    };
+    fmt::write(&mut writer, format_args!("{display}")).context("format STEP file")?;
+    writer.finish()
+}
 
-    let step_string =
-        out::CompleteStepDisplay::new(out::StepModel::from(&compressed), header).to_string();
+/// Exports every element of `project` but openings, which are voids, as one STEP assembly. Each
+/// element is a part named after it, its tagged faces and edges named as in
+/// [`export_step_tagged`], under a product tree following the spatial structure: model, site,
+/// buildings, storeys. Elements on no storey sit directly under the model. Parts keep their model
+/// coordinates, so every occurrence is placed by the identity. Each part's solid is colored with
+/// its layer color and assigned to a presentation layer of the same name (AP214 styling), so CAD
+/// tools show the model organized as in the viewer.
+pub fn export_step_model(project: &Project, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("create output directory {}", parent.display()))?;
+    }
+
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("model.step");
+    let model = &project.model;
+    let mut assembly = StepAssembly::new(file_name);
+    let root = assembly.add_node(&model.name);
+    let site = assembly.add_node(&model.site.name);
+    assembly.link(root, site, &model.site.name);
+    for building in &model.site.buildings {
+        let node = assembly.add_node(&building.name);
+        assembly.link(site, node, &building.name);
+        for storey in &building.storeys {
+            let storey_node = assembly.add_node(&storey.name);
+            assembly.link(node, storey_node, &storey.name);
+            for element in model.elements_on_storey(storey.guid) {
+                assembly.add_element(model, storey_node, element)?;
+            }
+        }
+    }
+    for element in model.elements() {
+        if model.storey_of(element.guid).is_none() {
+            assembly.add_element(model, root, element)?;
+        }
+    }
 
+    let step_string = assembly.finish()?;
     std::fs::write(path, step_string)
         .with_context(|| format!("write STEP file {}", path.display()))?;
     Ok(())
 }
 
-pub fn import_step(_path: impl AsRef<Path>) -> Result<Solid> {
-    Err(cryxtal_base::Error::NotImplemented("STEP import is not implemented").into())
+/// STEP data section built up from one exported part per element, renumbered to follow each
+/// other, and the assembly products and links between them.
+struct StepAssembly {
+    file_name: String,
+    /// Header of the first part's file, reused for the whole assembly.
+    header: Option<String>,
+    data: String,
+    next_id: usize,
+    /// Presentation style of each color written so far.
+    styles: HashMap<[u8; 3], usize>,
+    /// Solids on each layer, in the order layers are first used.
+    layers: Vec<(String, Vec<usize>)>,
+    /// Product definitions of the products without shape of their own, given an empty shape
+    /// representation once a geometric context is known.
+    nodes: Vec<usize>,
+    /// Shape representation of each product definition.
+    shapes: HashMap<usize, usize>,
+    /// Parent and child product definitions of each assembly link, with its name.
+    links: Vec<(usize, usize, String)>,
+    /// Geometric context of the first part, shared by the empty node representations.
+    context: Option<usize>,
+}
+
+/// What [`StepAssembly::add_part`] needs to link and style a part.
+struct StepPart {
+    definition: usize,
+    /// Solid representation items of the part.
+    items: Vec<usize>,
+    /// Geometric context of the part's shape representation.
+    context: Option<usize>,
+}
+
+impl StepAssembly {
+    fn new(file_name: &str) -> Self {
+        let mut assembly = Self {
+            file_name: file_name.to_string(),
+            header: None,
+            data: String::new(),
+            next_id: PRODUCT_DEFINITION_CONTEXT + 1,
+            styles: HashMap::new(),
+            layers: Vec::new(),
+            nodes: Vec::new(),
+            shapes: HashMap::new(),
+            links: Vec::new(),
+            context: None,
+        };
+        assembly.push(format!(
+            "#{APPLICATION_CONTEXT} = APPLICATION_CONTEXT('core data for automotive mechanical \
+             design processes')"
+        ));
+        assembly.push(format!(
+            "#2 = APPLICATION_PROTOCOL_DEFINITION('international standard', 'automotive_design', \
+             2000, #{APPLICATION_CONTEXT})"
+        ));
+        assembly.push(format!(
+            "#{PRODUCT_CONTEXT} = PRODUCT_CONTEXT('', #{APPLICATION_CONTEXT}, 'mechanical')"
+        ));
+        assembly.push(format!(
+            "#{PRODUCT_DEFINITION_CONTEXT} = PRODUCT_DEFINITION_CONTEXT('part definition', \
+             #{APPLICATION_CONTEXT}, 'design')"
+        ));
+        assembly
+    }
+
+    fn push(&mut self, statement: String) {
+        self.data.push_str(&statement);
+        self.data.push_str(";\n");
+    }
+
+    fn take_id(&mut self) -> usize {
+        self.next_id += 1;
+        self.next_id - 1
+    }
+
+    /// Adds a product without shape of its own, returning its product definition.
+    fn add_node(&mut self, name: &str) -> usize {
+        let name = step_string_literal(name);
+        let product = self.take_id();
+        let formation = self.take_id();
+        let definition = self.take_id();
+        self.push(format!(
+            "#{product} = PRODUCT({name}, {name}, '', (#{PRODUCT_CONTEXT}))"
+        ));
+        self.push(format!(
+            "#{formation} = PRODUCT_DEFINITION_FORMATION('', '', #{product})"
+        ));
+        self.push(format!(
+            "#{definition} = PRODUCT_DEFINITION('design', '', #{formation}, \
+             #{PRODUCT_DEFINITION_CONTEXT})"
+        ));
+        self.nodes.push(definition);
+        definition
+    }
+
+    /// Makes `child` a component of `parent`; the occurrence is written by
+    /// [`StepAssembly::finish`], once the shape representations of both are known.
+    fn link(&mut self, parent: usize, child: usize, name: &str) {
+        self.links.push((parent, child, name.to_string()));
+    }
+
+    /// Writes the occurrence of `child` in `parent` with the shape link CAD tools read the
+    /// assembly structure from: a `CONTEXT_DEPENDENT_SHAPE_REPRESENTATION` relating the two
+    /// shape representations by an identity transformation, both axes being `placement`.
+    fn write_link(&mut self, parent: usize, child: usize, name: &str, placement: usize) {
+        let occurrence = self.take_id();
+        let name = step_string_literal(name);
+        self.push(format!(
+            "#{occurrence} = NEXT_ASSEMBLY_USAGE_OCCURRENCE('{occurrence}', {name}, '', \
+             #{parent}, #{child}, $)"
+        ));
+        let (Some(parent_shape), Some(child_shape)) = (
+            self.shapes.get(&parent).copied(),
+            self.shapes.get(&child).copied(),
+        ) else {
+            return;
+        };
+        let shape = self.take_id();
+        self.push(format!(
+            "#{shape} = PRODUCT_DEFINITION_SHAPE('', '', #{occurrence})"
+        ));
+        let transformation = self.take_id();
+        self.push(format!(
+            "#{transformation} = ITEM_DEFINED_TRANSFORMATION('', '', #{placement}, #{placement})"
+        ));
+        let relationship = self.take_id();
+        self.push(format!(
+            "#{relationship} = ( REPRESENTATION_RELATIONSHIP('', '', #{child_shape}, \
+             #{parent_shape}) REPRESENTATION_RELATIONSHIP_WITH_TRANSFORMATION(#{transformation}) \
+             SHAPE_REPRESENTATION_RELATIONSHIP() )"
+        ));
+        let id = self.take_id();
+        self.push(format!(
+            "#{id} = CONTEXT_DEPENDENT_SHAPE_REPRESENTATION(#{relationship}, #{shape})"
+        ));
+    }
+
+    fn add_element(&mut self, model: &BimModel, parent: usize, element: &BimElement) -> Result<()> {
+        if element.category == BimCategory::Opening {
+            return Ok(());
+        }
+        let part = self
+            .add_part(&element.name, &element.geometry, &element.tags)
+            .with_context(|| format!("export {} to STEP", element.name))?;
+        self.link(parent, part.definition, &element.name);
+        if let Some(context) = part.context
+            && !part.items.is_empty()
+        {
+            let [r, g, b, _] = model.layer_color(element);
+            self.style(&part.items, [r, g, b], context);
+        }
+        // Elements on no known layer are shown in the first layer's color, so they go on it.
+        let layer = match element.parameters.get("Layer") {
+            Some(ParameterValue::Text(name)) => {
+                model.layers.iter().find(|layer| layer.name == *name)
+            }
+            _ => None,
+        }
+        .or(model.layers.first());
+        if let Some(layer) = layer {
+            match self.layers.iter_mut().find(|(name, _)| *name == layer.name) {
+                Some((_, items)) => items.extend(&part.items),
+                None => self.layers.push((layer.name.clone(), part.items)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Colors `items` with a surface style of `color`, written once per color.
+    fn style(&mut self, items: &[usize], color: [u8; 3], context: usize) {
+        let style = match self.styles.get(&color) {
+            Some(style) => *style,
+            None => {
+                let [r, g, b] = color.map(|channel| f64::from(channel) / 255.0);
+                let rgb = self.take_id();
+                self.push(format!("#{rgb} = COLOUR_RGB('', {r:.4}, {g:.4}, {b:.4})"));
+                let fill_colour = self.take_id();
+                self.push(format!(
+                    "#{fill_colour} = FILL_AREA_STYLE_COLOUR('', #{rgb})"
+                ));
+                let fill = self.take_id();
+                self.push(format!("#{fill} = FILL_AREA_STYLE('', (#{fill_colour}))"));
+                let area = self.take_id();
+                self.push(format!("#{area} = SURFACE_STYLE_FILL_AREA(#{fill})"));
+                let side = self.take_id();
+                self.push(format!("#{side} = SURFACE_SIDE_STYLE('', (#{area}))"));
+                let usage = self.take_id();
+                self.push(format!("#{usage} = SURFACE_STYLE_USAGE(.BOTH., #{side})"));
+                let style = self.take_id();
+                self.push(format!(
+                    "#{style} = PRESENTATION_STYLE_ASSIGNMENT((#{usage}))"
+                ));
+                self.styles.insert(color, style);
+                style
+            }
+        };
+        let mut styled = Vec::with_capacity(items.len());
+        for item in items {
+            let id = self.take_id();
+            self.push(format!("#{id} = STYLED_ITEM('color', (#{style}), #{item})"));
+            styled.push(format!("#{id}"));
+        }
+        let id = self.take_id();
+        self.push(format!(
+            "#{id} = MECHANICAL_DESIGN_GEOMETRIC_PRESENTATION_REPRESENTATION('', ({}), #{context})",
+            styled.join(", ")
+        ));
+    }
+
+    /// Appends the solid's STEP data with its ids moved past those already written, its product
+    /// named `name` and its faces and edges named by `tags`.
+    fn add_part(&mut self, name: &str, solid: &Solid, tags: &ShapeTags) -> Result<StepPart> {
+        let text = tagged_step_text(solid, tags, &self.file_name)?;
+        let data_start = text
+            .find("DATA;")
+            .context("STEP output has no data section")?;
+        let body = &text[data_start + "DATA;".len()..];
+        let body = &body[..body
+            .find("ENDSEC;")
+            .context("STEP data section is not closed")?];
+        if self.header.is_none() {
+            self.header = Some(text[..data_start].to_string());
+        }
+
+        let offset = self.next_id - 1;
+        let name = step_string_literal(name);
+        let mut definition = None;
+        let mut items = Vec::new();
+        let mut context = None;
+        let mut shape = None;
+        for statement in split_statements(body) {
+            let statement = renumber(statement, offset, &mut self.next_id);
+            if statement.contains("GEOMETRIC_REPRESENTATION_CONTEXT") {
+                context = statement_id(&statement);
+            }
+            match parse_statement(&statement) {
+                Some((id, "PRODUCT", arguments)) => {
+                    let arguments = split_arguments(arguments);
+                    let Some(contexts) = arguments.get(3) else {
+                        bail!("malformed PRODUCT #{id}");
+                    };
+                    self.push(format!("#{id} = PRODUCT({name}, {name}, '', {contexts})"));
+                }
+                Some((id, "PRODUCT_DEFINITION", _)) => {
+                    definition = Some(id);
+                    self.push(statement);
+                }
+                Some((id, "MANIFOLD_SOLID_BREP" | "BREP_WITH_VOIDS", _)) => {
+                    items.push(id);
+                    self.push(statement);
+                }
+                Some((_, "SHAPE_DEFINITION_REPRESENTATION", arguments)) => {
+                    shape = split_arguments(arguments)
+                        .get(1)
+                        .and_then(|reference| reference.strip_prefix('#'))
+                        .and_then(|id| id.parse().ok());
+                    self.push(statement);
+                }
+                _ => self.push(statement),
+            }
+        }
+        let definition = definition.context("STEP part has no product definition")?;
+        if let Some(shape) = shape {
+            self.shapes.insert(definition, shape);
+        }
+        self.context = self.context.or(context);
+        Ok(StepPart {
+            definition,
+            items,
+            context,
+        })
+    }
+
+    fn finish(mut self) -> Result<String> {
+        let Some(header) = self.header.take() else {
+            bail!("the model has no elements to export");
+        };
+        let placement = self.take_id();
+        let origin = self.take_id();
+        let axis = self.take_id();
+        let direction = self.take_id();
+        self.push(format!(
+            "#{placement} = AXIS2_PLACEMENT_3D('', #{origin}, #{axis}, #{direction})"
+        ));
+        self.push(format!("#{origin} = CARTESIAN_POINT('', (0., 0., 0.))"));
+        self.push(format!("#{axis} = DIRECTION('', (0., 0., 1.))"));
+        self.push(format!("#{direction} = DIRECTION('', (1., 0., 0.))"));
+        if let Some(context) = self.context {
+            for node in std::mem::take(&mut self.nodes) {
+                let shape = self.take_id();
+                self.push(format!(
+                    "#{shape} = PRODUCT_DEFINITION_SHAPE('', '', #{node})"
+                ));
+                let representation = self.take_id();
+                self.push(format!(
+                    "#{representation} = SHAPE_REPRESENTATION('', (#{placement}), #{context})"
+                ));
+                let id = self.take_id();
+                self.push(format!(
+                    "#{id} = SHAPE_DEFINITION_REPRESENTATION(#{shape}, #{representation})"
+                ));
+                self.shapes.insert(node, representation);
+            }
+        }
+        for (parent, child, name) in std::mem::take(&mut self.links) {
+            self.write_link(parent, child, &name, placement);
+        }
+        for (name, items) in std::mem::take(&mut self.layers) {
+            if items.is_empty() {
+                continue;
+            }
+            let id = self.take_id();
+            let items: Vec<String> = items.iter().map(|item| format!("#{item}")).collect();
+            self.push(format!(
+                "#{id} = PRESENTATION_LAYER_ASSIGNMENT({}, '', ({}))",
+                step_string_literal(&name),
+                items.join(", ")
+            ));
+        }
+        Ok(format!(
+            "{header}DATA;\n{}ENDSEC;\nEND-ISO-10303-21;\n",
+            self.data
+        ))
+    }
+}
+
+/// Statements of a STEP data section, trimmed and without their closing `;`.
+fn split_statements(data: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut in_string = false;
+    let mut start = 0;
+    for (index, c) in data.char_indices() {
+        match c {
+            '\'' => in_string = !in_string,
+            ';' if !in_string => {
+                statements.push(data[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    statements.retain(|statement| !statement.is_empty());
+    statements
+}
+
+/// `statement` with every `#id` outside strings moved by `offset`; `next_id` is raised past
+/// every id written.
+fn renumber(statement: &str, offset: usize, next_id: &mut usize) -> String {
+    let mut renumbered = String::with_capacity(statement.len() + 16);
+    let mut in_string = false;
+    let mut chars = statement.chars().peekable();
+    while let Some(c) = chars.next() {
+        renumbered.push(c);
+        match c {
+            '\'' => in_string = !in_string,
+            '#' if !in_string => {
+                let mut id = 0usize;
+                while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                    id = id * 10 + digit as usize;
+                    chars.next();
+                }
+                let id = id + offset;
+                *next_id = (*next_id).max(id + 1);
+                renumbered.push_str(&id.to_string());
+            }
+            _ => {}
+        }
+    }
+    renumbered
+}
+
+/// Id, entity keyword and the text between its outer parentheses of `#id = KEYWORD(...)`;
+/// `None` for complex entities, which start with a parenthesis.
+fn parse_statement(statement: &str) -> Option<(usize, &str, &str)> {
+    let id = statement_id(statement)?;
+    let (_, entity) = statement.split_once('=')?;
+    let entity = entity.trim();
+    let (keyword, arguments) = entity.split_once('(')?;
+    let arguments = arguments.strip_suffix(')')?;
+    Some((id, keyword.trim(), arguments))
+}
+
+/// Id of `#id = ...`, simple or complex.
+fn statement_id(statement: &str) -> Option<usize> {
+    let (id, _) = statement.strip_prefix('#')?.split_once('=')?;
+    id.trim().parse().ok()
+}
+
+/// Top-level arguments of an entity, split at commas outside strings and nested lists.
+fn split_arguments(arguments: &str) -> Vec<&str> {
+    let mut split = Vec::new();
+    let mut in_string = false;
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, c) in arguments.char_indices() {
+        match c {
+            '\'' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth = depth.saturating_sub(1),
+            ',' if !in_string && depth == 0 => {
+                split.push(arguments[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    split.push(arguments[start..].trim());
+    split
+}
+
+/// Collects the STEP text of a solid, naming the tagged faces and edges as their entities are
+/// written. Entities are written one per line, so each line is named once it is complete.
+struct NamingWriter<'a> {
+    text: String,
+    /// Start of the line being written in `text`.
+    line_start: usize,
+    faces: EntityNames<'a>,
+    edges: EntityNames<'a>,
+}
+
+impl NamingWriter<'_> {
+    fn name_line(&mut self) {
+        let line = self.line_start..self.text.len();
+        self.faces.name(&mut self.text, line.clone());
+        self.edges.name(&mut self.text, line);
+        self.line_start = self.text.len();
+    }
+
+    fn finish(mut self) -> Result<String> {
+        self.name_line();
+        self.faces.finish()?;
+        self.edges.finish()?;
+        Ok(self.text)
+    }
+}
+
+impl fmt::Write for NamingWriter<'_> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        for piece in text.split_inclusive('\n') {
+            self.text.push_str(piece);
+            if piece.ends_with('\n') {
+                self.name_line();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Names of the instances of one entity, by their index counted in file order, which is the
+/// order the solid's faces and edges are written in.
+struct EntityNames<'a> {
+    entity: &'static str,
+    /// The entity with its empty name, as it is written.
+    pattern: String,
+    names: Vec<(usize, &'a BTreeSet<String>)>,
+    /// Position in `names` of the next instance to name.
+    next: usize,
+    /// Instances written so far.
+    written: usize,
+}
+
+impl<'a> EntityNames<'a> {
+    fn new(
+        entity: &'static str,
+        names: impl IntoIterator<Item = (usize, &'a BTreeSet<String>)>,
+    ) -> Self {
+        Self {
+            entity,
+            pattern: format!("{entity}('',"),
+            names: names.into_iter().collect(),
+            next: 0,
+            written: 0,
+        }
+    }
+
+    /// Fills the empty name of the instance written on `line` of `text`, if it is tagged.
+    fn name(&mut self, text: &mut String, line: Range<usize>) {
+        let Some(start) = text[line.clone()].find(&self.pattern) else {
+            return;
+        };
+        if let Some(&(target, tags)) = self.names.get(self.next)
+            && target == self.written
+        {
+            let name = tags
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join("; ");
+            let quotes = line.start + start + self.entity.len() + 1;
+            text.replace_range(quotes..quotes + 2, &step_string_literal(&name));
+            self.next += 1;
+        }
+        self.written += 1;
+    }
+
+    fn finish(&self) -> Result<()> {
+        if let Some((target, _)) = self.names.get(self.next) {
+            bail!(
+                "tagged {} {target} is not in the exported solid ({} found)",
+                self.entity,
+                self.written
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Quotes `text` as a STEP string: apostrophes and backslashes doubled, non-ASCII characters
+/// as `\X2\` UTF-16 hex.
+pub(crate) fn step_string_literal(text: &str) -> String {
+    let mut literal = String::from("'");
+    for c in text.chars() {
+        match c {
+            '\'' => literal.push_str("''"),
+            '\\' => literal.push_str("\\\\"),
+            ' '..='~' => literal.push(c),
+            _ => {
+                literal.push_str("\\X2\\");
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    literal.push_str(&format!("{unit:04X}"));
+                }
+                literal.push_str("\\X0\\");
+            }
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+/// Reads a STEP file into one solid: its [`import_step_shells`] healed by [`heal_shells`] at
+/// [`STEP_IMPORT_TOLERANCE`], since STEP writers seldom close faces exactly.
+pub fn import_step(path: impl AsRef<Path>) -> Result<Solid> {
+    let path = path.as_ref();
+    let shells = import_step_shells(path)?;
+    let (solid, _) = heal_shells(&shells, STEP_IMPORT_TOLERANCE)
+        .with_context(|| format!("heal STEP file {}", path.display()))?;
+    Ok(solid)
+}
+
+/// Reads the shells of a STEP file as they are, in the order of their ids. Lines, B-splines,
+/// NURBS, planes and surfaces of revolution, cylinders and cones among them, carry over exactly;
+/// conic arcs become rational B-splines and extruded curves the B-spline surfaces they sweep.
+/// Spheres, tori and curves defined on surfaces are refused.
+pub fn import_step_shells(path: impl AsRef<Path>) -> Result<Vec<Shell>> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("read STEP file {}", path.display()))?;
+    let table = r#in::Table::from_step(&text)
+        .with_context(|| format!("parse STEP file {}", path.display()))?;
+    let mut holders: Vec<_> = table.shell.iter().collect();
+    holders.sort_by_key(|(id, _)| **id);
+    let mut shells = Vec::with_capacity(holders.len());
+    for (id, holder) in holders {
+        let shell = table
+            .to_compressed_shell(holder)
+            .map_err(|err| anyhow!("STEP shell #{id}: {err}"))?;
+        shells.push(kernel_shell(shell).with_context(|| format!("STEP shell #{id}"))?);
+    }
+    if shells.is_empty() {
+        bail!("{} has no shells", path.display());
+    }
+    Ok(shells)
+}
+
+fn kernel_shell(shell: CompressedShell<Point3, r#in::Curve3D, r#in::Surface>) -> Result<Shell> {
+    let edges = shell
+        .edges
+        .into_iter()
+        .map(|edge| -> Result<_> {
+            Ok(CompressedEdge {
+                vertices: edge.vertices,
+                curve: kernel_curve(&edge.curve)?,
+            })
+        })
+        .collect::<Result<_>>()?;
+    let faces = shell
+        .faces
+        .into_iter()
+        .map(|face| -> Result<_> {
+            Ok(CompressedFace {
+                boundaries: face.boundaries,
+                orientation: face.orientation,
+                surface: kernel_surface(&face.surface)?,
+            })
+        })
+        .collect::<Result<_>>()?;
+    Shell::extract(CompressedShell {
+        vertices: shell.vertices,
+        edges,
+        faces,
+    })
+    .map_err(|err| anyhow!("invalid STEP topology: {err}"))
+}
+
+fn kernel_curve(curve: &r#in::Curve3D) -> Result<Curve> {
+    Ok(match curve {
+        r#in::Curve3D::Line(line) => Curve::Line(*line),
+        r#in::Curve3D::Conic(conic) => Curve::NurbsCurve(rational_conic(conic)),
+        r#in::Curve3D::BSplineCurve(curve) => Curve::BSplineCurve(BSplineCurve::clone(curve)),
+        r#in::Curve3D::NurbsCurve(curve) => Curve::NurbsCurve(NurbsCurve::clone(curve)),
+        _ => bail!("STEP curve is neither a line, a conic, a B-spline nor a NURBS curve"),
+    })
+}
+
+fn kernel_surface(surface: &r#in::Surface) -> Result<Surface> {
+    Ok(match surface {
+        r#in::Surface::ElementarySurface(surface) => elementary_surface(surface)?,
+        r#in::Surface::SweptCurve(surface) => swept_surface(surface)?,
+        r#in::Surface::BSplineSurface(surface) => {
+            Surface::BSplineSurface(BSplineSurface::clone(surface))
+        }
+        r#in::Surface::NurbsSurface(surface) => Surface::NurbsSurface(NurbsSurface::clone(surface)),
+    })
+}
+
+fn elementary_surface(surface: &r#in::ElementarySurface) -> Result<Surface> {
+    Ok(match surface {
+        r#in::ElementarySurface::Plane(plane) => Surface::Plane(Plane::clone(plane)),
+        r#in::ElementarySurface::CylindricalSurface(surface) => {
+            revolved(surface, |line| Ok(Curve::Line(*line)))?
+        }
+        r#in::ElementarySurface::ConicalSurface(surface) => {
+            revolved(surface, |line| Ok(Curve::Line(*line)))?
+        }
+        _ => bail!("STEP spheres and tori are not supported"),
+    })
+}
+
+fn swept_surface(surface: &r#in::SweptCurve) -> Result<Surface> {
+    Ok(match surface {
+        r#in::SweptCurve::ExtrudedCurve(extrusion) => extruded(
+            &kernel_curve(extrusion.entity_curve())?,
+            extrusion.extruding_vector(),
+        )?,
+        r#in::SweptCurve::RevolutedCurve(revolution) => revolved(revolution, kernel_curve)?,
+    })
+}
+
+/// `processor`'s surface of revolution around the same axis, its profile converted by `curve`.
+fn revolved<C>(
+    processor: &Processor<RevolutedCurve<C>, Matrix4>,
+    curve: impl Fn(&C) -> Result<Curve>,
+) -> Result<Surface> {
+    let revolution = processor.entity();
+    let mut surface = Processor::new(RevolutedCurve::by_revolution(
+        curve(revolution.entity_curve())?,
+        revolution.origin(),
+        revolution.axis(),
+    ));
+    surface.transform_by(*processor.transform());
+    if !processor.orientation() {
+        surface.invert();
+    }
+    Ok(Surface::RevolutedCurve(surface))
+}
+
+/// The surface `curve` sweeps moving along `vector`: a plane for a line, else a B-spline surface
+/// of degree one across the sweep.
+fn extruded(curve: &Curve, vector: Vector3) -> Result<Surface> {
+    let across = KnotVec::bezier_knot(1);
+    Ok(match curve {
+        Curve::Line(line) => Surface::Plane(Plane::new(line.0, line.1, line.0 + vector)),
+        Curve::BSplineCurve(curve) => Surface::BSplineSurface(BSplineSurface::new(
+            (curve.knot_vec().clone(), across),
+            curve
+                .control_points()
+                .iter()
+                .map(|&point| vec![point, point + vector])
+                .collect(),
+        )),
+        Curve::NurbsCurve(curve) => {
+            let curve = curve.non_rationalized();
+            Surface::NurbsSurface(NurbsSurface::new(BSplineSurface::new(
+                (curve.knot_vec().clone(), across),
+                curve
+                    .control_points()
+                    .iter()
+                    .map(|&point| vec![point, point + vector.extend(0.0) * point.w])
+                    .collect(),
+            )))
+        }
+        Curve::IntersectionCurve(_) => bail!("cannot extrude an intersection curve"),
+    })
+}
+
+/// The arc of `conic` as a rational quadratic B-spline through the same points, in pieces of at
+/// most a quarter of a turn of its parameter. Each piece's middle parameter must give its
+/// shoulder point, the point whose tangent parallels the chord, as the angle of an ellipse and
+/// the parameters of a parabola and a hyperbola do.
+fn rational_conic<C>(conic: &C) -> NurbsCurve<Vector4>
+where
+    C: ParametricCurve<Point = Point3, Vector = Vector3> + BoundedCurve,
+{
+    let (start, end) = conic.range_tuple();
+    let pieces = ((end - start).abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+    let step = (end - start) / pieces as f64;
+    let mut knots = vec![0.0; 3];
+    let mut control_points = vec![conic.subs(start).to_vec().extend(1.0)];
+    for piece in 0..pieces {
+        let from = start + step * piece as f64;
+        let to = from + step;
+        let (first, last) = (conic.subs(from), conic.subs(to));
+        let shoulder = conic.subs((from + to) / 2.0);
+        let apex = tangent_intersection(first, conic.der(from), last, conic.der(to));
+        let weight = (shoulder - first.midpoint(last)).magnitude() / (apex - shoulder).magnitude();
+        control_points.push((apex.to_vec() * weight).extend(weight));
+        control_points.push(last.to_vec().extend(1.0));
+        let multiplicity = if piece + 1 == pieces { 3 } else { 2 };
+        knots.extend(std::iter::repeat_n((piece + 1) as f64, multiplicity));
+    }
+    NurbsCurve::new(BSplineCurve::new(KnotVec::from(knots), control_points))
+}
+
+/// The point of the line through `from` along `from_tangent` nearest to the line through `to`
+/// along `to_tangent`; where they cross, for the tangents of a conic arc.
+fn tangent_intersection(
+    from: Point3,
+    from_tangent: Vector3,
+    to: Point3,
+    to_tangent: Vector3,
+) -> Point3 {
+    let chord = to - from;
+    let (aa, ab, bb) = (
+        from_tangent.dot(from_tangent),
+        from_tangent.dot(to_tangent),
+        to_tangent.dot(to_tangent),
+    );
+    let along = (ab * to_tangent.dot(chord) - bb * from_tangent.dot(chord)) / (ab * ab - aa * bb);
+    from + from_tangent * along
 }
@@ -0,0 +1,298 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_bim::{BimElement, WeldAnnotation, WeldKind, WeldSide};
+use cryxtal_topology::{Edge, Point3, edge_length, sample_edge, solid_edges};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Segments each edge is drawn with; straight edges come out exact.
+const DRAWING_SEGMENTS: usize = 8;
+/// Longer side of the drawn model, in SVG pixels.
+const DRAWING_SIZE: f64 = 800.0;
+/// Room around the model for weld symbols.
+const DRAWING_MARGIN: f64 = 100.0;
+/// Height of an elementary weld symbol.
+const SYMBOL_HEIGHT: f64 = 8.0;
+/// Offset of the arrow's knee from the weld, and length of the reference line.
+const LEADER: f64 = 30.0;
+const REFERENCE_LINE: f64 = 60.0;
+
+/// Welds of one type, size and side, with their number and total length in millimetres.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeldRow {
+    pub kind: WeldKind,
+    pub size: f64,
+    pub side: WeldSide,
+    pub count: usize,
+    pub length: f64,
+}
+
+/// Aggregates the welds on `elements`, sorted by type, size and side. Each weld is as long as
+/// the edge it runs along; welds on edges the geometry no longer has are skipped.
+pub fn weld_schedule(elements: &[BimElement]) -> Vec<WeldRow> {
+    // Keyed by tenths of a millimetre of size.
+    let mut groups: BTreeMap<(WeldKind, i64, WeldSide), (usize, f64)> = BTreeMap::new();
+    for element in elements {
+        if element.welds.is_empty() {
+            continue;
+        }
+        let edges = solid_edges(&element.geometry);
+        for weld in &element.welds {
+            let Some(edge) = edges.get(weld.edge) else {
+                continue;
+            };
+            let key = (weld.kind, (weld.size * 10.0).round() as i64, weld.side);
+            let entry = groups.entry(key).or_insert((0, 0.0));
+            entry.0 += 1;
+            entry.1 += edge_length(edge);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|((kind, size, side), (count, length))| WeldRow {
+            kind,
+            size: size as f64 / 10.0,
+            side,
+            count,
+            length,
+        })
+        .collect()
+}
+
+/// Writes `rows` as CSV with a header line.
+pub fn write_weld_schedule_csv(rows: &[WeldRow], mut writer: impl Write) -> Result<()> {
+    writeln!(writer, "Type,Size,Side,Count,Length")?;
+    for row in rows {
+        writeln!(
+            writer,
+            "{},{:.1},{},{},{:.0}",
+            row.kind.label(),
+            row.size,
+            row.side.label(),
+            row.count,
+            row.length
+        )?;
+    }
+    Ok(())
+}
+
+pub fn export_weld_schedule_csv(rows: &[WeldRow], path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_weld_schedule_csv(rows, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Plan view (looking down Z) of the element edges as SVG, with every weld drawn heavy and
+/// labelled with its standard symbol: an arrow to the weld, the reference line, the
+/// elementary symbol below the line for the arrow side and above it for the other side, the
+/// size on its left and the weld length on its right, plus the all-around circle and the
+/// field flag.
+pub fn write_weld_drawing_svg(elements: &[BimElement], mut writer: impl Write) -> Result<()> {
+    let edges: Vec<(&BimElement, Vec<Edge>)> = elements
+        .iter()
+        .map(|element| (element, solid_edges(&element.geometry)))
+        .collect();
+    let points = edges
+        .iter()
+        .flat_map(|(_, edges)| edges.iter())
+        .flat_map(|edge| sample_edge(edge, DRAWING_SEGMENTS));
+    let (mut min, mut max) = ([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]);
+    for point in points {
+        min = [min[0].min(point.x), min[1].min(point.y)];
+        max = [max[0].max(point.x), max[1].max(point.y)];
+    }
+    if min[0] > max[0] {
+        bail!("nothing to draw");
+    }
+    let extent = (max[0] - min[0]).max(max[1] - min[1]).max(1.0e-6);
+    let scale = DRAWING_SIZE / extent;
+    let to_svg = |point: Point3| {
+        (
+            DRAWING_MARGIN + (point.x - min[0]) * scale,
+            DRAWING_MARGIN + (max[1] - point.y) * scale,
+        )
+    };
+    let width = (max[0] - min[0]) * scale + DRAWING_MARGIN * 2.0;
+    let height = (max[1] - min[1]) * scale + DRAWING_MARGIN * 2.0;
+    let polyline = |edge: &Edge| {
+        sample_edge(edge, DRAWING_SEGMENTS)
+            .into_iter()
+            .map(|point| {
+                let (x, y) = to_svg(point);
+                format!("{x:.1},{y:.1}")
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0}" height="{height:.0}" viewBox="0 0 {width:.1} {height:.1}">"#
+    )?;
+    writeln!(svg, r##"<g fill="none" stroke="#888" stroke-width="0.5">"##)?;
+    for edge in edges.iter().flat_map(|(_, edges)| edges.iter()) {
+        writeln!(svg, r#"<polyline points="{}"/>"#, polyline(edge))?;
+    }
+    writeln!(svg, "</g>")?;
+    writeln!(
+        svg,
+        r##"<g fill="none" stroke="#000" stroke-width="1" font-family="sans-serif" font-size="10">"##
+    )?;
+    for (element, edges) in &edges {
+        for weld in &element.welds {
+            let Some(edge) = edges.get(weld.edge) else {
+                continue;
+            };
+            writeln!(
+                svg,
+                r#"<polyline points="{}" stroke-width="2.5"/>"#,
+                polyline(edge)
+            )?;
+            let middle = sample_edge(edge, 2)[1];
+            write_weld_symbol(&mut svg, weld, to_svg(middle), edge_length(edge))?;
+        }
+    }
+    writeln!(svg, "</g>")?;
+    writeln!(svg, "</svg>")?;
+    writer.write_all(svg.as_bytes())?;
+    Ok(())
+}
+
+pub fn export_weld_drawing_svg(elements: &[BimElement], path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write_weld_drawing_svg(elements, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Weld symbol with its arrow pointing at `(x, y)`.
+fn write_weld_symbol(
+    svg: &mut String,
+    weld: &WeldAnnotation,
+    (x, y): (f64, f64),
+    length: f64,
+) -> std::fmt::Result {
+    let (knee_x, knee_y) = (x + LEADER, y - LEADER);
+    writeln!(
+        svg,
+        r#"<path d="M{knee_x:.1} {knee_y:.1} L{x:.1} {y:.1} M{:.1} {knee_y:.1} H{:.1}"/>"#,
+        knee_x,
+        knee_x + REFERENCE_LINE
+    )?;
+    // Arrowhead along the leader, which runs at 45°.
+    let head = SYMBOL_HEIGHT * 0.5;
+    writeln!(
+        svg,
+        r#"<path d="M{x:.1} {y:.1} L{:.1} {:.1} L{:.1} {:.1} Z" fill="black"/>"#,
+        x + head * 0.3,
+        y - head * 1.2,
+        x + head * 1.2,
+        y - head * 0.3
+    )?;
+
+    let center = knee_x + REFERENCE_LINE * 0.5;
+    let directions: &[f64] = match weld.side {
+        WeldSide::Arrow => &[1.0],
+        WeldSide::Other => &[-1.0],
+        WeldSide::Both => &[1.0, -1.0],
+    };
+    for (idx, direction) in directions.iter().enumerate() {
+        writeln!(
+            svg,
+            r#"<path d="{}"/>"#,
+            symbol_path(weld.kind, center, knee_y, *direction)
+        )?;
+        let text_y = if *direction > 0.0 {
+            knee_y + SYMBOL_HEIGHT + 2.0
+        } else {
+            knee_y - SYMBOL_HEIGHT - 2.0
+        };
+        let half = SYMBOL_HEIGHT * 0.5;
+        writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{text_y:.1}" text-anchor="end" stroke="none" fill="black">{}</text>"#,
+            center - half - 2.0,
+            format_number(weld.size)
+        )?;
+        if idx == 0 {
+            writeln!(
+                svg,
+                r#"<text x="{:.1}" y="{text_y:.1}" stroke="none" fill="black">{length:.0}</text>"#,
+                center + half + 2.0
+            )?;
+        }
+    }
+    if weld.all_around {
+        writeln!(
+            svg,
+            r#"<circle cx="{knee_x:.1}" cy="{knee_y:.1}" r="{:.1}"/>"#,
+            SYMBOL_HEIGHT * 0.5
+        )?;
+    }
+    if weld.field {
+        let top = knee_y - SYMBOL_HEIGHT * 1.75;
+        writeln!(
+            svg,
+            r#"<path d="M{knee_x:.1} {knee_y:.1} V{top:.1} L{:.1} {:.1} L{knee_x:.1} {:.1} Z" fill="black"/>"#,
+            knee_x + SYMBOL_HEIGHT,
+            top + SYMBOL_HEIGHT * 0.375,
+            top + SYMBOL_HEIGHT * 0.75
+        )?;
+    }
+    Ok(())
+}
+
+/// Elementary symbol on a reference line at height `y`; `direction` is `1.0` to draw it below
+/// the line (arrow side) and `-1.0` above it (other side).
+fn symbol_path(kind: WeldKind, center: f64, y: f64, direction: f64) -> String {
+    let half = SYMBOL_HEIGHT * 0.5;
+    let quarter = SYMBOL_HEIGHT * 0.25;
+    let far = y + direction * SYMBOL_HEIGHT;
+    match kind {
+        // The vertical leg of fillet and bevel symbols is always on the left.
+        WeldKind::Fillet => format!(
+            "M{:.1} {y:.1} V{far:.1} L{:.1} {y:.1} Z",
+            center - half,
+            center + half
+        ),
+        WeldKind::SquareButt => format!(
+            "M{:.1} {y:.1} V{far:.1} M{:.1} {y:.1} V{far:.1}",
+            center - quarter,
+            center + quarter
+        ),
+        WeldKind::SingleV => format!(
+            "M{:.1} {far:.1} L{center:.1} {y:.1} L{:.1} {far:.1}",
+            center - half,
+            center + half
+        ),
+        WeldKind::SingleBevel => format!(
+            "M{:.1} {y:.1} V{far:.1} M{:.1} {y:.1} L{:.1} {far:.1}",
+            center - quarter,
+            center - quarter,
+            center + half
+        ),
+        WeldKind::Plug => format!(
+            "M{:.1} {y:.1} V{far:.1} H{:.1} V{y:.1}",
+            center - half,
+            center + half
+        ),
+    }
+}
+
+fn format_number(value: f64) -> String {
+    if (value - value.round()).abs() < 1.0e-9 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.1}")
+    }
+}
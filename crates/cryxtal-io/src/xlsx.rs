@@ -0,0 +1,163 @@
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::io::Write;
+
+use crate::plan::escape;
+
+const CONTENT_TYPES: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+    r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+    r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+    r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+    r#"<Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+    "</Types>"
+);
+const ROOT_RELS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+    "</Relationships>"
+);
+const WORKBOOK_RELS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>"#,
+    "</Relationships>"
+);
+
+/// MS-DOS date of the entries, 1980-01-01; the files carry no meaningful time.
+const ZIP_DATE: u16 = (1 << 5) | 1;
+
+/// Value of a spreadsheet cell.
+pub(crate) enum Cell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+/// Writes `rows` as the single sheet `sheet` of an Office Open XML workbook (`.xlsx`), with
+/// text as inline strings and no styles, in a ZIP archive whose entries are stored
+/// uncompressed.
+pub(crate) fn write_xlsx(sheet: &str, rows: &[Vec<Cell>], mut writer: impl Write) -> Result<()> {
+    let workbook = format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+            r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+            r#"<sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets></workbook>"#
+        ),
+        escape(sheet).replace('"', "&quot;")
+    );
+    let entries = [
+        ("[Content_Types].xml", CONTENT_TYPES.to_string()),
+        ("_rels/.rels", ROOT_RELS.to_string()),
+        ("xl/workbook.xml", workbook),
+        ("xl/_rels/workbook.xml.rels", WORKBOOK_RELS.to_string()),
+        ("xl/worksheets/sheet1.xml", worksheet(rows)?),
+    ];
+
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (name, data) in &entries {
+        let (name, data) = (name.as_bytes(), data.as_bytes());
+        let offset = archive.len() as u32;
+        let crc = crc32(data);
+        // Local file header, then the data as is.
+        archive.extend(0x0403_4b50u32.to_le_bytes());
+        zip_entry_fields(&mut archive, crc, data.len() as u32, name.len() as u16);
+        archive.extend(name);
+        archive.extend(data);
+        // Central directory record pointing back at it.
+        directory.extend(0x0201_4b50u32.to_le_bytes());
+        directory.extend(20u16.to_le_bytes());
+        zip_entry_fields(&mut directory, crc, data.len() as u32, name.len() as u16);
+        directory.extend([0; 10]);
+        directory.extend(offset.to_le_bytes());
+        directory.extend(name);
+    }
+    let directory_offset = archive.len() as u32;
+    archive.extend(&directory);
+    archive.extend(0x0605_4b50u32.to_le_bytes());
+    archive.extend([0; 4]);
+    archive.extend((entries.len() as u16).to_le_bytes());
+    archive.extend((entries.len() as u16).to_le_bytes());
+    archive.extend((directory.len() as u32).to_le_bytes());
+    archive.extend(directory_offset.to_le_bytes());
+    archive.extend([0; 2]);
+    writer.write_all(&archive)?;
+    Ok(())
+}
+
+fn worksheet(rows: &[Vec<Cell>]) -> Result<String> {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+        "<sheetData>"
+    ));
+    for (row, cells) in rows.iter().enumerate() {
+        write!(xml, r#"<row r="{}">"#, row + 1)?;
+        for (column, cell) in cells.iter().enumerate() {
+            let reference = format!("{}{}", column_name(column), row + 1);
+            match cell {
+                Cell::Text(text) => write!(
+                    xml,
+                    r#"<c r="{reference}" t="inlineStr"><is><t>{}</t></is></c>"#,
+                    escape(text)
+                )?,
+                Cell::Number(value) if value.is_finite() => {
+                    write!(xml, r#"<c r="{reference}"><v>{value}</v></c>"#)?
+                }
+                Cell::Number(_) | Cell::Empty => {}
+            }
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    Ok(xml)
+}
+
+/// Spreadsheet column letters of the zero-based `column`: `A` to `Z`, then `AA`, `AB`, …
+fn column_name(mut column: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (column % 26) as u8);
+        if column < 26 {
+            break;
+        }
+        column = column / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// Fields local file headers and central directory records share, from the version needed
+/// to the extra field length: a stored entry without flags.
+fn zip_entry_fields(out: &mut Vec<u8>, crc: u32, size: u32, name_len: u16) {
+    out.extend(20u16.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+    out.extend(ZIP_DATE.to_le_bytes());
+    out.extend(crc.to_le_bytes());
+    out.extend(size.to_le_bytes());
+    out.extend(size.to_le_bytes());
+    out.extend(name_len.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+}
+
+/// CRC-32 (IEEE) checksum ZIP stores for each entry.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
@@ -1,9 +1,39 @@
 use anyhow::Result;
-use cryxtal_io::{DEFAULT_TESSELLATION_TOLERANCE, export_step, triangulate_solid};
-use cryxtal_topology::SolidBuilder;
+use cryxtal_base::{Guid, OpContext};
+use cryxtal_bim::{
+    BimCategory, BimElement, BimModel, ChangeSource, ElementGroup, GraphicOverride, GroupKind,
+    History, Layer, ModelCommand, NamedView, Opening, OpeningPlacement, OpeningProfile,
+    ParameterSet, ParameterValue, ViewFilter, WeldAnnotation, WeldKind, WeldSide,
+    bill_of_materials, takeoff,
+};
+use cryxtal_io::{
+    BAR_CORNERS_KEY, BREP_FORMAT_VERSION, BrepSolid, BvbsOptions, CUT_LENGTHS_KEY,
+    DEFAULT_TESSELLATION_TOLERANCE, GltfOptions, PackedElement, PackedMesh, Project, ProjectCamera,
+    SplitBy, TessellationOptions, brep_from_str, bvbs_bars, bvbs_checksum, check_mesh,
+    decimate_mesh, distance, export_bom, export_brep, export_glb_streaming, export_journal,
+    export_model_mesh, export_model_mesh_streaming, export_obj, export_step, export_step_model,
+    export_step_tagged, fix_normals, geometry_key, ifc_guid, import_brep, import_journal,
+    import_obj, import_point_cloud, import_step, load_mesh_cache, load_project, read_ids,
+    read_parameters_csv, read_parameters_json, read_ply, read_project, read_scan_points,
+    rebar_schedule, recognize_holes, reference_mesh_element, repair_mesh, save_mesh_cache,
+    save_project, scan_deviation, split_elements, triangulate_many, triangulate_many_with,
+    triangulate_solid, triangulate_solid_with, validate_ids, weld_schedule, write_bom_csv,
+    write_bom_xlsx, write_bvbs, write_deviation_csv, write_dxf_plan, write_glb, write_ifc,
+    write_parameters_csv, write_parameters_json, write_plan_svg, write_pxml,
+    write_rebar_schedule_csv, write_weld_drawing_svg,
+};
+use cryxtal_shapeops::{DEFAULT_SHAPEOPS_TOLERANCE, plate_with_hole};
+use cryxtal_topology::{
+    Matrix4, Point3, ShapeTags, SolidBuilder, Vector3, mass_properties, transform_solid,
+};
+use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
 use std::time::{SystemTime, UNIX_EPOCH};
+use truck_modeling::{EuclideanSpace, InnerSpace};
+use truck_polymesh::{Faces, PolygonMesh, StandardAttributes};
 
 fn temp_path(file_name: &str) -> PathBuf {
     let mut path = std::env::temp_dir();
@@ -29,11 +59,1675 @@ fn export_step_creates_file() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn tagged_step_export_names_faces_and_edges() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let mut tags = ShapeTags::new();
+    tags.tag_face(&solid, 1, "formwork")?;
+    tags.tag_face(&solid, 1, "finish:paint")?;
+    tags.tag_face(&solid, 5, "top")?;
+    tags.tag_edge(&solid, 0, "weld edge")?;
+    let path = temp_path("tagged.step");
+
+    export_step_tagged(&solid, &tags, &path)?;
+    let text = fs::read_to_string(&path)?;
+    let _ = fs::remove_file(&path);
+    assert_eq!(
+        text.matches("ADVANCED_FACE('finish:paint; formwork',")
+            .count(),
+        1
+    );
+    assert_eq!(text.matches("ADVANCED_FACE('top',").count(), 1);
+    // Untagged faces keep their empty names.
+    assert_eq!(text.matches("ADVANCED_FACE('',").count(), 4);
+    assert_eq!(text.matches("EDGE_CURVE('weld edge',").count(), 1);
+    Ok(())
+}
+
+#[test]
+fn step_import_reads_exported_solids_back() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let plate = plate_with_hole(200.0, 200.0, 20.0, 30.0, DEFAULT_SHAPEOPS_TOLERANCE)?;
+    for original in [solid, plate] {
+        let path = temp_path("import.step");
+        export_step(&original, &path)?;
+        let imported = import_step(&path);
+        let _ = fs::remove_file(&path);
+        let imported = imported?;
+
+        assert_eq!(imported.face_iter().count(), original.face_iter().count());
+        let volume = mass_properties(&original).volume;
+        assert!((mass_properties(&imported).volume - volume).abs() < volume * 1.0e-3);
+    }
+    Ok(())
+}
+
+#[test]
+fn step_import_keeps_holes() -> Result<()> {
+    let plate = plate_with_hole(200.0, 200.0, 20.0, 30.0, DEFAULT_SHAPEOPS_TOLERANCE)?;
+    let path = temp_path("plate.step");
+    export_step(&plate, &path)?;
+    let imported = import_step(&path);
+    let _ = fs::remove_file(&path);
+
+    let holes = recognize_holes(&imported?, DEFAULT_TESSELLATION_TOLERANCE);
+    assert_eq!(holes.len(), 1);
+    assert!((holes[0].diameter - 30.0).abs() < 0.5);
+    assert!(holes[0].through);
+    Ok(())
+}
+
+#[test]
+fn model_step_export_nests_elements_under_storeys() -> Result<()> {
+    let mut model = BimModel::new("Tower");
+    let ground = model.default_storey().expect("default storey");
+    let building = model.site.buildings[0].guid;
+    let upper = model.add_storey(building, "Level 1", 3000.0)?;
+    model.add_element(box_element("Column 'C1'", 100.0)?, ground)?;
+    model.add_element(box_element("Slab", 200.0)?, upper)?;
+    let project = Project {
+        model,
+        ..Project::default()
+    };
+    let path = temp_path("model.step");
+
+    export_step_model(&project, &path)?;
+    let text = fs::read_to_string(&path)?;
+    let _ = fs::remove_file(&path);
+    assert_eq!(text.matches("PRODUCT('Column ''C1''', ").count(), 1);
+    assert_eq!(text.matches("PRODUCT('Slab', ").count(), 1);
+    assert_eq!(text.matches("PRODUCT('Level 1', ").count(), 1);
+    // Site, building, both storeys and both elements each hang off a parent.
+    assert_eq!(text.matches("NEXT_ASSEMBLY_USAGE_OCCURRENCE(").count(), 6);
+    // Each occurrence relates the child's shape to its parent's, which CAD tools read the
+    // assembly structure from.
+    assert_eq!(
+        text.matches("CONTEXT_DEPENDENT_SHAPE_REPRESENTATION(")
+            .count(),
+        6
+    );
+    assert_eq!(text.matches("ITEM_DEFINED_TRANSFORMATION(").count(), 6);
+
+    let data = &text[text.find("DATA;").expect("data section")..];
+    let mut ids = HashSet::new();
+    for line in data.lines().filter(|line| line.starts_with('#')) {
+        let id = &line[..line.find(' ').expect("entity id")];
+        assert!(ids.insert(id), "{id} is defined twice");
+    }
+    Ok(())
+}
+
+#[test]
+fn model_step_export_names_tagged_faces_and_edges() -> Result<()> {
+    let mut model = BimModel::new("Tower");
+    let ground = model.default_storey().expect("default storey");
+    let mut wall = box_element("Wall", 100.0)?;
+    wall.tags.tag_face(&wall.geometry, 1, "formwork")?;
+    wall.tags.tag_edge(&wall.geometry, 0, "weld edge")?;
+    model.add_element(wall, ground)?;
+    model.add_element(box_element("Slab", 200.0)?, ground)?;
+    let project = Project {
+        model,
+        ..Project::default()
+    };
+    let path = temp_path("tagged-model.step");
+
+    export_step_model(&project, &path)?;
+    let text = fs::read_to_string(&path)?;
+    let _ = fs::remove_file(&path);
+    assert_eq!(text.matches("ADVANCED_FACE('formwork',").count(), 1);
+    assert_eq!(text.matches("EDGE_CURVE('weld edge',").count(), 1);
+    // The untagged slab keeps its empty names.
+    assert_eq!(text.matches("ADVANCED_FACE('',").count(), 11);
+    Ok(())
+}
+
+#[test]
+fn model_step_export_colors_parts_by_layer() -> Result<()> {
+    let mut model = BimModel::new("Layers");
+    model.layers.push(Layer::new("Structure", [255, 0, 0, 255]));
+    let storey = model.default_storey().expect("default storey");
+    let mut column = box_element("Column", 100.0)?;
+    column.insert_parameter("Layer", ParameterValue::Text("Structure".to_string()));
+    model.add_element(column, storey)?;
+    model.add_element(box_element("Beam", 100.0)?, storey)?;
+    model.add_element(box_element("Footing", 100.0)?, storey)?;
+    let project = Project {
+        model,
+        ..Project::default()
+    };
+    let path = temp_path("layers.step");
+
+    export_step_model(&project, &path)?;
+    let text = fs::read_to_string(&path)?;
+    let _ = fs::remove_file(&path);
+    assert_eq!(text.matches("COLOUR_RGB(").count(), 2);
+    assert_eq!(
+        text.matches("COLOUR_RGB('', 1.0000, 0.0000, 0.0000)")
+            .count(),
+        1
+    );
+    assert_eq!(text.matches("STYLED_ITEM('color', ").count(), 3);
+    assert_eq!(text.matches("PRESENTATION_LAYER_ASSIGNMENT(").count(), 2);
+    let default = text
+        .lines()
+        .find(|line| line.contains("PRESENTATION_LAYER_ASSIGNMENT('Default', "))
+        .expect("default layer");
+    assert_eq!(default.matches('#').count(), 3);
+    Ok(())
+}
+
 #[test]
 fn triangulation_produces_mesh() -> Result<()> {
     let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
-    let mesh = triangulate_solid(&solid, DEFAULT_TESSELLATION_TOLERANCE);
+    let mesh = triangulate_solid(&solid, &TessellationOptions::default());
     assert!(!mesh.positions().is_empty());
     assert!(mesh.faces().len() > 0);
     Ok(())
 }
+
+#[test]
+fn adaptive_tessellation_follows_curvature() -> Result<()> {
+    let fixed = TessellationOptions::default();
+    let adaptive = TessellationOptions {
+        curvature_adaptive: true,
+        ..fixed
+    };
+    // A small cylinder comes out faceted at the chordal tolerance alone.
+    let small = SolidBuilder::cylinder_z(Point3::new(0.0, 0.0, 0.0), 5.0, 20.0)?;
+    assert!(adaptive.tolerance_for(&small) < fixed.chordal_tol);
+    assert!(
+        triangulate_solid(&small, &adaptive).positions().len()
+            > triangulate_solid(&small, &fixed).positions().len()
+    );
+    // A large one is already fine enough and keeps the chordal tolerance.
+    let large = SolidBuilder::cylinder_z(Point3::new(0.0, 0.0, 0.0), 5000.0, 200.0)?;
+    assert_eq!(adaptive.tolerance_for(&large), fixed.chordal_tol);
+
+    let limited = TessellationOptions {
+        max_edge_length: Some(100.0),
+        ..fixed
+    };
+    let mesh = triangulate_solid(&SolidBuilder::box_solid(1000.0, 300.0, 50.0)?, &limited);
+    let positions = mesh.positions();
+    for face in mesh.tri_faces() {
+        for idx in 0..3 {
+            let edge = positions[face[(idx + 1) % 3].pos] - positions[face[idx].pos];
+            assert!(edge.magnitude() <= 100.0 + 1.0e-9);
+        }
+    }
+    // Faces split the edges they share alike, leaving no T-junctions to open the mesh.
+    assert!(check_mesh(&mesh).is_watertight());
+    let cylinder = SolidBuilder::cylinder_z(Point3::new(0.0, 0.0, 0.0), 400.0, 1000.0)?;
+    assert!(check_mesh(&triangulate_solid(&cylinder, &limited)).is_watertight());
+    Ok(())
+}
+
+/// Signed volume enclosed by the triangles of `mesh`; positive when they face outward.
+fn mesh_volume(mesh: &PolygonMesh) -> f64 {
+    let positions = mesh.positions();
+    mesh.tri_faces()
+        .iter()
+        .map(|face| {
+            let [a, b, c] = [0, 1, 2].map(|idx| positions[face[idx].pos].to_vec());
+            a.dot(b.cross(c)) / 6.0
+        })
+        .sum()
+}
+
+#[test]
+fn mesh_repair_welds_drops_slivers_and_orients() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let mesh = triangulate_solid(&solid, &TessellationOptions::default());
+    let clean = mesh.tri_faces().len();
+
+    // Every triangle on its own nudged copies of its corners, the first one flipped, plus a
+    // triangle with a repeated corner and a flat sliver along the first edge.
+    let mut positions = Vec::new();
+    let mut faces = Vec::new();
+    for (idx, face) in mesh.tri_faces().iter().enumerate() {
+        let nudge = Vector3::new(1.0e-3 * (idx % 2) as f64, 0.0, 0.0);
+        for corner in face {
+            positions.push(mesh.positions()[corner.pos] + nudge);
+        }
+        let first = idx * 3;
+        faces.push(match idx {
+            0 => [first, first + 2, first + 1],
+            _ => [first, first + 1, first + 2],
+        });
+    }
+    let (a, b) = (positions[0], positions[1]);
+    positions.push(Point3::from_vec((a.to_vec() + b.to_vec()) * 0.5));
+    faces.push([0, 1, 0]);
+    faces.push([0, 1, positions.len() - 1]);
+    let mut broken = PolygonMesh::new(
+        StandardAttributes {
+            positions,
+            ..Default::default()
+        },
+        Faces::from_iter(faces),
+    );
+
+    let report = repair_mesh(&mut broken, 1.0e-2);
+    assert_eq!(report.welded, 3 * clean - mesh.positions().len());
+    assert_eq!(report.degenerate, 2);
+    assert_eq!(report.flipped, 1);
+    assert_eq!(broken.tri_faces().len(), clean);
+    let volume = 100.0 * 200.0 * 300.0;
+    assert!((mesh_volume(&broken) - volume).abs() < volume * 1.0e-3);
+
+    // A clean tessellation is already wound outward.
+    let mut tessellated = mesh.clone();
+    assert_eq!(fix_normals(&mut tessellated), 0);
+    Ok(())
+}
+
+#[test]
+fn mesh_check_locates_holes_and_misoriented_triangles() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let options = TessellationOptions {
+        check_watertight: true,
+        ..TessellationOptions::default()
+    };
+    let mesh = triangulate_solid(&solid, &options);
+    let check = check_mesh(&mesh);
+    assert!(check.is_ok(), "{check}");
+
+    let faces: Vec<[usize; 3]> = mesh
+        .tri_faces()
+        .iter()
+        .map(|face| face.map(|corner| corner.pos))
+        .collect();
+    let rebuilt = |faces: &[[usize; 3]]| {
+        PolygonMesh::new(
+            StandardAttributes {
+                positions: mesh.positions().to_vec(),
+                ..Default::default()
+            },
+            Faces::from_iter(faces.iter().copied()),
+        )
+    };
+    // Without its first triangle the box has a three-edge hole where the triangle was.
+    let check = check_mesh(&rebuilt(&faces[1..]));
+    assert!(!check.is_watertight());
+    assert_eq!(check.holes.len(), 1);
+    assert_eq!(check.holes[0].edges, 3);
+    let center = faces[0]
+        .iter()
+        .fold(Vector3::new(0.0, 0.0, 0.0), |sum, &v| {
+            sum + mesh.positions()[v].to_vec() / 3.0
+        });
+    assert!((check.holes[0].center.to_vec() - center).magnitude() < 1.0e-9);
+    assert!(check.to_string().starts_with("1 hole(s): 3 edges"));
+
+    // A flipped triangle runs its edges the same way as its neighbours do.
+    let mut flipped = faces.clone();
+    flipped[0].swap(1, 2);
+    let check = check_mesh(&rebuilt(&flipped));
+    assert!(check.is_watertight());
+    assert!(!check.is_ok());
+    assert_eq!(check.misoriented.len(), 3);
+
+    let path = temp_path("checked.obj");
+    export_obj(&solid, &path, &options)?;
+    let _ = fs::remove_file(&path);
+    Ok(())
+}
+
+#[test]
+fn decimation_meets_the_target_and_keeps_the_shape() -> Result<()> {
+    let solid = SolidBuilder::cylinder_z(Point3::new(0.0, 0.0, 0.0), 500.0, 200.0)?;
+    let mut mesh = triangulate_solid(&solid, &TessellationOptions::chordal(0.05));
+    let before = mesh.tri_faces().len();
+    let volume = mesh_volume(&mesh);
+    let target = before / 4;
+
+    let removed = decimate_mesh(&mut mesh, target);
+    assert_eq!(removed, before - mesh.tri_faces().len());
+    assert!(mesh.tri_faces().len() <= target);
+    assert!((mesh_volume(&mesh) - volume).abs() < volume * 0.02);
+    assert_eq!(decimate_mesh(&mut mesh, before), 0);
+    Ok(())
+}
+
+#[test]
+fn cancelled_triangulation_stops() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let cancel = AtomicBool::new(true);
+    let ctx = OpContext::new().with_cancel(&cancel);
+    let result = triangulate_solid_with(&solid, &TessellationOptions::default(), &ctx);
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn triangulation_reports_progress_per_face() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let options = TessellationOptions::default();
+    let reports = Mutex::new(Vec::new());
+    let report = |fraction: f32| reports.lock().unwrap().push(fraction);
+    let ctx = OpContext::new().with_progress(&report);
+    let mesh = triangulate_solid_with(&solid, &options, &ctx)?;
+    assert_eq!(
+        mesh.tri_faces().len(),
+        triangulate_solid(&solid, &options).tri_faces().len()
+    );
+
+    let mut reports = reports.into_inner().unwrap();
+    // Start, one report per face of the box, and the end.
+    assert_eq!(reports.len(), 8);
+    reports.sort_by(f32::total_cmp);
+    reports.dedup();
+    assert_eq!(reports.len(), 8);
+    assert_eq!(reports.last(), Some(&1.0));
+    Ok(())
+}
+
+#[test]
+fn triangulate_many_keeps_input_order() -> Result<()> {
+    let solids = [
+        SolidBuilder::box_solid(100.0, 200.0, 300.0)?,
+        SolidBuilder::box_solid(10.0, 10.0, 10.0)?,
+        SolidBuilder::box_solid(5000.0, 20.0, 40.0)?,
+    ];
+    let refs: Vec<_> = solids.iter().collect();
+    let options = TessellationOptions::default();
+    let progress = Mutex::new(0.0f32);
+    let report = |fraction: f32| {
+        let mut last = progress.lock().unwrap();
+        *last = last.max(fraction);
+    };
+    let ctx = OpContext::new().with_progress(&report);
+    let meshes = triangulate_many_with(&refs, &options, &ctx)?;
+    assert_eq!(meshes.len(), solids.len());
+    for (solid, mesh) in solids.iter().zip(&meshes) {
+        assert_eq!(
+            mesh.positions(),
+            triangulate_solid(solid, &options).positions()
+        );
+    }
+    assert_eq!(*progress.lock().unwrap(), 1.0);
+    assert_eq!(triangulate_many(&refs, &options).len(), solids.len());
+
+    let cancel = AtomicBool::new(true);
+    let ctx = OpContext::new().with_cancel(&cancel);
+    assert!(triangulate_many_with(&refs, &options, &ctx).is_err());
+    Ok(())
+}
+
+#[test]
+fn project_round_trips_in_both_formats() -> Result<()> {
+    let mut project = Project::default();
+    let mut parameters = ParameterSet::new();
+    parameters.insert("Width".to_string(), ParameterValue::Number(100.0));
+    let element = BimElement::new(
+        Guid::new(),
+        "Box",
+        BimCategory::Generic,
+        parameters,
+        SolidBuilder::box_solid(100.0, 200.0, 300.0)?,
+    );
+    let guid = element.guid;
+    let storey = project.model.default_storey().expect("default storey");
+    project.model.add_element(element, storey)?;
+    project.camera = Some(ProjectCamera {
+        target: [0.0, 0.0, 0.0],
+        position: [500.0, 400.0, 300.0],
+        up: [0.0, 0.0, 1.0],
+        fov_deg: 45.0,
+    });
+
+    for file_name in ["project.cxp", "project.cxpb"] {
+        let path = temp_path(file_name);
+        save_project(&project, &path)?;
+        let loaded = load_project(&path)?;
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded.camera, project.camera);
+        assert_eq!(loaded.model.layers, project.model.layers);
+        let element = loaded
+            .model
+            .element(guid)
+            .expect("element survives round trip");
+        assert!(matches!(
+            element.parameters.get("Width"),
+            Some(ParameterValue::Number(width)) if *width == 100.0
+        ));
+        assert_eq!(
+            loaded.model.storey_of(guid).map(|storey| storey.guid),
+            Some(storey)
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn project_stores_brep_geometry_and_reads_version_1() -> Result<()> {
+    let mut project = Project::default();
+    let element = box_element("Box", 100.0)?;
+    let guid = element.guid;
+    let storey = project.model.default_storey().expect("default storey");
+    project.model.add_element(element, storey)?;
+    let volume = |project: &Project| {
+        mass_properties(&project.model.element(guid).expect("element").geometry).volume
+    };
+
+    let path = temp_path("brep_project.cxp");
+    save_project(&project, &path)?;
+    let text = fs::read_to_string(&path)?;
+    let _ = fs::remove_file(&path);
+    let file: serde_json::Value = serde_json::from_str(&text)?;
+    assert_eq!(file["version"], 2);
+    let geometry = &file["project"]["model"]["elements"][0]["geometry"];
+    assert!(geometry["shells"][0]["faces"].is_array());
+    let loaded = read_project(text.as_bytes())?;
+    assert!((volume(&loaded) - volume(&project)).abs() < 1.0e-6);
+
+    // Version 1 wrote the kernel's own serialization of each solid.
+    let old = serde_json::json!({
+        "format": "cryxtal-project",
+        "version": 1,
+        "project": &project,
+    });
+    let loaded = read_project(&serde_json::to_vec(&old)?)?;
+    assert!((volume(&loaded) - volume(&project)).abs() < 1.0e-6);
+    Ok(())
+}
+
+/// Unit tetrahedron as written by B-rep format version 1. Kept verbatim: it must keep loading.
+const BREP_V1_TETRAHEDRON: &str = r#"{
+  "format": "cryxtal-brep",
+  "version": 1,
+  "solid": {
+    "shells": [
+      {
+        "vertices": [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        "edges": [
+          { "vertices": [0, 1], "curve": { "type": "line", "from": [0.0, 0.0, 0.0], "to": [1.0, 0.0, 0.0] } },
+          { "vertices": [1, 2], "curve": { "type": "line", "from": [1.0, 0.0, 0.0], "to": [0.0, 1.0, 0.0] } },
+          { "vertices": [2, 0], "curve": { "type": "line", "from": [0.0, 1.0, 0.0], "to": [0.0, 0.0, 0.0] } },
+          { "vertices": [0, 3], "curve": { "type": "line", "from": [0.0, 0.0, 0.0], "to": [0.0, 0.0, 1.0] } },
+          { "vertices": [1, 3], "curve": { "type": "line", "from": [1.0, 0.0, 0.0], "to": [0.0, 0.0, 1.0] } },
+          { "vertices": [2, 3], "curve": { "type": "line", "from": [0.0, 1.0, 0.0], "to": [0.0, 0.0, 1.0] } }
+        ],
+        "faces": [
+          {
+            "boundaries": [[{ "edge": 2, "forward": false }, { "edge": 1, "forward": false }, { "edge": 0, "forward": false }]],
+            "forward": true,
+            "surface": { "type": "plane", "origin": [0.0, 0.0, 0.0], "u_axis": [0.0, 1.0, 0.0], "v_axis": [1.0, 0.0, 0.0] }
+          },
+          {
+            "boundaries": [[{ "edge": 0, "forward": true }, { "edge": 4, "forward": true }, { "edge": 3, "forward": false }]],
+            "forward": true,
+            "surface": { "type": "plane", "origin": [0.0, 0.0, 0.0], "u_axis": [1.0, 0.0, 0.0], "v_axis": [0.0, 0.0, 1.0] }
+          },
+          {
+            "boundaries": [[{ "edge": 3, "forward": true }, { "edge": 5, "forward": false }, { "edge": 2, "forward": true }]],
+            "forward": true,
+            "surface": { "type": "plane", "origin": [0.0, 0.0, 0.0], "u_axis": [0.0, 0.0, 1.0], "v_axis": [0.0, 1.0, 0.0] }
+          },
+          {
+            "boundaries": [[{ "edge": 1, "forward": true }, { "edge": 5, "forward": true }, { "edge": 4, "forward": false }]],
+            "forward": true,
+            "surface": { "type": "plane", "origin": [1.0, 0.0, 0.0], "u_axis": [-1.0, 1.0, 0.0], "v_axis": [-1.0, 0.0, 1.0] }
+          }
+        ]
+      }
+    ]
+  }
+}"#;
+
+#[test]
+fn brep_round_trips_and_reads_version_one() -> Result<()> {
+    let solid = SolidBuilder::cylinder_z(Point3::new(0.0, 0.0, 0.0), 50.0, 200.0)?;
+    let path = temp_path("cylinder.cxbrep");
+    export_brep(&solid, &path)?;
+    let loaded = import_brep(&path)?;
+    let _ = fs::remove_file(&path);
+    assert_eq!(
+        BrepSolid::from_solid(&loaded),
+        BrepSolid::from_solid(&solid)
+    );
+    let volume = mass_properties(&loaded).volume;
+    assert!((volume - mass_properties(&solid).volume).abs() < 1e-6);
+
+    let tetrahedron = brep_from_str(BREP_V1_TETRAHEDRON)?;
+    assert_eq!(tetrahedron.face_iter().count(), 4);
+    assert!((mass_properties(&tetrahedron).volume - 1.0 / 6.0).abs() < 1e-9);
+
+    let future = BREP_V1_TETRAHEDRON.replacen(
+        "\"version\": 1",
+        &format!("\"version\": {}", BREP_FORMAT_VERSION + 1),
+        1,
+    );
+    assert!(brep_from_str(&future).is_err());
+    // Face 3 loses an edge, leaving its boundary open.
+    let open = BREP_V1_TETRAHEDRON.replacen(", { \"edge\": 4, \"forward\": false }", "", 1);
+    assert!(brep_from_str(&open).is_err());
+    Ok(())
+}
+
+fn box_element(name: &str, size: f64) -> Result<BimElement> {
+    Ok(BimElement::new(
+        Guid::new(),
+        name,
+        BimCategory::Generic,
+        ParameterSet::new(),
+        SolidBuilder::box_solid(size, size, size)?,
+    ))
+}
+
+#[test]
+fn streaming_export_writes_one_object_per_element() -> Result<()> {
+    let elements = vec![box_element("A", 100.0)?, box_element("B", 200.0)?];
+
+    let obj = temp_path("model.obj");
+    let written = export_model_mesh_streaming(&elements, &obj, &TessellationOptions::default())?;
+    let text = fs::read_to_string(&obj)?;
+    let _ = fs::remove_file(&obj);
+    assert_eq!(written, 2);
+    assert_eq!(
+        text.lines().filter(|line| line.starts_with("o ")).count(),
+        2
+    );
+
+    let gltf = temp_path("model.gltf");
+    export_model_mesh_streaming(&elements, &gltf, &TessellationOptions::default())?;
+    let document: serde_json::Value = serde_json::from_str(&fs::read_to_string(&gltf)?)?;
+    let bin = gltf.with_extension("bin");
+    let bin_len = fs::metadata(&bin)?.len();
+    let _ = fs::remove_file(&gltf);
+    let _ = fs::remove_file(&bin);
+    assert_eq!(document["meshes"].as_array().map(Vec::len), Some(2));
+    assert_eq!(document["buffers"][0]["byteLength"].as_u64(), Some(bin_len));
+    Ok(())
+}
+
+#[test]
+fn merged_export_writes_single_object() -> Result<()> {
+    let elements = vec![box_element("A", 100.0)?, box_element("B", 200.0)?];
+    let obj = temp_path("merged.obj");
+    export_model_mesh(&elements, &obj, &TessellationOptions::default())?;
+    let text = fs::read_to_string(&obj)?;
+    let _ = fs::remove_file(&obj);
+    assert_eq!(
+        text.lines().filter(|line| line.starts_with("o ")).count(),
+        1
+    );
+    Ok(())
+}
+
+#[test]
+fn split_export_groups_elements_by_level_and_layer() -> Result<()> {
+    let mut model = BimModel::new("Split");
+    let ground = model.default_storey().expect("default storey");
+    let building = model.site.buildings[0].guid;
+    let upper = model.add_storey(building, "Level 1", 3000.0)?;
+    let mut roof = box_element("Roof", 100.0)?;
+    roof.insert_parameter("Layer", ParameterValue::Text("Roof / Cladding".to_string()));
+    let wall = box_element("Wall", 100.0)?;
+    let (roof_guid, wall_guid) = (roof.guid, wall.guid);
+    model.add_element(roof, upper)?;
+    model.add_element(wall, ground)?;
+
+    let levels = split_elements(&model, model.elements(), SplitBy::Level);
+    let names: Vec<&str> = levels.iter().map(|part| part.name.as_str()).collect();
+    assert_eq!(names, ["Level 0", "Level 1"]);
+    assert_eq!(levels[1].elements[0].guid, roof_guid);
+    assert_eq!(levels[0].file_stem, "Level_0");
+
+    let layers = split_elements(&model, model.elements(), SplitBy::Layer);
+    let stems: Vec<&str> = layers.iter().map(|part| part.file_stem.as_str()).collect();
+    assert_eq!(stems, ["Roof___Cladding", "Default"]);
+    assert_eq!(layers[1].elements[0].guid, wall_guid);
+    assert!("storey".parse::<SplitBy>().is_ok());
+    assert!("phase".parse::<SplitBy>().is_err());
+    Ok(())
+}
+
+#[test]
+fn packed_mesh_cache_round_trips() -> Result<()> {
+    let element = box_element("A", 1000.0)?;
+    let mesh = triangulate_solid(element.geometry(), &TessellationOptions::default());
+    let packed = PackedMesh::from_mesh(&mesh);
+    let key = geometry_key(element.geometry())?;
+
+    let path = temp_path("cache.cxmesh");
+    save_mesh_cache(
+        &path,
+        [PackedElement {
+            guid: element.guid,
+            name: element.name.clone(),
+            key: Some(key),
+            mesh: packed.clone(),
+        }],
+    )?;
+    let cache = load_mesh_cache(&path)?;
+    let _ = fs::remove_file(&path);
+
+    let cached = &cache[&key];
+    assert_eq!(cached, &packed);
+    let restored = cached.to_mesh();
+    assert_eq!(restored.positions().len(), mesh.positions().len());
+    for (a, b) in restored.positions().iter().zip(mesh.positions()) {
+        assert!((a.x - b.x).abs() < 0.1 && (a.y - b.y).abs() < 0.1 && (a.z - b.z).abs() < 0.1);
+    }
+    // Normals are kept as tessellated rather than recomputed.
+    assert_eq!(restored.normals().len(), mesh.normals().len());
+    for (a, b) in restored.normals().iter().zip(mesh.normals()) {
+        assert!((a - b).magnitude() < 1.0e-6);
+    }
+    assert_ne!(geometry_key(box_element("B", 900.0)?.geometry())?, key);
+    Ok(())
+}
+
+#[test]
+fn through_hole_is_recognized() -> Result<()> {
+    let plate = plate_with_hole(200.0, 200.0, 20.0, 30.0, DEFAULT_SHAPEOPS_TOLERANCE)?;
+    let holes = recognize_holes(&plate, DEFAULT_TESSELLATION_TOLERANCE);
+    assert_eq!(holes.len(), 1);
+    let hole = holes[0];
+    assert!((hole.diameter - 30.0).abs() < 0.5);
+    assert!((hole.depth - 20.0).abs() < 0.5);
+    assert!(hole.through);
+
+    let solid = SolidBuilder::cylinder_z(Point3::new(0.0, 0.0, 0.0), 15.0, 20.0)?;
+    assert!(recognize_holes(&solid, DEFAULT_TESSELLATION_TOLERANCE).is_empty());
+    Ok(())
+}
+
+fn rebar_element(parameters: &[(&str, ParameterValue)]) -> Result<BimElement> {
+    let mut set = ParameterSet::new();
+    for (key, value) in parameters {
+        set.insert(key.to_string(), value.clone());
+    }
+    Ok(BimElement::new(
+        Guid::new(),
+        "Rebar",
+        BimCategory::Rebar,
+        set,
+        SolidBuilder::box_solid(10.0, 10.0, 10.0)?,
+    ))
+}
+
+#[test]
+fn rebar_schedule_groups_bars_and_weighs_them() -> Result<()> {
+    let bar = || {
+        rebar_element(&[
+            ("Diameter", ParameterValue::Number(12.0)),
+            ("Length", ParameterValue::Number(2000.0)),
+            ("PointCount", ParameterValue::Integer(2)),
+        ])
+    };
+    let set = rebar_element(&[
+        ("Shape", ParameterValue::Text("U".to_string())),
+        ("Diameter", ParameterValue::Number(16.0)),
+        ("Length", ParameterValue::Number(15000.0)),
+        ("BarCount", ParameterValue::Integer(10)),
+    ])?;
+    let elements = vec![bar()?, set, bar()?, box_element("Box", 100.0)?];
+
+    let rows = rebar_schedule(&elements);
+
+    assert_eq!(rows.len(), 2);
+    assert_eq!((rows[0].shape.as_str(), rows[0].count), ("Straight", 2));
+    assert!((rows[0].weight - 4.0 * 0.888).abs() < 1.0e-9);
+    assert_eq!(
+        (rows[1].shape.as_str(), rows[1].count, rows[1].mark),
+        ("U", 10, 2)
+    );
+    assert!((rows[1].cut_length - 1500.0).abs() < 1.0e-9);
+
+    let mut csv = Vec::new();
+    write_rebar_schedule_csv(&rows, &mut csv)?;
+    let csv = String::from_utf8(csv)?;
+    assert_eq!(csv.lines().count(), 3);
+    assert_eq!(csv.lines().nth(2), Some("2,U,16.0,1500,10,15000,23.70"));
+    Ok(())
+}
+
+#[test]
+fn bvbs_export_encodes_legs_and_bends() -> Result<()> {
+    let bent = || {
+        rebar_element(&[
+            ("Diameter", ParameterValue::Number(12.0)),
+            ("PointCount", ParameterValue::Integer(3)),
+            ("Point1X", ParameterValue::Number(0.0)),
+            ("Point1Y", ParameterValue::Number(0.0)),
+            ("Point1Z", ParameterValue::Number(0.0)),
+            ("Point2X", ParameterValue::Number(1000.0)),
+            ("Point2Y", ParameterValue::Number(0.0)),
+            ("Point2Z", ParameterValue::Number(0.0)),
+            ("Point3X", ParameterValue::Number(1000.0)),
+            ("Point3Y", ParameterValue::Number(0.0)),
+            ("Point3Z", ParameterValue::Number(500.0)),
+        ])
+    };
+    let set = rebar_element(&[
+        ("Diameter", ParameterValue::Number(10.0)),
+        ("Shape", ParameterValue::Text("U".to_string())),
+        ("Leg", ParameterValue::Number(200.0)),
+        ("BarCount", ParameterValue::Integer(4)),
+        ("Length", ParameterValue::Number(4.0 * 1350.0)),
+    ])?;
+    let bars = bvbs_bars(&[bent()?, bent()?, set])?;
+    assert_eq!(bars.len(), 2);
+    assert_eq!(bars[0].legs, [200.0, 971.0, 200.0]);
+    assert_eq!(bars[0].count, 4);
+    assert_eq!(bars[1].count, 2);
+    assert_eq!(bars[1].angles, [90.0]);
+    assert_eq!(bars[1].mandrel, 48.0);
+
+    let mut out = Vec::new();
+    write_bvbs(&bars, &BvbsOptions::default(), &mut out)?;
+    let text = String::from_utf8(out)?;
+    let line = text.lines().nth(1).expect("second bar");
+    assert!(line.starts_with("BF2D@Hj@r@i@p2@l"));
+    assert!(line.contains("@n2@e"));
+    assert!(line.contains("@d12@gB500B@s48@v@Gl1000@w90@l500@w0@C"));
+    let (body, checksum) = line.split_at(line.rfind("@C").expect("checksum") + 2);
+    assert_eq!(checksum, format!("{}@", bvbs_checksum(body)));
+
+    let mut twisted = bent()?;
+    twisted
+        .parameters
+        .insert("PointCount".to_string(), ParameterValue::Integer(4));
+    for (key, value) in [("Point4X", 1000.0), ("Point4Y", 400.0), ("Point4Z", 500.0)] {
+        twisted
+            .parameters
+            .insert(key.to_string(), ParameterValue::Number(value));
+    }
+    assert!(bvbs_bars(&[twisted]).is_err());
+    Ok(())
+}
+
+#[test]
+fn bvbs_export_marks_each_cut_length_of_a_set() -> Result<()> {
+    let set = rebar_element(&[
+        ("Diameter", ParameterValue::Number(12.0)),
+        ("BarCount", ParameterValue::Integer(4)),
+        ("Length", ParameterValue::Number(6000.0)),
+        (
+            CUT_LENGTHS_KEY,
+            ParameterValue::Text("1600.0 1500.0 1400.0 1500.0".to_string()),
+        ),
+    ])?;
+    let bars = bvbs_bars(&[set.clone()])?;
+    let legs: Vec<_> = bars
+        .iter()
+        .map(|bar| (bar.legs.clone(), bar.count))
+        .collect();
+    assert_eq!(
+        legs,
+        [(vec![1400.0], 1), (vec![1500.0], 2), (vec![1600.0], 1)]
+    );
+    // The schedule lists the same bars at their own lengths.
+    let rows: Vec<_> = rebar_schedule(&[set.clone()])
+        .iter()
+        .map(|row| (row.cut_length, row.count))
+        .collect();
+    assert_eq!(rows, [(1400.0, 1), (1500.0, 2), (1600.0, 1)]);
+
+    // Sets saved before the list was kept fall back to the mean length.
+    let mut stale = set;
+    stale.parameters.insert(
+        CUT_LENGTHS_KEY.to_string(),
+        ParameterValue::Text("1500.0".to_string()),
+    );
+    let bars = bvbs_bars(&[stale])?;
+    assert_eq!((bars.len(), bars[0].count), (1, 4));
+    assert_eq!(bars[0].legs, [1500.0]);
+    Ok(())
+}
+
+#[test]
+fn pxml_export_writes_panels_in_panel_coordinates() -> Result<()> {
+    let mut model = BimModel::new("Precast");
+    let storey = model.default_storey().expect("default storey");
+    let mut parameters = ParameterSet::new();
+    for (key, value) in [
+        ("StartX", 1000.0),
+        ("StartY", 0.0),
+        ("StartZ", 0.0),
+        ("EndX", 1000.0),
+        ("EndY", 3000.0),
+        ("EndZ", 0.0),
+        ("Thickness", 200.0),
+        ("Height", 2500.0),
+    ] {
+        parameters.insert(key.to_string(), ParameterValue::Number(value));
+    }
+    let wall = BimElement::new(
+        Guid::new(),
+        "W-01",
+        BimCategory::Wall,
+        parameters,
+        SolidBuilder::box_solid(3000.0, 200.0, 2500.0)?,
+    );
+    let wall_guid = wall.guid;
+    model.add_element(wall, storey)?;
+    let door = BimElement::opening(
+        Opening::new(
+            wall_guid,
+            OpeningProfile {
+                width: 900.0,
+                height: 2000.0,
+            },
+            OpeningPlacement {
+                center_x: 1500.0,
+                center_y: 0.0,
+                center_z: 1000.0,
+            },
+        ),
+        "Door",
+        ParameterSet::new(),
+        SolidBuilder::box_solid(900.0, 200.0, 2000.0)?,
+    );
+    model.add_element(door, storey)?;
+    let mut bar = rebar_element(&[
+        ("Diameter", ParameterValue::Number(10.0)),
+        ("StartX", ParameterValue::Number(1000.0)),
+        ("StartY", ParameterValue::Number(500.0)),
+        ("StartZ", ParameterValue::Number(100.0)),
+        ("EndX", ParameterValue::Number(1000.0)),
+        ("EndY", ParameterValue::Number(500.0)),
+        ("EndZ", ParameterValue::Number(2400.0)),
+    ])?;
+    bar.host = Some(wall_guid);
+    model.add_element(bar, storey)?;
+    let mut set = rebar_element(&[
+        ("Diameter", ParameterValue::Number(12.0)),
+        ("Spacing", ParameterValue::Number(200.0)),
+        ("BarCount", ParameterValue::Integer(2)),
+        (
+            BAR_CORNERS_KEY,
+            ParameterValue::Text(
+                "1050.0,200.0,50.0 1050.0,200.0,2450.0;1050.0,400.0,50.0 1050.0,400.0,2450.0"
+                    .to_string(),
+            ),
+        ),
+    ])?;
+    set.host = Some(wall_guid);
+    model.add_element(set, storey)?;
+    let mut anchor = box_element("Lifting anchor", 50.0)?;
+    anchor.host = Some(wall_guid);
+    model.add_element(anchor, storey)?;
+
+    let mut out = Vec::new();
+    write_pxml(&model, "A-17", &mut out)?;
+    let xml = String::from_utf8(out)?;
+    assert_eq!(xml.matches("<Product>").count(), 1);
+    assert!(xml.contains("<OrderNo>A-17</OrderNo>"));
+    assert!(xml.contains("<ElementNo>W-01</ElementNo>"));
+    assert!(xml.contains("<SVertex><X>3000.0</X><Y>2500.0</Y></SVertex>"));
+    assert!(xml.contains("<Name>Door</Name>"));
+    assert!(xml.contains("<SVertex><X>1050.0</X><Y>0.0</Y></SVertex>"));
+    assert!(xml.contains("<SVertex><X>500.0</X><Y>100.0</Y><Z>100.0</Z></SVertex>"));
+    assert!(xml.contains("<SVertex><X>500.0</X><Y>2400.0</Y><Z>100.0</Z></SVertex>"));
+    assert_eq!(xml.matches("<Diameter>12.0</Diameter>").count(), 2);
+    assert!(xml.contains("<SVertex><X>400.0</X><Y>2450.0</Y><Z>50.0</Z></SVertex>"));
+    assert!(xml.contains("<Name>Lifting anchor</Name>"));
+    Ok(())
+}
+
+#[test]
+fn ifc_export_writes_spatial_structure_and_voids() -> Result<()> {
+    let mut model = BimModel::new("Office");
+    let storey = model.default_storey().expect("default storey");
+    let mut parameters = ParameterSet::new();
+    for (key, value) in [
+        ("StartX", 0.0),
+        ("StartY", 0.0),
+        ("StartZ", 0.0),
+        ("EndX", 4000.0),
+        ("EndY", 0.0),
+        ("EndZ", 0.0),
+        ("Length", 4000.0),
+        ("Thickness", 200.0),
+        ("Height", 2800.0),
+    ] {
+        parameters.insert(key.to_string(), ParameterValue::Number(value));
+    }
+    let wall = BimElement::new(
+        Guid::new(),
+        "W-01",
+        BimCategory::Wall,
+        parameters,
+        SolidBuilder::box_solid(4000.0, 200.0, 2800.0)?,
+    );
+    let wall_guid = wall.guid;
+    let mut lining = wall.clone();
+    lining.guid = Guid::new();
+    lining.name = "W-02".to_string();
+    let lining_guid = lining.guid;
+    model.add_element(wall, storey)?;
+    model.add_element(lining, storey)?;
+    let mut door = BimElement::opening(
+        Opening::new(
+            wall_guid,
+            OpeningProfile {
+                width: 900.0,
+                height: 2100.0,
+            },
+            OpeningPlacement {
+                center_x: 1000.0,
+                center_y: 0.0,
+                center_z: 1050.0,
+            },
+        ),
+        "Door",
+        ParameterSet::new(),
+        SolidBuilder::box_solid(900.0, 200.0, 2100.0)?,
+    );
+    // Also cuts through the lining, but is one opening.
+    door.cuts.push(lining_guid);
+    model.add_element(door, storey)?;
+    model.add_element(box_element("Pier", 300.0)?, storey)?;
+
+    let mut out = Vec::new();
+    write_ifc(
+        &model,
+        model.elements(),
+        &TessellationOptions::default(),
+        "office.ifc",
+        &mut out,
+    )?;
+    let text = String::from_utf8(out)?;
+    assert!(text.contains("FILE_SCHEMA(('IFC4'));"));
+    assert_eq!(text.matches("=IFCBUILDINGSTOREY(").count(), 1);
+    assert_eq!(text.matches("=IFCWALL(").count(), 2);
+    assert_eq!(text.matches("=IFCOPENINGELEMENT(").count(), 1);
+    assert_eq!(text.matches("=IFCRELVOIDSELEMENT(").count(), 2);
+    // The wall is swept from its footprint, the pier triangulated from its solid.
+    assert!(text.contains("=IFCEXTRUDEDAREASOLID("));
+    assert!(text.contains("=IFCTRIANGULATEDFACESET("));
+    assert_eq!(
+        text.matches("=IFCRELCONTAINEDINSPATIALSTRUCTURE(").count(),
+        1
+    );
+    assert!(text.contains("IFCPROPERTYSINGLEVALUE('Thickness',$,IFCREAL(200.),$)"));
+    let wall_id = ifc_guid(wall_guid);
+    assert_eq!(wall_id.len(), 22);
+    assert!(text.contains(&format!("IFCWALL('{wall_id}',$,'W-01',")));
+
+    let data = &text[text.find("DATA;").expect("data section")..];
+    let mut ids = HashSet::new();
+    let mut global_ids = HashSet::new();
+    for line in data.lines().filter(|line| line.starts_with('#')) {
+        let id = &line[..line.find('=').expect("entity id")];
+        assert!(ids.insert(id), "{id} is defined twice");
+        // Rooted entities open with their 22-character GlobalId.
+        let global_id = line
+            .find("('")
+            .and_then(|start| line.get(start + 2..start + 25))
+            .filter(|quoted| quoted.ends_with('\''))
+            .map(|quoted| &quoted[..22]);
+        if let Some(global_id) = global_id {
+            assert!(
+                global_ids.insert(global_id),
+                "GlobalId {global_id} is used twice"
+            );
+        }
+    }
+
+    // Re-exports keep the project's GlobalId.
+    let project_line = |text: &str| {
+        text.lines()
+            .find(|line| line.contains("=IFCPROJECT("))
+            .map(|line| line[line.find('=').expect("entity id")..].to_string())
+    };
+    let mut again = Vec::new();
+    write_ifc(
+        &model,
+        model.elements(),
+        &TessellationOptions::default(),
+        "office.ifc",
+        &mut again,
+    )?;
+    assert_eq!(
+        project_line(&String::from_utf8(again)?),
+        project_line(&text)
+    );
+    Ok(())
+}
+
+#[test]
+fn ifc_export_voids_elements_with_the_elements_cut_from_them() -> Result<()> {
+    let mut model = BimModel::new("Office");
+    let storey = model.default_storey().expect("default storey");
+    let wall = box_element("W-01", 3000.0)?;
+    let wall_guid = wall.guid;
+    let mut lintel = box_element("Lintel", 200.0)?;
+    lintel.category = BimCategory::Beam;
+    lintel.cuts = vec![wall_guid];
+    let lintel_guid = lintel.guid;
+    model.add_element(wall, storey)?;
+    model.add_element(lintel, storey)?;
+
+    let mut out = Vec::new();
+    write_ifc(
+        &model,
+        model.elements(),
+        &TessellationOptions::default(),
+        "office.ifc",
+        &mut out,
+    )?;
+    let text = String::from_utf8(out)?;
+    // The lintel is written as a beam and voids the wall with its shape.
+    assert_eq!(text.matches("=IFCBEAM(").count(), 1);
+    assert_eq!(text.matches("=IFCOPENINGELEMENT(").count(), 1);
+    assert!(text.contains(",$,'Lintel cut',$,$,#"));
+    assert_eq!(text.matches("=IFCRELVOIDSELEMENT(").count(), 1);
+    assert_eq!(text.matches(&ifc_guid(lintel_guid)).count(), 1);
+    Ok(())
+}
+
+#[test]
+fn scan_deviation_measures_points_against_nearest_faces() -> Result<()> {
+    let block = box_element("Block", 1000.0)?;
+    let cloud = "# x y z intensity\n4\n500 500 1004 0.9\n400,400,998\n300;300;1003\n\
+                 -10 500 500\n500 500 1500\n";
+    let points = read_scan_points(cloud.as_bytes())?;
+    assert_eq!(points.len(), 5);
+
+    let rows = scan_deviation([&block], &points, 50.0, DEFAULT_TESSELLATION_TOLERANCE)?;
+    // The point 500 above the block is too far from it to be measured.
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows.iter().map(|row| row.points).sum::<usize>(), 4);
+    let top = rows.iter().find(|row| row.points == 3).expect("top face");
+    assert!((top.min + 2.0).abs() < 1.0e-6);
+    assert!((top.max - 4.0).abs() < 1.0e-6);
+    assert!((top.mean - 5.0 / 3.0).abs() < 1.0e-6);
+    assert!((top.worst() - 4.0).abs() < 1.0e-6);
+    let side = rows.iter().find(|row| row.points == 1).expect("side face");
+    assert!((side.mean - 10.0).abs() < 1.0e-6);
+
+    let mut out = Vec::new();
+    write_deviation_csv(&rows, std::slice::from_ref(&block), &mut out)?;
+    let csv = String::from_utf8(out)?;
+    assert!(csv.starts_with("Element,Face,Points,Min,Max,Mean\n"));
+    assert!(csv.contains(",3,-2.00,4.00,1.67\n"));
+    assert!(scan_deviation([&block], &points, 0.0, DEFAULT_TESSELLATION_TOLERANCE).is_err());
+    Ok(())
+}
+
+#[test]
+fn ply_point_clouds_read_in_ascii_and_binary_and_decimate() -> Result<()> {
+    let ascii = "ply\nformat ascii 1.0\ncomment scan\nelement vertex 3\nproperty float x\n\
+                 property float y\nproperty float z\nproperty uchar red\nproperty uchar green\n\
+                 property uchar blue\nelement face 1\nproperty list uchar int vertex_indices\n\
+                 end_header\n0 0 0 255 0 0\n10 0 0 0 255 0\n500 0 0 0 0 255\n3 0 1 2\n";
+    let cloud = read_ply(ascii.as_bytes())?;
+    assert_eq!(cloud.points[2], Point3::new(500.0, 0.0, 0.0));
+    assert_eq!(cloud.colors, vec![[255, 0, 0], [0, 255, 0], [0, 0, 255]]);
+
+    // The same points as big-endian doubles, behind an intensity, without colors.
+    let mut binary = b"ply\nformat binary_big_endian 1.0\nelement vertex 3\n\
+                       property uchar intensity\nproperty double x\nproperty double y\n\
+                       property double z\nend_header\n"
+        .to_vec();
+    for x in [0.0f64, 10.0, 500.0] {
+        binary.push(7);
+        for value in [x, 0.0, 0.0] {
+            binary.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+    let path = temp_path("scan.ply");
+    fs::write(&path, &binary)?;
+    let points = import_point_cloud(&path, None)?;
+    assert_eq!(points.points, cloud.points);
+    assert!(points.colors.is_empty());
+
+    // A 100 cell keeps the first of the two points within 10 of each other.
+    let decimated = import_point_cloud(&path, Some(100.0))?;
+    assert_eq!(
+        decimated.points,
+        vec![Point3::new(0.0, 0.0, 0.0), Point3::new(500.0, 0.0, 0.0)]
+    );
+    let mut colored = cloud.clone();
+    assert_eq!(colored.decimate(100.0)?, 1);
+    assert_eq!(colored.colors, vec![[255, 0, 0], [0, 0, 255]]);
+    assert!(colored.decimate(0.0).is_err());
+    fs::remove_file(&path)?;
+
+    assert!(read_ply("ply\nformat ascii 1.0\nend_header\n".as_bytes()).is_err());
+    Ok(())
+}
+
+#[test]
+fn obj_import_wraps_mesh_as_reference_element() -> Result<()> {
+    // A square and a triangle sharing its right edge, as a surveyor's terrain patch.
+    let path = temp_path("terrain.obj");
+    fs::write(
+        &path,
+        "v 0 0 0\nv 1000 0 0\nv 1000 1000 0\nv 0 1000 0\nv 2000 500 100\n\
+         f 1 2 3 4\nf 2 5 3\n",
+    )?;
+    let mesh = import_obj(&path)?;
+    let _ = fs::remove_file(&path);
+    assert_eq!(mesh.positions().len(), 5);
+
+    let terrain = reference_mesh_element("Terrain", &mesh)?;
+    assert_eq!(terrain.category, BimCategory::Generic);
+    assert!(terrain.is_reference_mesh());
+    assert!(!box_element("Block", 100.0)?.is_reference_mesh());
+    assert_eq!(terrain.geometry.face_iter().count(), 3);
+    // The square's diagonal and the shared edge are single edges between two faces.
+    assert_eq!(terrain.geometry.edge_iter().count(), 9);
+    let unique: HashSet<_> = terrain.geometry.edge_iter().map(|edge| edge.id()).collect();
+    assert_eq!(unique.len(), 7);
+    assert!(
+        !triangulate_solid(&terrain.geometry, &TessellationOptions::default())
+            .positions()
+            .is_empty()
+    );
+
+    assert!(import_obj(temp_path("missing.obj")).is_err());
+
+    // Terrain is context: not counted, scheduled or exported, and not editable.
+    let mut model = BimModel::new("Site");
+    let storey = model.default_storey().expect("default storey");
+    let terrain_guid = terrain.guid;
+    model.add_element(terrain, storey)?;
+    model.add_element(box_element("Block", 100.0)?, storey)?;
+    assert_eq!(takeoff(&model).total.count, 1);
+    assert!(
+        bill_of_materials(&model)
+            .iter()
+            .all(|row| row.name != "Terrain")
+    );
+    let mut csv = Vec::new();
+    write_parameters_csv(&model, &mut csv)?;
+    assert!(!String::from_utf8(csv)?.contains("Terrain"));
+    let mut ifc = Vec::new();
+    write_ifc(
+        &model,
+        model.elements(),
+        &TessellationOptions::default(),
+        "site.ifc",
+        &mut ifc,
+    )?;
+    assert!(!String::from_utf8(ifc)?.contains("Terrain"));
+    let edit = ModelCommand::SetParameter {
+        guid: terrain_guid,
+        name: "Material".to_string(),
+        value: Some(ParameterValue::Text("Soil".to_string())),
+    };
+    assert!(edit.apply(&mut model).is_err());
+    let csv = format!("GUID,Material\n{terrain_guid},Soil\n");
+    assert!(read_parameters_csv(&model, csv.as_bytes()).is_err());
+    Ok(())
+}
+
+#[test]
+fn distance_finds_closest_points_between_elements() -> Result<()> {
+    let near = box_element("Near", 100.0)?;
+    let mut far = box_element("Far", 100.0)?;
+    far.geometry = transform_solid(
+        &far.geometry,
+        Matrix4::from_translation(Vector3::new(130.0, 140.0, 50.0)),
+    );
+
+    // The boxes overlap in Z, so the gap runs between the vertical edges at (100, 100).
+    let (gap, on_near, on_far) = distance(&near, &far)?;
+    assert!((gap - 50.0).abs() < 1.0e-6);
+    assert!((on_near - Point3::new(100.0, 100.0, on_near.z)).magnitude() < 1.0e-6);
+    assert!((on_far - Point3::new(130.0, 140.0, on_far.z)).magnitude() < 1.0e-6);
+    assert!((50.0..=100.0).contains(&on_near.z));
+
+    let touching = box_element("Touching", 50.0)?;
+    assert!(distance(&near, &touching)?.0 < 1.0e-9);
+    Ok(())
+}
+
+#[test]
+fn glb_export_writes_colored_nodes_and_quantized_positions() -> Result<()> {
+    let mut red = box_element("Red", 100.0)?;
+    red.insert_parameter("Layer", ParameterValue::Text("Concrete".to_string()));
+    let plain = box_element("Plain", 200.0)?;
+    let options = GltfOptions {
+        layers: vec![
+            Layer::new("Default", [200, 200, 200, 255]),
+            Layer::new("Concrete", [255, 0, 0, 128]),
+        ],
+        quantize: true,
+        ..GltfOptions::default()
+    };
+
+    let mut glb = Vec::new();
+    write_glb([&red, &plain], &options, &mut glb)?;
+    let word = |offset: usize| u32::from_le_bytes(glb[offset..offset + 4].try_into().unwrap());
+    assert_eq!(&glb[..4], b"glTF");
+    assert_eq!(word(4), 2);
+    assert_eq!(word(8) as usize, glb.len());
+    let json_length = word(12) as usize;
+    assert_eq!(&glb[16..20], b"JSON");
+    let document: serde_json::Value = serde_json::from_slice(&glb[20..20 + json_length])?;
+    let bin_length = word(20 + json_length) as usize;
+    assert_eq!(&glb[24 + json_length..28 + json_length], b"BIN\0");
+    assert_eq!(document["buffers"][0]["byteLength"], bin_length);
+
+    // Both elements and the root node.
+    let nodes = document["nodes"].as_array().expect("nodes");
+    assert_eq!(nodes.len(), 3);
+    assert_eq!(nodes[0]["extras"]["guid"], red.guid.to_string());
+    assert_eq!(nodes[0]["extras"]["name"], "Red");
+    assert_eq!(nodes[0]["translation"][0], 50.0);
+    let materials = document["materials"].as_array().expect("materials");
+    assert_eq!(materials.len(), 2);
+    assert_eq!(materials[0]["name"], "Concrete");
+    assert_eq!(materials[0]["alphaMode"], "BLEND");
+    assert_eq!(document["meshes"][1]["primitives"][0]["material"], 1);
+    assert_eq!(document["extensionsRequired"][0], "KHR_mesh_quantization");
+    assert_eq!(document["accessors"][0]["componentType"], 5122);
+    assert_eq!(document["accessors"][0]["max"][0], 32767);
+    Ok(())
+}
+
+#[test]
+fn streaming_glb_matches_in_memory_glb() -> Result<()> {
+    let elements = vec![box_element("A", 100.0)?, box_element("B", 200.0)?];
+    let options = GltfOptions::default();
+    let mut expected = Vec::new();
+    write_glb(&elements, &options, &mut expected)?;
+
+    let path = temp_path("streamed.glb");
+    let written = export_glb_streaming(&elements, &path, &options)?;
+    assert_eq!(written, 2);
+    assert_eq!(std::fs::read(&path)?, expected);
+    assert!(!path.with_extension("glb.part").exists());
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn weld_schedule_sums_edge_lengths_and_drawing_shows_symbols() -> Result<()> {
+    let mut element = box_element("Plate", 100.0)?;
+    let fillet = |edge| WeldAnnotation {
+        edge,
+        kind: WeldKind::Fillet,
+        size: 6.0,
+        side: WeldSide::Both,
+        all_around: false,
+        field: false,
+    };
+    element.add_weld(fillet(0))?;
+    element.add_weld(fillet(1))?;
+    element.add_weld(WeldAnnotation {
+        kind: WeldKind::SingleV,
+        size: 10.0,
+        side: WeldSide::Arrow,
+        field: true,
+        ..fillet(2)
+    })?;
+    assert!(element.add_weld(fillet(12)).is_err());
+
+    let elements = vec![element];
+    let rows = weld_schedule(&elements);
+    assert_eq!(rows.len(), 2);
+    assert_eq!((rows[0].kind, rows[0].count), (WeldKind::Fillet, 2));
+    assert!((rows[0].length - 200.0).abs() < 1.0e-6);
+    assert_eq!((rows[1].kind, rows[1].count), (WeldKind::SingleV, 1));
+
+    let mut svg = Vec::new();
+    write_weld_drawing_svg(&elements, &mut svg)?;
+    let svg = String::from_utf8(svg)?;
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(svg.matches("<polyline").count(), 12 + 3);
+    assert_eq!(svg.matches("<text").count(), 3 * 2 + 2);
+    Ok(())
+}
+
+#[test]
+fn dxf_plan_cuts_elements_on_their_layers() -> Result<()> {
+    let mut model = BimModel::new("Section");
+    model.layers = vec![
+        Layer::new("Structure", [255, 0, 0, 255]),
+        Layer::new("Walls: fire", [0, 0, 250, 255]),
+    ];
+    let storey = model.default_storey().expect("default storey");
+    let mut wall = box_element("Wall", 100.0)?;
+    wall.insert_parameter("Layer", ParameterValue::Text("Walls: fire".to_string()));
+    model.add_element(wall, storey)?;
+    model.add_element(box_element("Column", 50.0)?, storey)?;
+
+    let mut dxf = Vec::new();
+    write_dxf_plan(&model, 75.0, &TessellationOptions::default(), &mut dxf)?;
+    let dxf = String::from_utf8(dxf)?;
+    // Only the 100 box reaches the cut, as a closed outline on its layer.
+    assert_eq!(dxf.matches("POLYLINE").count(), 1);
+    assert!(dxf.contains("  8\nWalls_ fire\n 66\n1\n 70\n1\n"));
+    assert!(dxf.contains("  2\nStructure\n 70\n0\n 62\n1\n"));
+    assert!(dxf.contains("  2\nWalls_ fire\n 70\n0\n 62\n5\n"));
+    assert!(dxf.trim_end().ends_with("EOF"));
+
+    let mut low = Vec::new();
+    write_dxf_plan(&model, 25.0, &TessellationOptions::default(), &mut low)?;
+    assert_eq!(String::from_utf8(low)?.matches("POLYLINE").count(), 2);
+    assert!(write_dxf_plan(&model, 500.0, &TessellationOptions::default(), Vec::new()).is_err());
+    Ok(())
+}
+
+#[test]
+fn plan_exports_hatch_cuts_by_category() -> Result<()> {
+    let mut model = BimModel::new("Hatch");
+    let storey = model.default_storey().expect("default storey");
+    let mut wall = box_element("Wall", 1000.0)?;
+    wall.category = BimCategory::Wall;
+    model.add_element(wall, storey)?;
+
+    let mut dxf = Vec::new();
+    write_dxf_plan(&model, 500.0, &TessellationOptions::default(), &mut dxf)?;
+    let dxf = String::from_utf8(dxf)?;
+    // 45° lines 100 mm apart across a 1 m square.
+    let lines = dxf.matches("  0\nLINE\n").count();
+    assert!((13..=15).contains(&lines), "{lines} hatch lines");
+    assert_eq!(dxf.matches("POLYLINE").count(), 1);
+
+    let mut svg = Vec::new();
+    write_plan_svg(&model, None, &TessellationOptions::default(), &mut svg)?;
+    let svg = String::from_utf8(svg)?;
+    assert_eq!(svg.matches(r#"stroke-width="0.5""#).count(), 1);
+
+    // Generic elements are left unhatched.
+    let mut generic = BimModel::new("Plain");
+    let storey = generic.default_storey().expect("default storey");
+    generic.add_element(box_element("Box", 1000.0)?, storey)?;
+    let mut dxf = Vec::new();
+    write_dxf_plan(&generic, 500.0, &TessellationOptions::default(), &mut dxf)?;
+    assert!(!String::from_utf8(dxf)?.contains("  0\nLINE\n"));
+    Ok(())
+}
+
+#[test]
+fn bill_of_materials_is_written_as_csv_and_xlsx() -> Result<()> {
+    let mut model = BimModel::new("Precast");
+    let storey = model.default_storey().expect("default storey");
+    let panel = box_element("Panel, east", 1000.0)?;
+    let guid = panel.guid;
+    model.add_element(panel, storey)?;
+    model
+        .groups
+        .push(ElementGroup::new("P1", GroupKind::Assembly).with_members([guid]));
+    let rows = bill_of_materials(&model);
+
+    let mut csv = Vec::new();
+    write_bom_csv(&rows, &mut csv)?;
+    let csv = String::from_utf8(csv)?;
+    assert_eq!(
+        csv.lines().collect::<Vec<_>>(),
+        [
+            "Item,Level,Name,Kind,Material,Quantity,Volume,Weight",
+            "1,0,P1,Assembly,,1,1.0000,",
+            "1.1,1,\"Panel, east\",Generic,,1,1.0000,",
+        ]
+    );
+
+    let mut xlsx = Vec::new();
+    write_bom_xlsx(&rows, &mut xlsx)?;
+    assert!(xlsx.starts_with(b"PK\x03\x04"));
+    // Entries are stored uncompressed.
+    let text = String::from_utf8_lossy(&xlsx);
+    assert!(text.contains("xl/worksheets/sheet1.xml"));
+    assert!(text.contains("<t>Panel, east</t>"));
+    assert!(export_bom(&rows, temp_path("bom.txt")).is_err());
+    Ok(())
+}
+
+#[test]
+fn parameter_log_exports_changes_as_csv() -> Result<()> {
+    let mut model = BimModel::new("Audit");
+    model.author = "Doe, J.".to_string();
+    model.source = ChangeSource::Cli;
+    let storey = model.default_storey().expect("default storey");
+    let wall = box_element("Wall", 100.0)?;
+    let (guid, other) = (wall.guid, Guid::new());
+    model.add_element(wall, storey)?;
+    ModelCommand::SetParameter {
+        guid,
+        name: "FireRating".to_string(),
+        value: Some(ParameterValue::Text("EI60".to_string())),
+    }
+    .apply(&mut model)?;
+
+    let mut csv = Vec::new();
+    write_parameter_log_csv(&model, None, &mut csv)?;
+    let csv = String::from_utf8(csv)?;
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("Revision,Modified,Author,Source,Element"));
+    assert!(lines[1].starts_with("1,"));
+    assert!(lines[1].ends_with(&format!(r#","Doe, J.",CLI,{guid},Wall,FireRating,,EI60"#)));
+
+    let mut none = Vec::new();
+    write_parameter_log_csv(&model, Some(other), &mut none)?;
+    assert_eq!(String::from_utf8(none)?.lines().count(), 1);
+    Ok(())
+}
+
+#[test]
+fn journal_round_trips_and_replays() -> Result<()> {
+    let mut model = BimModel::new("Session");
+    let storey = model.default_storey().expect("default storey");
+    let base = model.clone();
+    let mut history = History::new();
+    let wall = box_element("Wall", 100.0)?;
+    let guid = wall.guid;
+    history.apply(
+        &mut model,
+        "Add wall",
+        ModelCommand::AddElements {
+            storey,
+            elements: vec![wall],
+        },
+    )?;
+    history.apply(&mut model, "Layer", ModelCommand::set_layer(guid, "Walls"))?;
+
+    let path = temp_path("session.cxjournal");
+    export_journal(history.journal(), &path)?;
+    let journal = import_journal(&path)?;
+    let _ = fs::remove_file(&path);
+    assert_eq!(journal.entries.len(), 2);
+
+    let mut replayed = base;
+    journal.replay(&mut replayed)?;
+    assert_eq!(replayed.revision(), model.revision());
+    assert_eq!(
+        replayed
+            .element(guid)
+            .and_then(|wall| wall.parameters.get("Layer")),
+        Some(&ParameterValue::Text("Walls".to_string()))
+    );
+    Ok(())
+}
+
+#[test]
+fn parameters_round_trip_through_csv_and_json() -> Result<()> {
+    let mut model = BimModel::new("Estimate");
+    let storey = model.default_storey().expect("default storey");
+    let mut wall = box_element("Wall, north", 100.0)?;
+    wall.insert_parameter("Width", ParameterValue::Number(200.0));
+    wall.insert_parameter("Material", ParameterValue::Text("C30/37".to_string()));
+    let guid = wall.guid;
+    model.add_element(wall, storey)?;
+    let mut slab = box_element("Slab", 100.0)?;
+    slab.insert_parameter("Count", ParameterValue::Integer(2));
+    model.add_element(slab, storey)?;
+
+    let mut csv = Vec::new();
+    write_parameters_csv(&model, &mut csv)?;
+    let csv = String::from_utf8(csv)?;
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "GUID,Name,Category,Count,Material,Width");
+    assert!(lines[1].ends_with(r#","Wall, north",Generic,,C30/37,200"#));
+
+    // Unchanged values are not applied again.
+    let unchanged = read_parameters_csv(&model, csv.as_bytes())?;
+    assert_eq!(unchanged.changed, 0);
+
+    let other = Guid::new();
+    let edited = format!("GUID,Width,Material,Fire\n{guid},250,,true\n{other},1,,\n");
+    let import = read_parameters_csv(&model, edited.as_bytes())?;
+    assert_eq!(import.changed, 3);
+    assert_eq!(import.unknown, vec![other]);
+    import.command.apply(&mut model)?;
+    let wall = model.element(guid).expect("wall");
+    assert_eq!(
+        wall.parameters.get("Width"),
+        Some(&ParameterValue::Number(250.0))
+    );
+    assert_eq!(wall.parameters.get("Material"), None);
+    assert_eq!(
+        wall.parameters.get("Fire"),
+        Some(&ParameterValue::Bool(true))
+    );
+    assert!(read_parameters_csv(&model, format!("GUID,Width\n{guid},wide\n").as_bytes()).is_err());
+    // Computed quantities are not inputs.
+    let volume = format!("GUID,Volume\n{guid},5\n");
+    assert!(read_parameters_csv(&model, volume.as_bytes()).is_err());
+    let volume = format!(r#"[{{"guid": "{guid}", "parameters": {{"Volume": 5}}}}]"#);
+    assert!(read_parameters_json(&model, volume.as_bytes()).is_err());
+
+    let mut json = Vec::new();
+    write_parameters_json(&model, &mut json)?;
+    assert_eq!(read_parameters_json(&model, json.as_slice())?.changed, 0);
+    let edited = format!(r#"[{{"guid": "{guid}", "parameters": {{"Width": 300, "Fire": null}}}}]"#);
+    let import = read_parameters_json(&model, edited.as_bytes())?;
+    assert_eq!(import.changed, 2);
+    import.command.apply(&mut model)?;
+    let wall = model.element(guid).expect("wall");
+    assert_eq!(
+        wall.parameters.get("Width"),
+        Some(&ParameterValue::Number(300.0))
+    );
+    assert_eq!(wall.parameters.get("Fire"), None);
+    Ok(())
+}
+
+#[test]
+fn ids_reports_pass_and_fail_per_element() -> Result<()> {
+    let mut model = BimModel::new("Delivery");
+    let storey = model.default_storey().expect("default storey");
+    let mut rated = box_element("Rated", 100.0)?;
+    rated.category = BimCategory::Wall;
+    rated.insert_parameter(
+        "Pset_WallCommon.FireRating",
+        ParameterValue::Text("EI60".into()),
+    );
+    rated.insert_parameter("Width", ParameterValue::Number(200.0));
+    let rated_guid = rated.guid;
+    model.add_element(rated, storey)?;
+    let mut unrated = box_element("Unrated", 100.0)?;
+    unrated.category = BimCategory::Wall;
+    unrated.insert_parameter("Width", ParameterValue::Number(450.0));
+    let unrated_guid = unrated.guid;
+    model.add_element(unrated, storey)?;
+    model.add_element(box_element("Proxy", 50.0)?, storey)?;
+
+    let ids = read_ids(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<ids:ids xmlns:ids="http://standards.buildingsmart.org/IDS"
+    xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <ids:info><ids:title>Walls</ids:title></ids:info>
+  <ids:specifications>
+    <ids:specification name="Wall fire rating" ifcVersion="IFC4">
+      <ids:applicability minOccurs="1" maxOccurs="unbounded">
+        <ids:entity><ids:name><ids:simpleValue>IfcWall</ids:simpleValue></ids:name></ids:entity>
+      </ids:applicability>
+      <ids:requirements>
+        <ids:property cardinality="required">
+          <ids:propertySet><ids:simpleValue>Pset_WallCommon</ids:simpleValue></ids:propertySet>
+          <ids:baseName><ids:simpleValue>FireRating</ids:simpleValue></ids:baseName>
+          <ids:value>
+            <xs:restriction base="xs:string">
+              <xs:enumeration value="EI60"/>
+              <xs:enumeration value="EI90"/>
+            </xs:restriction>
+          </ids:value>
+        </ids:property>
+        <ids:property cardinality="optional">
+          <ids:propertySet><ids:simpleValue>Cryxtal_Parameters</ids:simpleValue></ids:propertySet>
+          <ids:baseName><ids:simpleValue>Width</ids:simpleValue></ids:baseName>
+          <ids:value>
+            <xs:restriction base="xs:double"><xs:maxInclusive value="300"/></xs:restriction>
+          </ids:value>
+        </ids:property>
+      </ids:requirements>
+    </ids:specification>
+    <ids:specification name="Doors" ifcVersion="IFC4">
+      <ids:applicability minOccurs="1">
+        <ids:entity><ids:name><ids:simpleValue>IFCDOOR</ids:simpleValue></ids:name></ids:entity>
+      </ids:applicability>
+      <ids:requirements/>
+    </ids:specification>
+  </ids:specifications>
+</ids:ids>"#,
+    )?;
+    assert_eq!(ids.title, "Walls");
+    assert_eq!(ids.specifications.len(), 2);
+
+    let report = validate_ids(&model, &ids);
+    assert!(!report.passed());
+    let walls = &report.specifications[0];
+    assert_eq!(walls.elements.len(), 2);
+    let rated = walls
+        .elements
+        .iter()
+        .find(|result| result.element == rated_guid);
+    assert!(rated.expect("rated wall").passed());
+    let unrated = walls
+        .elements
+        .iter()
+        .find(|result| result.element == unrated_guid)
+        .expect("unrated wall");
+    assert_eq!(unrated.failures.len(), 2);
+    assert!(unrated.failures[0].contains("is missing"));
+    assert!(unrated.failures[1].contains("has another value"));
+    assert!(report.specifications[1].missing);
+
+    assert!(
+        read_ids(
+            "<ids><specifications><specification><requirements>\
+        <property><baseName><simpleValue>A</simpleValue></baseName></property>\
+        </requirements></specification></specifications></ids>"
+        )
+        .is_err()
+    );
+    assert!(read_ids("<model/>").is_err());
+    Ok(())
+}
+
+#[test]
+fn plan_drawing_colors_filtered_elements_and_adds_a_legend() -> Result<()> {
+    let mut model = BimModel::new("Plan");
+    let storey = model.default_storey().expect("default storey");
+    let mut rated = box_element("Rated", 100.0)?;
+    rated.insert_parameter("FireRating", ParameterValue::Text("EI60".to_string()));
+    model.add_element(rated, storey)?;
+    model.add_element(box_element("Plain", 50.0)?, storey)?;
+    let red = GraphicOverride {
+        color: Some([255, 0, 0]),
+        ..GraphicOverride::default()
+    };
+    let view = NamedView::new("Fire")
+        .with_filter(ViewFilter::new("EI60", "FireRating=EI60".parse()?, red))
+        .with_filter(ViewFilter::new("EI90", "FireRating=EI90".parse()?, red));
+
+    let mut svg = Vec::new();
+    write_plan_svg(
+        &model,
+        Some(&view),
+        &TessellationOptions::default(),
+        &mut svg,
+    )?;
+    let svg = String::from_utf8(svg)?;
+    assert_eq!(svg.matches(r##"stroke="#ff0000""##).count(), 12);
+    // Only filters matching an element get a legend row.
+    assert_eq!(svg.matches("<rect").count(), 1);
+    assert!(svg.contains(">EI60</text>"));
+
+    let mut plain = Vec::new();
+    write_plan_svg(&model, None, &TessellationOptions::default(), &mut plain)?;
+    assert!(!String::from_utf8(plain)?.contains("<rect"));
+    Ok(())
+}
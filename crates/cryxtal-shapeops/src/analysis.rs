@@ -0,0 +1,116 @@
+use std::f64::consts::PI;
+
+use cryxtal_topology::{
+    Point3, Solid, closest_point_on_triangle, solid_triangles, triangle_distance,
+};
+use truck_modeling::InnerSpace;
+
+use crate::{Error, Result};
+
+/// Whether `point` lies inside `solid` or within `tol` of its boundary. The solid is
+/// tessellated at `tol` and the point classified by its winding number, so inner shells (voids)
+/// exclude the points inside them and small gaps in the faces do not flip the answer.
+pub fn contains_point(solid: &Solid, point: Point3, tol: f64) -> Result<bool> {
+    check_tolerance(tol)?;
+    Ok(encloses(&solid_triangles(solid, tol), point, tol))
+}
+
+/// Whether `a` and `b` share any volume or their boundaries come within `tol`, so touching
+/// solids intersect. Both are tessellated at `tol`; without a boolean operation, this is
+/// cheap enough for rules and placement checks over many pairs.
+pub fn intersects(a: &Solid, b: &Solid, tol: f64) -> Result<bool> {
+    check_tolerance(tol)?;
+    let (triangles_a, triangles_b) = (solid_triangles(a, tol), solid_triangles(b, tol));
+    let (Some(bounds_a), Some(bounds_b)) = (Bounds::of(&triangles_a), Bounds::of(&triangles_b))
+    else {
+        return Ok(false);
+    };
+    if !bounds_a.overlaps(&bounds_b, tol) {
+        return Ok(false);
+    }
+
+    // Only triangles reaching into the other solid's bounds can touch it.
+    let near = |triangles: &[[Point3; 3]], other: &Bounds| -> Vec<([Point3; 3], Bounds)> {
+        triangles
+            .iter()
+            .filter_map(|triangle| {
+                let bounds = Bounds::of(std::slice::from_ref(triangle))?;
+                bounds.overlaps(other, tol).then_some((*triangle, bounds))
+            })
+            .collect()
+    };
+    let near_b = near(&triangles_b, &bounds_a);
+    for (triangle_a, box_a) in near(&triangles_a, &bounds_b) {
+        for (triangle_b, box_b) in &near_b {
+            if box_a.overlaps(box_b, tol) && triangle_distance(&triangle_a, triangle_b).0 <= tol {
+                return Ok(true);
+            }
+        }
+    }
+
+    // Boundaries apart: the solids are disjoint unless one lies wholly inside the other.
+    Ok(encloses(&triangles_a, triangles_b[0][0], tol)
+        || encloses(&triangles_b, triangles_a[0][0], tol))
+}
+
+fn check_tolerance(tol: f64) -> Result<()> {
+    if tol.is_nan() || tol <= 0.0 {
+        return Err(Error::InvalidParameter("tolerance must be > 0".to_string()));
+    }
+    Ok(())
+}
+
+/// Whether `point` is within `tol` of the tessellated boundary or has a nonzero winding number
+/// about it: the solid angles of the triangles seen from the point add up to ±4π inside and to
+/// 0 outside.
+fn encloses(triangles: &[[Point3; 3]], point: Point3, tol: f64) -> bool {
+    let on_boundary = triangles
+        .iter()
+        .any(|triangle| (closest_point_on_triangle(triangle, point) - point).magnitude() <= tol);
+    if on_boundary {
+        return true;
+    }
+    let solid_angle: f64 = triangles
+        .iter()
+        .map(|[a, b, c]| {
+            let (a, b, c) = (a - point, b - point, c - point);
+            let (la, lb, lc) = (a.magnitude(), b.magnitude(), c.magnitude());
+            let numerator = a.dot(b.cross(c));
+            let denominator = la * lb * lc + a.dot(b) * lc + b.dot(c) * la + c.dot(a) * lb;
+            2.0 * numerator.atan2(denominator)
+        })
+        .sum();
+    (solid_angle / (4.0 * PI)).abs() > 0.5
+}
+
+/// Axis-aligned bounds of a set of triangles.
+struct Bounds {
+    min: Point3,
+    max: Point3,
+}
+
+impl Bounds {
+    fn of(triangles: &[[Point3; 3]]) -> Option<Self> {
+        let first = triangles.first()?[0];
+        let (min, max) = triangles
+            .iter()
+            .flatten()
+            .fold((first, first), |(min, max), p| {
+                (
+                    Point3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                    Point3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+                )
+            });
+        Some(Self { min, max })
+    }
+
+    /// Whether the bounds overlap once grown by `tol`.
+    fn overlaps(&self, other: &Bounds, tol: f64) -> bool {
+        self.min.x <= other.max.x + tol
+            && other.min.x <= self.max.x + tol
+            && self.min.y <= other.max.y + tol
+            && other.min.y <= self.max.y + tol
+            && self.min.z <= other.max.z + tol
+            && other.min.z <= self.max.z + tol
+    }
+}
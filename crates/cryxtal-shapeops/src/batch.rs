@@ -0,0 +1,75 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use cryxtal_base::OpContext;
+use cryxtal_topology::Solid;
+use rayon::prelude::*;
+
+use crate::{Result, difference_with, imprint, union_with};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BooleanOp {
+    Difference,
+    Union,
+    Imprint,
+}
+
+/// A set of independent `(base, tool)` booleans evaluated on the rayon thread pool.
+#[derive(Clone, Debug)]
+pub struct BooleanBatch {
+    op: BooleanOp,
+    tol: f64,
+    pairs: Vec<(Solid, Solid)>,
+}
+
+impl BooleanBatch {
+    pub fn new(op: BooleanOp, tol: f64) -> Self {
+        Self {
+            op,
+            tol,
+            pairs: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, base: Solid, tool: Solid) -> &mut Self {
+        self.pairs.push((base, tool));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Runs every pair in parallel; results are returned in insertion order.
+    pub fn run(&self) -> Vec<Result<Solid>> {
+        self.run_with(&OpContext::default())
+    }
+
+    /// Like [`BooleanBatch::run`], reporting the fraction of finished pairs through `ctx`.
+    /// Pairs that have not finished when `ctx` is cancelled return [`crate::Error::Cancelled`].
+    pub fn run_with(&self, ctx: &OpContext) -> Vec<Result<Solid>> {
+        let total = self.pairs.len();
+        let finished = AtomicUsize::new(0);
+        // Pairs are cancelled with the batch, but only finished pairs count towards its progress.
+        let silent = |_: f32| {};
+        let pair_ctx = ctx.with_progress(&silent);
+        ctx.report(0.0);
+        self.pairs
+            .par_iter()
+            .map(|(base, tool)| {
+                crate::checkpoint(ctx)?;
+                let result = match self.op {
+                    BooleanOp::Difference => difference_with(base, tool, self.tol, &pair_ctx),
+                    BooleanOp::Union => union_with(base, tool, self.tol, &pair_ctx),
+                    BooleanOp::Imprint => imprint(base, tool, self.tol),
+                };
+                let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+                ctx.report(done as f32 / total as f32);
+                result
+            })
+            .collect()
+    }
+}
@@ -1,14 +1,40 @@
-use cryxtal_topology::{Point3, Solid, SolidBuilder};
+mod analysis;
+mod batch;
+
+use std::mem::discriminant;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use cryxtal_base::OpContext;
+use cryxtal_topology::{
+    Face, Point3, Shell, Solid, SolidBuilder, Surface, heal_shells, sample_edge,
+};
 use thiserror::Error;
+use truck_modeling::{InnerSpace, ParametricSurface, SPHint2D, SearchNearestParameter};
+
+pub use analysis::{contains_point, intersects};
+pub use batch::{BooleanBatch, BooleanOp};
 
 pub const DEFAULT_SHAPEOPS_TOLERANCE: f64 = 0.05;
 
+/// Segments each boundary edge of a piece is sampled with to test that it lies on a surface.
+const PIECE_EDGE_SEGMENTS: usize = 4;
+/// Newton iterations projecting a point onto a surface.
+const PROJECTION_TRIALS: usize = 100;
+/// Share of a boolean done once its operands are prepared for the kernel.
+const OPERANDS_PREPARED: f32 = 0.1;
+/// How often a cancellable boolean looks at its cancel flag while the kernel runs.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("invalid parameter: {0}")]
     InvalidParameter(String),
     #[error("boolean operation failed")]
     BooleanFailed,
+    #[error("operation cancelled")]
+    Cancelled,
     #[error(transparent)]
     Topology(#[from] cryxtal_topology::Error),
 }
@@ -16,23 +42,159 @@ pub enum Error {
 pub type Result<T> = std::result::Result<T, Error>;
 
 pub fn difference(base: &Solid, tool: &Solid, tol: f64) -> Result<Solid> {
+    difference_with(base, tool, tol, &OpContext::default())
+}
+
+/// Like [`difference`], reporting progress and honouring cancellation through `ctx`.
+pub fn difference_with(base: &Solid, tool: &Solid, tol: f64, ctx: &OpContext) -> Result<Solid> {
     if tol <= 0.0 {
         return Err(Error::InvalidParameter("tolerance must be > 0".to_string()));
     }
+    checkpoint(ctx)?;
+    ctx.report(0.0);
+    let result = subtract(base, tool, tol, ctx)?;
+    ctx.report(1.0);
+    Ok(result)
+}
 
+/// One subtraction through the kernel, reporting its stages through `ctx`.
+fn subtract(base: &Solid, tool: &Solid, tol: f64, ctx: &OpContext) -> Result<Solid> {
+    let base = base.clone();
     let mut inverted_tool = tool.clone();
     inverted_tool.not();
+    ctx.report(OPERANDS_PREPARED);
+    run_kernel(ctx, move || truck_shapeops::and(&base, &inverted_tool, tol))
+}
+
+/// Subtracts every solid in `tools` from `base` in turn.
+pub fn difference_many(base: &Solid, tools: &[Solid], tol: f64) -> Result<Solid> {
+    difference_many_with(base, tools, tol, &OpContext::default())
+}
 
-    let result = truck_shapeops::and(base, &inverted_tool, tol).ok_or(Error::BooleanFailed)?;
+/// Like [`difference_many`], reporting the fraction of subtracted tools through `ctx`.
+pub fn difference_many_with(
+    base: &Solid,
+    tools: &[Solid],
+    tol: f64,
+    ctx: &OpContext,
+) -> Result<Solid> {
+    if tol <= 0.0 {
+        return Err(Error::InvalidParameter("tolerance must be > 0".to_string()));
+    }
+    ctx.report(0.0);
+    let mut result = base.clone();
+    let count = tools.len() as f32;
+    for (idx, tool) in tools.iter().enumerate() {
+        checkpoint(ctx)?;
+        let start = idx as f32 / count;
+        let end = (idx + 1) as f32 / count;
+        result = ctx.span(start, end, |ctx| subtract(&result, tool, tol, ctx))?;
+        ctx.report(end);
+    }
+    checkpoint(ctx)?;
+    ctx.report(1.0);
     Ok(result)
 }
 
 pub fn union(base: &Solid, tool: &Solid, tol: f64) -> Result<Solid> {
+    union_with(base, tool, tol, &OpContext::default())
+}
+
+/// Like [`union`], reporting progress and honouring cancellation through `ctx`.
+pub fn union_with(base: &Solid, tool: &Solid, tol: f64, ctx: &OpContext) -> Result<Solid> {
     if tol <= 0.0 {
         return Err(Error::InvalidParameter("tolerance must be > 0".to_string()));
     }
+    checkpoint(ctx)?;
+    ctx.report(0.0);
+    let (base, tool) = (base.clone(), tool.clone());
+    ctx.report(OPERANDS_PREPARED);
+    let result = run_kernel(ctx, move || truck_shapeops::or(&base, &tool, tol))?;
+    ctx.report(1.0);
+    Ok(result)
+}
 
-    truck_shapeops::or(base, tool, tol).ok_or(Error::BooleanFailed)
+/// Runs one kernel boolean. The kernel splits, classifies and sews faces in a single call, so
+/// when `ctx` can be cancelled the call runs on a thread of its own that is polled for the
+/// result: cancelling returns at once, and the abandoned call finishes in the background with
+/// its result dropped.
+fn run_kernel(
+    ctx: &OpContext,
+    boolean: impl FnOnce() -> Option<Solid> + Send + 'static,
+) -> Result<Solid> {
+    // The browser build has no threads; the kernel runs in place there.
+    if !ctx.is_cancellable() || cfg!(target_arch = "wasm32") {
+        return boolean().ok_or(Error::BooleanFailed);
+    }
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(boolean());
+    });
+    loop {
+        checkpoint(ctx)?;
+        match receiver.recv_timeout(CANCEL_POLL_INTERVAL) {
+            Ok(result) => return result.ok_or(Error::BooleanFailed),
+            Err(RecvTimeoutError::Timeout) => {}
+            // The kernel panicked.
+            Err(RecvTimeoutError::Disconnected) => return Err(Error::BooleanFailed),
+        }
+    }
+}
+
+/// Splits the faces of `base` along the curves where the boundary of `tool` crosses them, so
+/// the imprinted regions can be picked, tagged or colored on their own. The result is one solid
+/// with the shape and volume of `base`; it comes back unchanged when `tool` misses `base` or
+/// holds it whole.
+pub fn imprint(base: &Solid, tool: &Solid, tol: f64) -> Result<Solid> {
+    if tol <= 0.0 {
+        return Err(Error::InvalidParameter("tolerance must be > 0".to_string()));
+    }
+
+    let inside = truck_shapeops::and(base, tool, tol).ok_or(Error::BooleanFailed)?;
+    if inside.face_iter().next().is_none() {
+        return Ok(base.clone());
+    }
+    let mut inverted_tool = tool.clone();
+    inverted_tool.not();
+    let outside = truck_shapeops::and(base, &inverted_tool, tol).ok_or(Error::BooleanFailed)?;
+    if outside.face_iter().next().is_none() {
+        return Ok(base.clone());
+    }
+
+    // Each half holds its pieces of the base faces, closed by caps on the tool's boundary.
+    // The base pieces of both halves, sewn along the section curves, bound the imprinted base.
+    let base_surfaces: Vec<Surface> = base.face_iter().map(|face| face.surface()).collect();
+    let pieces: Vec<Face> = inside
+        .face_iter()
+        .chain(outside.face_iter())
+        .filter(|face| {
+            base_surfaces
+                .iter()
+                .any(|base_surface| lies_on(face, base_surface, tol))
+        })
+        .cloned()
+        .collect();
+    let (imprinted, _) = heal_shells(&[Shell::from(pieces)], tol)?;
+    Ok(imprinted)
+}
+
+/// Whether `face` lies on `surface`, as the pieces a boolean cuts from a face on it do: its
+/// surface is of the same kind, and its boundary projects onto `surface` within `tol`. Faces
+/// running parallel to `surface` just beyond `tol`, such as a tool face close to a base face,
+/// do not count.
+fn lies_on(face: &Face, surface: &Surface, tol: f64) -> bool {
+    if discriminant(&face.surface()) != discriminant(surface) {
+        return false;
+    }
+    face.boundaries()
+        .iter()
+        .flat_map(|wire| wire.edge_iter())
+        .flat_map(|edge| sample_edge(edge, PIECE_EDGE_SEGMENTS))
+        .all(|point| {
+            surface
+                .search_nearest_parameter(point, SPHint2D::None, PROJECTION_TRIALS)
+                .is_some_and(|(u, v)| (surface.subs(u, v) - point).magnitude() <= tol)
+        })
 }
 
 pub fn plate_with_hole(
@@ -61,3 +223,10 @@ pub fn plate_with_hole(
 
     difference(&plate, &cylinder, tol)
 }
+
+fn checkpoint(ctx: &OpContext) -> Result<()> {
+    if ctx.is_cancelled() {
+        return Err(Error::Cancelled);
+    }
+    Ok(())
+}
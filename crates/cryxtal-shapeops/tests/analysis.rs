@@ -0,0 +1,38 @@
+use cryxtal_shapeops::{
+    DEFAULT_SHAPEOPS_TOLERANCE, Result, contains_point, intersects, plate_with_hole,
+};
+use cryxtal_topology::{Matrix4, Point3, SolidBuilder, Vector3, transform_solid};
+
+#[test]
+fn contains_point_respects_holes_and_boundary() -> Result<()> {
+    let tol = DEFAULT_SHAPEOPS_TOLERANCE;
+    let plate = plate_with_hole(100.0, 100.0, 10.0, 40.0, tol)?;
+    assert!(contains_point(&plate, Point3::new(10.0, 10.0, 5.0), tol)?);
+    assert!(!contains_point(&plate, Point3::new(50.0, 50.0, 5.0), tol)?);
+    assert!(!contains_point(&plate, Point3::new(10.0, 10.0, 20.0), tol)?);
+    // On the top face.
+    assert!(contains_point(&plate, Point3::new(10.0, 10.0, 10.0), tol)?);
+    assert!(contains_point(&plate, Point3::new(10.0, 10.0, 5.0), 0.0).is_err());
+    Ok(())
+}
+
+#[test]
+fn intersects_detects_overlap_touching_and_nesting() -> Result<()> {
+    let tol = DEFAULT_SHAPEOPS_TOLERANCE;
+    let block = SolidBuilder::box_solid(100.0, 100.0, 100.0)?;
+    let moved = |x: f64, y: f64, z: f64, size: f64| -> Result<_> {
+        let solid = SolidBuilder::box_solid(size, size, size)?;
+        Ok(transform_solid(
+            &solid,
+            Matrix4::from_translation(Vector3::new(x, y, z)),
+        ))
+    };
+    assert!(intersects(&block, &moved(50.0, 50.0, 50.0, 100.0)?, tol)?);
+    assert!(intersects(&block, &moved(100.0, 0.0, 0.0, 100.0)?, tol)?);
+    assert!(!intersects(&block, &moved(101.0, 0.0, 0.0, 100.0)?, tol)?);
+    // Wholly inside, with no faces touching.
+    let inner = moved(25.0, 25.0, 25.0, 50.0)?;
+    assert!(intersects(&block, &inner, tol)?);
+    assert!(intersects(&inner, &block, tol)?);
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use cryxtal_base::OpContext;
+use cryxtal_shapeops::{
+    BooleanBatch, BooleanOp, DEFAULT_SHAPEOPS_TOLERANCE, Error, Result, difference,
+    difference_many, difference_many_with, difference_with, imprint,
+};
+use cryxtal_topology::{
+    Matrix4, Point3, Solid, SolidBuilder, Vector3, mass_properties, transform_solid,
+};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Vertical cylinder of `radius` through a 1000 cube standing on the origin.
+fn drill(radius: f64) -> Result<Solid> {
+    Ok(SolidBuilder::cylinder_z(
+        Point3::new(500.0, 500.0, -100.0),
+        radius,
+        1200.0,
+    )?)
+}
+
+/// Distance of the vertex of `solid` closest to the axis of [`drill`].
+fn axis_distance(solid: &Solid) -> f64 {
+    solid
+        .vertex_iter()
+        .map(|vertex| {
+            let point = vertex.point();
+            (point.x - 500.0).hypot(point.y - 500.0)
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Block of `size` with its lowest corner at (`x`, `y`, `z`).
+fn block(size: [f64; 3], x: f64, y: f64, z: f64) -> Result<Solid> {
+    Ok(transform_solid(
+        &SolidBuilder::box_solid(size[0], size[1], size[2])?,
+        Matrix4::from_translation(Vector3::new(x, y, z)),
+    ))
+}
+
+fn has_vertex(solid: &Solid, point: Point3) -> bool {
+    solid.vertex_iter().any(|vertex| {
+        let offset = vertex.point() - point;
+        offset.x.abs() + offset.y.abs() + offset.z.abs() < 1.0e-6
+    })
+}
+
+#[test]
+fn imprint_splits_faces_and_keeps_volume() -> Result<()> {
+    let tol = DEFAULT_SHAPEOPS_TOLERANCE;
+    let wall = SolidBuilder::box_solid(1000.0, 200.0, 3000.0)?;
+    // A door-sized block through the wall's thickness.
+    let opening = block([400.0, 400.0, 600.0], 300.0, -100.0, 500.0)?;
+
+    let imprinted = imprint(&wall, &opening, tol)?;
+    let faces = |solid: &Solid| solid.face_iter().count();
+    assert!(faces(&imprinted) > faces(&wall));
+    let volume = mass_properties(&wall).volume;
+    assert!((mass_properties(&imprinted).volume - volume).abs() < volume * 1.0e-6);
+    Ok(())
+}
+
+#[test]
+fn imprint_ignores_tool_faces_just_off_the_base() -> Result<()> {
+    let tol = DEFAULT_SHAPEOPS_TOLERANCE;
+    let wall = SolidBuilder::box_solid(1000.0, 200.0, 3000.0)?;
+    // The tool stops ten tolerances short of the back face, parallel to it.
+    let near_miss = 200.0 - 10.0 * tol;
+    let recess = block([400.0, 100.0 + near_miss, 600.0], 300.0, -100.0, 500.0)?;
+
+    let imprinted = imprint(&wall, &recess, tol)?;
+    // Only the front face is split; the tool face inside the wall is no base piece.
+    assert_eq!(imprinted.face_iter().count(), wall.face_iter().count() + 1);
+    let hidden_corner = Point3::new(300.0, near_miss, 500.0);
+    assert!(!has_vertex(&imprinted, hidden_corner));
+    let volume = mass_properties(&wall).volume;
+    assert!((mass_properties(&imprinted).volume - volume).abs() < volume * 1.0e-6);
+    Ok(())
+}
+
+#[test]
+fn difference_many_subtracts_every_tool() -> Result<()> {
+    let tol = DEFAULT_SHAPEOPS_TOLERANCE;
+    let wall = SolidBuilder::box_solid(3000.0, 200.0, 3000.0)?;
+    // Two openings through the thickness, 400 x 600 and 800 x 1000.
+    let tools = [
+        block([400.0, 400.0, 600.0], 300.0, -100.0, 500.0)?,
+        block([800.0, 400.0, 1000.0], 1500.0, -100.0, 500.0)?,
+    ];
+
+    let cut = difference_many(&wall, &tools, tol)?;
+    // Each opening adds its four reveals.
+    assert_eq!(cut.face_iter().count(), 14);
+    assert!(has_vertex(&cut, Point3::new(300.0, 0.0, 500.0)));
+    assert!(has_vertex(&cut, Point3::new(2300.0, 200.0, 1500.0)));
+    let one_by_one = difference(&difference(&wall, &tools[0], tol)?, &tools[1], tol)?;
+    assert_eq!(one_by_one.face_iter().count(), 14);
+    assert_eq!(difference_many(&wall, &[], tol)?.face_iter().count(), 6);
+    Ok(())
+}
+
+#[test]
+fn difference_many_reports_the_stages_of_each_tool() -> Result<()> {
+    let wall = SolidBuilder::box_solid(3000.0, 200.0, 3000.0)?;
+    let tools = [
+        block([400.0, 400.0, 600.0], 300.0, -100.0, 500.0)?,
+        block([800.0, 400.0, 1000.0], 1500.0, -100.0, 500.0)?,
+    ];
+    let reports = Mutex::new(Vec::new());
+    let report = |fraction: f32| reports.lock().expect("reports").push(fraction);
+    let cancel = AtomicBool::new(false);
+    let ctx = OpContext::new().with_progress(&report).with_cancel(&cancel);
+
+    difference_many_with(&wall, &tools, DEFAULT_SHAPEOPS_TOLERANCE, &ctx)?;
+    let reports = reports.into_inner().expect("reports");
+    assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]));
+    // Each tool reports its prepared operands before the kernel runs.
+    assert!(
+        reports
+            .iter()
+            .any(|fraction| *fraction > 0.0 && *fraction < 0.5)
+    );
+    assert!(
+        reports
+            .iter()
+            .any(|fraction| *fraction > 0.5 && *fraction < 1.0)
+    );
+    assert_eq!(reports.last(), Some(&1.0));
+    Ok(())
+}
+
+#[test]
+fn cancelling_a_running_difference_stops_it() -> Result<()> {
+    let base = SolidBuilder::box_solid(1000.0, 1000.0, 1000.0)?;
+    let cancel = AtomicBool::new(false);
+    // Cancels once the operands are handed to the kernel.
+    let report = |fraction: f32| {
+        if fraction > 0.0 {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    };
+    let ctx = OpContext::new().with_progress(&report).with_cancel(&cancel);
+
+    let result = difference_with(&base, &drill(200.0)?, DEFAULT_SHAPEOPS_TOLERANCE, &ctx);
+    assert!(matches!(result, Err(Error::Cancelled)));
+    Ok(())
+}
+
+#[test]
+fn boolean_batch_keeps_pair_order() -> Result<()> {
+    let base = SolidBuilder::box_solid(1000.0, 1000.0, 1000.0)?;
+    let mut batch = BooleanBatch::new(BooleanOp::Difference, DEFAULT_SHAPEOPS_TOLERANCE);
+    assert!(batch.is_empty());
+    // Holes growing with the pair index.
+    let radii = [100.0, 200.0, 300.0, 400.0];
+    for radius in radii {
+        batch.push(base.clone(), drill(radius)?);
+    }
+    assert_eq!(batch.len(), radii.len());
+
+    let results = batch.run();
+    assert_eq!(results.len(), radii.len());
+    for (result, radius) in results.into_iter().zip(radii) {
+        assert!((axis_distance(&result?) - radius).abs() < 1.0e-6);
+    }
+    Ok(())
+}
+
+#[test]
+fn cancelled_boolean_batch_runs_no_pair() -> Result<()> {
+    let base = SolidBuilder::box_solid(1000.0, 1000.0, 1000.0)?;
+    let mut batch = BooleanBatch::new(BooleanOp::Union, DEFAULT_SHAPEOPS_TOLERANCE);
+    batch
+        .push(base.clone(), drill(200.0)?)
+        .push(base, drill(300.0)?);
+    let cancel = AtomicBool::new(true);
+    let results = batch.run_with(&OpContext::new().with_cancel(&cancel));
+    assert_eq!(results.len(), 2);
+    assert!(
+        results
+            .iter()
+            .all(|result| matches!(result, Err(Error::Cancelled)))
+    );
+    Ok(())
+}
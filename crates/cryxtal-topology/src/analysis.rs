@@ -0,0 +1,222 @@
+use truck_meshalgo::prelude::*;
+
+use crate::{Error, Point3, Result, Solid, Vector3};
+
+/// Tessellation tolerance of the faces [`voxelize`] samples, as a fraction of the voxel size.
+const TESSELLATION_FRACTION: f64 = 0.25;
+/// Most voxels [`voxelize`] allocates before refusing the voxel size as too fine.
+pub const MAX_VOXELS: usize = 1 << 26;
+/// Offset of the sampling rays from the voxel centers, as a fraction of the voxel size, so they
+/// never run exactly along the edges and diagonals of axis-aligned faces.
+const RAY_JITTER: [f64; 2] = [1.37e-7, 2.91e-7];
+
+/// Solid occupancy on a uniform grid of cubic voxels. A voxel is filled when its center lies
+/// inside the solid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoxelGrid {
+    /// Minimum corner of the first voxel.
+    pub origin: Point3,
+    pub voxel_size: f64,
+    /// Voxels along X, Y and Z.
+    pub dims: [usize; 3],
+    /// Occupancy, X varying fastest, then Y, then Z.
+    filled: Vec<bool>,
+}
+
+impl VoxelGrid {
+    /// Whether voxel `[x, y, z]` is filled; voxels outside the grid are empty.
+    pub fn is_filled(&self, [x, y, z]: [usize; 3]) -> bool {
+        let [nx, ny, nz] = self.dims;
+        x < nx && y < ny && z < nz && self.filled[x + nx * (y + ny * z)]
+    }
+
+    /// Indices of the filled voxels, in storage order.
+    pub fn filled(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        let [nx, ny, _] = self.dims;
+        self.filled
+            .iter()
+            .enumerate()
+            .filter(|(_, filled)| **filled)
+            .map(move |(index, _)| [index % nx, index / nx % ny, index / (nx * ny)])
+    }
+
+    pub fn filled_count(&self) -> usize {
+        self.filled.iter().filter(|filled| **filled).count()
+    }
+
+    /// Approximate volume of the solid: the filled voxels' total volume.
+    pub fn volume(&self) -> f64 {
+        self.filled_count() as f64 * self.voxel_size.powi(3)
+    }
+
+    pub fn voxel_center(&self, [x, y, z]: [usize; 3]) -> Point3 {
+        let half = 0.5 * self.voxel_size;
+        self.origin
+            + Vector3::new(
+                x as f64 * self.voxel_size + half,
+                y as f64 * self.voxel_size + half,
+                z as f64 * self.voxel_size + half,
+            )
+    }
+
+    /// Voxel containing `point`, if it lies within the grid.
+    pub fn voxel_at(&self, point: Point3) -> Option<[usize; 3]> {
+        let offset = point - self.origin;
+        let mut index = [0; 3];
+        for (axis, value) in [offset.x, offset.y, offset.z].into_iter().enumerate() {
+            let cell = (value / self.voxel_size).floor();
+            if !(0.0..self.dims[axis] as f64).contains(&cell) {
+                return None;
+            }
+            index[axis] = cell as usize;
+        }
+        Some(index)
+    }
+
+    /// Whether `point` falls in a filled voxel: an approximate containment test that does not
+    /// depend on the solid's faces being watertight or consistently oriented.
+    pub fn contains(&self, point: Point3) -> bool {
+        self.voxel_at(point)
+            .is_some_and(|index| self.is_filled(index))
+    }
+
+    /// Approximate volume shared with `other`: the filled voxels of this grid whose centers fall
+    /// in filled voxels of `other`. Zero when two solids do not clash, to within a voxel.
+    pub fn overlap_volume(&self, other: &VoxelGrid) -> f64 {
+        let shared = self
+            .filled()
+            .filter(|index| other.contains(self.voxel_center(*index)))
+            .count();
+        shared as f64 * self.voxel_size.powi(3)
+    }
+}
+
+/// Voxelizes `solid` on a grid of cubes `voxel_size` wide, starting at the minimum corner of its
+/// boundary. Each column of voxels is sampled with a ray along Z that counts the signed
+/// crossings of the solid's faces, so inner shells (voids) are left empty. Faces are
+/// tessellated to a quarter of the voxel size. Fails if `voxel_size` is not positive or the
+/// grid would exceed [`MAX_VOXELS`].
+pub fn voxelize(solid: &Solid, voxel_size: f64) -> Result<VoxelGrid> {
+    if !voxel_size.is_finite() || voxel_size <= 0.0 {
+        return Err(Error::InvalidParameter(format!(
+            "voxel size must be > 0, got {voxel_size}"
+        )));
+    }
+    let triangles = solid_triangles(solid, voxel_size * TESSELLATION_FRACTION);
+    let Some(first) = triangles.first().map(|triangle| triangle[0]) else {
+        return Ok(VoxelGrid {
+            origin: Point3::new(0.0, 0.0, 0.0),
+            voxel_size,
+            dims: [0; 3],
+            filled: Vec::new(),
+        });
+    };
+    let (min, max) = triangles
+        .iter()
+        .flatten()
+        .fold((first, first), |(min, max), p| {
+            (
+                Point3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                Point3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+            )
+        });
+    let extent = max - min;
+    let dims =
+        [extent.x, extent.y, extent.z].map(|length| ((length / voxel_size).ceil() as usize).max(1));
+    let count = dims
+        .iter()
+        .try_fold(1usize, |count, dim| count.checked_mul(*dim))
+        .filter(|count| *count <= MAX_VOXELS)
+        .ok_or_else(|| {
+            Error::InvalidParameter(format!(
+                "voxel size {voxel_size} needs more than {MAX_VOXELS} voxels"
+            ))
+        })?;
+    let [nx, ny, nz] = dims;
+
+    // Signed heights at which each column's ray crosses a face: -1 entering, +1 leaving.
+    let mut columns: Vec<Vec<(f64, i32)>> = vec![Vec::new(); nx * ny];
+    let ray = |index: usize, axis: usize| {
+        let base = if axis == 0 { min.x } else { min.y };
+        base + (index as f64 + 0.5 + RAY_JITTER[axis]) * voxel_size
+    };
+    for [a, b, c] in &triangles {
+        let area = (b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y);
+        if area.abs() <= f64::EPSILON * extent.x.max(extent.y).powi(2) {
+            continue;
+        }
+        let range = |low: f64, high: f64, base: f64, dim: usize| {
+            let first = ((low - base) / voxel_size - 0.5).floor().max(0.0) as usize;
+            let last = (((high - base) / voxel_size - 0.5).ceil().max(0.0) as usize).min(dim - 1);
+            first..=last
+        };
+        for y in range(a.y.min(b.y).min(c.y), a.y.max(b.y).max(c.y), min.y, ny) {
+            for x in range(a.x.min(b.x).min(c.x), a.x.max(b.x).max(c.x), min.x, nx) {
+                let (px, py) = (ray(x, 0), ray(y, 1));
+                let wa = ((b.x - px) * (c.y - py) - (c.x - px) * (b.y - py)) / area;
+                let wb = ((c.x - px) * (a.y - py) - (a.x - px) * (c.y - py)) / area;
+                let wc = 1.0 - wa - wb;
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+                let z = wa * a.z + wb * b.z + wc * c.z;
+                columns[x + nx * y].push((z, if area > 0.0 { 1 } else { -1 }));
+            }
+        }
+    }
+
+    let mut filled = vec![false; count];
+    for (column, hits) in columns.iter_mut().enumerate() {
+        if hits.is_empty() {
+            continue;
+        }
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut winding = 0;
+        let mut next = 0;
+        for z in 0..nz {
+            let center = min.z + (z as f64 + 0.5) * voxel_size;
+            while next < hits.len() && hits[next].0 < center {
+                winding -= hits[next].1;
+                next += 1;
+            }
+            filled[column + nx * ny * z] = winding != 0;
+        }
+    }
+
+    Ok(VoxelGrid {
+        origin: min,
+        voxel_size,
+        dims,
+        filled,
+    })
+}
+
+/// Triangles of the faces of `solid` tessellated at `tolerance`, wound counterclockwise seen from
+/// outside.
+pub fn solid_triangles(solid: &Solid, tolerance: f64) -> Vec<[Point3; 3]> {
+    let mut triangles = Vec::new();
+    for face in solid.triangulation(tolerance).face_iter() {
+        let Some(mesh) = face.surface() else {
+            continue;
+        };
+        let position = |index: usize| mesh.positions()[index];
+        let polygons = mesh
+            .tri_faces()
+            .iter()
+            .map(|tri| tri.to_vec())
+            .chain(mesh.quad_faces().iter().map(|quad| quad.to_vec()))
+            .chain(mesh.faces().other_faces().iter().cloned());
+        for polygon in polygons {
+            let first = position(polygon[0].pos);
+            for pair in polygon[1..].windows(2) {
+                let (a, b) = (position(pair[0].pos), position(pair[1].pos));
+                triangles.push(if face.orientation() {
+                    [first, a, b]
+                } else {
+                    [first, b, a]
+                });
+            }
+        }
+    }
+    triangles
+}
@@ -0,0 +1,25 @@
+use truck_modeling::{BoundedCurve, InnerSpace, ParametricCurve};
+
+use crate::{Edge, Point3};
+
+/// Segments [`edge_length`] measures curved edges with.
+const LENGTH_SEGMENTS: usize = 64;
+
+/// `segments + 1` points along `edge` at equal parameter steps, from its front vertex to its
+/// back vertex.
+pub fn sample_edge(edge: &Edge, segments: usize) -> Vec<Point3> {
+    let curve = edge.oriented_curve();
+    let (start, end) = curve.range_tuple();
+    let segments = segments.max(1);
+    (0..=segments)
+        .map(|idx| curve.subs(start + (end - start) * idx as f64 / segments as f64))
+        .collect()
+}
+
+/// Length of `edge` along a polyline through it; exact for straight edges.
+pub fn edge_length(edge: &Edge) -> f64 {
+    sample_edge(edge, LENGTH_SEGMENTS)
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).magnitude())
+        .sum()
+}
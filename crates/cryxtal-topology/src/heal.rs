@@ -0,0 +1,274 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use truck_modeling::InnerSpace;
+use truck_topology::shell::ShellCondition;
+
+use crate::mass::signed_volume;
+use crate::{
+    Edge, Error, Face, Point3, Result, Shell, Solid, Vertex, Wire, edge_length, sample_edge,
+};
+
+/// Segments each boundary edge is sampled with when [`heal_shells`] weighs a shell's volume.
+const VOLUME_SEGMENTS: usize = 4;
+
+/// What [`heal_shells`] fixed. All zero for geometry that was already sound.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HealReport {
+    /// Vertices merged into another vertex within the tolerance, closing small gaps.
+    pub merged_vertices: usize,
+    /// Edges merged into a coincident edge of a neighbouring face, sewing the two faces.
+    pub merged_edges: usize,
+    /// Edges dropped because both of their ends merged into one vertex.
+    pub removed_edges: usize,
+    /// Faces turned around to agree with their neighbours and face outward.
+    pub flipped_faces: usize,
+}
+
+impl HealReport {
+    pub fn is_clean(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for HealReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} vertices merged, {} edges merged, {} edges removed, {} faces flipped",
+            self.merged_vertices, self.merged_edges, self.removed_edges, self.flipped_faces
+        )
+    }
+}
+
+/// Heals the boundary shells of `solid`; see [`heal_shells`].
+pub fn heal_solid(solid: &Solid, tolerance: f64) -> Result<(Solid, HealReport)> {
+    heal_shells(solid.boundaries(), tolerance)
+}
+
+/// Builds a solid from shells whose faces do not quite fit together, as imported geometry often
+/// arrives: vertices closer than `tolerance` are merged, coincident edges of neighbouring faces
+/// become one shared edge, edges shorter than `tolerance` are dropped, and faces are oriented
+/// consistently and outward. Faces keep their order unless one collapses entirely, so face tags
+/// stay valid; edge order is not kept.
+///
+/// Fails when a shell is still open afterwards, such as one with a gap wider than `tolerance`.
+pub fn heal_shells(shells: &[Shell], tolerance: f64) -> Result<(Solid, HealReport)> {
+    if tolerance <= 0.0 || !tolerance.is_finite() {
+        return Err(Error::InvalidParameter("tolerance must be > 0".to_string()));
+    }
+    let mut report = HealReport::default();
+    let faces = || shells.iter().flat_map(|shell| shell.face_iter());
+    let edges = || {
+        faces()
+            .flat_map(|face| face.absolute_boundaries().iter())
+            .flat_map(|wire| wire.edge_iter())
+    };
+
+    // Vertices snap to the first vertex within `tolerance`, found through a grid of
+    // tolerance-sized cells.
+    let mut cells: HashMap<[i64; 3], Vec<Vertex>> = HashMap::new();
+    let mut vertices = HashMap::new();
+    for vertex in edges().flat_map(|edge| [edge.absolute_front(), edge.absolute_back()]) {
+        if vertices.contains_key(&vertex.id()) {
+            continue;
+        }
+        let point = vertex.point();
+        let cell = cell_of(point, tolerance);
+        let existing = neighbour_cells(cell)
+            .filter_map(|cell| cells.get(&cell))
+            .flatten()
+            .find(|other| (other.point() - point).magnitude() <= tolerance)
+            .cloned();
+        let merged = match existing {
+            Some(other) => {
+                report.merged_vertices += 1;
+                other
+            }
+            None => {
+                let created = Vertex::new(point);
+                cells.entry(cell).or_default().push(created.clone());
+                created
+            }
+        };
+        vertices.insert(vertex.id(), merged);
+    }
+
+    // Each edge maps to a rebuilt edge between merged vertices, and whether it runs the other
+    // way; `None` for edges that collapsed.
+    let mut sewn: HashMap<_, Vec<(Edge, Point3)>> = HashMap::new();
+    let mut edge_map = HashMap::new();
+    for edge in edges() {
+        if edge_map.contains_key(&edge.id()) {
+            continue;
+        }
+        let front = &vertices[&edge.absolute_front().id()];
+        let back = &vertices[&edge.absolute_back().id()];
+        let absolute = if edge.orientation() {
+            edge.clone()
+        } else {
+            edge.inverse()
+        };
+        let middle = sample_edge(&absolute, 2)[1];
+        if front.id() == back.id() {
+            if edge_length(&absolute) > tolerance * 2.0 {
+                return Err(Error::InvalidParameter(format!(
+                    "tolerance {tolerance} collapses a closed edge; use a smaller tolerance"
+                )));
+            }
+            report.removed_edges += 1;
+            edge_map.insert(edge.id(), None);
+            continue;
+        }
+        let coincident = [
+            (front.id(), back.id(), false),
+            (back.id(), front.id(), true),
+        ]
+        .into_iter()
+        .find_map(|(a, b, reversed)| {
+            sewn.get(&(a, b))?
+                .iter()
+                .find(|(_, other)| (*other - middle).magnitude() <= tolerance)
+                .map(|(other, _)| (other.clone(), reversed))
+        });
+        let mapped = match coincident {
+            Some(mapped) => {
+                report.merged_edges += 1;
+                mapped
+            }
+            None => {
+                let created = Edge::try_new(front, back, absolute.curve()).map_err(|err| {
+                    Error::InvalidParameter(format!("edge rebuild failed: {err}"))
+                })?;
+                sewn.entry((front.id(), back.id()))
+                    .or_default()
+                    .push((created.clone(), middle));
+                (created, false)
+            }
+        };
+        edge_map.insert(edge.id(), Some(mapped));
+    }
+
+    let mut healed = Vec::with_capacity(shells.len());
+    for shell in shells {
+        let mut faces = Vec::with_capacity(shell.len());
+        for face in shell.face_iter() {
+            let wires: Vec<Wire> = face
+                .absolute_boundaries()
+                .iter()
+                .map(|wire| {
+                    wire.edge_iter()
+                        .filter_map(|edge| {
+                            let (mapped, reversed) = edge_map[&edge.id()].as_ref()?;
+                            Some(if edge.orientation() != *reversed {
+                                mapped.clone()
+                            } else {
+                                mapped.inverse()
+                            })
+                        })
+                        .collect()
+                })
+                .filter(|wire: &Wire| !wire.is_empty())
+                .collect();
+            if wires.is_empty() {
+                continue;
+            }
+            let mut healed_face = Face::try_new(wires, face.surface())
+                .map_err(|err| Error::InvalidParameter(format!("face rebuild failed: {err}")))?;
+            if !face.orientation() {
+                healed_face.invert();
+            }
+            faces.push(healed_face);
+        }
+        report.flipped_faces += orient_faces(&mut faces);
+        healed.push(Shell::from(faces));
+    }
+
+    let open = healed
+        .iter()
+        .filter(|shell| shell.shell_condition() != ShellCondition::Closed)
+        .count();
+    if open > 0 {
+        return Err(Error::InvalidParameter(format!(
+            "{open} shell(s) still open after healing ({report})"
+        )));
+    }
+    let solid = Solid::try_new(healed)
+        .map_err(|err| Error::InvalidParameter(format!("healed shells are no solid: {err}")))?;
+    Ok((solid, report))
+}
+
+/// Flips faces so every shared edge is walked in opposite directions by its two faces, then
+/// turns each connected patch outward. Returns the number of faces flipped.
+fn orient_faces(faces: &mut [Face]) -> usize {
+    let mut users: HashMap<_, Vec<(usize, bool)>> = HashMap::new();
+    for (index, face) in faces.iter().enumerate() {
+        for edge in face.boundaries().iter().flat_map(|wire| wire.edge_iter()) {
+            users
+                .entry(edge.id())
+                .or_default()
+                .push((index, edge.orientation()));
+        }
+    }
+
+    let mut flip: Vec<Option<bool>> = vec![None; faces.len()];
+    let mut flipped = 0;
+    for start in 0..faces.len() {
+        if flip[start].is_some() {
+            continue;
+        }
+        let mut patch = vec![start];
+        flip[start] = Some(false);
+        let mut queue = VecDeque::from([start]);
+        while let Some(index) = queue.pop_front() {
+            let flipped_here = flip[index] == Some(true);
+            for edge in faces[index]
+                .boundaries()
+                .iter()
+                .flat_map(|wire| wire.edge_iter())
+            {
+                for &(other, orientation) in &users[&edge.id()] {
+                    if other == index || flip[other].is_some() {
+                        continue;
+                    }
+                    // Neighbours agree when they walk the shared edge in opposite directions.
+                    flip[other] = Some(flipped_here != (orientation == edge.orientation()));
+                    patch.push(other);
+                    queue.push_back(other);
+                }
+            }
+        }
+        for &index in &patch {
+            if flip[index] == Some(true) {
+                faces[index].invert();
+            }
+        }
+        // A consistent patch enclosing negative volume is inside out.
+        if signed_volume(patch.iter().map(|&index| &faces[index]), VOLUME_SEGMENTS) < 0.0 {
+            for &index in &patch {
+                faces[index].invert();
+                flip[index] = flip[index].map(|flipped| !flipped);
+            }
+        }
+        flipped += patch
+            .iter()
+            .filter(|&&index| flip[index] == Some(true))
+            .count();
+    }
+    flipped
+}
+
+fn cell_of(point: Point3, tolerance: f64) -> [i64; 3] {
+    [
+        (point.x / tolerance).floor() as i64,
+        (point.y / tolerance).floor() as i64,
+        (point.z / tolerance).floor() as i64,
+    ]
+}
+
+fn neighbour_cells(cell: [i64; 3]) -> impl Iterator<Item = [i64; 3]> {
+    (-1..=1).flat_map(move |dx| {
+        (-1..=1)
+            .flat_map(move |dy| (-1..=1).map(move |dz| [cell[0] + dx, cell[1] + dy, cell[2] + dz]))
+    })
+}
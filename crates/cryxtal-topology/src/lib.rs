@@ -1,7 +1,31 @@
 use thiserror::Error;
-use truck_modeling::{Rad, builder};
-
-pub use truck_modeling::{Curve, Edge, Face, Point3, Shell, Solid, Surface, Vector3, Vertex, Wire};
+use truck_modeling::{Rad, SquareMatrix, builder};
+
+mod analysis;
+mod edge;
+mod heal;
+mod mass;
+mod plane;
+mod proximity;
+mod section;
+mod tags;
+mod thicken;
+
+pub use analysis::{MAX_VOXELS, VoxelGrid, solid_triangles, voxelize};
+pub use edge::{edge_length, sample_edge};
+pub use heal::{HealReport, heal_shells, heal_solid};
+pub use mass::{MassProperties, mass_properties};
+pub use plane::{PlanarFace, Plane, face_at_point, face_plane, largest_planar_face, planar_faces};
+pub use proximity::{closest_point_on_triangle, triangle_distance};
+pub use section::plane_section;
+pub use tags::{ShapeTags, solid_edges};
+pub use thicken::{thicken, thicken_face};
+pub use truck_modeling::{
+    Curve, Edge, Face, Matrix4, Point3, Shell, Solid, Surface, Vector3, Vertex, Wire,
+};
+
+/// Segments each edge is sampled with by [`solid_bounds`].
+const BOUNDS_SEGMENTS: usize = 16;
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -45,6 +69,40 @@ impl SolidBuilder {
     }
 }
 
+/// Copy of `solid` mapped by `transform`. Mirroring transforms turn the faces inside out, so
+/// their orientation is flipped back to keep the boundary facing outward.
+pub fn transform_solid(solid: &Solid, transform: Matrix4) -> Solid {
+    let mut transformed = builder::transformed(solid, transform);
+    if transform.determinant() < 0.0 {
+        transformed.not();
+    }
+    transformed
+}
+
+/// Whether `a` and `b` are the same shape, as clones of one solid are: their faces share
+/// surfaces. Rebuilt geometry never counts as the same, even when it comes out identical.
+pub fn same_geometry(a: &Solid, b: &Solid) -> bool {
+    a.face_iter()
+        .map(|face| face.id())
+        .eq(b.face_iter().map(|face| face.id()))
+}
+
+/// Axis-aligned box around the edges of `solid`, each sampled at a few points, so curved
+/// edges may bulge slightly past it. `None` for a solid without edges.
+pub fn solid_bounds(solid: &Solid) -> Option<(Point3, Point3)> {
+    let edges = solid_edges(solid);
+    let mut points = edges
+        .iter()
+        .flat_map(|edge| sample_edge(edge, BOUNDS_SEGMENTS));
+    let first = points.next()?;
+    Some(points.fold((first, first), |(min, max), point| {
+        (
+            Point3::new(min.x.min(point.x), min.y.min(point.y), min.z.min(point.z)),
+            Point3::new(max.x.max(point.x), max.y.max(point.y), max.z.max(point.z)),
+        )
+    }))
+}
+
 fn rectangle_face(width: f64, height: f64, z: f64) -> Result<Face> {
     let v0 = builder::vertex(Point3::new(0.0, 0.0, z));
     let v1 = builder::vertex(Point3::new(width, 0.0, z));
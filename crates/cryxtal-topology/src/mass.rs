@@ -0,0 +1,112 @@
+use truck_meshalgo::prelude::*;
+use truck_modeling::InnerSpace;
+
+use crate::{Face, Point3, Solid, Vector3, sample_edge, solid_bounds};
+
+/// Segments each boundary edge is sampled with by [`mass_properties`].
+const MASS_SEGMENTS: usize = 8;
+/// Chordal tolerance faces are tessellated with to measure their area, relative to the
+/// diagonal of the solid's bounding box.
+const AREA_TOLERANCE_RATIO: f64 = 1.0e-3;
+
+/// Volume, surface area and centroid of a solid, in its own units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MassProperties {
+    pub volume: f64,
+    pub area: f64,
+    /// Centre of the enclosed volume; the origin for a solid without volume.
+    pub centroid: Point3,
+}
+
+/// Mass properties of `solid`. Volume and centroid come from its face boundaries: exact for
+/// solids bounded by planar faces, while curved faces count as the polygon through their
+/// sampled boundary, which is close for the narrow faces sweeps and extrusions are built from.
+/// Area is summed over the faces tessellated finely, so curved faces count in full.
+pub fn mass_properties(solid: &Solid) -> MassProperties {
+    let mut volume = 0.0;
+    let mut moment = Vector3::new(0.0, 0.0, 0.0);
+    let mut area = 0.0;
+    let tolerance = solid_bounds(solid)
+        .map(|(min, max)| (max - min).magnitude() * AREA_TOLERANCE_RATIO)
+        .filter(|tolerance| *tolerance > 0.0)
+        .unwrap_or(AREA_TOLERANCE_RATIO);
+    let meshed = solid.triangulation(tolerance);
+    for (face, meshed) in solid.face_iter().zip(meshed.face_iter()) {
+        let mut vector_area = Vector3::new(0.0, 0.0, 0.0);
+        for (first, triangle) in fan_triangles(face, MASS_SEGMENTS) {
+            let [a, b] = triangle;
+            // Tetrahedron from the origin to the triangle.
+            let tetra = first.dot(a.cross(b)) / 6.0;
+            volume += tetra;
+            moment += (first + a + b) * (tetra / 4.0);
+            vector_area += (a - first).cross(b - first) * 0.5;
+        }
+        // A face that fails to tessellate counts as its boundary polygon.
+        area += match meshed.surface() {
+            Some(mesh) => mesh_area(&mesh),
+            None => vector_area.magnitude(),
+        };
+    }
+    let centroid = if volume.abs() > f64::EPSILON {
+        moment / volume
+    } else {
+        Vector3::new(0.0, 0.0, 0.0)
+    };
+    MassProperties {
+        volume: volume.abs(),
+        area,
+        centroid: Point3::new(centroid.x, centroid.y, centroid.z),
+    }
+}
+
+/// Sum of the areas of the polygons of `mesh`, each split into a fan of triangles.
+fn mesh_area(mesh: &PolygonMesh) -> f64 {
+    let positions = mesh.positions();
+    mesh.faces()
+        .face_iter()
+        .map(|polygon| {
+            let first = positions[polygon[0].pos];
+            polygon[1..]
+                .windows(2)
+                .map(|pair| {
+                    let (a, b) = (positions[pair[0].pos], positions[pair[1].pos]);
+                    (a - first).cross(b - first).magnitude() * 0.5
+                })
+                .sum::<f64>()
+        })
+        .sum()
+}
+
+/// Volume enclosed by `faces`, positive when they face outward; see [`mass_properties`].
+pub(crate) fn signed_volume<'a>(faces: impl Iterator<Item = &'a Face>, segments: usize) -> f64 {
+    faces
+        .flat_map(|face| fan_triangles(face, segments))
+        .map(|(first, [a, b])| first.dot(a.cross(b)) / 6.0)
+        .sum()
+}
+
+/// Each boundary wire of `face`, sampled in the face's orientation, as a fan of triangles from
+/// its first point. Hole wires run the other way and cancel the area they cut out.
+pub(crate) fn fan_triangles(face: &Face, segments: usize) -> Vec<(Vector3, [Vector3; 2])> {
+    let mut triangles = Vec::new();
+    for wire in face.boundaries() {
+        let points: Vec<Vector3> = wire
+            .edge_iter()
+            .flat_map(|edge| {
+                let mut points = sample_edge(edge, segments);
+                points.pop();
+                points
+            })
+            .map(|point| Vector3::new(point.x, point.y, point.z))
+            .collect();
+        let Some(&first) = points.first() else {
+            continue;
+        };
+        triangles.extend(
+            points[1..]
+                .windows(2)
+                .map(|pair| (first, [pair[0], pair[1]])),
+        );
+    }
+    triangles
+}
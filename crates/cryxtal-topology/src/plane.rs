@@ -0,0 +1,160 @@
+use truck_modeling::InnerSpace;
+
+use crate::mass::fan_triangles;
+use crate::{Face, Point3, Solid, Vector3, sample_edge};
+
+/// Segments each boundary edge is sampled with to find and test a face's plane.
+const PLANE_SEGMENTS: usize = 8;
+
+/// A plane through `origin` with unit `normal`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    pub origin: Point3,
+    pub normal: Vector3,
+}
+
+impl Plane {
+    /// Signed distance of `point` from the plane, positive on the side `normal` points to.
+    pub fn distance(&self, point: Point3) -> f64 {
+        (point - self.origin).dot(self.normal)
+    }
+}
+
+/// A planar face of a solid, by its position in [`Solid::face_iter`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlanarFace {
+    pub index: usize,
+    /// Plane of the face through its centroid, with the normal facing out of the solid.
+    pub plane: Plane,
+    pub area: f64,
+}
+
+/// Plane of `face` through its centroid, normal in the face's orientation, when every sampled
+/// boundary point lies within `tolerance` of it. `None` for curved faces and faces without area.
+pub fn face_plane(face: &Face, tolerance: f64) -> Option<Plane> {
+    face_plane_area(face, tolerance).map(|(plane, _)| plane)
+}
+
+/// Planar faces of `solid`, in face order; see [`face_plane`].
+pub fn planar_faces(solid: &Solid, tolerance: f64) -> Vec<PlanarFace> {
+    solid
+        .face_iter()
+        .enumerate()
+        .filter_map(|(index, face)| {
+            let (plane, area) = face_plane_area(face, tolerance)?;
+            Some(PlanarFace { index, plane, area })
+        })
+        .collect()
+}
+
+/// The planar face of `solid` with the largest area, such as the main face of a wall or slab
+/// to sketch on.
+pub fn largest_planar_face(solid: &Solid, tolerance: f64) -> Option<PlanarFace> {
+    planar_faces(solid, tolerance)
+        .into_iter()
+        .max_by(|a, b| a.area.total_cmp(&b.area))
+}
+
+/// The planar face of `solid` that `point` lies on, within `tolerance`, such as the face under
+/// a point picked on the solid's mesh. The nearest face wins where faces meet.
+pub fn face_at_point(solid: &Solid, point: Point3, tolerance: f64) -> Option<usize> {
+    solid
+        .face_iter()
+        .enumerate()
+        .filter_map(|(index, face)| {
+            let plane = face_plane(face, tolerance)?;
+            let distance = plane.distance(point).abs();
+            (distance <= tolerance && contains(face, &plane, point, tolerance))
+                .then_some((index, distance))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(index, _)| index)
+}
+
+fn face_plane_area(face: &Face, tolerance: f64) -> Option<(Plane, f64)> {
+    let triangles = fan_triangles(face, PLANE_SEGMENTS);
+    let vector_area = triangles
+        .iter()
+        .fold(Vector3::new(0.0, 0.0, 0.0), |sum, (first, [a, b])| {
+            sum + (a - first).cross(b - first) * 0.5
+        });
+    let area = vector_area.magnitude();
+    if area <= f64::EPSILON {
+        return None;
+    }
+    let normal = vector_area / area;
+    let (first, _) = triangles.first()?;
+    let planar = triangles
+        .iter()
+        .flat_map(|(_, points)| points)
+        .all(|point| (point - first).dot(normal).abs() <= tolerance);
+    if !planar {
+        return None;
+    }
+    // Triangle centroids weighted by their signed area; hole triangles count negative.
+    let moment = triangles
+        .iter()
+        .fold(Vector3::new(0.0, 0.0, 0.0), |sum, (first, [a, b])| {
+            let weight = (a - first).cross(b - first).dot(normal) * 0.5;
+            sum + (first + a + b) * (weight / 3.0)
+        });
+    let centroid = moment / area;
+    let plane = Plane {
+        origin: Point3::new(centroid.x, centroid.y, centroid.z),
+        normal,
+    };
+    Some((plane, area))
+}
+
+/// Whether `point`, projected onto the face's `plane`, is inside its boundary wires; points
+/// within `tolerance` of the boundary count as inside.
+fn contains(face: &Face, plane: &Plane, point: Point3, tolerance: f64) -> bool {
+    let u = plane
+        .normal
+        .cross(any_perpendicular(plane.normal))
+        .normalize();
+    let v = plane.normal.cross(u);
+    let flat = |p: Point3| [(p - plane.origin).dot(u), (p - plane.origin).dot(v)];
+    let [x, y] = flat(point);
+    let mut inside = false;
+    for wire in face.boundaries() {
+        let polygon: Vec<[f64; 2]> = wire
+            .edge_iter()
+            .flat_map(|edge| {
+                let mut points = sample_edge(edge, PLANE_SEGMENTS);
+                points.pop();
+                points
+            })
+            .map(flat)
+            .collect();
+        for (i, &[x0, y0]) in polygon.iter().enumerate() {
+            let [x1, y1] = polygon[(i + 1) % polygon.len()];
+            if segment_distance([x, y], [x0, y0], [x1, y1]) <= tolerance {
+                return true;
+            }
+            if (y0 > y) != (y1 > y) && x < x0 + (y - y0) * (x1 - x0) / (y1 - y0) {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+fn segment_distance(p: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length = dx * dx + dy * dy;
+    let t = if length > 0.0 {
+        (((p[0] - a[0]) * dx + (p[1] - a[1]) * dy) / length).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (p[0] - a[0] - t * dx).hypot(p[1] - a[1] - t * dy)
+}
+
+fn any_perpendicular(normal: Vector3) -> Vector3 {
+    if normal.x.abs() < 0.9 {
+        Vector3::unit_x()
+    } else {
+        Vector3::unit_y()
+    }
+}
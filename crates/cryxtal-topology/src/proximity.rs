@@ -0,0 +1,127 @@
+use truck_modeling::InnerSpace;
+
+use crate::Point3;
+
+/// Shortest distance between two triangles with the closest point on each: 0 at a crossing
+/// point if an edge of one passes through the other, otherwise the nearest of the corner to
+/// triangle and edge to edge distances.
+pub fn triangle_distance(a: &[Point3; 3], b: &[Point3; 3]) -> (f64, Point3, Point3) {
+    for (triangle, other) in [(a, b), (b, a)] {
+        for edge in 0..3 {
+            let (start, end) = (triangle[edge], triangle[(edge + 1) % 3]);
+            if let Some(point) = segment_crossing(start, end, other) {
+                return (0.0, point, point);
+            }
+        }
+    }
+    let mut best = (f64::INFINITY, a[0], b[0]);
+    let mut consider = |pa: Point3, pb: Point3| {
+        let gap = (pb - pa).magnitude();
+        if gap < best.0 {
+            best = (gap, pa, pb);
+        }
+    };
+    for corner in a {
+        consider(*corner, closest_point_on_triangle(b, *corner));
+    }
+    for corner in b {
+        consider(closest_point_on_triangle(a, *corner), *corner);
+    }
+    for i in 0..3 {
+        for j in 0..3 {
+            let (pa, pb) = segment_closest_points(a[i], a[(i + 1) % 3], b[j], b[(j + 1) % 3]);
+            consider(pa, pb);
+        }
+    }
+    best
+}
+
+/// Where segment `start`-`end` passes through triangle `[a, b, c]`, if it does.
+fn segment_crossing(start: Point3, end: Point3, [a, b, c]: &[Point3; 3]) -> Option<Point3> {
+    let direction = end - start;
+    let (ab, ac) = (b - a, c - a);
+    let p = direction.cross(ac);
+    let det = ab.dot(p);
+    if det.abs() <= f64::EPSILON * ab.magnitude2().max(ac.magnitude2()) * direction.magnitude() {
+        return None;
+    }
+    let offset = start - a;
+    let u = offset.dot(p) / det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = offset.cross(ab);
+    let v = direction.dot(q) / det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = ac.dot(q) / det;
+    (0.0..=1.0).contains(&t).then(|| start + direction * t)
+}
+
+/// Closest points between segments `p1`-`q1` and `p2`-`q2`.
+fn segment_closest_points(p1: Point3, q1: Point3, p2: Point3, q2: Point3) -> (Point3, Point3) {
+    let (d1, d2, r) = (q1 - p1, q2 - p2, p1 - p2);
+    let (a, e, f) = (d1.magnitude2(), d2.magnitude2(), d2.dot(r));
+    let (s, t) = if a <= f64::EPSILON && e <= f64::EPSILON {
+        (0.0, 0.0)
+    } else if a <= f64::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+        if e <= f64::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denominator = a * e - b * b;
+            let s = if denominator > 0.0 {
+                ((b * f - c * e) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                ((-c / a).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+/// Point of triangle `[a, b, c]` closest to `p`, by the Voronoi regions of its corners and
+/// edges.
+pub fn closest_point_on_triangle([a, b, c]: &[Point3; 3], p: Point3) -> Point3 {
+    let (ab, ac, ap) = (b - a, c - a, p - a);
+    let (d1, d2) = (ab.dot(ap), ac.dot(ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return *a;
+    }
+    let bp = p - b;
+    let (d3, d4) = (ab.dot(bp), ac.dot(bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return *b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = p - c;
+    let (d5, d6) = (ab.dot(cp), ac.dot(cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return *c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let denominator = 1.0 / (va + vb + vc);
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
@@ -0,0 +1,77 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Plane, Point3, Solid, solid_triangles};
+
+/// Polylines where `plane` cuts the boundary of `solid` tessellated at `tolerance`. Cuts through
+/// closed shells come out as closed polylines, ending at the point they start from, wound
+/// counterclockwise seen from the side the plane's normal points to around the material (so
+/// voids run clockwise); open surfaces give open polylines. Points on the plane count as lying
+/// in front of it, so a plane through a face leaves the face out of the section.
+pub fn plane_section(solid: &Solid, plane: &Plane, tolerance: f64) -> Vec<Vec<Point3>> {
+    // One segment per crossed triangle, from where the boundary leaves the front of the plane
+    // to where it comes back. The crossing point of a mesh edge is computed the same way for
+    // both triangles sharing it, so segments chain on exact point equality.
+    let front = |point: &Point3| plane.distance(*point) >= 0.0;
+    let crossing = |a: Point3, b: Point3| {
+        let (a, b) = if (a.x, a.y, a.z) <= (b.x, b.y, b.z) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let (da, db) = (plane.distance(a), plane.distance(b));
+        a + (b - a) * (da / (da - db))
+    };
+    let mut segments: Vec<(Point3, Point3)> = Vec::new();
+    for triangle in solid_triangles(solid, tolerance) {
+        let (mut exit, mut entry) = (None, None);
+        for i in 0..3 {
+            let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+            match (front(&a), front(&b)) {
+                (true, false) => exit = Some(crossing(a, b)),
+                (false, true) => entry = Some(crossing(a, b)),
+                _ => {}
+            }
+        }
+        if let (Some(exit), Some(entry)) = (exit, entry) {
+            segments.push((exit, entry));
+        }
+    }
+
+    let key = |point: Point3| [point.x, point.y, point.z].map(f64::to_bits);
+    let mut starting: HashMap<[u64; 3], Vec<usize>> = HashMap::new();
+    for (index, (start, _)) in segments.iter().enumerate() {
+        starting.entry(key(*start)).or_default().push(index);
+    }
+    let ends: HashSet<[u64; 3]> = segments.iter().map(|(_, end)| key(*end)).collect();
+    let mut used = vec![false; segments.len()];
+    let mut polylines = Vec::new();
+    // Open chains first, from the segments nothing leads into, then the closed loops.
+    let open_starts: Vec<usize> = (0..segments.len())
+        .filter(|index| !ends.contains(&key(segments[*index].0)))
+        .collect();
+    for first in open_starts.into_iter().chain(0..segments.len()) {
+        if used[first] {
+            continue;
+        }
+        let mut polyline = vec![segments[first].0];
+        let mut current = first;
+        loop {
+            used[current] = true;
+            let end = segments[current].1;
+            if polyline.last() != Some(&end) {
+                polyline.push(end);
+            }
+            let next = starting
+                .get(&key(end))
+                .and_then(|next| next.iter().copied().find(|index| !used[*index]));
+            match next {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        if polyline.len() >= 2 {
+            polylines.push(polyline);
+        }
+    }
+    polylines
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::{Edge, Error, Result, Solid};
+
+/// Tags on individual faces and edges of a solid, such as `formwork`, `finish:paint` or
+/// `weld edge`.
+///
+/// Faces are addressed by their position in [`Solid::face_iter`] and edges by their position in
+/// [`solid_edges`]. Both orders are what the solid is stored and exported in, so indices stay
+/// valid across save/load and for solids rebuilt with the same topology.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShapeTags {
+    #[serde(default)]
+    faces: BTreeMap<usize, BTreeSet<String>>,
+    #[serde(default)]
+    edges: BTreeMap<usize, BTreeSet<String>>,
+}
+
+impl ShapeTags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.faces.is_empty() && self.edges.is_empty()
+    }
+
+    pub fn tag_face(&mut self, solid: &Solid, face: usize, tag: impl Into<String>) -> Result<()> {
+        ensure_index("face", face, solid.face_iter().count())?;
+        insert_tag(&mut self.faces, face, tag.into())
+    }
+
+    pub fn tag_edge(&mut self, solid: &Solid, edge: usize, tag: impl Into<String>) -> Result<()> {
+        ensure_index("edge", edge, solid_edges(solid).len())?;
+        insert_tag(&mut self.edges, edge, tag.into())
+    }
+
+    /// Returns `false` when the face did not carry `tag`.
+    pub fn untag_face(&mut self, face: usize, tag: &str) -> bool {
+        remove_tag(&mut self.faces, face, tag)
+    }
+
+    /// Returns `false` when the edge did not carry `tag`.
+    pub fn untag_edge(&mut self, edge: usize, tag: &str) -> bool {
+        remove_tag(&mut self.edges, edge, tag)
+    }
+
+    pub fn face_tags(&self, face: usize) -> impl Iterator<Item = &str> {
+        self.faces
+            .get(&face)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    pub fn edge_tags(&self, edge: usize) -> impl Iterator<Item = &str> {
+        self.edges
+            .get(&edge)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Tagged faces in index order.
+    pub fn tagged_faces(&self) -> impl Iterator<Item = (usize, &BTreeSet<String>)> {
+        self.faces.iter().map(|(index, tags)| (*index, tags))
+    }
+
+    /// Tagged edges in index order.
+    pub fn tagged_edges(&self) -> impl Iterator<Item = (usize, &BTreeSet<String>)> {
+        self.edges.iter().map(|(index, tags)| (*index, tags))
+    }
+
+    pub fn faces_with<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = usize> + 'a {
+        indices_with(&self.faces, tag)
+    }
+
+    pub fn edges_with<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = usize> + 'a {
+        indices_with(&self.edges, tag)
+    }
+
+    /// Drops tags on faces and edges `solid` does not have, e.g. after its geometry was
+    /// regenerated with fewer faces.
+    pub fn retain_valid(&mut self, solid: &Solid) {
+        let faces = solid.face_iter().count();
+        let edges = solid_edges(solid).len();
+        self.faces.retain(|index, _| *index < faces);
+        self.edges.retain(|index, _| *index < edges);
+    }
+}
+
+/// Edges of `solid` in stable order: first appearance while walking the face boundaries, with
+/// edges shared by two faces listed once.
+pub fn solid_edges(solid: &Solid) -> Vec<Edge> {
+    let mut seen = HashSet::new();
+    solid
+        .edge_iter()
+        .filter(|edge| seen.insert(edge.id()))
+        .collect()
+}
+
+fn ensure_index(kind: &str, index: usize, count: usize) -> Result<()> {
+    if index >= count {
+        return Err(Error::InvalidParameter(format!(
+            "{kind} {index} out of range (solid has {count})"
+        )));
+    }
+    Ok(())
+}
+
+fn insert_tag(
+    map: &mut BTreeMap<usize, BTreeSet<String>>,
+    index: usize,
+    tag: String,
+) -> Result<()> {
+    let tag = tag.trim();
+    if tag.is_empty() || tag.chars().any(char::is_control) {
+        return Err(Error::InvalidParameter(format!("invalid tag {tag:?}")));
+    }
+    map.entry(index).or_default().insert(tag.to_string());
+    Ok(())
+}
+
+fn remove_tag(map: &mut BTreeMap<usize, BTreeSet<String>>, index: usize, tag: &str) -> bool {
+    let Some(tags) = map.get_mut(&index) else {
+        return false;
+    };
+    let removed = tags.remove(tag);
+    if tags.is_empty() {
+        map.remove(&index);
+    }
+    removed
+}
+
+fn indices_with<'a>(
+    map: &'a BTreeMap<usize, BTreeSet<String>>,
+    tag: &'a str,
+) -> impl Iterator<Item = usize> + 'a {
+    map.iter()
+        .filter(move |(_, tags)| tags.contains(tag))
+        .map(|(index, _)| *index)
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use truck_modeling::{InnerSpace, Transformed, builder};
+
+use crate::mass::signed_volume;
+use crate::{
+    Edge, Error, Face, Matrix4, Result, Shell, Solid, Vector3, Vertex, Wire, face_plane,
+    heal_shells, sample_edge,
+};
+
+/// Segments each boundary edge is sampled with to weigh a thickened face's volume.
+const VOLUME_SEGMENTS: usize = 8;
+
+/// Solid from an open surface, such as a wall or panel imported as faces only: the faces are
+/// offset `thickness` along their normals (against them when negative) and joined to the
+/// originals along the open boundary. Where faces meet at an angle the offset faces meet
+/// along the same edge, as in a folded plate.
+///
+/// Faces must be planar, and at most three differently oriented faces may meet at a vertex.
+pub fn thicken(shell: &Shell, thickness: f64) -> Result<Solid> {
+    if thickness == 0.0 || !thickness.is_finite() {
+        return Err(Error::InvalidParameter(
+            "thickness must be non-zero".to_string(),
+        ));
+    }
+    let tolerance = thickness.abs() * 1.0e-3;
+    if let [face] = shell.as_slice() {
+        let normal = face_normal(face, tolerance)?;
+        let mut solid = builder::tsweep(face, normal * thickness);
+        if signed_volume(solid.face_iter(), VOLUME_SEGMENTS) < 0.0 {
+            solid.not();
+        }
+        return Ok(solid);
+    }
+
+    // Offset of each vertex: on the offset plane of every face around it.
+    let mut normals: HashMap<_, Vec<Vector3>> = HashMap::new();
+    let mut users: HashMap<_, usize> = HashMap::new();
+    for face in shell.face_iter() {
+        let normal = face_normal(face, tolerance)?;
+        for edge in face.boundaries().iter().flat_map(|wire| wire.edge_iter()) {
+            *users.entry(edge.id()).or_default() += 1;
+            let around = normals.entry(edge.front().id()).or_default();
+            if !around.iter().any(|other| other.dot(normal) > 1.0 - 1.0e-9) {
+                around.push(normal);
+            }
+        }
+    }
+    let mut offsets = HashMap::new();
+    for (vertex, around) in &normals {
+        offsets.insert(*vertex, vertex_offset(around, thickness, tolerance)?);
+    }
+
+    let mut vertices = HashMap::new();
+    let mut offset_vertex = |vertex: &Vertex| {
+        vertices
+            .entry(vertex.id())
+            .or_insert_with(|| builder::vertex(vertex.point() + offsets[&vertex.id()]))
+            .clone()
+    };
+    let mut offset_edges = HashMap::new();
+    let mut faces: Vec<Face> = shell.face_iter().cloned().collect();
+    let mut sides = Vec::new();
+    for face in shell.face_iter() {
+        let mut wires = Vec::new();
+        for wire in face.boundaries() {
+            let mut offset_wire = Wire::new();
+            for edge in wire.edge_iter() {
+                let offset = match offset_edges.get(&edge.id()) {
+                    Some((offset, orientation)) if *orientation == edge.orientation() => {
+                        Edge::clone(offset)
+                    }
+                    Some((offset, _)) => offset.inverse(),
+                    None => {
+                        let front = offsets[&edge.front().id()];
+                        let back = offsets[&edge.back().id()];
+                        let translation = (front - back).magnitude() <= tolerance;
+                        let (v0, v1) = (offset_vertex(edge.front()), offset_vertex(edge.back()));
+                        let offset = if translation {
+                            let curve = edge.oriented_curve();
+                            Edge::new(
+                                &v0,
+                                &v1,
+                                curve.transformed(Matrix4::from_translation(front)),
+                            )
+                        } else if is_straight(edge, tolerance) {
+                            builder::line(&v0, &v1)
+                        } else {
+                            return Err(Error::InvalidParameter(
+                                "a curved edge between faces at an angle cannot be offset"
+                                    .to_string(),
+                            ));
+                        };
+                        if users[&edge.id()] == 1 {
+                            sides.push(side_face(edge, &offset, translation.then_some(front))?);
+                        }
+                        offset_edges.insert(edge.id(), (offset.clone(), edge.orientation()));
+                        offset
+                    }
+                };
+                offset_wire.push_back(offset);
+            }
+            wires.push(offset_wire);
+        }
+        faces.push(builder::try_attach_plane(&wires)?);
+    }
+    if sides.is_empty() {
+        return Err(Error::InvalidParameter(
+            "shell is closed; only open surfaces can be thickened".to_string(),
+        ));
+    }
+    faces.extend(sides);
+    let (solid, _) = heal_shells(&[Shell::from(faces)], tolerance)?;
+    Ok(solid)
+}
+
+/// Thickens a single face; see [`thicken`].
+pub fn thicken_face(face: &Face, thickness: f64) -> Result<Solid> {
+    thicken(&Shell::from(vec![face.clone()]), thickness)
+}
+
+/// Unit normal of a planar `face`, in its orientation.
+fn face_normal(face: &Face, tolerance: f64) -> Result<Vector3> {
+    face_plane(face, tolerance)
+        .map(|plane| plane.normal)
+        .ok_or_else(|| Error::InvalidParameter("only planar faces can be thickened".to_string()))
+}
+
+/// The offset putting a vertex `thickness` off each of the planes with unit `normals` around it.
+fn vertex_offset(normals: &[Vector3], thickness: f64, tolerance: f64) -> Result<Vector3> {
+    let offset = match normals {
+        [normal] => normal * thickness,
+        [a, b] if 1.0 + a.dot(*b) > 1.0e-9 => (a + b) * (thickness / (1.0 + a.dot(*b))),
+        [_, _] => {
+            return Err(Error::InvalidParameter(
+                "faces fold back onto each other at a vertex".to_string(),
+            ));
+        }
+        [a, b, c, ..] => {
+            let determinant = a.dot(b.cross(*c));
+            if determinant.abs() <= 1.0e-9 {
+                return Err(Error::InvalidParameter(
+                    "faces around a vertex are too close to parallel to offset".to_string(),
+                ));
+            }
+            (b.cross(*c) + c.cross(*a) + a.cross(*b)) * (thickness / determinant)
+        }
+        [] => Vector3::new(0.0, 0.0, 0.0),
+    };
+    if normals
+        .iter()
+        .any(|normal| (normal.dot(offset) - thickness).abs() > tolerance)
+    {
+        return Err(Error::InvalidParameter(
+            "more than three differently oriented faces meet at a vertex".to_string(),
+        ));
+    }
+    Ok(offset)
+}
+
+/// Face closing the gap between an open boundary `edge` and its `offset`, swept along
+/// `translation` when the offset is a translated copy.
+fn side_face(edge: &Edge, offset: &Edge, translation: Option<Vector3>) -> Result<Face> {
+    if let Some(translation) = translation {
+        return Ok(builder::tsweep(edge, translation));
+    }
+    let wire: Wire = vec![
+        edge.clone(),
+        builder::line(edge.back(), offset.back()),
+        offset.inverse(),
+        builder::line(offset.front(), edge.front()),
+    ]
+    .into();
+    builder::try_attach_plane(&[wire]).map_err(|_| {
+        Error::InvalidParameter("the side along an open edge is not planar".to_string())
+    })
+}
+
+fn is_straight(edge: &Edge, tolerance: f64) -> bool {
+    let points = sample_edge(edge, 4);
+    let (start, end) = (points[0], points[points.len() - 1]);
+    let direction = end - start;
+    let length = direction.magnitude();
+    length > f64::EPSILON
+        && points
+            .iter()
+            .all(|point| (point - start).cross(direction).magnitude() / length <= tolerance)
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use cryxtal_topology::{
+    Edge, Face, Result, Shell, Solid, SolidBuilder, Vector3, Vertex, Wire, heal_shells, heal_solid,
+    solid_edges,
+};
+
+/// The faces of `solid` with vertices and edges of their own, face `n` nudged by `n * jitter`,
+/// the way faces arrive from a file that does not share topology between them.
+fn loose_faces(solid: &Solid, jitter: f64) -> Vec<Face> {
+    solid
+        .face_iter()
+        .enumerate()
+        .map(|(index, face)| {
+            let shift = Vector3::new(jitter * index as f64, 0.0, 0.0);
+            let mut vertices = HashMap::new();
+            let wires = face
+                .absolute_boundaries()
+                .iter()
+                .map(|wire| {
+                    wire.edge_iter()
+                        .map(|edge| {
+                            let mut vertex = |vertex: &Vertex| {
+                                vertices
+                                    .entry(vertex.id())
+                                    .or_insert_with(|| Vertex::new(vertex.point() + shift))
+                                    .clone()
+                            };
+                            let front = vertex(edge.front());
+                            let back = vertex(edge.back());
+                            Edge::new(&front, &back, edge.oriented_curve())
+                        })
+                        .collect::<Wire>()
+                })
+                .collect();
+            let mut loose = Face::new(wires, face.surface());
+            if !face.orientation() {
+                loose.invert();
+            }
+            loose
+        })
+        .collect()
+}
+
+#[test]
+fn healing_sews_loose_faces_into_a_closed_solid() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let (_, report) = heal_solid(&solid, 0.01)?;
+    assert!(report.is_clean());
+
+    let mut faces = loose_faces(&solid, 1.0e-4);
+    faces[2].invert();
+    let (healed, report) = heal_shells(&[Shell::from(faces)], 0.01)?;
+    assert_eq!(report.merged_vertices, 24 - 8);
+    assert_eq!(report.merged_edges, 24 - 12);
+    assert_eq!(report.removed_edges, 0);
+    assert_eq!(report.flipped_faces, 1);
+    assert_eq!(healed.face_iter().count(), 6);
+    assert_eq!(solid_edges(&healed).len(), 12);
+
+    // Gaps wider than the tolerance stay open.
+    assert!(heal_shells(&[Shell::from(loose_faces(&solid, 1.0))], 0.01).is_err());
+    assert!(heal_solid(&solid, 0.0).is_err());
+    Ok(())
+}
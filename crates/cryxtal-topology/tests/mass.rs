@@ -0,0 +1,19 @@
+use cryxtal_topology::{Point3, Result, SolidBuilder, mass_properties};
+
+#[test]
+fn mass_properties_measure_boxes_exactly_and_cylinders_closely() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let mass = mass_properties(&solid);
+    assert!((mass.volume - 6.0e6).abs() < 1.0e-6);
+    assert!((mass.area - 2.2e5).abs() < 1.0e-6);
+    let Point3 { x, y, z } = mass.centroid;
+    assert!((x - 50.0).abs() < 1.0e-9 && (y - 100.0).abs() < 1.0e-9 && (z - 150.0).abs() < 1.0e-9);
+
+    let cylinder = SolidBuilder::cylinder_z(Point3::new(0.0, 0.0, 0.0), 50.0, 100.0)?;
+    let exact = std::f64::consts::PI * 50.0 * 50.0 * 100.0;
+    assert!((mass_properties(&cylinder).volume - exact).abs() / exact < 0.01);
+    // The curved side counts in full, not as the polygon through its seam.
+    let exact = std::f64::consts::PI * 50.0 * (2.0 * 50.0 + 2.0 * 100.0);
+    assert!((mass_properties(&cylinder).area - exact).abs() / exact < 0.005);
+    Ok(())
+}
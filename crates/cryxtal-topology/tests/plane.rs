@@ -0,0 +1,30 @@
+use cryxtal_topology::{
+    Point3, Result, SolidBuilder, Vector3, face_at_point, face_plane, largest_planar_face,
+    planar_faces,
+};
+
+#[test]
+fn planar_faces_are_found_measured_and_picked() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let faces = planar_faces(&solid, 1.0e-6);
+    assert_eq!(faces.len(), 6);
+    let total: f64 = faces.iter().map(|face| face.area).sum();
+    assert!((total - 2.2e5).abs() < 1.0e-6);
+
+    // The largest faces are the 200 x 300 ones at x = 0 and x = 100, facing out.
+    let largest = largest_planar_face(&solid, 1.0e-6).expect("largest face");
+    assert!((largest.area - 6.0e4).abs() < 1.0e-6);
+    let Point3 { x, y, z } = largest.plane.origin;
+    assert!((y - 100.0).abs() < 1.0e-9 && (z - 150.0).abs() < 1.0e-9);
+    let outward = if x < 50.0 { -1.0 } else { 1.0 };
+    assert_eq!(largest.plane.normal, Vector3::new(outward, 0.0, 0.0));
+
+    let top = face_at_point(&solid, Point3::new(40.0, 120.0, 300.0), 1.0e-6).expect("top face");
+    let plane = face_plane(solid.face_iter().nth(top).expect("face"), 1.0e-6).expect("planar");
+    assert!((plane.normal.z - 1.0).abs() < 1.0e-9);
+    assert!(face_at_point(&solid, Point3::new(40.0, 120.0, 310.0), 1.0e-6).is_none());
+
+    let cylinder = SolidBuilder::cylinder_z(Point3::new(0.0, 0.0, 0.0), 50.0, 100.0)?;
+    assert_eq!(planar_faces(&cylinder, 1.0e-6).len(), 2);
+    Ok(())
+}
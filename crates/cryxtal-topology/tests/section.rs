@@ -0,0 +1,28 @@
+use cryxtal_topology::{Plane, Point3, Result, SolidBuilder, Vector3, plane_section};
+
+#[test]
+fn box_sections_are_closed_counterclockwise_loops() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let plane = Plane {
+        origin: Point3::new(0.0, 0.0, 150.0),
+        normal: Vector3::new(0.0, 0.0, 1.0),
+    };
+    let loops = plane_section(&solid, &plane, 0.1);
+    assert_eq!(loops.len(), 1);
+    let outline = &loops[0];
+    assert_eq!(outline.first(), outline.last());
+    assert!(outline.iter().all(|point| (point.z - 150.0).abs() < 1.0e-9));
+    let area: f64 = outline
+        .windows(2)
+        .map(|pair| pair[0].x * pair[1].y - pair[1].x * pair[0].y)
+        .sum::<f64>()
+        / 2.0;
+    assert!((area - 20_000.0).abs() < 1.0e-6);
+
+    let above = Plane {
+        origin: Point3::new(0.0, 0.0, 400.0),
+        ..plane
+    };
+    assert!(plane_section(&solid, &above, 0.1).is_empty());
+    Ok(())
+}
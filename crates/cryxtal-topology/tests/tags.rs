@@ -0,0 +1,44 @@
+use cryxtal_topology::{Result, ShapeTags, SolidBuilder, edge_length, solid_edges};
+
+#[test]
+fn tags_address_faces_and_edges_by_index() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    assert_eq!(solid_edges(&solid).len(), 12);
+
+    let mut tags = ShapeTags::new();
+    tags.tag_face(&solid, 0, "formwork")?;
+    tags.tag_face(&solid, 5, "finish:paint")?;
+    tags.tag_face(&solid, 5, "formwork")?;
+    tags.tag_edge(&solid, 11, "weld edge")?;
+    assert!(tags.tag_face(&solid, 6, "formwork").is_err());
+    assert!(tags.tag_edge(&solid, 0, " ").is_err());
+
+    assert_eq!(tags.faces_with("formwork").collect::<Vec<_>>(), [0, 5]);
+    assert_eq!(
+        tags.face_tags(5).collect::<Vec<_>>(),
+        ["finish:paint", "formwork"]
+    );
+    assert_eq!(tags.edges_with("weld edge").collect::<Vec<_>>(), [11]);
+
+    assert!(tags.untag_face(0, "formwork"));
+    assert!(!tags.untag_face(0, "formwork"));
+    assert_eq!(tags.faces_with("formwork").collect::<Vec<_>>(), [5]);
+
+    let cube = SolidBuilder::box_solid(10.0, 10.0, 10.0)?;
+    let mut kept = tags.clone();
+    kept.retain_valid(&cube);
+    assert_eq!(kept, tags);
+    Ok(())
+}
+
+#[test]
+fn box_edges_measure_their_sides() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let mut lengths: Vec<f64> = solid_edges(&solid).iter().map(edge_length).collect();
+    lengths.sort_by(f64::total_cmp);
+    let total: f64 = lengths.iter().sum();
+    assert!((total - 2400.0).abs() < 1.0e-6);
+    assert!((lengths[0] - 100.0).abs() < 1.0e-6);
+    assert!((lengths[11] - 300.0).abs() < 1.0e-6);
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use cryxtal_topology::{
+    Result, Shell, SolidBuilder, edge_length, mass_properties, thicken, thicken_face,
+};
+
+#[test]
+fn thickening_gives_surfaces_volume_and_mitres_folds() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let faces: Vec<_> = solid.face_iter().cloned().collect();
+    let area = |index: usize| -> Result<f64> {
+        Ok(mass_properties(&thicken_face(&faces[index], 1.0)?).volume)
+    };
+
+    let panel = thicken_face(&faces[0], -10.0)?;
+    assert!((mass_properties(&panel).volume - area(0)? * 10.0).abs() < 1.0e-6);
+
+    // Two faces meeting along an edge thicken into an L with a square corner.
+    let shared = faces[1..]
+        .iter()
+        .enumerate()
+        .find_map(|(index, face)| {
+            let edges: Vec<_> = face.edge_iter().map(|edge| edge.id()).collect();
+            faces[0]
+                .edge_iter()
+                .find(|edge| edges.contains(&edge.id()))
+                .map(|edge| (index + 1, edge_length(&edge)))
+        })
+        .expect("box faces share edges");
+    let (other, length) = shared;
+    let folded = thicken(
+        &Shell::from(vec![faces[0].clone(), faces[other].clone()]),
+        10.0,
+    )?;
+    let expected = (area(0)? + area(other)?) * 10.0 + 10.0 * 10.0 * length;
+    assert!((mass_properties(&folded).volume - expected).abs() < 1.0e-6);
+
+    assert!(thicken(&Shell::from(faces.clone()), 10.0).is_err());
+    assert!(thicken_face(&faces[0], 0.0).is_err());
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use cryxtal_topology::{Point3, Result, SolidBuilder, mass_properties, voxelize};
+
+#[test]
+fn voxelized_boxes_fill_exactly_and_overlap() -> Result<()> {
+    let solid = SolidBuilder::box_solid(100.0, 200.0, 300.0)?;
+    let grid = voxelize(&solid, 10.0)?;
+    assert_eq!(grid.dims, [10, 20, 30]);
+    assert_eq!(grid.filled_count(), 6000);
+    assert!((grid.volume() - 6.0e6).abs() < 1.0e-6);
+    assert!(grid.contains(Point3::new(50.0, 100.0, 150.0)));
+    assert!(!grid.contains(Point3::new(150.0, 100.0, 150.0)));
+    assert_eq!(
+        grid.voxel_at(Point3::new(15.0, 5.0, 299.0)),
+        Some([1, 0, 29])
+    );
+
+    let corner = voxelize(&SolidBuilder::box_solid(50.0, 50.0, 50.0)?, 10.0)?;
+    assert!((corner.overlap_volume(&grid) - 125_000.0).abs() < 1.0e-6);
+
+    assert!(voxelize(&solid, 0.0).is_err());
+    assert!(voxelize(&solid, 1.0e-3).is_err());
+    Ok(())
+}
+
+#[test]
+fn voxelized_cylinder_approximates_its_volume() -> Result<()> {
+    let cylinder = SolidBuilder::cylinder_z(Point3::new(0.0, 0.0, 0.0), 50.0, 100.0)?;
+    let grid = voxelize(&cylinder, 2.0)?;
+    let exact = mass_properties(&cylinder).volume;
+    assert!((grid.volume() - exact).abs() / exact < 0.03);
+    assert!(grid.contains(Point3::new(0.0, 0.0, 50.0)));
+    assert!(!grid.contains(Point3::new(45.0, 45.0, 50.0)));
+    Ok(())
+}
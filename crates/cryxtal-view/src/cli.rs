@@ -1,4 +1,5 @@
 use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "cryxtal-view")]
@@ -6,6 +7,11 @@ use clap::{Args, Parser, Subcommand};
 pub struct CliArgs {
     #[command(subcommand)]
     pub mode: Option<Mode>,
+    /// Project to open in the GUI.
+    pub project: Option<PathBuf>,
+    /// Open the project for review: navigation, selection and annotations only.
+    #[arg(long)]
+    pub read_only: bool,
 }
 
 #[derive(Subcommand)]
@@ -23,12 +29,24 @@ pub enum HeadlessCommand {
         command: GenerateCommand,
     },
     Triangulate(TriangulateArgs),
+    Regenerate(RegenerateArgs),
+    Bench(BenchArgs),
 }
 
 #[derive(Subcommand)]
 pub enum GenerateCommand {
     Box(BoxArgs),
     Plate(PlateArgs),
+    Column(ColumnArgs),
+    Beam(BeamArgs),
+    Footing(FootingArgs),
+    StripFooting(StripFootingArgs),
+    PileCap(PileCapArgs),
+    Roof(RoofArgs),
+    Ramp(RampArgs),
+    Pipe(PipeArgs),
+    Duct(DuctArgs),
+    Connection(ConnectionArgs),
 }
 
 #[derive(Args)]
@@ -59,6 +77,212 @@ pub struct PlateArgs {
     pub name: Option<String>,
 }
 
+/// Steel columns take `--profile`, rectangular ones `--width` and `--depth`, round ones
+/// `--diameter`. The top is
+/// either `--height` above the base or `--top-storey`; storeys are looked up by name in
+/// `--project`, which the column is then added to.
+#[derive(Args)]
+pub struct ColumnArgs {
+    #[arg(long, default_value = "0,0,0")]
+    pub base: String,
+    #[arg(long)]
+    pub height: Option<f64>,
+    #[arg(long)]
+    pub width: Option<f64>,
+    #[arg(long)]
+    pub depth: Option<f64>,
+    #[arg(long)]
+    pub diameter: Option<f64>,
+    #[arg(long)]
+    pub profile: Option<String>,
+    #[arg(long)]
+    pub project: Option<String>,
+    /// Storey the column stands on; replaces the Z of `--base`.
+    #[arg(long)]
+    pub base_storey: Option<String>,
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    pub base_offset: f64,
+    #[arg(long)]
+    pub top_storey: Option<String>,
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    pub top_offset: f64,
+    #[arg(long)]
+    pub out: Option<String>,
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+/// Steel beams take `--profile` (e.g. `IPE200`, `HEB300`), concrete ones `--width` and
+/// `--depth`. The beam is exported to `--out` and/or added to `--project`.
+#[derive(Args)]
+pub struct BeamArgs {
+    #[arg(long)]
+    pub start: String,
+    #[arg(long)]
+    pub end: String,
+    #[arg(long)]
+    pub profile: Option<String>,
+    #[arg(long)]
+    pub width: Option<f64>,
+    #[arg(long)]
+    pub depth: Option<f64>,
+    /// Section roll about the beam axis, in degrees.
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    pub roll: f64,
+    /// Extension past the start point; negative values cut the beam back.
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    pub start_offset: f64,
+    /// Extension past the end point; negative values cut the beam back.
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    pub end_offset: f64,
+    #[arg(long)]
+    pub project: Option<String>,
+    #[arg(long)]
+    pub out: Option<String>,
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct FootingArgs {
+    /// Center of the footing's top face.
+    #[arg(long, default_value = "0,0,0")]
+    pub center: String,
+    /// Width, depth and thickness.
+    #[arg(long)]
+    pub size: String,
+    #[command(flatten)]
+    pub rebar: FoundationRebarArgs,
+    #[arg(long)]
+    pub out: String,
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+/// Strip footing whose top face runs along `--start` to `--end`.
+#[derive(Args)]
+pub struct StripFootingArgs {
+    #[arg(long)]
+    pub start: String,
+    #[arg(long)]
+    pub end: String,
+    #[arg(long)]
+    pub width: f64,
+    #[arg(long)]
+    pub thickness: f64,
+    #[command(flatten)]
+    pub rebar: FoundationRebarArgs,
+    #[arg(long)]
+    pub out: String,
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+/// Pile cap added to `--project`, with a grid of piles under it when `--pile-diameter` is
+/// given; lengths in millimetres.
+#[derive(Args)]
+pub struct PileCapArgs {
+    #[arg(long)]
+    pub project: String,
+    /// Center of the cap's top face.
+    #[arg(long, default_value = "0,0,0")]
+    pub center: String,
+    /// Width, depth and thickness.
+    #[arg(long)]
+    pub size: String,
+    #[arg(long)]
+    pub pile_diameter: Option<f64>,
+    #[arg(long, default_value_t = 2)]
+    pub pile_rows: usize,
+    #[arg(long, default_value_t = 2)]
+    pub pile_columns: usize,
+    /// Center-to-center pile spacing.
+    #[arg(long, default_value_t = 900.0)]
+    pub pile_spacing: f64,
+    #[arg(long, default_value_t = 10_000.0)]
+    pub pile_length: f64,
+    #[command(flatten)]
+    pub rebar: FoundationRebarArgs,
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+/// Reinforcement recorded on a foundation when `--rebar-diameter` is given.
+#[derive(Args)]
+pub struct FoundationRebarArgs {
+    #[arg(long)]
+    pub rebar_diameter: Option<f64>,
+    #[arg(long, default_value_t = 200.0)]
+    pub rebar_spacing: f64,
+    #[arg(long, default_value_t = 50.0)]
+    pub cover: f64,
+    /// Add a top mat to the bottom one.
+    #[arg(long)]
+    pub top_mat: bool,
+}
+
+/// Ramp whose top face spans from the `--start` edge to the `--end` edge, each given as two
+/// `x,y,z` points separated by `;`.
+#[derive(Args)]
+pub struct RampArgs {
+    #[arg(long)]
+    pub start: String,
+    #[arg(long)]
+    pub end: String,
+    #[arg(long)]
+    pub thickness: f64,
+    #[arg(long)]
+    pub out: String,
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct RoofArgs {
+    /// Slab outline as `x,y,z` points separated by `;`; the first edge is the eave.
+    #[arg(long)]
+    pub outline: String,
+    #[arg(long)]
+    pub thickness: f64,
+    /// Pitch in degrees.
+    #[arg(long, default_value_t = 0.0)]
+    pub slope: f64,
+    #[arg(long)]
+    pub out: String,
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct PipeArgs {
+    #[arg(long)]
+    pub start: String,
+    #[arg(long)]
+    pub end: String,
+    #[arg(long)]
+    pub diameter: f64,
+    #[arg(long)]
+    pub out: String,
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DuctArgs {
+    #[arg(long)]
+    pub start: String,
+    #[arg(long)]
+    pub end: String,
+    #[arg(long)]
+    pub width: f64,
+    #[arg(long)]
+    pub height: f64,
+    #[arg(long)]
+    pub out: String,
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
 #[derive(Args)]
 pub struct TriangulateArgs {
     #[arg(long = "in")]
@@ -66,3 +290,65 @@ pub struct TriangulateArgs {
     #[arg(long)]
     pub out: String,
 }
+
+/// Bolts an end plate on the `--beam` end nearest to the steel `--column` (both looked up by
+/// name in `--project`) and drills the plate and column; lengths in millimetres.
+#[derive(Args)]
+pub struct ConnectionArgs {
+    #[arg(long)]
+    pub project: String,
+    #[arg(long)]
+    pub beam: String,
+    #[arg(long)]
+    pub column: String,
+    #[arg(long, default_value_t = 20.0)]
+    pub bolt_diameter: f64,
+    /// Horizontal bolt spacing.
+    #[arg(long, default_value_t = 100.0)]
+    pub gauge: f64,
+    /// Vertical bolt spacing.
+    #[arg(long, default_value_t = 80.0)]
+    pub pitch: f64,
+    #[arg(long, default_value_t = 2)]
+    pub rows: usize,
+    #[arg(long, default_value_t = 2)]
+    pub bolt_columns: usize,
+    #[arg(long, default_value_t = 15.0)]
+    pub plate_thickness: f64,
+    /// Distance from the outer bolts to the plate edges.
+    #[arg(long, default_value_t = 40.0)]
+    pub edge: f64,
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+/// Regenerates the storey-bound columns and walls of a project, after moving storeys with
+/// `--storey`.
+#[derive(Args)]
+pub struct RegenerateArgs {
+    #[arg(long)]
+    pub project: String,
+    /// New storey elevation as `NAME=ELEVATION`; repeatable.
+    #[arg(long, allow_hyphen_values = true)]
+    pub storey: Vec<String>,
+}
+
+/// Renders the project `--in` offscreen for `--frames` frames along a scripted camera path
+/// orbiting the model, then reports the adapter and frame time statistics. Needs the `gui`
+/// feature.
+#[derive(Args)]
+pub struct BenchArgs {
+    #[arg(long = "in")]
+    pub input: String,
+    #[arg(long, default_value_t = 500)]
+    pub frames: usize,
+    /// Render target as `WIDTHxHEIGHT` pixels.
+    #[arg(long, default_value = "1920x1080")]
+    pub resolution: String,
+    /// Render on the software (CPU) adapter.
+    #[arg(long)]
+    pub software: bool,
+    /// Also write the report as JSON, to attach to bug reports.
+    #[arg(long)]
+    pub json: Option<String>,
+}
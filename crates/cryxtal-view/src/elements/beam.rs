@@ -0,0 +1,148 @@
+use anyhow::{Context, Result};
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, ParameterSet, ParameterValue};
+use cryxtal_topology::Point3;
+
+use super::profile::{SectionProfile, element_name, extrude_section, write_axis};
+
+/// Placement adjustments of a beam about its set-out line.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BeamOptions {
+    /// Turn of the section about the axis, in degrees.
+    pub roll_deg: f64,
+    /// Extension past the start point; negative values cut the beam back.
+    pub start_offset: f64,
+    /// Extension past the end point; negative values cut the beam back.
+    pub end_offset: f64,
+}
+
+/// Beam swept from `start` to `end`, centered on the line between them. The set-out points
+/// are kept as `Start*`/`End*`; the offsets only move the ends of the solid.
+pub fn build_beam_between_points(
+    start: Point3,
+    end: Point3,
+    profile: SectionProfile,
+    options: BeamOptions,
+    name: Option<&str>,
+) -> Result<BimElement> {
+    let axis = end - start;
+    let span = (axis.x * axis.x + axis.y * axis.y + axis.z * axis.z).sqrt();
+    if span <= 1.0e-6 {
+        anyhow::bail!("beam length is too small");
+    }
+    let dir = axis / span;
+    let length = span + options.start_offset + options.end_offset;
+    if length <= 1.0e-6 {
+        anyhow::bail!("beam end offsets leave no length");
+    }
+    let solid_start = start - dir * options.start_offset;
+    let solid_end = end + dir * options.end_offset;
+    let solid = extrude_section(solid_start, solid_end, profile, options.roll_deg)
+        .context("failed to build beam solid")?;
+
+    let mut parameters = ParameterSet::new();
+    profile.write_parameters(&mut parameters);
+    parameters.insert("Length".to_string(), ParameterValue::Number(length));
+    parameters.insert("Roll".to_string(), ParameterValue::Number(options.roll_deg));
+    parameters.insert(
+        "StartOffset".to_string(),
+        ParameterValue::Number(options.start_offset),
+    );
+    parameters.insert(
+        "EndOffset".to_string(),
+        ParameterValue::Number(options.end_offset),
+    );
+    write_axis(&mut parameters, start, end);
+
+    Ok(BimElement::new(
+        Guid::new(),
+        element_name(name, "Beam"),
+        BimCategory::Beam,
+        parameters,
+        solid,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::steel_section;
+    use cryxtal_topology::Solid;
+
+    fn ipe120() -> Result<SectionProfile> {
+        Ok(SectionProfile::Steel(
+            steel_section("ipe 120").context("IPE120 is in the library")?,
+        ))
+    }
+
+    /// Corners of the box around the vertices of `solid`.
+    fn bounds(solid: &Solid) -> (Point3, Point3) {
+        let mut points = solid.vertex_iter().map(|vertex| vertex.point());
+        let first = points.next().unwrap_or(Point3::new(0.0, 0.0, 0.0));
+        points.fold((first, first), |(min, max), point| {
+            (
+                Point3::new(min.x.min(point.x), min.y.min(point.y), min.z.min(point.z)),
+                Point3::new(max.x.max(point.x), max.y.max(point.y), max.z.max(point.z)),
+            )
+        })
+    }
+
+    #[test]
+    fn offsets_move_the_beam_ends() -> Result<()> {
+        let options = BeamOptions {
+            start_offset: -100.0,
+            end_offset: 50.0,
+            ..BeamOptions::default()
+        };
+        let start = Point3::new(0.0, 0.0, 0.0);
+        let end = Point3::new(4000.0, 0.0, 0.0);
+        let beam = build_beam_between_points(start, end, ipe120()?, options, None)?;
+        assert_eq!(
+            beam.parameters.get("Length"),
+            Some(&ParameterValue::Number(3950.0))
+        );
+        assert_eq!(
+            beam.parameters.get("EndX"),
+            Some(&ParameterValue::Number(4000.0))
+        );
+        assert_eq!(
+            beam.parameters.get("Profile"),
+            Some(&ParameterValue::Text("IPE120".into()))
+        );
+        let (min, max) = bounds(&beam.geometry);
+        assert!((min.x - 100.0).abs() < 1.0e-6 && (max.x - 4050.0).abs() < 1.0e-6);
+        // Flanges 64 wide across the axis, the section 120 high.
+        assert!((max.y - min.y - 64.0).abs() < 1.0e-6);
+        assert!((max.z - min.z - 120.0).abs() < 1.0e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn roll_turns_the_section() -> Result<()> {
+        let options = BeamOptions {
+            roll_deg: 90.0,
+            ..BeamOptions::default()
+        };
+        let start = Point3::new(0.0, 0.0, 0.0);
+        let end = Point3::new(4000.0, 0.0, 0.0);
+        let beam = build_beam_between_points(start, end, ipe120()?, options, None)?;
+        let (min, max) = bounds(&beam.geometry);
+        assert!((max.y - min.y - 120.0).abs() < 1.0e-6);
+        assert!((max.z - min.z - 64.0).abs() < 1.0e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn offsets_may_not_swallow_the_beam() -> Result<()> {
+        let options = BeamOptions {
+            start_offset: -600.0,
+            end_offset: -500.0,
+            ..BeamOptions::default()
+        };
+        let start = Point3::new(0.0, 0.0, 0.0);
+        let end = Point3::new(1000.0, 0.0, 0.0);
+        assert!(build_beam_between_points(start, end, ipe120()?, options, None).is_err());
+        assert!(steel_section("IPE999").is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use anyhow::{Context, Result};
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, BimModel, ParameterSet, ParameterValue};
+use cryxtal_topology::{Point3, Vector3};
+
+use super::profile::{SectionProfile, element_name, extrude_section, write_axis};
+
+/// Vertical column standing on `base`.
+pub fn build_column_element(
+    base: Point3,
+    profile: SectionProfile,
+    height: f64,
+    name: Option<&str>,
+) -> Result<BimElement> {
+    if height <= 0.0 {
+        anyhow::bail!("column height must be > 0");
+    }
+    let top = base + Vector3::new(0.0, 0.0, height);
+    let solid = extrude_section(base, top, profile, 0.0).context("failed to build column solid")?;
+
+    let mut parameters = ParameterSet::new();
+    profile.write_parameters(&mut parameters);
+    parameters.insert("Height".to_string(), ParameterValue::Number(height));
+    write_axis(&mut parameters, base, top);
+
+    Ok(BimElement::new(
+        Guid::new(),
+        element_name(name, "Column"),
+        BimCategory::Column,
+        parameters,
+        solid,
+    ))
+}
+
+/// One end of a column: a storey elevation plus an offset, or a fixed elevation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnLevel {
+    Storey { storey: Guid, offset: f64 },
+    Elevation(f64),
+}
+
+impl ColumnLevel {
+    pub fn elevation(self, model: &BimModel) -> Result<f64> {
+        match self {
+            ColumnLevel::Storey { storey, offset } => model
+                .storey(storey)
+                .map(|storey| storey.elevation + offset)
+                .with_context(|| format!("unknown storey {storey}")),
+            ColumnLevel::Elevation(elevation) => Ok(elevation),
+        }
+    }
+
+    /// Storey ends are kept as `{prefix}Storey`/`{prefix}Offset`; fixed ends only live in the
+    /// axis parameters.
+    fn write_parameters(self, parameters: &mut ParameterSet, prefix: &str) {
+        if let ColumnLevel::Storey { storey, offset } = self {
+            parameters.insert(
+                format!("{prefix}Storey"),
+                ParameterValue::Text(storey.to_string()),
+            );
+            parameters.insert(format!("{prefix}Offset"), ParameterValue::Number(offset));
+        }
+    }
+
+    fn read_parameters(parameters: &ParameterSet, prefix: &str, fixed: f64) -> Result<Self> {
+        let Some(storey) = parameters.get(&format!("{prefix}Storey")) else {
+            return Ok(ColumnLevel::Elevation(fixed));
+        };
+        let ParameterValue::Text(storey) = storey else {
+            anyhow::bail!("invalid column parameter: {prefix}Storey");
+        };
+        let offset = match parameters.get(&format!("{prefix}Offset")) {
+            Some(ParameterValue::Number(offset)) => *offset,
+            None => 0.0,
+            Some(_) => anyhow::bail!("invalid column parameter: {prefix}Offset"),
+        };
+        Ok(ColumnLevel::Storey {
+            storey: storey.parse()?,
+            offset,
+        })
+    }
+}
+
+/// Column at the plan position of `position`, running from the `base` level to the `top`
+/// level. Storey levels are recorded so [`regenerate_column`] can follow the storeys.
+pub fn build_level_column(
+    model: &BimModel,
+    position: Point3,
+    profile: SectionProfile,
+    base: ColumnLevel,
+    top: ColumnLevel,
+    name: Option<&str>,
+) -> Result<BimElement> {
+    let base_z = base.elevation(model)?;
+    let top_z = top.elevation(model)?;
+    if top_z <= base_z {
+        anyhow::bail!("column top level must be above its base level");
+    }
+    let mut element = build_column_element(
+        Point3::new(position.x, position.y, base_z),
+        profile,
+        top_z - base_z,
+        name,
+    )?;
+    base.write_parameters(&mut element.parameters, "Base");
+    top.write_parameters(&mut element.parameters, "Top");
+    Ok(element)
+}
+
+/// Rebuilds a column from its parameters against the current storey elevations. The guid,
+/// name and unrelated parameters (layer, type) are kept.
+pub fn regenerate_column(model: &BimModel, column: &BimElement) -> Result<BimElement> {
+    if column.category != BimCategory::Column {
+        anyhow::bail!("{} is not a column", column.name);
+    }
+    let parameters = &column.parameters;
+    let number = |key: &str| match parameters.get(key) {
+        Some(ParameterValue::Number(value)) => Ok(*value),
+        _ => anyhow::bail!("missing or invalid column parameter: {key}"),
+    };
+    let profile = SectionProfile::read_parameters(parameters)?;
+    let position = Point3::new(number("StartX")?, number("StartY")?, 0.0);
+    let base = ColumnLevel::read_parameters(parameters, "Base", number("StartZ")?)?;
+    let top = ColumnLevel::read_parameters(parameters, "Top", number("EndZ")?)?;
+    let rebuilt = build_level_column(model, position, profile, base, top, None)?;
+
+    let mut regenerated = column.clone();
+    regenerated.geometry = rebuilt.geometry;
+    regenerated.parameters.extend(rebuilt.parameters);
+    Ok(regenerated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARE: SectionProfile = SectionProfile::Rectangular {
+        width: 300.0,
+        depth: 300.0,
+    };
+
+    /// Model with the default storey and one 3000 above it.
+    fn model() -> Result<(BimModel, Guid, Guid)> {
+        let mut model = BimModel::new("Test");
+        let ground = model.default_storey().context("default storey")?;
+        let building = model.site.buildings[0].guid;
+        let elevation = storey_elevation(&model, ground)? + 3000.0;
+        let first = model.add_storey(building, "First", elevation)?;
+        Ok((model, ground, first))
+    }
+
+    fn storey_elevation(model: &BimModel, storey: Guid) -> Result<f64> {
+        Ok(model.storey(storey).context("storey")?.elevation)
+    }
+
+    fn set_elevation(model: &mut BimModel, storey: Guid, elevation: f64) -> Result<()> {
+        let storey = model
+            .site
+            .buildings
+            .iter_mut()
+            .flat_map(|building| building.storeys.iter_mut())
+            .find(|candidate| candidate.guid == storey)
+            .context("storey")?;
+        storey.elevation = elevation;
+        Ok(())
+    }
+
+    /// Lowest and highest vertex of a column.
+    fn z_range(column: &BimElement) -> (f64, f64) {
+        column
+            .geometry
+            .vertex_iter()
+            .map(|vertex| vertex.point().z)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), z| {
+                (min.min(z), max.max(z))
+            })
+    }
+
+    #[test]
+    fn columns_follow_their_storeys() -> Result<()> {
+        let (mut model, ground, first) = model()?;
+        let base = storey_elevation(&model, ground)?;
+        let column = build_level_column(
+            &model,
+            Point3::new(1000.0, 2000.0, 0.0),
+            SQUARE,
+            ColumnLevel::Storey {
+                storey: ground,
+                offset: 0.0,
+            },
+            ColumnLevel::Storey {
+                storey: first,
+                offset: -200.0,
+            },
+            Some("C1"),
+        )?;
+        let (bottom, top) = z_range(&column);
+        assert!((bottom - base).abs() < 1.0e-6 && (top - (base + 2800.0)).abs() < 1.0e-6);
+
+        set_elevation(&mut model, first, base + 3500.0)?;
+        let regenerated = regenerate_column(&model, &column)?;
+        assert_eq!(regenerated.guid, column.guid);
+        assert_eq!(regenerated.name, "C1");
+        let (bottom, top) = z_range(&regenerated);
+        assert!((bottom - base).abs() < 1.0e-6 && (top - (base + 3300.0)).abs() < 1.0e-6);
+        assert_eq!(
+            regenerated.parameters.get("Height"),
+            Some(&ParameterValue::Number(3300.0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fixed_ends_stay_put() -> Result<()> {
+        let (mut model, ground, first) = model()?;
+        let base = storey_elevation(&model, ground)?;
+        let column = build_level_column(
+            &model,
+            Point3::new(0.0, 0.0, 0.0),
+            SQUARE,
+            ColumnLevel::Elevation(base + 500.0),
+            ColumnLevel::Storey {
+                storey: first,
+                offset: 0.0,
+            },
+            None,
+        )?;
+        set_elevation(&mut model, first, base + 4000.0)?;
+        let (bottom, top) = z_range(&regenerate_column(&model, &column)?);
+        assert!((bottom - (base + 500.0)).abs() < 1.0e-6);
+        assert!((top - (base + 4000.0)).abs() < 1.0e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn top_level_must_be_above_the_base() -> Result<()> {
+        let (model, ground, _) = model()?;
+        let result = build_level_column(
+            &model,
+            Point3::new(0.0, 0.0, 0.0),
+            SQUARE,
+            ColumnLevel::Storey {
+                storey: ground,
+                offset: 0.0,
+            },
+            ColumnLevel::Elevation(-1.0e6),
+            None,
+        );
+        assert!(result.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,413 @@
+use anyhow::{Context, Result};
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, ParameterSet, ParameterValue};
+use cryxtal_shapeops::{BooleanBatch, BooleanOp, DEFAULT_SHAPEOPS_TOLERANCE};
+use cryxtal_topology::{Point3, Solid, Vector3, Wire};
+use truck_modeling::{InnerSpace, builder};
+
+use super::profile::{SectionProfile, element_name, extrude_section, read_axis};
+
+/// Smallest cosine of the angle between the beam and the column face normal.
+const SQUARE_TOLERANCE: f64 = 0.9998;
+/// How far hole cutters reach past the faces they cut, so no skin is left behind.
+const CUTTER_OVERRUN: f64 = 1.0;
+
+/// End plate on a beam end bolted to a steel column face: `rows` × `columns` bolts of
+/// `bolt_diameter`, `pitch` apart vertically and `gauge` apart horizontally, with `edge`
+/// between the outer bolts and the plate edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoltedConnection {
+    pub bolt_diameter: f64,
+    pub gauge: f64,
+    pub pitch: f64,
+    pub rows: usize,
+    pub columns: usize,
+    pub plate_thickness: f64,
+    pub edge: f64,
+}
+
+impl BoltedConnection {
+    fn validate(&self) -> Result<()> {
+        if self.rows == 0 || self.columns == 0 {
+            anyhow::bail!("connection needs at least one bolt row and column");
+        }
+        if self.bolt_diameter <= 0.0
+            || self.gauge <= 0.0
+            || self.pitch <= 0.0
+            || self.plate_thickness <= 0.0
+        {
+            anyhow::bail!("bolt diameter, gauge, pitch and plate thickness must be > 0");
+        }
+        if self.edge < self.hole_diameter() * 0.5 {
+            anyhow::bail!("edge distance must clear the bolt holes");
+        }
+        if (self.columns > 1 && self.gauge < self.hole_diameter())
+            || (self.rows > 1 && self.pitch < self.hole_diameter())
+        {
+            anyhow::bail!("gauge and pitch must clear the bolt holes");
+        }
+        Ok(())
+    }
+
+    /// Bolt diameter plus the normal clearance of EN 1090-2: 2 mm up to M24, 3 mm above.
+    pub fn hole_diameter(&self) -> f64 {
+        let clearance = if self.bolt_diameter <= 24.0 { 2.0 } else { 3.0 };
+        self.bolt_diameter + clearance
+    }
+
+    fn bolt_spread(&self) -> (f64, f64) {
+        (
+            (self.columns - 1) as f64 * self.gauge,
+            (self.rows - 1) as f64 * self.pitch,
+        )
+    }
+}
+
+/// Elements of a placed connection: the plate and bolts to add, and the column with its bolt
+/// holes drilled.
+pub struct ConnectionParts {
+    pub plate: BimElement,
+    pub bolts: BimElement,
+    pub column: BimElement,
+}
+
+/// Places `connection` on the end of `beam` nearest to `column`, against the column face the
+/// beam runs into: a flange when the beam runs along the section depth, otherwise the web.
+/// The plate is welded to the beam, so only the plate and the column are drilled. Bolt heads
+/// and nuts are modeled as round. Regenerating the column from its parameters drops the holes.
+pub fn build_bolted_connection(
+    beam: &BimElement,
+    column: &BimElement,
+    connection: BoltedConnection,
+    name: Option<&str>,
+) -> Result<ConnectionParts> {
+    if beam.category != BimCategory::Beam {
+        anyhow::bail!("{} is not a beam", beam.name);
+    }
+    if column.category != BimCategory::Column {
+        anyhow::bail!("{} is not a column", column.name);
+    }
+    connection.validate()?;
+    let SectionProfile::Steel(section) = SectionProfile::read_parameters(&column.parameters)?
+    else {
+        anyhow::bail!("bolted connections need a steel column");
+    };
+    let (base, top) = read_axis(&column.parameters)?;
+    if (top.x - base.x).abs() > 1.0e-6 || (top.y - base.y).abs() > 1.0e-6 {
+        anyhow::bail!("bolted connections need a vertical column");
+    }
+
+    let (start, end) = read_axis(&beam.parameters)?;
+    let plan_distance = |point: Point3| (point.x - base.x).hypot(point.y - base.y);
+    let (from, to) = if plan_distance(end) <= plan_distance(start) {
+        (start, end)
+    } else {
+        (end, start)
+    };
+    let run = Vector3::new(to.x - from.x, to.y - from.y, 0.0);
+    let run_length = run.x.hypot(run.y);
+    if run_length <= 1.0e-6 {
+        anyhow::bail!("beam must not be vertical");
+    }
+    let run = run / run_length;
+
+    // Face normal towards the beam, the face's distance from the column axis, the thickness
+    // bolts pass through and the width the bolts must fit in.
+    let (normal, offset, wall, available) = if run.y.abs() >= run.x.abs() {
+        (
+            Vector3::new(0.0, -run.y.signum(), 0.0),
+            section.height * 0.5,
+            section.flange,
+            section.width,
+        )
+    } else {
+        (
+            Vector3::new(-run.x.signum(), 0.0, 0.0),
+            section.web * 0.5,
+            section.web,
+            section.height - section.flange * 2.0,
+        )
+    };
+    if run.x.abs().max(run.y.abs()) < SQUARE_TOLERANCE {
+        anyhow::bail!("beam must meet the column face square");
+    }
+    let (spread_across, spread_up) = connection.bolt_spread();
+    if spread_across + connection.hole_diameter() > available {
+        anyhow::bail!(
+            "bolts spread {:.0} wide do not fit the {:.0} column face",
+            spread_across + connection.hole_diameter(),
+            available
+        );
+    }
+
+    let up = Vector3::unit_z();
+    let across = up.cross(normal);
+    let face = Point3::new(base.x, base.y, to.z) + normal * offset;
+    let center = face + across * (to - face).dot(across);
+    let width = spread_across + connection.edge * 2.0;
+    let height = spread_up + connection.edge * 2.0;
+    let mut plate = plate_solid(center, across, normal, width, height, connection)?;
+
+    let bolt_points: Vec<Point3> = (0..connection.rows)
+        .flat_map(|row| (0..connection.columns).map(move |col| (row, col)))
+        .map(|(row, col)| {
+            center
+                + across * (col as f64 * connection.gauge - spread_across * 0.5)
+                + up * (row as f64 * connection.pitch - spread_up * 0.5)
+        })
+        .collect();
+
+    let mut drilled = column.geometry.clone();
+    for point in &bolt_points {
+        let cutter = extrude_section(
+            *point + normal * (connection.plate_thickness + CUTTER_OVERRUN),
+            *point - normal * (wall + CUTTER_OVERRUN),
+            SectionProfile::Circular {
+                diameter: connection.hole_diameter(),
+            },
+            0.0,
+        )
+        .context("failed to build bolt hole")?;
+        let mut batch = BooleanBatch::new(BooleanOp::Difference, DEFAULT_SHAPEOPS_TOLERANCE);
+        batch.push(plate, cutter.clone()).push(drilled, cutter);
+        let mut results = batch.run().into_iter();
+        plate = results
+            .next()
+            .context("missing plate result")?
+            .context("failed to drill the end plate")?;
+        drilled = results
+            .next()
+            .context("missing column result")?
+            .context("failed to drill the column")?;
+    }
+
+    let diameter = connection.bolt_diameter;
+    let (head, nut) = (diameter * 0.6, diameter * 0.8);
+    let mut shells = Vec::new();
+    for point in &bolt_points {
+        let plate_face = *point + normal * connection.plate_thickness;
+        let column_back = *point - normal * wall;
+        let parts = [
+            (plate_face + normal * head, plate_face, diameter * 1.6),
+            (plate_face, column_back, diameter),
+            (column_back, column_back - normal * nut, diameter * 1.6),
+        ];
+        for (start, end, diameter) in parts {
+            let solid = extrude_section(start, end, SectionProfile::Circular { diameter }, 0.0)
+                .context("failed to build bolt")?;
+            shells.extend(solid.into_boundaries());
+        }
+    }
+    let bolts = Solid::try_new(shells).context("failed to build bolts solid")?;
+
+    let mut plate_parameters = ParameterSet::new();
+    write_connection_parameters(&mut plate_parameters, &connection);
+    plate_parameters.insert("PlateWidth".to_string(), ParameterValue::Number(width));
+    plate_parameters.insert("PlateHeight".to_string(), ParameterValue::Number(height));
+    plate_parameters.insert(
+        "Beam".to_string(),
+        ParameterValue::Text(beam.guid.to_string()),
+    );
+    plate_parameters.insert(
+        "Column".to_string(),
+        ParameterValue::Text(column.guid.to_string()),
+    );
+    let plate = BimElement::new(
+        Guid::new(),
+        element_name(name, "End Plate"),
+        BimCategory::Generic,
+        plate_parameters,
+        plate,
+    );
+
+    let mut bolt_parameters = ParameterSet::new();
+    write_connection_parameters(&mut bolt_parameters, &connection);
+    bolt_parameters.insert(
+        "BoltCount".to_string(),
+        ParameterValue::Integer(bolt_points.len() as i64),
+    );
+    bolt_parameters.insert(
+        "BoltLength".to_string(),
+        ParameterValue::Number(connection.plate_thickness + wall + nut),
+    );
+    bolt_parameters.insert(
+        "Connection".to_string(),
+        ParameterValue::Text(plate.guid.to_string()),
+    );
+    let bolts = BimElement::new(
+        Guid::new(),
+        format!("{} Bolts", plate.name),
+        BimCategory::Generic,
+        bolt_parameters,
+        bolts,
+    );
+
+    let mut column = column.clone();
+    column.geometry = drilled;
+    let holes = match column.parameters.get("BoltHoles") {
+        Some(ParameterValue::Integer(holes)) => *holes,
+        _ => 0,
+    };
+    column.parameters.insert(
+        "BoltHoles".to_string(),
+        ParameterValue::Integer(holes + bolt_points.len() as i64),
+    );
+    Ok(ConnectionParts {
+        plate,
+        bolts,
+        column,
+    })
+}
+
+/// Plate of `width` × `height` centered on `center` in the face plane, thickened along
+/// `normal`.
+fn plate_solid(
+    center: Point3,
+    across: Vector3,
+    normal: Vector3,
+    width: f64,
+    height: f64,
+    connection: BoltedConnection,
+) -> Result<Solid> {
+    let up = Vector3::unit_z();
+    // Counter-clockwise about `normal`, so the sweep runs outward.
+    let vertices: Vec<_> = [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)]
+        .iter()
+        .map(|(a, b)| builder::vertex(center + across * (a * width) + up * (b * height)))
+        .collect();
+    let wire: Wire = (0..vertices.len())
+        .map(|idx| builder::line(&vertices[idx], &vertices[(idx + 1) % vertices.len()]))
+        .collect::<Vec<_>>()
+        .into();
+    let face = builder::try_attach_plane(&[wire]).context("failed to build plate face")?;
+    Ok(builder::tsweep(&face, normal * connection.plate_thickness))
+}
+
+fn write_connection_parameters(parameters: &mut ParameterSet, connection: &BoltedConnection) {
+    let numbers = [
+        ("BoltDiameter", connection.bolt_diameter),
+        ("HoleDiameter", connection.hole_diameter()),
+        ("Gauge", connection.gauge),
+        ("Pitch", connection.pitch),
+        ("PlateThickness", connection.plate_thickness),
+        ("EdgeDistance", connection.edge),
+    ];
+    for (key, value) in numbers {
+        parameters.insert(key.to_string(), ParameterValue::Number(value));
+    }
+    parameters.insert(
+        "BoltRows".to_string(),
+        ParameterValue::Integer(connection.rows as i64),
+    );
+    parameters.insert(
+        "BoltColumns".to_string(),
+        ParameterValue::Integer(connection.columns as i64),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::{
+        BeamOptions, build_beam_between_points, build_column_element, steel_section,
+    };
+
+    const CONNECTION: BoltedConnection = BoltedConnection {
+        bolt_diameter: 20.0,
+        gauge: 100.0,
+        pitch: 80.0,
+        rows: 3,
+        columns: 2,
+        plate_thickness: 15.0,
+        edge: 40.0,
+    };
+
+    fn steel(name: &str) -> Result<SectionProfile> {
+        Ok(SectionProfile::Steel(
+            steel_section(name).context("section is in the library")?,
+        ))
+    }
+
+    /// HEB300 column on the origin, 4000 high.
+    fn column() -> Result<BimElement> {
+        build_column_element(Point3::new(0.0, 0.0, 0.0), steel("HEB300")?, 4000.0, None)
+    }
+
+    /// IPE120 beam running to `end` from 2000 further along `from`.
+    fn beam(end: Point3, from: Vector3) -> Result<BimElement> {
+        build_beam_between_points(
+            end + from * 2000.0,
+            end,
+            steel("IPE120")?,
+            BeamOptions::default(),
+            None,
+        )
+    }
+
+    /// Corners of the box around the vertices of `solid`.
+    fn bounds(solid: &Solid) -> (Point3, Point3) {
+        let mut points = solid.vertex_iter().map(|vertex| vertex.point());
+        let first = points.next().unwrap_or(Point3::new(0.0, 0.0, 0.0));
+        points.fold((first, first), |(min, max), point| {
+            (
+                Point3::new(min.x.min(point.x), min.y.min(point.y), min.z.min(point.z)),
+                Point3::new(max.x.max(point.x), max.y.max(point.y), max.z.max(point.z)),
+            )
+        })
+    }
+
+    #[test]
+    fn end_plates_are_bolted_through_the_flange() -> Result<()> {
+        let column = column()?;
+        // Into the flange face at y = 150.
+        let beam = beam(Point3::new(0.0, 150.0, 3000.0), Vector3::unit_y())?;
+        let parts = build_bolted_connection(&beam, &column, CONNECTION, None)?;
+
+        let (min, max) = bounds(&parts.plate.geometry);
+        let expected = [(-90.0, 90.0), (150.0, 165.0), (2880.0, 3120.0)];
+        let actual = [(min.x, max.x), (min.y, max.y), (min.z, max.z)];
+        for ((low, high), (want_low, want_high)) in actual.into_iter().zip(expected) {
+            assert!((low - want_low).abs() < 1.0e-6 && (high - want_high).abs() < 1.0e-6);
+        }
+        assert_eq!(
+            parts.bolts.parameters.get("BoltCount"),
+            Some(&ParameterValue::Integer(6))
+        );
+        assert_eq!(
+            parts.column.parameters.get("BoltHoles"),
+            Some(&ParameterValue::Integer(6))
+        );
+        assert_eq!(parts.column.guid, column.guid);
+
+        // Each of the six holes lines the flange and the plate with at least one face.
+        let faces = |solid: &Solid| solid.face_iter().count();
+        assert!(faces(&parts.column.geometry) >= faces(&column.geometry) + 6);
+        assert!(faces(&parts.plate.geometry) >= 6 + 6);
+        Ok(())
+    }
+
+    #[test]
+    fn connections_must_fit_the_column() -> Result<()> {
+        let column = column()?;
+        let square = beam(Point3::new(0.0, 150.0, 3000.0), Vector3::unit_y())?;
+        let wide = BoltedConnection {
+            columns: 4,
+            ..CONNECTION
+        };
+        assert!(build_bolted_connection(&square, &column, wide, None).is_err());
+        let skew = beam(Point3::new(0.0, 150.0, 3000.0), Vector3::new(0.6, 0.8, 0.0))?;
+        assert!(build_bolted_connection(&skew, &column, CONNECTION, None).is_err());
+        let concrete = build_column_element(
+            Point3::new(0.0, 0.0, 0.0),
+            SectionProfile::Rectangular {
+                width: 300.0,
+                depth: 300.0,
+            },
+            4000.0,
+            None,
+        )?;
+        assert!(build_bolted_connection(&square, &concrete, CONNECTION, None).is_err());
+        assert!(build_bolted_connection(&column, &column, CONNECTION, None).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result};
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, BimModel};
+
+use super::rebar_set::{is_rebar_set, regenerate_rebar_set};
+use super::rebuild_host;
+
+/// Elements whose constraints read one of `changed`, re-solved by
+/// [`BimModel::solve_constraints_touching`] with their geometry regenerated, for a
+/// [`cryxtal_bim::ModelCommand::ReplaceElements`]: constrained walls are rebuilt with their
+/// openings, walls are rebuilt around re-centered openings, and rebar sets follow their rebuilt
+/// or re-solved hosts. Empty when every such constraint already holds.
+///
+/// Elements whose constraints can no longer hold and rebar sets that no longer fit are left as
+/// they are and returned as messages with the reason.
+pub fn regenerate_constrained(
+    model: &BimModel,
+    changed: &BTreeSet<Guid>,
+) -> Result<(Vec<BimElement>, Vec<String>)> {
+    let (solved, unsolved) = model.solve_constraints_touching(changed);
+    let mut failures: Vec<String> = unsolved
+        .into_iter()
+        .map(|err| format!("Constraint not solved: {err}"))
+        .collect();
+    if solved.is_empty() {
+        return Ok((Vec::new(), failures));
+    }
+    let mut elements = model.elements().to_vec();
+    let mut changed = BTreeSet::new();
+    let mut hosts = BTreeSet::new();
+    for element in solved {
+        let index = model
+            .element_index(element.guid)
+            .context("solved element is not in the model")?;
+        let host = match element.opening {
+            Some(opening) => model.element_index(opening.host),
+            None if matches!(element.category, BimCategory::Wall | BimCategory::Slab) => {
+                Some(index)
+            }
+            None => None,
+        };
+        hosts.extend(host);
+        changed.insert(element.guid);
+        elements[index] = element;
+    }
+    for &index in &hosts {
+        rebuild_host(&mut elements, index)
+            .with_context(|| format!("failed to regenerate {}", elements[index].name))?;
+        let host = elements[index].guid;
+        changed.insert(host);
+        changed.extend(
+            model
+                .hosted_openings(host)
+                .into_iter()
+                .map(|(_, opening)| opening.guid),
+        );
+    }
+    let rebuilt: BTreeSet<Guid> = hosts.iter().map(|&index| elements[index].guid).collect();
+    let sets: Vec<(usize, Guid)> = elements
+        .iter()
+        .enumerate()
+        .filter(|(_, element)| is_rebar_set(element))
+        .filter_map(|(index, element)| {
+            let host = element.host?;
+            (rebuilt.contains(&host) || changed.contains(&element.guid)).then_some((index, host))
+        })
+        .collect();
+    for (index, host) in sets {
+        let Some(host) = elements
+            .iter()
+            .find(|candidate| candidate.guid == host)
+            .cloned()
+        else {
+            continue;
+        };
+        let mut set = elements[index].clone();
+        match regenerate_rebar_set(&mut set, &host) {
+            Ok(()) => {
+                changed.insert(set.guid);
+                elements[index] = set;
+            }
+            Err(err) => failures.push(format!("Rebar set {} not regenerated: {err}", set.name)),
+        }
+    }
+    let elements = elements
+        .into_iter()
+        .filter(|element| changed.contains(&element.guid))
+        .collect();
+    Ok((elements, failures))
+}
@@ -0,0 +1,37 @@
+use anyhow::{Context, Result};
+use cryxtal_base::OpContext;
+use cryxtal_bim::{BimElement, ParameterValue};
+use cryxtal_shapeops::{DEFAULT_SHAPEOPS_TOLERANCE, difference_many_with};
+use cryxtal_topology::Solid;
+
+const CUT_THROUGH_KEY: &str = "CutThrough";
+
+/// Marks an opening to also cut the other walls and slabs it passes through.
+pub fn set_cut_through(element: &mut BimElement, cut_through: bool) {
+    element.insert_parameter(CUT_THROUGH_KEY, ParameterValue::Bool(cut_through));
+}
+
+pub fn is_cut_through(element: &BimElement) -> bool {
+    matches!(
+        element.parameters.get(CUT_THROUGH_KEY),
+        Some(ParameterValue::Bool(true))
+    )
+}
+
+/// Subtracts the solids of openings hosted elsewhere that cut through `host` from its geometry.
+pub(super) fn cut_through<'a>(
+    host: &mut BimElement,
+    cutters: impl IntoIterator<Item = &'a BimElement>,
+    ctx: &OpContext,
+) -> Result<()> {
+    let tools: Vec<Solid> = cutters
+        .into_iter()
+        .map(|opening| opening.geometry.clone())
+        .collect();
+    if tools.is_empty() {
+        return Ok(());
+    }
+    host.geometry = difference_many_with(&host.geometry, &tools, DEFAULT_SHAPEOPS_TOLERANCE, ctx)
+        .context("failed to cut openings through host")?;
+    Ok(())
+}
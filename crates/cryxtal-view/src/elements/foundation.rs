@@ -0,0 +1,321 @@
+use anyhow::{Context, Result};
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, ParameterSet, ParameterValue};
+use cryxtal_io::bar_mass_per_metre;
+use cryxtal_topology::{Point3, Solid, SolidBuilder, Vector3};
+use truck_modeling::{InnerSpace, builder};
+
+use super::profile::{SectionProfile, element_name, extrude_section, write_axis};
+
+/// Bottom mat (and optionally a top mat) a foundation is to be reinforced with. It is recorded
+/// on the element as parameters, with an estimated mass, until the bars are detailed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FoundationReinforcement {
+    pub diameter: f64,
+    /// Spacing of the bars in both directions of each mat.
+    pub spacing: f64,
+    pub cover: f64,
+    pub top_mat: bool,
+}
+
+impl Default for FoundationReinforcement {
+    fn default() -> Self {
+        Self {
+            diameter: 12.0,
+            spacing: 200.0,
+            cover: 50.0,
+            top_mat: false,
+        }
+    }
+}
+
+impl FoundationReinforcement {
+    fn validate(&self) -> Result<()> {
+        if self.diameter <= 0.0 || self.spacing <= 0.0 {
+            anyhow::bail!("reinforcement diameter and spacing must be > 0");
+        }
+        if self.cover < 0.0 {
+            anyhow::bail!("reinforcement cover must be >= 0");
+        }
+        Ok(())
+    }
+
+    /// Writes the reinforcement of a `width` × `length` plan and its estimated mass in kg,
+    /// from both directions of each mat laid out inside the cover.
+    fn write_parameters(&self, parameters: &mut ParameterSet, width: f64, length: f64) {
+        let (inner_width, inner_length) = (width - 2.0 * self.cover, length - 2.0 * self.cover);
+        let bars = |across: f64| (across.max(0.0) / self.spacing).floor() + 1.0;
+        let mat_length = if inner_width > 0.0 && inner_length > 0.0 {
+            bars(inner_width) * inner_length + bars(inner_length) * inner_width
+        } else {
+            0.0
+        };
+        let layers = if self.top_mat { 2 } else { 1 };
+        let mass = mat_length * f64::from(layers) * 1.0e-3 * bar_mass_per_metre(self.diameter);
+        parameters.insert(
+            "RebarDiameter".to_string(),
+            ParameterValue::Number(self.diameter),
+        );
+        parameters.insert(
+            "RebarSpacing".to_string(),
+            ParameterValue::Number(self.spacing),
+        );
+        parameters.insert("Cover".to_string(), ParameterValue::Number(self.cover));
+        parameters.insert(
+            "RebarLayers".to_string(),
+            ParameterValue::Integer(i64::from(layers)),
+        );
+        parameters.insert("RebarMass".to_string(), ParameterValue::Number(mass));
+    }
+}
+
+/// Grid of `rows` × `columns` bored piles of `diameter`, `spacing` apart center to center and
+/// `length` long below the cap.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PileLayout {
+    pub rows: usize,
+    pub columns: usize,
+    pub spacing: f64,
+    pub diameter: f64,
+    pub length: f64,
+}
+
+/// Rectangular pile cap, `width` along X and `depth` along Y, with its piles if any.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PileCap {
+    pub width: f64,
+    pub depth: f64,
+    pub thickness: f64,
+    pub piles: Option<PileLayout>,
+}
+
+/// Elements of a pile cap built by [`build_pile_cap`].
+pub struct PileCapParts {
+    pub cap: BimElement,
+    /// Hosted by the cap and removed with it.
+    pub piles: Vec<BimElement>,
+}
+
+/// Rectangular pad footing centered under `top_center`, hanging `thickness` below it.
+pub fn build_footing_element(
+    top_center: Point3,
+    width: f64,
+    depth: f64,
+    thickness: f64,
+    reinforcement: Option<&FoundationReinforcement>,
+    name: Option<&str>,
+) -> Result<BimElement> {
+    let mut parameters = ParameterSet::new();
+    let solid = pad_solid(
+        top_center,
+        width,
+        depth,
+        thickness,
+        reinforcement,
+        &mut parameters,
+    )
+    .context("failed to build footing solid")?;
+    Ok(BimElement::new(
+        Guid::new(),
+        element_name(name, "Footing"),
+        BimCategory::Foundation,
+        parameters,
+        solid,
+    ))
+}
+
+/// Strip footing under a wall: `width` across the line from `start` to `end`, which runs
+/// along its top face, and `thickness` below it.
+pub fn build_strip_footing(
+    start: Point3,
+    end: Point3,
+    width: f64,
+    thickness: f64,
+    reinforcement: Option<&FoundationReinforcement>,
+    name: Option<&str>,
+) -> Result<BimElement> {
+    if thickness <= 0.0 {
+        anyhow::bail!("footing thickness must be > 0");
+    }
+    let down = Vector3::new(0.0, 0.0, thickness * 0.5);
+    let solid = extrude_section(
+        start - down,
+        end - down,
+        SectionProfile::Rectangular {
+            width,
+            depth: thickness,
+        },
+        0.0,
+    )
+    .context("failed to build strip footing solid")?;
+    let length = (end - start).magnitude();
+
+    let mut parameters = ParameterSet::new();
+    parameters.insert("Width".to_string(), ParameterValue::Number(width));
+    parameters.insert("Thickness".to_string(), ParameterValue::Number(thickness));
+    parameters.insert("Length".to_string(), ParameterValue::Number(length));
+    write_axis(&mut parameters, start, end);
+    write_quantities(&mut parameters, width, length, thickness);
+    if let Some(reinforcement) = reinforcement {
+        reinforcement.validate()?;
+        reinforcement.write_parameters(&mut parameters, width, length);
+    }
+
+    Ok(BimElement::new(
+        Guid::new(),
+        element_name(name, "Strip Footing"),
+        BimCategory::Foundation,
+        parameters,
+        solid,
+    ))
+}
+
+/// Pile cap centered under `top_center` and its piles, laid out symmetrically under it and
+/// running down from its underside.
+pub fn build_pile_cap(
+    top_center: Point3,
+    cap: &PileCap,
+    reinforcement: Option<&FoundationReinforcement>,
+    name: Option<&str>,
+) -> Result<PileCapParts> {
+    let mut parameters = ParameterSet::new();
+    let solid = pad_solid(
+        top_center,
+        cap.width,
+        cap.depth,
+        cap.thickness,
+        reinforcement,
+        &mut parameters,
+    )
+    .context("failed to build pile cap solid")?;
+    let pile_count = cap.piles.map_or(0, |piles| piles.rows * piles.columns);
+    parameters.insert(
+        "PileCount".to_string(),
+        ParameterValue::Integer(pile_count as i64),
+    );
+    let cap_element = BimElement::new(
+        Guid::new(),
+        element_name(name, "Pile Cap"),
+        BimCategory::Foundation,
+        parameters,
+        solid,
+    );
+
+    let mut piles = Vec::new();
+    if let Some(layout) = cap.piles {
+        let bottom = top_center - Vector3::new(0.0, 0.0, cap.thickness);
+        for center in pile_centers(bottom, cap, &layout)? {
+            let mut pile = build_pile(center, &layout)?;
+            pile.host = Some(cap_element.guid);
+            pile.removed_with_host = true;
+            piles.push(pile);
+        }
+    }
+    Ok(PileCapParts {
+        cap: cap_element,
+        piles,
+    })
+}
+
+/// Pile heads on the underside of `cap`, whose center is `bottom`. The grid must fit inside
+/// the cap.
+fn pile_centers(bottom: Point3, cap: &PileCap, layout: &PileLayout) -> Result<Vec<Point3>> {
+    if layout.rows == 0 || layout.columns == 0 {
+        anyhow::bail!("pile layout needs at least one row and column");
+    }
+    if layout.diameter <= 0.0 || layout.length <= 0.0 {
+        anyhow::bail!("pile diameter and length must be > 0");
+    }
+    let span = |count: usize| (count - 1) as f64 * layout.spacing;
+    if (layout.rows > 1 || layout.columns > 1) && layout.spacing < layout.diameter {
+        anyhow::bail!("pile spacing must be at least the pile diameter");
+    }
+    if span(layout.columns) + layout.diameter > cap.width
+        || span(layout.rows) + layout.diameter > cap.depth
+    {
+        anyhow::bail!("piles do not fit under the pile cap");
+    }
+    let origin = bottom - Vector3::new(span(layout.columns) * 0.5, span(layout.rows) * 0.5, 0.0);
+    Ok((0..layout.rows)
+        .flat_map(|row| {
+            (0..layout.columns).map(move |column| {
+                origin
+                    + Vector3::new(
+                        column as f64 * layout.spacing,
+                        row as f64 * layout.spacing,
+                        0.0,
+                    )
+            })
+        })
+        .collect())
+}
+
+fn build_pile(head: Point3, layout: &PileLayout) -> Result<BimElement> {
+    let toe = head - Vector3::new(0.0, 0.0, layout.length);
+    let profile = SectionProfile::Circular {
+        diameter: layout.diameter,
+    };
+    let solid = extrude_section(head, toe, profile, 0.0).context("failed to build pile solid")?;
+
+    let mut parameters = ParameterSet::new();
+    profile.write_parameters(&mut parameters);
+    parameters.insert("Length".to_string(), ParameterValue::Number(layout.length));
+    write_axis(&mut parameters, head, toe);
+    let area = std::f64::consts::PI * layout.diameter * layout.diameter * 0.25;
+    parameters.insert(
+        "Volume".to_string(),
+        ParameterValue::Number(area * layout.length),
+    );
+    Ok(BimElement::new(
+        Guid::new(),
+        "Pile",
+        BimCategory::Foundation,
+        parameters,
+        solid,
+    ))
+}
+
+/// Box hanging below `top_center` with its size, quantity and reinforcement parameters.
+fn pad_solid(
+    top_center: Point3,
+    width: f64,
+    depth: f64,
+    thickness: f64,
+    reinforcement: Option<&FoundationReinforcement>,
+    parameters: &mut ParameterSet,
+) -> Result<Solid> {
+    let solid = SolidBuilder::box_solid(width, depth, thickness)?;
+    let solid = builder::translated(
+        &solid,
+        Vector3::new(
+            top_center.x - width * 0.5,
+            top_center.y - depth * 0.5,
+            top_center.z - thickness,
+        ),
+    );
+
+    parameters.insert("Width".to_string(), ParameterValue::Number(width));
+    parameters.insert("Depth".to_string(), ParameterValue::Number(depth));
+    parameters.insert("Thickness".to_string(), ParameterValue::Number(thickness));
+    parameters.insert("StartX".to_string(), ParameterValue::Number(top_center.x));
+    parameters.insert("StartY".to_string(), ParameterValue::Number(top_center.y));
+    parameters.insert("StartZ".to_string(), ParameterValue::Number(top_center.z));
+    write_quantities(parameters, width, depth, thickness);
+    if let Some(reinforcement) = reinforcement {
+        reinforcement.validate()?;
+        reinforcement.write_parameters(parameters, width, depth);
+    }
+    Ok(solid)
+}
+
+/// Concrete volume and the formwork area of the sides of a `width` × `length` block.
+fn write_quantities(parameters: &mut ParameterSet, width: f64, length: f64, thickness: f64) {
+    parameters.insert(
+        "Volume".to_string(),
+        ParameterValue::Number(width * length * thickness),
+    );
+    parameters.insert(
+        "FormworkArea".to_string(),
+        ParameterValue::Number(2.0 * (width + length) * thickness),
+    );
+}
@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use cryxtal_base::Guid;
+#[cfg(feature = "gui")]
+use cryxtal_bim::ModelCommand;
+use cryxtal_bim::{BimCategory, BimElement, BimModel, ParameterValue};
+use cryxtal_topology::Vector3;
+
+use super::column::regenerate_column;
+use super::translate_element;
+
+/// Storey the element's base is bound to (`BaseStorey`), if any.
+pub fn base_level(element: &BimElement) -> Option<Guid> {
+    match element.parameters.get("BaseStorey") {
+        Some(ParameterValue::Text(storey)) => storey.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Binds the base of a wall or column to `storey`, or unbinds it with `None`. The element stays
+/// where it is: `BaseOffset` is its current base height above the storey.
+#[cfg(feature = "gui")]
+pub fn set_base_level(
+    model: &BimModel,
+    element: &mut BimElement,
+    storey: Option<Guid>,
+) -> Result<()> {
+    if !matches!(element.category, BimCategory::Wall | BimCategory::Column) {
+        anyhow::bail!("only walls and columns have a base level");
+    }
+    let Some(storey) = storey else {
+        element.parameters.remove("BaseStorey");
+        element.parameters.remove("BaseOffset");
+        return Ok(());
+    };
+    let elevation = model
+        .storey(storey)
+        .with_context(|| format!("unknown storey {storey}"))?
+        .elevation;
+    let base = base_z(element)?;
+    element.insert_parameter("BaseStorey", ParameterValue::Text(storey.to_string()));
+    element.insert_parameter("BaseOffset", ParameterValue::Number(base - elevation));
+    Ok(())
+}
+
+/// Regenerated versions of every element bound to a storey, for a
+/// [`ModelCommand::ReplaceElements`] after storey elevations changed: columns are rebuilt
+/// between their levels, and walls are moved to stand `BaseOffset` above their base storey.
+/// Openings and elements hosted by a moved wall or column move with it.
+pub fn regenerate_level_elements(model: &BimModel) -> Result<Vec<BimElement>> {
+    regenerate_bound(model, None)
+}
+
+/// Undoable step moving `storey` to `elevation` and regenerating the elements bound to it.
+#[cfg(feature = "gui")]
+pub fn set_storey_elevation(
+    model: &BimModel,
+    storey: Guid,
+    elevation: f64,
+) -> Result<ModelCommand> {
+    let command = ModelCommand::SetStoreyElevation { storey, elevation };
+    let mut moved = model.clone();
+    command.clone().apply(&mut moved)?;
+    let elements = regenerate_bound(&moved, Some(storey))?;
+    Ok(ModelCommand::Batch(vec![
+        command,
+        ModelCommand::ReplaceElements { elements },
+    ]))
+}
+
+/// Bound walls and columns, all of them or those bound to `storey`, with what they host.
+fn regenerate_bound(model: &BimModel, storey: Option<Guid>) -> Result<Vec<BimElement>> {
+    let mut regenerated = Vec::new();
+    for element in model
+        .query()
+        .matching(move |element| is_bound(element, storey))
+    {
+        let rebuilt = match element.category {
+            BimCategory::Column => regenerate_column(model, element),
+            BimCategory::Wall => regenerate_wall(model, element),
+            _ => continue,
+        }
+        .with_context(|| format!("failed to regenerate {}", element.name))?;
+        let shift = base_z(&rebuilt)? - base_z(element)?;
+        let hosted: Vec<&BimElement> = model
+            .hosted_openings(element.guid)
+            .into_iter()
+            .filter_map(|(index, _)| model.elements().get(index))
+            .chain(model.hosted_elements(element.guid))
+            .collect();
+        regenerated.push(rebuilt);
+        if shift.abs() <= 1.0e-9 {
+            continue;
+        }
+        for hosted in hosted {
+            let mut moved = hosted.clone();
+            translate_element(&mut moved, Vector3::new(0.0, 0.0, shift));
+            regenerated.push(moved);
+        }
+    }
+    Ok(regenerated)
+}
+
+fn is_bound(element: &BimElement, storey: Option<Guid>) -> bool {
+    let bound_to = |key: &str| match (element.parameters.get(key), storey) {
+        (Some(ParameterValue::Text(bound)), Some(storey)) => *bound == storey.to_string(),
+        (Some(_), None) => true,
+        _ => false,
+    };
+    match element.category {
+        BimCategory::Column => bound_to("BaseStorey") || bound_to("TopStorey"),
+        BimCategory::Wall => bound_to("BaseStorey"),
+        _ => false,
+    }
+}
+
+/// Wall moved up or down to stand `BaseOffset` above its base storey.
+fn regenerate_wall(model: &BimModel, wall: &BimElement) -> Result<BimElement> {
+    let storey = base_level(wall).context("wall has no base storey")?;
+    let elevation = model
+        .storey(storey)
+        .with_context(|| format!("unknown storey {storey}"))?
+        .elevation;
+    let offset = match wall.parameters.get("BaseOffset") {
+        Some(ParameterValue::Number(offset)) => *offset,
+        None => 0.0,
+        Some(_) => anyhow::bail!("invalid wall parameter: BaseOffset"),
+    };
+    let mut regenerated = wall.clone();
+    let shift = elevation + offset - base_z(wall)?;
+    if shift.abs() > 1.0e-9 {
+        translate_element(&mut regenerated, Vector3::new(0.0, 0.0, shift));
+    }
+    Ok(regenerated)
+}
+
+fn base_z(element: &BimElement) -> Result<f64> {
+    match element.parameters.get("StartZ") {
+        Some(ParameterValue::Number(z)) => Ok(*z),
+        _ => anyhow::bail!("{} has no StartZ", element.name),
+    }
+}
@@ -0,0 +1,237 @@
+use anyhow::Result;
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, Opening, ParameterSet, ParameterUnit, ParameterValue};
+use cryxtal_topology::Point3;
+
+use super::beam::{BeamOptions, build_beam_between_points};
+use super::profile::{SectionProfile, extrude_section, write_axis};
+use super::wall_opening::{WallData, wall_data};
+
+/// Rules for the lintels and sills generated at wall openings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LintelRules {
+    /// Openings narrower than this get neither.
+    pub min_width: f64,
+    /// Depth of the lintel over the opening.
+    pub lintel_depth: f64,
+    /// How far the lintel bears on the wall past each side of the opening.
+    pub bearing: f64,
+    /// Depth of the sill under the opening; 0 generates no sills.
+    pub sill_depth: f64,
+    /// How far the sill runs past each side of the opening.
+    pub sill_extension: f64,
+}
+
+/// Parameters the rules are stored under on each generated element, in the order of
+/// [`LintelRules::values`].
+const RULE_KEYS: [&str; 5] = [
+    "LintelMinWidth",
+    "LintelDepth",
+    "LintelBearing",
+    "SillDepth",
+    "SillExtension",
+];
+
+impl LintelRules {
+    fn values(&self) -> [f64; 5] {
+        [
+            self.min_width,
+            self.lintel_depth,
+            self.bearing,
+            self.sill_depth,
+            self.sill_extension,
+        ]
+    }
+
+    /// Rules `element` was generated from, when it is a lintel or sill from [`build_lintels`].
+    pub fn of(element: &BimElement) -> Option<Self> {
+        let mut values = [0.0; 5];
+        for (value, key) in values.iter_mut().zip(RULE_KEYS) {
+            let Some(ParameterValue::Number(number)) = element.parameters.get(key) else {
+                return None;
+            };
+            *value = *number;
+        }
+        let [min_width, lintel_depth, bearing, sill_depth, sill_extension] = values;
+        Some(Self {
+            min_width,
+            lintel_depth,
+            bearing,
+            sill_depth,
+            sill_extension,
+        })
+    }
+}
+
+/// Lintels and sills of an opening rebuilt by [`regenerate_lintels`].
+#[derive(Debug, Default)]
+pub struct RegeneratedLintels {
+    /// Rebuilt in place of an earlier one, keeping its guid and name.
+    pub replaced: Vec<BimElement>,
+    /// New, such as the sill of a door moved up into a window.
+    pub added: Vec<BimElement>,
+    /// No longer generated, such as the lintel of an opening narrowed below the minimum width.
+    pub removed: Vec<Guid>,
+}
+
+impl Default for LintelRules {
+    fn default() -> Self {
+        Self {
+            min_width: 600.0,
+            lintel_depth: 200.0,
+            bearing: 150.0,
+            sill_depth: 50.0,
+            sill_extension: 50.0,
+        }
+    }
+}
+
+/// Lintel beam over each of `openings` in `wall` at least `rules.min_width` wide, and a sill
+/// under those that do not start at the wall base, such as windows. Both are cut back to the
+/// wall and skipped where the wall leaves no room. Each element is hosted by its opening and
+/// removed with it, is cut from the wall, and keeps `rules` so it can follow the opening.
+pub fn build_lintels(
+    wall: &BimElement,
+    openings: &[Opening],
+    rules: &LintelRules,
+) -> Result<Vec<BimElement>> {
+    if wall.category != BimCategory::Wall {
+        anyhow::bail!("lintels can only be generated for wall openings");
+    }
+    let data = wall_data(wall)?;
+    let mut elements = Vec::new();
+    for opening in openings.iter().filter(|opening| opening.host == wall.guid) {
+        let width = opening.profile.width;
+        if width < rules.min_width {
+            continue;
+        }
+        let center = opening.placement.center_x;
+        let top = opening.placement.center_z + opening.profile.height * 0.5;
+        let bottom = opening.placement.center_z - opening.profile.height * 0.5;
+
+        let lintel_top = (top + rules.lintel_depth).min(data.height);
+        if rules.lintel_depth > 0.0 && lintel_top - top > 1.0e-6 {
+            let half = width * 0.5 + rules.bearing;
+            let (start_x, end_x) = ((center - half).max(0.0), (center + half).min(data.length));
+            let z = (top + lintel_top) * 0.5;
+            let profile = SectionProfile::Rectangular {
+                width: data.thickness,
+                depth: lintel_top - top,
+            };
+            let lintel = build_beam_between_points(
+                wall_point(&data, start_x, z),
+                wall_point(&data, end_x, z),
+                profile,
+                BeamOptions::default(),
+                Some("Lintel"),
+            )?;
+            elements.push(generated_for(lintel, wall, opening, rules));
+        }
+
+        let sill_bottom = (bottom - rules.sill_depth).max(0.0);
+        if rules.sill_depth > 0.0 && bottom - sill_bottom > 1.0e-6 {
+            let half = width * 0.5 + rules.sill_extension;
+            let (start_x, end_x) = ((center - half).max(0.0), (center + half).min(data.length));
+            elements.push(generated_for(
+                build_sill(&data, start_x, end_x, sill_bottom, bottom)?,
+                wall,
+                opening,
+                rules,
+            ));
+        }
+    }
+    Ok(elements)
+}
+
+fn build_sill(
+    wall: &WallData,
+    start_x: f64,
+    end_x: f64,
+    bottom: f64,
+    top: f64,
+) -> Result<BimElement> {
+    let z = (bottom + top) * 0.5;
+    let (start, end) = (wall_point(wall, start_x, z), wall_point(wall, end_x, z));
+    let profile = SectionProfile::Rectangular {
+        width: wall.thickness,
+        depth: top - bottom,
+    };
+    let solid = extrude_section(start, end, profile, 0.0)?;
+    let mut parameters = ParameterSet::new();
+    profile.write_parameters(&mut parameters);
+    parameters.insert(
+        "Length".to_string(),
+        ParameterValue::Number(end_x - start_x),
+    );
+    write_axis(&mut parameters, start, end);
+    Ok(BimElement::new(
+        Guid::new(),
+        "Sill",
+        BimCategory::Generic,
+        parameters,
+        solid,
+    ))
+}
+
+/// Rebuilds `existing`, the elements generated for `opening` in `wall`, from the rules they were
+/// generated with. Lintels and sills are matched by category. `None` when none of `existing`
+/// came from [`build_lintels`].
+pub fn regenerate_lintels(
+    wall: &BimElement,
+    opening: &Opening,
+    existing: &[&BimElement],
+) -> Result<Option<RegeneratedLintels>> {
+    let Some(rules) = existing.iter().find_map(|element| LintelRules::of(element)) else {
+        return Ok(None);
+    };
+    let mut previous: Vec<&BimElement> = existing
+        .iter()
+        .copied()
+        .filter(|element| LintelRules::of(element).is_some())
+        .collect();
+    let mut regenerated = RegeneratedLintels::default();
+    for mut element in build_lintels(wall, std::slice::from_ref(opening), &rules)? {
+        match previous
+            .iter()
+            .position(|earlier| earlier.category == element.category)
+        {
+            Some(index) => {
+                let earlier = previous.remove(index);
+                element.guid = earlier.guid;
+                element.name = earlier.name.clone();
+                regenerated.replaced.push(element);
+            }
+            None => regenerated.added.push(element),
+        }
+    }
+    regenerated.removed = previous.iter().map(|element| element.guid).collect();
+    Ok(Some(regenerated))
+}
+
+fn generated_for(
+    mut element: BimElement,
+    wall: &BimElement,
+    opening: &Opening,
+    rules: &LintelRules,
+) -> BimElement {
+    element.host = Some(opening.guid);
+    element.removed_with_host = true;
+    element.cuts = vec![wall.guid];
+    for (key, value) in RULE_KEYS.into_iter().zip(rules.values()) {
+        element.insert_parameter(key, ParameterValue::Number(value));
+        element
+            .parameter_units
+            .insert(key.to_string(), ParameterUnit::Length);
+    }
+    element.regen_hash = Some(element.parameter_hash());
+    element
+}
+
+/// Point `x` along the wall axis from its start and `z` above its base.
+fn wall_point(wall: &WallData, x: f64, z: f64) -> Point3 {
+    Point3::new(
+        wall.start.x + x * wall.angle.cos(),
+        wall.start.y + x * wall.angle.sin(),
+        wall.start.z + z,
+    )
+}
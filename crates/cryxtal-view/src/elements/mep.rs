@@ -0,0 +1,43 @@
+use anyhow::{Context, Result};
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, ParameterSet, ParameterValue};
+use cryxtal_topology::Point3;
+
+use super::profile::{SectionProfile, element_name, extrude_section, write_axis};
+
+/// Straight MEP run: round profiles make a pipe, rectangular ones a duct.
+pub fn build_mep_segment(
+    start: Point3,
+    end: Point3,
+    profile: SectionProfile,
+    name: Option<&str>,
+) -> Result<BimElement> {
+    let category = match profile {
+        SectionProfile::Circular { .. } => BimCategory::Pipe,
+        SectionProfile::Rectangular { .. } => BimCategory::Duct,
+        SectionProfile::Steel(_) => {
+            anyhow::bail!("MEP segments need a round or rectangular profile")
+        }
+    };
+    let solid =
+        extrude_section(start, end, profile, 0.0).context("failed to build segment solid")?;
+    let axis = end - start;
+    let length = (axis.x * axis.x + axis.y * axis.y + axis.z * axis.z).sqrt();
+
+    let mut parameters = ParameterSet::new();
+    profile.write_parameters(&mut parameters);
+    parameters.insert("Length".to_string(), ParameterValue::Number(length));
+    write_axis(&mut parameters, start, end);
+
+    let fallback = match category {
+        BimCategory::Pipe => "Pipe",
+        _ => "Duct",
+    };
+    Ok(BimElement::new(
+        Guid::new(),
+        element_name(name, fallback),
+        category,
+        parameters,
+        solid,
+    ))
+}
@@ -1,17 +1,35 @@
 use anyhow::{Context, Result};
 use cryxtal_base::Guid;
+#[cfg(feature = "gui")]
+use cryxtal_base::OpContext;
 use cryxtal_bim::{BimCategory, BimElement, ParameterSet, ParameterValue};
 use cryxtal_shapeops::{DEFAULT_SHAPEOPS_TOLERANCE, plate_with_hole};
 use cryxtal_topology::SolidBuilder;
 #[cfg(feature = "gui")]
 use cryxtal_topology::Point3;
-#[cfg(feature = "gui")]
 use cryxtal_topology::Vector3;
-#[cfg(feature = "gui")]
 use truck_modeling::builder;
 #[cfg(feature = "gui")]
 use truck_modeling::Rad;
 
+mod beam;
+mod column;
+mod connection;
+mod foundation;
+mod level;
+#[cfg(feature = "gui")]
+mod cut_through;
+#[cfg(feature = "gui")]
+mod lintel;
+mod mep;
+mod profile;
+mod ramp;
+mod roof;
+mod steel;
+#[cfg(feature = "gui")]
+mod slab;
+#[cfg(feature = "gui")]
+mod wall_join;
 #[cfg(feature = "gui")]
 mod wall_opening;
 #[cfg(feature = "gui")]
@@ -19,14 +37,49 @@ mod opening_outline;
 #[cfg(feature = "gui")]
 mod rebar;
 #[cfg(feature = "gui")]
+mod rebar_set;
+#[cfg(feature = "gui")]
+mod constraint;
+pub use beam::{BeamOptions, build_beam_between_points};
+pub use column::{ColumnLevel, build_column_element, build_level_column, regenerate_column};
+pub use connection::{BoltedConnection, ConnectionParts, build_bolted_connection};
+pub use foundation::{
+    FoundationReinforcement, PileCap, PileCapParts, PileLayout, build_footing_element,
+    build_pile_cap, build_strip_footing,
+};
+pub use level::{base_level, regenerate_level_elements};
+pub use mep::build_mep_segment;
+pub use profile::SectionProfile;
+pub use ramp::build_ramp_element;
+pub use roof::build_roof_element;
+pub use steel::steel_section;
+#[cfg(feature = "gui")]
+pub use level::{set_base_level, set_storey_elevation};
+#[cfg(feature = "gui")]
+pub use cut_through::{is_cut_through, set_cut_through};
+#[cfg(feature = "gui")]
+pub use lintel::{LintelRules, RegeneratedLintels, build_lintels, regenerate_lintels};
+#[cfg(feature = "gui")]
+pub use slab::{
+    apply_slab_opening, build_slab_element, rebuild_hosted_slab, rebuild_hosted_slab_with,
+};
+#[cfg(feature = "gui")]
 pub use wall_opening::{
-    apply_wall_opening, build_opening_element, opening_index_at_point,
-    rebuild_wall_from_openings, sync_opening_from_wall,
+    WallOpeningMode, apply_wall_opening, build_opening_element, displaced_openings,
+    opening_at_point, rebuild_hosted_wall, rebuild_hosted_wall_with,
 };
 #[cfg(feature = "gui")]
+pub use wall_join::{WallJoinStyle, join_walls};
+#[cfg(feature = "gui")]
 pub use opening_outline::opening_outline_points;
 #[cfg(feature = "gui")]
-pub use rebar::{apply_rebar_edit, build_rebar_between_points, rebar_data};
+pub use rebar::{RebarShape, apply_rebar_edit, build_rebar_between_points, rebar_data};
+#[cfg(feature = "gui")]
+pub use constraint::regenerate_constrained;
+#[cfg(feature = "gui")]
+pub use rebar_set::{
+    RebarFace, RebarSet, apply_rebar_set, build_rebar_set, is_rebar_set, regenerate_rebar_set,
+};
 
 pub fn build_box_element(
     width: f64,
@@ -96,6 +149,54 @@ pub fn build_plate_element(
     ))
 }
 
+/// Rebuilds a wall or slab together with the openings it hosts.
+#[cfg(feature = "gui")]
+pub fn rebuild_host(elements: &mut [BimElement], host_index: usize) -> Result<()> {
+    rebuild_host_with(elements, host_index, &OpContext::default())
+}
+
+/// Like [`rebuild_host`], reporting progress and honouring cancellation through `ctx`.
+#[cfg(feature = "gui")]
+pub fn rebuild_host_with(
+    elements: &mut [BimElement],
+    host_index: usize,
+    ctx: &OpContext,
+) -> Result<()> {
+    match elements.get(host_index).map(|element| element.category) {
+        Some(BimCategory::Slab) => rebuild_hosted_slab_with(elements, host_index, ctx),
+        _ => rebuild_hosted_wall_with(elements, host_index, ctx),
+    }
+}
+
+/// Moves an element's geometry and its positional parameters (`Start*`, `End*`, `Point{n}*`).
+pub fn translate_element(element: &mut BimElement, offset: Vector3) {
+    element.geometry = builder::translated(&element.geometry, offset);
+    for (key, value) in element.parameters.iter_mut() {
+        let ParameterValue::Number(number) = value else {
+            continue;
+        };
+        let Some(axis) = positional_axis(key) else {
+            continue;
+        };
+        *number += match axis {
+            'X' => offset.x,
+            'Y' => offset.y,
+            _ => offset.z,
+        };
+    }
+}
+
+fn positional_axis(key: &str) -> Option<char> {
+    let axis = key.chars().last().filter(|axis| matches!(axis, 'X' | 'Y' | 'Z'))?;
+    let stem = &key[..key.len() - 1];
+    let positional = stem == "Start"
+        || stem == "End"
+        || stem
+            .strip_prefix("Point")
+            .is_some_and(|index| !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()));
+    positional.then_some(axis)
+}
+
 #[cfg(feature = "gui")]
 pub fn build_wall_between_points(
     start: Point3,
@@ -1,22 +1,17 @@
-use cryxtal_bim::{BimCategory, BimElement, ParameterValue};
+use cryxtal_bim::{BimElement, ParameterValue, opening_host};
 use cryxtal_topology::Point3;
 
 pub fn opening_outline_points(
     opening: &BimElement,
     elements: &[BimElement],
 ) -> Option<[Point3; 4]> {
-    if opening.category != BimCategory::Opening {
-        return None;
-    }
-
-    let width = read_number(opening, "Width")?;
-    let height = read_number(opening, "Height")?;
+    let data = opening.opening?;
+    let (width, height) = (data.profile.width, data.profile.height);
     if width <= 0.0 || height <= 0.0 {
         return None;
     }
-    let center_x = read_number(opening, "CenterX")?;
-    let center_z = read_number(opening, "CenterZ")?;
-    let host = find_opening_host(opening, elements)?;
+    let (center_x, center_z) = (data.placement.center_x, data.placement.center_z);
+    let host = &elements[opening_host(elements, &data)?];
     let (start_x, start_y, start_z, end_x, end_y) = wall_start_end(host)?;
 
     let angle = (end_y - start_y).atan2(end_x - start_x);
@@ -62,23 +57,3 @@ fn wall_start_end(host: &BimElement) -> Option<(f64, f64, f64, f64, f64)> {
         read_number(host, "EndY")?,
     ))
 }
-
-fn find_opening_host<'a>(opening: &BimElement, elements: &'a [BimElement]) -> Option<&'a BimElement> {
-    if let Some(ParameterValue::Integer(value)) = opening.parameters.get("HostIndex") {
-        if *value >= 0 {
-            if let Some(host) = elements.get(*value as usize) {
-                if host.category == BimCategory::Wall {
-                    return Some(host);
-                }
-            }
-        }
-    }
-
-    let guid = match opening.parameters.get("HostGuid") {
-        Some(ParameterValue::Text(value)) => value.as_str(),
-        _ => return None,
-    };
-    elements
-        .iter()
-        .find(|element| element.category == BimCategory::Wall && element.guid.to_string() == guid)
-}
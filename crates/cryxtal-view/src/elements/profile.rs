@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use cryxtal_bim::{ParameterSet, ParameterValue};
+use cryxtal_topology::{Point3, Solid, Vector3, Wire};
+use truck_modeling::{Rad, builder};
+
+use super::steel::{SteelSection, steel_section};
+
+/// Cross-section swept along a member axis: columns, beams, pipes and ducts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SectionProfile {
+    /// `width` runs horizontally across the axis, `depth` perpendicular to it.
+    Rectangular {
+        width: f64,
+        depth: f64,
+    },
+    Circular {
+        diameter: f64,
+    },
+    /// Rolled section from the steel profile library; its height runs like `depth`.
+    Steel(SteelSection),
+}
+
+impl SectionProfile {
+    pub(super) fn write_parameters(self, parameters: &mut ParameterSet) {
+        match self {
+            SectionProfile::Rectangular { width, depth } => {
+                parameters.insert(
+                    "Profile".to_string(),
+                    ParameterValue::Text("Rectangular".into()),
+                );
+                parameters.insert("ProfileWidth".to_string(), ParameterValue::Number(width));
+                parameters.insert("ProfileDepth".to_string(), ParameterValue::Number(depth));
+            }
+            SectionProfile::Circular { diameter } => {
+                parameters.insert(
+                    "Profile".to_string(),
+                    ParameterValue::Text("Circular".into()),
+                );
+                parameters.insert("Diameter".to_string(), ParameterValue::Number(diameter));
+            }
+            SectionProfile::Steel(section) => {
+                parameters.insert(
+                    "Profile".to_string(),
+                    ParameterValue::Text(section.name.into()),
+                );
+                parameters.insert(
+                    "ProfileWidth".to_string(),
+                    ParameterValue::Number(section.width),
+                );
+                parameters.insert(
+                    "ProfileDepth".to_string(),
+                    ParameterValue::Number(section.height),
+                );
+                parameters.insert(
+                    "WebThickness".to_string(),
+                    ParameterValue::Number(section.web),
+                );
+                parameters.insert(
+                    "FlangeThickness".to_string(),
+                    ParameterValue::Number(section.flange),
+                );
+            }
+        }
+    }
+
+    /// Reads back a profile written by [`SectionProfile::write_parameters`].
+    pub(super) fn read_parameters(parameters: &ParameterSet) -> Result<Self> {
+        let number = |key: &str| match parameters.get(key) {
+            Some(ParameterValue::Number(value)) => Ok(*value),
+            _ => anyhow::bail!("missing or invalid profile parameter: {key}"),
+        };
+        match parameters.get("Profile") {
+            Some(ParameterValue::Text(name)) if name == "Rectangular" => {
+                Ok(SectionProfile::Rectangular {
+                    width: number("ProfileWidth")?,
+                    depth: number("ProfileDepth")?,
+                })
+            }
+            Some(ParameterValue::Text(name)) if name == "Circular" => {
+                Ok(SectionProfile::Circular {
+                    diameter: number("Diameter")?,
+                })
+            }
+            Some(ParameterValue::Text(name)) => steel_section(name)
+                .map(SectionProfile::Steel)
+                .with_context(|| format!("unknown profile `{name}`")),
+            _ => anyhow::bail!("missing or invalid profile parameter: Profile"),
+        }
+    }
+
+    fn validate(self) -> Result<()> {
+        let positive = match self {
+            SectionProfile::Rectangular { width, depth } => width > 0.0 && depth > 0.0,
+            SectionProfile::Circular { diameter } => diameter > 0.0,
+            SectionProfile::Steel(section) => {
+                section.width > section.web
+                    && section.height > section.flange * 2.0
+                    && section.web > 0.0
+                    && section.flange > 0.0
+            }
+        };
+        if !positive {
+            anyhow::bail!("profile dimensions must be > 0");
+        }
+        Ok(())
+    }
+}
+
+/// Sweeps `profile` from `start` to `end`, centered on the axis. Rectangular and steel
+/// sections keep their width horizontal (along X on vertical axes), then turn by `roll_deg`
+/// about the axis.
+pub(super) fn extrude_section(
+    start: Point3,
+    end: Point3,
+    profile: SectionProfile,
+    roll_deg: f64,
+) -> Result<Solid> {
+    profile.validate()?;
+    let axis = end - start;
+    let length = (axis.x * axis.x + axis.y * axis.y + axis.z * axis.z).sqrt();
+    if length <= 1.0e-6 {
+        anyhow::bail!("member length is too small");
+    }
+    let dir = axis / length;
+    let horizontal = Vector3::new(dir.y, -dir.x, 0.0);
+    let horizontal_len = (horizontal.x * horizontal.x + horizontal.y * horizontal.y).sqrt();
+    let across = if horizontal_len <= 1.0e-8 {
+        Vector3::unit_x()
+    } else {
+        horizontal / horizontal_len
+    };
+    let up = Vector3::new(
+        dir.y * across.z - dir.z * across.y,
+        dir.z * across.x - dir.x * across.z,
+        dir.x * across.y - dir.y * across.x,
+    );
+    let (sin, cos) = roll_deg.to_radians().sin_cos();
+    let u = across * cos + up * sin;
+    let v = up * cos - across * sin;
+    let polygon = |points: &[(f64, f64)]| -> Wire {
+        let vertices: Vec<_> = points
+            .iter()
+            .map(|(a, b)| builder::vertex(start + u * *a + v * *b))
+            .collect();
+        (0..vertices.len())
+            .map(|idx| builder::line(&vertices[idx], &vertices[(idx + 1) % vertices.len()]))
+            .collect::<Vec<_>>()
+            .into()
+    };
+
+    let wire: Wire = match profile {
+        SectionProfile::Rectangular { width, depth } => polygon(&[
+            (-0.5 * width, -0.5 * depth),
+            (0.5 * width, -0.5 * depth),
+            (0.5 * width, 0.5 * depth),
+            (-0.5 * width, 0.5 * depth),
+        ]),
+        SectionProfile::Steel(section) => polygon(&section.outline()),
+        SectionProfile::Circular { diameter } => {
+            let vertex = builder::vertex(start + u * (diameter * 0.5));
+            builder::rsweep(&vertex, start, dir, Rad(std::f64::consts::PI * 2.0), 32)
+        }
+    };
+    let face = builder::try_attach_plane(&[wire]).context("failed to build section face")?;
+    Ok(builder::tsweep(&face, axis))
+}
+
+pub(super) fn write_axis(parameters: &mut ParameterSet, start: Point3, end: Point3) {
+    parameters.insert("StartX".to_string(), ParameterValue::Number(start.x));
+    parameters.insert("StartY".to_string(), ParameterValue::Number(start.y));
+    parameters.insert("StartZ".to_string(), ParameterValue::Number(start.z));
+    parameters.insert("EndX".to_string(), ParameterValue::Number(end.x));
+    parameters.insert("EndY".to_string(), ParameterValue::Number(end.y));
+    parameters.insert("EndZ".to_string(), ParameterValue::Number(end.z));
+}
+
+/// Reads back the axis written by [`write_axis`].
+pub(super) fn read_axis(parameters: &ParameterSet) -> Result<(Point3, Point3)> {
+    let number = |key: &str| match parameters.get(key) {
+        Some(ParameterValue::Number(value)) => Ok(*value),
+        _ => anyhow::bail!("missing or invalid axis parameter: {key}"),
+    };
+    Ok((
+        Point3::new(number("StartX")?, number("StartY")?, number("StartZ")?),
+        Point3::new(number("EndX")?, number("EndY")?, number("EndZ")?),
+    ))
+}
+
+pub(super) fn element_name(name: Option<&str>, fallback: &str) -> String {
+    match name {
+        Some(value) if !value.trim().is_empty() => value.trim().to_string(),
+        _ => fallback.to_string(),
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, ParameterSet, ParameterValue};
+use cryxtal_topology::{Point3, Vector3, Wire};
+use truck_modeling::{InnerSpace, builder};
+
+use super::profile::element_name;
+use super::roof::face_points_down;
+
+/// Formula keeping `SlopePercent` in step with the rise and run, which also makes it
+/// read-only.
+const SLOPE_FORMULA: &str = "Height / Length * 100";
+
+/// Sloped slab whose top face spans from the `start` edge to the `end` edge, such as a parking
+/// or accessibility ramp. The four corners must lie in one plane, which must not be vertical.
+/// `thickness` is measured vertically and the sides stay vertical. `Height` is the rise from
+/// the start edge's midpoint to the end edge's (negative when the ramp descends), `Length` the
+/// run between them in plan, and `SlopePercent` follows from both.
+pub fn build_ramp_element(
+    start: [Point3; 2],
+    end: [Point3; 2],
+    thickness: f64,
+    name: Option<&str>,
+) -> Result<BimElement> {
+    if thickness <= 0.0 {
+        anyhow::bail!("ramp thickness must be > 0");
+    }
+    let outline = [start[0], start[1], end[1], end[0]];
+    let normal = (start[1] - start[0]).cross(end[0] - start[0]);
+    let size = outline
+        .iter()
+        .map(|point| (*point - start[0]).magnitude())
+        .fold(0.0, f64::max);
+    if normal.magnitude() <= 1.0e-9 * size * size {
+        anyhow::bail!("ramp edges must span an area");
+    }
+    let normal = normal.normalize();
+    if (end[1] - start[0]).dot(normal).abs() > 1.0e-6 * size.max(1.0) {
+        anyhow::bail!("ramp edges must lie in one plane");
+    }
+    if normal.z.abs() <= 1.0e-6 {
+        anyhow::bail!("ramp surface must not be vertical");
+    }
+
+    let midpoint =
+        |[a, b]: [Point3; 2]| Point3::new((a.x + b.x) * 0.5, (a.y + b.y) * 0.5, (a.z + b.z) * 0.5);
+    let (low, high) = (midpoint(start), midpoint(end));
+    let run = ((high.x - low.x).powi(2) + (high.y - low.y).powi(2)).sqrt();
+    if run <= 1.0e-6 {
+        anyhow::bail!("ramp edges must be apart in plan");
+    }
+
+    let down = Vector3::new(0.0, 0.0, thickness);
+    let vertices: Vec<_> = outline
+        .iter()
+        .map(|point| builder::vertex(*point - down))
+        .collect();
+    let wire: Wire = (0..vertices.len())
+        .map(|idx| builder::line(&vertices[idx], &vertices[(idx + 1) % vertices.len()]))
+        .collect::<Vec<_>>()
+        .into();
+    let face = builder::try_attach_plane(&[wire]).context("failed to build ramp face")?;
+    let face = if face_points_down(&outline) {
+        face.inverse()
+    } else {
+        face
+    };
+    let solid = builder::tsweep(&face, down);
+
+    let mut parameters = ParameterSet::new();
+    parameters.insert("Thickness".to_string(), ParameterValue::Number(thickness));
+    parameters.insert(
+        "Width".to_string(),
+        ParameterValue::Number((start[1] - start[0]).magnitude()),
+    );
+    parameters.insert("Length".to_string(), ParameterValue::Number(run));
+    parameters.insert("Height".to_string(), ParameterValue::Number(high.z - low.z));
+    parameters.insert(
+        "PointCount".to_string(),
+        ParameterValue::Integer(outline.len() as i64),
+    );
+    for (index, point) in outline.iter().enumerate() {
+        let idx = index + 1;
+        parameters.insert(format!("Point{idx}X"), ParameterValue::Number(point.x));
+        parameters.insert(format!("Point{idx}Y"), ParameterValue::Number(point.y));
+        parameters.insert(format!("Point{idx}Z"), ParameterValue::Number(point.z));
+    }
+
+    let mut element = BimElement::new(
+        Guid::new(),
+        element_name(name, "Ramp"),
+        BimCategory::Ramp,
+        parameters,
+        solid,
+    );
+    element.set_formula("SlopePercent", SLOPE_FORMULA)?;
+    Ok(element)
+}
@@ -1,9 +1,250 @@
 use anyhow::{Context, Result};
 use cryxtal_base::Guid;
 use cryxtal_bim::{BimCategory, BimElement, ParameterSet, ParameterValue};
+use cryxtal_io::{bend_radius, hook_extension};
 use cryxtal_shapeops::{DEFAULT_SHAPEOPS_TOLERANCE, union};
 use cryxtal_topology::{Point3, Solid, SolidBuilder, Vector3};
-use truck_modeling::{builder, Rad};
+use truck_modeling::{InnerSpace, Rad, builder};
+
+/// Number of pieces each bend of a shaped bar is swept in.
+const BEND_DIVISION: usize = 4;
+
+/// Standard bar shape: a straight run between two points, optionally bent into legs or closed
+/// into a tie. Legs and tie depths are measured between bar center lines, so they must be longer
+/// than the bend they start with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RebarShape {
+    Straight,
+    /// One 90° bend at the end of the run into a leg of length `leg`.
+    L {
+        leg: f64,
+    },
+    /// 90° bends at both ends of the run into legs of length `leg` on the same side.
+    U {
+        leg: f64,
+    },
+    /// Closed rectangular tie with the run as one side and `depth` as the other, closed by two
+    /// 135° hooks at the start corner.
+    Stirrup {
+        depth: f64,
+    },
+}
+
+impl RebarShape {
+    pub const LABELS: [&'static str; 4] = ["Straight", "L", "U", "Stirrup"];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RebarShape::Straight => "Straight",
+            RebarShape::L { .. } => "L",
+            RebarShape::U { .. } => "U",
+            RebarShape::Stirrup { .. } => "Stirrup",
+        }
+    }
+
+    /// Leg length of L and U bars, depth of stirrups.
+    pub fn dimension(self) -> Option<f64> {
+        match self {
+            RebarShape::Straight => None,
+            RebarShape::L { leg } | RebarShape::U { leg } => Some(leg),
+            RebarShape::Stirrup { depth } => Some(depth),
+        }
+    }
+
+    /// Shape named `label`, with `dimension` as its leg length or tie depth.
+    pub fn from_label(label: &str, dimension: f64) -> Option<Self> {
+        match label {
+            "Straight" => Some(RebarShape::Straight),
+            "L" => Some(RebarShape::L { leg: dimension }),
+            "U" => Some(RebarShape::U { leg: dimension }),
+            "Stirrup" => Some(RebarShape::Stirrup { depth: dimension }),
+            _ => None,
+        }
+    }
+
+    /// Center line corners before bending, for a run from `start` to `end` with legs and tie
+    /// sides pointing along `side`.
+    fn corners(
+        self,
+        start: Point3,
+        end: Point3,
+        side: Vector3,
+        diameter: f64,
+    ) -> Result<Vec<Point3>> {
+        let Some(dimension) = self.dimension() else {
+            return Ok(vec![start, end]);
+        };
+        if dimension <= 0.0 {
+            anyhow::bail!("rebar {} dimension must be > 0", self.label());
+        }
+        let run = (end - start).normalize();
+        let side = side - run * side.dot(run);
+        if side.magnitude() <= 1.0e-9 {
+            anyhow::bail!("rebar legs must not run along the bar");
+        }
+        let side = side.normalize() * dimension;
+        Ok(match self {
+            RebarShape::Straight => vec![start, end],
+            RebarShape::L { .. } => vec![start, end, end + side],
+            RebarShape::U { .. } => vec![start + side, start, end, end + side],
+            RebarShape::Stirrup { .. } => {
+                let hook = (run + side.normalize()).normalize();
+                let tangent = bend_center_radius(diameter) * (0.375 * std::f64::consts::PI).tan();
+                let tail = start + hook * (tangent + hook_extension(diameter));
+                vec![tail, start, end, end + side, start + side, start, tail]
+            }
+        })
+    }
+}
+
+fn bend_center_radius(diameter: f64) -> f64 {
+    bend_radius(diameter) + diameter * 0.5
+}
+
+/// Bar of a standard shape with its corners bent at [`bend_radius`].
+#[derive(Clone, Debug)]
+pub struct BentBar {
+    corners: Vec<Point3>,
+    segments: Vec<BarSegment>,
+    diameter: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum BarSegment {
+    Straight {
+        start: Point3,
+        end: Point3,
+    },
+    /// Arc from `start`, leaving along `direction`, turning `angle` about `axis` through
+    /// `center`.
+    Bend {
+        start: Point3,
+        direction: Vector3,
+        center: Point3,
+        axis: Vector3,
+        angle: f64,
+    },
+}
+
+impl BentBar {
+    /// Bends `shape` for a run from `start` to `end`; `side` is the direction its legs and tie
+    /// sides point in and is made perpendicular to the run.
+    pub fn new(
+        shape: RebarShape,
+        start: Point3,
+        end: Point3,
+        side: Vector3,
+        diameter: f64,
+    ) -> Result<Self> {
+        if diameter <= 0.0 {
+            anyhow::bail!("rebar diameter must be > 0");
+        }
+        if (end - start).magnitude() <= 1.0e-6 {
+            anyhow::bail!("rebar segment is too short");
+        }
+        let corners = shape.corners(start, end, side, diameter)?;
+        let radius = bend_center_radius(diameter);
+        let mut segments = Vec::new();
+        let mut cursor = corners[0];
+        for window in corners.windows(3) {
+            let (previous, corner, next) = (window[0], window[1], window[2]);
+            let incoming = (corner - previous).normalize();
+            let outgoing = (next - corner).normalize();
+            let angle = incoming.dot(outgoing).clamp(-1.0, 1.0).acos();
+            if angle <= 1.0e-6 {
+                continue;
+            }
+            let tangent = radius * (angle * 0.5).tan();
+            let bend_start = corner - incoming * tangent;
+            if (bend_start - cursor).dot(incoming) < -1.0e-6 {
+                anyhow::bail!("rebar {} leg is too short for its bends", shape.label());
+            }
+            if (bend_start - cursor).magnitude() > 1.0e-6 {
+                segments.push(BarSegment::Straight {
+                    start: cursor,
+                    end: bend_start,
+                });
+            }
+            let inward = (outgoing - incoming * incoming.dot(outgoing)).normalize();
+            segments.push(BarSegment::Bend {
+                start: bend_start,
+                direction: incoming,
+                center: bend_start + inward * radius,
+                axis: incoming.cross(outgoing).normalize(),
+                angle,
+            });
+            cursor = corner + outgoing * tangent;
+        }
+        let last = corners[corners.len() - 1];
+        let previous = corners[corners.len() - 2];
+        if (last - cursor).dot(last - previous) < -1.0e-6 {
+            anyhow::bail!("rebar {} leg is too short for its bends", shape.label());
+        }
+        if (last - cursor).magnitude() > 1.0e-6 {
+            segments.push(BarSegment::Straight {
+                start: cursor,
+                end: last,
+            });
+        }
+        Ok(Self {
+            corners,
+            segments,
+            diameter,
+        })
+    }
+
+    /// Center line corners the bar is bent at, as bar lists give its shape.
+    pub fn corners(&self) -> &[Point3] {
+        &self.corners
+    }
+
+    /// Developed center line length the bar is cut at: straight runs plus bend arcs.
+    pub fn cut_length(&self) -> f64 {
+        let radius = bend_center_radius(self.diameter);
+        self.segments
+            .iter()
+            .map(|segment| match *segment {
+                BarSegment::Straight { start, end } => (end - start).magnitude(),
+                BarSegment::Bend { angle, .. } => radius * angle,
+            })
+            .sum()
+    }
+
+    /// Circular section swept along the center line. Straight runs and bends are separate
+    /// shells meeting at their end sections, which avoids boolean unions of tangent pieces.
+    pub fn solid(&self) -> Result<Solid> {
+        let mut shells = Vec::new();
+        for segment in &self.segments {
+            let solid = match *segment {
+                BarSegment::Straight { start, end } => {
+                    build_rebar_segment(start, end, self.diameter)?
+                }
+                BarSegment::Bend {
+                    start,
+                    direction,
+                    center,
+                    axis,
+                    angle,
+                } => {
+                    let radial = (start - center).normalize() * (self.diameter * 0.5);
+                    let vertex = builder::vertex(start + radial);
+                    let wire = builder::rsweep(
+                        &vertex,
+                        start,
+                        direction,
+                        Rad(std::f64::consts::PI * 2.0),
+                        32,
+                    );
+                    let section = builder::try_attach_plane(&[wire])
+                        .context("failed to build rebar section")?;
+                    builder::rsweep(&section, center, axis, Rad(angle), BEND_DIVISION)
+                }
+            };
+            shells.extend(solid.into_boundaries());
+        }
+        Solid::try_new(shells).context("failed to build bent rebar solid")
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct RebarData {
@@ -239,3 +480,78 @@ fn is_rebar_param_key(key: &str) -> bool {
         || (key.starts_with("Point")
             && (key.ends_with('X') || key.ends_with('Y') || key.ends_with('Z')))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    const DIAMETER: f64 = 12.0;
+
+    /// Ends of a 1000 run along X, with legs pointing along Y.
+    fn run() -> (Point3, Point3) {
+        (Point3::new(0.0, 0.0, 0.0), Point3::new(1000.0, 0.0, 0.0))
+    }
+
+    fn bar(shape: RebarShape) -> Result<BentBar> {
+        let (start, end) = run();
+        BentBar::new(shape, start, end, Vector3::unit_y(), DIAMETER)
+    }
+
+    fn corners(shape: RebarShape) -> Result<Vec<Point3>> {
+        let (start, end) = run();
+        shape.corners(start, end, Vector3::unit_y(), DIAMETER)
+    }
+
+    #[test]
+    fn cut_lengths_follow_the_bends() -> Result<()> {
+        // 2Ø bend radius to the inside of the bar, 30 to its center line.
+        let radius = bend_center_radius(DIAMETER);
+        assert!((radius - 30.0).abs() < 1.0e-9);
+        assert!((bar(RebarShape::Straight)?.cut_length() - 1000.0).abs() < 1.0e-9);
+        // Each 90° bend cuts its corner short by twice the radius and adds a quarter circle.
+        let bend = PI * 0.5 * radius - 2.0 * radius;
+        let l = RebarShape::L { leg: 300.0 };
+        assert_eq!(corners(l)?.len(), 3);
+        assert!((bar(l)?.cut_length() - (1300.0 + bend)).abs() < 1.0e-9);
+        let u = RebarShape::U { leg: 300.0 };
+        assert_eq!(corners(u)?[0], Point3::new(0.0, 300.0, 0.0));
+        assert!((bar(u)?.cut_length() - (1600.0 + 2.0 * bend)).abs() < 1.0e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn stirrups_close_with_hooks() -> Result<()> {
+        let shape = RebarShape::Stirrup { depth: 300.0 };
+        let corners = corners(shape)?;
+        assert_eq!(corners.len(), 7);
+        assert_eq!(corners[0], corners[6]);
+        // Both hooks end in a straight extension of 5Ø, at least 50.
+        let stirrup = bar(shape)?;
+        let segments = &stirrup.segments;
+        for segment in [segments[0], segments[segments.len() - 1]] {
+            let BarSegment::Straight { start, end } = segment else {
+                panic!("hooks end straight");
+            };
+            assert!(((end - start).magnitude() - hook_extension(DIAMETER)).abs() < 1.0e-6);
+        }
+        stirrup.solid()?;
+        Ok(())
+    }
+
+    #[test]
+    fn legs_must_outlast_their_bends() -> Result<()> {
+        assert!(bar(RebarShape::L { leg: 20.0 }).is_err());
+        assert!(bar(RebarShape::U { leg: 0.0 }).is_err());
+        let (start, end) = run();
+        let along = BentBar::new(
+            RebarShape::L { leg: 300.0 },
+            start,
+            end,
+            Vector3::unit_x(),
+            DIAMETER,
+        );
+        assert!(along.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,538 @@
+use anyhow::{Context, Result};
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, ParameterSet, ParameterValue};
+use cryxtal_io::{BAR_CORNERS_KEY, CUT_LENGTHS_KEY};
+use cryxtal_topology::{Point3, Solid, Vector3};
+
+use super::profile::element_name;
+use super::rebar::{BentBar, RebarShape};
+use super::slab::slab_data;
+use super::wall_opening::wall_data;
+
+const SHAPE_KEY: &str = "Shape";
+const LEG_KEY: &str = "Leg";
+const TIE_DEPTH_KEY: &str = "TieDepth";
+const FACE_KEY: &str = "Face";
+const SPACING_KEY: &str = "Spacing";
+const COUNT_KEY: &str = "Count";
+const COVER_KEY: &str = "Cover";
+const BAR_COUNT_KEY: &str = "BarCount";
+const CUT_LENGTH_KEY: &str = "MaxCutLength";
+const MIN_BAR_LENGTH: f64 = 1.0;
+
+/// Host face a rebar set runs along. Walls use `Left` (the `+Y` side in wall-local
+/// coordinates) and `Right`; slabs use `Top` and `Bottom`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebarFace {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl RebarFace {
+    pub fn label(self) -> &'static str {
+        match self {
+            RebarFace::Left => "Left",
+            RebarFace::Right => "Right",
+            RebarFace::Top => "Top",
+            RebarFace::Bottom => "Bottom",
+        }
+    }
+
+    /// Faces bars can be laid along on a host of `category`.
+    pub fn for_host(category: BimCategory) -> &'static [RebarFace] {
+        match category {
+            BimCategory::Wall => &[RebarFace::Left, RebarFace::Right],
+            BimCategory::Slab => &[RebarFace::Top, RebarFace::Bottom],
+            _ => &[],
+        }
+    }
+
+    fn from_label(label: &str) -> Option<Self> {
+        [
+            RebarFace::Left,
+            RebarFace::Right,
+            RebarFace::Top,
+            RebarFace::Bottom,
+        ]
+        .into_iter()
+        .find(|face| face.label() == label)
+    }
+}
+
+/// Bars of one shape repeated at `spacing` along a wall or slab face, `cover` inside the host.
+/// Wall bars stand vertical and are distributed along the wall; slab bars run parallel to the
+/// first outline edge and are clipped to the outline. `count` of `None` fills the face.
+/// Legs and ties of bent shapes point into the host: across the wall, or down from the top of a
+/// slab and up from its bottom. Openings in the host are not cut out of the set.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RebarSet {
+    pub host: Guid,
+    pub shape: RebarShape,
+    pub face: RebarFace,
+    pub diameter: f64,
+    pub spacing: f64,
+    pub count: Option<usize>,
+    pub cover: f64,
+}
+
+impl RebarSet {
+    /// Reads the set stored on a rebar set element.
+    pub fn read(element: &BimElement) -> Result<Self> {
+        if !is_rebar_set(element) {
+            anyhow::bail!("{} is not a rebar set", element.name);
+        }
+        let host = element.host.context("rebar set has no host")?;
+        let shape = match element.parameters.get(SHAPE_KEY) {
+            Some(ParameterValue::Text(label)) => {
+                let dimension = match label.as_str() {
+                    "Stirrup" => read_number(element, TIE_DEPTH_KEY)?,
+                    "L" | "U" => read_number(element, LEG_KEY)?,
+                    _ => 0.0,
+                };
+                RebarShape::from_label(label, dimension)
+                    .with_context(|| format!("unknown rebar shape: {label}"))?
+            }
+            _ => RebarShape::Straight,
+        };
+        let face = match element.parameters.get(FACE_KEY) {
+            Some(ParameterValue::Text(label)) => RebarFace::from_label(label)
+                .with_context(|| format!("unknown rebar face: {label}"))?,
+            _ => anyhow::bail!("missing or invalid rebar set parameter: {FACE_KEY}"),
+        };
+        let count = match element.parameters.get(COUNT_KEY) {
+            Some(ParameterValue::Integer(count)) => Some((*count).max(0) as usize),
+            _ => None,
+        };
+        Ok(Self {
+            host,
+            shape,
+            face,
+            diameter: read_number(element, "Diameter")?,
+            spacing: read_number(element, SPACING_KEY)?,
+            count,
+            cover: read_number(element, COVER_KEY)?,
+        })
+    }
+
+    /// Bars inside `host`, bent to the set's shape.
+    pub fn bars(&self, host: &BimElement) -> Result<Vec<BentBar>> {
+        if host.guid != self.host {
+            anyhow::bail!("rebar set is hosted by another element");
+        }
+        if self.diameter <= 0.0 {
+            anyhow::bail!("rebar diameter must be > 0");
+        }
+        if self.spacing <= 0.0 {
+            anyhow::bail!("rebar spacing must be > 0");
+        }
+        if self.cover < 0.0 {
+            anyhow::bail!("rebar cover must be >= 0");
+        }
+        if !RebarFace::for_host(host.category).contains(&self.face) {
+            anyhow::bail!(
+                "{} face is not available on {:?} hosts",
+                self.face.label(),
+                host.category
+            );
+        }
+        let (runs, side) = match host.category {
+            BimCategory::Wall => self.wall_runs(host)?,
+            _ => self.slab_runs(host)?,
+        };
+        if runs.is_empty() {
+            anyhow::bail!("rebar set has no bars inside the host");
+        }
+        runs.into_iter()
+            .map(|[start, end]| BentBar::new(self.shape, start, end, side, self.diameter))
+            .collect()
+    }
+
+    /// Straight runs of the bars and the direction their legs point in.
+    fn wall_runs(&self, host: &BimElement) -> Result<(Vec<[Point3; 2]>, Vector3)> {
+        let wall = wall_data(host)?;
+        let inset = self.cover + self.diameter * 0.5;
+        if wall.thickness < inset * 2.0 || wall.height < self.cover * 2.0 + MIN_BAR_LENGTH {
+            anyhow::bail!("wall is too small for {:.0} cover", self.cover);
+        }
+        let along = Vector3::new(wall.angle.cos(), wall.angle.sin(), 0.0);
+        let side = match self.face {
+            RebarFace::Left => 1.0,
+            _ => -1.0,
+        };
+        let across = Vector3::new(-along.y, along.x, 0.0) * (side * (wall.thickness * 0.5 - inset));
+        let bottom = wall.start.z + self.cover;
+        let top = wall.start.z + wall.height - self.cover;
+        let positions = self.distribute(inset, wall.length - inset)?;
+        let runs = positions
+            .into_iter()
+            .map(|position| {
+                let base = wall.start + along * position + across;
+                [
+                    Point3::new(base.x, base.y, bottom),
+                    Point3::new(base.x, base.y, top),
+                ]
+            })
+            .collect();
+        Ok((runs, -across))
+    }
+
+    fn slab_runs(&self, host: &BimElement) -> Result<(Vec<[Point3; 2]>, Vector3)> {
+        let (outline, thickness) = slab_data(host)?;
+        let inset = self.cover + self.diameter * 0.5;
+        if thickness < inset * 2.0 {
+            anyhow::bail!("slab is too thin for {:.0} cover", self.cover);
+        }
+        let origin = outline[0];
+        let edge = outline[1] - origin;
+        let edge_length = (edge.x * edge.x + edge.y * edge.y).sqrt();
+        if edge_length <= 1.0e-6 {
+            anyhow::bail!("slab outline starts with a zero-length edge");
+        }
+        let along = Vector3::new(edge.x / edge_length, edge.y / edge_length, 0.0);
+        let across = Vector3::new(-along.y, along.x, 0.0);
+        let (z, side) = match self.face {
+            RebarFace::Top => (origin.z - inset, -Vector3::unit_z()),
+            _ => (origin.z - thickness + inset, Vector3::unit_z()),
+        };
+        let project = |point: Point3, axis: Vector3| {
+            (point.x - origin.x) * axis.x + (point.y - origin.y) * axis.y
+        };
+        let (min, max) = outline
+            .iter()
+            .map(|point| project(*point, across))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                (min.min(value), max.max(value))
+            });
+
+        let mut runs = Vec::new();
+        for offset in self.distribute(min + inset, max - inset)? {
+            // Even-odd crossings of the bar line with the outline give the spans inside it.
+            let mut crossings: Vec<f64> = (0..outline.len())
+                .filter_map(|idx| {
+                    let a = outline[idx];
+                    let b = outline[(idx + 1) % outline.len()];
+                    let (da, db) = (project(a, across) - offset, project(b, across) - offset);
+                    if (da > 0.0) == (db > 0.0) {
+                        return None;
+                    }
+                    let t = da / (da - db);
+                    let (sa, sb) = (project(a, along), project(b, along));
+                    Some(sa + (sb - sa) * t)
+                })
+                .collect();
+            crossings.sort_by(f64::total_cmp);
+            for span in crossings.chunks_exact(2) {
+                let (start, end) = (span[0] + self.cover, span[1] - self.cover);
+                if end - start < MIN_BAR_LENGTH {
+                    continue;
+                }
+                let point = |s: f64| {
+                    let p = origin + along * s + across * offset;
+                    Point3::new(p.x, p.y, z)
+                };
+                runs.push([point(start), point(end)]);
+            }
+        }
+        Ok((runs, side))
+    }
+
+    /// Bar positions between `min` and `max`, centered when the bars do not fill the range.
+    fn distribute(&self, min: f64, max: f64) -> Result<Vec<f64>> {
+        let available = max - min;
+        if available < 0.0 {
+            anyhow::bail!("host is too small for {:.0} cover", self.cover);
+        }
+        let count = self
+            .count
+            .unwrap_or_else(|| (available / self.spacing + 1.0e-9).floor() as usize + 1);
+        if count == 0 {
+            anyhow::bail!("rebar set needs at least one bar");
+        }
+        let span = (count - 1) as f64 * self.spacing;
+        if span > available + 1.0e-6 {
+            anyhow::bail!(
+                "{count} bars at {:.0} spacing need {span:.0}, only {available:.0} available",
+                self.spacing
+            );
+        }
+        let first = min + (available - span) * 0.5;
+        Ok((0..count)
+            .map(|idx| first + idx as f64 * self.spacing)
+            .collect())
+    }
+
+    fn write_parameters(&self, parameters: &mut ParameterSet, bars: &[BentBar]) {
+        parameters.insert(
+            SHAPE_KEY.to_string(),
+            ParameterValue::Text(self.shape.label().to_string()),
+        );
+        parameters.remove(LEG_KEY);
+        parameters.remove(TIE_DEPTH_KEY);
+        match self.shape {
+            RebarShape::Straight => {}
+            RebarShape::L { leg } | RebarShape::U { leg } => {
+                parameters.insert(LEG_KEY.to_string(), ParameterValue::Number(leg));
+            }
+            RebarShape::Stirrup { depth } => {
+                parameters.insert(TIE_DEPTH_KEY.to_string(), ParameterValue::Number(depth));
+            }
+        }
+        parameters.insert(
+            FACE_KEY.to_string(),
+            ParameterValue::Text(self.face.label().to_string()),
+        );
+        parameters.insert(
+            "Diameter".to_string(),
+            ParameterValue::Number(self.diameter),
+        );
+        parameters.insert(
+            SPACING_KEY.to_string(),
+            ParameterValue::Number(self.spacing),
+        );
+        parameters.insert(COVER_KEY.to_string(), ParameterValue::Number(self.cover));
+        match self.count {
+            Some(count) => {
+                parameters.insert(COUNT_KEY.to_string(), ParameterValue::Integer(count as i64));
+            }
+            None => {
+                parameters.remove(COUNT_KEY);
+            }
+        }
+        parameters.insert(
+            BAR_COUNT_KEY.to_string(),
+            ParameterValue::Integer(bars.len() as i64),
+        );
+        parameters.insert(
+            CUT_LENGTH_KEY.to_string(),
+            ParameterValue::Number(bars.iter().map(BentBar::cut_length).fold(0.0, f64::max)),
+        );
+        parameters.insert(
+            "Length".to_string(),
+            ParameterValue::Number(bars.iter().map(BentBar::cut_length).sum()),
+        );
+        let cut_lengths: Vec<String> = bars
+            .iter()
+            .map(|bar| format!("{:.1}", bar.cut_length()))
+            .collect();
+        parameters.insert(
+            CUT_LENGTHS_KEY.to_string(),
+            ParameterValue::Text(cut_lengths.join(" ")),
+        );
+        let corners: Vec<String> = bars
+            .iter()
+            .map(|bar| {
+                let corners: Vec<String> = bar
+                    .corners()
+                    .iter()
+                    .map(|point| format!("{:.1},{:.1},{:.1}", point.x, point.y, point.z))
+                    .collect();
+                corners.join(" ")
+            })
+            .collect();
+        parameters.insert(
+            BAR_CORNERS_KEY.to_string(),
+            ParameterValue::Text(corners.join(";")),
+        );
+    }
+}
+
+pub fn is_rebar_set(element: &BimElement) -> bool {
+    element.category == BimCategory::Rebar && element.parameters.contains_key(SPACING_KEY)
+}
+
+/// Builds a rebar set element hosted by `host`; all bars form one solid.
+pub fn build_rebar_set(
+    set: &RebarSet,
+    host: &BimElement,
+    name: Option<&str>,
+) -> Result<BimElement> {
+    let bars = set.bars(host)?;
+    let mut parameters = ParameterSet::new();
+    set.write_parameters(&mut parameters, &bars);
+    let mut element = BimElement::new(
+        Guid::new(),
+        element_name(name, "Rebar Set"),
+        BimCategory::Rebar,
+        parameters,
+        build_set_solid(&bars)?,
+    );
+    element.host = Some(set.host);
+    Ok(element)
+}
+
+/// Stores `set` on `element` and rebuilds its bars against `host`, e.g. after the set was
+/// edited or the host's geometry changed. The element is untouched when the rebuild fails.
+pub fn apply_rebar_set(element: &mut BimElement, set: &RebarSet, host: &BimElement) -> Result<()> {
+    let bars = set.bars(host)?;
+    let geometry = build_set_solid(&bars)?;
+    set.write_parameters(&mut element.parameters, &bars);
+    element.geometry = geometry;
+    element.host = Some(set.host);
+    Ok(())
+}
+
+/// Rebuilds a rebar set element from its stored set against `host`.
+pub fn regenerate_rebar_set(element: &mut BimElement, host: &BimElement) -> Result<()> {
+    let set = RebarSet::read(element)?;
+    apply_rebar_set(element, &set, host)
+}
+
+fn build_set_solid(bars: &[BentBar]) -> Result<Solid> {
+    let mut shells = Vec::new();
+    for bar in bars {
+        shells.extend(bar.solid()?.into_boundaries());
+    }
+    Solid::try_new(shells).context("failed to build rebar set solid")
+}
+
+fn read_number(element: &BimElement, key: &str) -> Result<f64> {
+    match element.parameters.get(key) {
+        Some(ParameterValue::Number(value)) => Ok(*value),
+        _ => anyhow::bail!("missing or invalid rebar set parameter: {key}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::{build_slab_element, build_wall_between_points};
+
+    fn wall(length: f64) -> Result<BimElement> {
+        build_wall_between_points(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(length, 0.0, 0.0),
+            200.0,
+            3000.0,
+            None,
+        )
+    }
+
+    fn set(host: &BimElement, face: RebarFace, spacing: f64, cover: f64) -> RebarSet {
+        RebarSet {
+            host: host.guid,
+            shape: RebarShape::Straight,
+            face,
+            diameter: 10.0,
+            spacing,
+            count: None,
+            cover,
+        }
+    }
+
+    /// Straight runs the bars of `set` are bent around in `host`.
+    fn runs(set: &RebarSet, host: &BimElement) -> Result<Vec<[Point3; 2]>> {
+        let (runs, _) = match host.category {
+            BimCategory::Wall => set.wall_runs(host)?,
+            _ => set.slab_runs(host)?,
+        };
+        Ok(runs)
+    }
+
+    #[test]
+    fn wall_bars_fill_the_face_centered() -> Result<()> {
+        let host = wall(3000.0)?;
+        let left = set(&host, RebarFace::Left, 200.0, 30.0);
+        // 2930 between the end covers holds 15 bars, centered from 100 to 2900.
+        assert_eq!(left.bars(&host)?.len(), 15);
+        let bars = runs(&left, &host)?;
+        assert_eq!(
+            bars[0],
+            [
+                Point3::new(100.0, 65.0, 30.0),
+                Point3::new(100.0, 65.0, 2970.0)
+            ]
+        );
+        assert!((bars[14][0].x - 2900.0).abs() < 1.0e-9);
+        let right = RebarSet {
+            face: RebarFace::Right,
+            ..left
+        };
+        assert!((runs(&right, &host)?[0][0].y + 65.0).abs() < 1.0e-9);
+
+        let element = build_rebar_set(&left, &host, None)?;
+        assert_eq!(element.host, Some(host.guid));
+        assert_eq!(
+            element.parameters.get(BAR_COUNT_KEY),
+            Some(&ParameterValue::Integer(15))
+        );
+        assert_eq!(RebarSet::read(&element)?, left);
+        Ok(())
+    }
+
+    #[test]
+    fn sets_follow_their_host() -> Result<()> {
+        let host = wall(3000.0)?;
+        let mut element = build_rebar_set(&set(&host, RebarFace::Left, 200.0, 30.0), &host, None)?;
+        let mut longer = wall(4000.0)?;
+        longer.guid = host.guid;
+        regenerate_rebar_set(&mut element, &longer)?;
+        assert_eq!(
+            element.parameters.get(BAR_COUNT_KEY),
+            Some(&ParameterValue::Integer(20))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn impossible_sets_are_rejected() -> Result<()> {
+        let host = wall(3000.0)?;
+        let too_many = RebarSet {
+            count: Some(20),
+            ..set(&host, RebarFace::Left, 200.0, 30.0)
+        };
+        assert!(too_many.bars(&host).is_err());
+        assert!(set(&host, RebarFace::Top, 200.0, 30.0).bars(&host).is_err());
+        assert!(
+            set(&host, RebarFace::Left, 200.0, 100.0)
+                .bars(&host)
+                .is_err()
+        );
+        // Bars are only laid into the set's own host.
+        assert!(
+            set(&wall(2000.0)?, RebarFace::Left, 200.0, 30.0)
+                .bars(&host)
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn slab_bars_are_clipped_to_the_outline() -> Result<()> {
+        // L-shaped slab, its top at 0: 2000 x 1000 with a 1000 x 1000 wing on one side.
+        let outline: Vec<Point3> = [
+            (0.0, 0.0),
+            (2000.0, 0.0),
+            (2000.0, 1000.0),
+            (1000.0, 1000.0),
+            (1000.0, 2000.0),
+            (0.0, 2000.0),
+        ]
+        .into_iter()
+        .map(|(x, y)| Point3::new(x, y, 0.0))
+        .collect();
+        let host = build_slab_element(&outline, 200.0, None)?;
+        let top = set(&host, RebarFace::Top, 500.0, 50.0);
+        assert_eq!(top.bars(&host)?.len(), 4);
+        let spans: Vec<[f64; 4]> = runs(&top, &host)?
+            .iter()
+            .map(|[start, end]| [start.x, end.x, start.y, start.z])
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                [50.0, 1950.0, 250.0, -55.0],
+                [50.0, 1950.0, 750.0, -55.0],
+                [50.0, 950.0, 1250.0, -55.0],
+                [50.0, 950.0, 1750.0, -55.0],
+            ]
+        );
+        let bottom = RebarSet {
+            face: RebarFace::Bottom,
+            ..top
+        };
+        assert!((runs(&bottom, &host)?[0][0].z + 145.0).abs() < 1.0e-9);
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use anyhow::{Context, Result};
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, ParameterSet, ParameterValue};
+use cryxtal_topology::{Point3, Vector3, Wire};
+use truck_modeling::builder;
+
+use super::profile::element_name;
+
+/// Mono-pitch roof over a slab outline. The first outline edge is the eave: the underside
+/// sits at the outline's height there and rises inward at `slope_deg`. Sides stay vertical.
+pub fn build_roof_element(
+    outline: &[Point3],
+    thickness: f64,
+    slope_deg: f64,
+    name: Option<&str>,
+) -> Result<BimElement> {
+    if outline.len() < 3 {
+        anyhow::bail!("roof outline needs at least 3 points");
+    }
+    if thickness <= 0.0 {
+        anyhow::bail!("roof thickness must be > 0");
+    }
+    if !(0.0..80.0).contains(&slope_deg) {
+        anyhow::bail!("roof slope must be between 0° and 80°");
+    }
+
+    let eave = outline[0];
+    let edge = outline[1] - eave;
+    let edge_len = (edge.x * edge.x + edge.y * edge.y).sqrt();
+    if edge_len <= 1.0e-6 {
+        anyhow::bail!("roof eave edge is too short");
+    }
+    let count = outline.len() as f64;
+    let centroid = outline
+        .iter()
+        .fold(Vector3::new(0.0, 0.0, 0.0), |sum, point| {
+            sum + Vector3::new(point.x, point.y, point.z)
+        })
+        / count;
+    let mut inward = Vector3::new(-edge.y / edge_len, edge.x / edge_len, 0.0);
+    if (centroid.x - eave.x) * inward.x + (centroid.y - eave.y) * inward.y < 0.0 {
+        inward = -inward;
+    }
+    let rise = slope_deg.to_radians().tan();
+
+    let vertices: Vec<_> = outline
+        .iter()
+        .map(|point| {
+            let run = (point.x - eave.x) * inward.x + (point.y - eave.y) * inward.y;
+            builder::vertex(Point3::new(point.x, point.y, eave.z + run * rise))
+        })
+        .collect();
+    let wire: Wire = (0..vertices.len())
+        .map(|idx| builder::line(&vertices[idx], &vertices[(idx + 1) % vertices.len()]))
+        .collect::<Vec<_>>()
+        .into();
+    let face = builder::try_attach_plane(&[wire]).context("failed to build roof face")?;
+    let face = if face_points_down(outline) {
+        face.inverse()
+    } else {
+        face
+    };
+    let solid = builder::tsweep(&face, Vector3::new(0.0, 0.0, thickness));
+
+    let mut parameters = ParameterSet::new();
+    parameters.insert("Thickness".to_string(), ParameterValue::Number(thickness));
+    parameters.insert("Slope".to_string(), ParameterValue::Number(slope_deg));
+    parameters.insert(
+        "PointCount".to_string(),
+        ParameterValue::Integer(outline.len() as i64),
+    );
+    for (index, point) in outline.iter().enumerate() {
+        let idx = index + 1;
+        parameters.insert(format!("Point{idx}X"), ParameterValue::Number(point.x));
+        parameters.insert(format!("Point{idx}Y"), ParameterValue::Number(point.y));
+        parameters.insert(format!("Point{idx}Z"), ParameterValue::Number(point.z));
+    }
+
+    Ok(BimElement::new(
+        Guid::new(),
+        element_name(name, "Roof"),
+        BimCategory::Roof,
+        parameters,
+        solid,
+    ))
+}
+
+/// Clockwise outlines (seen from above) give a downward face normal, which would sweep the
+/// roof inside out.
+pub(super) fn face_points_down(outline: &[Point3]) -> bool {
+    let area: f64 = (0..outline.len())
+        .map(|idx| {
+            let a = outline[idx];
+            let b = outline[(idx + 1) % outline.len()];
+            a.x * b.y - b.x * a.y
+        })
+        .sum();
+    area < 0.0
+}
@@ -0,0 +1,320 @@
+use anyhow::{Context, Result};
+use cryxtal_base::{Guid, OpContext};
+use cryxtal_bim::{
+    BimCategory, BimElement, Opening, OpeningPlacement, OpeningProfile, ParameterSet,
+    ParameterValue, cutting_openings, hosted_openings,
+};
+use cryxtal_topology::{Point3, Solid, SolidBuilder, Vector3, Wire};
+use truck_modeling::builder;
+
+use super::cut_through::cut_through;
+use super::profile::element_name;
+
+const LEVEL_TOLERANCE: f64 = 1.0e-6;
+
+/// Plan rectangle of a slab opening in world coordinates.
+#[derive(Clone, Copy, Debug)]
+struct OpeningRect {
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+}
+
+/// Horizontal slab over a closed outline. The outline is the top face; the slab hangs
+/// `thickness` below it.
+pub fn build_slab_element(
+    outline: &[Point3],
+    thickness: f64,
+    name: Option<&str>,
+) -> Result<BimElement> {
+    validate_outline(outline)?;
+    if thickness <= 0.0 {
+        anyhow::bail!("slab thickness must be > 0");
+    }
+    let solid = build_slab_solid(outline, thickness, &[])?;
+
+    let mut parameters = ParameterSet::new();
+    parameters.insert("Thickness".to_string(), ParameterValue::Number(thickness));
+    parameters.insert(
+        "Area".to_string(),
+        ParameterValue::Number(signed_area(outline).abs()),
+    );
+    parameters.insert(
+        "PointCount".to_string(),
+        ParameterValue::Integer(outline.len() as i64),
+    );
+    for (index, point) in outline.iter().enumerate() {
+        let idx = index + 1;
+        parameters.insert(format!("Point{idx}X"), ParameterValue::Number(point.x));
+        parameters.insert(format!("Point{idx}Y"), ParameterValue::Number(point.y));
+        parameters.insert(format!("Point{idx}Z"), ParameterValue::Number(point.z));
+    }
+
+    Ok(BimElement::new(
+        Guid::new(),
+        element_name(name, "Slab"),
+        BimCategory::Slab,
+        parameters,
+        solid,
+    ))
+}
+
+/// Places a `width` (along X) by `depth` (along Y) opening centered on `world_center`. The
+/// placement is stored as the plan offset from the slab's first outline point.
+pub fn apply_slab_opening(
+    host: &BimElement,
+    world_center: Point3,
+    width: f64,
+    depth: f64,
+) -> Result<Opening> {
+    if host.category != BimCategory::Slab {
+        anyhow::bail!("opening can only be applied to slab elements");
+    }
+    if width <= 0.0 {
+        anyhow::bail!("opening width must be > 0");
+    }
+    if depth <= 0.0 {
+        anyhow::bail!("opening depth must be > 0");
+    }
+
+    let (outline, _) = slab_data(host)?;
+    let origin = outline[0];
+    let opening = Opening::new(
+        host.guid,
+        OpeningProfile {
+            width,
+            height: depth,
+        },
+        OpeningPlacement {
+            center_x: world_center.x - origin.x,
+            center_y: world_center.y - origin.y,
+            center_z: 0.0,
+        },
+    );
+    ensure_inside(&outline, opening_rect(origin, &opening))?;
+    Ok(opening)
+}
+
+/// Resolves the openings hosted by `elements[host_index]` and regenerates the slab and every
+/// opening element against it; openings hosted elsewhere that cut through the slab are
+/// subtracted as well. Nothing is modified when the rebuild fails, or when neither the slab's
+/// parameters nor its openings changed since it was last built.
+pub fn rebuild_hosted_slab(elements: &mut [BimElement], host_index: usize) -> Result<()> {
+    rebuild_hosted_slab_with(elements, host_index, &OpContext::default())
+}
+
+/// Like [`rebuild_hosted_slab`], reporting progress and honouring cancellation through `ctx`.
+pub fn rebuild_hosted_slab_with(
+    elements: &mut [BimElement],
+    host_index: usize,
+    ctx: &OpContext,
+) -> Result<()> {
+    let Some(host) = elements.get(host_index) else {
+        anyhow::bail!("host element {host_index} does not exist");
+    };
+    let hosted = hosted_openings(elements, host.guid);
+    let cutters = cutting_openings(elements, host.guid);
+    let dependencies: Vec<usize> = hosted
+        .iter()
+        .map(|(idx, _)| *idx)
+        .chain(cutters.iter().copied())
+        .collect();
+    if host.is_up_to_date(dependencies.iter().map(|idx| &elements[*idx])) {
+        return Ok(());
+    }
+    let (outline, thickness) = slab_data(host)?;
+    let rects: Vec<OpeningRect> = hosted
+        .iter()
+        .map(|(_, opening)| opening_rect(outline[0], opening))
+        .collect();
+    for rect in &rects {
+        ensure_inside(&outline, *rect)?;
+    }
+    ensure_openings_do_not_overlap(&rects)?;
+
+    let mut slab = host.clone();
+    slab.geometry = build_slab_solid(&outline, thickness, &rects)?;
+    cut_through(&mut slab, cutters.iter().map(|idx| &elements[*idx]), ctx)?;
+    let solids = rects
+        .iter()
+        .map(|rect| build_opening_solid(&outline, thickness, *rect))
+        .collect::<Result<Vec<_>>>()?;
+    for ((idx, _), solid) in hosted.iter().zip(solids) {
+        let element = &mut elements[*idx];
+        element.geometry = solid;
+        element.regen_hash = Some(element.parameter_hash());
+    }
+    slab.regen_hash = Some(slab.regen_key(dependencies.iter().map(|idx| &elements[*idx])));
+    elements[host_index] = slab;
+    Ok(())
+}
+
+/// Solid shown for an opening element cut into a slab.
+pub(super) fn slab_opening_solid(host: &BimElement, opening: &Opening) -> Result<Solid> {
+    let (outline, thickness) = slab_data(host)?;
+    build_opening_solid(&outline, thickness, opening_rect(outline[0], opening))
+}
+
+pub(super) fn slab_data(element: &BimElement) -> Result<(Vec<Point3>, f64)> {
+    let count = match element.parameters.get("PointCount") {
+        Some(ParameterValue::Integer(count)) if *count >= 3 => *count as usize,
+        _ => anyhow::bail!("missing or invalid slab parameter: PointCount"),
+    };
+    let outline = (1..=count)
+        .map(|idx| {
+            Ok(Point3::new(
+                read_number(element, &format!("Point{idx}X"))?,
+                read_number(element, &format!("Point{idx}Y"))?,
+                read_number(element, &format!("Point{idx}Z"))?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let thickness = read_number(element, "Thickness")?;
+    if thickness <= 0.0 {
+        anyhow::bail!("slab thickness is too small");
+    }
+    Ok((outline, thickness))
+}
+
+fn validate_outline(outline: &[Point3]) -> Result<()> {
+    if outline.len() < 3 {
+        anyhow::bail!("slab outline needs at least 3 points");
+    }
+    let level = outline[0].z;
+    if outline
+        .iter()
+        .any(|point| (point.z - level).abs() > LEVEL_TOLERANCE)
+    {
+        anyhow::bail!("slab outline must be horizontal");
+    }
+    if signed_area(outline).abs() <= LEVEL_TOLERANCE {
+        anyhow::bail!("slab outline encloses no area");
+    }
+    Ok(())
+}
+
+fn build_slab_solid(outline: &[Point3], thickness: f64, holes: &[OpeningRect]) -> Result<Solid> {
+    validate_outline(outline)?;
+    let base = outline[0].z - thickness;
+    // A counter-clockwise outer loop faces up, so the sweep runs outward; holes run the other way.
+    let mut points: Vec<(f64, f64)> = outline.iter().map(|point| (point.x, point.y)).collect();
+    if signed_area(outline) < 0.0 {
+        points.reverse();
+    }
+    let mut wires = vec![polygon_wire(&points, base)];
+    for hole in holes {
+        wires.push(polygon_wire(
+            &[
+                (hole.min_x, hole.min_y),
+                (hole.min_x, hole.max_y),
+                (hole.max_x, hole.max_y),
+                (hole.max_x, hole.min_y),
+            ],
+            base,
+        ));
+    }
+    let face = builder::try_attach_plane(wires).context("failed to build slab face")?;
+    Ok(builder::tsweep(&face, Vector3::unit_z() * thickness))
+}
+
+fn build_opening_solid(outline: &[Point3], thickness: f64, rect: OpeningRect) -> Result<Solid> {
+    let margin = (thickness * 0.02).max(1.0);
+    let height = thickness + margin * 2.0;
+    let solid = SolidBuilder::box_solid(rect.max_x - rect.min_x, rect.max_y - rect.min_y, height)
+        .context("failed to build opening solid")?;
+    Ok(builder::translated(
+        &solid,
+        Vector3::new(rect.min_x, rect.min_y, outline[0].z - thickness - margin),
+    ))
+}
+
+fn opening_rect(origin: Point3, opening: &Opening) -> OpeningRect {
+    let OpeningProfile { width, height } = opening.profile;
+    let OpeningPlacement {
+        center_x, center_y, ..
+    } = opening.placement;
+    let x = origin.x + center_x;
+    let y = origin.y + center_y;
+    OpeningRect {
+        min_x: x - width * 0.5,
+        min_y: y - height * 0.5,
+        max_x: x + width * 0.5,
+        max_y: y + height * 0.5,
+    }
+}
+
+/// Openings must lie inside the outline: every corner inside and no outline vertex inside the
+/// rectangle (which would mean an outline edge crosses it).
+fn ensure_inside(outline: &[Point3], rect: OpeningRect) -> Result<()> {
+    let corners = [
+        (rect.min_x, rect.min_y),
+        (rect.max_x, rect.min_y),
+        (rect.max_x, rect.max_y),
+        (rect.min_x, rect.max_y),
+    ];
+    let corners_inside = corners
+        .iter()
+        .all(|&(x, y)| point_in_outline(outline, x, y));
+    let vertex_inside = outline.iter().any(|point| {
+        point.x > rect.min_x && point.x < rect.max_x && point.y > rect.min_y && point.y < rect.max_y
+    });
+    if !corners_inside || vertex_inside {
+        anyhow::bail!("opening must lie inside the slab outline");
+    }
+    Ok(())
+}
+
+fn ensure_openings_do_not_overlap(openings: &[OpeningRect]) -> Result<()> {
+    for (idx, opening) in openings.iter().enumerate() {
+        for other in openings.iter().skip(idx + 1) {
+            let overlap_x = opening.min_x < other.max_x && other.min_x < opening.max_x;
+            let overlap_y = opening.min_y < other.max_y && other.min_y < opening.max_y;
+            if overlap_x && overlap_y {
+                anyhow::bail!("openings overlap");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn point_in_outline(outline: &[Point3], x: f64, y: f64) -> bool {
+    let mut inside = false;
+    for idx in 0..outline.len() {
+        let a = outline[idx];
+        let b = outline[(idx + 1) % outline.len()];
+        if (a.y > y) != (b.y > y) && x < a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn signed_area(outline: &[Point3]) -> f64 {
+    (0..outline.len())
+        .map(|idx| {
+            let a = outline[idx];
+            let b = outline[(idx + 1) % outline.len()];
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f64>()
+        * 0.5
+}
+
+fn polygon_wire(points: &[(f64, f64)], z: f64) -> Wire {
+    let vertices: Vec<_> = points
+        .iter()
+        .map(|(x, y)| builder::vertex(Point3::new(*x, *y, z)))
+        .collect();
+    (0..vertices.len())
+        .map(|idx| builder::line(&vertices[idx], &vertices[(idx + 1) % vertices.len()]))
+        .collect::<Vec<_>>()
+        .into()
+}
+
+fn read_number(element: &BimElement, key: &str) -> Result<f64> {
+    match element.parameters.get(key) {
+        Some(ParameterValue::Number(value)) => Ok(*value),
+        _ => anyhow::bail!("missing or invalid slab parameter: {key}"),
+    }
+}
@@ -0,0 +1,104 @@
+/// Rolled I/H section. `height` runs across the flanges, `width` along them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SteelSection {
+    pub name: &'static str,
+    pub height: f64,
+    pub width: f64,
+    pub web: f64,
+    pub flange: f64,
+}
+
+const fn section(
+    name: &'static str,
+    height: f64,
+    width: f64,
+    web: f64,
+    flange: f64,
+) -> SteelSection {
+    SteelSection {
+        name,
+        height,
+        width,
+        web,
+        flange,
+    }
+}
+
+/// European IPE, HEA and HEB sections (mm), root radii omitted.
+pub const STEEL_SECTIONS: &[SteelSection] = &[
+    section("IPE100", 100.0, 55.0, 4.1, 5.7),
+    section("IPE120", 120.0, 64.0, 4.4, 6.3),
+    section("IPE140", 140.0, 73.0, 4.7, 6.9),
+    section("IPE160", 160.0, 82.0, 5.0, 7.4),
+    section("IPE180", 180.0, 91.0, 5.3, 8.0),
+    section("IPE200", 200.0, 100.0, 5.6, 8.5),
+    section("IPE220", 220.0, 110.0, 5.9, 9.2),
+    section("IPE240", 240.0, 120.0, 6.2, 9.8),
+    section("IPE270", 270.0, 135.0, 6.6, 10.2),
+    section("IPE300", 300.0, 150.0, 7.1, 10.7),
+    section("IPE330", 330.0, 160.0, 7.5, 11.5),
+    section("IPE360", 360.0, 170.0, 8.0, 12.7),
+    section("IPE400", 400.0, 180.0, 8.6, 13.5),
+    section("IPE450", 450.0, 190.0, 9.4, 14.6),
+    section("IPE500", 500.0, 200.0, 10.2, 16.0),
+    section("IPE550", 550.0, 210.0, 11.1, 17.2),
+    section("IPE600", 600.0, 220.0, 12.0, 19.0),
+    section("HEA100", 96.0, 100.0, 5.0, 8.0),
+    section("HEA120", 114.0, 120.0, 5.0, 8.0),
+    section("HEA140", 133.0, 140.0, 5.5, 8.5),
+    section("HEA160", 152.0, 160.0, 6.0, 9.0),
+    section("HEA180", 171.0, 180.0, 6.0, 9.5),
+    section("HEA200", 190.0, 200.0, 6.5, 10.0),
+    section("HEA220", 210.0, 220.0, 7.0, 11.0),
+    section("HEA240", 230.0, 240.0, 7.5, 12.0),
+    section("HEA260", 250.0, 260.0, 7.5, 12.5),
+    section("HEA280", 270.0, 280.0, 8.0, 13.0),
+    section("HEA300", 290.0, 300.0, 8.5, 14.0),
+    section("HEB100", 100.0, 100.0, 6.0, 10.0),
+    section("HEB120", 120.0, 120.0, 6.5, 11.0),
+    section("HEB140", 140.0, 140.0, 7.0, 12.0),
+    section("HEB160", 160.0, 160.0, 8.0, 13.0),
+    section("HEB180", 180.0, 180.0, 8.5, 14.0),
+    section("HEB200", 200.0, 200.0, 9.0, 15.0),
+    section("HEB220", 220.0, 220.0, 9.5, 16.0),
+    section("HEB240", 240.0, 240.0, 10.0, 17.0),
+    section("HEB260", 260.0, 260.0, 10.0, 17.5),
+    section("HEB280", 280.0, 280.0, 10.5, 18.0),
+    section("HEB300", 300.0, 300.0, 11.0, 19.0),
+];
+
+/// Looks a section up by designation, ignoring case and spaces (`"IPE 200"`, `"heb300"`).
+pub fn steel_section(name: &str) -> Option<SteelSection> {
+    let key: String = name
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_uppercase();
+    STEEL_SECTIONS
+        .iter()
+        .find(|section| section.name == key)
+        .copied()
+}
+
+impl SteelSection {
+    /// Outline in section coordinates `(across, up)`, centered on the axis.
+    pub(super) fn outline(&self) -> [(f64, f64); 12] {
+        let (half_w, half_h) = (self.width * 0.5, self.height * 0.5);
+        let half_web = self.web * 0.5;
+        let inner = half_h - self.flange;
+        [
+            (-half_w, -half_h),
+            (half_w, -half_h),
+            (half_w, -inner),
+            (half_web, -inner),
+            (half_web, inner),
+            (half_w, inner),
+            (half_w, half_h),
+            (-half_w, half_h),
+            (-half_w, inner),
+            (-half_web, inner),
+            (-half_web, -inner),
+            (-half_w, -inner),
+        ]
+    }
+}
@@ -0,0 +1,498 @@
+use anyhow::Result;
+use cryxtal_bim::{BimCategory, BimElement, ParameterValue};
+
+use super::wall_opening::rebuild_hosted_wall;
+
+const JOIN_TOLERANCE: f64 = 1.0;
+const PARALLEL_TOLERANCE: f64 = 1.0e-6;
+const MAX_EXTENSION_FACTOR: f64 = 4.0;
+
+const START_LEFT_KEY: &str = "StartExtensionLeft";
+const START_RIGHT_KEY: &str = "StartExtensionRight";
+const END_LEFT_KEY: &str = "EndExtensionLeft";
+const END_RIGHT_KEY: &str = "EndExtensionRight";
+const JOIN_STYLE_KEY: &str = "JoinStyle";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WallJoinStyle {
+    #[default]
+    Miter,
+    Butt,
+}
+
+impl WallJoinStyle {
+    pub fn label(self) -> &'static str {
+        match self {
+            WallJoinStyle::Miter => "Miter",
+            WallJoinStyle::Butt => "Butt",
+        }
+    }
+
+    /// Stores the corner style a wall prefers; a corner is butted if either wall asks for it.
+    pub fn apply_to(self, element: &mut BimElement) {
+        element.insert_parameter(
+            JOIN_STYLE_KEY,
+            ParameterValue::Text(self.label().to_string()),
+        );
+    }
+
+    fn of(element: &BimElement) -> Self {
+        match element.parameters.get(JOIN_STYLE_KEY) {
+            Some(ParameterValue::Text(value)) if value == "Butt" => WallJoinStyle::Butt,
+            _ => WallJoinStyle::Miter,
+        }
+    }
+}
+
+/// Signed extension of each wall face past its start/end point, measured along the wall axis.
+/// Left is the `+Y` side in wall-local coordinates; negative values trim the face.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WallEnds {
+    pub start_left: f64,
+    pub start_right: f64,
+    pub end_left: f64,
+    pub end_right: f64,
+}
+
+impl WallEnds {
+    pub fn is_square(&self) -> bool {
+        [
+            self.start_left,
+            self.start_right,
+            self.end_left,
+            self.end_right,
+        ]
+        .iter()
+        .all(|value| value.abs() <= 1.0e-9)
+    }
+
+    fn set(&mut self, end: WallEnd, side: f64, value: f64) {
+        match (end, side > 0.0) {
+            (WallEnd::Start, true) => self.start_left = value,
+            (WallEnd::Start, false) => self.start_right = value,
+            (WallEnd::End, true) => self.end_left = value,
+            (WallEnd::End, false) => self.end_right = value,
+        }
+    }
+}
+
+pub fn wall_ends(element: &BimElement) -> WallEnds {
+    let read = |key: &str| match element.parameters.get(key) {
+        Some(ParameterValue::Number(value)) => *value,
+        _ => 0.0,
+    };
+    WallEnds {
+        start_left: read(START_LEFT_KEY),
+        start_right: read(START_RIGHT_KEY),
+        end_left: read(END_LEFT_KEY),
+        end_right: read(END_RIGHT_KEY),
+    }
+}
+
+fn store_wall_ends(element: &mut BimElement, ends: WallEnds) {
+    if ends.is_square() {
+        for key in [START_LEFT_KEY, START_RIGHT_KEY, END_LEFT_KEY, END_RIGHT_KEY] {
+            element.parameters.remove(key);
+        }
+        return;
+    }
+    element.insert_parameter(START_LEFT_KEY, ParameterValue::Number(ends.start_left));
+    element.insert_parameter(START_RIGHT_KEY, ParameterValue::Number(ends.start_right));
+    element.insert_parameter(END_LEFT_KEY, ParameterValue::Number(ends.end_left));
+    element.insert_parameter(END_RIGHT_KEY, ParameterValue::Number(ends.end_right));
+}
+
+/// Detects walls sharing endpoints and regenerates their geometry with cleaned-up corners.
+/// Corners are mitered unless either wall's `JoinStyle` is `Butt`; tee junctions (an end
+/// landing on another wall's side) are always butted.
+pub fn join_walls(elements: &mut [BimElement]) -> Result<()> {
+    let walls: Vec<WallAxis> = elements
+        .iter()
+        .enumerate()
+        .filter(|(_, element)| element.category == BimCategory::Wall)
+        .filter_map(|(index, element)| WallAxis::from_element(index, element))
+        .collect();
+
+    let touching = |a_idx: usize, a_end: WallEnd| -> Vec<(usize, WallEnd)> {
+        let a = &walls[a_idx];
+        let point = a.point(a_end);
+        let mut found = Vec::new();
+        for (b_idx, b) in walls.iter().enumerate() {
+            if b_idx == a_idx || (b.z - a.z).abs() > JOIN_TOLERANCE {
+                continue;
+            }
+            for b_end in [WallEnd::Start, WallEnd::End] {
+                if distance(point, b.point(b_end)) <= JOIN_TOLERANCE {
+                    found.push((b_idx, b_end));
+                }
+            }
+        }
+        found
+    };
+
+    let mut ends = vec![WallEnds::default(); walls.len()];
+    for (a_idx, a) in walls.iter().enumerate() {
+        for a_end in [WallEnd::Start, WallEnd::End] {
+            match touching(a_idx, a_end).as_slice() {
+                [(b_idx, b_end)] => {
+                    if a_idx > *b_idx || touching(*b_idx, *b_end).len() != 1 {
+                        continue;
+                    }
+                    let b = &walls[*b_idx];
+                    let style = if a.style == WallJoinStyle::Butt || b.style == WallJoinStyle::Butt
+                    {
+                        WallJoinStyle::Butt
+                    } else {
+                        WallJoinStyle::Miter
+                    };
+                    if let Some(((a_side, a_points), (b_side, b_points))) =
+                        corner_join(a, a_end, b, *b_end, style)
+                    {
+                        apply_end(&mut ends[a_idx], a, a_end, a_side, a_points);
+                        apply_end(&mut ends[*b_idx], b, *b_end, b_side, b_points);
+                    }
+                }
+                [] => {
+                    let point = a.point(a_end);
+                    let host = walls.iter().enumerate().find(|(b_idx, b)| {
+                        *b_idx != a_idx
+                            && (b.z - a.z).abs() <= JOIN_TOLERANCE
+                            && b.contains_interior(point)
+                    });
+                    if let Some((_, b)) = host
+                        && let Some((side, points)) = tee_join(a, a_end, b)
+                    {
+                        apply_end(&mut ends[a_idx], a, a_end, side, points);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for (wall, next) in walls.iter().zip(ends) {
+        if wall_ends(&elements[wall.index]) == next {
+            continue;
+        }
+        store_wall_ends(&mut elements[wall.index], next);
+        rebuild_hosted_wall(elements, wall.index)?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WallEnd {
+    Start,
+    End,
+}
+
+type Point = (f64, f64);
+
+#[derive(Clone, Copy, Debug)]
+struct WallAxis {
+    index: usize,
+    start: Point,
+    end: Point,
+    z: f64,
+    thickness: f64,
+    length: f64,
+    dir: Point,
+    left: Point,
+    style: WallJoinStyle,
+}
+
+impl WallAxis {
+    fn from_element(index: usize, element: &BimElement) -> Option<Self> {
+        let read = |key: &str| match element.parameters.get(key) {
+            Some(ParameterValue::Number(value)) => Some(*value),
+            _ => None,
+        };
+        let start = (read("StartX")?, read("StartY")?);
+        let end = (read("EndX")?, read("EndY")?);
+        let thickness = read("Thickness")?;
+        let length = distance(start, end);
+        if length <= 1.0e-6 || thickness <= 0.0 {
+            return None;
+        }
+        let dir = ((end.0 - start.0) / length, (end.1 - start.1) / length);
+        Some(Self {
+            index,
+            start,
+            end,
+            z: read("StartZ")?,
+            thickness,
+            length,
+            dir,
+            left: (-dir.1, dir.0),
+            style: WallJoinStyle::of(element),
+        })
+    }
+
+    fn point(&self, end: WallEnd) -> Point {
+        match end {
+            WallEnd::Start => self.start,
+            WallEnd::End => self.end,
+        }
+    }
+
+    fn far_point(&self, end: WallEnd) -> Point {
+        match end {
+            WallEnd::Start => self.end,
+            WallEnd::End => self.start,
+        }
+    }
+
+    /// Face line on `side` (+1 left, -1 right) as point and direction.
+    fn face(&self, side: f64) -> (Point, Point) {
+        let offset = side * self.thickness * 0.5;
+        (
+            (
+                self.start.0 + self.left.0 * offset,
+                self.start.1 + self.left.1 * offset,
+            ),
+            self.dir,
+        )
+    }
+
+    fn side_of(&self, point: Point) -> f64 {
+        let rel = sub(point, self.start);
+        if dot(rel, self.left) >= 0.0 {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+
+    fn contains_interior(&self, point: Point) -> bool {
+        let rel = sub(point, self.start);
+        let along = dot(rel, self.dir);
+        let across = dot(rel, self.left).abs();
+        along > JOIN_TOLERANCE && along < self.length - JOIN_TOLERANCE && across <= JOIN_TOLERANCE
+    }
+
+    /// Extension of a face reaching `point` past the given end, along the wall axis.
+    fn extension_to(&self, end: WallEnd, point: Point) -> f64 {
+        let along = dot(sub(point, self.start), self.dir);
+        match end {
+            WallEnd::Start => -along,
+            WallEnd::End => along - self.length,
+        }
+    }
+}
+
+/// Face end points for one wall end: `(side, [point on that side, point on the opposite side])`.
+type EndValues = (f64, [Point; 2]);
+
+fn corner_join(
+    a: &WallAxis,
+    a_end: WallEnd,
+    b: &WallAxis,
+    b_end: WallEnd,
+    style: WallJoinStyle,
+) -> Option<(EndValues, EndValues)> {
+    if cross(a.dir, b.dir).abs() <= PARALLEL_TOLERANCE {
+        return None;
+    }
+    let a_inner = a.side_of(b.far_point(b_end));
+    let b_inner = b.side_of(a.far_point(a_end));
+
+    match style {
+        WallJoinStyle::Miter => {
+            let inner = intersect(a.face(a_inner), b.face(b_inner))?;
+            let outer = intersect(a.face(-a_inner), b.face(-b_inner))?;
+            Some(((a_inner, [inner, outer]), (b_inner, [inner, outer])))
+        }
+        WallJoinStyle::Butt => {
+            let b_outer = b.face(-b_inner);
+            let a_through = [
+                intersect(a.face(a_inner), b_outer)?,
+                intersect(a.face(-a_inner), b_outer)?,
+            ];
+            let a_face = a.face(a_inner);
+            let b_stop = [
+                intersect(b.face(b_inner), a_face)?,
+                intersect(b.face(-b_inner), a_face)?,
+            ];
+            Some(((a_inner, a_through), (b_inner, b_stop)))
+        }
+    }
+}
+
+fn tee_join(a: &WallAxis, a_end: WallEnd, host: &WallAxis) -> Option<EndValues> {
+    if cross(a.dir, host.dir).abs() <= PARALLEL_TOLERANCE {
+        return None;
+    }
+    let near_face = host.face(host.side_of(a.far_point(a_end)));
+    Some((
+        1.0,
+        [
+            intersect(a.face(1.0), near_face)?,
+            intersect(a.face(-1.0), near_face)?,
+        ],
+    ))
+}
+
+fn apply_end(ends: &mut WallEnds, wall: &WallAxis, end: WallEnd, side: f64, points: [Point; 2]) {
+    let inner = wall.extension_to(end, points[0]);
+    let outer = wall.extension_to(end, points[1]);
+    let limit = wall.thickness * MAX_EXTENSION_FACTOR;
+    if inner.abs() > limit || outer.abs() > limit || inner.min(outer) <= -wall.length {
+        return;
+    }
+    ends.set(end, side, inner);
+    ends.set(end, -side, outer);
+}
+
+fn intersect(first: (Point, Point), second: (Point, Point)) -> Option<Point> {
+    let (p, r) = first;
+    let (q, s) = second;
+    let denom = cross(r, s);
+    if denom.abs() <= PARALLEL_TOLERANCE {
+        return None;
+    }
+    let t = cross(sub(q, p), s) / denom;
+    Some((p.0 + r.0 * t, p.1 + r.1 * t))
+}
+
+fn sub(a: Point, b: Point) -> Point {
+    (a.0 - b.0, a.1 - b.1)
+}
+
+fn dot(a: Point, b: Point) -> f64 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+fn cross(a: Point, b: Point) -> f64 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    let d = sub(a, b);
+    dot(d, d).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::build_wall_between_points;
+    use anyhow::Context;
+    use cryxtal_topology::{Point3, solid_bounds};
+
+    fn wall(start: (f64, f64), end: (f64, f64)) -> Result<BimElement> {
+        build_wall_between_points(
+            Point3::new(start.0, start.1, 0.0),
+            Point3::new(end.0, end.1, 0.0),
+            200.0,
+            3000.0,
+            None,
+        )
+    }
+
+    fn assert_ends(element: &BimElement, expected: WallEnds) {
+        let ends = wall_ends(element);
+        let pairs = [
+            (ends.start_left, expected.start_left),
+            (ends.start_right, expected.start_right),
+            (ends.end_left, expected.end_left),
+            (ends.end_right, expected.end_right),
+        ];
+        assert!(
+            pairs
+                .iter()
+                .all(|(value, want)| (value - want).abs() < 1.0e-6),
+            "{ends:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn corners_are_mitered() -> Result<()> {
+        let mut walls = vec![
+            wall((0.0, 0.0), (3000.0, 0.0))?,
+            wall((3000.0, 0.0), (3000.0, 3000.0))?,
+        ];
+        join_walls(&mut walls)?;
+        assert_ends(
+            &walls[0],
+            WallEnds {
+                end_left: -100.0,
+                end_right: 100.0,
+                ..WallEnds::default()
+            },
+        );
+        assert_ends(
+            &walls[1],
+            WallEnds {
+                start_left: -100.0,
+                start_right: 100.0,
+                ..WallEnds::default()
+            },
+        );
+        // The outer faces meet at the outside corner.
+        let (_, max) = solid_bounds(&walls[0].geometry).context("wall has edges")?;
+        assert!((max.x - 3100.0).abs() < 1.0e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn butted_corners_run_one_wall_through() -> Result<()> {
+        let mut walls = vec![
+            wall((0.0, 0.0), (3000.0, 0.0))?,
+            wall((3000.0, 0.0), (3000.0, 3000.0))?,
+        ];
+        WallJoinStyle::Butt.apply_to(&mut walls[0]);
+        join_walls(&mut walls)?;
+        assert_ends(
+            &walls[0],
+            WallEnds {
+                end_left: 100.0,
+                end_right: 100.0,
+                ..WallEnds::default()
+            },
+        );
+        assert_ends(
+            &walls[1],
+            WallEnds {
+                start_left: -100.0,
+                start_right: -100.0,
+                ..WallEnds::default()
+            },
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tees_stop_at_the_near_face() -> Result<()> {
+        let mut walls = vec![
+            wall((0.0, 0.0), (3000.0, 0.0))?,
+            wall((1500.0, 2000.0), (1500.0, 0.0))?,
+        ];
+        join_walls(&mut walls)?;
+        assert!(wall_ends(&walls[0]).is_square());
+        assert_ends(
+            &walls[1],
+            WallEnds {
+                end_left: -100.0,
+                end_right: -100.0,
+                ..WallEnds::default()
+            },
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn moving_a_wall_away_squares_the_corner_again() -> Result<()> {
+        let mut walls = vec![
+            wall((0.0, 0.0), (3000.0, 0.0))?,
+            wall((3000.0, 0.0), (3000.0, 3000.0))?,
+        ];
+        join_walls(&mut walls)?;
+        assert!(!wall_ends(&walls[0]).is_square());
+
+        walls[1] = wall((5000.0, 0.0), (5000.0, 3000.0))?;
+        join_walls(&mut walls)?;
+        assert!(wall_ends(&walls[0]).is_square());
+        let (_, max) = solid_bounds(&walls[0].geometry).context("wall has edges")?;
+        assert!((max.x - 3000.0).abs() < 1.0e-6);
+        Ok(())
+    }
+}
@@ -1,25 +1,25 @@
 use anyhow::{Context, Result};
-use cryxtal_base::Guid;
-use cryxtal_bim::{BimCategory, BimElement, ParameterSet, ParameterValue};
+use cryxtal_base::OpContext;
+use cryxtal_bim::{
+    BimCategory, BimElement, Opening, OpeningPlacement, OpeningProfile, ParameterSet,
+    ParameterValue, cutting_openings, embedded_members, hosted_openings,
+};
+use cryxtal_shapeops::{DEFAULT_SHAPEOPS_TOLERANCE, difference_many_with};
 use cryxtal_topology::{Point3, Solid, SolidBuilder, Vector3, Wire};
 use truck_modeling::{builder, Rad};
 
-#[derive(Clone, Copy, Debug)]
-pub struct OpeningData {
-    pub index: usize,
-    pub width: f64,
-    pub height: f64,
-    pub center_x: f64,
-    pub center_z: f64,
-}
+use super::cut_through::cut_through;
+use super::profile::read_axis;
+use super::slab::slab_opening_solid;
+use super::wall_join::{WallEnds, wall_ends};
 
 #[derive(Clone, Copy, Debug)]
-struct WallData {
-    start: Point3,
-    length: f64,
-    thickness: f64,
-    height: f64,
-    angle: f64,
+pub(super) struct WallData {
+    pub(super) start: Point3,
+    pub(super) length: f64,
+    pub(super) thickness: f64,
+    pub(super) height: f64,
+    pub(super) angle: f64,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -29,15 +29,62 @@ struct OpeningRect {
     min_z: f64,
     max_z: f64,
     cut_bottom: bool,
+    cut_start: bool,
+    cut_end: bool,
+}
+
+const OPENING_MODE_KEY: &str = "OpeningMode";
+
+/// How a wall's openings are cut into it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WallOpeningMode {
+    /// Openings become holes and notches in the outline the wall is swept from.
+    #[default]
+    Outline,
+    /// Opening boxes are subtracted from the plain wall, so openings may also run out at
+    /// the wall ends.
+    Boolean,
+}
+
+impl WallOpeningMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            WallOpeningMode::Outline => "Outline",
+            WallOpeningMode::Boolean => "Boolean",
+        }
+    }
+
+    /// Stores how openings are cut into the wall.
+    pub fn apply_to(self, element: &mut BimElement) {
+        element.insert_parameter(
+            OPENING_MODE_KEY,
+            ParameterValue::Text(self.label().to_string()),
+        );
+    }
+
+    fn of(element: &BimElement) -> Self {
+        match element.parameters.get(OPENING_MODE_KEY) {
+            Some(ParameterValue::Text(value)) if value == "Boolean" => WallOpeningMode::Boolean,
+            _ => WallOpeningMode::Outline,
+        }
+    }
+
+    /// Least distance kept between an opening and the wall ends.
+    fn end_margin(self, margin: f64) -> f64 {
+        match self {
+            WallOpeningMode::Outline => margin,
+            WallOpeningMode::Boolean => 0.0,
+        }
+    }
 }
 
 pub fn apply_wall_opening(
-    element: &mut BimElement,
+    host: &BimElement,
     world_center: Point3,
     opening_width: f64,
     opening_height: f64,
-) -> Result<OpeningData> {
-    if element.category != BimCategory::Wall {
+) -> Result<Opening> {
+    if host.category != BimCategory::Wall {
         anyhow::bail!("opening can only be applied to wall elements");
     }
     if opening_width <= 0.0 {
@@ -47,16 +94,17 @@ pub fn apply_wall_opening(
         anyhow::bail!("opening height must be > 0");
     }
 
-    let wall = wall_data(element)?;
+    let wall = wall_data(host)?;
     let margin = opening_margin(wall.thickness);
-    if wall.length <= margin * 2.0 {
+    let end_margin = WallOpeningMode::of(host).end_margin(margin);
+    if wall.length <= end_margin * 2.0 {
         anyhow::bail!("wall length is too small for opening");
     }
     if wall.height <= margin * 2.0 {
         anyhow::bail!("wall height is too small for opening");
     }
 
-    let max_width = (wall.length - margin * 2.0).max(0.0);
+    let max_width = (wall.length - end_margin * 2.0).max(0.0);
     let max_height = (wall.height - margin * 2.0).max(0.0);
     let opening_width = opening_width.min(max_width);
     let opening_height = opening_height.min(max_height);
@@ -67,163 +115,198 @@ pub fn apply_wall_opening(
     let local = world_to_wall_local(world_center, wall.start, wall.angle);
     let half_width = opening_width * 0.5;
     let half_height = opening_height * 0.5;
-    let center_x = local
-        .x
-        .clamp(half_width + margin, wall.length - half_width - margin);
+    let center_x = local.x.clamp(
+        half_width + end_margin,
+        wall.length - half_width - end_margin,
+    );
     let min_center_z = half_height;
     let max_center_z = (wall.height - half_height - margin).max(min_center_z);
     let center_z = local.z.clamp(min_center_z, max_center_z);
 
-    let next_index = match element.parameters.get("OpeningCount") {
-        Some(ParameterValue::Integer(value)) if *value >= 0 => (*value as usize) + 1,
-        _ => 1,
-    };
-    element.insert_parameter(
-        "OpeningCount",
-        ParameterValue::Integer(next_index as i64),
-    );
-    let prefix = format!("Opening{next_index}");
-    element.insert_parameter(format!("{prefix}Width"), ParameterValue::Number(opening_width));
-    element.insert_parameter(format!("{prefix}Height"), ParameterValue::Number(opening_height));
-    element.insert_parameter(format!("{prefix}CenterX"), ParameterValue::Number(center_x));
-    element.insert_parameter(format!("{prefix}CenterZ"), ParameterValue::Number(center_z));
-
-    rebuild_wall_from_openings(element)?;
-    read_opening_from_wall(element, next_index)
+    Ok(Opening::new(
+        host.guid,
+        OpeningProfile {
+            width: opening_width,
+            height: opening_height,
+        },
+        OpeningPlacement {
+            center_x,
+            center_y: 0.0,
+            center_z,
+        },
+    ))
 }
 
-pub fn rebuild_wall_from_openings(element: &mut BimElement) -> Result<()> {
+/// Rebuilds the wall solid around `openings`, clamping each one into the wall face in place.
+/// `members[i]` are the lintels and sills set into the wall at `openings[i]`, cut out with it.
+/// Joined walls, walls in [`WallOpeningMode::Boolean`] and walls with members are cut with
+/// booleans.
+fn rebuild_wall_from_openings(
+    element: &mut BimElement,
+    openings: &mut [Opening],
+    members: &[Vec<OpeningRect>],
+    ctx: &OpContext,
+) -> Result<()> {
     if element.category != BimCategory::Wall {
         anyhow::bail!("openings can only be applied to wall elements");
     }
     let wall = wall_data(element)?;
     let margin = opening_margin(wall.thickness);
+    let mode = WallOpeningMode::of(element);
 
-    let openings = collect_openings(element, wall.length, wall.height, margin)?;
-    ensure_openings_do_not_overlap(&openings)?;
-    element.geometry = build_wall_with_openings(
-        wall.start,
+    let rects = clamp_openings(
+        openings,
         wall.length,
-        wall.thickness,
         wall.height,
-        wall.angle,
-        &openings,
+        mode.end_margin(margin),
+        margin,
     )?;
+    ensure_openings_do_not_overlap(&rects)?;
+    let ends = wall_ends(element);
+    let embedded = members.iter().any(|members| !members.is_empty());
+    element.geometry = if ends.is_square() && mode == WallOpeningMode::Outline && !embedded {
+        build_wall_with_openings(
+            wall.start,
+            wall.length,
+            wall.thickness,
+            wall.height,
+            wall.angle,
+            &rects,
+        )?
+    } else {
+        build_wall_by_difference(&wall, &ends, &rects, members, margin, ctx)?
+    };
 
     Ok(())
 }
 
-pub fn read_opening_from_wall(element: &BimElement, index: usize) -> Result<OpeningData> {
-    let prefix = format!("Opening{index}");
-    let width = read_number(element, &format!("{prefix}Width"))?;
-    let height = read_number(element, &format!("{prefix}Height"))?;
-    let center_x = read_number(element, &format!("{prefix}CenterX"))?;
-    let center_z = read_number(element, &format!("{prefix}CenterZ"))?;
-    Ok(OpeningData {
-        index,
-        width,
-        height,
-        center_x,
-        center_z,
-    })
+/// Resolves the openings hosted by `elements[host_index]` and regenerates the wall and every
+/// opening element against it; openings hosted elsewhere that cut through the wall are
+/// subtracted as well, and so are the lintels and sills set into the wall over its openings.
+/// Nothing is modified when the rebuild fails, or when neither the wall's parameters nor its
+/// openings changed since it was last built.
+pub fn rebuild_hosted_wall(elements: &mut [BimElement], host_index: usize) -> Result<()> {
+    rebuild_hosted_wall_with(elements, host_index, &OpContext::default())
 }
 
-pub fn build_opening_element(host: &BimElement, data: &OpeningData) -> Result<BimElement> {
-    if host.category != BimCategory::Wall {
-        anyhow::bail!("host element is not a wall");
+/// Like [`rebuild_hosted_wall`], reporting progress and honouring cancellation through `ctx`.
+pub fn rebuild_hosted_wall_with(
+    elements: &mut [BimElement],
+    host_index: usize,
+    ctx: &OpContext,
+) -> Result<()> {
+    let Some(host) = elements.get(host_index) else {
+        anyhow::bail!("host element {host_index} does not exist");
+    };
+    let hosted = hosted_openings(elements, host.guid);
+    let cutters = cutting_openings(elements, host.guid);
+    let embedded = embedded_members(elements, host.guid);
+    let dependencies: Vec<usize> = hosted
+        .iter()
+        .map(|(idx, _)| *idx)
+        .chain(cutters.iter().copied())
+        .chain(embedded.iter().copied())
+        .collect();
+    if host.is_up_to_date(dependencies.iter().map(|idx| &elements[*idx])) {
+        return Ok(());
     }
-    let wall = wall_data(host)?;
-    let solid = build_opening_solid(&wall, data)?;
-
-    let mut parameters = ParameterSet::new();
-    parameters.insert("Width".to_string(), ParameterValue::Number(data.width));
-    parameters.insert("Height".to_string(), ParameterValue::Number(data.height));
-    parameters.insert("CenterX".to_string(), ParameterValue::Number(data.center_x));
-    parameters.insert("CenterZ".to_string(), ParameterValue::Number(data.center_z));
-    parameters.insert(
-        "OpeningIndex".to_string(),
-        ParameterValue::Integer(data.index as i64),
-    );
-    parameters.insert(
-        "HostGuid".to_string(),
-        ParameterValue::Text(host.guid.to_string()),
-    );
-    parameters.insert(
-        "HostName".to_string(),
-        ParameterValue::Text(host.name.clone()),
-    );
-    parameters.insert(
-        "Thickness".to_string(),
-        ParameterValue::Number(wall.thickness),
-    );
-
-    let name = format!("Opening {}", data.index);
-    Ok(BimElement::new(
-        Guid::new(),
-        name,
-        BimCategory::Opening,
-        parameters,
-        solid,
-    ))
+    let data = wall_data(host)?;
+    let members = hosted
+        .iter()
+        .map(|(idx, _)| {
+            embedded
+                .iter()
+                .map(|member| &elements[*member])
+                .filter(|member| member.host == Some(elements[*idx].guid))
+                .map(|member| member_rect(&data, member))
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut openings: Vec<Opening> = hosted.iter().map(|(_, opening)| *opening).collect();
+    let mut wall = host.clone();
+    ctx.span(0.0, 0.5, |ctx| {
+        rebuild_wall_from_openings(&mut wall, &mut openings, &members, ctx)
+    })?;
+    ctx.span(0.5, 1.0, |ctx| {
+        cut_through(&mut wall, cutters.iter().map(|idx| &elements[*idx]), ctx)
+    })?;
+
+    let data = wall_data(&wall)?;
+    let solids = openings
+        .iter()
+        .map(|opening| build_opening_solid(&data, opening))
+        .collect::<Result<Vec<_>>>()?;
+    for ((&(idx, _), opening), solid) in hosted.iter().zip(openings).zip(solids) {
+        let element = &mut elements[idx];
+        element.opening = Some(opening);
+        element.geometry = solid;
+        element.regen_hash = Some(element.parameter_hash());
+    }
+    wall.regen_hash = Some(wall.regen_key(dependencies.iter().map(|idx| &elements[*idx])));
+    elements[host_index] = wall;
+    Ok(())
 }
 
-pub fn sync_opening_from_wall(opening: &mut BimElement, host: &BimElement) -> Result<()> {
-    if opening.category != BimCategory::Opening {
-        anyhow::bail!("syncing requires an opening element");
+/// Openings among `openings` that do not fit `wall` as they are: rebuilding the wall would
+/// move or shrink them into its face. Openings hosted by other elements are ignored.
+pub fn displaced_openings(wall: &BimElement, openings: &[Opening]) -> Result<Vec<Opening>> {
+    let data = wall_data(wall)?;
+    let margin = opening_margin(data.thickness);
+    let end_margin = WallOpeningMode::of(wall).end_margin(margin);
+    let mut displaced = Vec::new();
+    for opening in openings.iter().filter(|opening| opening.host == wall.guid) {
+        let mut clamped = [*opening];
+        let fits = clamp_openings(&mut clamped, data.length, data.height, end_margin, margin)
+            .is_ok_and(|_| clamped[0] == *opening);
+        if !fits {
+            displaced.push(*opening);
+        }
     }
-    if host.category != BimCategory::Wall {
-        anyhow::bail!("syncing requires a wall host");
+    Ok(displaced)
+}
+
+pub fn build_opening_element(
+    host: &BimElement,
+    opening: &Opening,
+    number: usize,
+) -> Result<BimElement> {
+    if opening.host != host.guid {
+        anyhow::bail!("opening is hosted by another element");
     }
-    let index = read_opening_index(opening)?;
-    let data = read_opening_from_wall(host, index)?;
-    let wall = wall_data(host)?;
-    update_opening_parameters(opening, host, wall.thickness, &data);
-    opening.geometry = build_opening_solid(&wall, &data)?;
-    Ok(())
+    let solid = match host.category {
+        BimCategory::Wall => build_opening_solid(&wall_data(host)?, opening)?,
+        BimCategory::Slab => slab_opening_solid(host, opening)?,
+        _ => anyhow::bail!("host element is not a wall or slab"),
+    };
+    Ok(BimElement::opening(
+        *opening,
+        format!("Opening {number}"),
+        ParameterSet::new(),
+        solid,
+    ))
 }
 
-pub fn opening_index_at_point(element: &BimElement, world_point: Point3) -> Result<Option<usize>> {
-    if element.category != BimCategory::Wall {
+/// Index of the element among `hosted` whose opening contains `world_point` on the wall face.
+pub fn opening_at_point(
+    host: &BimElement,
+    hosted: &[(usize, Opening)],
+    world_point: Point3,
+) -> Result<Option<usize>> {
+    if host.category != BimCategory::Wall {
         anyhow::bail!("opening lookup expects a wall element");
     }
-    let wall = wall_data(element)?;
+    let wall = wall_data(host)?;
     let local = world_to_wall_local(world_point, wall.start, wall.angle);
-    let count = match element.parameters.get("OpeningCount") {
-        Some(ParameterValue::Integer(value)) if *value > 0 => *value as usize,
-        _ => 0,
-    };
-    if count == 0 {
-        return Ok(None);
-    }
 
     let eps = 1.0e-4;
-    for index in 1..=count {
-        let prefix = format!("Opening{index}");
-        let width_key = format!("{prefix}Width");
-        let height_key = format!("{prefix}Height");
-        let center_x_key = format!("{prefix}CenterX");
-        let center_z_key = format!("{prefix}CenterZ");
-
-        let width = match read_number(element, &width_key) {
-            Ok(value) => value,
-            Err(_) => continue,
-        };
-        let height = match read_number(element, &height_key) {
-            Ok(value) => value,
-            Err(_) => continue,
-        };
-        let center_x = match read_number(element, &center_x_key) {
-            Ok(value) => value,
-            Err(_) => continue,
-        };
-        let center_z = match read_number(element, &center_z_key) {
-            Ok(value) => value,
-            Err(_) => continue,
-        };
+    for (idx, opening) in hosted {
+        let OpeningProfile { width, height } = opening.profile;
         if width <= 0.0 || height <= 0.0 {
             continue;
         }
-
+        let OpeningPlacement {
+            center_x, center_z, ..
+        } = opening.placement;
         let half_width = width * 0.5;
         let half_height = height * 0.5;
         let min_x = center_x - half_width - eps;
@@ -231,46 +314,14 @@ pub fn opening_index_at_point(element: &BimElement, world_point: Point3) -> Resu
         let min_z = center_z - half_height - eps;
         let max_z = center_z + half_height + eps;
         if local.x >= min_x && local.x <= max_x && local.z >= min_z && local.z <= max_z {
-            return Ok(Some(index));
+            return Ok(Some(*idx));
         }
     }
 
     Ok(None)
 }
 
-fn update_opening_parameters(
-    opening: &mut BimElement,
-    host: &BimElement,
-    thickness: f64,
-    data: &OpeningData,
-) {
-    opening.insert_parameter("Width", ParameterValue::Number(data.width));
-    opening.insert_parameter("Height", ParameterValue::Number(data.height));
-    opening.insert_parameter("CenterX", ParameterValue::Number(data.center_x));
-    opening.insert_parameter("CenterZ", ParameterValue::Number(data.center_z));
-    opening.insert_parameter(
-        "OpeningIndex",
-        ParameterValue::Integer(data.index as i64),
-    );
-    opening.insert_parameter(
-        "HostGuid",
-        ParameterValue::Text(host.guid.to_string()),
-    );
-    opening.insert_parameter(
-        "HostName",
-        ParameterValue::Text(host.name.clone()),
-    );
-    opening.insert_parameter("Thickness", ParameterValue::Number(thickness));
-}
-
-fn read_opening_index(opening: &BimElement) -> Result<usize> {
-    match opening.parameters.get("OpeningIndex") {
-        Some(ParameterValue::Integer(value)) if *value > 0 => Ok(*value as usize),
-        _ => anyhow::bail!("opening index is missing"),
-    }
-}
-
-fn wall_data(element: &BimElement) -> Result<WallData> {
+pub(super) fn wall_data(element: &BimElement) -> Result<WallData> {
     let start = Point3::new(
         read_number(element, "StartX")?,
         read_number(element, "StartY")?,
@@ -309,19 +360,23 @@ fn opening_margin(thickness: f64) -> f64 {
     (thickness * 0.02).max(1.0)
 }
 
-fn build_opening_solid(wall: &WallData, data: &OpeningData) -> Result<Solid> {
-    let half_width = data.width * 0.5;
-    let half_height = data.height * 0.5;
+fn build_opening_solid(wall: &WallData, opening: &Opening) -> Result<Solid> {
+    let OpeningProfile { width, height } = opening.profile;
+    let OpeningPlacement {
+        center_x, center_z, ..
+    } = opening.placement;
+    let half_width = width * 0.5;
+    let half_height = height * 0.5;
     let highlight_offset = opening_margin(wall.thickness);
     let visual_thickness = wall.thickness + highlight_offset * 2.0;
-    let mut opening = SolidBuilder::box_solid(data.width, visual_thickness, data.height)
+    let mut opening = SolidBuilder::box_solid(width, visual_thickness, height)
         .context("failed to build opening solid")?;
     opening = builder::translated(
         &opening,
         Vector3::new(
-            data.center_x - half_width,
+            center_x - half_width,
             -visual_thickness * 0.5,
-            data.center_z - half_height,
+            center_z - half_height,
         ),
     );
     opening = builder::rotated(
@@ -336,77 +391,57 @@ fn build_opening_solid(wall: &WallData, data: &OpeningData) -> Result<Solid> {
     ))
 }
 
-fn collect_openings(
-    element: &mut BimElement,
+fn clamp_openings(
+    openings: &mut [Opening],
     length: f64,
     wall_height: f64,
+    end_margin: f64,
     margin: f64,
 ) -> Result<Vec<OpeningRect>> {
-    let count = match element.parameters.get("OpeningCount") {
-        Some(ParameterValue::Integer(value)) if *value > 0 => *value as usize,
-        _ => 0,
-    };
-
-    let mut openings = Vec::with_capacity(count);
-    let mut updates = Vec::new();
-
-    for index in 1..=count {
-        let prefix = format!("Opening{index}");
-        let width_key = format!("{prefix}Width");
-        let height_key = format!("{prefix}Height");
-        let center_x_key = format!("{prefix}CenterX");
-        let center_z_key = format!("{prefix}CenterZ");
-
-        let orig_width = read_number(element, &width_key)?;
-        let orig_height = read_number(element, &height_key)?;
-        let center_x = read_number(element, &center_x_key)?;
-        let center_z = read_number(element, &center_z_key)?;
-
-    let max_width = (length - margin * 2.0).max(0.0);
+    let max_width = (length - end_margin * 2.0).max(0.0);
     let max_height = (wall_height - margin * 2.0).max(0.0);
-        let width = orig_width.min(max_width);
-        let height = orig_height.min(max_height);
+
+    let mut rects = Vec::with_capacity(openings.len());
+    for (idx, opening) in openings.iter_mut().enumerate() {
+        let width = opening.profile.width.min(max_width);
+        let height = opening.profile.height.min(max_height);
         if width <= 0.0 || height <= 0.0 {
-            anyhow::bail!("opening {index} is too large for wall");
+            anyhow::bail!("opening {} is too large for wall", idx + 1);
         }
 
         let half_width = width * 0.5;
         let half_height = height * 0.5;
-        let adj_center_x =
-            center_x.clamp(half_width + margin, length - half_width - margin);
+        let center_x = opening
+            .placement
+            .center_x
+            .clamp(half_width + end_margin, length - half_width - end_margin);
         let min_center_z = half_height;
         let max_center_z = (wall_height - half_height - margin).max(min_center_z);
-        let adj_center_z = center_z.clamp(min_center_z, max_center_z);
+        let center_z = opening.placement.center_z.clamp(min_center_z, max_center_z);
 
-        if (width - orig_width).abs() > f64::EPSILON {
-            updates.push((width_key, ParameterValue::Number(width)));
-        }
-        if (height - orig_height).abs() > f64::EPSILON {
-            updates.push((height_key, ParameterValue::Number(height)));
-        }
-        if (adj_center_x - center_x).abs() > f64::EPSILON {
-            updates.push((center_x_key, ParameterValue::Number(adj_center_x)));
-        }
-        if (adj_center_z - center_z).abs() > f64::EPSILON {
-            updates.push((center_z_key, ParameterValue::Number(adj_center_z)));
-        }
+        opening.profile = OpeningProfile { width, height };
+        opening.placement = OpeningPlacement {
+            center_x,
+            center_y: 0.0,
+            center_z,
+        };
 
-        let min_z = (adj_center_z - half_height).max(0.0);
-        let max_z = adj_center_z + half_height;
-        openings.push(OpeningRect {
-            min_x: adj_center_x - half_width,
-            max_x: adj_center_x + half_width,
+        let min_x = center_x - half_width;
+        let max_x = center_x + half_width;
+        let min_z = (center_z - half_height).max(0.0);
+        let max_z = center_z + half_height;
+        rects.push(OpeningRect {
+            min_x,
+            max_x,
             min_z,
             max_z,
             cut_bottom: min_z <= 1.0e-6,
+            cut_start: min_x <= 1.0e-6,
+            cut_end: max_x >= length - 1.0e-6,
         });
     }
 
-    for (key, value) in updates {
-        element.insert_parameter(key, value);
-    }
-
-    Ok(openings)
+    Ok(rects)
 }
 
 fn ensure_openings_do_not_overlap(openings: &[OpeningRect]) -> Result<()> {
@@ -472,6 +507,151 @@ fn build_wall_with_openings(
     ))
 }
 
+/// Sweeps the wall footprint, trimmed or extended by `ends`, and subtracts a box for every
+/// opening. Boxes at the bottom or the wall ends reach past the wall so the cut is clean. An
+/// opening with `members` is cut as one prism outlining it together with the lintel and sill
+/// bordering it; members apart from it get boxes of their own.
+fn build_wall_by_difference(
+    wall: &WallData,
+    ends: &WallEnds,
+    openings: &[OpeningRect],
+    members: &[Vec<OpeningRect>],
+    margin: f64,
+    ctx: &OpContext,
+) -> Result<Solid> {
+    let half = wall.thickness * 0.5;
+    let footprint = [
+        (-ends.start_right, -half),
+        (wall.length + ends.end_right, -half),
+        (wall.length + ends.end_left, half),
+        (-ends.start_left, half),
+    ];
+    if footprint[1].0 <= footprint[0].0 || footprint[2].0 <= footprint[3].0 {
+        anyhow::bail!("wall join trims the wall away");
+    }
+
+    let vertices = footprint.map(|(x, y)| builder::vertex(Point3::new(x, y, 0.0)));
+    let edges = vec![
+        builder::line(&vertices[0], &vertices[1]),
+        builder::line(&vertices[1], &vertices[2]),
+        builder::line(&vertices[2], &vertices[3]),
+        builder::line(&vertices[3], &vertices[0]),
+    ];
+    let face = builder::try_attach_plane(vec![Wire::from(edges)])
+        .context("failed to build wall footprint")?;
+    let solid = builder::tsweep(&face, Vector3::unit_z() * wall.height);
+
+    let depth = wall.thickness + margin * 2.0;
+    let mut cutters = Vec::with_capacity(openings.len());
+    for (idx, opening) in openings.iter().enumerate() {
+        let min_x = if opening.cut_start {
+            -ends.start_left.max(ends.start_right).max(0.0) - margin
+        } else {
+            opening.min_x
+        };
+        let max_x = if opening.cut_end {
+            wall.length + ends.end_left.max(ends.end_right).max(0.0) + margin
+        } else {
+            opening.max_x
+        };
+        let min_z = if opening.cut_bottom {
+            -margin
+        } else {
+            opening.min_z
+        };
+        let opening = OpeningRect {
+            min_x,
+            max_x,
+            min_z,
+            ..*opening
+        };
+        let (bordering, apart): (Vec<OpeningRect>, Vec<OpeningRect>) = members
+            .get(idx)
+            .into_iter()
+            .flatten()
+            .partition(|member| borders(&opening, member));
+        let mut stack = vec![opening];
+        stack.extend(bordering);
+        stack.sort_by(|a, b| a.min_z.total_cmp(&b.min_z));
+        cutters.push(stacked_cutter(&stack, depth)?);
+        for member in apart {
+            cutters.push(stacked_cutter(&[member], depth)?);
+        }
+    }
+    let solid = difference_many_with(&solid, &cutters, DEFAULT_SHAPEOPS_TOLERANCE, ctx)
+        .context("failed to cut openings from wall")?;
+
+    let solid = builder::rotated(
+        &solid,
+        Point3::new(0.0, 0.0, 0.0),
+        Vector3::unit_z(),
+        Rad(wall.angle),
+    );
+    Ok(builder::translated(
+        &solid,
+        Vector3::new(wall.start.x, wall.start.y, wall.start.z),
+    ))
+}
+
+/// Whether `member` sits right on top of or under `opening`, overlapping it along the wall.
+fn borders(opening: &OpeningRect, member: &OpeningRect) -> bool {
+    let eps = 1.0e-6;
+    let touches =
+        (member.min_z - opening.max_z).abs() < eps || (member.max_z - opening.min_z).abs() < eps;
+    touches && member.min_x < opening.max_x && opening.min_x < member.max_x
+}
+
+/// Prism `depth` thick through the wall over the outline of `rects`, stacked bottom to top.
+fn stacked_cutter(rects: &[OpeningRect], depth: f64) -> Result<Solid> {
+    let left = rects
+        .iter()
+        .flat_map(|rect| [(rect.min_x, rect.min_z), (rect.min_x, rect.max_z)]);
+    let right = rects
+        .iter()
+        .rev()
+        .flat_map(|rect| [(rect.max_x, rect.max_z), (rect.max_x, rect.min_z)]);
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    for point in left.chain(right) {
+        let repeated = points.last().is_some_and(|last: &(f64, f64)| {
+            (last.0 - point.0).abs() < 1.0e-9 && (last.1 - point.1).abs() < 1.0e-9
+        });
+        if !repeated {
+            points.push(point);
+        }
+    }
+    let face = builder::try_attach_plane(vec![polygon_wire(&points)])
+        .context("failed to build opening cutter")?;
+    let cutter = builder::tsweep(&face, Vector3::unit_y() * depth);
+    Ok(builder::translated(
+        &cutter,
+        Vector3::new(0.0, -depth * 0.5, 0.0),
+    ))
+}
+
+/// Extent in the wall face of a lintel or sill set into `wall`, read from its axis and section
+/// depth.
+fn member_rect(wall: &WallData, member: &BimElement) -> Result<OpeningRect> {
+    let (start, end) = read_axis(&member.parameters)?;
+    let (start, end) = (
+        world_to_wall_local(start, wall.start, wall.angle),
+        world_to_wall_local(end, wall.start, wall.angle),
+    );
+    let Some(ParameterValue::Number(depth)) = member.parameters.get("ProfileDepth") else {
+        anyhow::bail!("{} has no section depth", member.name);
+    };
+    let half_depth = depth * 0.5;
+    let z = (start.z + end.z) * 0.5;
+    Ok(OpeningRect {
+        min_x: start.x.min(end.x),
+        max_x: start.x.max(end.x),
+        min_z: z - half_depth,
+        max_z: z + half_depth,
+        cut_bottom: false,
+        cut_start: false,
+        cut_end: false,
+    })
+}
+
 fn outline_with_bottom_cuts(length: f64, wall_height: f64, cuts: &[OpeningRect]) -> Wire {
     let mut cuts = cuts.to_vec();
     cuts.sort_by(|a, b| {
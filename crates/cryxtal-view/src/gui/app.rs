@@ -1,42 +1,128 @@
 use anyhow::Result;
-use cryxtal_bim::{BimCategory, BimElement, ParameterValue};
-use cryxtal_io::{DEFAULT_TESSELLATION_TOLERANCE, triangulate_solid};
+use cryxtal_bim::{
+    BimCategory, BimElement, BimModel, History, Layer, ModelCommand, Opening, ParameterValue,
+    WeldAnnotation,
+};
+use cryxtal_base::Guid;
+use cryxtal_io::{geometry_key, triangulate_many};
 use cryxtal_topology::Point3;
 use egui::{self, FontId};
 use egui_wgpu::{RenderState, RendererOptions, WgpuConfiguration, WgpuSetup, WgpuSetupCreateNew};
 use egui_wgpu::winit::Painter;
 use egui_winit::State as EguiWinitState;
+use std::collections::{HashMap, HashSet};
 use std::num::NonZeroU32;
-use std::sync::Arc;
-use std::time::Instant;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::{sync::mpsc, thread};
 use truck_polymesh::PolygonMesh;
+use web_time::Instant;
 use winit::dpi::LogicalSize;
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
 
-use crate::elements::build_wall_between_points;
+use crate::elements::{LintelRules, WallJoinStyle, WallOpeningMode, build_wall_between_points};
 use crate::viewer::{
     Align2 as ViewerAlign2, Color32, Modifiers, OverlayPainter, Point2, Rect, Stroke, Vec2,
     GizmoMode, GizmoRenderer, ViewMode, ViewerInput, ViewerMesh, ViewerState, TruckRenderer,
+    PolarTracking, TrackingHit,
 };
-use super::layers::Layer;
+use super::crash::{PendingCrash, install_panic_hook, pending_crash};
+use super::icon::app_icon;
+use super::gpu::{GpuDiagnostics, SharedDiagnostics, adapter_selector, power_preference};
 use super::model::{ModelInfo, format_point, merge_bounds, mesh_bounds};
-use super::params::WallParams;
+use super::params::{SlabParams, WallParams};
+use super::prefs::Preferences;
+use self::background::BackgroundEdit;
+use self::commands::{AppCommand, Clipboard};
+use self::context_menu::ContextTarget;
+use self::datums::DatumState;
 use self::hover_outline::paint_hover_outline;
+use self::hud::{FrameStats, paint_hud};
+use self::issues::IssuesWindow;
+use self::legend::paint_legend;
+use self::lengths::{length_value, parameter_text};
+#[cfg(feature = "live-link")]
+use self::live_link::LiveLinkState;
+use self::measure::Measurement;
+use self::notifications::Toast;
+use self::project_file::{MeshCache, ProjectDialog};
+use self::opening_conflict::OpeningConflict;
 use self::opening_params::WallOpeningParams;
+use self::parameter_history::{ParameterHistory, PropertiesTab};
+use self::phases::PhaseState;
+use self::point_cloud::PointClouds;
 use self::rebar_params::RebarParams;
 use self::rebar_wireframe::tune_rebar_wireframe;
-
+use self::read_only::EditMode;
+use self::reference::ReferenceTool;
+use self::scan_deviation::{ScanDeviation, paint_scan_deviation};
+use self::script_console::ScriptConsole;
+use self::parameter_rename::ParameterRenameTool;
+use self::search::ElementSearch;
+use self::status_bar::CursorStatus;
+use self::storey_copy::StoreyCopy;
+use self::view_filters::ViewFilterState;
+use self::viewer_window::ViewerWindow;
+use self::welds::default_weld;
+
+mod background;
+mod commands;
+mod context_menu;
+mod coordinate_entry;
+mod crash_recovery;
+mod datums;
+mod element_types;
+mod group;
+mod history;
 mod hover;
 mod hover_outline;
+mod hud;
+mod issues;
+mod journal;
+mod legend;
+mod lengths;
+mod lintels;
+#[cfg(feature = "live-link")]
+mod live_link;
+mod measure;
+mod notifications;
 mod opening;
+mod opening_conflict;
 mod opening_params;
+mod parameter_history;
+mod parameter_rename;
+mod parameter_sheet;
+mod phases;
+mod point_cloud;
+mod preferences;
+mod project_file;
 mod rebar;
 mod rebar_params;
+mod rebar_set;
 mod rebar_wireframe;
+mod read_only;
+mod reference;
+mod scan_deviation;
+mod script_console;
+mod search;
+mod similar;
+mod slab;
+mod status_bar;
+mod storey_copy;
+mod tracking;
+mod view_filters;
+mod viewer_window;
+mod welds;
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+mod web;
+mod work_plane;
+
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub(super) use self::web::run_web;
 
 const SELECTION_DRAG_THRESHOLD: f32 = 4.0;
+const APP_TITLE: &str = "CryXtal Castor";
 
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -45,6 +131,8 @@ enum ToolMode {
     CreateWall,
     CreateOpening,
     CreateRebar,
+    CreateReference,
+    CreateSlab,
 }
 
 impl Default for ToolMode {
@@ -68,33 +156,69 @@ struct InputState {
     key_v_down: bool,
 }
 
-struct MeshBuildResult {
-    idx: usize,
-    viewer_mesh: ViewerMesh,
-    poly_mesh: PolygonMesh,
-    bounds: Option<(Point3, Point3)>,
-    vertices: usize,
-    faces: usize,
+/// Runs the GUI, opening `project` if given; `read_only` opens it for review only.
+pub fn run_gui(project: Option<PathBuf>, read_only: bool) -> Result<()> {
+    run(read_only, |app| {
+        if let Some(project) = project {
+            app.open_project_from(project);
+        }
+    })
 }
 
-pub fn run_gui() -> Result<()> {
+/// Runs the GUI on `model`, which has no project file until it is saved.
+pub fn run_gui_with_model(model: BimModel, read_only: bool) -> Result<()> {
+    run(read_only, |app| app.show_model(model))
+}
+
+fn run(read_only: bool, open: impl FnOnce(&mut CryxtalApp)) -> Result<()> {
+    install_panic_hook();
     let event_loop = EventLoop::new().map_err(|err| anyhow::anyhow!(err.to_string()))?;
     let window = event_loop
         .create_window(
             winit::window::Window::default_attributes()
-                .with_title("CryXtal Castor")
+                .with_title(APP_TITLE)
+                .with_window_icon(app_icon())
                 .with_min_inner_size(LogicalSize::new(1200.0, 720.0)),
         )
         .map_err(|err| anyhow::anyhow!(err.to_string()))?;
     let window = Arc::new(window);
 
     let egui_ctx = egui::Context::default();
-    let mut painter = create_painter(egui_ctx.clone())?;
-    pollster::block_on(painter.set_window(egui::ViewportId::ROOT, Some(window.clone())))
-        .map_err(|err| anyhow::anyhow!(err.to_string()))?;
+    let preferences = Preferences::load();
+    let diagnostics: SharedDiagnostics = Arc::new(Mutex::new(GpuDiagnostics::default()));
+    let mut painter = pollster::block_on(create_painter(
+        egui_ctx.clone(),
+        &preferences,
+        diagnostics.clone(),
+        preferences.software_renderer,
+    ));
+    if let Err(err) =
+        pollster::block_on(painter.set_window(egui::ViewportId::ROOT, Some(window.clone())))
+    {
+        record_gpu_failure(&diagnostics, err.to_string());
+        if preferences.software_renderer {
+            return Err(gpu_init_error(&diagnostics));
+        }
+        painter = pollster::block_on(create_painter(
+            egui_ctx.clone(),
+            &preferences,
+            diagnostics.clone(),
+            true,
+        ));
+        if let Err(err) =
+            pollster::block_on(painter.set_window(egui::ViewportId::ROOT, Some(window.clone())))
+        {
+            record_gpu_failure(&diagnostics, format!("software fallback: {err}"));
+            return Err(gpu_init_error(&diagnostics));
+        }
+        if let Ok(mut diagnostics) = diagnostics.lock() {
+            diagnostics.software_fallback = true;
+        }
+    }
     let render_state = painter
         .render_state()
         .ok_or_else(|| anyhow::anyhow!("wgpu render state not initialized"))?;
+    let gpu_diagnostics = record_active_adapter(&diagnostics, &render_state)?;
 
     let mut egui_state = EguiWinitState::new(
         egui_ctx.clone(),
@@ -109,23 +233,46 @@ pub fn run_gui() -> Result<()> {
         render_state.adapter.clone(),
         render_state.device.clone(),
         render_state.queue.clone(),
+        preferences,
+        gpu_diagnostics,
     );
+    if read_only {
+        app.edit_mode = EditMode::Review;
+    }
+    open(&mut app);
+    app.crash_recovery = pending_crash();
+    #[cfg(feature = "live-link")]
+    app.start_live_link();
 
-    let clear_color = egui_ctx.style().visuals.window_fill;
-    let [r, g, b, a] = clear_color.to_array();
-    let clear_color = [
-        r as f32 / 255.0,
-        g as f32 / 255.0,
-        b as f32 / 255.0,
-        a as f32 / 255.0,
-    ];
+    let clear_color = clear_color(&egui_ctx);
+
+    let mut viewer_windows: Vec<ViewerWindow> = Vec::new();
+    let mut window_title = String::new();
 
     #[allow(deprecated)]
     event_loop
         .run(move |event, event_loop| {
             event_loop.set_control_flow(ControlFlow::Poll);
             match event {
-                Event::WindowEvent { event, window_id } if window_id == window.id() => {
+                Event::WindowEvent { event, window_id } if window_id != window.id() => {
+                    let Some(idx) = viewer_windows.iter().position(|view| view.id() == window_id)
+                    else {
+                        return;
+                    };
+                    match event {
+                        WindowEvent::CloseRequested => {
+                            viewer_windows.remove(idx).close(&mut painter, &render_state);
+                        }
+                        WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {
+                            viewer_windows[idx].resized(&mut painter);
+                        }
+                        WindowEvent::RedrawRequested => {
+                            viewer_windows[idx].paint(&app, &mut painter, &render_state);
+                        }
+                        other => viewer_windows[idx].on_event(&other),
+                    }
+                }
+                Event::WindowEvent { event, .. } => {
                     if matches!(event, WindowEvent::CloseRequested) {
                         event_loop.exit();
                         return;
@@ -171,12 +318,29 @@ pub fn run_gui() -> Result<()> {
                                 Vec::new(),
                             );
                             app.on_frame_presented();
+
+                            let title = app.window_title();
+                            if title != window_title {
+                                window.set_title(&title);
+                                window_title = title;
+                            }
+                            if app.take_viewer_window_request() {
+                                match app.open_viewer_window(event_loop, &mut painter) {
+                                    Ok(view) => viewer_windows.push(view),
+                                    Err(err) => {
+                                        app.notify_error(format!("Viewer window failed: {err}"))
+                                    }
+                                }
+                            }
                         }
                         _ => {}
                     }
                 }
                 Event::AboutToWait => {
                     window.request_redraw();
+                    for view in &viewer_windows {
+                        view.request_redraw();
+                    }
                 }
                 _ => {}
             }
@@ -186,20 +350,24 @@ pub fn run_gui() -> Result<()> {
     Ok(())
 }
 
-fn create_painter(ctx: egui::Context) -> Result<Painter> {
+/// Painter on the preferred adapter, or on a software one with `software`. Async because
+/// the browser cannot block on adapter and device requests.
+async fn create_painter(
+    ctx: egui::Context,
+    preferences: &Preferences,
+    diagnostics: SharedDiagnostics,
+    software: bool,
+) -> Painter {
     let mut configuration = WgpuConfiguration::default();
-    let power_preference = match std::env::var("CRYXTAL_POWER_PREF") {
-        Ok(value) => match value.trim().to_ascii_lowercase().as_str() {
-            "high" | "high_performance" | "high-performance" => {
-                wgpu::PowerPreference::HighPerformance
-            }
-            "default" => wgpu::PowerPreference::default(),
-            _ => wgpu::PowerPreference::LowPower,
-        },
-        Err(_) => wgpu::PowerPreference::LowPower,
-    };
+    let power_preference = power_preference();
     configuration.wgpu_setup = WgpuSetup::CreateNew(WgpuSetupCreateNew {
         power_preference,
+        native_adapter_selector: Some(adapter_selector(
+            preferences,
+            diagnostics,
+            power_preference,
+            software,
+        )),
         device_descriptor: Arc::new(|adapter| {
             let required_limits =
                 wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits());
@@ -215,13 +383,45 @@ fn create_painter(ctx: egui::Context) -> Result<Painter> {
         ..Default::default()
     });
 
-    let painter = pollster::block_on(Painter::new(
-        ctx,
-        configuration,
-        false,
-        RendererOptions::default(),
-    ));
-    Ok(painter)
+    Painter::new(ctx, configuration, false, RendererOptions::default()).await
+}
+
+/// Notes the adapter and limits the painter ended up with, for the GPU diagnostics panel.
+fn record_active_adapter(
+    diagnostics: &SharedDiagnostics,
+    render_state: &RenderState,
+) -> Result<GpuDiagnostics> {
+    let mut diagnostics = diagnostics
+        .lock()
+        .map_err(|_| anyhow::anyhow!("gpu diagnostics lock poisoned"))?;
+    diagnostics.active = Some(render_state.adapter.get_info());
+    diagnostics.limits = Some(render_state.device.limits());
+    Ok(diagnostics.clone())
+}
+
+/// Window background of the egui style, as the painter's clear color.
+fn clear_color(ctx: &egui::Context) -> [f32; 4] {
+    let [r, g, b, a] = ctx.style().visuals.window_fill.to_array();
+    [
+        r as f32 / 255.0,
+        g as f32 / 255.0,
+        b as f32 / 255.0,
+        a as f32 / 255.0,
+    ]
+}
+
+fn record_gpu_failure(diagnostics: &SharedDiagnostics, failure: String) {
+    if let Ok(mut diagnostics) = diagnostics.lock() {
+        diagnostics.failures.push(failure);
+    }
+}
+
+fn gpu_init_error(diagnostics: &SharedDiagnostics) -> anyhow::Error {
+    let report = diagnostics
+        .lock()
+        .map(|diagnostics| diagnostics.report())
+        .unwrap_or_default();
+    anyhow::anyhow!("failed to initialize the GPU renderer\n{report}")
 }
 
 struct CryxtalApp {
@@ -231,15 +431,40 @@ struct CryxtalApp {
     wall_params: WallParams,
     opening_params: WallOpeningParams,
     rebar_params: RebarParams,
+    slab_params: SlabParams,
     tool_mode: ToolMode,
     pending_wall_start: Option<Point3>,
     pending_rebar_start: Option<Point3>,
+    reference_tool: ReferenceTool,
+    pending_reference: Vec<Point3>,
+    pending_slab: Vec<Point3>,
+    show_references: bool,
+    view_filters: ViewFilterState,
+    phase_view: PhaseState,
+    datums: DatumState,
+    coordinate_input: String,
+    /// Last placed point; the base for relative coordinate entry outside a pending segment.
+    last_point: Option<Point3>,
+    polar: PolarTracking,
+    tracking: Option<TrackingHit>,
     selected: Option<usize>,
     last_selected: Option<usize>,
+    /// Elements selected along with `selected` by Select Similar.
+    similar: Vec<Guid>,
     hovered: Option<usize>,
-    elements: Vec<BimElement>,
+    model: BimModel,
+    /// Edit computed on a worker thread, applied when it arrives.
+    background_edit: Option<BackgroundEdit>,
+    /// Opening values the background edit is applying, shown in the panel meanwhile.
+    opening_edit_preview: Option<Opening>,
     element_meshes: Vec<ViewerMesh>,
     element_polymeshes: Vec<PolygonMesh>,
+    /// Element each entry of `element_meshes` was built for, to reuse clean meshes.
+    element_mesh_guids: Vec<Guid>,
+    /// [`geometry_key`] of the solid each entry of `element_meshes` was tessellated from, so
+    /// only meshes still matching their element are written to the mesh cache.
+    element_mesh_keys: Vec<Option<u64>>,
+    mesh_cache: MeshCache,
     model_info: Option<ModelInfo>,
     viewer: ViewerState,
     viewer_mesh: Option<ViewerMesh>,
@@ -249,7 +474,6 @@ struct CryxtalApp {
     gizmo_init_started: bool,
     frame_presented: bool,
     log: Vec<String>,
-    layers: Vec<Layer>,
     active_layer: usize,
     view_mode: ViewMode,
     mesh_revision: u64,
@@ -273,15 +497,59 @@ struct CryxtalApp {
     render_texture_revision: u64,
     gizmo_texture_id: Option<egui::TextureId>,
     gizmo_texture_revision: u64,
+    show_hud: bool,
+    frame_stats: FrameStats,
+    preferences: Preferences,
+    gpu_diagnostics: GpuDiagnostics,
+    show_preferences: bool,
+    preferences_message: String,
+    dirty: bool,
+    history: History,
+    project_path: Option<PathBuf>,
+    project_path_input: String,
+    project_dialog: Option<ProjectDialog>,
+    storey_copy: Option<StoreyCopy>,
+    search: Option<ElementSearch>,
+    parameter_rename: Option<ParameterRenameTool>,
+    /// Rules of the open Lintels and Sills window.
+    lintel_rules: Option<LintelRules>,
+    /// Offset the Properties panel moves a group by, in model units.
+    group_offset: [f64; 3],
+    /// Weld the Properties panel adds next, its size in model units.
+    weld_draft: WeldAnnotation,
+    parameter_history: ParameterHistory,
+    scan_deviation: Option<ScanDeviation>,
+    issues: Option<IssuesWindow>,
+    measurement: Option<Measurement>,
+    point_clouds: PointClouds,
+    viewer_window_requested: bool,
+    viewer_window_count: usize,
+    toasts: Vec<Toast>,
+    show_log: bool,
+    console: ScriptConsole,
+    #[cfg(feature = "live-link")]
+    live_link: Option<LiveLinkState>,
+    hidden: HashSet<Guid>,
+    clipboard: Option<Clipboard>,
+    context_target: Option<ContextTarget>,
+    cursor_status: CursorStatus,
+    edit_mode: EditMode,
+    opening_conflict: Option<OpeningConflict>,
+    /// Crash bundle from an earlier run, offered for recovery on start.
+    crash_recovery: Option<PendingCrash>,
+    /// Model revision, journal length and project the crash context was last taken at.
+    crash_context_key: Option<(u64, usize, Option<PathBuf>)>,
 }
 
 impl CryxtalApp {
-    fn new(adapter: wgpu::Adapter, device: wgpu::Device, queue: wgpu::Queue) -> Self {
+    fn new(
+        adapter: wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        preferences: Preferences,
+        gpu_diagnostics: GpuDiagnostics,
+    ) -> Self {
         let truck_renderer = TruckRenderer::new(adapter.clone(), device.clone(), queue.clone());
-        let layers = vec![Layer {
-            name: "Default".to_string(),
-            color: Color32::from_rgb(180, 190, 200),
-        }];
         Self {
             adapter,
             device,
@@ -289,15 +557,36 @@ impl CryxtalApp {
             wall_params: WallParams::default(),
             opening_params: WallOpeningParams::default(),
             rebar_params: RebarParams::default(),
+            slab_params: SlabParams::default(),
             tool_mode: ToolMode::default(),
             pending_wall_start: None,
             pending_rebar_start: None,
+            reference_tool: ReferenceTool::Point,
+            pending_reference: Vec::new(),
+            pending_slab: Vec::new(),
+            show_references: true,
+            view_filters: ViewFilterState::default(),
+            phase_view: PhaseState::default(),
+            datums: DatumState::default(),
+            coordinate_input: String::new(),
+            last_point: None,
+            polar: PolarTracking::default(),
+            tracking: None,
             selected: None,
             last_selected: None,
+            similar: Vec::new(),
             hovered: None,
-            elements: Vec::new(),
+            model: BimModel {
+                author: preferences.author(),
+                ..BimModel::default()
+            },
+            background_edit: None,
+            opening_edit_preview: None,
             element_meshes: Vec::new(),
             element_polymeshes: Vec::new(),
+            element_mesh_guids: Vec::new(),
+            element_mesh_keys: Vec::new(),
+            mesh_cache: MeshCache::new(),
             model_info: None,
             viewer: ViewerState::default(),
             viewer_mesh: None,
@@ -307,7 +596,6 @@ impl CryxtalApp {
             gizmo_init_started: false,
             frame_presented: false,
             log: Vec::new(),
-            layers,
             active_layer: 0,
             view_mode: ViewMode::LayerOpaque,
             mesh_revision: 0,
@@ -331,12 +619,52 @@ impl CryxtalApp {
             render_texture_revision: 0,
             gizmo_texture_id: None,
             gizmo_texture_revision: 0,
+            show_hud: false,
+            frame_stats: FrameStats::default(),
+            show_preferences: gpu_diagnostics.software_fallback,
+            preferences,
+            gpu_diagnostics,
+            preferences_message: String::new(),
+            dirty: false,
+            history: History::new(),
+            project_path: None,
+            project_path_input: String::new(),
+            project_dialog: None,
+            storey_copy: None,
+            search: None,
+            parameter_rename: None,
+            lintel_rules: None,
+            group_offset: [0.0; 3],
+            weld_draft: default_weld(),
+            parameter_history: ParameterHistory::default(),
+            scan_deviation: None,
+            issues: None,
+            measurement: None,
+            point_clouds: PointClouds::default(),
+            viewer_window_requested: false,
+            viewer_window_count: 0,
+            toasts: Vec::new(),
+            show_log: false,
+            console: ScriptConsole::default(),
+            #[cfg(feature = "live-link")]
+            live_link: None,
+            hidden: HashSet::new(),
+            clipboard: None,
+            context_target: None,
+            cursor_status: CursorStatus::default(),
+            edit_mode: EditMode::default(),
+            opening_conflict: None,
+            crash_recovery: None,
+            crash_context_key: None,
         }
     }
 
     fn ui(&mut self, ctx: &egui::Context, render_state: &RenderState) {
         self.try_finish_gizmo_init();
         self.start_gizmo_init_if_needed();
+        #[cfg(feature = "live-link")]
+        self.poll_live_link();
+        self.poll_background_edit(ctx);
         self.sync_selection_on_change();
         self.update_view_rows_if_needed();
 
@@ -344,6 +672,8 @@ impl CryxtalApp {
             ToolMode::CreateWall => "wall",
             ToolMode::CreateOpening => "opening",
             ToolMode::CreateRebar => "rebar",
+            ToolMode::CreateReference => "reference",
+            ToolMode::CreateSlab => "slab",
             ToolMode::Select if self.selected.is_some() => "selection",
             _ => "view",
         };
@@ -353,34 +683,96 @@ impl CryxtalApp {
                 ui.spacing_mut().item_spacing = egui::vec2(10.0, 0.0);
                 ui.heading("CryXtal Castor");
                 ui.add(egui::Separator::default().vertical());
-
-                if ui
-                    .selectable_label(self.tool_mode == ToolMode::CreateWall, "Wall")
-                    .clicked()
-                {
-                    self.activate_wall_tool();
+                for command in [
+                    AppCommand::OpenProject,
+                    AppCommand::SaveProject,
+                    AppCommand::SaveProjectAs,
+                    AppCommand::ImportObj,
+                    AppCommand::ImportPointCloud,
+                    AppCommand::ExportJournal,
+                    AppCommand::ReplayJournal,
+                    AppCommand::ExportParameters,
+                    AppCommand::ImportParameters,
+                ] {
+                    if ui.button(command.label()).clicked() {
+                        self.execute(command);
+                    }
                 }
-                if ui
-                    .selectable_label(self.tool_mode == ToolMode::CreateOpening, "Opening")
-                    .clicked()
-                {
-                    self.activate_opening_tool();
+                ui.add(egui::Separator::default().vertical());
+                let undo = ui
+                    .add_enabled(
+                        self.can_undo(),
+                        egui::Button::new(AppCommand::Undo.label()),
+                    )
+                    .on_hover_text(self.history.undo_label().unwrap_or_default());
+                if undo.clicked() {
+                    self.execute(AppCommand::Undo);
+                }
+                let redo = ui
+                    .add_enabled(
+                        self.can_redo(),
+                        egui::Button::new(AppCommand::Redo.label()),
+                    )
+                    .on_hover_text(self.history.redo_label().unwrap_or_default());
+                if redo.clicked() {
+                    self.execute(AppCommand::Redo);
                 }
+                ui.add(egui::Separator::default().vertical());
+
+                // Reference geometry is an annotation, so it stays available when read-only.
+                let editable = !self.edit_mode.is_read_only();
+                ui.add_enabled_ui(editable, |ui| {
+                    if ui
+                        .selectable_label(self.tool_mode == ToolMode::CreateWall, "Wall")
+                        .clicked()
+                    {
+                        self.activate_wall_tool();
+                    }
+                    if ui
+                        .selectable_label(self.tool_mode == ToolMode::CreateSlab, "Slab")
+                        .clicked()
+                    {
+                        self.activate_slab_tool();
+                    }
+                    if ui
+                        .selectable_label(self.tool_mode == ToolMode::CreateOpening, "Opening")
+                        .clicked()
+                    {
+                        self.activate_opening_tool();
+                    }
+                    if ui
+                        .selectable_label(self.tool_mode == ToolMode::CreateRebar, "Rebar")
+                        .clicked()
+                    {
+                        self.activate_rebar_tool();
+                    }
+                });
                 if ui
-                    .selectable_label(self.tool_mode == ToolMode::CreateRebar, "Rebar")
+                    .selectable_label(self.tool_mode == ToolMode::CreateReference, "Reference")
                     .clicked()
                 {
-                    self.activate_rebar_tool();
+                    self.activate_reference_tool();
                 }
-                if ui.button("Reset View").clicked() {
-                    self.viewer.reset_view();
-                }
-                if ui.button("Fit Model").clicked() {
-                    self.fit_model();
+                for command in [
+                    AppCommand::ResetView,
+                    AppCommand::FitModel,
+                    AppCommand::Find,
+                    AppCommand::RenameParameters,
+                    AppCommand::GenerateLintels,
+                    AppCommand::ScanDeviation,
+                    AppCommand::Issues,
+                    AppCommand::ClearModel,
+                    AppCommand::NewWindow,
+                ] {
+                    if ui.button(command.label()).clicked() {
+                        self.execute(command);
+                    }
                 }
-                if ui.button("Clear").clicked() {
-                    self.clear_model();
+                if ui.button("Preferences").clicked() {
+                    self.show_preferences = true;
+                    self.preferences_message.clear();
                 }
+                self.read_only_toggle(ui);
             });
         });
 
@@ -396,6 +788,8 @@ impl CryxtalApp {
                         "wall" => self.wall_panel(ui),
                         "opening" => self.opening_panel(ui),
                         "rebar" => self.rebar_panel(ui),
+                        "reference" => self.reference_panel(ui),
+                        "slab" => self.slab_panel(ui),
                         _ => self.view_panel(ui),
                     });
                     ui.add_space(20.0);
@@ -411,9 +805,22 @@ impl CryxtalApp {
                     self.show_layer_creator = true;
                     self.layer_creator_message.clear();
                 }
+                ui.add(egui::Separator::default().vertical());
+                if ui.button("Log").clicked() {
+                    self.show_log = !self.show_log;
+                }
+                self.script_console_toggle(ui);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    self.status_readout(ui);
+                    self.background_edit_status(ui);
+                });
             });
         });
 
+        if self.console.open {
+            self.script_console_panel(ctx);
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             let available = ui.available_size();
             let (rect, response) = ui.allocate_exact_size(available, egui::Sense::click_and_drag());
@@ -426,34 +833,109 @@ impl CryxtalApp {
         if self.show_layer_creator {
             self.layer_creator_modal(ctx);
         }
+        if self.show_preferences {
+            self.preferences_window(ctx);
+        }
+        if self.project_dialog.is_some() {
+            self.project_dialog_window(ctx);
+        }
+        if self.storey_copy.is_some() {
+            self.storey_copy_window(ctx);
+        }
+        if self.search.is_some() {
+            self.search_window(ctx);
+        }
+        if self.parameter_rename.is_some() {
+            self.parameter_rename_window(ctx);
+        }
+        if self.lintel_rules.is_some() {
+            self.lintel_rules_window(ctx);
+        }
+        if self.scan_deviation.is_some() {
+            self.scan_deviation_window(ctx);
+        }
+        if self.issues.is_some() {
+            self.issues_window(ctx);
+        }
+        if self.opening_conflict.is_some() {
+            self.opening_conflict_window(ctx);
+        }
+        if self.show_log {
+            self.log_window(ctx);
+        }
+        if self.crash_recovery.is_some() {
+            self.crash_recovery_window(ctx);
+        }
+        self.show_toasts(ctx);
 
         self.sync_selected_name();
+        self.refresh_crash_context();
     }
 
     fn selection_panel(&mut self, ui: &mut egui::Ui) {
         ui.heading("Properties");
+        self.properties_tabs(ui);
+        if self.parameter_history.tab == PropertiesTab::History {
+            match self.selected {
+                Some(index) => self.parameter_history_panel(ui, index),
+                None => {
+                    ui.weak("Select an element to see its parameter history");
+                }
+            }
+            return;
+        }
         let category = self.selected_category();
         if !category.is_empty() {
             ui.label(category);
         }
+        if let Some(revision) = self
+            .selected
+            .and_then(|idx| self.model.elements().get(idx))
+            .map(|element| &element.revision)
+            .filter(|revision| revision.revision > 0)
+        {
+            let author = if revision.author.is_empty() {
+                "unknown"
+            } else {
+                revision.author.as_str()
+            };
+            ui.weak(format!("Revision {} by {author}", revision.revision));
+        }
 
         ui.add_space(4.0);
         ui.label("Name");
-        ui.add(egui::TextEdit::singleline(&mut self.selected_name));
+        // Reference meshes are context and are not edited.
+        let reference = self
+            .selected
+            .and_then(|idx| self.model.elements().get(idx))
+            .is_some_and(BimElement::is_reference_mesh);
+        ui.add_enabled(
+            !self.edit_mode.is_read_only() && !reference,
+            egui::TextEdit::singleline(&mut self.selected_name),
+        );
 
         ui.label("Layer");
         self.selected_layer_combo(ui);
+        self.similar_selection_panel(ui);
+        if let Some(index) = self.selected {
+            self.element_type_panel(ui, index);
+            self.element_group_panel(ui, index);
+            self.element_weld_panel(ui, index);
+            self.element_level_panel(ui, index);
+            self.wall_top_level_panel(ui, index);
+            self.element_phase_panel(ui, index);
+        }
 
         ui.add_space(8.0);
         ui.add(egui::Separator::default());
         let is_opening = self
             .selected
-            .and_then(|idx| self.elements.get(idx))
+            .and_then(|idx| self.model.elements().get(idx))
             .map(|element| element.category == BimCategory::Opening)
             .unwrap_or(false);
         let is_rebar = self
             .selected
-            .and_then(|idx| self.elements.get(idx))
+            .and_then(|idx| self.model.elements().get(idx))
             .map(|element| element.category == BimCategory::Rebar)
             .unwrap_or(false);
         if is_opening {
@@ -465,32 +947,46 @@ impl CryxtalApp {
             for (key, value) in self.selection_rows() {
                 ui.label(format!("{key}: {value}"));
             }
+            if let Some(index) = self.selected {
+                self.rebar_set_host_panel(ui, index);
+            }
         }
     }
 
     fn wall_panel(&mut self, ui: &mut egui::Ui) {
         ui.heading("Wall Tool");
 
+        self.tool_type_controls(ui, BimCategory::Wall);
+        let unit = self.model.units.length;
         ui.label("Thickness");
-        ui.add(
-            egui::DragValue::new(&mut self.wall_params.thickness)
-                .range(10.0..=100000.0)
-                .speed(1.0)
-                .fixed_decimals(0),
+        ui.add_enabled(
+            self.wall_params.type_id.is_none(),
+            length_value(&mut self.wall_params.thickness, unit, 10.0..=100000.0),
         );
 
         ui.label("Height");
-        ui.add(
-            egui::DragValue::new(&mut self.wall_params.height)
-                .range(10.0..=100000.0)
-                .speed(1.0)
-                .fixed_decimals(0),
-        );
+        ui.add(length_value(&mut self.wall_params.height, unit, 10.0..=100000.0));
 
         ui.label("Name");
         ui.add(egui::TextEdit::singleline(&mut self.wall_params.name));
 
+        ui.label("Corner Join");
+        ui.horizontal(|ui| {
+            for style in [WallJoinStyle::Miter, WallJoinStyle::Butt] {
+                ui.selectable_value(&mut self.wall_params.join_style, style, style.label());
+            }
+        });
+
+        ui.label("Openings");
+        ui.horizontal(|ui| {
+            for mode in [WallOpeningMode::Outline, WallOpeningMode::Boolean] {
+                ui.selectable_value(&mut self.wall_params.opening_mode, mode, mode.label());
+            }
+        });
+
         ui.label(self.wall_status_text());
+        self.polar_tracking_controls(ui);
+        self.coordinate_entry(ui);
 
         if ui.button("Cancel Wall").clicked() {
             self.cancel_wall();
@@ -516,6 +1012,15 @@ impl CryxtalApp {
                 self.viewer.set_gizmo_mode(GizmoMode::Axis);
             }
         });
+        ui.add_space(8.0);
+        ui.checkbox(&mut self.show_references, "Reference geometry");
+        ui.checkbox(&mut self.show_hud, "Performance HUD (F3)");
+        ui.add_space(8.0);
+        self.datum_panel(ui);
+        ui.add_space(8.0);
+        self.phase_panel(ui);
+        ui.add_space(8.0);
+        self.view_filter_panel(ui);
     }
 
     fn draw_viewport(
@@ -538,6 +1043,7 @@ impl CryxtalApp {
         if response.double_clicked() {
             self.input.double_clicked = true;
         }
+        self.viewport_context_menu(&response, rect);
 
         
         let viewport_rect = Rect::from_min_size(
@@ -581,8 +1087,14 @@ impl CryxtalApp {
         let mut overlay = EguiOverlayPainter::new(&overlay_painter, rect.min.to_vec2());
         let snap_active = matches!(
             self.tool_mode,
-            ToolMode::CreateWall | ToolMode::CreateOpening | ToolMode::CreateRebar
+            ToolMode::CreateWall
+                | ToolMode::CreateOpening
+                | ToolMode::CreateRebar
+                | ToolMode::CreateReference
+                | ToolMode::CreateSlab
         ) || self.viewer.is_pivot_pick_active(self.input.key_v_down);
+        self.sync_references();
+        self.update_cursor_status(viewport_rect, hovered, snap_active);
         self.viewer.paint_overlay(
             &mut overlay,
             viewport_rect,
@@ -593,18 +1105,41 @@ impl CryxtalApp {
             self.input.pointer_pos,
             self.viewer.gizmo_mode() == GizmoMode::Axis,
         );
+        if let Some(hit) = self.tracking.filter(|_| hovered) {
+            self.viewer.paint_tracking(&mut overlay, viewport_rect, &hit);
+        }
+        self.paint_grids(&mut overlay, viewport_rect);
+        self.paint_measurement(&mut overlay, viewport_rect);
         let element_visibility = self.element_visibility();
         paint_hover_outline(
             &self.viewer,
             &mut overlay,
             viewport_rect,
             &self.element_meshes,
-            &self.elements,
+            self.model.elements(),
             self.hovered,
             self.selected,
             &element_visibility,
         );
 
+        // The heat map brings its own legend in place of the view's.
+        match self.scan_deviation.as_ref().filter(|scan| scan.shows_heat_map()) {
+            Some(scan) => paint_scan_deviation(&self.viewer, &mut overlay, viewport_rect, scan),
+            None => {
+                if let Some((view, legend)) = self.active_legend() {
+                    paint_legend(&mut overlay, viewport_rect, &view.name, &legend);
+                }
+            }
+        }
+        if self.show_hud {
+            paint_hud(
+                &mut overlay,
+                viewport_rect,
+                &self.frame_stats,
+                self.truck_renderer.stats(),
+            );
+        }
+
         if self.tool_mode == ToolMode::Select {
             if let Some(selection) = self.selection_drag_rect {
                 let fill = Color32::from_rgba_unmultiplied(120, 170, 255, 40);
@@ -628,6 +1163,7 @@ impl CryxtalApp {
         let now = Instant::now();
         let mut dt = now.duration_since(self.last_frame).as_secs_f64();
         self.last_frame = now;
+        self.frame_stats.record_frame(dt);
         if !dt.is_finite() || dt <= 0.0 {
             dt = 0.016;
         }
@@ -653,6 +1189,8 @@ impl CryxtalApp {
         let element_wireframe = self.element_wireframe();
         let element_skeleton_solid = self.element_skeleton_solid();
         let bounds = self.viewer_mesh.as_ref().and_then(|mesh| mesh.bounds);
+        self.truck_renderer
+            .set_point_clouds(self.point_clouds.revision, &self.point_clouds.clouds);
         let rendered = self.truck_renderer.render(
             rect,
             pixels_per_point,
@@ -781,10 +1319,25 @@ impl CryxtalApp {
             self.clear_selection_drag();
             self.pending_wall_start = None;
             self.pending_rebar_start = None;
+            self.pending_reference.clear();
+            self.pending_slab.clear();
             self.viewer.cancel_interaction();
         }
 
         if focused {
+            if ctx.input(|i| i.key_pressed(egui::Key::F3)) {
+                self.show_hud = !self.show_hud;
+            }
+            if ctx.input(|i| i.key_pressed(egui::Key::F10)) {
+                self.polar.enabled = !self.polar.enabled;
+            }
+            // Enter in a text field submits that field, not the outline.
+            if self.tool_mode == ToolMode::CreateSlab
+                && ctx.memory(|memory| memory.focused().is_none())
+                && ctx.input(|i| i.key_pressed(egui::Key::Enter))
+            {
+                self.finish_slab();
+            }
 
             if modifiers.ctrl {
                 if ctx.input(|i| i.key_pressed(egui::Key::Num1)) {
@@ -795,6 +1348,20 @@ impl CryxtalApp {
                     self.view_mode = ViewMode::LayerTransparent;
                 } else if ctx.input(|i| i.key_pressed(egui::Key::Num4)) {
                     self.view_mode = ViewMode::Material;
+                } else if ctx.input(|i| i.key_pressed(egui::Key::Z)) {
+                    if modifiers.shift {
+                        self.execute(AppCommand::Redo);
+                    } else {
+                        self.execute(AppCommand::Undo);
+                    }
+                } else if ctx.input(|i| i.key_pressed(egui::Key::Y)) {
+                    self.execute(AppCommand::Redo);
+                } else if ctx.input(|i| i.key_pressed(egui::Key::S)) {
+                    self.execute(AppCommand::SaveProject);
+                } else if ctx.input(|i| i.key_pressed(egui::Key::O)) {
+                    self.execute(AppCommand::OpenProject);
+                } else if ctx.input(|i| i.key_pressed(egui::Key::F)) {
+                    self.execute(AppCommand::Find);
                 }
             }
 
@@ -913,10 +1480,16 @@ impl CryxtalApp {
         let queue = self.queue.clone();
         self.gizmo_init_started = true;
         self.gizmo_init_rx = Some(rx);
-        thread::spawn(move || {
+        let init = move || {
             let renderer = GizmoRenderer::new(adapter, device, queue);
             let _ = tx.send(renderer);
-        });
+        };
+        // The browser build has no threads; the cube is built within this frame instead.
+        if cfg!(target_arch = "wasm32") {
+            init();
+        } else {
+            thread::spawn(init);
+        }
     }
 
     fn on_frame_presented(&mut self) {
@@ -941,47 +1514,84 @@ impl CryxtalApp {
     }
 
     fn clear_model(&mut self) {
-        self.elements.clear();
-        self.rebuild_scene();
+        // Openings and generated elements go with their hosts; deleting them separately
+        // would fail.
+        let commands = self
+            .model
+            .elements()
+            .iter()
+            .filter(|element| {
+                let host = element
+                    .opening
+                    .map(|opening| opening.host)
+                    .or(element.host.filter(|_| element.removed_with_host));
+                host.is_none_or(|host| self.model.element(host).is_none())
+            })
+            .map(|element| ModelCommand::DeleteElement { guid: element.guid })
+            .collect();
+        if !self.apply_command("Clear model", ModelCommand::Batch(commands)) {
+            return;
+        }
+        self.hidden.clear();
         self.set_selected(None);
         self.clear_selection_drag();
         self.pending_wall_start = None;
         self.pending_rebar_start = None;
+        self.pending_reference.clear();
+        self.pending_slab.clear();
+        self.last_point = None;
+        self.measurement = None;
         self.push_log("Model cleared".to_string());
     }
 
     fn set_active_layer(&mut self, index: usize) {
-        if index < self.layers.len() {
+        if index < self.model.layers.len() {
             self.active_layer = index;
         }
     }
 
-    fn set_element_layer(&mut self, index: usize) {
-        let Some(selected) = self.selected else {
+    fn set_element_layer(&mut self, index: usize, layer: usize) {
+        if layer >= self.model.layers.len() {
             return;
-        };
-        if index >= self.layers.len() {
-            return;
-        }
-        if let Some(element) = self.elements.get_mut(selected) {
-            let name = self.layers[index].name.clone();
-            element.insert_parameter("Layer", ParameterValue::Text(name));
         }
+        let name = self.model.layers[layer].name.clone();
+        let Some(element) = self.model.elements().get(index) else {
+            return;
+        };
+        let label = format!("Set layer of {}", element.name);
+        let command = if self.selected == Some(index) && !self.similar.is_empty() {
+            ModelCommand::Batch(
+                self.selection_guids()
+                    .into_iter()
+                    .map(|guid| ModelCommand::set_layer(guid, name.clone()))
+                    .collect(),
+            )
+        } else {
+            ModelCommand::set_layer(element.guid, name)
+        };
+        self.apply_command(&label, command);
     }
 
     fn create_layer(&mut self) {
+        if self.edit_mode.is_read_only() {
+            self.layer_creator_message = "The model is read-only".to_string();
+            return;
+        }
         let name = self.new_layer_name.trim().to_string();
         if name.is_empty() {
             self.layer_creator_message = "Layer name is empty".to_string();
             return;
         }
-        if self.layers.iter().any(|layer| layer.name == name) {
+        if self.model.layers.iter().any(|layer| layer.name == name) {
             self.layer_creator_message = "Layer name already exists".to_string();
             return;
         }
-        let color = self.new_layer_color;
-        self.layers.push(Layer { name, color });
-        self.active_layer = self.layers.len().saturating_sub(1);
+        let color = self.new_layer_color.to_array();
+        let label = format!("Add layer {name}");
+        if !self.apply_command(&label, ModelCommand::AddLayer(Layer::new(name, color))) {
+            return;
+        }
+        self.active_layer = self.model.layers.len().saturating_sub(1);
         self.show_layer_creator = false;
         self.new_layer_name.clear();
         self.layer_creator_message.clear();
@@ -1017,28 +1627,8 @@ impl CryxtalApp {
                 }
             }
             ToolMode::CreateWall => {
-                if let Some(point) = self.viewer.pick_point(pos, rect, &self.element_meshes, true) {
-                    let point = Point3::new(point.x, point.y, point.z);
-                    let name = self.wall_params.name.clone();
-
-                    if let Some(start) = self.pending_wall_start {
-                        match build_wall_between_points(
-                            start,
-                            point,
-                            self.wall_params.thickness,
-                            self.wall_params.height,
-                            Some(&name),
-                        ) {
-                            Ok(element) => {
-                                self.pending_wall_start = None;
-                                self.add_elements(vec![element], "Wall added", false);
-                            }
-                            Err(err) => self.push_log(format!("Wall build failed: {err}")),
-                        }
-                    } else {
-                        self.pending_wall_start = Some(point);
-                        self.push_log("Wall start set".to_string());
-                    }
+                if let Some(point) = self.placement_point(pos, rect) {
+                    self.place_wall_point(point);
                 }
             }
             ToolMode::CreateOpening => {
@@ -1047,6 +1637,48 @@ impl CryxtalApp {
             ToolMode::CreateRebar => {
                 self.handle_rebar_click(pos, rect);
             }
+            ToolMode::CreateReference => {
+                if let Some(point) = self.placement_point(pos, rect) {
+                    self.place_reference_point(point);
+                }
+            }
+            ToolMode::CreateSlab => {
+                if let Some(point) = self.placement_point(pos, rect) {
+                    self.place_slab_point(point);
+                }
+            }
+        }
+    }
+
+    pub(super) fn place_wall_point(&mut self, point: Point3) {
+        self.last_point = Some(point);
+        let Some(start) = self.pending_wall_start else {
+            self.pending_wall_start = Some(point);
+            self.push_log("Wall start set".to_string());
+            return;
+        };
+
+        self.execute(AppCommand::PlaceWall(start, point));
+    }
+
+    /// Adds a wall from `start` to `end` with the wall tool's settings.
+    fn place_wall(&mut self, start: Point3, end: Point3) {
+        let name = self.wall_params.name.clone();
+        match build_wall_between_points(
+            start,
+            end,
+            self.wall_params.thickness,
+            self.wall_params.height,
+            Some(&name),
+        ) {
+            Ok(mut element) => {
+                self.wall_params.join_style.apply_to(&mut element);
+                self.wall_params.opening_mode.apply_to(&mut element);
+                self.apply_tool_type(&mut element);
+                self.pending_wall_start = None;
+                self.add_elements(vec![element], "Wall added", false);
+            }
+            Err(err) => self.notify_error(format!("Wall build failed: {err}")),
         }
     }
 
@@ -1079,16 +1711,19 @@ impl CryxtalApp {
         self.last_selected = self.selected;
         self.selected_name = self
             .selected
-            .and_then(|idx| self.elements.get(idx).map(|element| element.name.clone()))
+            .and_then(|idx| self.model.elements().get(idx).map(|element| element.name.clone()))
             .unwrap_or_default();
 
         if let Some(selected) = self.selected {
             let active = self
+                .model
                 .layers
                 .get(self.active_layer)
                 .map(|layer| layer.name.clone())
                 .unwrap_or_else(|| "Default".to_string());
-            if let Some(element) = self.elements.get_mut(selected) {
+            if !self.edit_mode.is_read_only()
+                && let Some(element) = self.model.elements_mut().get_mut(selected)
+            {
                 if element.parameters.get("Layer").is_none() {
                     element.insert_parameter("Layer", ParameterValue::Text(active));
                 }
@@ -1100,7 +1735,9 @@ impl CryxtalApp {
         let Some(selected) = self.selected else {
             return String::new();
         };
-        self.elements
+        self
+            .model
+            .elements()
             .get(selected)
             .map(|element| format!("{:?}", element.category))
             .unwrap_or_default()
@@ -1110,7 +1747,7 @@ impl CryxtalApp {
         let Some(selected) = self.selected else {
             return None;
         };
-        let element = self.elements.get(selected)?;
+        let element = self.model.elements().get(selected)?;
         let layer_name = match element.parameters.get("Layer") {
             Some(ParameterValue::Text(value)) => value.as_str(),
             _ => "",
@@ -1118,14 +1755,14 @@ impl CryxtalApp {
         if layer_name.is_empty() {
             return None;
         }
-        self.layers.iter().position(|layer| layer.name == layer_name)
+        self.model.layers.iter().position(|layer| layer.name == layer_name)
     }
 
     fn selection_rows(&self) -> Vec<(String, String)> {
         let Some(selected) = self.selected else {
             return Vec::new();
         };
-        let Some(element) = self.elements.get(selected) else {
+        let Some(element) = self.model.elements().get(selected) else {
             return Vec::new();
         };
         let mut rows = Vec::new();
@@ -1133,7 +1770,12 @@ impl CryxtalApp {
             if key == "Layer" {
                 continue;
             }
-            rows.push((key.clone(), format!("{value:?}")));
+            let value = parameter_text(element, key, value, self.model.units.length);
+            let value = match element.formula(key) {
+                Some(formula) => format!("{value} = {formula}"),
+                None => value,
+            };
+            rows.push((key.clone(), value));
         }
         rows
     }
@@ -1166,6 +1808,10 @@ impl CryxtalApp {
             format!("{:.2}, {:.2}, {:.2}", pivot.x, pivot.y, pivot.z),
         ));
 
+        rows.push((
+            "Units".to_string(),
+            self.model.units.length.symbol().to_string(),
+        ));
         if let Some(info) = &self.model_info {
             rows.push(("Model".to_string(), info.label.clone()));
             rows.push(("Elements".to_string(), info.elements.to_string()));
@@ -1197,7 +1843,10 @@ impl CryxtalApp {
         let Some(selected) = self.selected else {
             return;
         };
-        let Some(element) = self.elements.get_mut(selected) else {
+        if self.edit_mode.is_read_only() {
+            return;
+        }
+        let Some(element) = self.model.elements_mut().get_mut(selected) else {
             return;
         };
         if element.name != self.selected_name {
@@ -1206,40 +1855,39 @@ impl CryxtalApp {
     }
 
     fn element_colors(&self) -> Vec<Color32> {
-        let default_color = self
-            .layers
-            .first()
-            .map(|layer| layer.color)
-            .unwrap_or_else(|| Color32::from_rgb(180, 190, 200));
-        self.elements
+        let view = self.active_view();
+        self.model
+            .elements()
             .iter()
             .map(|element| {
-                let layer_name = match element.parameters.get("Layer") {
-                    Some(ParameterValue::Text(value)) => value.as_str(),
-                    _ => "",
-                };
-                self.layers
-                    .iter()
-                    .find(|layer| layer.name == layer_name)
-                    .map(|layer| layer.color)
-                    .unwrap_or(default_color)
+                let color = self.model.display_color(element, view);
+                let [r, g, b, a] = self.phase_color(self.shown_phase_status(element), color);
+                Color32::from_rgba_unmultiplied(r, g, b, a)
             })
             .collect()
     }
 
     fn element_visibility(&self) -> Vec<bool> {
-        self.elements
+        self
+            .model
+            .elements()
             .iter()
-            .map(|element| element.category != BimCategory::Opening)
+            .map(|element| {
+                element.category != BimCategory::Opening
+                    && !self.hidden.contains(&element.guid)
+                    && self.shown_phase_status(element).is_some()
+            })
             .collect()
     }
 
     fn element_wireframe(&self) -> Vec<bool> {
-        self.elements.iter().map(|_| true).collect()
+        self.model.elements().iter().map(|_| true).collect()
     }
 
     fn element_skeleton_solid(&self) -> Vec<bool> {
-        self.elements
+        self
+            .model
+            .elements()
             .iter()
             .map(|element| element.category == BimCategory::Rebar)
             .collect()
@@ -1248,6 +1896,7 @@ impl CryxtalApp {
 
     fn add_elements(&mut self, mut elements: Vec<BimElement>, log_label: &str, select_last: bool) {
         let active_layer = self
+            .model
             .layers
             .get(self.active_layer)
             .map(|layer| layer.name.clone())
@@ -1255,12 +1904,19 @@ impl CryxtalApp {
         for element in &mut elements {
             element.insert_parameter("Layer", ParameterValue::Text(active_layer.clone()));
         }
-        let was_empty = self.elements.is_empty();
-        self.elements.append(&mut elements);
-        self.rebuild_scene();
+        let was_empty = self.model.elements().is_empty();
+        let Some(storey) = self.model.default_storey() else {
+            self.notify_error("Model has no storey to place elements on".to_string());
+            return;
+        };
+        // Walls the new ones touch are re-joined in the same undo step (`with_wall_joins`).
+        let command = ModelCommand::AddElements { storey, elements };
+        if !self.apply_command(log_label, command) {
+            return;
+        }
         if select_last {
-            if !self.elements.is_empty() {
-                self.set_selected(Some(self.elements.len() - 1));
+            if !self.model.elements().is_empty() {
+                self.set_selected(Some(self.model.elements().len() - 1));
             } else {
                 self.set_selected(None);
             }
@@ -1273,94 +1929,128 @@ impl CryxtalApp {
         self.push_log(log_label.to_string());
     }
 
+    fn window_title(&self) -> String {
+        let dirty = if self.dirty { "*" } else { "" };
+        let mode = if self.edit_mode.is_read_only() { " [Read-only]" } else { "" };
+        format!("{APP_TITLE} - {}{dirty}{mode}", self.model.name)
+    }
+
     fn rebuild_scene(&mut self) {
+        self.dirty = true;
         self.viewer.invalidate_snap_cache();
-        if self.elements.is_empty() {
+        if self.model.elements().is_empty() {
             self.viewer_mesh = None;
             self.model_info = None;
             self.element_meshes.clear();
             self.element_polymeshes.clear();
+            self.element_mesh_guids.clear();
+            self.element_mesh_keys.clear();
+            self.model.take_dirty();
             self.set_selected(None);
             self.mesh_revision = self.mesh_revision.wrapping_add(1);
             self.view_rows_dirty = true;
             return;
         }
 
-        let mut meshes = Vec::new();
-        let mut poly_meshes = Vec::new();
-        let mut bounds: Option<(Point3, Point3)> = None;
-        let mut total_vertices = 0usize;
-        let mut total_faces = 0usize;
-
-        if self.elements.len() <= 1 {
-            for element in &self.elements {
-                let mesh = triangulate_solid(element.geometry(), DEFAULT_TESSELLATION_TOLERANCE);
-                total_vertices += mesh.positions().len();
-                total_faces += mesh.faces().len();
-                bounds = merge_bounds(bounds, mesh_bounds(mesh.positions()));
-                let mut viewer_mesh = ViewerMesh::from_mesh(&mesh);
-                if element.category == BimCategory::Rebar {
-                    tune_rebar_wireframe(&mut viewer_mesh);
-                }
-                poly_meshes.push(mesh);
-                meshes.push(viewer_mesh);
-            }
-        } else {
-            let (tx, rx) = mpsc::channel::<MeshBuildResult>();
-            thread::scope(|scope| {
-                for (idx, element) in self.elements.iter().enumerate() {
-                    let element = element.clone();
-                    let tx = tx.clone();
-                    scope.spawn(move || {
-                        let mesh =
-                            triangulate_solid(element.geometry(), DEFAULT_TESSELLATION_TOLERANCE);
-                        let vertices = mesh.positions().len();
-                        let faces = mesh.faces().len();
-                        let bounds = mesh_bounds(mesh.positions());
-                        let mut viewer_mesh = ViewerMesh::from_mesh(&mesh);
-                        if element.category == BimCategory::Rebar {
-                            tune_rebar_wireframe(&mut viewer_mesh);
-                        }
-                        let _ = tx.send(MeshBuildResult {
-                            idx,
-                            viewer_mesh,
-                            poly_mesh: mesh,
-                            bounds,
-                            vertices,
-                            faces,
-                        });
-                    });
+        let tessellation_started = Instant::now();
+        let cache = std::mem::take(&mut self.mesh_cache);
+        // Meshes of elements whose geometry is unchanged are kept; only dirty ones are
+        // tessellated again.
+        let dirty = self.model.take_dirty();
+        let mut previous: HashMap<Guid, usize> = self
+            .element_mesh_guids
+            .iter()
+            .enumerate()
+            .map(|(idx, guid)| (*guid, idx))
+            .collect();
+        let old_keys = std::mem::take(&mut self.element_mesh_keys);
+        let mut old_meshes: Vec<Option<(ViewerMesh, PolygonMesh)>> =
+            std::mem::take(&mut self.element_meshes)
+                .into_iter()
+                .zip(std::mem::take(&mut self.element_polymeshes))
+                .map(Some)
+                .collect();
+        let mut keys = vec![None; self.model.elements().len()];
+        let mut built: Vec<Option<(ViewerMesh, PolygonMesh)>> = self
+            .model
+            .elements()
+            .iter()
+            .enumerate()
+            .map(|(element_idx, element)| {
+                if dirty.get(&element.guid).is_some_and(|flags| flags.geometry) {
+                    return None;
                 }
-            });
-            drop(tx);
-
-            let mut results = Vec::with_capacity(self.elements.len());
-            for result in rx {
-                results.push(result);
+                let idx = previous.remove(&element.guid)?;
+                keys[element_idx] = old_keys.get(idx).copied().flatten();
+                old_meshes.get_mut(idx).and_then(Option::take)
+            })
+            .collect();
+        drop(old_meshes);
+        let stale: Vec<usize> = built
+            .iter()
+            .enumerate()
+            .filter(|(_, mesh)| mesh.is_none())
+            .map(|(idx, _)| idx)
+            .collect();
+
+        // Cached meshes are restored as they are; the rest are tessellated together on the
+        // rayon pool, which runs them on the current thread where threads are unavailable.
+        let mut restored = Vec::new();
+        let mut fresh = Vec::new();
+        for &idx in &stale {
+            let key = geometry_key(self.model.elements()[idx].geometry()).ok();
+            keys[idx] = key;
+            match key.and_then(|key| cache.get(&key)) {
+                Some(packed) => restored.push((idx, packed.to_mesh())),
+                None => fresh.push(idx),
             }
-            results.sort_by_key(|result| result.idx);
-
-            for result in results {
-                total_vertices += result.vertices;
-                total_faces += result.faces;
-                bounds = merge_bounds(bounds, result.bounds);
-                poly_meshes.push(result.poly_mesh);
-                meshes.push(result.viewer_mesh);
+        }
+        let solids: Vec<_> = fresh
+            .iter()
+            .map(|&idx| self.model.elements()[idx].geometry())
+            .collect();
+        let fresh_meshes = triangulate_many(&solids, &self.preferences.tessellation());
+        for (idx, mesh) in restored.into_iter().chain(fresh.into_iter().zip(fresh_meshes)) {
+            let mut viewer_mesh = ViewerMesh::from_mesh(&mesh);
+            if self.model.elements()[idx].category == BimCategory::Rebar {
+                tune_rebar_wireframe(&mut viewer_mesh);
             }
+            built[idx] = Some((viewer_mesh, mesh));
+        }
+
+        let mut meshes = Vec::with_capacity(built.len());
+        let mut poly_meshes = Vec::with_capacity(built.len());
+        let mut bounds: Option<(Point3, Point3)> = None;
+        let mut total_vertices = 0usize;
+        let mut total_faces = 0usize;
+        for (viewer_mesh, mesh) in built.into_iter().flatten() {
+            total_vertices += mesh.positions().len();
+            total_faces += mesh.faces().len();
+            bounds = merge_bounds(bounds, mesh_bounds(mesh.positions()));
+            meshes.push(viewer_mesh);
+            poly_meshes.push(mesh);
         }
+        self.element_mesh_guids = self
+            .model
+            .elements()
+            .iter()
+            .map(|element| element.guid)
+            .collect();
+        self.element_mesh_keys = keys;
 
+        self.frame_stats.record_tessellation(tessellation_started.elapsed());
         self.element_meshes = meshes;
         self.element_polymeshes = poly_meshes;
         self.viewer_mesh = ViewerMesh::merge(&self.element_meshes);
         self.mesh_revision = self.mesh_revision.wrapping_add(1);
-        let label = if self.elements.len() == 1 {
-            self.elements[0].name.clone()
+        let label = if self.model.elements().len() == 1 {
+            self.model.elements()[0].name.clone()
         } else {
-            format!("Scene ({})", self.elements.len())
+            format!("Scene ({})", self.model.elements().len())
         };
         self.model_info = Some(ModelInfo {
             label,
-            elements: self.elements.len(),
+            elements: self.model.elements().len(),
             vertices: total_vertices,
             faces: total_faces,
             bounds,
@@ -1368,7 +2058,7 @@ impl CryxtalApp {
         self.view_rows_dirty = true;
 
         if let Some(selected) = self.selected {
-            if selected >= self.elements.len() {
+            if selected >= self.model.elements().len() {
                 self.set_selected(None);
             }
         }
@@ -1383,6 +2073,7 @@ impl CryxtalApp {
 
     fn active_layer_combo(&mut self, ui: &mut egui::Ui) {
         let current = self
+            .model
             .layers
             .get(self.active_layer)
             .map(|layer| layer.name.clone())
@@ -1392,7 +2083,7 @@ impl CryxtalApp {
             .selected_text(current)
             .show_ui(ui, |ui| {
                 let mut next = None;
-                for (idx, layer) in self.layers.iter().enumerate() {
+                for (idx, layer) in self.model.layers.iter().enumerate() {
                     if ui.selectable_label(idx == self.active_layer, &layer.name).clicked() {
                         next = Some(idx);
                     }
@@ -1412,21 +2103,21 @@ impl CryxtalApp {
         };
         let current_index = self.selected_layer_index();
         let current = current_index
-            .and_then(|idx| self.layers.get(idx).map(|layer| layer.name.clone()))
+            .and_then(|idx| self.model.layers.get(idx).map(|layer| layer.name.clone()))
             .unwrap_or_else(|| "No layers".to_string());
 
         egui::ComboBox::from_id_source("selected_layer_combo")
             .selected_text(current)
             .show_ui(ui, |ui| {
                 let mut next = None;
-                for (idx, layer) in self.layers.iter().enumerate() {
+                for (idx, layer) in self.model.layers.iter().enumerate() {
                     let selected_row = current_index == Some(idx);
                     if ui.selectable_label(selected_row, &layer.name).clicked() {
                         next = Some(idx);
                     }
                 }
-                if let Some(idx) = next {
-                    self.set_element_layer(idx);
+                if let (Some(idx), Some(selected)) = (next, self.selected) {
+                    self.execute(AppCommand::SetLayer(selected, idx));
                     self.last_selected = None;
                 }
             });
@@ -1483,6 +2174,7 @@ impl CryxtalApp {
     fn set_selected(&mut self, selected: Option<usize>) {
         self.selected = selected;
         self.last_selected = None;
+        self.similar.clear();
     }
 }
 
@@ -0,0 +1,151 @@
+use cryxtal_base::OpContext;
+use cryxtal_bim::{BimModel, ModelCommand};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use super::CryxtalApp;
+
+/// How often the UI repaints to follow a running edit.
+const PROGRESS_REPAINT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Runs on the app once the command of a background edit is applied.
+pub(super) type EditFinish = Box<dyn FnOnce(&mut CryxtalApp)>;
+
+/// A model edit whose booleans and host rebuilds run on a worker thread, against a copy of the
+/// model. The command it computes is applied when it arrives, unless the model changed since.
+pub(super) struct BackgroundEdit {
+    label: String,
+    merge: bool,
+    /// [`BimModel::revision`] of the copy the edit was computed on.
+    revision: u64,
+    cancel: Arc<AtomicBool>,
+    /// Completion in `[0, 1]`, as the bits of an `f32`.
+    progress: Arc<AtomicU32>,
+    result: Receiver<anyhow::Result<ModelCommand>>,
+    finish: Option<EditFinish>,
+}
+
+impl CryxtalApp {
+    /// Computes the command of an edit named `label` on a worker thread; `merge` applies it as
+    /// [`CryxtalApp::apply_merged_command`] does. One edit runs at a time: a merged edit
+    /// replaces a running one with the same label, so dragging a value only computes the
+    /// latest, and any other edit waits for the running one to finish or be cancelled. Returns
+    /// whether the edit started.
+    pub(super) fn spawn_edit(
+        &mut self,
+        label: String,
+        merge: bool,
+        job: impl FnOnce(&BimModel, &OpContext) -> anyhow::Result<ModelCommand> + Send + 'static,
+        finish: Option<EditFinish>,
+    ) -> bool {
+        if let Some(running) = &self.background_edit {
+            let replaces = merge && running.merge && running.label == label;
+            if !replaces {
+                let message = format!("{} is still running", running.label);
+                self.notify_warning(message);
+                return false;
+            }
+            running.cancel.store(true, Ordering::Relaxed);
+        }
+        let model = self.model.clone();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let progress = Arc::new(AtomicU32::new(0));
+        let (sender, result) = mpsc::channel();
+        let worker_cancel = Arc::clone(&cancel);
+        let worker_progress = Arc::clone(&progress);
+        self.background_edit = Some(BackgroundEdit {
+            label,
+            merge,
+            revision: model.revision(),
+            cancel,
+            progress,
+            result,
+            finish,
+        });
+        let run = move || {
+            let report =
+                |fraction: f32| worker_progress.store(fraction.to_bits(), Ordering::Relaxed);
+            let ctx = OpContext::new()
+                .with_progress(&report)
+                .with_cancel(&worker_cancel);
+            let _ = sender.send(job(&model, &ctx));
+        };
+        // The browser build has no threads; the edit is computed within this frame instead.
+        if cfg!(target_arch = "wasm32") {
+            run();
+        } else {
+            thread::spawn(run);
+        }
+        true
+    }
+
+    /// Applies the command of the background edit once it has been computed.
+    pub(super) fn poll_background_edit(&mut self, ctx: &egui::Context) {
+        let Some(edit) = &self.background_edit else {
+            return;
+        };
+        let outcome = match edit.result.try_recv() {
+            Ok(outcome) => outcome,
+            Err(TryRecvError::Empty) => {
+                ctx.request_repaint_after(PROGRESS_REPAINT_INTERVAL);
+                return;
+            }
+            Err(TryRecvError::Disconnected) => Err(anyhow::anyhow!("the worker thread stopped")),
+        };
+        let Some(edit) = self.background_edit.take() else {
+            return;
+        };
+        self.opening_edit_preview = None;
+        let command = match outcome {
+            Ok(command) => command,
+            Err(err) => {
+                self.notify_error(format!("{} failed: {err:#}", edit.label));
+                return;
+            }
+        };
+        if self.model.revision() != edit.revision {
+            self.notify_warning(format!(
+                "{} was dropped: the model changed while it was computed",
+                edit.label
+            ));
+            return;
+        }
+        let applied = if edit.merge {
+            self.apply_merged_command(&edit.label, command)
+        } else {
+            self.apply_command(&edit.label, command)
+        };
+        if applied && let Some(finish) = edit.finish {
+            finish(self);
+        }
+    }
+
+    /// Stops the background edit, leaving the model as it is.
+    pub(super) fn cancel_background_edit(&mut self) {
+        if let Some(edit) = self.background_edit.take() {
+            edit.cancel.store(true, Ordering::Relaxed);
+            self.opening_edit_preview = None;
+            self.push_log(format!("{} cancelled", edit.label));
+        }
+    }
+
+    /// Laid out right to left: the progress of the background edit and a button cancelling it.
+    pub(super) fn background_edit_status(&mut self, ui: &mut egui::Ui) {
+        let Some(edit) = &self.background_edit else {
+            return;
+        };
+        let progress = f32::from_bits(edit.progress.load(Ordering::Relaxed));
+        ui.add(egui::Separator::default().vertical());
+        ui.add(
+            egui::ProgressBar::new(progress)
+                .desired_width(160.0)
+                .text(edit.label.as_str()),
+        );
+        if ui.button("Cancel").clicked() {
+            self.cancel_background_edit();
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use cryxtal_base::{Guid, OpContext};
+use cryxtal_bim::{BimElement, ElementGroup, ModelCommand};
+use cryxtal_topology::{Matrix4, Point3, Vector3};
+use std::collections::HashMap;
+use truck_base::cgmath64::SquareMatrix;
+
+use crate::elements::translate_element;
+use crate::viewer::{Point2, Rect};
+
+use super::opening::staged_host_rebuild;
+use super::project_file::ProjectDialog;
+use super::{CryxtalApp, ToolMode};
+
+/// Copied elements waiting to be pasted, with the group they were copied as, if any.
+#[derive(Clone)]
+pub(super) struct Clipboard {
+    pub(super) elements: Vec<BimElement>,
+    pub(super) group: Option<ElementGroup>,
+    /// Point the copy was taken relative to; it lands on the paste point.
+    pub(super) anchor: Point3,
+}
+
+/// Every user-triggerable action in the viewer; menus and buttons issue these through
+/// [`CryxtalApp::execute`] instead of mutating state directly.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum AppCommand {
+    OpenProject,
+    SaveProject,
+    SaveProjectAs,
+    /// Adds a surveyor's OBJ mesh as a reference element.
+    ImportObj,
+    /// Shows an as-built PLY or XYZ scan over the model.
+    ImportPointCloud,
+    /// Saves the commands applied this session for replay against the opened project.
+    ExportJournal,
+    /// Applies a saved session journal to the model it started from.
+    ReplayJournal,
+    /// Writes every element's parameters to CSV or JSON for editing in a spreadsheet.
+    ExportParameters,
+    /// Reads edited parameter values back, matched by guid.
+    ImportParameters,
+    Undo,
+    Redo,
+    ResetView,
+    FitModel,
+    /// Opens the element search (Ctrl+F).
+    Find,
+    /// Opens the project-wide parameter key rename.
+    RenameParameters,
+    /// Opens the rules for lintels and sills generated at wall openings.
+    GenerateLintels,
+    /// Opens the comparison of a laser scan with the model.
+    ScanDeviation,
+    /// Opens the review issues raised on the model.
+    Issues,
+    ClearModel,
+    NewWindow,
+    ShowAll,
+    Hide(usize),
+    Isolate(usize),
+    /// Hides everything outside the group of an element (by index).
+    IsolateGroup(usize),
+    ZoomTo(usize),
+    /// Measures the shortest distance from the selected element to an element (by index).
+    MeasureDistance(usize),
+    Properties(usize),
+    /// Selects an element with every element geometrically similar to it.
+    SelectSimilar(usize),
+    Delete(usize),
+    Copy(usize, Point3),
+    CopyToLevels(usize),
+    /// Copies the group of an element (by index).
+    CopyGroup(usize, Point3),
+    CreateAssembly(usize),
+    Paste(Point3),
+    CreateWallAt(Point3),
+    CreateOpeningAt(Point2, Rect),
+    PlaceWall(Point3, Point3),
+    /// Moves an element (by index) onto a layer (by index).
+    SetLayer(usize, usize),
+    /// Looks straight at the planar face of an element under a point picked on it, or at its
+    /// largest planar face.
+    LookAtFace(usize, Option<Point3>),
+    /// Makes a face, picked as for [`AppCommand::LookAtFace`], the work plane.
+    SketchOnFace(usize, Option<Point3>),
+    ClearWorkPlane,
+    ClearMeasurement,
+    ClearPointClouds,
+}
+
+impl AppCommand {
+    pub(super) fn label(&self) -> &'static str {
+        match self {
+            AppCommand::OpenProject => "Open",
+            AppCommand::SaveProject => "Save",
+            AppCommand::SaveProjectAs => "Save As",
+            AppCommand::ImportObj => "Import OBJ",
+            AppCommand::ImportPointCloud => "Import Point Cloud",
+            AppCommand::ExportJournal => "Export Journal",
+            AppCommand::ReplayJournal => "Replay Journal",
+            AppCommand::ExportParameters => "Export Parameters",
+            AppCommand::ImportParameters => "Import Parameters",
+            AppCommand::Undo => "Undo",
+            AppCommand::Redo => "Redo",
+            AppCommand::ResetView => "Reset View",
+            AppCommand::FitModel => "Fit Model",
+            AppCommand::Find => "Find",
+            AppCommand::RenameParameters => "Rename Parameters",
+            AppCommand::GenerateLintels => "Lintels",
+            AppCommand::ScanDeviation => "Scan Deviation",
+            AppCommand::Issues => "Issues",
+            AppCommand::ClearModel => "Clear",
+            AppCommand::NewWindow => "New Window",
+            AppCommand::ShowAll => "Show All",
+            AppCommand::Hide(_) => "Hide",
+            AppCommand::Isolate(_) => "Isolate",
+            AppCommand::IsolateGroup(_) => "Isolate Group",
+            AppCommand::ZoomTo(_) => "Zoom To",
+            AppCommand::MeasureDistance(_) => "Measure from Selected",
+            AppCommand::Properties(_) => "Properties",
+            AppCommand::SelectSimilar(_) => "Select Similar",
+            AppCommand::Delete(_) => "Delete",
+            AppCommand::Copy(..) => "Copy",
+            AppCommand::CopyToLevels(_) => "Copy to Levels",
+            AppCommand::CopyGroup(..) => "Copy Group",
+            AppCommand::CreateAssembly(_) => "Make Assembly",
+            AppCommand::Paste(_) => "Paste",
+            AppCommand::CreateWallAt(_) => "Create Wall Here",
+            AppCommand::CreateOpeningAt(..) => "Create Opening Here",
+            AppCommand::PlaceWall(..) => "Place Wall",
+            AppCommand::SetLayer(..) => "Set Layer",
+            AppCommand::LookAtFace(..) => "Look at Face",
+            AppCommand::SketchOnFace(..) => "Sketch on Face",
+            AppCommand::ClearWorkPlane => "Clear Work Plane",
+            AppCommand::ClearMeasurement => "Clear Measurement",
+            AppCommand::ClearPointClouds => "Clear Point Clouds",
+        }
+    }
+
+    /// Whether the command edits the model, so is refused in read-only mode.
+    pub(super) fn edits_model(&self) -> bool {
+        matches!(
+            self,
+            AppCommand::ClearModel
+                | AppCommand::ImportObj
+                | AppCommand::ReplayJournal
+                | AppCommand::ImportParameters
+                | AppCommand::RenameParameters
+                | AppCommand::GenerateLintels
+                | AppCommand::Delete(_)
+                | AppCommand::CopyToLevels(_)
+                | AppCommand::CreateAssembly(_)
+                | AppCommand::Paste(_)
+                | AppCommand::CreateWallAt(_)
+                | AppCommand::CreateOpeningAt(..)
+                | AppCommand::PlaceWall(..)
+                | AppCommand::SetLayer(..)
+        )
+    }
+}
+
+impl CryxtalApp {
+    pub(super) fn execute(&mut self, command: AppCommand) {
+        if self.edit_mode.is_read_only() && command.edits_model() {
+            self.notify_warning(format!(
+                "{} is not available in read-only mode",
+                command.label()
+            ));
+            return;
+        }
+        self.record_macro(command);
+        match command {
+            AppCommand::OpenProject => self.open_project_dialog(ProjectDialog::Open),
+            // A read-only project is never overwritten; saving it asks for a new file.
+            AppCommand::SaveProject => match self.project_path.clone() {
+                Some(path) if !self.edit_mode.is_read_only() => self.save_project_to(path),
+                _ => self.open_project_dialog(ProjectDialog::SaveAs),
+            },
+            AppCommand::SaveProjectAs => self.open_project_dialog(ProjectDialog::SaveAs),
+            AppCommand::ImportObj => self.open_project_dialog(ProjectDialog::ImportObj),
+            AppCommand::ImportPointCloud => {
+                self.open_project_dialog(ProjectDialog::ImportPointCloud)
+            }
+            AppCommand::ExportJournal => self.open_project_dialog(ProjectDialog::ExportJournal),
+            AppCommand::ReplayJournal => self.open_project_dialog(ProjectDialog::ReplayJournal),
+            AppCommand::ExportParameters => {
+                self.open_project_dialog(ProjectDialog::ExportParameters)
+            }
+            AppCommand::ImportParameters => {
+                self.open_project_dialog(ProjectDialog::ImportParameters)
+            }
+            AppCommand::Undo => self.undo(),
+            AppCommand::Redo => self.redo(),
+            AppCommand::ResetView => self.viewer.reset_view(),
+            AppCommand::FitModel => self.fit_model(),
+            AppCommand::Find => self.open_search(),
+            AppCommand::RenameParameters => self.open_parameter_rename(),
+            AppCommand::GenerateLintels => self.open_lintel_rules(),
+            AppCommand::ScanDeviation => self.open_scan_deviation(),
+            AppCommand::Issues => self.open_issues(),
+            AppCommand::ClearModel => self.clear_model(),
+            AppCommand::NewWindow => self.viewer_window_requested = true,
+            AppCommand::ShowAll => self.hidden.clear(),
+            AppCommand::Hide(index) => {
+                if let Some(element) = self.model.elements().get(index) {
+                    self.hidden.insert(element.guid);
+                    if self.selected == Some(index) {
+                        self.set_selected(None);
+                    }
+                }
+            }
+            AppCommand::Isolate(index) => {
+                let Some(keep) = self.model.elements().get(index).map(|element| element.guid)
+                else {
+                    return;
+                };
+                self.hidden = self
+                    .model
+                    .elements()
+                    .iter()
+                    .map(|element| element.guid)
+                    .filter(|guid| *guid != keep)
+                    .collect();
+            }
+            AppCommand::IsolateGroup(index) => self.isolate_group(index),
+            AppCommand::ZoomTo(index) => {
+                if let Some(bounds) = self.element_meshes.get(index).and_then(|mesh| mesh.bounds) {
+                    self.viewer.fit_bounds(bounds);
+                }
+            }
+            AppCommand::MeasureDistance(index) => self.measure_distance(index),
+            AppCommand::Properties(index) => {
+                self.tool_mode = ToolMode::Select;
+                self.set_selected(Some(index));
+            }
+            AppCommand::SelectSimilar(index) => self.select_similar(index),
+            AppCommand::Delete(index) => self.delete_element(index),
+            AppCommand::Copy(index, anchor) => {
+                let Some(element) = self.model.elements().get(index) else {
+                    return;
+                };
+                let name = element.name.clone();
+                // Openings and hosted rebar go along, so pasting rebuilds the whole assembly.
+                match self.model.deep_clone(element.guid, Matrix4::identity()) {
+                    Ok(elements) => {
+                        self.clipboard = Some(Clipboard {
+                            elements,
+                            group: None,
+                            anchor,
+                        });
+                        self.push_log(format!("Copied {name}"));
+                    }
+                    Err(err) => self.notify_error(format!("Copy failed: {err}")),
+                }
+            }
+            AppCommand::CopyToLevels(index) => self.open_storey_copy(index),
+            AppCommand::CopyGroup(index, anchor) => self.copy_group(index, anchor),
+            AppCommand::CreateAssembly(index) => self.create_assembly(index),
+            AppCommand::Paste(point) => self.paste_at(point),
+            AppCommand::CreateWallAt(point) => {
+                self.activate_wall_tool();
+                self.pending_wall_start = Some(point);
+                self.push_log("Wall start set".to_string());
+            }
+            AppCommand::CreateOpeningAt(pos, rect) => {
+                self.activate_opening_tool();
+                self.handle_opening_click(pos, rect);
+            }
+            AppCommand::PlaceWall(start, end) => self.place_wall(start, end),
+            AppCommand::SetLayer(index, layer) => self.set_element_layer(index, layer),
+            AppCommand::LookAtFace(index, point) => self.look_at_face(index, point),
+            AppCommand::SketchOnFace(index, point) => self.sketch_on_face(index, point),
+            AppCommand::ClearWorkPlane => self.clear_work_plane(),
+            AppCommand::ClearMeasurement => self.measurement = None,
+            AppCommand::ClearPointClouds => self.clear_point_clouds(),
+        }
+    }
+
+    pub(super) fn delete_element(&mut self, index: usize) {
+        let Some(element) = self.model.elements().get(index).cloned() else {
+            return;
+        };
+
+        let mut commands = vec![ModelCommand::DeleteElement { guid: element.guid }];
+        if let Some(opening) = element.opening
+            && self.model.element(opening.host).is_some()
+        {
+            let edit = |staged: &mut Vec<BimElement>| {
+                staged.retain(|candidate| candidate.guid != element.guid)
+            };
+            match staged_host_rebuild(&self.model, opening.host, edit, &OpContext::default()) {
+                Ok(rebuilt) => commands.push(ModelCommand::ReplaceElements { elements: rebuilt }),
+                Err(err) => self.notify_error(format!("Host rebuild failed: {err}")),
+            }
+        }
+        for host in &element.cuts {
+            if self.model.element(*host).is_none() {
+                continue;
+            }
+            let edit = |staged: &mut Vec<BimElement>| {
+                staged.retain(|candidate| candidate.guid != element.guid)
+            };
+            match staged_host_rebuild(&self.model, *host, edit, &OpContext::default()) {
+                Ok(rebuilt) => commands.push(ModelCommand::ReplaceElements {
+                    elements: rebuilt.into_iter().take(1).collect(),
+                }),
+                Err(err) => self.notify_error(format!("Host rebuild failed: {err}")),
+            }
+        }
+        let label = format!("Delete {}", element.name);
+        if !self.apply_command(&label, ModelCommand::Batch(commands)) {
+            return;
+        }
+        self.hidden.remove(&element.guid);
+        self.set_selected(None);
+        self.push_log(format!("Deleted {}", element.name));
+        let orphaned = self.model.hosted_elements(element.guid).len();
+        if orphaned > 0 {
+            self.notify_warning(format!(
+                "{orphaned} element(s) hosted by {} no longer have a host",
+                element.name
+            ));
+        }
+    }
+
+    fn paste_at(&mut self, point: Point3) {
+        let Some(Clipboard {
+            elements: mut pasted,
+            group,
+            anchor,
+        }) = self.clipboard.clone()
+        else {
+            return;
+        };
+        let offset = Vector3::new(point.x - anchor.x, point.y - anchor.y, point.z - anchor.z);
+        let mut guids = HashMap::new();
+        for element in &mut pasted {
+            let guid = Guid::new();
+            guids.insert(element.guid, guid);
+            element.guid = guid;
+            translate_element(element, offset);
+        }
+        for element in &mut pasted {
+            if let Some(opening) = element.opening.as_mut() {
+                opening.guid = element.guid;
+                if let Some(host) = guids.get(&opening.host) {
+                    opening.host = *host;
+                }
+            }
+            // Pasted rebar only keeps a host that was copied along with it.
+            element.host = element.host.and_then(|host| guids.get(&host).copied());
+            element.cuts.retain(|host| guids.contains_key(host));
+            for host in &mut element.cuts {
+                *host = guids[host];
+            }
+        }
+        match group {
+            Some(mut group) => {
+                group.guid = Guid::new();
+                for member in &mut group.members {
+                    *member = guids.get(member).copied().unwrap_or(*member);
+                }
+                self.paste_group(pasted, group);
+            }
+            None => self.add_elements(pasted, "Pasted", false),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use cryxtal_bim::{BimCategory, BimElement};
+use cryxtal_topology::Point3;
+
+use crate::viewer::{Point2, Rect, Vec2};
+
+use super::CryxtalApp;
+use super::commands::AppCommand;
+
+/// What the pointer was over when the viewport context menu was opened.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct ContextTarget {
+    element: Option<usize>,
+    /// Where the pointer ray hits the element, unsnapped, for face picking.
+    surface: Option<Point3>,
+    point: Option<Point3>,
+    pos: Point2,
+    rect: Rect,
+}
+
+impl CryxtalApp {
+    pub(super) fn viewport_context_menu(&mut self, response: &egui::Response, rect: egui::Rect) {
+        if response.secondary_clicked()
+            && let Some(pointer) = response.interact_pointer_pos()
+        {
+            self.context_target = Some(self.context_target_at(pointer, rect));
+        }
+        let Some(target) = self.context_target else {
+            return;
+        };
+
+        let mut command = None;
+        response.context_menu(|ui| {
+            let commands = self.context_commands(&target);
+            for (idx, item) in commands.iter().enumerate() {
+                if idx > 0 && matches!(item, AppCommand::Delete(_) | AppCommand::ShowAll) {
+                    ui.separator();
+                }
+                let enabled = match item {
+                    _ if self.edit_mode.is_read_only() && item.edits_model() => false,
+                    AppCommand::Paste(_) => self.clipboard.is_some(),
+                    AppCommand::ShowAll => !self.hidden.is_empty(),
+                    _ => true,
+                };
+                if ui
+                    .add_enabled(enabled, egui::Button::new(item.label()))
+                    .clicked()
+                {
+                    command = Some(*item);
+                }
+            }
+        });
+
+        if let Some(command) = command {
+            self.context_target = None;
+            self.execute(command);
+        }
+    }
+
+    fn context_target_at(&self, pointer: egui::Pos2, rect: egui::Rect) -> ContextTarget {
+        let pos = Point2::new(pointer.x - rect.min.x, pointer.y - rect.min.y);
+        let viewport = Rect::from_min_size(
+            Point2::new(0.0, 0.0),
+            Vec2::new(rect.width(), rect.height()),
+        );
+        let hit = self
+            .viewer
+            .pick_element(pos, viewport, &self.element_meshes)
+            .filter(|(index, _)| {
+                self.model
+                    .elements()
+                    .get(*index)
+                    .is_some_and(|element| !self.hidden.contains(&element.guid))
+            });
+        let element = hit.map(|(index, _)| index);
+        let surface = hit.map(|(_, point)| Point3::new(point.x, point.y, point.z));
+        let point = self
+            .viewer
+            .pick_point(pos, viewport, &self.element_meshes, true)
+            .map(|point| Point3::new(point.x, point.y, point.z));
+        ContextTarget {
+            element,
+            surface,
+            point,
+            pos,
+            rect: viewport,
+        }
+    }
+
+    fn context_commands(&self, target: &ContextTarget) -> Vec<AppCommand> {
+        let mut commands = Vec::new();
+        match target.element {
+            Some(index) => {
+                commands.extend([
+                    AppCommand::Hide(index),
+                    AppCommand::Isolate(index),
+                    AppCommand::ZoomTo(index),
+                    AppCommand::LookAtFace(index, target.surface),
+                    AppCommand::SketchOnFace(index, target.surface),
+                    AppCommand::Properties(index),
+                    AppCommand::SelectSimilar(index),
+                ]);
+                if self.selected.is_some_and(|selected| selected != index) {
+                    commands.push(AppCommand::MeasureDistance(index));
+                }
+                let element = self.model.elements().get(index);
+                let category = element.map(|element| element.category);
+                // Imported reference meshes are context only, not copied or grouped.
+                let reference = element.is_some_and(BimElement::is_reference_mesh);
+                if category == Some(BimCategory::Wall) {
+                    commands.push(AppCommand::CreateOpeningAt(target.pos, target.rect));
+                }
+                if category != Some(BimCategory::Opening) && !reference {
+                    if let Some(point) = target.point {
+                        commands.push(AppCommand::Copy(index, point));
+                    }
+                    commands.push(AppCommand::CopyToLevels(index));
+                }
+                match self.group_at(index) {
+                    Some(_) => {
+                        commands.push(AppCommand::IsolateGroup(index));
+                        if let Some(point) = target.point {
+                            commands.push(AppCommand::CopyGroup(index, point));
+                        }
+                    }
+                    None if category != Some(BimCategory::Opening) && !reference => {
+                        commands.push(AppCommand::CreateAssembly(index));
+                    }
+                    None => {}
+                }
+                commands.push(AppCommand::Delete(index));
+            }
+            None => {
+                if let Some(point) = target.point {
+                    commands.push(AppCommand::Paste(point));
+                    commands.push(AppCommand::CreateWallAt(point));
+                }
+            }
+        }
+        if self.viewer.work_plane().is_some() {
+            commands.push(AppCommand::ClearWorkPlane);
+        }
+        if self.measurement.is_some() {
+            commands.push(AppCommand::ClearMeasurement);
+        }
+        if !self.point_clouds.clouds.is_empty() {
+            commands.push(AppCommand::ClearPointClouds);
+        }
+        commands.push(AppCommand::ShowAll);
+        commands
+    }
+}
@@ -0,0 +1,89 @@
+use cryxtal_base::{evaluate_expression, parse_coordinate};
+use cryxtal_bim::BimCategory;
+use cryxtal_topology::Point3;
+use egui::Ui;
+
+use super::{CryxtalApp, ToolMode};
+
+impl CryxtalApp {
+    /// Text entry for the next point of the active placement tool, as an alternative to clicking.
+    pub(super) fn coordinate_entry(&mut self, ui: &mut Ui) {
+        ui.add_space(6.0);
+        ui.label("Next point (x,y,z, @dx,dy,dz or a distance along the tracking ray)");
+        let mut submitted = false;
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.coordinate_input)
+                    .hint_text("@1000,0,0")
+                    .desired_width(140.0),
+            );
+            // The Enter that submits the point is used up here, so it does not also close a
+            // slab outline.
+            submitted = response.lost_focus()
+                && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Enter));
+            if ui.button("Place").clicked() {
+                submitted = true;
+            }
+        });
+        if submitted && !self.coordinate_input.trim().is_empty() {
+            self.submit_coordinate();
+        }
+    }
+
+    fn submit_coordinate(&mut self) {
+        // A bare distance while a tracking ray is active places the point along that ray.
+        if !self.coordinate_input.contains(',')
+            && let Some(point) = evaluate_expression(&self.coordinate_input)
+                .ok()
+                .and_then(|distance| self.tracked_point_at(distance))
+        {
+            self.place_point(point);
+            return;
+        }
+
+        let input = match parse_coordinate(&self.coordinate_input) {
+            Ok(input) => input,
+            Err(err) => {
+                self.notify_warning(format!("Coordinate entry: {err}"));
+                return;
+            }
+        };
+        let reference = self.coordinate_reference();
+        let [x, y, z] = input.resolve([reference.x, reference.y, reference.z]);
+        self.place_point(Point3::new(x, y, z));
+    }
+
+    fn place_point(&mut self, point: Point3) {
+        match self.tool_mode {
+            ToolMode::CreateWall => self.place_wall_point(point),
+            ToolMode::CreateRebar => self.place_rebar_point(point),
+            ToolMode::CreateReference => self.place_reference_point(point),
+            ToolMode::CreateSlab => self.place_slab_point(point),
+            ToolMode::CreateOpening => {
+                let selected_host = self
+                    .selected
+                    .and_then(|idx| self.model.elements().get(idx))
+                    .filter(|element| {
+                        matches!(element.category, BimCategory::Wall | BimCategory::Slab)
+                    })
+                    .map(|element| element.guid);
+                let Some(host) = selected_host else {
+                    self.notify_warning(
+                        "Select a wall or slab before typing an opening center".to_string(),
+                    );
+                    return;
+                };
+                self.place_opening_point(host, point);
+            }
+            ToolMode::Select => return,
+        }
+        self.coordinate_input.clear();
+    }
+
+    /// Relative input continues from the pending segment start, else from the last placed point.
+    fn coordinate_reference(&self) -> Point3 {
+        self.tracking_origin()
+            .or(self.last_point)
+            .unwrap_or(Point3::new(0.0, 0.0, 0.0))
+    }
+}
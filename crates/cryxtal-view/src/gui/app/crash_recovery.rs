@@ -0,0 +1,91 @@
+use cryxtal_io::Project;
+
+use crate::gui::crash::{CrashContext, PendingCrash, set_crash_context};
+
+use super::CryxtalApp;
+
+impl CryxtalApp {
+    /// Hands the panic hook the model, project and journal once any of them changed, so a
+    /// crash on any thread is bundled with the state the app was last in.
+    pub(super) fn refresh_crash_context(&mut self) {
+        let key = (
+            self.mesh_revision,
+            self.history.journal().entries.len(),
+            self.project_path.clone(),
+        );
+        if self.crash_context_key.as_ref() == Some(&key) {
+            return;
+        }
+        set_crash_context(CrashContext {
+            project: Project {
+                model: self.model.clone(),
+                camera: Some(self.viewer.camera()),
+            },
+            project_path: self.project_path.clone(),
+            journal: self.history.journal().clone(),
+        });
+        self.crash_context_key = Some(key);
+    }
+
+    /// Offers what an earlier crash left behind: its snapshot to restore and its bundle to
+    /// attach to a bug report.
+    pub(super) fn crash_recovery_window(&mut self, ctx: &egui::Context) {
+        let Some(crash) = self.crash_recovery.clone() else {
+            return;
+        };
+        let mut restore = false;
+        let mut close = false;
+        egui::Window::new("Recover from Crash")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("CryXtal Castor closed unexpectedly last time.");
+                ui.weak(&crash.report.message);
+                if let Some(project) = &crash.report.project {
+                    ui.label(format!("Project: {}", project.display()));
+                }
+                if !crash.report.last_steps.is_empty() {
+                    ui.label(format!(
+                        "Last steps: {}",
+                        crash.report.last_steps.join(", ")
+                    ));
+                }
+                ui.add_space(6.0);
+                ui.label("Attach this folder to the bug report:");
+                ui.monospace(crash.dir.display().to_string());
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(crash.snapshot().is_some(), egui::Button::new("Restore"))
+                        .on_hover_text("Open the model as it was when the crash happened")
+                        .clicked()
+                    {
+                        restore = true;
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if restore {
+            self.restore_crash_snapshot(&crash);
+        }
+        if restore || close {
+            crash.dismiss();
+            self.crash_recovery = None;
+        }
+    }
+
+    /// Opens the snapshot in place of the crashed project, unsaved, so saving writes back to
+    /// the project rather than into the bundle.
+    fn restore_crash_snapshot(&mut self, crash: &PendingCrash) {
+        let Some(snapshot) = crash.snapshot() else {
+            return;
+        };
+        self.open_project_from(snapshot);
+        self.project_path = crash.report.project.clone();
+        self.dirty = true;
+    }
+}
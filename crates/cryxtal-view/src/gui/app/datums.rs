@@ -0,0 +1,446 @@
+use cryxtal_base::{Guid, LengthUnit};
+use cryxtal_bim::{BimCategory, Constraint, Grid, ModelCommand, ParameterValue};
+use cryxtal_topology::Point3;
+use egui::Ui;
+use std::ops::RangeInclusive;
+
+use crate::elements::{base_level, set_base_level, set_storey_elevation};
+use crate::viewer::{Align2, Color32, OverlayPainter, Rect, Stroke, dashed_line};
+
+use super::CryxtalApp;
+use super::lengths::length_value;
+
+const GRID_ARC_SEGMENTS: usize = 48;
+const GRID_BUBBLE_RADIUS: f32 = 11.0;
+/// Millimetres a level or grid field moves per pixel dragged.
+const DATUM_STEP: f64 = 10.0;
+const UNBOUNDED: RangeInclusive<f64> = f64::NEG_INFINITY..=f64::INFINITY;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GridShape {
+    Line,
+    Arc,
+}
+
+/// Levels & Grids panel input: the level and grid being added, and whether grids are drawn.
+pub(super) struct DatumState {
+    pub(super) show_grids: bool,
+    level_name: String,
+    level_elevation: f64,
+    grid_label: String,
+    grid_shape: GridShape,
+    /// Line start and end; arcs use the first as their center.
+    grid_points: [[f64; 2]; 2],
+    grid_radius: f64,
+    grid_angles: [f64; 2],
+}
+
+impl Default for DatumState {
+    fn default() -> Self {
+        Self {
+            show_grids: true,
+            level_name: String::new(),
+            level_elevation: 3000.0,
+            grid_label: "A".to_string(),
+            grid_shape: GridShape::Line,
+            grid_points: [[0.0, 0.0], [0.0, 10000.0]],
+            grid_radius: 10000.0,
+            grid_angles: [0.0, 90.0],
+        }
+    }
+}
+
+impl DatumState {
+    /// Rescales the lengths typed in so far by `factor`, as when the model's unit changes.
+    pub(super) fn scale(&mut self, factor: f64) {
+        self.level_elevation *= factor;
+        for coordinate in self.grid_points.as_flattened_mut() {
+            *coordinate *= factor;
+        }
+        self.grid_radius *= factor;
+    }
+}
+
+impl CryxtalApp {
+    pub(super) fn datum_panel(&mut self, ui: &mut Ui) {
+        ui.label("Levels");
+        // Storeys are listed in model order so rows stay put while an elevation is dragged.
+        let levels: Vec<(Guid, String, f64)> = self
+            .model
+            .storeys()
+            .map(|storey| (storey.guid, storey.name.clone(), storey.elevation))
+            .collect();
+        let unit = self.model.units.length;
+        let step = DATUM_STEP * LengthUnit::Millimeter.factor_to(unit);
+        let mut moved = None;
+        for (guid, name, elevation) in &levels {
+            ui.horizontal(|ui| {
+                ui.label(name);
+                let mut value = *elevation;
+                let changed = ui
+                    .add(length_value(&mut value, unit, UNBOUNDED).speed(step))
+                    .changed();
+                if changed {
+                    moved = Some((*guid, name.clone(), value));
+                }
+            });
+        }
+        if let Some((storey, name, elevation)) = moved {
+            let label = format!("Move level {name}");
+            match set_storey_elevation(&self.model, storey, elevation) {
+                Ok(command) => {
+                    self.apply_merged_command(&label, command);
+                }
+                Err(err) => self.notify_error(format!("{label} failed: {err:#}")),
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.datums.level_name)
+                    .hint_text("Level name")
+                    .desired_width(100.0),
+            );
+            ui.add(length_value(&mut self.datums.level_elevation, unit, UNBOUNDED).speed(step));
+            if ui.button("Add Level").clicked() {
+                self.add_level();
+            }
+        });
+        if ui
+            .button("Assign Levels")
+            .on_hover_text(
+                "Move each element to the level nearest its base; elements with StoreyLocked \
+                 stay put",
+            )
+            .clicked()
+        {
+            self.assign_levels();
+        }
+
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            ui.label(format!("Grids ({})", self.model.grids.len()));
+            ui.checkbox(&mut self.datums.show_grids, "Show");
+        });
+        let mut delete = None;
+        for grid in &self.model.grids {
+            ui.horizontal(|ui| {
+                ui.label(&grid.label);
+                if ui.small_button("Delete").clicked() {
+                    delete = Some(grid.guid);
+                }
+            });
+        }
+        if let Some(guid) = delete {
+            self.apply_command("Delete grid", ModelCommand::DeleteGrid { guid });
+        }
+        self.grid_creator(ui);
+    }
+
+    fn grid_creator(&mut self, ui: &mut Ui) {
+        let unit = self.model.units.length;
+        let step = DATUM_STEP * LengthUnit::Millimeter.factor_to(unit);
+        let datums = &mut self.datums;
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut datums.grid_label)
+                    .hint_text("Label")
+                    .desired_width(48.0),
+            );
+            ui.selectable_value(&mut datums.grid_shape, GridShape::Line, "Line");
+            ui.selectable_value(&mut datums.grid_shape, GridShape::Arc, "Arc");
+        });
+        let [first, second] = &mut datums.grid_points;
+        let coordinate = |ui: &mut Ui, label: &str, point: &mut [f64; 2]| {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                for (value, axis) in point.iter_mut().zip(["x ", "y "]) {
+                    ui.add(
+                        length_value(value, unit, UNBOUNDED)
+                            .speed(step)
+                            .prefix(axis),
+                    );
+                }
+            });
+        };
+        match datums.grid_shape {
+            GridShape::Line => {
+                coordinate(ui, "Start", first);
+                coordinate(ui, "End", second);
+            }
+            GridShape::Arc => {
+                coordinate(ui, "Center", first);
+                ui.horizontal(|ui| {
+                    ui.label("Radius");
+                    ui.add(length_value(&mut datums.grid_radius, unit, UNBOUNDED).speed(step));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Angles");
+                    ui.add(egui::DragValue::new(&mut datums.grid_angles[0]).suffix("°"));
+                    ui.add(egui::DragValue::new(&mut datums.grid_angles[1]).suffix("°"));
+                });
+            }
+        }
+        if ui.button("Add Grid").clicked() {
+            self.add_grid();
+        }
+    }
+
+    /// Base level picker for the wall or column at `index`.
+    pub(super) fn element_level_panel(&mut self, ui: &mut Ui, index: usize) {
+        let Some(element) = self.model.elements().get(index) else {
+            return;
+        };
+        if !matches!(element.category, BimCategory::Wall | BimCategory::Column) {
+            return;
+        }
+        let current = base_level(element);
+        let mut next = current;
+        let levels: Vec<(Guid, String)> = self
+            .model
+            .storeys()
+            .map(|storey| (storey.guid, storey.name.clone()))
+            .collect();
+        let selected = current
+            .and_then(|guid| levels.iter().find(|(storey, _)| *storey == guid))
+            .map_or("None", |(_, name)| name.as_str());
+        ui.label("Base Level");
+        egui::ComboBox::from_id_source("base_level_combo")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut next, None, "None");
+                for (guid, name) in &levels {
+                    ui.selectable_value(&mut next, Some(*guid), name);
+                }
+            });
+        if next == current {
+            return;
+        }
+        let mut edited = element.clone();
+        let label = format!("Set base level of {}", edited.name);
+        match set_base_level(&self.model, &mut edited, next) {
+            Ok(()) => {
+                self.apply_command(
+                    &label,
+                    ModelCommand::ReplaceElements {
+                        elements: vec![edited],
+                    },
+                );
+            }
+            Err(err) => self.notify_error(format!("{label} failed: {err:#}")),
+        }
+    }
+
+    /// Top level picker for the wall at `index`: an attached top stays `Top Offset` above the
+    /// level, so the wall's height follows when the level moves.
+    pub(super) fn wall_top_level_panel(&mut self, ui: &mut Ui, index: usize) {
+        let Some(element) = self.model.elements().get(index) else {
+            return;
+        };
+        if element.category != BimCategory::Wall {
+            return;
+        }
+        let current = element
+            .constraints
+            .iter()
+            .find_map(|constraint| match constraint {
+                Constraint::TopAttached { storey, offset } => Some((*storey, *offset)),
+                _ => None,
+            });
+        let mut next = current.map(|(storey, _)| storey);
+        let mut offset = current.map_or(0.0, |(_, offset)| offset);
+        let levels: Vec<(Guid, String, f64)> = self
+            .model
+            .storeys()
+            .map(|storey| (storey.guid, storey.name.clone(), storey.elevation))
+            .collect();
+        let selected = next
+            .and_then(|guid| levels.iter().find(|(storey, ..)| *storey == guid))
+            .map_or("None", |(_, name, _)| name.as_str());
+        ui.label("Top Level");
+        egui::ComboBox::from_id_source("top_level_combo")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut next, None, "None");
+                for (guid, name, _) in &levels {
+                    ui.selectable_value(&mut next, Some(*guid), name);
+                }
+            });
+        let mut offset_changed = false;
+        if current.is_some() {
+            ui.label("Top Offset");
+            offset_changed = ui
+                .add(length_value(
+                    &mut offset,
+                    self.model.units.length,
+                    UNBOUNDED,
+                ))
+                .changed();
+        }
+        let level_changed = next != current.map(|(storey, _)| storey);
+        if !level_changed && !offset_changed {
+            return;
+        }
+        let mut edited = element.clone();
+        edited
+            .constraints
+            .retain(|constraint| !matches!(constraint, Constraint::TopAttached { .. }));
+        if let Some(storey) = next {
+            if level_changed {
+                // Attaching keeps the wall's current top.
+                let number = |key: &str| match edited.parameters.get(key) {
+                    Some(ParameterValue::Number(value)) => *value,
+                    _ => 0.0,
+                };
+                let elevation = levels
+                    .iter()
+                    .find(|(guid, ..)| *guid == storey)
+                    .map_or(0.0, |(.., elevation)| *elevation);
+                offset = number("StartZ") + number("Height") - elevation;
+            }
+            edited
+                .constraints
+                .push(Constraint::TopAttached { storey, offset });
+        }
+        let label = format!("Set top level of {}", edited.name);
+        self.apply_merged_command(
+            &label,
+            ModelCommand::ReplaceElements {
+                elements: vec![edited],
+            },
+        );
+    }
+
+    /// Grid lines, dashed at the lowest level, with a label bubble at their start.
+    pub(super) fn paint_grids(&self, painter: &mut impl OverlayPainter, rect: Rect) {
+        if !self.datums.show_grids {
+            return;
+        }
+        let z = self
+            .model
+            .default_storey()
+            .and_then(|guid| self.model.storey(guid))
+            .map_or(0.0, |storey| storey.elevation);
+        let stroke = Stroke::new(1.0, Color32::from_rgb(214, 120, 64));
+        for grid in &self.model.grids {
+            let points: Vec<_> = grid
+                .curve
+                .points(GRID_ARC_SEGMENTS)
+                .into_iter()
+                .map(|[x, y]| self.viewer.project_point3(Point3::new(x, y, z), rect))
+                .collect();
+            for pair in points.windows(2) {
+                if let [Some(start), Some(end)] = pair {
+                    dashed_line(painter, *start, *end, stroke);
+                }
+            }
+            if let Some(Some(bubble)) = points.first() {
+                painter.circle_filled(
+                    *bubble,
+                    GRID_BUBBLE_RADIUS,
+                    Color32::from_rgba_unmultiplied(12, 14, 18, 210),
+                );
+                painter.circle_stroke(*bubble, GRID_BUBBLE_RADIUS, stroke);
+                painter.text(
+                    *bubble,
+                    Align2::CenterCenter,
+                    grid.label.clone(),
+                    12.0,
+                    Color32::from_gray(235),
+                );
+            }
+        }
+    }
+
+    fn add_level(&mut self) {
+        let name = self.datums.level_name.trim().to_string();
+        if name.is_empty() {
+            self.notify_error("Level name is empty".to_string());
+            return;
+        }
+        let Some(building) = self
+            .model
+            .site
+            .buildings
+            .first()
+            .map(|building| building.guid)
+        else {
+            self.notify_error("Model has no building to add the level to".to_string());
+            return;
+        };
+        match self
+            .model
+            .add_storey(building, &name, self.datums.level_elevation)
+        {
+            Ok(_) => {
+                self.datums.level_name.clear();
+                self.dirty = true;
+                self.push_log(format!("Level {name} added"));
+            }
+            Err(err) => self.notify_error(format!("Add level failed: {err}")),
+        }
+    }
+
+    /// Moves every element onto the level its geometry stands on, in one undo step.
+    fn assign_levels(&mut self) {
+        let commands: Vec<ModelCommand> = self
+            .model
+            .storey_assignments()
+            .into_iter()
+            .map(|assignment| ModelCommand::MoveToStorey {
+                element: assignment.element,
+                storey: Some(assignment.to),
+            })
+            .collect();
+        if commands.is_empty() {
+            self.push_log("Every element is on its level".to_string());
+            return;
+        }
+        let label = format!("Assign {} element(s) to levels", commands.len());
+        if self.apply_command(&label, ModelCommand::Batch(commands)) {
+            self.push_log(label);
+        }
+    }
+
+    fn add_grid(&mut self) {
+        let datums = &self.datums;
+        let label = datums.grid_label.trim().to_string();
+        if label.is_empty() {
+            self.notify_error("Grid label is empty".to_string());
+            return;
+        }
+        if self.model.grids.iter().any(|grid| grid.label == label) {
+            self.notify_error(format!("Grid {label} already exists"));
+            return;
+        }
+        let [first, second] = datums.grid_points;
+        let grid = match datums.grid_shape {
+            GridShape::Line => Grid::line(&label, first, second),
+            GridShape::Arc => {
+                let [start, end] = datums.grid_angles;
+                Grid::arc(&label, first, datums.grid_radius, start, end)
+            }
+        };
+        match grid {
+            Ok(grid) => {
+                if self.apply_command("Add grid", ModelCommand::AddGrid(grid)) {
+                    self.datums.grid_label = next_grid_label(&label);
+                }
+            }
+            Err(err) => self.notify_error(format!("Grid failed: {err}")),
+        }
+    }
+}
+
+/// The label after `label` in its series: `A` → `B`, `3` → `4`; otherwise empty.
+fn next_grid_label(label: &str) -> String {
+    if let Ok(number) = label.parse::<u32>() {
+        return (number + 1).to_string();
+    }
+    let mut chars = label.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_alphabetic() && !matches!(letter, 'z' | 'Z') => {
+            char::from(letter as u8 + 1).to_string()
+        }
+        _ => String::new(),
+    }
+}
@@ -0,0 +1,268 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, BimElementType, ModelCommand, ParameterValue};
+use egui::Ui;
+
+use crate::elements::{
+    apply_rebar_edit, is_rebar_set, join_walls, rebar_data, rebuild_host, regenerate_column,
+    regenerate_rebar_set,
+};
+
+use super::CryxtalApp;
+
+/// The size parameter a type drives, for the categories the tools create.
+fn driving_parameter(category: BimCategory) -> Option<&'static str> {
+    match category {
+        BimCategory::Wall => Some("Thickness"),
+        BimCategory::Rebar => Some("Diameter"),
+        _ => None,
+    }
+}
+
+impl CryxtalApp {
+    /// Type picker for the wall and rebar tools. A picked type locks the tool's size to its
+    /// value; "Save as Type" turns the current size into a new type.
+    pub(super) fn tool_type_controls(&mut self, ui: &mut Ui, category: BimCategory) {
+        let Some(key) = driving_parameter(category) else {
+            return;
+        };
+        let (mut selected, value, family) = match category {
+            BimCategory::Wall => (
+                self.wall_params.type_id,
+                self.wall_params.thickness,
+                "Basic Wall",
+            ),
+            _ => (
+                self.rebar_params.type_id,
+                self.rebar_params.diameter,
+                "Rebar",
+            ),
+        };
+
+        ui.label("Type");
+        ui.horizontal(|ui| {
+            self.type_combo(ui, "tool_type_combo", category, &mut selected);
+            if ui.button("Save as Type").clicked() {
+                let element_type =
+                    BimElementType::new(format!("{value:.0}mm {family}"), category, family)
+                        .with_parameter(key, ParameterValue::Number(value));
+                let guid = element_type.guid;
+                if self.apply_command("Add type", ModelCommand::AddType(element_type)) {
+                    selected = Some(guid);
+                }
+            }
+        });
+
+        let resolved = selected
+            .and_then(|guid| self.model.element_type(guid))
+            .and_then(|element_type| Some((element_type.guid, element_type.number(key)?)));
+        let (type_id, size) = match category {
+            BimCategory::Wall => (
+                &mut self.wall_params.type_id,
+                &mut self.wall_params.thickness,
+            ),
+            _ => (
+                &mut self.rebar_params.type_id,
+                &mut self.rebar_params.diameter,
+            ),
+        };
+        *type_id = resolved.map(|(guid, _)| guid);
+        if let Some((_, value)) = resolved {
+            *size = value;
+        }
+    }
+
+    /// Links a freshly built element to the type picked in its tool.
+    pub(super) fn apply_tool_type(&self, element: &mut BimElement) {
+        let type_id = match element.category {
+            BimCategory::Wall => self.wall_params.type_id,
+            BimCategory::Rebar => self.rebar_params.type_id,
+            _ => None,
+        };
+        if let Some(element_type) = type_id.and_then(|guid| self.model.element_type(guid)) {
+            element_type.apply_to(element);
+        }
+    }
+
+    /// Type section of the properties panel: switches the element to another type, or edits
+    /// its type, which regenerates every instance.
+    pub(super) fn element_type_panel(&mut self, ui: &mut Ui, index: usize) {
+        let Some(element) = self.model.elements().get(index) else {
+            return;
+        };
+        let Some(key) = driving_parameter(element.category) else {
+            return;
+        };
+        let guid = element.guid;
+        let category = element.category;
+        let mut selected = element.type_id;
+
+        ui.label("Type");
+        if self.type_combo(ui, "element_type_combo", category, &mut selected) {
+            self.change_element_type(guid, selected);
+        }
+        let Some(element_type) = selected.and_then(|guid| self.model.element_type(guid)) else {
+            return;
+        };
+        let Some(mut value) = element_type.number(key) else {
+            return;
+        };
+        let instances = self.model.instances_of(element_type.guid).len();
+        ui.label(format!("Type {key} ({instances} instances)"));
+        let changed = ui
+            .add(
+                egui::DragValue::new(&mut value)
+                    .range(1.0..=100000.0)
+                    .speed(1.0)
+                    .fixed_decimals(1),
+            )
+            .changed();
+        if changed {
+            let edited = element_type
+                .clone()
+                .with_parameter(key, ParameterValue::Number(value));
+            self.edit_type(edited);
+        }
+    }
+
+    fn type_combo(
+        &self,
+        ui: &mut Ui,
+        id: &str,
+        category: BimCategory,
+        selected: &mut Option<Guid>,
+    ) -> bool {
+        let text = selected
+            .and_then(|guid| self.model.element_type(guid))
+            .map(|element_type| element_type.name.clone())
+            .unwrap_or_else(|| "None".to_string());
+        let mut changed = false;
+        egui::ComboBox::from_id_source(id)
+            .selected_text(text)
+            .show_ui(ui, |ui| {
+                changed |= ui.selectable_value(selected, None, "None").changed();
+                for element_type in self.model.types_for(category) {
+                    changed |= ui
+                        .selectable_value(selected, Some(element_type.guid), &element_type.name)
+                        .changed();
+                }
+            });
+        changed
+    }
+
+    /// Switches the element, and the similar elements selected with it, to `type_id`.
+    fn change_element_type(&mut self, guid: Guid, type_id: Option<Guid>) {
+        let Some(name) = self.model.element(guid).map(|element| element.name.clone()) else {
+            return;
+        };
+        let guids = match self.selection_guids() {
+            selection if selection.first() == Some(&guid) => selection,
+            _ => vec![guid],
+        };
+        let element_type = type_id.and_then(|guid| self.model.element_type(guid));
+        let edited = guids
+            .iter()
+            .filter_map(|guid| self.model.element(*guid))
+            .map(|element| {
+                let mut element = element.clone();
+                match element_type {
+                    Some(element_type) => element_type.apply_to(&mut element),
+                    None => element.type_id = None,
+                }
+                element
+            })
+            .collect();
+        let label = format!("Change type of {name}");
+        match self.regenerate_instances(edited) {
+            Ok(elements) => {
+                self.apply_command(&label, ModelCommand::ReplaceElements { elements });
+            }
+            Err(err) => self.notify_error(format!("Type change failed: {err:#}")),
+        }
+    }
+
+    fn edit_type(&mut self, element_type: BimElementType) {
+        let instances = self
+            .model
+            .instances_of(element_type.guid)
+            .into_iter()
+            .map(|instance| {
+                let mut instance = instance.clone();
+                element_type.apply_to(&mut instance);
+                instance
+            })
+            .collect();
+        let label = format!("Edit type {}", element_type.name);
+        match self.regenerate_instances(instances) {
+            Ok(elements) => {
+                let command = ModelCommand::Batch(vec![
+                    ModelCommand::ReplaceType(element_type),
+                    ModelCommand::ReplaceElements { elements },
+                ]);
+                self.apply_merged_command(&label, command);
+            }
+            Err(err) => self.notify_error(format!("Type update failed: {err:#}")),
+        }
+    }
+
+    /// Regenerates the geometry of edited elements on copies. Walls and slabs are rebuilt with
+    /// their openings and walls re-joined, so the result also carries every other wall, slab
+    /// and opening, and the lintels and sills set into walls. Categories without a builder keep
+    /// their geometry.
+    pub(super) fn regenerate_instances(
+        &self,
+        edited: Vec<BimElement>,
+    ) -> anyhow::Result<Vec<BimElement>> {
+        let mut regenerated = Vec::new();
+        let mut hosts = Vec::new();
+        for mut element in edited {
+            match element.category {
+                BimCategory::Rebar if is_rebar_set(&element) => {
+                    let host = element
+                        .host
+                        .and_then(|host| self.model.element(host))
+                        .ok_or_else(|| anyhow::anyhow!("{} has no host", element.name))?;
+                    regenerate_rebar_set(&mut element, host)?;
+                    regenerated.push(element);
+                }
+                BimCategory::Rebar => {
+                    let data = rebar_data(&element)?;
+                    apply_rebar_edit(&mut element, &data.points, data.diameter)?;
+                    regenerated.push(element);
+                }
+                BimCategory::Column => regenerated.push(regenerate_column(&self.model, &element)?),
+                BimCategory::Wall | BimCategory::Slab => hosts.push(element),
+                _ => regenerated.push(element),
+            }
+        }
+        if hosts.is_empty() {
+            return Ok(regenerated);
+        }
+
+        let mut staged: Vec<BimElement> = self
+            .model
+            .elements()
+            .iter()
+            .filter(|element| {
+                matches!(
+                    element.category,
+                    BimCategory::Wall | BimCategory::Slab | BimCategory::Opening
+                ) || !element.cuts.is_empty()
+            })
+            .map(|element| {
+                hosts
+                    .iter()
+                    .find(|host| host.guid == element.guid)
+                    .unwrap_or(element)
+                    .clone()
+            })
+            .collect();
+        for host in &hosts {
+            if let Some(index) = staged.iter().position(|element| element.guid == host.guid) {
+                rebuild_host(&mut staged, index)?;
+            }
+        }
+        join_walls(&mut staged)?;
+        regenerated.extend(staged);
+        Ok(regenerated)
+    }
+}
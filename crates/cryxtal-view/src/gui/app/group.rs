@@ -0,0 +1,196 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, ElementGroup, GroupKind, ModelCommand};
+use cryxtal_topology::{Matrix4, Point3, Vector3};
+use egui::Ui;
+use std::ops::RangeInclusive;
+use truck_base::cgmath64::SquareMatrix;
+
+use super::CryxtalApp;
+use super::commands::Clipboard;
+use super::lengths::length_value;
+
+const UNBOUNDED: RangeInclusive<f64> = f64::NEG_INFINITY..=f64::INFINITY;
+
+impl CryxtalApp {
+    /// Lists the groups the element at `index` belongs to, with ways to move or dissolve them,
+    /// to form an assembly from the element or to add it to an existing group.
+    pub(super) fn element_group_panel(&mut self, ui: &mut Ui, index: usize) {
+        let Some(element) = self.model.elements().get(index) else {
+            return;
+        };
+        let guid = element.guid;
+        // Openings and reference meshes are never members themselves, as in the context menu.
+        let groupable = element.category != BimCategory::Opening && !element.is_reference_mesh();
+        let groups: Vec<(Guid, String)> = self
+            .model
+            .groups_of(guid)
+            .into_iter()
+            .map(|group| {
+                (
+                    group.guid,
+                    format!("{}: {}", group.kind.label(), group.name),
+                )
+            })
+            .collect();
+
+        let mut dissolve = None;
+        let mut moved = None;
+        for (group, label) in &groups {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                if ui
+                    .small_button("Move")
+                    .on_hover_text("Move the group by the offset below")
+                    .clicked()
+                {
+                    moved = Some(*group);
+                }
+                if ui.small_button("Dissolve").clicked() {
+                    dissolve = Some(*group);
+                }
+            });
+        }
+        if !groups.is_empty() {
+            let unit = self.model.units.length;
+            ui.horizontal(|ui| {
+                ui.label("Offset");
+                for value in &mut self.group_offset {
+                    ui.add(length_value(value, unit, UNBOUNDED));
+                }
+            });
+        }
+        if groups.is_empty() && groupable && ui.button("Make Assembly").clicked() {
+            self.create_assembly(index);
+        }
+
+        let others: Vec<(Guid, String)> = self
+            .model
+            .groups
+            .iter()
+            .filter(|group| groups.iter().all(|(member_of, _)| *member_of != group.guid))
+            .map(|group| {
+                (
+                    group.guid,
+                    format!("{}: {}", group.kind.label(), group.name),
+                )
+            })
+            .collect();
+        let mut join = None;
+        if groupable && !others.is_empty() {
+            egui::ComboBox::from_id_source("add_to_group_combo")
+                .selected_text("Add to group")
+                .show_ui(ui, |ui| {
+                    for (group, label) in &others {
+                        if ui.selectable_label(false, label).clicked() {
+                            join = Some(*group);
+                        }
+                    }
+                });
+        }
+
+        if let Some(group) = moved {
+            self.move_group(group);
+        }
+        if let Some(group) = join {
+            self.add_to_group(group, guid);
+        }
+        if let Some(group) = dissolve {
+            self.apply_command("Dissolve group", ModelCommand::DeleteGroup { guid: group });
+        }
+    }
+
+    /// Moves every element of group `guid` by the offset set in the panel, as one step.
+    fn move_group(&mut self, guid: Guid) {
+        let [x, y, z] = self.group_offset;
+        let transform = Matrix4::from_translation(Vector3::new(x, y, z));
+        match self.model.moved_group(guid, transform) {
+            Ok(elements) => {
+                self.apply_command("Move group", ModelCommand::ReplaceElements { elements });
+            }
+            Err(err) => self.notify_error(format!("Move failed: {err}")),
+        }
+    }
+
+    /// Makes the element `member`, with its openings and hosted elements, part of group `guid`.
+    fn add_to_group(&mut self, guid: Guid, member: Guid) {
+        let Some(group) = self.model.group(guid) else {
+            return;
+        };
+        let group = group.clone().with_members([member]);
+        let label = format!("Add to {}", group.name);
+        self.apply_command(&label, ModelCommand::ReplaceGroup(group));
+    }
+
+    /// The first group the element at `index` belongs to.
+    pub(super) fn group_at(&self, index: usize) -> Option<Guid> {
+        let element = self.model.elements().get(index)?;
+        self.model
+            .groups_of(element.guid)
+            .first()
+            .map(|group| group.guid)
+    }
+
+    /// Makes the element at `index`, with its openings and hosted elements, an assembly.
+    pub(super) fn create_assembly(&mut self, index: usize) {
+        let Some(element) = self.model.elements().get(index) else {
+            return;
+        };
+        let name = format!("Assembly {}", self.model.groups.len() + 1);
+        let group = ElementGroup::new(&name, GroupKind::Assembly).with_members([element.guid]);
+        if self.apply_command("Create assembly", ModelCommand::AddGroup(group)) {
+            self.push_log(format!("Created {name}"));
+        }
+    }
+
+    /// Hides everything outside the group the element at `index` belongs to.
+    pub(super) fn isolate_group(&mut self, index: usize) {
+        let Some(members) = self
+            .group_at(index)
+            .and_then(|group| self.model.group_elements(group).ok())
+        else {
+            return;
+        };
+        self.hidden = self
+            .model
+            .elements()
+            .iter()
+            .map(|element| element.guid)
+            .filter(|guid| !members.contains(guid))
+            .collect();
+    }
+
+    /// Puts the whole group of the element at `index` on the clipboard.
+    pub(super) fn copy_group(&mut self, index: usize, anchor: Point3) {
+        let Some(group) = self.group_at(index) else {
+            return;
+        };
+        match self.model.copy_group(group, Matrix4::identity()) {
+            Ok((group, elements)) => {
+                self.push_log(format!("Copied {}", group.name));
+                self.clipboard = Some(Clipboard {
+                    elements,
+                    group: Some(group),
+                    anchor,
+                });
+            }
+            Err(err) => self.notify_error(format!("Copy failed: {err}")),
+        }
+    }
+
+    /// Adds pasted group members and the group holding them as one step. Members keep their
+    /// layers and are not joined to the walls around them.
+    pub(super) fn paste_group(&mut self, elements: Vec<BimElement>, group: ElementGroup) {
+        let Some(storey) = self.model.default_storey() else {
+            self.notify_error("Model has no storey to place elements on".to_string());
+            return;
+        };
+        let name = group.name.clone();
+        let command = ModelCommand::Batch(vec![
+            ModelCommand::AddElements { storey, elements },
+            ModelCommand::AddGroup(group),
+        ]);
+        if self.apply_command("Paste group", command) {
+            self.push_log(format!("Pasted {name}"));
+        }
+    }
+}
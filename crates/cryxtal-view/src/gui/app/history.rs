@@ -0,0 +1,255 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, BimModel, ModelCommand, parameter_schema};
+use std::collections::HashMap;
+
+use crate::elements::{join_walls, regenerate_constrained};
+
+use super::CryxtalApp;
+
+impl CryxtalApp {
+    /// Applies an undoable model edit and refreshes the scene. Failures are reported and leave
+    /// the model untouched.
+    pub(super) fn apply_command(&mut self, label: &str, command: ModelCommand) -> bool {
+        self.record_command(label, command, false)
+    }
+
+    /// Like [`CryxtalApp::apply_command`], but consecutive edits with the same label (dragging a
+    /// property value) collapse into one undo step.
+    pub(super) fn apply_merged_command(&mut self, label: &str, command: ModelCommand) -> bool {
+        self.record_command(label, command, true)
+    }
+
+    fn record_command(&mut self, label: &str, command: ModelCommand, merge: bool) -> bool {
+        if !self.allows_edit(label, &command) {
+            return false;
+        }
+        let command = match self.with_regenerated_parameters(command) {
+            Ok(command) => command,
+            Err(err) => {
+                self.notify_error(format!("{label} failed: {err:#}"));
+                return false;
+            }
+        };
+        let command = self.with_hosted_rebar_sets(command);
+        let command = match self.with_constraints(command) {
+            Ok(command) => command,
+            Err(err) => {
+                self.notify_error(format!("{label} failed: {err}"));
+                return false;
+            }
+        };
+        let command = self.with_wall_joins(command);
+        let command = match self.with_lintels(command) {
+            Ok(command) => command,
+            Err(err) => {
+                self.notify_error(format!("{label} failed: {err:#}"));
+                return false;
+            }
+        };
+        // Edits pushing openings out of a shortened wall wait for the user to resolve them.
+        let Some(command) = self.hold_opening_conflict(label, command, merge) else {
+            return false;
+        };
+        self.commit_command(label, command, merge)
+    }
+
+    /// Applies a fully expanded `command` to the model and history.
+    pub(super) fn commit_command(
+        &mut self,
+        label: &str,
+        command: ModelCommand,
+        merge: bool,
+    ) -> bool {
+        let result = if merge {
+            self.history.apply_merged(&mut self.model, label, command)
+        } else {
+            self.history.apply(&mut self.model, label, command)
+        };
+        match result {
+            Ok(()) => {
+                self.rebuild_scene();
+                true
+            }
+            Err(err) => {
+                self.notify_error(format!("{label} failed: {err}"));
+                false
+            }
+        }
+    }
+
+    /// Adds the elements whose driving parameters `command` sets, rebuilt with the new values,
+    /// so a `Thickness` set from a parameter sheet or a script resizes the wall in the same undo
+    /// step. Driving parameters are those of the category's [`parameter_schema`].
+    fn with_regenerated_parameters(&self, command: ModelCommand) -> anyhow::Result<ModelCommand> {
+        let mut edited = HashMap::new();
+        collect_parameter_edits(&self.model, &command, &mut edited);
+        if edited.is_empty() {
+            return Ok(command);
+        }
+        let elements = self.regenerate_instances(edited.into_values().collect())?;
+        Ok(ModelCommand::Batch(vec![
+            command,
+            ModelCommand::ReplaceElements { elements },
+        ]))
+    }
+
+    /// Adds the elements whose [`cryxtal_bim::Constraint`]s read what `command` changes,
+    /// re-solved and regenerated, so walls attached to a moved level and openings centered in a
+    /// resized wall follow in the same undo step. The model is only staged when such a
+    /// constraint exists. A constraint that can no longer hold, such as a wall top attached
+    /// below its base, is reported and left unsolved; the edit itself goes through.
+    fn with_constraints(&mut self, command: ModelCommand) -> anyhow::Result<ModelCommand> {
+        let changed = command.targets();
+        let mut edited = Vec::new();
+        collect_edited(&command, &mut edited);
+        let touched = self.model.elements().iter().chain(edited).any(|element| {
+            element
+                .constraints
+                .iter()
+                .any(|constraint| constraint.depends_on(element, &changed))
+        });
+        if !touched {
+            return Ok(command);
+        }
+        let mut staged = self.model.clone();
+        if command.clone().apply(&mut staged).is_err() {
+            // The history reports the failure when the command is applied for real.
+            return Ok(command);
+        }
+        let (elements, failures) = regenerate_constrained(&staged, &changed)?;
+        for failure in failures {
+            self.notify_warning(failure);
+        }
+        if elements.is_empty() {
+            return Ok(command);
+        }
+        Ok(ModelCommand::Batch(vec![
+            command,
+            ModelCommand::ReplaceElements { elements },
+        ]))
+    }
+
+    /// Adds the walls whose corners `command` changes, re-joined and rebuilt, so the join
+    /// between two walls follows when one of them is added, moved, resized or deleted. The model
+    /// is only staged when the command touches a wall. A join that cannot be rebuilt is reported
+    /// and the edit goes through with the corners as they were.
+    fn with_wall_joins(&mut self, command: ModelCommand) -> ModelCommand {
+        let mut edited = Vec::new();
+        collect_edited(&command, &mut edited);
+        let touches_wall = edited
+            .iter()
+            .any(|element| element.category == BimCategory::Wall)
+            || command.targets().into_iter().any(|guid| {
+                self.model
+                    .element(guid)
+                    .is_some_and(|element| element.category == BimCategory::Wall)
+            });
+        if !touches_wall {
+            return command;
+        }
+        let mut staged = self.model.clone();
+        if command.clone().apply(&mut staged).is_err() {
+            // The history reports the failure when the command is applied for real.
+            return command;
+        }
+        let mut elements = staged.elements().to_vec();
+        if let Err(err) = join_walls(&mut elements) {
+            self.notify_warning(format!("Wall join failed: {err:#}"));
+            return command;
+        }
+        // Joining only rewrites the end extensions of the walls it rebuilds.
+        let joined: Vec<BimElement> = elements
+            .into_iter()
+            .zip(staged.elements())
+            .filter(|(joined, staged)| joined.parameters != staged.parameters)
+            .map(|(joined, _)| joined)
+            .collect();
+        if joined.is_empty() {
+            return command;
+        }
+        ModelCommand::Batch(vec![
+            command,
+            ModelCommand::ReplaceElements { elements: joined },
+        ])
+    }
+
+    pub(super) fn undo(&mut self) {
+        if self.history.undo_label().is_some() && !self.can_undo() {
+            self.notify_warning("Undo is not available in read-only mode".to_string());
+            return;
+        }
+        match self.history.undo(&mut self.model) {
+            Ok(Some(label)) => self.after_history_step(format!("Undo {label}")),
+            Ok(None) => {}
+            Err(err) => self.notify_error(format!("Undo failed: {err}")),
+        }
+    }
+
+    pub(super) fn redo(&mut self) {
+        if self.history.redo_label().is_some() && !self.can_redo() {
+            self.notify_warning("Redo is not available in read-only mode".to_string());
+            return;
+        }
+        match self.history.redo(&mut self.model) {
+            Ok(Some(label)) => self.after_history_step(format!("Redo {label}")),
+            Ok(None) => {}
+            Err(err) => self.notify_error(format!("Redo failed: {err}")),
+        }
+    }
+
+    /// Element indices shift when steps re-add or remove elements, so selection is dropped.
+    fn after_history_step(&mut self, message: String) {
+        self.set_selected(None);
+        self.rebuild_scene();
+        self.push_log(message);
+    }
+}
+
+/// Copies of the elements `command` sets driving parameters on, with the values set.
+fn collect_parameter_edits(
+    model: &BimModel,
+    command: &ModelCommand,
+    edited: &mut HashMap<Guid, BimElement>,
+) {
+    match command {
+        ModelCommand::SetParameter { guid, name, value } => {
+            let Some(current) = model.element(*guid) else {
+                return;
+            };
+            if !parameter_schema(current.category)
+                .iter()
+                .any(|spec| spec.key == name)
+            {
+                return;
+            }
+            let element = edited.entry(*guid).or_insert_with(|| current.clone());
+            match value {
+                Some(value) => element.insert_parameter(name.clone(), value.clone()),
+                None => {
+                    element.parameters.remove(name);
+                }
+            }
+        }
+        ModelCommand::Batch(commands) => {
+            for command in commands {
+                collect_parameter_edits(model, command, edited);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Elements `command` adds or swaps in, which may carry constraints the model does not have yet.
+pub(super) fn collect_edited<'a>(command: &'a ModelCommand, edited: &mut Vec<&'a BimElement>) {
+    match command {
+        ModelCommand::AddElements { elements, .. } | ModelCommand::ReplaceElements { elements } => {
+            edited.extend(elements);
+        }
+        ModelCommand::Batch(commands) => {
+            for command in commands {
+                collect_edited(command, edited);
+            }
+        }
+        _ => {}
+    }
+}
@@ -1,7 +1,7 @@
-use cryxtal_bim::{BimCategory, ParameterValue};
+use cryxtal_bim::{BimCategory, hosted_openings};
 use cryxtal_topology::Point3;
 
-use crate::elements::opening_index_at_point;
+use crate::elements::opening_at_point;
 use crate::viewer::Rect;
 
 use super::CryxtalApp;
@@ -29,7 +29,7 @@ impl CryxtalApp {
             return;
         };
 
-        let Some(element) = self.elements.get(index) else {
+        let Some(element) = self.model.elements().get(index) else {
             self.hovered = None;
             return;
         };
@@ -41,52 +41,13 @@ impl CryxtalApp {
 
         if element.category == BimCategory::Wall {
             let world_point = Point3::new(hit_point.x, hit_point.y, hit_point.z);
-            if let Ok(Some(opening_index)) = opening_index_at_point(element, world_point) {
-                if let Some(opening_element) =
-                    self.find_opening_element_index(index, opening_index)
-                {
-                    self.hovered = Some(opening_element);
-                    return;
-                }
+            let hosted = hosted_openings(self.model.elements(), element.guid);
+            if let Ok(Some(opening_element)) = opening_at_point(element, &hosted, world_point) {
+                self.hovered = Some(opening_element);
+                return;
             }
         }
 
         self.hovered = Some(index);
     }
-
-    fn find_opening_element_index(&self, host_index: usize, opening_index: usize) -> Option<usize> {
-        let host_guid = self.elements.get(host_index)?.guid.to_string();
-        self.elements
-            .iter()
-            .enumerate()
-            .find_map(|(idx, element)| {
-                if element.category != BimCategory::Opening {
-                    return None;
-                }
-                let matches_opening = match element.parameters.get("OpeningIndex") {
-                    Some(ParameterValue::Integer(value)) if *value > 0 => {
-                        *value as usize == opening_index
-                    }
-                    _ => false,
-                };
-                if !matches_opening {
-                    return None;
-                }
-                let guid_match = match element.parameters.get("HostGuid") {
-                    Some(ParameterValue::Text(value)) => value == &host_guid,
-                    _ => false,
-                };
-                let index_match = match element.parameters.get("HostIndex") {
-                    Some(ParameterValue::Integer(value)) if *value >= 0 => {
-                        *value as usize == host_index
-                    }
-                    _ => false,
-                };
-                if guid_match || index_match {
-                    Some(idx)
-                } else {
-                    None
-                }
-            })
-    }
 }
@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::viewer::{Align2, Color32, OverlayPainter, Rect, RenderStats, Vec2};
+
+const FRAME_HISTORY: usize = 120;
+const HUD_LINE_HEIGHT: f32 = 15.0;
+const HUD_WIDTH: f32 = 230.0;
+
+#[derive(Default)]
+pub(super) struct FrameStats {
+    frame_times: VecDeque<f64>,
+    last_tessellation: Option<Duration>,
+}
+
+impl FrameStats {
+    pub(super) fn record_frame(&mut self, dt: f64) {
+        if !dt.is_finite() || dt <= 0.0 {
+            return;
+        }
+        if self.frame_times.len() >= FRAME_HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(dt);
+    }
+
+    pub(super) fn record_tessellation(&mut self, duration: Duration) {
+        self.last_tessellation = Some(duration);
+    }
+
+    fn average_frame_time(&self) -> Option<f64> {
+        if self.frame_times.is_empty() {
+            return None;
+        }
+        Some(self.frame_times.iter().sum::<f64>() / self.frame_times.len() as f64)
+    }
+
+    fn worst_frame_time(&self) -> Option<f64> {
+        self.frame_times.iter().copied().reduce(f64::max)
+    }
+}
+
+pub(super) fn paint_hud(
+    painter: &mut impl OverlayPainter,
+    rect: Rect,
+    frame: &FrameStats,
+    render: RenderStats,
+) {
+    let mut lines = Vec::new();
+    match frame.average_frame_time() {
+        Some(avg) => {
+            lines.push(format!("FPS: {:.0} ({:.1} ms)", 1.0 / avg, avg * 1000.0));
+        }
+        None => lines.push("FPS: -".to_string()),
+    }
+    if let Some(worst) = frame.worst_frame_time() {
+        lines.push(format!("Worst frame: {:.1} ms", worst * 1000.0));
+    }
+    lines.push(format!(
+        "GPU submit: {:.2} ms",
+        render.render_time.as_secs_f64() * 1000.0
+    ));
+    match frame.last_tessellation {
+        Some(duration) => lines.push(format!(
+            "Tessellation: {:.1} ms",
+            duration.as_secs_f64() * 1000.0
+        )),
+        None => lines.push("Tessellation: -".to_string()),
+    }
+    lines.push(format!("Instances: {}", render.instances));
+    lines.push(format!("Draw calls: {}", render.draw_calls));
+    lines.push(format!("Triangles: {}", render.triangles));
+    lines.push(format!(
+        "Mesh buffers: {}",
+        format_bytes(render.buffer_bytes)
+    ));
+
+    let origin = rect.left_top() + Vec2::new(8.0, 28.0);
+    let height = lines.len() as f32 * HUD_LINE_HEIGHT + 8.0;
+    let background = Rect::from_min_size(origin, Vec2::new(HUD_WIDTH, height));
+    painter.rect_filled(
+        background,
+        4.0,
+        Color32::from_rgba_unmultiplied(12, 14, 18, 190),
+    );
+
+    for (idx, line) in lines.into_iter().enumerate() {
+        painter.text(
+            origin + Vec2::new(6.0, 4.0 + idx as f32 * HUD_LINE_HEIGHT),
+            Align2::LeftTop,
+            line,
+            12.0,
+            Color32::from_rgb(170, 220, 160),
+        );
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    let bytes = bytes as f64;
+    if bytes >= 1024.0 * 1024.0 {
+        format!("{:.1} MiB", bytes / (1024.0 * 1024.0))
+    } else if bytes >= 1024.0 {
+        format!("{:.1} KiB", bytes / 1024.0)
+    } else {
+        format!("{bytes:.0} B")
+    }
+}
@@ -0,0 +1,197 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimModel, Issue, IssueComment, ModelCommand};
+
+use super::CryxtalApp;
+use super::commands::AppCommand;
+
+/// Issues window: review issues raised on elements, with their comments. Issues are
+/// annotations, so reviewers raise and discuss them in read-only sessions too.
+#[derive(Default)]
+pub(super) struct IssuesWindow {
+    /// Title of the next issue raised.
+    title: String,
+    /// Comment being written, and the issue it is for.
+    comment: (Option<Guid>, String),
+    show_closed: bool,
+}
+
+/// What the window asked for this frame, applied once it is drawn.
+enum IssueAction {
+    Raise(String),
+    Comment(Guid, String),
+    SetClosed(Guid, bool),
+    Delete(Guid),
+    Show(usize),
+}
+
+impl CryxtalApp {
+    pub(super) fn open_issues(&mut self) {
+        self.issues.get_or_insert_with(IssuesWindow::default);
+    }
+
+    pub(super) fn issues_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = self.issues.as_mut() else {
+            return;
+        };
+        let selected = self
+            .selected
+            .and_then(|index| self.model.elements().get(index))
+            .map(|element| element.name.clone());
+
+        let mut open = true;
+        let mut action = None;
+        egui::Window::new("Issues")
+            .open(&mut open)
+            .collapsible(true)
+            .resizable(true)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut window.title)
+                            .hint_text("New issue")
+                            .desired_width(220.0),
+                    );
+                    let raise = ui
+                        .add_enabled(!window.title.trim().is_empty(), egui::Button::new("Raise"))
+                        .on_hover_text(match &selected {
+                            Some(name) => format!("Raise the issue on {name}"),
+                            None => "Raise the issue on the model".to_string(),
+                        });
+                    if raise.clicked() {
+                        action = Some(IssueAction::Raise(std::mem::take(&mut window.title)));
+                    }
+                });
+                ui.checkbox(&mut window.show_closed, "Show closed issues");
+                ui.separator();
+
+                let show_closed = window.show_closed;
+                let issues = self
+                    .model
+                    .issues
+                    .iter()
+                    .filter(|issue| show_closed || !issue.closed);
+                egui::ScrollArea::vertical()
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        for issue in issues {
+                            let status = if issue.closed { "closed" } else { "open" };
+                            egui::CollapsingHeader::new(format!("{} ({status})", issue.title))
+                                .id_salt(issue.guid)
+                                .show(ui, |ui| {
+                                    let comment = &mut window.comment;
+                                    if let Some(chosen) =
+                                        issue_body(ui, &self.model, issue, comment)
+                                    {
+                                        action = Some(chosen);
+                                    }
+                                });
+                        }
+                    });
+            });
+
+        if !open {
+            self.issues = None;
+            return;
+        }
+        match action {
+            Some(IssueAction::Raise(title)) => self.raise_issue(title),
+            Some(IssueAction::Comment(guid, text)) => {
+                let author = self.model.author.clone();
+                self.edit_issue(guid, "Comment on issue", |issue| {
+                    issue.comments.push(IssueComment {
+                        author,
+                        text: text.trim().to_string(),
+                    });
+                });
+            }
+            Some(IssueAction::SetClosed(guid, closed)) => {
+                let label = if closed {
+                    "Close issue"
+                } else {
+                    "Reopen issue"
+                };
+                self.edit_issue(guid, label, |issue| issue.closed = closed);
+            }
+            Some(IssueAction::Delete(guid)) => {
+                self.apply_command("Delete issue", ModelCommand::DeleteIssue { guid });
+            }
+            Some(IssueAction::Show(index)) => {
+                self.set_selected(Some(index));
+                self.execute(AppCommand::ZoomTo(index));
+            }
+            None => {}
+        }
+    }
+
+    /// Raises an issue titled `title` on the selected element, or on the model as a whole.
+    fn raise_issue(&mut self, title: String) {
+        let element = self
+            .selected
+            .and_then(|index| self.model.elements().get(index))
+            .map(|element| element.guid);
+        let issue = Issue::new(title.trim(), element);
+        let label = format!("Raise issue {}", issue.title);
+        self.apply_command(&label, ModelCommand::AddIssue(issue));
+    }
+
+    fn edit_issue(&mut self, guid: Guid, label: &str, edit: impl FnOnce(&mut Issue)) {
+        let Some(issue) = self.model.issue(guid) else {
+            return;
+        };
+        let mut issue = issue.clone();
+        edit(&mut issue);
+        self.apply_command(label, ModelCommand::ReplaceIssue(issue));
+    }
+}
+
+/// Elements, comments and actions of `issue`. `comment` is the comment being written and the
+/// issue it is for, so typing under one issue leaves the others empty.
+fn issue_body(
+    ui: &mut egui::Ui,
+    model: &BimModel,
+    issue: &Issue,
+    comment: &mut (Option<Guid>, String),
+) -> Option<IssueAction> {
+    let mut action = None;
+    ui.horizontal_wrapped(|ui| {
+        for guid in &issue.elements {
+            let Some(index) = model.element_index(*guid) else {
+                continue;
+            };
+            if ui.link(&model.elements()[index].name).clicked() {
+                action = Some(IssueAction::Show(index));
+            }
+        }
+    });
+    for comment in &issue.comments {
+        ui.label(format!("{}: {}", comment.author, comment.text));
+    }
+    let mut text = match comment {
+        (Some(guid), text) if *guid == issue.guid => text.clone(),
+        _ => String::new(),
+    };
+    let edit = ui.add(
+        egui::TextEdit::multiline(&mut text)
+            .hint_text("Comment")
+            .desired_rows(2),
+    );
+    if edit.changed() {
+        *comment = (Some(issue.guid), text.clone());
+    }
+    ui.horizontal(|ui| {
+        let send = ui.add_enabled(!text.trim().is_empty(), egui::Button::new("Comment"));
+        if send.clicked() {
+            *comment = (None, String::new());
+            action = Some(IssueAction::Comment(issue.guid, text));
+        }
+        let toggle = if issue.closed { "Reopen" } else { "Close" };
+        if ui.button(toggle).clicked() {
+            action = Some(IssueAction::SetClosed(issue.guid, !issue.closed));
+        }
+        if ui.button("Delete").clicked() {
+            action = Some(IssueAction::Delete(issue.guid));
+        }
+    });
+    action
+}
@@ -0,0 +1,64 @@
+use cryxtal_io::{export_journal, import_journal};
+use std::path::Path;
+
+use super::CryxtalApp;
+
+impl CryxtalApp {
+    /// Writes the commands applied since the project was opened, to attach to a bug report
+    /// with the project file they started from.
+    pub(super) fn export_journal_to(&mut self, path: &Path) {
+        let journal = self.history.journal();
+        if journal.is_empty() {
+            self.notify_warning("Nothing has been edited in this session yet".to_string());
+            return;
+        }
+        let count = journal.entries.len();
+        match export_journal(journal, path) {
+            Ok(()) => self.push_log(format!("Exported {count} step(s) to {}", path.display())),
+            Err(err) => self.notify_error(format!("Journal export failed: {err:#}")),
+        }
+    }
+
+    /// Applies the journal at `path` to the open model, which must be the one the session
+    /// started from. Each entry becomes an undo step; replay stops at the first that fails.
+    pub(super) fn replay_journal_from(&mut self, path: &Path) {
+        let journal = match import_journal(path) {
+            Ok(journal) => journal,
+            Err(err) => {
+                self.notify_error(format!("Journal replay failed: {err:#}"));
+                return;
+            }
+        };
+        if self.model.revision() != journal.base_revision {
+            self.notify_error(format!(
+                "The journal starts from revision {} of {}; this model is at revision {}",
+                journal.base_revision,
+                journal.model,
+                self.model.revision()
+            ));
+            return;
+        }
+        let total = journal.entries.len();
+        let mut applied = 0;
+        for (index, entry) in journal.entries.into_iter().enumerate() {
+            let label = entry.label.clone();
+            if let Err(err) = self
+                .history
+                .apply(&mut self.model, entry.label, entry.command)
+            {
+                self.notify_error(format!(
+                    "Journal step {} ({label}) failed: {err}",
+                    index + 1
+                ));
+                break;
+            }
+            applied += 1;
+        }
+        self.set_selected(None);
+        self.rebuild_scene();
+        self.push_log(format!(
+            "Replayed {applied} of {total} step(s) from {}",
+            path.display()
+        ));
+    }
+}
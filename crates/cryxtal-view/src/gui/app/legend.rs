@@ -0,0 +1,50 @@
+use cryxtal_bim::LegendEntry;
+
+use crate::viewer::{Align2, Color32, OverlayPainter, Point2, Rect, Stroke, Vec2};
+
+const LEGEND_LINE_HEIGHT: f32 = 18.0;
+const LEGEND_SWATCH: f32 = 12.0;
+const LEGEND_WIDTH: f32 = 200.0;
+
+/// Legend of the active named view in the bottom-left corner of the viewport, so screenshots
+/// explain their colors.
+pub(super) fn paint_legend(
+    painter: &mut impl OverlayPainter,
+    rect: Rect,
+    title: &str,
+    entries: &[LegendEntry],
+) {
+    if entries.is_empty() {
+        return;
+    }
+    let height = (entries.len() + 1) as f32 * LEGEND_LINE_HEIGHT + 8.0;
+    let origin = Point2::new(rect.min.x + 8.0, rect.max.y - height - 8.0);
+    let background = Rect::from_min_size(origin, Vec2::new(LEGEND_WIDTH, height));
+    painter.rect_filled(
+        background,
+        4.0,
+        Color32::from_rgba_unmultiplied(12, 14, 18, 190),
+    );
+    painter.text(
+        origin + Vec2::new(6.0, 4.0),
+        Align2::LeftTop,
+        title.to_string(),
+        13.0,
+        Color32::from_gray(235),
+    );
+
+    for (idx, entry) in entries.iter().enumerate() {
+        let top = origin + Vec2::new(6.0, 4.0 + (idx + 1) as f32 * LEGEND_LINE_HEIGHT);
+        let [r, g, b, a] = entry.color;
+        let swatch = Rect::from_min_size(top, Vec2::new(LEGEND_SWATCH, LEGEND_SWATCH));
+        painter.rect_filled(swatch, 2.0, Color32::from_rgba_unmultiplied(r, g, b, a));
+        painter.rect_stroke(swatch, 2.0, Stroke::new(1.0, Color32::from_gray(90)));
+        painter.text(
+            top + Vec2::new(LEGEND_SWATCH + 6.0, 0.0),
+            Align2::LeftTop,
+            entry.label.clone(),
+            12.0,
+            Color32::from_gray(220),
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use cryxtal_base::{LengthUnit, format_length};
+use cryxtal_bim::{BimElement, ParameterUnit, ParameterValue};
+use std::ops::RangeInclusive;
+
+use crate::elements::{LintelRules, RebarShape};
+
+use super::CryxtalApp;
+
+/// Field for a length in the model's `unit`, suffixed with its symbol. `range_mm` is given in
+/// millimetres, as the tool defaults are, and dragging moves about a millimetre per pixel.
+pub(super) fn length_value(
+    value: &mut f64,
+    unit: LengthUnit,
+    range_mm: RangeInclusive<f64>,
+) -> egui::DragValue<'_> {
+    let factor = LengthUnit::Millimeter.factor_to(unit);
+    let decimals = match unit {
+        LengthUnit::Millimeter => 1,
+        LengthUnit::Inch => 3,
+        LengthUnit::Meter | LengthUnit::Foot => 4,
+    };
+    egui::DragValue::new(value)
+        .range(range_mm.start() * factor..=range_mm.end() * factor)
+        .speed(factor)
+        .min_decimals(0)
+        .max_decimals(decimals)
+        .suffix(format!(" {}", unit.symbol()))
+}
+
+/// `value` of parameter `key` as the properties list shows it: lengths in `unit` with its
+/// symbol, angles in degrees.
+pub(super) fn parameter_text(
+    element: &BimElement,
+    key: &str,
+    value: &ParameterValue,
+    unit: LengthUnit,
+) -> String {
+    match (value, element.parameter_unit(key)) {
+        (ParameterValue::Number(number), ParameterUnit::Length) => format_length(*number, unit),
+        (ParameterValue::Number(number), ParameterUnit::Degrees) => format!("{number}°"),
+        _ => value.to_string(),
+    }
+}
+
+impl CryxtalApp {
+    /// Rescales the tool settings, kept in the model's length unit, after the model changed
+    /// from `from` to its current unit, so a wall drawn next is as thick as before.
+    pub(super) fn adopt_length_unit(&mut self, from: LengthUnit) {
+        let factor = from.factor_to(self.model.units.length);
+        if factor == 1.0 {
+            return;
+        }
+        for value in [
+            &mut self.wall_params.thickness,
+            &mut self.wall_params.height,
+            &mut self.opening_params.width,
+            &mut self.opening_params.height,
+            &mut self.slab_params.thickness,
+            &mut self.rebar_params.diameter,
+            &mut self.rebar_params.set_spacing,
+            &mut self.rebar_params.set_cover,
+            &mut self.weld_draft.size,
+        ] {
+            *value *= factor;
+        }
+        let shape = self.rebar_params.set_shape;
+        if let Some(dimension) = shape.dimension()
+            && let Some(scaled) = RebarShape::from_label(shape.label(), dimension * factor)
+        {
+            self.rebar_params.set_shape = scaled;
+        }
+        for value in &mut self.group_offset {
+            *value *= factor;
+        }
+        self.datums.scale(factor);
+        if let Some(rules) = self.lintel_rules.as_mut() {
+            scale_lintel_rules(rules, factor);
+        }
+    }
+}
+
+pub(super) fn scale_lintel_rules(rules: &mut LintelRules, factor: f64) {
+    for value in [
+        &mut rules.min_width,
+        &mut rules.lintel_depth,
+        &mut rules.bearing,
+        &mut rules.sill_depth,
+        &mut rules.sill_extension,
+    ] {
+        *value *= factor;
+    }
+}
@@ -0,0 +1,273 @@
+use cryxtal_base::{Guid, LengthUnit};
+use cryxtal_bim::{BimCategory, BimElement, ModelCommand, Opening};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::elements::{LintelRules, build_lintels, rebuild_host, regenerate_lintels};
+
+use super::CryxtalApp;
+use super::history::collect_edited;
+use super::lengths::{length_value, scale_lintel_rules};
+
+impl CryxtalApp {
+    pub(super) fn open_lintel_rules(&mut self) {
+        let factor = LengthUnit::Millimeter.factor_to(self.model.units.length);
+        self.lintel_rules.get_or_insert_with(|| {
+            let mut rules = LintelRules::default();
+            scale_lintel_rules(&mut rules, factor);
+            rules
+        });
+    }
+
+    pub(super) fn lintel_rules_window(&mut self, ctx: &egui::Context) {
+        let unit = self.model.units.length;
+        let Some(rules) = self.lintel_rules.as_mut() else {
+            return;
+        };
+        let mut open = true;
+        let mut generate = false;
+        egui::Window::new("Lintels and Sills")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("Generated for every wall opening at least the minimum width.");
+                ui.label("Running again replaces the ones generated before.");
+                egui::Grid::new("lintel_rules").show(ui, |ui| {
+                    let rows = [
+                        ("Minimum opening width", &mut rules.min_width),
+                        ("Lintel depth", &mut rules.lintel_depth),
+                        ("Lintel bearing", &mut rules.bearing),
+                        ("Sill depth (0: none)", &mut rules.sill_depth),
+                        ("Sill extension", &mut rules.sill_extension),
+                    ];
+                    for (label, value) in rows {
+                        ui.label(label);
+                        ui.add(length_value(value, unit, 0.0..=10_000.0));
+                        ui.end_row();
+                    }
+                });
+                ui.add_space(6.0);
+                if ui.button("Generate").clicked() {
+                    generate = true;
+                }
+            });
+
+        if generate {
+            let rules = *rules;
+            self.generate_lintels(&rules);
+        }
+        if !open {
+            self.lintel_rules = None;
+        }
+    }
+
+    /// Replaces the lintels and sills of every wall opening by ones built from `rules`, in one
+    /// undo step. Each lands on its wall's storey.
+    fn generate_lintels(&mut self, rules: &LintelRules) {
+        let mut commands = Vec::new();
+        let mut count = 0;
+        let mut failure = None;
+        for wall in self
+            .model
+            .elements()
+            .iter()
+            .filter(|wall| wall.category == BimCategory::Wall)
+        {
+            let openings: Vec<Opening> = self
+                .model
+                .hosted_openings(wall.guid)
+                .into_iter()
+                .map(|(_, opening)| opening)
+                .collect();
+            if openings.is_empty() {
+                continue;
+            }
+            let generated = match build_lintels(wall, &openings, rules) {
+                Ok(generated) => generated,
+                Err(err) => {
+                    failure = Some(format!("Lintels for {} failed: {err:#}", wall.name));
+                    break;
+                }
+            };
+            let Some(storey) = self
+                .model
+                .storey_of(wall.guid)
+                .map(|storey| storey.guid)
+                .or(self.model.default_storey())
+            else {
+                continue;
+            };
+            for opening in &openings {
+                commands.extend(
+                    self.model
+                        .hosted_elements(opening.guid)
+                        .into_iter()
+                        .filter(|element| element.removed_with_host)
+                        .map(|element| ModelCommand::DeleteElement { guid: element.guid }),
+                );
+            }
+            count += generated.len();
+            if !generated.is_empty() {
+                commands.push(ModelCommand::AddElements {
+                    storey,
+                    elements: generated,
+                });
+            }
+        }
+        if let Some(failure) = failure {
+            self.notify_error(failure);
+            return;
+        }
+        if commands.is_empty() {
+            self.push_log("No wall openings to generate lintels for".to_string());
+            return;
+        }
+        if self.apply_command("Generate lintels", ModelCommand::Batch(commands)) {
+            self.push_log(format!("Generated {count} lintel(s) and sill(s)"));
+        }
+    }
+
+    /// Adds the lintels and sills of the openings `command` moves or resizes, or whose walls it
+    /// changes, rebuilt from the rules they were generated with, and recuts the walls whose
+    /// lintels and sills change, so they follow in the same undo step. The model is only staged
+    /// when it or `command` has lintels or sills. Ones that no longer build are reported and
+    /// left as they are.
+    pub(super) fn with_lintels(&mut self, command: ModelCommand) -> anyhow::Result<ModelCommand> {
+        let mut edited = Vec::new();
+        collect_edited(&command, &mut edited);
+        if !self.model.elements().iter().chain(edited).any(is_embedded) {
+            return Ok(command);
+        }
+        let mut staged = self.model.clone();
+        if command.clone().apply(&mut staged).is_err() {
+            // The history reports the failure when the command is applied for real.
+            return Ok(command);
+        }
+
+        let mut lintels = Vec::new();
+        let mut failures = Vec::new();
+        let mut generated: BTreeMap<Guid, Vec<&BimElement>> = BTreeMap::new();
+        for element in staged.elements() {
+            if let Some(opening) = element.host
+                && LintelRules::of(element).is_some()
+            {
+                generated.entry(opening).or_default().push(element);
+            }
+        }
+        for (guid, existing) in generated {
+            let Some(element) = staged.element(guid) else {
+                continue;
+            };
+            let Some(opening) = element.opening else {
+                continue;
+            };
+            let Some(wall) = staged.element(opening.host) else {
+                continue;
+            };
+            let moved = self.model.element(guid).and_then(|before| before.opening) != Some(opening);
+            let wall_changed = self
+                .model
+                .element(wall.guid)
+                .is_none_or(|before| before.parameter_hash() != wall.parameter_hash());
+            if !moved && !wall_changed {
+                continue;
+            }
+            let regenerated = match regenerate_lintels(wall, &opening, &existing) {
+                Ok(Some(regenerated)) => regenerated,
+                Ok(None) => continue,
+                Err(err) => {
+                    failures.push(format!(
+                        "Lintels of {} not regenerated: {err:#}",
+                        element.name
+                    ));
+                    continue;
+                }
+            };
+            lintels.extend(
+                regenerated
+                    .removed
+                    .into_iter()
+                    .map(|guid| ModelCommand::DeleteElement { guid }),
+            );
+            if !regenerated.replaced.is_empty() {
+                lintels.push(ModelCommand::ReplaceElements {
+                    elements: regenerated.replaced,
+                });
+            }
+            let storey = staged
+                .storey_of(wall.guid)
+                .map(|storey| storey.guid)
+                .or(staged.default_storey());
+            if let Some(storey) = storey
+                && !regenerated.added.is_empty()
+            {
+                lintels.push(ModelCommand::AddElements {
+                    storey,
+                    elements: regenerated.added,
+                });
+            }
+        }
+        for failure in failures {
+            self.notify_warning(failure);
+        }
+        let mut commands = vec![command];
+        if !lintels.is_empty() {
+            let lintels = ModelCommand::Batch(lintels);
+            lintels.clone().apply(&mut staged)?;
+            commands.push(lintels);
+        }
+
+        let before: HashMap<Guid, &BimElement> = embedded_by_guid(self.model.elements());
+        let after: HashMap<Guid, &BimElement> = embedded_by_guid(staged.elements());
+        let mut walls = BTreeSet::new();
+        for (guid, element) in &after {
+            let unchanged = before.get(guid).is_some_and(|before| {
+                before.cuts == element.cuts && before.parameter_hash() == element.parameter_hash()
+            });
+            if !unchanged {
+                walls.extend(element.cuts.iter().copied());
+            }
+        }
+        for (guid, element) in &before {
+            if !after.contains_key(guid) {
+                walls.extend(element.cuts.iter().copied());
+            }
+        }
+        let mut elements = staged.elements().to_vec();
+        let mut rebuilt = Vec::new();
+        for wall in walls {
+            let Some(index) = staged.element_index(wall) else {
+                continue;
+            };
+            rebuild_host(&mut elements, index)?;
+            rebuilt.push(elements[index].clone());
+            rebuilt.extend(
+                staged
+                    .hosted_openings(wall)
+                    .into_iter()
+                    .map(|(index, _)| elements[index].clone()),
+            );
+        }
+        if !rebuilt.is_empty() {
+            commands.push(ModelCommand::ReplaceElements { elements: rebuilt });
+        }
+        Ok(match commands.len() {
+            1 => commands.remove(0),
+            _ => ModelCommand::Batch(commands),
+        })
+    }
+}
+
+/// Whether `element` is set into the hosts it cuts, like a lintel, rather than an opening.
+fn is_embedded(element: &BimElement) -> bool {
+    element.opening.is_none() && !element.cuts.is_empty()
+}
+
+fn embedded_by_guid(elements: &[BimElement]) -> HashMap<Guid, &BimElement> {
+    elements
+        .iter()
+        .filter(|element| is_embedded(element))
+        .map(|element| (element.guid, element))
+        .collect()
+}
@@ -0,0 +1,116 @@
+use cryxtal_base::Guid;
+use cryxtal_io::ProjectCamera;
+
+use crate::gui::live_link::{LiveCommand, LiveEvent, LiveLink, LiveMessage};
+
+use super::CryxtalApp;
+
+/// Running live link and the state last broadcast, compared every frame to detect changes.
+pub(super) struct LiveLinkState {
+    link: LiveLink,
+    revision: u64,
+    selection: Option<Guid>,
+}
+
+impl CryxtalApp {
+    /// Starts the WebSocket server when `CRYXTAL_LIVE_LINK` names an address.
+    pub(super) fn start_live_link(&mut self) {
+        match LiveLink::from_env() {
+            Some(Ok(link)) => {
+                self.push_log(format!("Live link listening on ws://{}", link.address()));
+                self.live_link = Some(LiveLinkState {
+                    link,
+                    revision: self.mesh_revision,
+                    selection: self.selected_guid(),
+                });
+            }
+            Some(Err(err)) => self.notify_error(format!("Live link failed to start: {err}")),
+            None => {}
+        }
+    }
+
+    /// Applies commands received since the last frame, then broadcasts model and selection
+    /// changes.
+    pub(super) fn poll_live_link(&mut self) {
+        let Some(state) = self.live_link.as_mut() else {
+            return;
+        };
+        for message in state.link.poll() {
+            match message {
+                LiveMessage::Connected(client) => {
+                    client.send(&self.live_model_event());
+                    client.send(&LiveEvent::Selection {
+                        guid: self.selected_guid(),
+                    });
+                }
+                LiveMessage::Command(client, command) => {
+                    if let Err(message) = self.apply_live_command(command) {
+                        client.send(&LiveEvent::Error { message });
+                    }
+                }
+                LiveMessage::Invalid(client, message) => {
+                    client.send(&LiveEvent::Error { message });
+                }
+            }
+        }
+
+        let model_event = self.live_model_event();
+        let selection = self.selected_guid();
+        let revision = self.mesh_revision;
+        let Some(state) = self.live_link.as_mut() else {
+            return;
+        };
+        if state.revision != revision {
+            state.revision = revision;
+            state.link.broadcast(&model_event);
+        }
+        if state.selection != selection {
+            state.selection = selection;
+            state
+                .link
+                .broadcast(&LiveEvent::Selection { guid: selection });
+        }
+    }
+
+    fn apply_live_command(&mut self, command: LiveCommand) -> Result<(), String> {
+        match command {
+            LiveCommand::Select { guid: None } => self.set_selected(None),
+            LiveCommand::Select { guid: Some(guid) } => {
+                let index = self
+                    .model
+                    .element_index(guid)
+                    .ok_or_else(|| format!("unknown element {guid}"))?;
+                self.set_selected(Some(index));
+            }
+            LiveCommand::Camera {
+                target,
+                position,
+                up,
+                fov_deg,
+            } => {
+                let current = self.viewer.camera();
+                self.viewer.set_camera(&ProjectCamera {
+                    target,
+                    position,
+                    up: up.unwrap_or(current.up),
+                    fov_deg: fov_deg.unwrap_or(current.fov_deg),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn live_model_event(&self) -> LiveEvent {
+        LiveEvent::Model {
+            revision: self.mesh_revision,
+            elements: self.model.elements().len(),
+            edit: self.history.undo_label().map(str::to_string),
+        }
+    }
+
+    fn selected_guid(&self) -> Option<Guid> {
+        self.selected
+            .and_then(|index| self.model.elements().get(index))
+            .map(|element| element.guid)
+    }
+}
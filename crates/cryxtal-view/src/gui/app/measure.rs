@@ -0,0 +1,72 @@
+use cryxtal_io::{DEFAULT_TESSELLATION_TOLERANCE, distance_with_tolerance};
+use cryxtal_topology::Point3;
+
+use crate::viewer::{Align2, Color32, OverlayPainter, Point2, Rect, Stroke};
+
+use super::CryxtalApp;
+
+const MEASURE_POINT_RADIUS: f32 = 3.5;
+
+/// Shortest distance between two elements, drawn as a dimension line until the model changes.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Measurement {
+    /// Closest points on the selected and on the measured element.
+    start: Point3,
+    end: Point3,
+    distance: f64,
+    /// Model revision the distance was measured at.
+    revision: u64,
+}
+
+impl CryxtalApp {
+    /// Measures from the selected element to the element at `index`.
+    pub(super) fn measure_distance(&mut self, index: usize) {
+        let elements = self.model.elements();
+        let (Some(from), Some(to)) = (
+            self.selected.and_then(|selected| elements.get(selected)),
+            elements.get(index),
+        ) else {
+            self.notify_warning("Select an element to measure from".to_string());
+            return;
+        };
+        match distance_with_tolerance(from, to, DEFAULT_TESSELLATION_TOLERANCE) {
+            Ok((distance, start, end)) => {
+                let line = format!("Distance {} to {}: {distance:.1}", from.name, to.name);
+                self.measurement = Some(Measurement {
+                    start,
+                    end,
+                    distance,
+                    revision: self.model.revision(),
+                });
+                self.push_log(line);
+            }
+            Err(err) => self.notify_error(format!("Measure failed: {err:#}")),
+        }
+    }
+
+    /// The last measurement as a line between its closest points, labelled at its middle.
+    pub(super) fn paint_measurement(&self, painter: &mut impl OverlayPainter, rect: Rect) {
+        let Some(measurement) = self
+            .measurement
+            .filter(|measurement| measurement.revision == self.model.revision())
+        else {
+            return;
+        };
+        let color = Color32::from_rgb(240, 200, 60);
+        let start = self.viewer.project_point3(measurement.start, rect);
+        let end = self.viewer.project_point3(measurement.end, rect);
+        let (Some(start), Some(end)) = (start, end) else {
+            return;
+        };
+        painter.line_segment(start, end, Stroke::new(1.5, color));
+        painter.circle_filled(start, MEASURE_POINT_RADIUS, color);
+        painter.circle_filled(end, MEASURE_POINT_RADIUS, color);
+        painter.text(
+            Point2::new((start.x + end.x) * 0.5, (start.y + end.y) * 0.5),
+            Align2::LeftBottom,
+            format!("{:.1}", measurement.distance),
+            13.0,
+            color,
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use std::time::Duration;
+use web_time::Instant;
+
+use super::CryxtalApp;
+
+const TOAST_LIFETIME: Duration = Duration::from_secs(4);
+const ERROR_TOAST_LIFETIME: Duration = Duration::from_secs(10);
+const MAX_TOASTS: usize = 5;
+const TOAST_WIDTH: f32 = 320.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum ToastKind {
+    Success,
+    Warning,
+    Error,
+}
+
+impl ToastKind {
+    fn lifetime(self) -> Duration {
+        match self {
+            ToastKind::Error => ERROR_TOAST_LIFETIME,
+            _ => TOAST_LIFETIME,
+        }
+    }
+
+    fn color(self) -> egui::Color32 {
+        match self {
+            ToastKind::Success => egui::Color32::from_rgb(96, 176, 112),
+            ToastKind::Warning => egui::Color32::from_rgb(242, 179, 95),
+            ToastKind::Error => egui::Color32::from_rgb(226, 88, 80),
+        }
+    }
+
+    fn log_prefix(self) -> &'static str {
+        match self {
+            ToastKind::Success => "",
+            ToastKind::Warning => "Warning: ",
+            ToastKind::Error => "Error: ",
+        }
+    }
+}
+
+pub(super) struct Toast {
+    kind: ToastKind,
+    message: String,
+    created: Instant,
+}
+
+impl CryxtalApp {
+    pub(super) fn notify(&mut self, kind: ToastKind, message: String) {
+        self.push_log(format!("{}{message}", kind.log_prefix()));
+        if self.toasts.len() >= MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+        self.toasts.push(Toast {
+            kind,
+            message,
+            created: Instant::now(),
+        });
+    }
+
+    pub(super) fn notify_success(&mut self, message: String) {
+        self.notify(ToastKind::Success, message);
+    }
+
+    pub(super) fn notify_warning(&mut self, message: String) {
+        self.notify(ToastKind::Warning, message);
+    }
+
+    pub(super) fn notify_error(&mut self, message: String) {
+        self.notify(ToastKind::Error, message);
+    }
+
+    pub(super) fn show_toasts(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        self.toasts
+            .retain(|toast| now.duration_since(toast.created) < toast.kind.lifetime());
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        let mut open_log = false;
+        let mut dismissed = None;
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -44.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.set_max_width(TOAST_WIDTH);
+                for (idx, toast) in self.toasts.iter().enumerate().rev() {
+                    let response = egui::Frame::popup(ui.style())
+                        .stroke(egui::Stroke::new(1.0, toast.kind.color()))
+                        .show(ui, |ui| {
+                            ui.set_width(TOAST_WIDTH);
+                            ui.colored_label(toast.kind.color(), &toast.message);
+                        })
+                        .response
+                        .interact(egui::Sense::click())
+                        .on_hover_text("Click to open the log");
+                    if response.clicked() {
+                        open_log = true;
+                        dismissed = Some(idx);
+                    }
+                }
+            });
+
+        if let Some(idx) = dismissed {
+            self.toasts.remove(idx);
+        }
+        if open_log {
+            self.show_log = true;
+        }
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+
+    pub(super) fn log_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_log;
+        let mut clear = false;
+        egui::Window::new("Log")
+            .open(&mut open)
+            .default_size(egui::vec2(480.0, 280.0))
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .stick_to_bottom(true)
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        for line in &self.log {
+                            ui.label(line);
+                        }
+                    });
+                ui.separator();
+                if ui.button("Clear").clicked() {
+                    clear = true;
+                }
+            });
+
+        if clear {
+            self.log.clear();
+        }
+        if !open {
+            self.show_log = false;
+        }
+    }
+}
@@ -1,35 +1,47 @@
-use cryxtal_bim::{BimCategory, BimElement, ParameterValue};
+use anyhow::Context;
+use cryxtal_base::{Guid, OpContext};
+use cryxtal_bim::{
+    BimCategory, BimElement, BimModel, Constraint, ModelCommand, Opening, OpeningPlacement,
+    OpeningProfile, ParameterValue, hosted_openings, opening_host,
+};
 use cryxtal_topology::Point3;
 use egui::Ui;
 
 use crate::elements::{
-    apply_wall_opening, build_opening_element, rebuild_wall_from_openings, sync_opening_from_wall,
+    apply_slab_opening, apply_wall_opening, build_opening_element, is_cut_through,
+    rebuild_host_with, set_cut_through,
 };
 use crate::viewer::{Point2, Rect};
 
+use super::lengths::length_value;
 use super::{CryxtalApp, ToolMode};
 
 impl CryxtalApp {
     pub(super) fn opening_panel(&mut self, ui: &mut Ui) {
-        ui.heading("Wall Opening");
+        ui.heading("Opening");
 
+        let unit = self.model.units.length;
         ui.label("Width");
-        ui.add(
-            egui::DragValue::new(&mut self.opening_params.width)
-                .range(10.0..=100000.0)
-                .speed(1.0)
-                .fixed_decimals(0),
-        );
-
-        ui.label("Height");
-        ui.add(
-            egui::DragValue::new(&mut self.opening_params.height)
-                .range(10.0..=100000.0)
-                .speed(1.0)
-                .fixed_decimals(0),
+        ui.add(length_value(
+            &mut self.opening_params.width,
+            unit,
+            10.0..=100000.0,
+        ));
+
+        ui.label("Height (depth in slabs)");
+        ui.add(length_value(
+            &mut self.opening_params.height,
+            unit,
+            10.0..=100000.0,
+        ));
+
+        ui.checkbox(
+            &mut self.opening_params.cut_through,
+            "Cut through adjacent walls and slabs",
         );
 
         ui.label(self.opening_status_text());
+        self.coordinate_entry(ui);
 
         if ui.button("Cancel Opening").clicked() {
             self.cancel_opening();
@@ -40,88 +52,115 @@ impl CryxtalApp {
         let Some(selected) = self.selected else {
             return;
         };
-        let Some(opening) = self.elements.get(selected) else {
+        let Some(opening) = self.model.elements().get(selected) else {
             return;
         };
         if opening.category != BimCategory::Opening {
             return;
         }
 
-        let Some(opening_index) = opening_index(opening) else {
-            ui.label("Opening index is missing.");
-            return;
-        };
-        let Some(mut width) = opening_number(opening, "Width") else {
-            ui.label("Opening width is missing.");
+        let Some(data) = opening.opening else {
+            ui.label("Opening host relationship is missing.");
             return;
         };
-        let Some(mut height) = opening_number(opening, "Height") else {
-            ui.label("Opening height is missing.");
-            return;
+        // While an edit of the opening is computed, the values it applies stay on show.
+        let data = match self.opening_edit_preview {
+            Some(preview) if preview.guid == data.guid && self.background_edit.is_some() => preview,
+            _ => data,
         };
-        let Some(mut center_x) = opening_number(opening, "CenterX") else {
-            ui.label("Opening center X is missing.");
-            return;
+        let mut width = data.profile.width;
+        let mut height = data.profile.height;
+        let mut center_x = data.placement.center_x;
+        let placement = data.placement;
+        let host_label = self.opening_host_label(&data);
+        // Slab openings are placed by plan offset from the slab's first outline point.
+        let in_slab = self
+            .model
+            .element(data.host)
+            .is_some_and(|host| host.category == BimCategory::Slab);
+        let (height_label, x_label, second_label, min_center) = if in_slab {
+            ("Depth", "Offset X", "Offset Y", -100000.0)
+        } else {
+            ("Height", "Center X", "Center Z", 0.0)
         };
-        let Some(mut center_z) = opening_number(opening, "CenterZ") else {
-            ui.label("Opening center Z is missing.");
-            return;
+        // The second coordinate is plan Y in slabs and height in walls.
+        let mut second = if in_slab {
+            placement.center_y
+        } else {
+            placement.center_z
         };
 
+        let mut cut_through = is_cut_through(opening);
+        let mut centered = opening.constraints.contains(&Constraint::CenteredInHost);
+        let cut_labels: Vec<String> = opening
+            .cuts
+            .iter()
+            .filter_map(|guid| self.model.element(*guid))
+            .map(|host| host.name.clone())
+            .collect();
+
         ui.heading("Opening Properties");
-        ui.label(format!("Index: {opening_index}"));
-        ui.label(format!("Host: {}", opening_host_label(opening)));
+        ui.label(format!("Host: {host_label}"));
+        if ui
+            .checkbox(&mut cut_through, "Cut through adjacent walls and slabs")
+            .changed()
+        {
+            self.set_opening_cut_through(selected, cut_through);
+            return;
+        }
+        if !cut_labels.is_empty() {
+            ui.label(format!("Also cuts: {}", cut_labels.join(", ")));
+        }
+        if !in_slab
+            && ui
+                .checkbox(&mut centered, "Keep centered in wall")
+                .changed()
+        {
+            self.set_opening_centered(selected, centered);
+            return;
+        }
 
         ui.add_space(6.0);
+        let unit = self.model.units.length;
         ui.label("Width");
         let changed_width = ui
-            .add(
-                egui::DragValue::new(&mut width)
-                    .range(10.0..=100000.0)
-                    .speed(1.0)
-                    .fixed_decimals(0),
-            )
+            .add(length_value(&mut width, unit, 10.0..=100000.0))
             .changed();
 
-        ui.label("Height");
+        ui.label(height_label);
         let changed_height = ui
-            .add(
-                egui::DragValue::new(&mut height)
-                    .range(10.0..=100000.0)
-                    .speed(1.0)
-                    .fixed_decimals(0),
-            )
+            .add(length_value(&mut height, unit, 10.0..=100000.0))
             .changed();
 
-        ui.label("Center X");
+        ui.label(x_label);
         let changed_center_x = ui
-            .add(
-                egui::DragValue::new(&mut center_x)
-                    .range(0.0..=100000.0)
-                    .speed(1.0)
-                    .fixed_decimals(0),
+            .add_enabled(
+                !centered,
+                length_value(&mut center_x, unit, min_center..=100000.0),
             )
             .changed();
 
-        ui.label("Center Z");
-        let changed_center_z = ui
-            .add(
-                egui::DragValue::new(&mut center_z)
-                    .range(0.0..=100000.0)
-                    .speed(1.0)
-                    .fixed_decimals(0),
-            )
+        ui.label(second_label);
+        let changed_second = ui
+            .add(length_value(&mut second, unit, min_center..=100000.0))
             .changed();
 
-        if changed_width || changed_height || changed_center_x || changed_center_z {
-            self.apply_opening_edits(
-                selected,
-                opening_index,
-                width,
-                height,
-                center_x,
-                center_z,
-            );
+        if changed_width || changed_height || changed_center_x || changed_second {
+            let profile = OpeningProfile { width, height };
+            let placement = if in_slab {
+                OpeningPlacement {
+                    center_x,
+                    center_y: second,
+                    ..placement
+                }
+            } else {
+                OpeningPlacement {
+                    center_x,
+                    center_z: second,
+                    ..placement
+                }
+            };
+            self.apply_opening_edits(selected, profile, placement);
         }
     }
 
@@ -129,15 +168,15 @@ impl CryxtalApp {
         if self.tool_mode != ToolMode::CreateOpening {
             return String::new();
         }
-        let has_wall_selected = self
+        let has_host_selected = self
             .selected
-            .and_then(|idx| self.elements.get(idx))
-            .map(|element| element.category == BimCategory::Wall)
+            .and_then(|idx| self.model.elements().get(idx))
+            .map(|element| matches!(element.category, BimCategory::Wall | BimCategory::Slab))
             .unwrap_or(false);
-        if has_wall_selected {
-            "Click the opening center on the wall.".to_string()
+        if has_host_selected {
+            "Click the opening center on the host.".to_string()
         } else {
-            "Select a wall or click one to place the opening center.".to_string()
+            "Select a wall or slab, or click one to place the opening center.".to_string()
         }
     }
 
@@ -156,12 +195,14 @@ impl CryxtalApp {
     pub(super) fn handle_opening_click(&mut self, pos: Point2, rect: Rect) {
         let picked = self.viewer.pick_element(pos, rect, &self.element_meshes);
         let Some((index, picked_point)) = picked else {
-            self.push_log("No element under cursor".to_string());
+            self.notify_warning("No element under cursor".to_string());
             return;
         };
 
-        let host_index = match self.elements.get(index) {
-            Some(element) if element.category == BimCategory::Wall => Some(index),
+        let host_index = match self.model.elements().get(index) {
+            Some(element) if matches!(element.category, BimCategory::Wall | BimCategory::Slab) => {
+                Some(index)
+            }
             Some(element) if element.category == BimCategory::Opening => {
                 self.opening_host_index(element)
             }
@@ -169,7 +210,7 @@ impl CryxtalApp {
         };
 
         let Some(host_index) = host_index else {
-            self.push_log("Opening tool expects a wall".to_string());
+            self.notify_warning("Opening tool expects a wall or slab".to_string());
             return;
         };
 
@@ -180,240 +221,253 @@ impl CryxtalApp {
                 .unwrap_or(picked_point),
             None => picked_point,
         };
-        let point = Point3::new(snapped.x, snapped.y, snapped.z);
+        let host = self.model.elements()[host_index].guid;
+        self.place_opening_point(host, Point3::new(snapped.x, snapped.y, snapped.z));
+    }
 
-        let Some(host) = self.elements.get_mut(host_index) else {
+    pub(super) fn place_opening_point(&mut self, host: Guid, point: Point3) {
+        self.last_point = Some(point);
+        let Some(host) = self.model.element(host).cloned() else {
+            self.notify_warning("Opening host wall not found".to_string());
             return;
         };
 
-        let data = match apply_wall_opening(
-            host,
-            point,
-            self.opening_params.width,
-            self.opening_params.height,
-        ) {
-            Ok(data) => data,
+        let (width, height) = (self.opening_params.width, self.opening_params.height);
+        let opening = match host.category {
+            BimCategory::Slab => apply_slab_opening(&host, point, width, height),
+            _ => apply_wall_opening(&host, point, width, height),
+        };
+        let opening = match opening {
+            Ok(opening) => opening,
             Err(err) => {
-                self.push_log(format!("Opening failed: {err}"));
+                self.notify_error(format!("Opening failed: {err}"));
                 return;
             }
         };
 
-        let host_snapshot = match self.elements.get(host_index).cloned() {
-            Some(element) => element,
-            None => return,
-        };
-
-        let mut opening_element = match build_opening_element(&host_snapshot, &data) {
+        let number = hosted_openings(self.model.elements(), host.guid).len() + 1;
+        let mut opening_element = match build_opening_element(&host, &opening, number) {
             Ok(element) => element,
             Err(err) => {
-                self.push_log(format!("Opening build failed: {err}"));
+                self.notify_error(format!("Opening build failed: {err}"));
                 return;
             }
         };
-        opening_element.insert_parameter(
-            "HostIndex",
-            ParameterValue::Integer(host_index as i64),
-        );
 
-        self.add_opening_element(opening_element, host_index);
+        if self.opening_params.cut_through {
+            set_cut_through(&mut opening_element, true);
+        }
+        self.add_opening_element(opening_element, host.guid);
     }
 
-    fn add_opening_element(&mut self, mut element: BimElement, host_index: usize) {
-        let host_layer = self
-            .elements
-            .get(host_index)
-            .and_then(|host| match host.parameters.get("Layer") {
-                Some(ParameterValue::Text(value)) => Some(value.clone()),
-                _ => None,
-            });
+    fn add_opening_element(&mut self, mut element: BimElement, host_guid: Guid) {
+        let host_layer =
+            self.model
+                .element(host_guid)
+                .and_then(|host| match host.parameters.get("Layer") {
+                    Some(ParameterValue::Text(value)) => Some(value.clone()),
+                    _ => None,
+                });
         let fallback_layer = self
+            .model
             .layers
             .get(self.active_layer)
             .map(|layer| layer.name.clone())
             .unwrap_or_else(|| "Default".to_string());
         let layer = host_layer.unwrap_or(fallback_layer);
         element.insert_parameter("Layer", ParameterValue::Text(layer));
-        self.elements.push(element);
-        self.sync_openings_for_wall(host_index);
-        self.rebuild_scene();
-        if !self.elements.is_empty() {
-            self.set_selected(Some(self.elements.len() - 1));
-        }
-        self.push_log("Opening added".to_string());
+        let storey = self
+            .model
+            .storey_of(host_guid)
+            .map(|storey| storey.guid)
+            .or_else(|| self.model.default_storey());
+        let Some(storey) = storey else {
+            self.notify_error("Model has no storey to place the opening on".to_string());
+            return;
+        };
+        let job = move |model: &BimModel, ctx: &OpContext| -> anyhow::Result<ModelCommand> {
+            let mut rebuilt = ctx.span(0.0, 0.5, |ctx| {
+                staged_host_rebuild(model, host_guid, |staged| staged.push(element), ctx)
+            })?;
+            let mut added = rebuilt.pop().context("opening was not staged")?;
+            let hosts = ctx.span(0.5, 1.0, |ctx| staged_cut_rebuilds(model, &mut added, ctx))?;
+            rebuilt.extend(hosts);
+            Ok(ModelCommand::Batch(vec![
+                ModelCommand::ReplaceElements { elements: rebuilt },
+                ModelCommand::AddElements {
+                    storey,
+                    elements: vec![added],
+                },
+            ]))
+        };
+        let finish = |app: &mut CryxtalApp| {
+            app.set_selected(Some(app.model.elements().len() - 1));
+            app.push_log("Opening added".to_string());
+        };
+        self.spawn_edit(
+            "Add opening".to_string(),
+            false,
+            job,
+            Some(Box::new(finish)),
+        );
     }
 
     fn apply_opening_edits(
         &mut self,
         opening_idx: usize,
-        opening_index: usize,
-        width: f64,
-        height: f64,
-        center_x: f64,
-        center_z: f64,
+        profile: OpeningProfile,
+        placement: OpeningPlacement,
     ) {
-        let host_index = self
-            .elements
-            .get(opening_idx)
-            .and_then(|opening| self.opening_host_index(opening));
-        let Some(host_index) = host_index else {
-            self.push_log("Opening host wall not found".to_string());
+        let Some(element) = self.model.elements().get(opening_idx) else {
             return;
         };
-
-        let Some(host) = self.elements.get(host_index).cloned() else {
+        let Some(previous) = element.opening else {
             return;
         };
-
-        let mut candidate = host.clone();
-        update_wall_opening_params(
-            &mut candidate,
-            opening_index,
-            width,
-            height,
-            center_x,
-            center_z,
-        );
-        if let Err(err) = rebuild_wall_from_openings(&mut candidate) {
-            self.push_log(format!("Opening update failed: {err}"));
+        if self.model.element(previous.host).is_none() {
+            self.notify_warning("Opening host wall not found".to_string());
             return;
         }
 
-        if let Some(host_mut) = self.elements.get_mut(host_index) {
-            host_mut.parameters = candidate.parameters;
-            host_mut.geometry = candidate.geometry;
+        let label = format!("Edit {}", element.name);
+        let preview = Opening {
+            profile,
+            placement,
+            ..previous
+        };
+        let job = move |model: &BimModel, ctx: &OpContext| -> anyhow::Result<ModelCommand> {
+            let edit = |staged: &mut Vec<BimElement>| {
+                for candidate in staged.iter_mut() {
+                    if candidate.guid == previous.guid {
+                        candidate.opening = Some(preview);
+                    }
+                }
+            };
+            let mut elements = ctx.span(0.0, 0.5, |ctx| {
+                staged_host_rebuild(model, previous.host, edit, ctx)
+            })?;
+            if let Some(opening) = elements
+                .iter_mut()
+                .find(|candidate| candidate.guid == previous.guid)
+            {
+                let hosts = ctx.span(0.5, 1.0, |ctx| staged_cut_rebuilds(model, opening, ctx))?;
+                elements.extend(hosts);
+            }
+            Ok(ModelCommand::ReplaceElements { elements })
+        };
+        if self.spawn_edit(label, true, job, None) {
+            self.opening_edit_preview = Some(preview);
         }
-
-        self.sync_openings_for_wall(host_index);
-        self.rebuild_scene();
     }
 
-    fn sync_openings_for_wall(&mut self, host_index: usize) {
-        let Some(host) = self.elements.get(host_index).cloned() else {
+    fn set_opening_cut_through(&mut self, opening_idx: usize, cut_through: bool) {
+        let Some(element) = self.model.elements().get(opening_idx) else {
             return;
         };
-        let host_guid = host.guid.to_string();
-
-        let opening_indices: Vec<usize> = self
-            .elements
-            .iter()
-            .enumerate()
-            .filter_map(|(idx, element)| {
-                if element.category != BimCategory::Opening {
-                    return None;
-                }
-                let guid_match = opening_host_guid(element)
-                    .map(|guid| guid == host_guid)
-                    .unwrap_or(false);
-                let index_match = opening_host_index_param(element) == Some(host_index);
-                if guid_match || index_match {
-                    Some(idx)
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        let host_layer = match host.parameters.get("Layer") {
-            Some(ParameterValue::Text(value)) => Some(value.clone()),
-            _ => None,
+        let label = format!("Edit {}", element.name);
+        let mut opening = element.clone();
+        set_cut_through(&mut opening, cut_through);
+        let job = move |model: &BimModel, ctx: &OpContext| -> anyhow::Result<ModelCommand> {
+            let hosts = staged_cut_rebuilds(model, &mut opening, ctx)?;
+            let mut elements = vec![opening];
+            elements.extend(hosts);
+            Ok(ModelCommand::ReplaceElements { elements })
         };
-
-        for idx in opening_indices {
-            if let Some(opening) = self.elements.get_mut(idx) {
-                if let Some(layer) = host_layer.clone() {
-                    opening.insert_parameter("Layer", ParameterValue::Text(layer));
-                }
-                opening.insert_parameter(
-                    "HostIndex",
-                    ParameterValue::Integer(host_index as i64),
-                );
-                if let Err(err) = sync_opening_from_wall(opening, &host) {
-                    self.push_log(format!("Opening sync failed: {err}"));
-                }
-            }
-        }
+        self.spawn_edit(label, false, job, None);
     }
 
-    fn opening_host_index(&self, opening: &BimElement) -> Option<usize> {
-        if let Some(ParameterValue::Integer(value)) = opening.parameters.get("HostIndex") {
-            let index = *value as usize;
-            if self
-                .elements
-                .get(index)
-                .map(|element| element.category == BimCategory::Wall)
-                .unwrap_or(false)
-            {
-                return Some(index);
-            }
+    /// Constrains the opening to the middle of its wall, or releases it where it is.
+    fn set_opening_centered(&mut self, opening_idx: usize, centered: bool) {
+        let Some(element) = self.model.elements().get(opening_idx) else {
+            return;
+        };
+        let label = format!("Edit {}", element.name);
+        let mut opening = element.clone();
+        opening
+            .constraints
+            .retain(|constraint| *constraint != Constraint::CenteredInHost);
+        if centered {
+            opening.constraints.push(Constraint::CenteredInHost);
         }
-
-        let guid = opening_host_guid(opening)?;
-        self.elements.iter().position(|element| {
-            element.category == BimCategory::Wall && element.guid.to_string() == guid
-        })
-    }
-}
-
-fn opening_number(opening: &BimElement, key: &str) -> Option<f64> {
-    match opening.parameters.get(key) {
-        Some(ParameterValue::Number(value)) => Some(*value),
-        _ => None,
+        self.apply_command(
+            &label,
+            ModelCommand::ReplaceElements {
+                elements: vec![opening],
+            },
+        );
     }
-}
 
-fn opening_index(opening: &BimElement) -> Option<usize> {
-    match opening.parameters.get("OpeningIndex") {
-        Some(ParameterValue::Integer(value)) if *value > 0 => Some(*value as usize),
-        _ => None,
+    pub(super) fn opening_host_index(&self, opening: &BimElement) -> Option<usize> {
+        opening_host(self.model.elements(), opening.opening.as_ref()?)
     }
-}
 
-fn opening_host_guid(opening: &BimElement) -> Option<&str> {
-    match opening.parameters.get("HostGuid") {
-        Some(ParameterValue::Text(value)) => Some(value.as_str()),
-        _ => None,
+    fn opening_host_label(&self, opening: &Opening) -> String {
+        match opening_host(self.model.elements(), opening) {
+            Some(index) if !self.model.elements()[index].name.trim().is_empty() => {
+                self.model.elements()[index].name.clone()
+            }
+            _ => opening.host.to_string(),
+        }
     }
 }
 
-fn opening_host_index_param(opening: &BimElement) -> Option<usize> {
-    match opening.parameters.get("HostIndex") {
-        Some(ParameterValue::Integer(value)) if *value >= 0 => Some(*value as usize),
-        _ => None,
+/// Registers the walls and slabs `opening` passes through as its cuts, when it is set to cut
+/// through, and rebuilds them along with the hosts it cut before. Returns the rebuilt hosts;
+/// `opening` itself is left for the caller to apply.
+pub(super) fn staged_cut_rebuilds(
+    model: &BimModel,
+    opening: &mut BimElement,
+    ctx: &OpContext,
+) -> anyhow::Result<Vec<BimElement>> {
+    let mut hosts = std::mem::take(&mut opening.cuts);
+    if is_cut_through(opening) {
+        opening.cuts = model.cut_hosts(opening);
     }
-}
-
-fn opening_host_label(opening: &BimElement) -> String {
-    if let Some(ParameterValue::Text(value)) = opening.parameters.get("HostName") {
-        if !value.trim().is_empty() {
-            return value.clone();
+    for guid in &opening.cuts {
+        if !hosts.contains(guid) {
+            hosts.push(*guid);
         }
     }
-    opening_host_guid(opening).unwrap_or("Unknown").to_string()
+    hosts.retain(|host| model.element(*host).is_some());
+    let count = hosts.len() as f32;
+    let mut rebuilt = Vec::with_capacity(hosts.len());
+    for (idx, host) in hosts.into_iter().enumerate() {
+        let edit = |staged: &mut Vec<BimElement>| {
+            staged.retain(|candidate| candidate.guid != opening.guid);
+            staged.push(opening.clone());
+        };
+        let staged = ctx.span(idx as f32 / count, (idx + 1) as f32 / count, |ctx| {
+            staged_host_rebuild(model, host, edit, ctx)
+        })?;
+        rebuilt.extend(staged.into_iter().next());
+    }
+    Ok(rebuilt)
 }
 
-fn update_wall_opening_params(
-    host: &mut BimElement,
-    index: usize,
-    width: f64,
-    height: f64,
-    center_x: f64,
-    center_z: f64,
-) {
-    let count = match host.parameters.get("OpeningCount") {
-        Some(ParameterValue::Integer(value)) if *value > 0 => *value as usize,
-        _ => 0,
+/// Rebuilds a host wall and its openings on copies, leaving `model` untouched until the result
+/// is applied as a command. Openings hosted elsewhere that cut through the host and the lintels
+/// and sills set into it are staged too. `edit` adjusts the staged opening elements first; the
+/// host comes first in the returned list.
+pub(super) fn staged_host_rebuild(
+    model: &BimModel,
+    host: Guid,
+    edit: impl FnOnce(&mut Vec<BimElement>),
+    ctx: &OpContext,
+) -> anyhow::Result<Vec<BimElement>> {
+    let Some(host_element) = model.element(host) else {
+        anyhow::bail!("host wall {host} not found");
     };
-    if index > count {
-        host.insert_parameter(
-            "OpeningCount",
-            ParameterValue::Integer(index as i64),
-        );
-    }
-
-    let prefix = format!("Opening{index}");
-    host.insert_parameter(format!("{prefix}Width"), ParameterValue::Number(width));
-    host.insert_parameter(format!("{prefix}Height"), ParameterValue::Number(height));
-    host.insert_parameter(format!("{prefix}CenterX"), ParameterValue::Number(center_x));
-    host.insert_parameter(format!("{prefix}CenterZ"), ParameterValue::Number(center_z));
+    let mut staged = vec![host_element.clone()];
+    staged.extend(
+        model
+            .hosted_openings(host)
+            .into_iter()
+            .map(|(index, _)| index)
+            .chain(model.cutting_openings(host))
+            .chain(model.embedded_members(host))
+            .map(|index| model.elements()[index].clone()),
+    );
+    edit(&mut staged);
+    rebuild_host_with(&mut staged, 0, ctx)?;
+    Ok(staged)
 }
@@ -0,0 +1,229 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, ModelCommand, Opening};
+use std::collections::HashMap;
+
+use crate::elements::{displaced_openings, rebuild_host};
+
+use super::CryxtalApp;
+use super::rebar_set::collect_replaced;
+
+/// A wall edit held back because it pushes openings out of the wall, until the user picks how
+/// to resolve it.
+pub(super) struct OpeningConflict {
+    label: String,
+    command: ModelCommand,
+    merge: bool,
+    openings: Vec<DisplacedOpening>,
+    /// Model revision the edit was made at; it is dropped if the model changes meanwhile.
+    revision: u64,
+}
+
+struct DisplacedOpening {
+    guid: Guid,
+    name: String,
+    wall: String,
+}
+
+impl CryxtalApp {
+    /// Holds `command` for [`CryxtalApp::opening_conflict_window`] when it shrinks a wall so that
+    /// openings which fit it no longer do. Returns the command back when nothing is displaced.
+    pub(super) fn hold_opening_conflict(
+        &mut self,
+        label: &str,
+        command: ModelCommand,
+        merge: bool,
+    ) -> Option<ModelCommand> {
+        let openings = self.displaced_by(&command);
+        if openings.is_empty() {
+            return Some(command);
+        }
+        self.notify_warning(format!(
+            "{label} leaves {} opening(s) outside the wall",
+            openings.len()
+        ));
+        self.opening_conflict = Some(OpeningConflict {
+            label: label.to_string(),
+            command,
+            merge,
+            openings,
+            revision: self.model.revision(),
+        });
+        None
+    }
+
+    /// Openings that fit the walls `command` replaces, but not the walls it replaces them with.
+    fn displaced_by(&self, command: &ModelCommand) -> Vec<DisplacedOpening> {
+        let mut replaced = HashMap::new();
+        collect_replaced(command, &mut replaced);
+        let mut displaced = Vec::new();
+        for wall in replaced.values() {
+            if wall.category != BimCategory::Wall {
+                continue;
+            }
+            let Some(current) = self.model.element(wall.guid) else {
+                continue;
+            };
+            let openings: Vec<Opening> = self
+                .model
+                .hosted_openings(wall.guid)
+                .into_iter()
+                .map(|(_, opening)| opening)
+                .collect();
+            if openings.is_empty() {
+                continue;
+            }
+            let (Ok(before), Ok(after)) = (
+                displaced_openings(current, &openings),
+                displaced_openings(wall, &openings),
+            ) else {
+                continue;
+            };
+            for opening in after
+                .into_iter()
+                .filter(|opening| !before.contains(opening))
+            {
+                let name = self
+                    .model
+                    .element(opening.guid)
+                    .map(|element| element.name.clone())
+                    .unwrap_or_else(|| opening.guid.to_string());
+                displaced.push(DisplacedOpening {
+                    guid: opening.guid,
+                    name,
+                    wall: wall.name.clone(),
+                });
+            }
+        }
+        displaced.sort_by(|a, b| (&a.wall, &a.name).cmp(&(&b.wall, &b.name)));
+        displaced
+    }
+
+    pub(super) fn opening_conflict_window(&mut self, ctx: &egui::Context) {
+        let Some(conflict) = &self.opening_conflict else {
+            return;
+        };
+        if conflict.revision != self.model.revision() {
+            let label = conflict.label.clone();
+            self.opening_conflict = None;
+            self.push_log(format!("{label} aborted: the model changed"));
+            return;
+        }
+        let mut choice = None;
+        let mut open = true;
+        egui::Window::new("Openings Outside Wall")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} leaves these openings outside the wall:",
+                    conflict.label
+                ));
+                for opening in &conflict.openings {
+                    ui.weak(format!("{} in {}", opening.name, opening.wall));
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Delete Openings")
+                        .on_hover_text("Remove the openings and rebuild the wall without them")
+                        .clicked()
+                    {
+                        choice = Some(ConflictChoice::Delete);
+                    }
+                    if ui
+                        .button("Clamp")
+                        .on_hover_text("Move and shrink the openings to fit the wall")
+                        .clicked()
+                    {
+                        choice = Some(ConflictChoice::Clamp);
+                    }
+                    if ui.button("Abort").clicked() {
+                        choice = Some(ConflictChoice::Abort);
+                    }
+                });
+            });
+        if !open {
+            choice = Some(ConflictChoice::Abort);
+        }
+        let Some(choice) = choice else {
+            return;
+        };
+        let Some(conflict) = self.opening_conflict.take() else {
+            return;
+        };
+        match choice {
+            ConflictChoice::Clamp => {
+                if self.commit_command(&conflict.label, conflict.command, conflict.merge) {
+                    self.push_log(format!(
+                        "Clamped {} opening(s) into the wall",
+                        conflict.openings.len()
+                    ));
+                }
+            }
+            ConflictChoice::Delete => {
+                let count = conflict.openings.len();
+                match self.without_openings(conflict.command, &conflict.openings) {
+                    Ok(command) => {
+                        if self.commit_command(&conflict.label, command, false) {
+                            self.push_log(format!("Deleted {count} opening(s) outside the wall"));
+                        }
+                    }
+                    Err(err) => self.notify_error(format!("{} failed: {err:#}", conflict.label)),
+                }
+            }
+            ConflictChoice::Abort => self.push_log(format!("{} aborted", conflict.label)),
+        }
+    }
+
+    /// `command` followed by deleting `openings` and rebuilding their walls without them, as
+    /// one undo step.
+    fn without_openings(
+        &self,
+        command: ModelCommand,
+        openings: &[DisplacedOpening],
+    ) -> anyhow::Result<ModelCommand> {
+        let mut staged = self.model.clone();
+        command.clone().apply(&mut staged)?;
+        let mut commands = vec![command];
+        let mut hosts = Vec::new();
+        for opening in openings {
+            if let Some(host) = staged
+                .element(opening.guid)
+                .and_then(|element| element.opening)
+                .map(|opening| opening.host)
+                && !hosts.contains(&host)
+            {
+                hosts.push(host);
+            }
+            let delete = ModelCommand::DeleteElement { guid: opening.guid };
+            delete.clone().apply(&mut staged)?;
+            commands.push(delete);
+        }
+        let mut elements = staged.elements().to_vec();
+        let mut rebuilt = Vec::new();
+        for host in hosts {
+            let Some(index) = staged.element_index(host) else {
+                continue;
+            };
+            rebuild_host(&mut elements, index)?;
+            rebuilt.push(elements[index].clone());
+            rebuilt.extend(
+                staged
+                    .hosted_openings(host)
+                    .into_iter()
+                    .map(|(index, _)| elements[index].clone()),
+            );
+        }
+        commands.push(ModelCommand::ReplaceElements { elements: rebuilt });
+        Ok(ModelCommand::Batch(commands))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ConflictChoice {
+    Delete,
+    Clamp,
+    Abort,
+}
@@ -1,6 +1,8 @@
 pub struct WallOpeningParams {
     pub width: f64,
     pub height: f64,
+    /// New openings also cut the other walls and slabs they pass through.
+    pub cut_through: bool,
 }
 
 impl Default for WallOpeningParams {
@@ -8,6 +10,7 @@ impl Default for WallOpeningParams {
         Self {
             width: 900.0,
             height: 2100.0,
+            cut_through: false,
         }
     }
 }
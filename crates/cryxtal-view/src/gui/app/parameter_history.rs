@@ -0,0 +1,90 @@
+use cryxtal_base::Guid;
+use cryxtal_io::export_parameter_log_csv;
+use egui::Ui;
+
+use super::CryxtalApp;
+
+/// Which tab of the properties panel is shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) enum PropertiesTab {
+    #[default]
+    Properties,
+    History,
+}
+
+/// History tab state: where the parameter log is exported to.
+#[derive(Default)]
+pub(super) struct ParameterHistory {
+    pub(super) tab: PropertiesTab,
+    export_path: String,
+}
+
+impl CryxtalApp {
+    pub(super) fn properties_tabs(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let tab = &mut self.parameter_history.tab;
+            ui.selectable_value(tab, PropertiesTab::Properties, "Properties");
+            ui.selectable_value(tab, PropertiesTab::History, "History");
+        });
+    }
+
+    /// Parameter changes of the element at `index`, newest first, with who made them, from
+    /// where and in which revision, and the export of the model's whole log.
+    pub(super) fn parameter_history_panel(&mut self, ui: &mut Ui, index: usize) {
+        let Some(element) = self.model.elements().get(index) else {
+            return;
+        };
+        let guid = element.guid;
+        let entries = self.model.parameter_history(guid);
+        if entries.is_empty() {
+            ui.weak("No parameter changes recorded");
+        }
+        egui::ScrollArea::vertical()
+            .max_height(320.0)
+            .show(ui, |ui| {
+                for entry in entries.iter().rev() {
+                    let author = if entry.author.is_empty() {
+                        "unknown"
+                    } else {
+                        entry.author.as_str()
+                    };
+                    ui.weak(format!(
+                        "Revision {} by {author} ({})",
+                        entry.revision,
+                        entry.source.label()
+                    ));
+                    let [old, new] = [&entry.old, &entry.new]
+                        .map(|value| value.as_ref().map_or("—".to_string(), ToString::to_string));
+                    ui.label(format!("{}: {old} → {new}", entry.name));
+                    ui.add_space(2.0);
+                }
+            });
+
+        ui.add_space(6.0);
+        ui.label("Export log (CSV)");
+        ui.add(
+            egui::TextEdit::singleline(&mut self.parameter_history.export_path)
+                .hint_text("parameter-log.csv"),
+        );
+        ui.horizontal(|ui| {
+            if ui.button("This Element").clicked() {
+                self.export_parameter_log(Some(guid));
+            }
+            if ui.button("All Elements").clicked() {
+                self.export_parameter_log(None);
+            }
+        });
+    }
+
+    fn export_parameter_log(&mut self, element: Option<Guid>) {
+        let path = self.parameter_history.export_path.trim().to_string();
+        if path.is_empty() {
+            self.notify_warning("Enter a path to export the parameter log to".to_string());
+            return;
+        }
+        match export_parameter_log_csv(&self.model, element, &path) {
+            Ok(()) => self.push_log(format!("Exported parameter log to {path}")),
+            Err(err) => self.notify_error(format!("Parameter log export failed: {err:#}")),
+        }
+    }
+}
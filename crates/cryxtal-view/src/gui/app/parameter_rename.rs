@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use cryxtal_bim::ParameterRename;
+
+use super::CryxtalApp;
+
+/// Conflicts listed at most in the preview.
+const CONFLICT_LIMIT: usize = 20;
+
+/// Rename Parameters window: the mapping being edited, old key to new key, and its preview.
+#[derive(Default)]
+pub(super) struct ParameterRenameTool {
+    rows: Vec<(String, String)>,
+    /// Preview of `rows` and the model revision it was taken at; cleared whenever either
+    /// changes, since applying it replaces elements as they were then.
+    preview: Option<(ParameterRename, u64)>,
+    message: String,
+}
+
+impl CryxtalApp {
+    pub(super) fn open_parameter_rename(&mut self) {
+        self.parameter_rename
+            .get_or_insert_with(ParameterRenameTool::default);
+    }
+
+    pub(super) fn parameter_rename_window(&mut self, ctx: &egui::Context) {
+        let Some(tool) = self.parameter_rename.as_mut() else {
+            return;
+        };
+        let keys = self.model.parameter_keys();
+        if tool.rows.is_empty() {
+            tool.rows.push((String::new(), String::new()));
+        }
+        if tool
+            .preview
+            .as_ref()
+            .is_some_and(|(_, revision)| *revision != self.model.revision())
+        {
+            tool.preview = None;
+        }
+
+        let mut open = true;
+        let mut preview = false;
+        let mut apply = false;
+        egui::Window::new("Rename Parameters")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("Old key → new key. Keys renamed onto an existing key merge into it.");
+                let mut edited = false;
+                let mut removed = None;
+                egui::Grid::new("parameter_rename_rows").show(ui, |ui| {
+                    for (index, (from, to)) in tool.rows.iter_mut().enumerate() {
+                        let count = keys.get(from.trim()).copied().unwrap_or(0);
+                        egui::ComboBox::from_id_source(("parameter_rename_from", index))
+                            .selected_text(if from.is_empty() {
+                                "Key"
+                            } else {
+                                from.as_str()
+                            })
+                            .show_ui(ui, |ui| {
+                                for (key, count) in &keys {
+                                    if ui
+                                        .selectable_label(from == key, format!("{key} ({count})"))
+                                        .clicked()
+                                    {
+                                        *from = key.clone();
+                                        edited = true;
+                                    }
+                                }
+                            });
+                        edited |= ui
+                            .add(
+                                egui::TextEdit::singleline(to)
+                                    .hint_text("New key")
+                                    .desired_width(140.0),
+                            )
+                            .changed();
+                        ui.weak(format!("{count} use(s)"));
+                        if ui.small_button("Remove").clicked() {
+                            removed = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(index) = removed {
+                    tool.rows.remove(index);
+                    edited = true;
+                }
+                if edited {
+                    tool.preview = None;
+                    tool.message.clear();
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Add Row").clicked() {
+                        tool.rows.push((String::new(), String::new()));
+                    }
+                    if ui.button("Preview").clicked() {
+                        preview = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            tool.preview
+                                .as_ref()
+                                .is_some_and(|(preview, _)| !preview.is_empty()),
+                            egui::Button::new("Apply"),
+                        )
+                        .clicked()
+                    {
+                        apply = true;
+                    }
+                });
+                if let Some((preview, _)) = &tool.preview {
+                    ui.separator();
+                    for row in &preview.renamed {
+                        ui.label(format!(
+                            "{} → {}: {} element(s), {} type(s)",
+                            row.from, row.to, row.elements, row.types
+                        ));
+                    }
+                    if !preview.conflicts.is_empty() {
+                        ui.label(format!(
+                            "{} conflict(s); the value under the new key is kept:",
+                            preview.conflicts.len()
+                        ));
+                        for conflict in preview.conflicts.iter().take(CONFLICT_LIMIT) {
+                            ui.weak(format!(
+                                "{} {}: keeps {:?}, drops {:?}",
+                                conflict.name, conflict.key, conflict.kept, conflict.dropped
+                            ));
+                        }
+                    }
+                }
+                if !tool.message.is_empty() {
+                    ui.label(&tool.message);
+                }
+            });
+
+        if preview {
+            let mapping: BTreeMap<String, String> = tool
+                .rows
+                .iter()
+                .filter(|(from, _)| !from.trim().is_empty())
+                .cloned()
+                .collect();
+            match self.model.rename_parameters(&mapping) {
+                Ok(rename) => {
+                    tool.message = if rename.is_empty() {
+                        "No parameters match.".to_string()
+                    } else {
+                        String::new()
+                    };
+                    tool.preview = Some((rename, self.model.revision()));
+                }
+                Err(err) => {
+                    tool.message = err.to_string();
+                    tool.preview = None;
+                }
+            }
+        }
+        if apply && let Some((rename, _)) = tool.preview.take() {
+            let renamed = rename.renamed.len();
+            if self.apply_command("Rename parameters", rename.command()) {
+                self.push_log(format!("Renamed {renamed} parameter key(s)"));
+                self.parameter_rename = None;
+                return;
+            }
+        }
+        if !open {
+            self.parameter_rename = None;
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use cryxtal_io::{export_parameters_csv, export_parameters_json, import_parameters};
+use std::path::Path;
+
+use super::CryxtalApp;
+
+impl CryxtalApp {
+    /// Writes every element's parameters to `path` for editing in a spreadsheet: JSON for a
+    /// `.json` path, CSV otherwise.
+    pub(super) fn export_parameters_to(&mut self, path: &Path) {
+        let result = if is_json(path) {
+            export_parameters_json(&self.model, path)
+        } else {
+            export_parameters_csv(&self.model, path)
+        };
+        match result {
+            Ok(()) => self.push_log(format!("Exported parameters to {}", path.display())),
+            Err(err) => self.notify_error(format!("Parameter export failed: {err:#}")),
+        }
+    }
+
+    /// Applies the values edited in the export at `path`, matched by guid, as one undo step.
+    pub(super) fn import_parameters_from(&mut self, path: &Path) {
+        let import = match import_parameters(&self.model, path) {
+            Ok(import) => import,
+            Err(err) => {
+                self.notify_error(format!("Parameter import failed: {err:#}"));
+                return;
+            }
+        };
+        if !import.unknown.is_empty() {
+            self.notify_warning(format!(
+                "{} row(s) name no element of this model and were skipped",
+                import.unknown.len()
+            ));
+        }
+        if import.changed == 0 {
+            self.push_log(format!("No parameter changes in {}", path.display()));
+            return;
+        }
+        if self.apply_command("Import parameters", import.command) {
+            self.push_log(format!(
+                "Imported {} parameter change(s) from {}",
+                import.changed,
+                path.display()
+            ));
+        }
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
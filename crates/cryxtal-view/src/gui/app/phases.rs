@@ -0,0 +1,156 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimElement, GraphicOverride, ModelCommand, Phase, PhaseStatus};
+use egui::Ui;
+
+use super::CryxtalApp;
+
+/// Existing elements are washed out so the phase's new work stands out.
+const EXISTING_GRAPHICS: GraphicOverride = GraphicOverride {
+    color: None,
+    transparency: None,
+    halftone: true,
+};
+const DEMOLISHED_GRAPHICS: GraphicOverride = GraphicOverride {
+    color: Some([214, 64, 52]),
+    transparency: Some(0.6),
+    halftone: false,
+};
+
+/// Phase the viewport shows, and the phase being named.
+#[derive(Default)]
+pub(super) struct PhaseState {
+    /// `None` shows every element, whatever its phases.
+    active: Option<Guid>,
+    new_phase_name: String,
+}
+
+impl CryxtalApp {
+    /// How the element stands in the shown phase; `None` hides it. Without a shown phase every
+    /// element stands as existing.
+    pub(super) fn shown_phase_status(&self, element: &BimElement) -> Option<PhaseStatus> {
+        match self.phase_view.active {
+            Some(phase) => self.model.phase_status(element, phase),
+            None => Some(PhaseStatus::Existing),
+        }
+    }
+
+    /// `color` as an element with `status` is drawn in the shown phase.
+    pub(super) fn phase_color(&self, status: Option<PhaseStatus>, color: [u8; 4]) -> [u8; 4] {
+        if self.phase_view.active.is_none() {
+            return color;
+        }
+        match status {
+            Some(PhaseStatus::Existing) => EXISTING_GRAPHICS.apply(color),
+            Some(PhaseStatus::Demolished) => DEMOLISHED_GRAPHICS.apply(color),
+            Some(PhaseStatus::New) | None => color,
+        }
+    }
+
+    pub(super) fn phase_panel(&mut self, ui: &mut Ui) {
+        ui.label("Phase");
+        let current = self
+            .phase_view
+            .active
+            .and_then(|guid| self.model.phase(guid))
+            .map_or("All phases", |phase| phase.name.as_str())
+            .to_string();
+        let mut next = self.phase_view.active;
+        egui::ComboBox::from_id_source("phase_combo")
+            .selected_text(current)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut next, None, "All phases");
+                for phase in &self.model.phases {
+                    ui.selectable_value(&mut next, Some(phase.guid), &phase.name);
+                }
+            });
+        if next != self.phase_view.active {
+            self.phase_view.active = next;
+            self.dirty = true;
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.phase_view.new_phase_name)
+                    .hint_text("Phase name")
+                    .desired_width(120.0),
+            );
+            if ui.button("Add Phase").clicked() {
+                self.add_phase();
+            }
+        });
+    }
+
+    /// Created and demolished phase pickers for the element at `index`.
+    pub(super) fn element_phase_panel(&mut self, ui: &mut Ui, index: usize) {
+        if self.model.phases.is_empty() {
+            return;
+        }
+        let Some(element) = self.model.elements().get(index) else {
+            return;
+        };
+        let (mut created, mut demolished) = (element.phase_created, element.phase_demolished);
+        let phases: Vec<(Guid, String)> = self
+            .model
+            .phases
+            .iter()
+            .map(|phase| (phase.guid, phase.name.clone()))
+            .collect();
+        let name = |guid: Option<Guid>, none: &'static str| {
+            guid.and_then(|guid| phases.iter().find(|(phase, _)| *phase == guid))
+                .map_or(none.to_string(), |(_, name)| name.clone())
+        };
+
+        ui.label("Phase Created");
+        egui::ComboBox::from_id_source("phase_created_combo")
+            .selected_text(name(created, "Before first phase"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut created, None, "Before first phase");
+                for (guid, name) in &phases {
+                    ui.selectable_value(&mut created, Some(*guid), name);
+                }
+            });
+        ui.label("Phase Demolished");
+        egui::ComboBox::from_id_source("phase_demolished_combo")
+            .selected_text(name(demolished, "None"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut demolished, None, "None");
+                for (guid, name) in &phases {
+                    ui.selectable_value(&mut demolished, Some(*guid), name);
+                }
+            });
+
+        if created != element.phase_created || demolished != element.phase_demolished {
+            let mut edited = element.clone();
+            edited.phase_created = created;
+            edited.phase_demolished = demolished;
+            let label = format!("Set phase of {}", edited.name);
+            self.apply_command(
+                &label,
+                ModelCommand::ReplaceElements {
+                    elements: vec![edited],
+                },
+            );
+        }
+    }
+
+    fn add_phase(&mut self) {
+        let name = self.phase_view.new_phase_name.trim().to_string();
+        if name.is_empty() {
+            self.notify_error("Phase name is empty".to_string());
+            return;
+        }
+        if self.model.phases.iter().any(|phase| phase.name == name) {
+            self.notify_error(format!("Phase {name} already exists"));
+            return;
+        }
+        let phase = Phase::new(name);
+        let guid = phase.guid;
+        let label = format!("Add phase {}", phase.name);
+        let index = self.model.phases.len();
+        if !self.apply_command(&label, ModelCommand::AddPhase { index, phase }) {
+            return;
+        }
+        self.phase_view.active = Some(guid);
+        self.phase_view.new_phase_name.clear();
+    }
+}
@@ -0,0 +1,58 @@
+use cryxtal_io::import_point_cloud;
+use std::path::Path;
+
+use crate::viewer::{Color32, ViewerPointCloud};
+
+use super::CryxtalApp;
+
+/// Color of cloud points the file gives no color.
+const CLOUD_COLOR: Color32 = Color32::from_rgb(90, 200, 220);
+
+/// Point clouds shown over the model as context for the session; they are not saved with the
+/// project.
+pub(super) struct PointClouds {
+    pub(super) clouds: Vec<ViewerPointCloud>,
+    /// Bumped whenever `clouds` change, so renderers know to upload them again.
+    pub(super) revision: u64,
+    /// Cube size clouds are thinned to on import, in model units; 0 keeps every point.
+    pub(super) decimation: f64,
+}
+
+impl Default for PointClouds {
+    fn default() -> Self {
+        Self {
+            clouds: Vec::new(),
+            revision: 0,
+            decimation: 10.0,
+        }
+    }
+}
+
+impl CryxtalApp {
+    /// Reads the cloud at `path`, thinned to the chosen decimation, and shows it over the model.
+    pub(super) fn import_point_cloud_from(&mut self, path: &Path) {
+        let decimation = Some(self.point_clouds.decimation).filter(|cell| *cell > 0.0);
+        match import_point_cloud(path, decimation) {
+            Ok(cloud) => {
+                let count = cloud.len();
+                self.point_clouds.clouds.push(ViewerPointCloud {
+                    points: cloud.points,
+                    colors: cloud
+                        .colors
+                        .into_iter()
+                        .map(|[r, g, b]| Color32::from_rgb(r, g, b))
+                        .collect(),
+                    color: CLOUD_COLOR,
+                });
+                self.point_clouds.revision += 1;
+                self.push_log(format!("Imported {count} point(s) from {}", path.display()));
+            }
+            Err(err) => self.notify_error(format!("Point cloud import failed: {err:#}")),
+        }
+    }
+
+    pub(super) fn clear_point_clouds(&mut self) {
+        self.point_clouds.clouds.clear();
+        self.point_clouds.revision += 1;
+    }
+}
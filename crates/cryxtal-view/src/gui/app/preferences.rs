@@ -0,0 +1,138 @@
+use crate::gui::gpu::describe_adapter;
+use crate::gui::prefs::Preferences;
+
+use super::CryxtalApp;
+
+impl CryxtalApp {
+    pub(super) fn preferences_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_preferences;
+        egui::Window::new("Preferences")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.heading("Graphics Adapter");
+                ui.add_space(6.0);
+
+                let selected_text = self
+                    .preferences
+                    .adapter
+                    .clone()
+                    .unwrap_or_else(|| "Automatic".to_string());
+                egui::ComboBox::from_id_source("gpu_adapter_combo")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.preferences.adapter, None, "Automatic");
+                        for info in &self.gpu_diagnostics.adapters {
+                            ui.selectable_value(
+                                &mut self.preferences.adapter,
+                                Some(info.name.clone()),
+                                describe_adapter(info),
+                            );
+                        }
+                    });
+                ui.checkbox(
+                    &mut self.preferences.software_renderer,
+                    "Use software renderer",
+                );
+                ui.label("Adapter changes take effect after restart.");
+
+                ui.add_space(6.0);
+                ui.separator();
+                ui.heading("Author");
+                let mut author = self.preferences.author.clone().unwrap_or_default();
+                let hint = Preferences::default().author();
+                if ui
+                    .add(egui::TextEdit::singleline(&mut author).hint_text(hint))
+                    .changed()
+                {
+                    self.preferences.author = (!author.trim().is_empty()).then_some(author);
+                    self.model.author = self.preferences.author();
+                }
+                ui.label("Stamped on the elements you edit.");
+
+                ui.add_space(6.0);
+                ui.separator();
+                ui.heading("Viewport Tessellation");
+                let mut tessellation = self.preferences.tessellation();
+                let tolerance = ui.add(
+                    egui::DragValue::new(&mut tessellation.chordal_tol)
+                        .range(0.001..=1000.0)
+                        .speed(0.01)
+                        .prefix("Tolerance "),
+                );
+                let adaptive = ui.checkbox(
+                    &mut tessellation.curvature_adaptive,
+                    "Follow curvature of small curved faces",
+                );
+                if tolerance.changed() || adaptive.changed() {
+                    self.preferences.viewport_tolerance = Some(tessellation.chordal_tol);
+                    self.preferences.viewport_adaptive = tessellation.curvature_adaptive;
+                }
+                // Dragging the tolerance retessellates once it is let go.
+                if tolerance.drag_stopped()
+                    || (tolerance.changed() && !tolerance.dragged())
+                    || adaptive.changed()
+                {
+                    self.retessellate();
+                }
+                ui.label("Largest gap between the viewport mesh and curved faces.");
+
+                ui.add_space(6.0);
+                ui.separator();
+                ui.heading("Diagnostics");
+                if self.gpu_diagnostics.software_fallback {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(242, 179, 95),
+                        "The hardware adapter failed to start; using the software renderer.",
+                    );
+                }
+                ui.add(egui::Label::new(
+                    egui::RichText::new(self.gpu_diagnostics.report()).monospace(),
+                ));
+
+                if !self.preferences_message.is_empty() {
+                    ui.label(&self.preferences_message);
+                }
+
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        self.save_preferences();
+                    }
+                    if ui.button("Copy Report").clicked() {
+                        ui.ctx().copy_text(self.gpu_diagnostics.report());
+                    }
+                    if ui.button("Close").clicked() {
+                        self.show_preferences = false;
+                    }
+                });
+            });
+
+        if !open {
+            self.show_preferences = false;
+        }
+    }
+
+    /// Tessellates every element again with the viewport tessellation preferences, dropping
+    /// the meshes restored from the project's cache.
+    fn retessellate(&mut self) {
+        self.mesh_cache.clear();
+        self.element_mesh_guids.clear();
+        self.rebuild_scene();
+    }
+
+    fn save_preferences(&mut self) {
+        match self.preferences.save() {
+            Ok(()) => {
+                self.preferences_message = "Preferences saved".to_string();
+                self.notify_success("Preferences saved".to_string());
+            }
+            Err(err) => {
+                self.preferences_message = format!("Save failed: {err}");
+                self.notify_error(format!("Preferences save failed: {err}"));
+            }
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimElement, BimModel, validate_parameters};
+use cryxtal_io::{
+    PROJECT_EXTENSION, PackedElement, PackedMesh, Project, geometry_key, import_obj,
+    load_mesh_cache, load_project, mesh_cache_path, read_project, reference_mesh_element,
+    save_mesh_cache, save_project,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::phases::PhaseState;
+use super::{CryxtalApp, ToolMode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum ProjectDialog {
+    Open,
+    SaveAs,
+    /// Adds an OBJ mesh to the model as a reference element.
+    ImportObj,
+    /// Shows a point cloud over the model as context.
+    ImportPointCloud,
+    /// Saves the session journal.
+    ExportJournal,
+    /// Replays a session journal on the open model.
+    ReplayJournal,
+    /// Writes element parameters to CSV or JSON.
+    ExportParameters,
+    /// Reads edited element parameters back.
+    ImportParameters,
+}
+
+/// Meshes from the `.cxmesh` file next to an opened project, keyed by the geometry key of the
+/// solid they were tessellated from.
+pub(super) type MeshCache = HashMap<u64, PackedMesh>;
+
+impl ProjectDialog {
+    fn title(self) -> &'static str {
+        match self {
+            ProjectDialog::Open => "Open Project",
+            ProjectDialog::SaveAs => "Save Project As",
+            ProjectDialog::ImportObj => "Import OBJ",
+            ProjectDialog::ImportPointCloud => "Import Point Cloud",
+            ProjectDialog::ExportJournal => "Export Journal",
+            ProjectDialog::ReplayJournal => "Replay Journal",
+            ProjectDialog::ExportParameters => "Export Parameters",
+            ProjectDialog::ImportParameters => "Import Parameters",
+        }
+    }
+}
+
+impl CryxtalApp {
+    pub(super) fn open_project_dialog(&mut self, dialog: ProjectDialog) {
+        if self.project_path_input.is_empty() {
+            self.project_path_input = match &self.project_path {
+                Some(path) => path.display().to_string(),
+                None => format!("{}.{PROJECT_EXTENSION}", self.model.name),
+            };
+        }
+        self.project_dialog = Some(dialog);
+    }
+
+    pub(super) fn project_dialog_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = self.project_dialog else {
+            return;
+        };
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new(dialog.title())
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(match dialog {
+                    ProjectDialog::ImportObj => "Path of the .obj mesh",
+                    ProjectDialog::ImportPointCloud => "Path of the .ply, .xyz or .pts cloud",
+                    ProjectDialog::ExportJournal | ProjectDialog::ReplayJournal => {
+                        "Path of the .cxjournal session journal"
+                    }
+                    ProjectDialog::ExportParameters | ProjectDialog::ImportParameters => {
+                        "Path of the .csv or .json parameter sheet"
+                    }
+                    _ => "Path (.cxp for JSON, .cxpb for binary)",
+                });
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.project_path_input).desired_width(360.0),
+                );
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    confirmed = true;
+                }
+                if dialog == ProjectDialog::ImportPointCloud {
+                    ui.horizontal(|ui| {
+                        ui.label("Keep one point per");
+                        ui.add(
+                            egui::DragValue::new(&mut self.point_clouds.decimation)
+                                .range(0.0..=10000.0)
+                                .speed(1.0),
+                        );
+                    })
+                    .response
+                    .on_hover_text("Cube size in model units; 0 keeps every point");
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    let label = match dialog {
+                        ProjectDialog::Open => "Open",
+                        ProjectDialog::SaveAs => "Save",
+                        ProjectDialog::ImportObj
+                        | ProjectDialog::ImportPointCloud
+                        | ProjectDialog::ImportParameters => "Import",
+                        ProjectDialog::ExportJournal | ProjectDialog::ExportParameters => "Export",
+                        ProjectDialog::ReplayJournal => "Replay",
+                    };
+                    if ui.button(label).clicked() {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if confirmed {
+            let path = PathBuf::from(self.project_path_input.trim());
+            match dialog {
+                ProjectDialog::Open => self.open_project_from(path),
+                ProjectDialog::SaveAs => self.save_project_to(path),
+                ProjectDialog::ImportObj => self.import_obj_from(&path),
+                ProjectDialog::ImportPointCloud => self.import_point_cloud_from(&path),
+                ProjectDialog::ExportJournal => self.export_journal_to(&path),
+                ProjectDialog::ReplayJournal => self.replay_journal_from(&path),
+                ProjectDialog::ExportParameters => self.export_parameters_to(&path),
+                ProjectDialog::ImportParameters => self.import_parameters_from(&path),
+            }
+        }
+        if confirmed || cancelled || !open {
+            self.project_dialog = None;
+        }
+    }
+
+    /// Adds the mesh at `path` as a reference element named after the file.
+    fn import_obj_from(&mut self, path: &Path) {
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("Mesh")
+            .to_string();
+        match import_obj(path).and_then(|mesh| reference_mesh_element(&name, &mesh)) {
+            Ok(element) => self.add_elements(vec![element], &format!("Imported {name}"), false),
+            Err(err) => self.notify_error(format!("Import failed: {err:#}")),
+        }
+    }
+
+    pub(super) fn save_project_to(&mut self, path: PathBuf) {
+        if self.edit_mode.is_read_only() && self.project_path.as_ref() == Some(&path) {
+            self.notify_error("The project is open read-only; save it as a new file".to_string());
+            return;
+        }
+        if self.project_path.as_ref() != Some(&path)
+            && let Some(stem) = path.file_stem().and_then(|stem| stem.to_str())
+        {
+            self.model.name = stem.to_string();
+        }
+        let project = Project {
+            model: self.model.clone(),
+            camera: Some(self.viewer.camera()),
+        };
+        match save_project(&project, &path) {
+            Ok(()) => {
+                self.dirty = false;
+                self.notify_success(format!("Saved {}", path.display()));
+                self.write_mesh_cache(&path);
+                self.project_path = Some(path);
+            }
+            Err(err) => self.notify_error(format!("Save failed: {err:#}")),
+        }
+    }
+
+    pub(super) fn open_project_from(&mut self, path: PathBuf) {
+        let project = match load_project(&path) {
+            Ok(project) => project,
+            Err(err) => {
+                self.notify_error(format!("Open failed: {err:#}"));
+                return;
+            }
+        };
+        // A missing or outdated cache only means tessellating from scratch.
+        let cache = load_mesh_cache(mesh_cache_path(&path)).unwrap_or_default();
+        self.open_project(project, cache, &path.display().to_string());
+        self.project_path = Some(path);
+    }
+
+    /// Opens a project file read into memory, such as one dropped on the browser page. It
+    /// has no path, so saving asks for one.
+    pub(super) fn open_project_bytes(&mut self, name: &str, bytes: &[u8]) {
+        match read_project(bytes) {
+            Ok(project) => {
+                self.open_project(project, MeshCache::new(), name);
+                self.project_path = None;
+            }
+            Err(err) => self.notify_error(format!("Open failed: {name}: {err:#}")),
+        }
+    }
+
+    fn open_project(&mut self, project: Project, cache: MeshCache, name: &str) {
+        self.cancel_background_edit();
+        let unit = self.model.units.length;
+        self.model = project.model;
+        self.adopt_length_unit(unit);
+        self.model.author = self.preferences.author();
+        self.history.clear();
+        self.active_layer = 0;
+        self.hidden.clear();
+        self.phase_view = PhaseState::default();
+        self.tool_mode = ToolMode::Select;
+        self.clear_selection_drag();
+        self.pending_wall_start = None;
+        self.pending_rebar_start = None;
+        self.pending_reference.clear();
+        self.pending_slab.clear();
+        self.last_point = None;
+        self.measurement = None;
+        self.set_selected(None);
+        self.mesh_cache = cache;
+        // Meshes of the previous project must not be reused for elements with the same guid.
+        self.element_mesh_guids.clear();
+        self.rebuild_scene();
+        match project.camera {
+            Some(camera) => self.viewer.set_camera(&camera),
+            None => self.fit_model(),
+        }
+        self.dirty = false;
+        self.notify_success(format!("Opened {name}"));
+        let orphans = self.model.orphans().len();
+        if orphans > 0 {
+            self.notify_warning(format!("{orphans} element(s) refer to a missing host"));
+        }
+        let invalid = self
+            .model
+            .elements()
+            .iter()
+            .filter(|element| !validate_parameters(element).is_empty())
+            .count();
+        if invalid > 0 {
+            self.notify_warning(format!(
+                "{invalid} element(s) have parameters that break their schema"
+            ));
+        }
+        let shortfalls = self.model.rating_shortfalls().len();
+        if shortfalls > 0 {
+            self.notify_warning(format!(
+                "{shortfalls} opening rating(s) fall short of their wall's"
+            ));
+        }
+    }
+
+    /// Replaces the model with `model`, as handed over by library code to preview.
+    pub(super) fn show_model(&mut self, model: BimModel) {
+        self.cancel_background_edit();
+        let unit = self.model.units.length;
+        self.model = model;
+        self.adopt_length_unit(unit);
+        self.model.author = self.preferences.author();
+        self.history.clear();
+        self.project_path = None;
+        self.set_selected(None);
+        self.element_mesh_guids.clear();
+        self.rebuild_scene();
+        self.fit_model();
+        self.dirty = false;
+    }
+
+    /// Stores the current element meshes next to the project so reopening it skips
+    /// tessellation. Meshes whose element is gone or has changed geometry since it was
+    /// tessellated are out of date and left out; identical geometry is stored once. Failing to
+    /// write the cache does not fail the save.
+    fn write_mesh_cache(&mut self, project_path: &Path) {
+        let elements: HashMap<Guid, &BimElement> = self
+            .model
+            .elements()
+            .iter()
+            .map(|element| (element.guid, element))
+            .collect();
+        let mut written = HashSet::new();
+        let entries: Vec<PackedElement> = self
+            .element_mesh_guids
+            .iter()
+            .zip(&self.element_mesh_keys)
+            .zip(&self.element_polymeshes)
+            .filter_map(|((guid, key), mesh)| {
+                let (element, key) = (elements.get(guid)?, (*key)?);
+                let current = geometry_key(element.geometry()).ok()?;
+                (current == key && written.insert(key)).then(|| PackedElement {
+                    guid: *guid,
+                    name: element.name.clone(),
+                    key: Some(key),
+                    mesh: PackedMesh::from_mesh(mesh),
+                })
+            })
+            .collect();
+        if let Err(err) = save_mesh_cache(mesh_cache_path(project_path), entries) {
+            self.notify_warning(format!("Mesh cache not written: {err:#}"));
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use cryxtal_bim::ModelCommand;
+use egui::Ui;
+
+use super::{CryxtalApp, ToolMode};
+
+/// Whether the model can be edited. Read-only sessions still navigate, select, filter and add
+/// annotations such as reference geometry, but refuse every other model edit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) enum EditMode {
+    #[default]
+    Edit,
+    ReadOnly,
+    /// Read-only from the command line (`--read-only`); the session cannot leave it.
+    Review,
+}
+
+impl EditMode {
+    pub(super) fn is_read_only(self) -> bool {
+        self != EditMode::Edit
+    }
+}
+
+impl CryxtalApp {
+    /// Whether `command` may be applied in the current mode; refused edits are reported.
+    pub(super) fn allows_edit(&mut self, label: &str, command: &ModelCommand) -> bool {
+        if !self.edit_mode.is_read_only() || command.is_annotation() {
+            return true;
+        }
+        self.notify_warning(format!("{label} is not available in read-only mode"));
+        false
+    }
+
+    /// Whether the next undo only touches annotations or the model is editable.
+    pub(super) fn can_undo(&self) -> bool {
+        self.history
+            .undo_command()
+            .is_some_and(|command| !self.edit_mode.is_read_only() || command.is_annotation())
+    }
+
+    pub(super) fn can_redo(&self) -> bool {
+        self.history
+            .redo_command()
+            .is_some_and(|command| !self.edit_mode.is_read_only() || command.is_annotation())
+    }
+
+    /// Read-only switch for the top bar; locked on in review sessions.
+    pub(super) fn read_only_toggle(&mut self, ui: &mut Ui) {
+        let mut read_only = self.edit_mode.is_read_only();
+        let toggle = ui
+            .add_enabled(
+                self.edit_mode != EditMode::Review,
+                egui::Checkbox::new(&mut read_only, "Read-only"),
+            )
+            .on_disabled_hover_text("Opened for review with --read-only");
+        if toggle.changed() {
+            self.set_read_only(read_only);
+        }
+    }
+
+    pub(super) fn set_read_only(&mut self, read_only: bool) {
+        if self.edit_mode == EditMode::Review {
+            return;
+        }
+        self.edit_mode = if read_only {
+            EditMode::ReadOnly
+        } else {
+            EditMode::Edit
+        };
+        if read_only && !matches!(self.tool_mode, ToolMode::Select | ToolMode::CreateReference) {
+            self.tool_mode = ToolMode::Select;
+            self.clear_selection_drag();
+            self.pending_wall_start = None;
+            self.pending_rebar_start = None;
+            self.pending_slab.clear();
+        }
+        let mode = if read_only { "read-only" } else { "editable" };
+        self.push_log(format!("Model is {mode}"));
+    }
+}
@@ -1,28 +1,32 @@
-use cryxtal_bim::BimCategory;
+use cryxtal_base::{Guid, format_length};
+use cryxtal_bim::{BimCategory, ModelCommand};
 use cryxtal_topology::Point3;
 use egui::Ui;
 
-use crate::elements::{apply_rebar_edit, build_rebar_between_points, rebar_data};
+use crate::elements::{apply_rebar_edit, build_rebar_between_points, is_rebar_set, rebar_data};
 use crate::viewer::{Point2, Rect};
 
+use super::lengths::length_value;
 use super::{CryxtalApp, ToolMode};
 
 impl CryxtalApp {
     pub(super) fn rebar_panel(&mut self, ui: &mut Ui) {
         ui.heading("Rebar Tool");
 
+        self.tool_type_controls(ui, BimCategory::Rebar);
+        let unit = self.model.units.length;
         ui.label("Diameter");
-        ui.add(
-            egui::DragValue::new(&mut self.rebar_params.diameter)
-                .range(2.0..=1000.0)
-                .speed(1.0)
-                .fixed_decimals(1),
+        ui.add_enabled(
+            self.rebar_params.type_id.is_none(),
+            length_value(&mut self.rebar_params.diameter, unit, 2.0..=1000.0),
         );
 
         ui.label("Name");
         ui.add(egui::TextEdit::singleline(&mut self.rebar_params.name));
 
         ui.label(self.rebar_status_text());
+        self.polar_tracking_controls(ui);
+        self.coordinate_entry(ui);
 
         if ui.button("Cancel Rebar").clicked() {
             self.cancel_rebar();
@@ -33,12 +37,16 @@ impl CryxtalApp {
         let Some(selected) = self.selected else {
             return;
         };
-        let Some(rebar) = self.elements.get(selected) else {
+        let Some(rebar) = self.model.elements().get(selected) else {
             return;
         };
         if rebar.category != BimCategory::Rebar {
             return;
         }
+        if is_rebar_set(rebar) {
+            self.rebar_set_properties_panel(ui, selected);
+            return;
+        }
 
         let data = match rebar_data(rebar) {
             Ok(data) => data,
@@ -58,78 +66,52 @@ impl CryxtalApp {
         let mut end_z = end.z;
         let mut diameter = data.diameter;
 
+        let unit = self.model.units.length;
         ui.heading("Rebar Properties");
-        ui.label(format!("Length: {:.1}", data.length));
+        ui.label(format!("Length: {}", format_length(data.length, unit)));
+        let host = match rebar.host {
+            Some(host) => match self.model.element(host) {
+                Some(wall) => wall.name.clone(),
+                None => format!("{host} (missing)"),
+            },
+            None => "None".to_string(),
+        };
+        ui.label(format!("Host: {host}"));
 
         ui.add_space(6.0);
         ui.label("Start X");
         let changed_start_x = ui
-            .add(
-                egui::DragValue::new(&mut start_x)
-                    .range(-1.0e6..=1.0e6)
-                    .speed(1.0)
-                    .fixed_decimals(2),
-            )
+            .add(length_value(&mut start_x, unit, -1.0e6..=1.0e6))
             .changed();
 
         ui.label("Start Y");
         let changed_start_y = ui
-            .add(
-                egui::DragValue::new(&mut start_y)
-                    .range(-1.0e6..=1.0e6)
-                    .speed(1.0)
-                    .fixed_decimals(2),
-            )
+            .add(length_value(&mut start_y, unit, -1.0e6..=1.0e6))
             .changed();
 
         ui.label("Start Z");
         let changed_start_z = ui
-            .add(
-                egui::DragValue::new(&mut start_z)
-                    .range(-1.0e6..=1.0e6)
-                    .speed(1.0)
-                    .fixed_decimals(2),
-            )
+            .add(length_value(&mut start_z, unit, -1.0e6..=1.0e6))
             .changed();
 
         ui.label("End X");
         let changed_end_x = ui
-            .add(
-                egui::DragValue::new(&mut end_x)
-                    .range(-1.0e6..=1.0e6)
-                    .speed(1.0)
-                    .fixed_decimals(2),
-            )
+            .add(length_value(&mut end_x, unit, -1.0e6..=1.0e6))
             .changed();
 
         ui.label("End Y");
         let changed_end_y = ui
-            .add(
-                egui::DragValue::new(&mut end_y)
-                    .range(-1.0e6..=1.0e6)
-                    .speed(1.0)
-                    .fixed_decimals(2),
-            )
+            .add(length_value(&mut end_y, unit, -1.0e6..=1.0e6))
             .changed();
 
         ui.label("End Z");
         let changed_end_z = ui
-            .add(
-                egui::DragValue::new(&mut end_z)
-                    .range(-1.0e6..=1.0e6)
-                    .speed(1.0)
-                    .fixed_decimals(2),
-            )
+            .add(length_value(&mut end_z, unit, -1.0e6..=1.0e6))
             .changed();
 
         ui.label("Diameter");
         let changed_diameter = ui
-            .add(
-                egui::DragValue::new(&mut diameter)
-                    .range(2.0..=1000.0)
-                    .speed(1.0)
-                    .fixed_decimals(1),
-            )
+            .add(length_value(&mut diameter, unit, 2.0..=1000.0))
             .changed();
 
         if changed_start_x
@@ -170,26 +152,50 @@ impl CryxtalApp {
     }
 
     pub(super) fn handle_rebar_click(&mut self, pos: Point2, rect: Rect) {
-        let Some(point) = self.viewer.pick_point(pos, rect, &self.element_meshes, true) else {
+        if let Some(point) = self.placement_point(pos, rect) {
+            self.place_rebar_point(point);
+        }
+    }
+
+    pub(super) fn place_rebar_point(&mut self, point: Point3) {
+        self.last_point = Some(point);
+        let Some(start) = self.pending_rebar_start else {
+            self.pending_rebar_start = Some(point);
+            self.push_log("Rebar start set".to_string());
             return;
         };
-        let point = Point3::new(point.x, point.y, point.z);
-        let name = self.rebar_params.name.clone();
 
-        if let Some(start) = self.pending_rebar_start {
-            match build_rebar_between_points(start, point, self.rebar_params.diameter, Some(&name)) {
-                Ok(element) => {
-                    self.pending_rebar_start = None;
-                    self.add_elements(vec![element], "Rebar added", false);
-                }
-                Err(err) => self.push_log(format!("Rebar build failed: {err}")),
+        let name = self.rebar_params.name.clone();
+        match build_rebar_between_points(start, point, self.rebar_params.diameter, Some(&name)) {
+            Ok(mut element) => {
+                self.pending_rebar_start = None;
+                element.host = self.rebar_host_at(Point3::new(
+                    (start.x + point.x) * 0.5,
+                    (start.y + point.y) * 0.5,
+                    (start.z + point.z) * 0.5,
+                ));
+                self.apply_tool_type(&mut element);
+                self.add_elements(vec![element], "Rebar added", false);
             }
-        } else {
-            self.pending_rebar_start = Some(point);
-            self.push_log("Rebar start set".to_string());
+            Err(err) => self.notify_error(format!("Rebar build failed: {err}")),
         }
     }
 
+    /// The wall whose bounds hold `point`; rebar placed there is hosted by it.
+    fn rebar_host_at(&self, point: Point3) -> Option<Guid> {
+        self.model
+            .elements()
+            .iter()
+            .zip(&self.element_meshes)
+            .find_map(|(element, mesh)| {
+                let (min, max) = mesh.bounds?;
+                let inside = (min.x..=max.x).contains(&point.x)
+                    && (min.y..=max.y).contains(&point.y)
+                    && (min.z..=max.z).contains(&point.z);
+                (element.category == BimCategory::Wall && inside).then_some(element.guid)
+            })
+    }
+
     fn rebar_status_text(&self) -> String {
         if self.tool_mode != ToolMode::CreateRebar {
             return String::new();
@@ -201,19 +207,20 @@ impl CryxtalApp {
         }
     }
 
-    fn apply_rebar_edits(
-        &mut self,
-        index: usize,
-        points: &[Point3],
-        diameter: f64,
-    ) {
-        let Some(rebar) = self.elements.get_mut(index) else {
+    fn apply_rebar_edits(&mut self, index: usize, points: &[Point3], diameter: f64) {
+        let Some(mut rebar) = self.model.elements().get(index).cloned() else {
             return;
         };
-        if let Err(err) = apply_rebar_edit(rebar, points, diameter) {
-            self.push_log(format!("Rebar update failed: {err}"));
+        if let Err(err) = apply_rebar_edit(&mut rebar, points, diameter) {
+            self.notify_error(format!("Rebar update failed: {err}"));
             return;
         }
-        self.rebuild_scene();
+        let label = format!("Edit {}", rebar.name);
+        self.apply_merged_command(
+            &label,
+            ModelCommand::ReplaceElements {
+                elements: vec![rebar],
+            },
+        );
     }
 }
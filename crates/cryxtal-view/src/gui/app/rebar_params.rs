@@ -1,6 +1,19 @@
+use cryxtal_base::Guid;
+
+use crate::elements::{RebarFace, RebarShape};
+
 pub struct RebarParams {
     pub diameter: f64,
     pub name: String,
+    /// Rebar type (bar size) new bars are instances of; it fixes `diameter`.
+    pub type_id: Option<Guid>,
+    /// Options for rebar sets added from a wall or slab's properties.
+    pub set_shape: RebarShape,
+    pub set_face: RebarFace,
+    pub set_spacing: f64,
+    pub set_cover: f64,
+    /// Fixed bar count; `None` fills the face at `set_spacing`.
+    pub set_count: Option<usize>,
 }
 
 impl Default for RebarParams {
@@ -8,6 +21,12 @@ impl Default for RebarParams {
         Self {
             diameter: 16.0,
             name: String::new(),
+            type_id: None,
+            set_shape: RebarShape::Straight,
+            set_face: RebarFace::Left,
+            set_spacing: 200.0,
+            set_cover: 30.0,
+            set_count: None,
         }
     }
 }
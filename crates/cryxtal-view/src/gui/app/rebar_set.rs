@@ -0,0 +1,269 @@
+use cryxtal_base::{Guid, LengthUnit};
+use cryxtal_bim::{BimElement, Constraint, ModelCommand, ParameterValue};
+use egui::Ui;
+use std::collections::HashMap;
+
+use crate::elements::{
+    RebarFace, RebarSet, RebarShape, apply_rebar_set, build_rebar_set, is_rebar_set,
+    regenerate_rebar_set,
+};
+
+use super::CryxtalApp;
+use super::lengths::length_value;
+
+/// Leg length or tie depth in millimetres a set starts with when switched to a bent shape.
+const DEFAULT_SHAPE_DIMENSION: f64 = 300.0;
+
+impl CryxtalApp {
+    /// Rebar set section of a selected wall or slab's properties: lays a new set along one of
+    /// its faces.
+    pub(super) fn rebar_set_host_panel(&mut self, ui: &mut Ui, index: usize) {
+        let Some(host) = self.model.elements().get(index) else {
+            return;
+        };
+        let faces = RebarFace::for_host(host.category);
+        if faces.is_empty() {
+            return;
+        }
+        if !faces.contains(&self.rebar_params.set_face) {
+            self.rebar_params.set_face = faces[0];
+        }
+
+        ui.add_space(8.0);
+        ui.label("Rebar Set");
+        let mut set = RebarSet {
+            host: host.guid,
+            shape: self.rebar_params.set_shape,
+            face: self.rebar_params.set_face,
+            diameter: self.rebar_params.diameter,
+            spacing: self.rebar_params.set_spacing,
+            count: self.rebar_params.set_count,
+            cover: self.rebar_params.set_cover,
+        };
+        let unit = self.model.units.length;
+        rebar_set_controls(ui, "new_rebar_set", faces, &mut set, true, unit);
+        self.rebar_params.set_shape = set.shape;
+        self.rebar_params.set_face = set.face;
+        self.rebar_params.set_spacing = set.spacing;
+        self.rebar_params.set_count = set.count;
+        self.rebar_params.set_cover = set.cover;
+        if self.rebar_params.type_id.is_none() {
+            self.rebar_params.diameter = set.diameter;
+        }
+
+        if ui.button("Add Rebar Set").clicked() {
+            let Some(host) = self.model.elements().get(index) else {
+                return;
+            };
+            let name = self.rebar_params.name.clone();
+            match build_rebar_set(&set, host, Some(&name)) {
+                Ok(mut element) => {
+                    self.apply_tool_type(&mut element);
+                    self.add_elements(vec![element], "Rebar set added", false);
+                }
+                Err(err) => self.notify_error(format!("Rebar set failed: {err}")),
+            }
+        }
+    }
+
+    /// Properties of a selected rebar set; edits rebuild its bars against the host.
+    pub(super) fn rebar_set_properties_panel(&mut self, ui: &mut Ui, index: usize) {
+        let Some(element) = self.model.elements().get(index) else {
+            return;
+        };
+        ui.heading("Rebar Set Properties");
+        let set = match RebarSet::read(element) {
+            Ok(set) => set,
+            Err(err) => {
+                ui.label(format!("Rebar set data error: {err}"));
+                return;
+            }
+        };
+        let Some(host) = self.model.element(set.host) else {
+            ui.label(format!("Host: {} (missing)", set.host));
+            return;
+        };
+        ui.label(format!("Host: {}", host.name));
+        if let Some(ParameterValue::Integer(count)) = element.parameters.get("BarCount") {
+            ui.label(format!("Bars: {count}"));
+        }
+        if let Some(ParameterValue::Number(length)) = element.parameters.get("MaxCutLength") {
+            ui.label(format!("Cut length: {length:.1}"));
+        }
+        if let Some(ParameterValue::Number(length)) = element.parameters.get("Length") {
+            ui.label(format!("Total length: {length:.1}"));
+        }
+
+        let mut edited = set;
+        let faces = RebarFace::for_host(host.category);
+        rebar_set_controls(
+            ui,
+            "rebar_set",
+            faces,
+            &mut edited,
+            element.type_id.is_none(),
+            self.model.units.length,
+        );
+        let face_offset = Constraint::FaceOffset {
+            face: set.face.label().to_string(),
+            offset: set.cover,
+        };
+        let mut kept = element.constraints.contains(&face_offset);
+        let keep_changed = ui
+            .checkbox(&mut kept, "Keep face and cover")
+            .on_hover_text("The bars stay this far inside this face as the host changes")
+            .changed();
+        if edited == set && !keep_changed {
+            return;
+        }
+        let mut element = element.clone();
+        if let Err(err) = apply_rebar_set(&mut element, &edited, host) {
+            self.notify_error(format!("Rebar set update failed: {err}"));
+            return;
+        }
+        // A kept face and cover follow edits to the set.
+        element
+            .constraints
+            .retain(|constraint| !matches!(constraint, Constraint::FaceOffset { .. }));
+        if kept {
+            element.constraints.push(Constraint::FaceOffset {
+                face: edited.face.label().to_string(),
+                offset: edited.cover,
+            });
+        }
+        let label = format!("Edit {}", element.name);
+        self.apply_merged_command(
+            &label,
+            ModelCommand::ReplaceElements {
+                elements: vec![element],
+            },
+        );
+    }
+
+    /// Adds the rebar sets hosted by walls and slabs that `command` replaces, rebuilt against
+    /// the replaced hosts, so the sets follow host edits in the same undo step. Hosts whose
+    /// parameters did not change leave their sets alone. Sets that no longer fit keep their
+    /// bars and are reported.
+    pub(super) fn with_hosted_rebar_sets(&mut self, command: ModelCommand) -> ModelCommand {
+        let mut replaced = HashMap::new();
+        collect_replaced(&command, &mut replaced);
+        let mut sets = Vec::new();
+        let mut failures = Vec::new();
+        for element in self.model.elements() {
+            let Some(host) = element.host.and_then(|host| replaced.get(&host)) else {
+                continue;
+            };
+            if !is_rebar_set(element) || replaced.contains_key(&element.guid) {
+                continue;
+            }
+            let unchanged = self
+                .model
+                .element(host.guid)
+                .is_some_and(|current| current.parameter_hash() == host.parameter_hash());
+            if unchanged {
+                continue;
+            }
+            let mut set = element.clone();
+            match regenerate_rebar_set(&mut set, host) {
+                Ok(()) => sets.push(set),
+                Err(err) => failures.push(format!("{}: {err}", element.name)),
+            }
+        }
+        for failure in failures {
+            self.notify_warning(format!("Rebar set not regenerated: {failure}"));
+        }
+        if sets.is_empty() {
+            return command;
+        }
+        ModelCommand::Batch(vec![
+            command,
+            ModelCommand::ReplaceElements { elements: sets },
+        ])
+    }
+}
+
+fn rebar_set_controls(
+    ui: &mut Ui,
+    id: &str,
+    faces: &[RebarFace],
+    set: &mut RebarSet,
+    diameter_enabled: bool,
+    unit: LengthUnit,
+) {
+    ui.label("Shape");
+    let dimension = set
+        .shape
+        .dimension()
+        .unwrap_or(DEFAULT_SHAPE_DIMENSION * LengthUnit::Millimeter.factor_to(unit));
+    egui::ComboBox::from_id_source(format!("{id}_shape"))
+        .selected_text(set.shape.label())
+        .show_ui(ui, |ui| {
+            for label in RebarShape::LABELS {
+                if ui
+                    .selectable_label(set.shape.label() == label, label)
+                    .clicked()
+                    && let Some(shape) = RebarShape::from_label(label, dimension)
+                {
+                    set.shape = shape;
+                }
+            }
+        });
+    if let Some(mut dimension) = set.shape.dimension() {
+        let label = match set.shape {
+            RebarShape::Stirrup { .. } => "Tie depth",
+            _ => "Leg",
+        };
+        ui.label(label);
+        ui.add(length_value(&mut dimension, unit, 1.0..=100000.0));
+        if let Some(shape) = RebarShape::from_label(set.shape.label(), dimension) {
+            set.shape = shape;
+        }
+    }
+
+    ui.label("Face");
+    egui::ComboBox::from_id_source(id)
+        .selected_text(set.face.label())
+        .show_ui(ui, |ui| {
+            for face in faces {
+                ui.selectable_value(&mut set.face, *face, face.label());
+            }
+        });
+
+    ui.label("Diameter");
+    ui.add_enabled(
+        diameter_enabled,
+        length_value(&mut set.diameter, unit, 2.0..=1000.0),
+    );
+    ui.label("Spacing");
+    ui.add(length_value(&mut set.spacing, unit, 10.0..=10000.0));
+    ui.label("Cover");
+    ui.add(length_value(&mut set.cover, unit, 0.0..=1000.0));
+
+    let mut fixed = set.count.is_some();
+    ui.checkbox(&mut fixed, "Fixed bar count");
+    if fixed {
+        let mut count = set.count.unwrap_or(1);
+        ui.add(egui::DragValue::new(&mut count).range(1..=10000).speed(1.0));
+        set.count = Some(count);
+    } else {
+        set.count = None;
+    }
+}
+
+/// Elements replaced by `command`, by guid.
+pub(super) fn collect_replaced<'a>(
+    command: &'a ModelCommand,
+    replaced: &mut HashMap<Guid, &'a BimElement>,
+) {
+    match command {
+        ModelCommand::ReplaceElements { elements } => {
+            replaced.extend(elements.iter().map(|element| (element.guid, element)));
+        }
+        ModelCommand::Batch(commands) => {
+            for command in commands {
+                collect_replaced(command, replaced);
+            }
+        }
+        _ => {}
+    }
+}
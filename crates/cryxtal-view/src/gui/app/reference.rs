@@ -0,0 +1,172 @@
+use cryxtal_bim::{ModelCommand, ReferenceGeometry, ReferenceKind};
+use cryxtal_topology::Point3;
+use egui::Ui;
+
+use crate::viewer::ReferenceShape;
+
+use super::{CryxtalApp, ToolMode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum ReferenceTool {
+    Point,
+    Line,
+    Plane,
+}
+
+impl ReferenceTool {
+    fn label(self) -> &'static str {
+        match self {
+            ReferenceTool::Point => "Point",
+            ReferenceTool::Line => "Line",
+            ReferenceTool::Plane => "Plane",
+        }
+    }
+
+    fn points_needed(self) -> usize {
+        match self {
+            ReferenceTool::Point => 1,
+            ReferenceTool::Line => 2,
+            ReferenceTool::Plane => 3,
+        }
+    }
+}
+
+impl CryxtalApp {
+    pub(super) fn reference_panel(&mut self, ui: &mut Ui) {
+        ui.heading("Reference Tool");
+
+        ui.horizontal(|ui| {
+            for tool in [
+                ReferenceTool::Point,
+                ReferenceTool::Line,
+                ReferenceTool::Plane,
+            ] {
+                if ui
+                    .selectable_label(self.reference_tool == tool, tool.label())
+                    .clicked()
+                {
+                    self.reference_tool = tool;
+                    self.pending_reference.clear();
+                }
+            }
+        });
+        ui.checkbox(&mut self.show_references, "Show references");
+
+        ui.label(self.reference_status_text());
+        self.polar_tracking_controls(ui);
+        self.coordinate_entry(ui);
+
+        if ui.button("Cancel Reference").clicked() {
+            self.cancel_reference();
+        }
+
+        ui.add_space(8.0);
+        ui.label(format!("References ({})", self.model.references.len()));
+        let mut delete = None;
+        for reference in &self.model.references {
+            ui.horizontal(|ui| {
+                ui.label(&reference.name);
+                if ui.small_button("Delete").clicked() {
+                    delete = Some(reference.guid);
+                }
+            });
+        }
+        if let Some(guid) = delete {
+            self.apply_command("Delete reference", ModelCommand::DeleteReference { guid });
+        }
+    }
+
+    pub(super) fn reference_status_text(&self) -> String {
+        let step = match (self.reference_tool, self.pending_reference.len()) {
+            (ReferenceTool::Point, _) => "pick point",
+            (ReferenceTool::Line, 0) => "pick first point",
+            (ReferenceTool::Line, _) => "pick second point",
+            (ReferenceTool::Plane, 0) => "pick first point",
+            (ReferenceTool::Plane, 1) => "pick second point",
+            (ReferenceTool::Plane, _) => "pick third point",
+        };
+        format!(
+            "Reference {}: {step}",
+            self.reference_tool.label().to_lowercase()
+        )
+    }
+
+    pub(super) fn activate_reference_tool(&mut self) {
+        self.tool_mode = ToolMode::CreateReference;
+        self.clear_selection_drag();
+        self.pending_reference.clear();
+        self.set_selected(None);
+    }
+
+    fn cancel_reference(&mut self) {
+        self.tool_mode = ToolMode::Select;
+        self.clear_selection_drag();
+        self.pending_reference.clear();
+        self.viewer.cancel_interaction();
+    }
+
+    pub(super) fn place_reference_point(&mut self, point: Point3) {
+        self.last_point = Some(point);
+        self.pending_reference.push(point);
+        if self.pending_reference.len() < self.reference_tool.points_needed() {
+            return;
+        }
+
+        let points: Vec<[f64; 3]> = self
+            .pending_reference
+            .drain(..)
+            .map(|point| [point.x, point.y, point.z])
+            .collect();
+        let name = format!(
+            "{} {}",
+            self.reference_tool.label(),
+            self.model.references.len() + 1
+        );
+        let reference = match self.reference_tool {
+            ReferenceTool::Point => Ok(ReferenceGeometry::point(name, points[0])),
+            ReferenceTool::Line => ReferenceGeometry::line(name, points[0], points[1]),
+            ReferenceTool::Plane => {
+                ReferenceGeometry::plane(name, [points[0], points[1], points[2]])
+            }
+        };
+        match reference {
+            Ok(reference) => {
+                let message = format!("{} created", reference.name);
+                if self.apply_command("Add reference", ModelCommand::AddReference(reference)) {
+                    self.push_log(message);
+                }
+            }
+            Err(err) => self.notify_error(format!("Reference failed: {err}")),
+        }
+    }
+
+    /// Pushes the model's references to the viewer, or none while they are hidden.
+    pub(super) fn sync_references(&mut self) {
+        let shapes = if self.show_references {
+            self.model
+                .references
+                .iter()
+                .map(|reference| match reference.kind {
+                    ReferenceKind::Point { position } => {
+                        ReferenceShape::Point(point3(position).into())
+                    }
+                    ReferenceKind::Line { origin, direction } => ReferenceShape::Line {
+                        origin: point3(origin).into(),
+                        direction: point3(direction).into(),
+                    },
+                    ReferenceKind::Plane { origin, normal } => ReferenceShape::Plane {
+                        origin: point3(origin).into(),
+                        normal: point3(normal).into(),
+                    },
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        self.viewer.set_references(shapes);
+    }
+}
+
+fn point3(value: [f64; 3]) -> Point3 {
+    Point3::new(value[0], value[1], value[2])
+}
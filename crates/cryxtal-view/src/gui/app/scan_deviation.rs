@@ -0,0 +1,294 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::LegendEntry;
+use cryxtal_io::{
+    DEFAULT_TESSELLATION_TOLERANCE, FaceDeviation, TessellationOptions, import_scan_points,
+    scan_deviation, triangulate_faces,
+};
+use cryxtal_topology::Point3;
+use std::collections::HashMap;
+
+use crate::viewer::{Color32, OverlayPainter, Rect, Stroke, ViewerState};
+
+use super::CryxtalApp;
+use super::commands::AppCommand;
+use super::legend::paint_legend;
+
+/// Faces listed at most in the table, worst first.
+const ROW_LIMIT: usize = 200;
+/// Opacity of the heat map drawn over the model.
+const HEAT_MAP_ALPHA: u8 = 170;
+
+/// Scan deviation window: a laser scan compared with the designed elements, face by face.
+pub(super) struct ScanDeviation {
+    path: String,
+    max_distance: f64,
+    /// Deviation at which the heat map saturates; smaller deviations blend towards green.
+    range: f64,
+    show_heat_map: bool,
+    rows: Vec<FaceDeviation>,
+    /// Triangles of every measured face, keyed like the rows.
+    faces: HashMap<(Guid, usize), Vec<[Point3; 3]>>,
+    /// Scan points read and how many of them were measured.
+    points: (usize, usize),
+    /// Model revision the analysis ran at; edits since make it stale.
+    revision: u64,
+    message: String,
+}
+
+impl Default for ScanDeviation {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            max_distance: 50.0,
+            range: 20.0,
+            show_heat_map: true,
+            rows: Vec::new(),
+            faces: HashMap::new(),
+            points: (0, 0),
+            revision: 0,
+            message: String::new(),
+        }
+    }
+}
+
+impl ScanDeviation {
+    pub(super) fn shows_heat_map(&self) -> bool {
+        self.show_heat_map && !self.rows.is_empty()
+    }
+}
+
+impl CryxtalApp {
+    pub(super) fn open_scan_deviation(&mut self) {
+        self.scan_deviation
+            .get_or_insert_with(ScanDeviation::default);
+    }
+
+    pub(super) fn scan_deviation_window(&mut self, ctx: &egui::Context) {
+        let Some(scan) = self.scan_deviation.as_mut() else {
+            return;
+        };
+        let names: HashMap<Guid, (usize, String)> = self
+            .model
+            .elements()
+            .iter()
+            .enumerate()
+            .map(|(index, element)| (element.guid, (index, element.name.clone())))
+            .collect();
+        let stale = !scan.rows.is_empty() && scan.revision != self.model.revision();
+
+        let mut open = true;
+        let mut run = false;
+        let mut chosen = None;
+        egui::Window::new("Scan Deviation")
+            .open(&mut open)
+            .collapsible(true)
+            .resizable(true)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Scan");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut scan.path)
+                            .hint_text("scan.xyz, .pts, .csv or .obj")
+                            .desired_width(260.0),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Max distance");
+                    ui.add(
+                        egui::DragValue::new(&mut scan.max_distance)
+                            .range(0.1..=10000.0)
+                            .speed(1.0),
+                    )
+                    .on_hover_text("Scan points further than this from every face are ignored");
+                    if ui.button("Analyze").clicked() {
+                        run = true;
+                    }
+                });
+                if !scan.message.is_empty() {
+                    ui.weak(&scan.message);
+                }
+                if scan.rows.is_empty() {
+                    return;
+                }
+                if stale {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(230, 170, 60),
+                        "The model changed since the analysis; analyze again",
+                    );
+                }
+                ui.label(format!(
+                    "{} of {} point(s) measured on {} face(s)",
+                    scan.points.1,
+                    scan.points.0,
+                    scan.rows.len()
+                ));
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut scan.show_heat_map, "Heat map");
+                    ui.label("Range ±");
+                    ui.add(
+                        egui::DragValue::new(&mut scan.range)
+                            .range(0.1..=10000.0)
+                            .speed(0.5),
+                    );
+                });
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(320.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("scan_deviation_rows")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for header in ["Element", "Face", "Points", "Mean", "Max"] {
+                                    ui.strong(header);
+                                }
+                                ui.end_row();
+                                for row in scan.rows.iter().take(ROW_LIMIT) {
+                                    let (index, name) = names
+                                        .get(&row.element)
+                                        .map(|(index, name)| (Some(*index), name.clone()))
+                                        .unwrap_or_else(|| (None, row.element.to_string()));
+                                    if ui.link(name).clicked() {
+                                        chosen = index;
+                                    }
+                                    ui.label((row.face + 1).to_string());
+                                    ui.label(row.points.to_string());
+                                    ui.label(format!("{:+.1}", row.mean));
+                                    let [r, g, b, _] = heat_color(row.worst(), scan.range);
+                                    ui.colored_label(
+                                        egui::Color32::from_rgb(r, g, b),
+                                        format!("{:+.1}", row.worst()),
+                                    );
+                                    ui.end_row();
+                                }
+                            });
+                        if scan.rows.len() > ROW_LIMIT {
+                            ui.weak(format!("{} more face(s)", scan.rows.len() - ROW_LIMIT));
+                        }
+                    });
+            });
+
+        if !open {
+            self.scan_deviation = None;
+            return;
+        }
+        if run {
+            self.run_scan_deviation();
+        }
+        if let Some(index) = chosen {
+            self.set_selected(Some(index));
+            self.execute(AppCommand::ZoomTo(index));
+        }
+    }
+
+    /// Reads the scan and measures it against every visible element, worst faces first.
+    fn run_scan_deviation(&mut self) {
+        let Some(scan) = self.scan_deviation.as_mut() else {
+            return;
+        };
+        let path = scan.path.trim().to_string();
+        let elements: Vec<_> = self
+            .model
+            .elements()
+            .iter()
+            .filter(|element| !self.hidden.contains(&element.guid))
+            .collect();
+        let result = import_scan_points(&path).and_then(|points| {
+            let rows = scan_deviation(
+                elements.iter().copied(),
+                &points,
+                scan.max_distance,
+                DEFAULT_TESSELLATION_TOLERANCE,
+            )?;
+            Ok((points.len(), rows))
+        });
+        let (total, mut rows) = match result {
+            Ok(result) => result,
+            Err(err) => {
+                scan.message = format!("{err:#}");
+                return;
+            }
+        };
+        rows.sort_by(|a, b| b.worst().abs().total_cmp(&a.worst().abs()));
+        scan.faces.clear();
+        for element in &elements {
+            if !rows.iter().any(|row| row.element == element.guid) {
+                continue;
+            }
+            let options = TessellationOptions::chordal(DEFAULT_TESSELLATION_TOLERANCE);
+            for (face, triangles) in triangulate_faces(&element.geometry, &options)
+                .into_iter()
+                .enumerate()
+            {
+                scan.faces.insert((element.guid, face), triangles);
+            }
+        }
+        let measured = rows.iter().map(|row| row.points).sum();
+        scan.points = (total, measured);
+        scan.message.clear();
+        scan.revision = self.model.revision();
+        scan.rows = rows;
+        self.push_log(format!(
+            "Scan deviation: {measured} of {total} point(s) measured from {path}"
+        ));
+    }
+}
+
+/// Measured faces tinted by their worst deviation, with a legend of the color ramp. Only
+/// faces turned towards the camera are drawn, since overlays have no depth test.
+pub(super) fn paint_scan_deviation(
+    viewer: &ViewerState,
+    painter: &mut impl OverlayPainter,
+    rect: Rect,
+    scan: &ScanDeviation,
+) {
+    if !scan.shows_heat_map() {
+        return;
+    }
+    let none = Stroke::new(0.0, Color32::from_rgba_unmultiplied(0, 0, 0, 0));
+    for row in &scan.rows {
+        let Some(triangles) = scan.faces.get(&(row.element, row.face)) else {
+            continue;
+        };
+        let [r, g, b, a] = heat_color(row.worst(), scan.range);
+        let fill = Color32::from_rgba_unmultiplied(r, g, b, a);
+        for triangle in triangles {
+            let Some(points) = triangle
+                .iter()
+                .map(|point| viewer.project_point3(*point, rect))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+            // Screen Y points down, so faces wound counterclockwise in 3D come out negative.
+            let area = (points[1].x - points[0].x) * (points[2].y - points[0].y)
+                - (points[2].x - points[0].x) * (points[1].y - points[0].y);
+            if area < 0.0 {
+                painter.polygon(points, fill, none);
+            }
+        }
+    }
+
+    let steps = [-1.0, -0.5, 0.0, 0.5, 1.0];
+    let legend: Vec<LegendEntry> = steps
+        .iter()
+        .rev()
+        .map(|step| LegendEntry {
+            label: format!("{:+.1}", step * scan.range),
+            color: heat_color(step * scan.range, scan.range),
+        })
+        .collect();
+    paint_legend(painter, rect, "Deviation", &legend);
+}
+
+/// Blue for material missing (inside the design), green on the design, red for material in
+/// excess, saturating at `range`.
+fn heat_color(deviation: f64, range: f64) -> [u8; 4] {
+    let t = (deviation / range).clamp(-1.0, 1.0);
+    let channel = |value: f64| (value * 255.0).round() as u8;
+    if t < 0.0 {
+        [0, channel(1.0 + t), channel(-t), HEAT_MAP_ALPHA]
+    } else {
+        [channel(t), channel(1.0 - t), 0, HEAT_MAP_ALPHA]
+    }
+}
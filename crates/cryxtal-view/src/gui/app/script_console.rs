@@ -0,0 +1,371 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::{ChangeSource, ModelCommand};
+use cryxtal_topology::Point3;
+use egui::Ui;
+use std::path::PathBuf;
+
+use crate::gui::script::{ScriptAction, ScriptEngine, point_literal};
+
+use super::CryxtalApp;
+use super::commands::AppCommand;
+
+const MAX_CONSOLE_LINES: usize = 500;
+
+/// Interactive Rhai console in a bottom panel. Lines starting with `>` echo the input.
+pub(super) struct ScriptConsole {
+    pub(super) open: bool,
+    input: String,
+    output: Vec<String>,
+    history: Vec<String>,
+    /// Position while recalling earlier input with the arrow keys.
+    recall: Option<usize>,
+    engine: ScriptEngine,
+    /// Script lines of the macro being recorded.
+    recording: Option<Vec<String>>,
+    /// Last recorded or loaded macro.
+    macro_script: String,
+    macro_path: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MacroAction {
+    Record,
+    Replay,
+    Export,
+    Load,
+}
+
+impl Default for ScriptConsole {
+    fn default() -> Self {
+        Self {
+            open: false,
+            input: String::new(),
+            output: vec![
+                "Rhai console: elements(), count(category), param(guid, name), \
+                 add_wall([x, y], [x, y]), delete(guid), set_layer(guid, layer), \
+                 set_param(guid, name, value), set_formula(guid, name, formula), select(guid), \
+                 undo(), redo()"
+                    .to_string(),
+            ],
+            history: Vec::new(),
+            recall: None,
+            engine: ScriptEngine::new(),
+            recording: None,
+            macro_script: String::new(),
+            macro_path: "macro.rhai".to_string(),
+        }
+    }
+}
+
+impl ScriptConsole {
+    fn push_output(&mut self, line: String) {
+        self.output.push(line);
+        if self.output.len() > MAX_CONSOLE_LINES {
+            let excess = self.output.len() - MAX_CONSOLE_LINES;
+            self.output.drain(..excess);
+        }
+    }
+
+    fn recall(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let index = match (self.recall, older) {
+            (None, true) => self.history.len() - 1,
+            (None, false) => return,
+            (Some(index), true) => index.saturating_sub(1),
+            (Some(index), false) if index + 1 < self.history.len() => index + 1,
+            (Some(_), false) => {
+                self.recall = None;
+                self.input.clear();
+                return;
+            }
+        };
+        self.recall = Some(index);
+        self.input = self.history[index].clone();
+    }
+}
+
+impl CryxtalApp {
+    pub(super) fn script_console_panel(&mut self, ctx: &egui::Context) {
+        let mut run = false;
+        let mut macro_action = None;
+        egui::TopBottomPanel::bottom("script_console")
+            .resizable(true)
+            .default_height(200.0)
+            .min_height(120.0)
+            .show(ctx, |ui| {
+                let console = &mut self.console;
+                let input_height = 2.0 * ui.spacing().interact_size.y + 20.0;
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .stick_to_bottom(true)
+                    .max_height((ui.available_height() - input_height).max(40.0))
+                    .show(ui, |ui| {
+                        for line in &console.output {
+                            ui.monospace(line);
+                        }
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let width = ui.available_width() - 120.0;
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut console.input)
+                            .font(egui::TextStyle::Monospace)
+                            .hint_text("Rhai expression")
+                            .desired_width(width.max(120.0)),
+                    );
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        run = true;
+                        response.request_focus();
+                    }
+                    if response.has_focus() {
+                        if ui.input(|i| i.key_pressed(egui::Key::ArrowUp)) {
+                            console.recall(true);
+                        } else if ui.input(|i| i.key_pressed(egui::Key::ArrowDown)) {
+                            console.recall(false);
+                        }
+                    }
+                    if ui.button("Run").clicked() {
+                        run = true;
+                    }
+                    if ui.button("Clear").clicked() {
+                        console.output.clear();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let record = if console.recording.is_some() {
+                        "Stop Recording"
+                    } else {
+                        "Record Macro"
+                    };
+                    if ui.button(record).clicked() {
+                        macro_action = Some(MacroAction::Record);
+                    }
+                    let has_macro = !console.macro_script.is_empty();
+                    if ui
+                        .add_enabled(has_macro, egui::Button::new("Replay"))
+                        .clicked()
+                    {
+                        macro_action = Some(MacroAction::Replay);
+                    }
+                    ui.add(
+                        egui::TextEdit::singleline(&mut console.macro_path).desired_width(200.0),
+                    );
+                    if ui
+                        .add_enabled(has_macro, egui::Button::new("Export"))
+                        .clicked()
+                    {
+                        macro_action = Some(MacroAction::Export);
+                    }
+                    if ui.button("Load").clicked() {
+                        macro_action = Some(MacroAction::Load);
+                    }
+                });
+            });
+
+        if run {
+            self.run_input();
+        }
+        match macro_action {
+            Some(MacroAction::Record) => self.toggle_macro_recording(),
+            Some(MacroAction::Replay) => {
+                let script = self.console.macro_script.clone();
+                self.console.push_output("> (macro)".to_string());
+                self.run_script(&script);
+            }
+            Some(MacroAction::Export) => self.export_macro(),
+            Some(MacroAction::Load) => self.load_macro(),
+            None => {}
+        }
+    }
+
+    fn run_input(&mut self) {
+        let code = self.console.input.trim().to_string();
+        if code.is_empty() {
+            return;
+        }
+        self.console.input.clear();
+        self.console.recall = None;
+        if self.console.history.last() != Some(&code) {
+            self.console.history.push(code.clone());
+        }
+        self.console.push_output(format!("> {code}"));
+        self.run_script(&code);
+    }
+
+    fn run_script(&mut self, code: &str) {
+        let run = self.console.engine.run(&mut self.model, code);
+        for line in run.output {
+            self.console.push_output(line);
+        }
+        if let Some(err) = run.error {
+            self.console.push_output(format!("error: {err}"));
+            return;
+        }
+        // Parameter changes the script makes are logged as coming from it.
+        self.model.source = ChangeSource::Script;
+        for action in run.actions {
+            if let Err(err) = self.apply_script_action(action) {
+                self.console.push_output(format!("error: {err}"));
+                break;
+            }
+        }
+        self.model.source = ChangeSource::Gui;
+    }
+
+    /// Applies one edit requested by a script through the command registry; each edit is its
+    /// own undo step.
+    fn apply_script_action(&mut self, action: ScriptAction) -> Result<(), String> {
+        let command = match action {
+            ScriptAction::AddWall { start, end } => {
+                AppCommand::PlaceWall(point3(start), point3(end))
+            }
+            ScriptAction::Delete(guid) => AppCommand::Delete(self.script_index(guid)?),
+            ScriptAction::SetLayer(guid, layer) => {
+                let layer = self
+                    .model
+                    .layers
+                    .iter()
+                    .position(|candidate| candidate.name == layer)
+                    .ok_or_else(|| format!("unknown layer {layer}"))?;
+                AppCommand::SetLayer(self.script_index(guid)?, layer)
+            }
+            // Driving parameters such as `Thickness` regenerate the element in the same step.
+            ScriptAction::SetParameter(guid, name, value) => {
+                let label = format!("Set {name} of {guid}");
+                let command = ModelCommand::SetParameter {
+                    guid,
+                    name,
+                    value: Some(value),
+                };
+                if !self.apply_command(&label, command) {
+                    return Err(format!("{label} failed"));
+                }
+                return Ok(());
+            }
+            ScriptAction::SetFormula(guid, name, expression) => {
+                let label = format!("Set formula of {name} of {guid}");
+                let command = ModelCommand::SetFormula {
+                    guid,
+                    name,
+                    expression: Some(expression),
+                };
+                if !self.apply_command(&label, command) {
+                    return Err(format!("{label} failed"));
+                }
+                return Ok(());
+            }
+            ScriptAction::Select(guid) => {
+                let index = self.script_index(guid)?;
+                self.set_selected(Some(index));
+                return Ok(());
+            }
+            ScriptAction::Hide(guid) => AppCommand::Hide(self.script_index(guid)?),
+            ScriptAction::Isolate(guid) => AppCommand::Isolate(self.script_index(guid)?),
+            ScriptAction::ShowAll => AppCommand::ShowAll,
+            ScriptAction::Copy(guid, anchor) => {
+                AppCommand::Copy(self.script_index(guid)?, point3(anchor))
+            }
+            ScriptAction::Paste(point) => AppCommand::Paste(point3(point)),
+            ScriptAction::Undo => AppCommand::Undo,
+            ScriptAction::Redo => AppCommand::Redo,
+        };
+        self.execute(command);
+        Ok(())
+    }
+
+    fn script_index(&self, guid: Guid) -> Result<usize, String> {
+        self.model
+            .element_index(guid)
+            .ok_or_else(|| format!("unknown element {guid}"))
+    }
+
+    /// Appends `command` to the macro being recorded, as the script call that replays it.
+    /// Commands without a script equivalent (views, files, tool switches) are not recorded.
+    pub(super) fn record_macro(&mut self, command: AppCommand) {
+        if self.console.recording.is_none() {
+            return;
+        }
+        let guid = |index: usize| self.model.elements().get(index).map(|element| element.guid);
+        let line = match command {
+            AppCommand::PlaceWall(start, end) => Some(format!(
+                "add_wall({}, {});",
+                point_literal(start.into()),
+                point_literal(end.into())
+            )),
+            AppCommand::Delete(index) => guid(index).map(|guid| format!("delete(\"{guid}\");")),
+            AppCommand::SetLayer(index, layer) => {
+                let layer = self.model.layers.get(layer).map(|layer| layer.name.clone());
+                guid(index)
+                    .zip(layer)
+                    .map(|(guid, layer)| format!("set_layer(\"{guid}\", {layer:?});"))
+            }
+            AppCommand::Hide(index) => guid(index).map(|guid| format!("hide(\"{guid}\");")),
+            AppCommand::Isolate(index) => guid(index).map(|guid| format!("isolate(\"{guid}\");")),
+            AppCommand::ShowAll => Some("show_all();".to_string()),
+            AppCommand::Copy(index, anchor) => guid(index)
+                .map(|guid| format!("copy(\"{guid}\", {});", point_literal(anchor.into()))),
+            AppCommand::Paste(point) => Some(format!("paste({});", point_literal(point.into()))),
+            AppCommand::Undo => Some("undo();".to_string()),
+            AppCommand::Redo => Some("redo();".to_string()),
+            _ => None,
+        };
+        if let (Some(line), Some(recording)) = (line, self.console.recording.as_mut()) {
+            recording.push(line);
+        }
+    }
+
+    fn toggle_macro_recording(&mut self) {
+        match self.console.recording.take() {
+            Some(lines) => {
+                self.console
+                    .push_output(format!("Recorded {} command(s)", lines.len()));
+                self.console.macro_script = lines.join("\n");
+            }
+            None => {
+                self.console.recording = Some(Vec::new());
+                self.console.push_output("Recording macro".to_string());
+            }
+        }
+    }
+
+    fn export_macro(&mut self) {
+        let path = PathBuf::from(self.console.macro_path.trim());
+        match std::fs::write(&path, format!("{}\n", self.console.macro_script)) {
+            Ok(()) => self.notify_success(format!("Macro exported to {}", path.display())),
+            Err(err) => self.notify_error(format!("Macro export failed: {err}")),
+        }
+    }
+
+    fn load_macro(&mut self) {
+        let path = PathBuf::from(self.console.macro_path.trim());
+        match std::fs::read_to_string(&path) {
+            Ok(script) => {
+                self.console.macro_script = script.trim_end().to_string();
+                self.console
+                    .push_output(format!("Loaded macro from {}", path.display()));
+            }
+            Err(err) => self.notify_error(format!("Macro load failed: {err}")),
+        }
+    }
+
+    pub(super) fn script_console_toggle(&mut self, ui: &mut Ui) {
+        let text = if self.console.recording.is_some() {
+            "Console (recording)"
+        } else {
+            "Console"
+        };
+        if ui
+            .selectable_label(self.console.open, text)
+            .on_hover_text("Rhai scripting console")
+            .clicked()
+        {
+            self.console.open = !self.console.open;
+        }
+    }
+}
+
+fn point3(value: [f64; 3]) -> Point3 {
+    Point3::new(value[0], value[1], value[2])
+}
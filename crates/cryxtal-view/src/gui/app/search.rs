@@ -0,0 +1,135 @@
+use egui::{Key, Modifiers};
+
+use super::CryxtalApp;
+use super::commands::AppCommand;
+
+/// Results shown at most; typing more narrows the search.
+const SEARCH_LIMIT: usize = 50;
+
+/// The search text box; the window only takes arrow keys, Enter and Escape while it has focus.
+const SEARCH_TEXT_ID: &str = "element_search_text";
+
+/// Quick search (Ctrl+F) over element names, guids and parameter values.
+#[derive(Default)]
+pub(super) struct ElementSearch {
+    text: String,
+    /// Element indices matching `text`, in model order.
+    results: Vec<usize>,
+    /// Text and model revision `results` were queried for.
+    queried: Option<(String, u64)>,
+    /// Position in `results` that Enter zooms to.
+    highlighted: usize,
+    /// The text box takes focus on the first frame after opening.
+    focus: bool,
+}
+
+impl CryxtalApp {
+    pub(super) fn open_search(&mut self) {
+        let search = self.search.get_or_insert_with(ElementSearch::default);
+        search.focus = true;
+    }
+
+    pub(super) fn search_window(&mut self, ctx: &egui::Context) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        let text_id = egui::Id::new(SEARCH_TEXT_ID);
+        let (down, up, enter, escape) = if ctx.memory(|memory| memory.has_focus(text_id)) {
+            ctx.input_mut(|i| {
+                (
+                    i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                    i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                    i.consume_key(Modifiers::NONE, Key::Enter),
+                    i.consume_key(Modifiers::NONE, Key::Escape),
+                )
+            })
+        } else {
+            (false, false, false, false)
+        };
+        // Query again only when the text or the model changed, so the indices follow edits
+        // made while the window is open.
+        let revision = self.model.revision();
+        let stale = search
+            .queried
+            .as_ref()
+            .is_none_or(|(text, queried)| *text != search.text || *queried != revision);
+        if stale {
+            search.results = if search.text.trim().is_empty() {
+                Vec::new()
+            } else {
+                self.model.query().text(&search.text).indices()
+            };
+            search.queried = Some((search.text.clone(), revision));
+        }
+        let shown = search.results.len().min(SEARCH_LIMIT);
+        if down {
+            search.highlighted += 1;
+        }
+        if up {
+            search.highlighted = search.highlighted.saturating_sub(1);
+        }
+        search.highlighted = search.highlighted.min(shown.saturating_sub(1));
+
+        let mut open = true;
+        let mut chosen = search
+            .results
+            .get(search.highlighted)
+            .copied()
+            .filter(|_| enter);
+        egui::Window::new("Find")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 48.0))
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut search.text)
+                        .id(text_id)
+                        .hint_text("Name, guid or parameter value")
+                        .desired_width(320.0),
+                );
+                if std::mem::take(&mut search.focus) {
+                    response.request_focus();
+                }
+                if response.changed() {
+                    search.highlighted = 0;
+                }
+
+                if search.results.is_empty() && !search.text.trim().is_empty() {
+                    ui.weak("No matching elements");
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(280.0)
+                    .show(ui, |ui| {
+                        for (row, index) in search.results.iter().take(SEARCH_LIMIT).enumerate() {
+                            let Some(element) = self.model.elements().get(*index) else {
+                                continue;
+                            };
+                            let label = format!("{} ({:?})", element.name, element.category);
+                            let highlighted = row == search.highlighted;
+                            let item = ui.selectable_label(highlighted, label);
+                            if highlighted && (up || down) {
+                                item.scroll_to_me(None);
+                            }
+                            if item.clicked() {
+                                chosen = Some(*index);
+                            }
+                        }
+                    });
+                if search.results.len() > SEARCH_LIMIT {
+                    ui.weak(format!(
+                        "{} more; refine the search",
+                        search.results.len() - SEARCH_LIMIT
+                    ));
+                }
+            });
+
+        if let Some(index) = chosen {
+            self.execute(AppCommand::Properties(index));
+            self.execute(AppCommand::ZoomTo(index));
+        }
+        if !open || escape {
+            self.search = None;
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::SIMILARITY_TOLERANCE;
+use egui::Ui;
+
+use super::{CryxtalApp, ToolMode};
+
+impl CryxtalApp {
+    /// Selects the element at `index` together with every element geometrically similar to it
+    /// (`BimModel::similar_elements`), so layer and type changes apply to all of them.
+    pub(super) fn select_similar(&mut self, index: usize) {
+        let Some(guid) = self.model.elements().get(index).map(|element| element.guid) else {
+            return;
+        };
+        match self.model.similar_elements(guid, SIMILARITY_TOLERANCE) {
+            Ok(similar) => {
+                self.tool_mode = ToolMode::Select;
+                self.set_selected(Some(index));
+                self.push_log(format!("Selected {} similar element(s)", similar.len()));
+                self.similar = similar;
+            }
+            Err(err) => self.notify_error(format!("Select similar failed: {err}")),
+        }
+    }
+
+    /// Guids of the selected element and the similar elements selected with it.
+    pub(super) fn selection_guids(&self) -> Vec<Guid> {
+        self.selected
+            .and_then(|index| self.model.elements().get(index))
+            .map(|element| element.guid)
+            .into_iter()
+            .chain(self.similar.iter().copied())
+            .collect()
+    }
+
+    /// Note in the properties panel while similar elements are selected along.
+    pub(super) fn similar_selection_panel(&mut self, ui: &mut Ui) {
+        if self.similar.is_empty() {
+            return;
+        }
+        ui.add_space(4.0);
+        ui.label(format!(
+            "{} similar element(s) also selected; Layer and Type apply to all",
+            self.similar.len()
+        ));
+        ui.horizontal(|ui| {
+            if ui.small_button("Isolate").clicked() {
+                let keep = self.selection_guids();
+                self.hidden = self
+                    .model
+                    .elements()
+                    .iter()
+                    .map(|element| element.guid)
+                    .filter(|guid| !keep.contains(guid))
+                    .collect();
+            }
+            if ui.small_button("Clear").clicked() {
+                self.similar.clear();
+            }
+        });
+    }
+}
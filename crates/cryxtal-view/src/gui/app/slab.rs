@@ -0,0 +1,112 @@
+use cryxtal_base::LengthUnit;
+use cryxtal_topology::Point3;
+use egui::Ui;
+
+use crate::elements::build_slab_element;
+
+use super::lengths::length_value;
+use super::{CryxtalApp, ToolMode};
+
+/// A click this close (in plan, in millimetres) to the first outline point closes the outline.
+const CLOSE_TOLERANCE: f64 = 50.0;
+
+impl CryxtalApp {
+    pub(super) fn slab_panel(&mut self, ui: &mut Ui) {
+        ui.heading("Slab Tool");
+
+        ui.label("Thickness");
+        ui.add(length_value(
+            &mut self.slab_params.thickness,
+            self.model.units.length,
+            10.0..=100000.0,
+        ));
+
+        ui.label("Name");
+        ui.add(egui::TextEdit::singleline(&mut self.slab_params.name));
+
+        ui.label(self.slab_status_text());
+        self.polar_tracking_controls(ui);
+        self.coordinate_entry(ui);
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    self.pending_slab.len() >= 3,
+                    egui::Button::new("Finish Slab"),
+                )
+                .clicked()
+            {
+                self.finish_slab();
+            }
+            if ui
+                .add_enabled(
+                    !self.pending_slab.is_empty(),
+                    egui::Button::new("Undo Point"),
+                )
+                .clicked()
+            {
+                self.pending_slab.pop();
+            }
+        });
+        if ui.button("Cancel Slab").clicked() {
+            self.cancel_slab();
+        }
+    }
+
+    pub(super) fn slab_status_text(&self) -> String {
+        match self.pending_slab.len() {
+            0 => "Slab: pick first outline point".to_string(),
+            1 | 2 => format!("Slab: pick point {}", self.pending_slab.len() + 1),
+            count => format!(
+                "Slab: pick point {} or close the outline (Enter)",
+                count + 1
+            ),
+        }
+    }
+
+    pub(super) fn activate_slab_tool(&mut self) {
+        self.tool_mode = ToolMode::CreateSlab;
+        self.clear_selection_drag();
+        self.pending_slab.clear();
+        self.set_selected(None);
+    }
+
+    fn cancel_slab(&mut self) {
+        self.tool_mode = ToolMode::Select;
+        self.clear_selection_drag();
+        self.pending_slab.clear();
+        self.viewer.cancel_interaction();
+    }
+
+    /// Adds an outline point; clicking the first point again closes the outline. Points take
+    /// the first point's level so the outline stays horizontal.
+    pub(super) fn place_slab_point(&mut self, point: Point3) {
+        self.last_point = Some(point);
+        let Some(first) = self.pending_slab.first().copied() else {
+            self.pending_slab.push(point);
+            return;
+        };
+        let tolerance = CLOSE_TOLERANCE * LengthUnit::Millimeter.factor_to(self.model.units.length);
+        let closes = (point.x - first.x).hypot(point.y - first.y) <= tolerance;
+        if closes && self.pending_slab.len() >= 3 {
+            self.finish_slab();
+            return;
+        }
+        self.pending_slab
+            .push(Point3::new(point.x, point.y, first.z));
+    }
+
+    pub(super) fn finish_slab(&mut self) {
+        if self.pending_slab.len() < 3 {
+            return;
+        }
+        let name = self.slab_params.name.clone();
+        match build_slab_element(&self.pending_slab, self.slab_params.thickness, Some(&name)) {
+            Ok(element) => {
+                self.pending_slab.clear();
+                self.add_elements(vec![element], "Slab added", false);
+            }
+            Err(err) => self.notify_error(format!("Slab build failed: {err}")),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use cryxtal_topology::Point3;
+
+use crate::gui::model::format_point;
+use crate::viewer::Rect;
+
+use super::{CryxtalApp, ToolMode};
+
+/// What the pointer is over in the viewport, refreshed every frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct CursorStatus {
+    world: Option<Point3>,
+    snap: Option<&'static str>,
+    snap_active: bool,
+}
+
+impl CryxtalApp {
+    pub(super) fn update_cursor_status(&mut self, rect: Rect, hovered: bool, snap_active: bool) {
+        let pos = self.input.pointer_pos.filter(|_| hovered);
+        let snap = match pos {
+            Some(pos) if snap_active => self.viewer.snap_target(pos, rect, &self.element_meshes),
+            _ => None,
+        };
+        let world = match (snap, pos) {
+            (Some((_, point)), _) => Some(point),
+            (None, Some(pos)) => self
+                .viewer
+                .pick_point(pos, rect, &self.element_meshes, false)
+                .map(|point| Point3::new(point.x, point.y, point.z)),
+            (None, None) => None,
+        };
+        self.update_tracking(world, snap.is_some());
+        let world = match self.tracking {
+            Some(hit) if world.is_some() => {
+                let point = hit.point();
+                Some(Point3::new(point.x, point.y, point.z))
+            }
+            _ => world,
+        };
+        self.cursor_status = CursorStatus {
+            world,
+            snap: snap.map(|(label, _)| label),
+            snap_active,
+        };
+    }
+
+    /// Laid out right to left, so the cursor readout sits at the far right.
+    pub(super) fn status_readout(&self, ui: &mut egui::Ui) {
+        let status = self.cursor_status;
+        let cursor = status
+            .world
+            .map(|point| format_point(&point))
+            .unwrap_or_else(|| "-".to_string());
+        ui.label(egui::RichText::new(format!("XYZ: {cursor}")).monospace());
+        ui.add(egui::Separator::default().vertical());
+        let snap = match (status.snap_active, status.snap) {
+            (false, _) => "Snap: off".to_string(),
+            (true, None) => "Snap: on".to_string(),
+            (true, Some(label)) => format!("Snap: {label}"),
+        };
+        ui.label(snap);
+        ui.add(egui::Separator::default().vertical());
+        ui.label(self.tool_status());
+        ui.add(egui::Separator::default().vertical());
+        let selected = self.selection_guids().len();
+        ui.label(format!("Selected: {selected}"));
+    }
+
+    fn tool_status(&self) -> String {
+        match self.tool_mode {
+            ToolMode::Select => "Select".to_string(),
+            ToolMode::CreateWall if self.pending_wall_start.is_some() => {
+                "Wall: pick end point".to_string()
+            }
+            ToolMode::CreateWall => "Wall: pick start point".to_string(),
+            ToolMode::CreateOpening => "Opening: pick center on wall or slab".to_string(),
+            ToolMode::CreateRebar if self.pending_rebar_start.is_some() => {
+                "Rebar: pick end point".to_string()
+            }
+            ToolMode::CreateRebar => "Rebar: pick start point".to_string(),
+            ToolMode::CreateReference => self.reference_status_text(),
+            ToolMode::CreateSlab => self.slab_status_text(),
+        }
+    }
+}
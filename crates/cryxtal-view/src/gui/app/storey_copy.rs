@@ -0,0 +1,98 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::ModelCommand;
+
+use super::CryxtalApp;
+
+/// Element being copied to other levels, with the levels ticked so far.
+pub(super) struct StoreyCopy {
+    element: Guid,
+    storeys: Vec<Guid>,
+}
+
+impl CryxtalApp {
+    pub(super) fn open_storey_copy(&mut self, index: usize) {
+        let Some(element) = self.model.elements().get(index) else {
+            return;
+        };
+        self.storey_copy = Some(StoreyCopy {
+            element: element.guid,
+            storeys: Vec::new(),
+        });
+    }
+
+    pub(super) fn storey_copy_window(&mut self, ctx: &egui::Context) {
+        let Some(copy) = self.storey_copy.as_mut() else {
+            return;
+        };
+        let own = self.model.storey_of(copy.element).map(|storey| storey.guid);
+        let mut storeys: Vec<_> = self
+            .model
+            .storeys()
+            .filter(|storey| Some(storey.guid) != own)
+            .collect();
+        storeys.sort_by(|a, b| a.elevation.total_cmp(&b.elevation));
+
+        let mut open = true;
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("Copy to Levels")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                if storeys.is_empty() {
+                    ui.label("The model has no other levels.");
+                }
+                for storey in &storeys {
+                    let mut ticked = copy.storeys.contains(&storey.guid);
+                    let label = format!("{} ({:.0})", storey.name, storey.elevation);
+                    if ui.checkbox(&mut ticked, label).changed() {
+                        if ticked {
+                            copy.storeys.push(storey.guid);
+                        } else {
+                            copy.storeys.retain(|guid| *guid != storey.guid);
+                        }
+                    }
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!copy.storeys.is_empty(), egui::Button::new("Copy"))
+                        .clicked()
+                    {
+                        confirmed = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancelled = true;
+                    }
+                });
+            });
+
+        if confirmed && let Some(copy) = self.storey_copy.take() {
+            self.copy_to_storeys(copy.element, &copy.storeys);
+        }
+        if !open || cancelled {
+            self.storey_copy = None;
+        }
+    }
+
+    /// Copies an element, with its openings and hosted elements, onto each of `storeys`.
+    fn copy_to_storeys(&mut self, element: Guid, storeys: &[Guid]) {
+        let copies = match self.model.copy_to_storeys(&[element], storeys) {
+            Ok(copies) => copies,
+            Err(err) => {
+                self.notify_error(format!("Copy to levels failed: {err}"));
+                return;
+            }
+        };
+        let count = copies.len();
+        let commands = copies
+            .into_iter()
+            .map(|(storey, elements)| ModelCommand::AddElements { storey, elements })
+            .collect();
+        if self.apply_command("Copy to levels", ModelCommand::Batch(commands)) {
+            self.push_log(format!("Copied to {count} level(s)"));
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use cryxtal_topology::Point3;
+use egui::Ui;
+
+use crate::viewer::{Point2, Rect, TRACKING_INCREMENTS};
+
+use super::{CryxtalApp, ToolMode};
+
+impl CryxtalApp {
+    pub(super) fn polar_tracking_controls(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.polar.enabled, "Polar tracking (F10)");
+            egui::ComboBox::from_id_source("polar_increment_combo")
+                .selected_text(format!("{:.0}°", self.polar.increment_deg))
+                .show_ui(ui, |ui| {
+                    for increment in TRACKING_INCREMENTS {
+                        ui.selectable_value(
+                            &mut self.polar.increment_deg,
+                            increment,
+                            format!("{increment:.0}°"),
+                        );
+                    }
+                });
+        });
+    }
+
+    /// The point tracking rays start from: the pending segment start, else the last placed point.
+    pub(super) fn tracking_origin(&self) -> Option<Point3> {
+        let pending = match self.tool_mode {
+            ToolMode::CreateWall => self.pending_wall_start,
+            ToolMode::CreateRebar => self.pending_rebar_start,
+            ToolMode::CreateReference => self.pending_reference.last().copied(),
+            ToolMode::CreateSlab => self.pending_slab.last().copied(),
+            _ => return None,
+        };
+        pending.or(self.last_point)
+    }
+
+    /// Refreshes the tracking ray for the cursor's world point. The last ray is kept while the
+    /// pointer is outside the viewport so a distance can still be typed along it.
+    pub(super) fn update_tracking(&mut self, cursor: Option<Point3>, snapped: bool) {
+        let Some(origin) = self.tracking_origin() else {
+            self.tracking = None;
+            return;
+        };
+        let Some(cursor) = cursor else {
+            if self
+                .tracking
+                .is_some_and(|hit| hit.origin.x != origin.x || hit.origin.y != origin.y)
+            {
+                self.tracking = None;
+            }
+            return;
+        };
+        self.tracking = if snapped {
+            None
+        } else {
+            self.polar.track(origin.into(), cursor.into())
+        };
+    }
+
+    /// Where a click at `pos` places a point: object snaps win, then the tracking ray.
+    pub(super) fn placement_point(&mut self, pos: Point2, rect: Rect) -> Option<Point3> {
+        if let Some((_, point)) = self.viewer.snap_target(pos, rect, &self.element_meshes) {
+            return Some(point);
+        }
+        let picked = self
+            .viewer
+            .pick_point(pos, rect, &self.element_meshes, false)?;
+        let tracked = self
+            .tracking_origin()
+            .and_then(|origin| self.polar.track(origin.into(), picked));
+        let point = tracked.map(|hit| hit.point()).unwrap_or(picked);
+        Some(Point3::new(point.x, point.y, point.z))
+    }
+
+    pub(super) fn tracked_point_at(&self, distance: f64) -> Option<Point3> {
+        self.tracking
+            .map(|hit| hit.at_distance(distance))
+            .map(|point| Point3::new(point.x, point.y, point.z))
+    }
+}
@@ -0,0 +1,167 @@
+use cryxtal_bim::{FilterRule, GraphicOverride, LegendEntry, ModelCommand, NamedView, ViewFilter};
+use egui::Ui;
+
+use super::CryxtalApp;
+
+/// Named view being applied, and the filter being written for it.
+#[derive(Default)]
+pub(super) struct ViewFilterState {
+    /// Index into `BimModel::views`; `None` shows layer colors only.
+    active: Option<usize>,
+    new_view_name: String,
+    rule: String,
+    color: Option<[u8; 3]>,
+    transparency: f32,
+    halftone: bool,
+    hide_legend: bool,
+    message: String,
+}
+
+impl CryxtalApp {
+    /// The named view whose filters color the elements, if any.
+    pub(super) fn active_view(&self) -> Option<&NamedView> {
+        self.view_filters
+            .active
+            .and_then(|index| self.model.views.get(index))
+    }
+
+    /// The active view with its legend, unless the legend is hidden or empty.
+    pub(super) fn active_legend(&self) -> Option<(&NamedView, Vec<LegendEntry>)> {
+        if self.view_filters.hide_legend {
+            return None;
+        }
+        let view = self.active_view()?;
+        let legend = self.model.legend(view);
+        (!legend.is_empty()).then_some((view, legend))
+    }
+
+    pub(super) fn view_filter_panel(&mut self, ui: &mut Ui) {
+        ui.label("Named view");
+        let active = self.view_filters.active;
+        let current = active
+            .and_then(|index| self.model.views.get(index))
+            .map_or("Layer colors", |view| view.name.as_str())
+            .to_string();
+        let mut next = active;
+        egui::ComboBox::from_id_source("named_view_combo")
+            .selected_text(current)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut next, None, "Layer colors");
+                for (index, view) in self.model.views.iter().enumerate() {
+                    ui.selectable_value(&mut next, Some(index), &view.name);
+                }
+            });
+        self.view_filters.active = next;
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.view_filters.new_view_name)
+                    .hint_text("View name")
+                    .desired_width(120.0),
+            );
+            if ui.button("New View").clicked() {
+                self.create_named_view();
+            }
+        });
+
+        let Some(index) = self
+            .view_filters
+            .active
+            .filter(|index| *index < self.model.views.len())
+        else {
+            return;
+        };
+        let mut show_legend = !self.view_filters.hide_legend;
+        ui.checkbox(&mut show_legend, "Legend");
+        self.view_filters.hide_legend = !show_legend;
+        let mut view = self.model.views[index].clone();
+        let mut removed = None;
+        for (filter_index, filter) in view.filters.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                let label = format!("{}: {}", filter.name, filter.rule);
+                ui.checkbox(&mut filter.enabled, label);
+                if ui.small_button("Remove").clicked() {
+                    removed = Some(filter_index);
+                }
+            });
+        }
+        if let Some(filter_index) = removed {
+            view.filters.remove(filter_index);
+        }
+        if view != self.model.views[index] {
+            let label = format!("Edit view {}", view.name);
+            self.apply_command(&label, ModelCommand::ReplaceView(view));
+        }
+
+        ui.add_space(4.0);
+        ui.add(
+            egui::TextEdit::singleline(&mut self.view_filters.rule).hint_text("FireRating=EI60"),
+        );
+        ui.horizontal(|ui| {
+            let mut recolor = self.view_filters.color.is_some();
+            ui.checkbox(&mut recolor, "Color");
+            if recolor {
+                let mut rgb = self.view_filters.color.unwrap_or([230, 60, 50]);
+                ui.color_edit_button_srgb(&mut rgb);
+                self.view_filters.color = Some(rgb);
+            } else {
+                self.view_filters.color = None;
+            }
+            ui.checkbox(&mut self.view_filters.halftone, "Halftone");
+        });
+        ui.add(
+            egui::Slider::new(&mut self.view_filters.transparency, 0.0..=1.0).text("Transparency"),
+        );
+        if ui.button("Add Filter").clicked() {
+            self.add_view_filter(index);
+        }
+        if !self.view_filters.message.is_empty() {
+            ui.label(&self.view_filters.message);
+        }
+    }
+
+    fn create_named_view(&mut self) {
+        let name = self.view_filters.new_view_name.trim().to_string();
+        if name.is_empty() {
+            self.view_filters.message = "View name is empty".to_string();
+            return;
+        }
+        if self.model.views.iter().any(|view| view.name == name) {
+            self.view_filters.message = "View name already exists".to_string();
+            return;
+        }
+        let label = format!("Add view {name}");
+        if !self.apply_command(&label, ModelCommand::AddView(NamedView::new(name))) {
+            return;
+        }
+        self.view_filters.active = Some(self.model.views.len() - 1);
+        self.view_filters.new_view_name.clear();
+        self.view_filters.message.clear();
+    }
+
+    fn add_view_filter(&mut self, view: usize) {
+        let rule = match self.view_filters.rule.parse::<FilterRule>() {
+            Ok(rule) => rule,
+            Err(err) => {
+                self.view_filters.message = err.to_string();
+                return;
+            }
+        };
+        let transparency = self.view_filters.transparency;
+        let graphics = GraphicOverride {
+            color: self.view_filters.color,
+            transparency: (transparency > 0.0).then_some(transparency),
+            halftone: self.view_filters.halftone,
+        };
+        let Some(mut view) = self.model.views.get(view).cloned() else {
+            return;
+        };
+        view.filters
+            .push(ViewFilter::new(rule.to_string(), rule, graphics));
+        let label = format!("Edit view {}", view.name);
+        if self.apply_command(&label, ModelCommand::ReplaceView(view)) {
+            self.view_filters.rule.clear();
+            self.view_filters.message.clear();
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use egui_wgpu::RenderState;
+use egui_wgpu::winit::Painter;
+use std::sync::Arc;
+use web_time::Instant;
+use winit::dpi::LogicalSize;
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{Window, WindowId};
+
+use crate::gui::icon::app_icon;
+use crate::viewer::{Modifiers, Point2, Rect, TruckRenderer, Vec2, ViewerInput, ViewerState};
+
+use super::CryxtalApp;
+
+const SCROLL_LINE_POINTS: f32 = 50.0;
+
+/// A secondary native window showing the shared model through its own camera.
+pub(super) struct ViewerWindow {
+    window: Arc<Window>,
+    viewport_id: egui::ViewportId,
+    viewer: ViewerState,
+    renderer: TruckRenderer,
+    texture_id: Option<egui::TextureId>,
+    texture_revision: u64,
+    pointer_pos: Option<Point2>,
+    pointer_delta: Vec2,
+    scroll_delta: f32,
+    secondary_down: bool,
+    middle_down: bool,
+    modifiers: Modifiers,
+    last_frame: Instant,
+}
+
+impl ViewerWindow {
+    pub(super) fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    pub(super) fn request_redraw(&self) {
+        self.window.request_redraw();
+    }
+
+    pub(super) fn resized(&self, painter: &mut Painter) {
+        let size = self.window.inner_size();
+        if let (Some(width), Some(height)) = (
+            std::num::NonZeroU32::new(size.width),
+            std::num::NonZeroU32::new(size.height),
+        ) {
+            painter.on_window_resized(self.viewport_id, width, height);
+        }
+    }
+
+    pub(super) fn on_event(&mut self, event: &WindowEvent) {
+        let scale = self.window.scale_factor() as f32;
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                let pos = Point2::new(position.x as f32 / scale, position.y as f32 / scale);
+                if let Some(last) = self.pointer_pos {
+                    self.pointer_delta = self.pointer_delta + (pos - last);
+                }
+                self.pointer_pos = Some(pos);
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.pointer_pos = None;
+                self.secondary_down = false;
+                self.middle_down = false;
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let down = *state == ElementState::Pressed;
+                match button {
+                    MouseButton::Right => self.secondary_down = down,
+                    MouseButton::Middle => self.middle_down = down,
+                    _ => {}
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll_delta += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y * SCROLL_LINE_POINTS,
+                    MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / scale,
+                };
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                let state = modifiers.state();
+                self.modifiers = Modifiers {
+                    shift: state.shift_key(),
+                    ctrl: state.control_key(),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn paint(
+        &mut self,
+        app: &CryxtalApp,
+        painter: &mut Painter,
+        render_state: &RenderState,
+    ) {
+        let now = Instant::now();
+        let dt = now
+            .duration_since(self.last_frame)
+            .as_secs_f64()
+            .clamp(0.0, 0.1);
+        self.last_frame = now;
+
+        let scale = self.window.scale_factor() as f32;
+        let size = self.window.inner_size();
+        let rect = Rect::from_min_size(
+            Point2::new(0.0, 0.0),
+            Vec2::new(size.width as f32 / scale, size.height as f32 / scale),
+        );
+        let input = ViewerInput {
+            rect,
+            pointer_pos: self.pointer_pos,
+            pointer_delta: self.pointer_delta,
+            primary_down: false,
+            secondary_down: self.secondary_down,
+            middle_down: self.middle_down,
+            primary_clicked: false,
+            double_clicked: false,
+            scroll_delta: self.scroll_delta,
+            modifiers: self.modifiers,
+            hovered: self.pointer_pos.is_some(),
+            key_v_pressed: false,
+            key_v_down: false,
+        };
+        self.pointer_delta = Vec2::new(0.0, 0.0);
+        self.scroll_delta = 0.0;
+        self.viewer.handle_input(&input, &app.element_meshes);
+        self.viewer.update(dt);
+
+        if app.render_model_view(&mut self.renderer, &self.viewer, rect, scale) {
+            self.sync_texture(render_state);
+        }
+        let Some(texture_id) = self.texture_id else {
+            return;
+        };
+
+        let screen =
+            egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(rect.width(), rect.height()));
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        let mut mesh = egui::Mesh::with_texture(texture_id);
+        mesh.add_rect_with_uv(screen, uv, egui::Color32::WHITE);
+        let primitives = [egui::ClippedPrimitive {
+            clip_rect: screen,
+            primitive: egui::epaint::Primitive::Mesh(mesh),
+        }];
+        let _ = painter.paint_and_update_textures(
+            self.viewport_id,
+            scale,
+            [0.0, 0.0, 0.0, 1.0],
+            &primitives,
+            &egui::TexturesDelta::default(),
+            Vec::new(),
+        );
+    }
+
+    pub(super) fn close(self, painter: &mut Painter, render_state: &RenderState) {
+        if let Some(id) = self.texture_id {
+            render_state.renderer.write().free_texture(&id);
+        }
+        let _ = pollster::block_on(painter.set_window(self.viewport_id, None));
+    }
+
+    fn sync_texture(&mut self, render_state: &RenderState) {
+        let revision = self.renderer.target_revision();
+        if self.texture_revision == revision && self.texture_id.is_some() {
+            return;
+        }
+
+        let view = self.renderer.target_view();
+        let mut renderer = render_state.renderer.write();
+        let texture_id = if let Some(id) = self.texture_id {
+            renderer.update_egui_texture_from_wgpu_texture(
+                &render_state.device,
+                view,
+                wgpu::FilterMode::Linear,
+                id,
+            );
+            id
+        } else {
+            renderer.register_native_texture(&render_state.device, view, wgpu::FilterMode::Linear)
+        };
+        self.texture_id = Some(texture_id);
+        self.texture_revision = revision;
+    }
+}
+
+impl CryxtalApp {
+    pub(super) fn open_viewer_window(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        painter: &mut Painter,
+    ) -> anyhow::Result<ViewerWindow> {
+        self.viewer_window_count += 1;
+        let title = format!(
+            "{} - Viewer {}",
+            self.window_title(),
+            self.viewer_window_count + 1
+        );
+        let window = event_loop.create_window(
+            Window::default_attributes()
+                .with_title(title)
+                .with_window_icon(app_icon())
+                .with_inner_size(LogicalSize::new(960.0, 640.0)),
+        )?;
+        let window = Arc::new(window);
+        let viewport_id =
+            egui::ViewportId::from_hash_of(("cryxtal-viewer", self.viewer_window_count));
+        pollster::block_on(painter.set_window(viewport_id, Some(window.clone())))?;
+
+        let mut viewer = ViewerState::default();
+        viewer.set_gizmo_mode(self.viewer.gizmo_mode());
+        if let Some(bounds) = self.viewer_mesh.as_ref().and_then(|mesh| mesh.bounds) {
+            viewer.fit_bounds(bounds);
+        }
+        self.push_log(format!(
+            "Opened viewer window {}",
+            self.viewer_window_count + 1
+        ));
+
+        Ok(ViewerWindow {
+            window,
+            viewport_id,
+            viewer,
+            renderer: TruckRenderer::new(
+                self.adapter.clone(),
+                self.device.clone(),
+                self.queue.clone(),
+            ),
+            texture_id: None,
+            texture_revision: 0,
+            pointer_pos: None,
+            pointer_delta: Vec2::new(0.0, 0.0),
+            scroll_delta: 0.0,
+            secondary_down: false,
+            middle_down: false,
+            modifiers: Modifiers::default(),
+            last_frame: Instant::now(),
+        })
+    }
+
+    pub(super) fn take_viewer_window_request(&mut self) -> bool {
+        std::mem::take(&mut self.viewer_window_requested)
+    }
+
+    fn render_model_view(
+        &self,
+        renderer: &mut TruckRenderer,
+        viewer: &ViewerState,
+        rect: Rect,
+        scale: f32,
+    ) -> bool {
+        let bounds = self.viewer_mesh.as_ref().and_then(|mesh| mesh.bounds);
+        renderer.set_point_clouds(self.point_clouds.revision, &self.point_clouds.clouds);
+        renderer.render(
+            rect,
+            scale,
+            viewer,
+            bounds,
+            &self.element_meshes,
+            &self.element_polymeshes,
+            self.mesh_revision,
+            &self.element_colors(),
+            &self.element_visibility(),
+            &self.element_wireframe(),
+            &self.element_skeleton_solid(),
+            None,
+            self.selected,
+            self.view_mode,
+        )
+    }
+}
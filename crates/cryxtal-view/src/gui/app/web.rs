@@ -0,0 +1,133 @@
+use anyhow::Result;
+use egui_winit::State as EguiWinitState;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use web_sys::HtmlCanvasElement;
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::platform::web::{EventLoopExtWebSys, WindowAttributesExtWebSys};
+
+use super::read_only::EditMode;
+use super::{
+    APP_TITLE, CryxtalApp, clear_color, create_painter, gpu_init_error, record_active_adapter,
+    record_gpu_failure,
+};
+use crate::gui::gpu::{GpuDiagnostics, SharedDiagnostics};
+use crate::gui::prefs::Preferences;
+use crate::gui::web::PendingFiles;
+
+/// Runs the viewer in `canvas`. The GPU (WebGPU, else WebGL2) is set up without blocking,
+/// then the event loop is handed to the browser and this returns. Project files read by the
+/// page arrive through `files` and open on the next frame, read-only until unlocked, since
+/// the browser has no project file to save back to.
+#[allow(deprecated)]
+pub(in crate::gui) async fn run_web(canvas: HtmlCanvasElement, files: PendingFiles) -> Result<()> {
+    let event_loop = EventLoop::new().map_err(|err| anyhow::anyhow!(err.to_string()))?;
+    let window = event_loop
+        .create_window(
+            winit::window::Window::default_attributes()
+                .with_title(APP_TITLE)
+                .with_canvas(Some(canvas)),
+        )
+        .map_err(|err| anyhow::anyhow!(err.to_string()))?;
+    let window = Arc::new(window);
+
+    let egui_ctx = egui::Context::default();
+    // Preferences are kept on disk, which the page does not have.
+    let preferences = Preferences::default();
+    let diagnostics: SharedDiagnostics = Arc::new(Mutex::new(GpuDiagnostics::default()));
+    let mut painter =
+        create_painter(egui_ctx.clone(), &preferences, diagnostics.clone(), false).await;
+    if let Err(err) = painter
+        .set_window(egui::ViewportId::ROOT, Some(window.clone()))
+        .await
+    {
+        record_gpu_failure(&diagnostics, err.to_string());
+        return Err(gpu_init_error(&diagnostics));
+    }
+    let render_state = painter
+        .render_state()
+        .ok_or_else(|| anyhow::anyhow!("wgpu render state not initialized"))?;
+    let gpu_diagnostics = record_active_adapter(&diagnostics, &render_state)?;
+
+    let mut egui_state = EguiWinitState::new(
+        egui_ctx.clone(),
+        egui::ViewportId::ROOT,
+        &event_loop,
+        Some(window.scale_factor() as f32),
+        window.theme(),
+        painter.max_texture_side(),
+    );
+
+    let mut app = CryxtalApp::new(
+        render_state.adapter.clone(),
+        render_state.device.clone(),
+        render_state.queue.clone(),
+        preferences,
+        gpu_diagnostics,
+    );
+    app.edit_mode = EditMode::ReadOnly;
+    let clear_color = clear_color(&egui_ctx);
+
+    event_loop.spawn(move |event, event_loop| {
+        event_loop.set_control_flow(ControlFlow::Poll);
+        match event {
+            Event::WindowEvent { event, .. } => {
+                let response = egui_state.on_window_event(&window, &event);
+                if response.repaint {
+                    window.request_redraw();
+                }
+
+                match event {
+                    WindowEvent::Resized(size) => {
+                        if let (Some(width), Some(height)) =
+                            (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+                        {
+                            painter.on_window_resized(egui::ViewportId::ROOT, width, height);
+                        }
+                    }
+                    WindowEvent::RedrawRequested => {
+                        let loaded = std::mem::take(&mut *files.borrow_mut());
+                        for file in loaded {
+                            match file.bytes {
+                                Ok(bytes) => app.open_project_bytes(&file.name, &bytes),
+                                Err(err) => {
+                                    app.notify_error(format!("Loading {} failed: {err}", file.name))
+                                }
+                            }
+                        }
+
+                        let raw_input = egui_state.take_egui_input(&window);
+                        let full_output = egui_ctx.run(raw_input, |ctx| {
+                            app.ui(ctx, &render_state);
+                        });
+                        egui_state.handle_platform_output(&window, full_output.platform_output);
+
+                        let clipped_primitives =
+                            egui_ctx.tessellate(full_output.shapes, full_output.pixels_per_point);
+                        let _ = painter.paint_and_update_textures(
+                            egui::ViewportId::ROOT,
+                            full_output.pixels_per_point,
+                            clear_color,
+                            &clipped_primitives,
+                            &full_output.textures_delta,
+                            Vec::new(),
+                        );
+                        app.on_frame_presented();
+
+                        if app.take_viewer_window_request() {
+                            app.notify_warning(
+                                "Extra viewer windows are not available in the browser".to_string(),
+                            );
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::AboutToWait => window.request_redraw(),
+            _ => {}
+        }
+    });
+
+    Ok(())
+}
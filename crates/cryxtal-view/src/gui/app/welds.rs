@@ -0,0 +1,96 @@
+use cryxtal_base::format_length;
+use cryxtal_bim::{BimCategory, ModelCommand, WeldAnnotation, WeldKind, WeldSide};
+use cryxtal_topology::solid_edges;
+use egui::Ui;
+
+use super::CryxtalApp;
+use super::lengths::length_value;
+
+/// Weld a new annotation starts from: a 6 mm fillet on the arrow side.
+pub(super) fn default_weld() -> WeldAnnotation {
+    WeldAnnotation {
+        edge: 0,
+        kind: WeldKind::Fillet,
+        size: 6.0,
+        side: WeldSide::Arrow,
+        all_around: false,
+        field: false,
+    }
+}
+
+impl CryxtalApp {
+    /// Lists the welds on the edges of the element at `index`, with a way to add or remove
+    /// them. Welds are annotations, so they can be edited in read-only sessions too.
+    pub(super) fn element_weld_panel(&mut self, ui: &mut Ui, index: usize) {
+        let Some(element) = self.model.elements().get(index) else {
+            return;
+        };
+        if element.category == BimCategory::Opening || element.is_reference_mesh() {
+            return;
+        }
+        let guid = element.guid;
+        let welds = element.welds.clone();
+        let unit = self.model.units.length;
+        let mut next = None;
+        egui::CollapsingHeader::new(format!("Welds ({})", welds.len()))
+            .id_salt("element_welds")
+            .show(ui, |ui| {
+                for (position, weld) in welds.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "Edge {}: {} {}, {}",
+                            weld.edge,
+                            weld.kind.label(),
+                            format_length(weld.size, unit),
+                            weld.side.label()
+                        ));
+                        if ui.small_button("Remove").clicked() {
+                            let mut kept = welds.clone();
+                            kept.remove(position);
+                            next = Some(kept);
+                        }
+                    });
+                }
+
+                let edges = solid_edges(&element.geometry).len();
+                let draft = &mut self.weld_draft;
+                ui.horizontal(|ui| {
+                    ui.label("Edge");
+                    ui.add(
+                        egui::DragValue::new(&mut draft.edge).range(0..=edges.saturating_sub(1)),
+                    );
+                    egui::ComboBox::from_id_source("weld_kind_combo")
+                        .selected_text(draft.kind.label())
+                        .show_ui(ui, |ui| {
+                            for kind in WeldKind::ALL {
+                                ui.selectable_value(&mut draft.kind, kind, kind.label());
+                            }
+                        });
+                    ui.add(length_value(&mut draft.size, unit, 0.1..=1000.0));
+                });
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("weld_side_combo")
+                        .selected_text(draft.side.label())
+                        .show_ui(ui, |ui| {
+                            for side in [WeldSide::Arrow, WeldSide::Other, WeldSide::Both] {
+                                ui.selectable_value(&mut draft.side, side, side.label());
+                            }
+                        });
+                    ui.checkbox(&mut draft.all_around, "All around");
+                    ui.checkbox(&mut draft.field, "Field");
+                });
+                if ui
+                    .add_enabled(edges > 0, egui::Button::new("Add Weld"))
+                    .clicked()
+                {
+                    let mut added = welds.clone();
+                    added.push(*draft);
+                    next = Some(added);
+                }
+            });
+
+        if let Some(welds) = next {
+            self.apply_command("Edit welds", ModelCommand::SetWelds { guid, welds });
+        }
+    }
+}
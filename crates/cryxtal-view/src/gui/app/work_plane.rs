@@ -0,0 +1,56 @@
+use cryxtal_topology::{
+    Plane, Point3, face_at_point, face_plane, largest_planar_face, solid_bounds,
+};
+
+use super::CryxtalApp;
+
+/// Face pick tolerance relative to the size of the element, absorbing tessellation round-off.
+const FACE_PICK_TOLERANCE: f64 = 1.0e-6;
+
+impl CryxtalApp {
+    /// Turns the camera straight onto a planar face of element `index`; see [`Self::face_at`].
+    pub(super) fn look_at_face(&mut self, index: usize, point: Option<Point3>) {
+        let Some((face, plane)) = self.face_at(index, point) else {
+            self.notify_error("No planar face to look at".to_string());
+            return;
+        };
+        self.viewer.look_at(plane.origin, -plane.normal);
+        self.push_log(format!("Looking at face {face}"));
+    }
+
+    /// Makes a planar face of element `index` the work plane points are picked on.
+    pub(super) fn sketch_on_face(&mut self, index: usize, point: Option<Point3>) {
+        let Some((face, plane)) = self.face_at(index, point) else {
+            self.notify_error("No planar face to sketch on".to_string());
+            return;
+        };
+        self.viewer
+            .set_work_plane(Some((plane.origin, plane.normal)));
+        self.push_log(format!("Work plane set to face {face}"));
+    }
+
+    pub(super) fn clear_work_plane(&mut self) {
+        self.viewer.set_work_plane(None);
+        self.push_log("Work plane cleared".to_string());
+    }
+
+    /// Index and plane of the planar face of element `index` under `point`, a point picked on
+    /// its mesh, or of its largest planar face when there is none there.
+    fn face_at(&self, index: usize, point: Option<Point3>) -> Option<(usize, Plane)> {
+        let solid = &self.model.elements().get(index)?.geometry;
+        let (min, max) = solid_bounds(solid)?;
+        let extent = max - min;
+        let tolerance = extent.x.max(extent.y).max(extent.z).max(1.0) * FACE_PICK_TOLERANCE;
+        let picked = point.and_then(|point| face_at_point(solid, point, tolerance));
+        match picked {
+            Some(face) => {
+                let plane = solid
+                    .face_iter()
+                    .nth(face)
+                    .and_then(|face| face_plane(face, tolerance))?;
+                Some((face, plane))
+            }
+            None => largest_planar_face(solid, tolerance).map(|face| (face.index, face.plane)),
+        }
+    }
+}
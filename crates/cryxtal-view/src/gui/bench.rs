@@ -0,0 +1,238 @@
+use anyhow::{Context, Result, bail};
+use cryxtal_bim::BimCategory;
+use cryxtal_io::{ProjectCamera, TessellationOptions, load_project, triangulate_many};
+use cryxtal_topology::Point3;
+use serde_json::json;
+use std::time::{Duration, Instant};
+use truck_modeling::InnerSpace;
+
+use crate::cli::BenchArgs;
+use crate::viewer::{
+    Color32, Point2, Rect, TruckRenderer, Vec2, ViewMode, ViewerMesh, ViewerState,
+};
+
+use super::gpu::{GpuDiagnostics, describe_adapter, power_preference};
+use super::model::{merge_bounds, mesh_bounds};
+
+/// Renders a project offscreen along [`path_camera`] and prints the adapter and frame time
+/// statistics; see [`BenchArgs`].
+pub fn run_bench(args: &BenchArgs) -> Result<()> {
+    if args.frames == 0 {
+        bail!("--frames must be at least 1");
+    }
+    let (width, height) = parse_resolution(&args.resolution)?;
+    let project = load_project(&args.input)?;
+    let model = &project.model;
+
+    let tessellation_started = Instant::now();
+    let solids: Vec<_> = model
+        .elements()
+        .iter()
+        .map(|element| element.geometry())
+        .collect();
+    let poly_meshes = triangulate_many(&solids, &TessellationOptions::default());
+    let tessellation = tessellation_started.elapsed();
+    let meshes: Vec<ViewerMesh> = poly_meshes.iter().map(ViewerMesh::from_mesh).collect();
+    let Some((low, high)) = poly_meshes.iter().fold(None, |bounds, mesh| {
+        merge_bounds(bounds, mesh_bounds(mesh.positions()))
+    }) else {
+        bail!("{} has nothing to render", args.input);
+    };
+    let viewer_bounds = ViewerMesh::merge(&meshes).and_then(|mesh| mesh.bounds);
+    let colors: Vec<Color32> = model
+        .elements()
+        .iter()
+        .map(|element| {
+            let [r, g, b, a] = model.display_color(element, None);
+            Color32::from_rgba_unmultiplied(r, g, b, a)
+        })
+        .collect();
+    let visible: Vec<bool> = model
+        .elements()
+        .iter()
+        .map(|element| element.category != BimCategory::Opening)
+        .collect();
+    let wireframe = vec![true; visible.len()];
+    let skeleton_solid: Vec<bool> = model
+        .elements()
+        .iter()
+        .map(|element| element.category == BimCategory::Rebar)
+        .collect();
+
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::from_env_or_default());
+    let adapters: Vec<_> = instance
+        .enumerate_adapters(wgpu::Backends::all())
+        .iter()
+        .map(|adapter| adapter.get_info())
+        .collect();
+    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference: power_preference(),
+        force_fallback_adapter: args.software,
+        compatible_surface: None,
+    }))
+    .context("no GPU adapter is available")?;
+    let required_limits =
+        wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits());
+    let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+        label: Some("cryxtal-view bench"),
+        required_features: wgpu::Features::empty(),
+        required_limits,
+        experimental_features: wgpu::ExperimentalFeatures::disabled(),
+        memory_hints: wgpu::MemoryHints::MemoryUsage,
+        trace: wgpu::Trace::default(),
+    }))
+    .context("failed to create the GPU device")?;
+    let diagnostics = GpuDiagnostics {
+        adapters,
+        active: Some(adapter.get_info()),
+        limits: Some(device.limits()),
+        failures: Vec::new(),
+        software_fallback: args.software,
+    };
+
+    let mut renderer = TruckRenderer::new(adapter.clone(), device.clone(), queue);
+    let mut viewer = ViewerState::default();
+    let rect = Rect::from_min_size(
+        Point2::new(0.0, 0.0),
+        Vec2::new(width as f32, height as f32),
+    );
+    let mut render = |viewer: &ViewerState| -> Result<Duration> {
+        let started = Instant::now();
+        renderer.render(
+            rect,
+            1.0,
+            viewer,
+            viewer_bounds,
+            &meshes,
+            &poly_meshes,
+            1,
+            &colors,
+            &visible,
+            &wireframe,
+            &skeleton_solid,
+            None,
+            None,
+            ViewMode::LayerOpaque,
+        );
+        // Frames count until the GPU has finished them, not when they are submitted.
+        device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .context("GPU failed to finish a frame")?;
+        Ok(started.elapsed())
+    };
+
+    // The first frame uploads the meshes; it is reported apart.
+    viewer.set_camera(&path_camera(0.0, low, high));
+    let first_frame = render(&viewer)?;
+    let mut frame_times = Vec::with_capacity(args.frames);
+    for frame in 0..args.frames {
+        viewer.set_camera(&path_camera(frame as f64 / args.frames as f64, low, high));
+        frame_times.push(render(&viewer)?.as_secs_f64() * 1.0e3);
+    }
+    let stats = renderer.stats();
+
+    let total_ms: f64 = frame_times.iter().sum();
+    let mean = total_ms / frame_times.len() as f64;
+    frame_times.sort_by(f64::total_cmp);
+    let percentile = |fraction: f64| {
+        let rank = (fraction * (frame_times.len() - 1) as f64).round() as usize;
+        frame_times[rank]
+    };
+    let (min, median, p95, p99, max) = (
+        frame_times[0],
+        percentile(0.5),
+        percentile(0.95),
+        percentile(0.99),
+        frame_times[frame_times.len() - 1],
+    );
+    let fps = 1.0e3 / mean;
+
+    print!("{}", diagnostics.report());
+    println!(
+        "Model: {} elements, {} triangles, tessellated in {:.1} ms",
+        model.elements().len(),
+        stats.triangles,
+        tessellation.as_secs_f64() * 1.0e3
+    );
+    println!(
+        "Frames: {} at {width}x{height}, {} draw calls, {:.1} MiB of mesh buffers",
+        frame_times.len(),
+        stats.draw_calls,
+        stats.buffer_bytes as f64 / (1024.0 * 1024.0)
+    );
+    println!(
+        "First frame (mesh upload): {:.2} ms",
+        first_frame.as_secs_f64() * 1.0e3
+    );
+    println!(
+        "Frame time (ms): mean {mean:.2}, min {min:.2}, median {median:.2}, p95 {p95:.2}, \
+         p99 {p99:.2}, max {max:.2}"
+    );
+    println!("Average FPS: {fps:.1}");
+
+    if let Some(path) = args.json.as_deref() {
+        let adapter = adapter.get_info();
+        let report = json!({
+            "adapter": describe_adapter(&adapter),
+            "driver": format!("{} {}", adapter.driver, adapter.driver_info).trim(),
+            "software": args.software,
+            "model": args.input,
+            "elements": model.elements().len(),
+            "triangles": stats.triangles,
+            "draw_calls": stats.draw_calls,
+            "buffer_bytes": stats.buffer_bytes,
+            "resolution": [width, height],
+            "frames": frame_times.len(),
+            "tessellation_ms": tessellation.as_secs_f64() * 1.0e3,
+            "first_frame_ms": first_frame.as_secs_f64() * 1.0e3,
+            "frame_ms": {
+                "mean": mean,
+                "min": min,
+                "median": median,
+                "p95": p95,
+                "p99": p99,
+                "max": max,
+            },
+            "fps": fps,
+        });
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)
+            .with_context(|| format!("write benchmark report {path}"))?;
+        println!("Report written: {path}");
+    }
+    Ok(())
+}
+
+/// Camera at `t` (0 to 1) along the benchmark path: one turn around the model's bounds,
+/// dollying between 1.5 and 3.5 times its radius and rising and sinking twice, so frames
+/// see the whole model, close-ups and grazing views alike.
+fn path_camera(t: f64, low: Point3, high: Point3) -> ProjectCamera {
+    let center = [
+        (low.x + high.x) * 0.5,
+        (low.y + high.y) * 0.5,
+        (low.z + high.z) * 0.5,
+    ];
+    let radius = (high - low).magnitude().max(1.0) * 0.5;
+    let angle = t * std::f64::consts::TAU;
+    let distance = radius * (2.5 + angle.cos());
+    let pitch = 0.35 + 0.25 * (2.0 * angle).sin();
+    ProjectCamera {
+        target: center,
+        position: [
+            center[0] + distance * angle.cos() * pitch.cos(),
+            center[1] + distance * angle.sin() * pitch.cos(),
+            center[2] + distance * pitch.sin(),
+        ],
+        up: [0.0, 0.0, 1.0],
+        fov_deg: 60.0,
+    }
+}
+
+fn parse_resolution(value: &str) -> Result<(u32, u32)> {
+    let parsed = value.split_once(['x', 'X']).and_then(|(width, height)| {
+        Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+    });
+    match parsed {
+        Some((width, height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => bail!("--resolution expects WIDTHxHEIGHT, got `{value}`"),
+    }
+}
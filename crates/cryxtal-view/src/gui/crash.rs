@@ -0,0 +1,176 @@
+use anyhow::{Context, Result};
+use cryxtal_bim::SessionJournal;
+use cryxtal_io::{JOURNAL_EXTENSION, Project, ProjectFormat, export_journal, save_project_as};
+use serde::{Deserialize, Serialize};
+use std::backtrace::Backtrace;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::prefs::data_dir;
+
+const CRASH_DIR: &str = "crashes";
+const REPORT_FILE: &str = "report.json";
+const SNAPSHOT_FILE: &str = "snapshot.cxpb";
+/// Present while the bundle has not been shown in the recovery dialog.
+const PENDING_FILE: &str = "pending";
+/// Journal steps listed in the report itself; the whole journal is written beside it.
+const REPORTED_STEPS: usize = 20;
+
+/// State a crash bundle is written from, kept current by the app as the model changes so the
+/// panic hook has it whichever thread panics.
+static CRASH_CONTEXT: Mutex<Option<CrashContext>> = Mutex::new(None);
+/// Bundles written by this process, numbering their directories.
+static BUNDLES_WRITTEN: AtomicUsize = AtomicUsize::new(0);
+
+/// What the app was working on, for the snapshot and journal of a crash bundle.
+pub struct CrashContext {
+    pub project: Project,
+    pub project_path: Option<PathBuf>,
+    pub journal: SessionJournal,
+}
+
+/// Replaces the state crash bundles are written from.
+pub fn set_crash_context(context: CrashContext) {
+    if let Ok(mut current) = CRASH_CONTEXT.lock() {
+        *current = Some(context);
+    }
+}
+
+/// What a crash bundle records about the crash, besides the snapshot and journal files.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub version: String,
+    /// Panic message and location.
+    pub message: String,
+    pub backtrace: String,
+    /// Project open at the time, if it had been saved.
+    pub project: Option<PathBuf>,
+    /// Labels of the last steps of the session journal, oldest first.
+    pub last_steps: Vec<String>,
+}
+
+/// A crash bundle left by an earlier run, with its report.
+#[derive(Clone, Debug)]
+pub struct PendingCrash {
+    pub dir: PathBuf,
+    pub report: CrashReport,
+}
+
+impl PendingCrash {
+    /// Model as it was when the application crashed, if the snapshot was written.
+    pub fn snapshot(&self) -> Option<PathBuf> {
+        let path = self.dir.join(SNAPSHOT_FILE);
+        path.exists().then_some(path)
+    }
+
+    /// Keeps the bundle on disk but stops offering it on start.
+    pub fn dismiss(&self) {
+        let _ = std::fs::remove_file(self.dir.join(PENDING_FILE));
+    }
+}
+
+/// Chains a panic hook that writes a crash bundle for any panic, on any thread, from the last
+/// [`set_crash_context`], then reports it as before.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let message = match info.location() {
+            Some(location) => format!("{message} at {location}"),
+            None => message,
+        };
+        let backtrace = Backtrace::force_capture().to_string();
+        // A panic while the context is being replaced writes no bundle rather than deadlock.
+        if let Ok(context) = CRASH_CONTEXT.try_lock()
+            && let Some(context) = context.as_ref()
+        {
+            match write_crash_bundle(context, message, backtrace) {
+                Ok(dir) => eprintln!("crash bundle written to {}", dir.display()),
+                Err(err) => eprintln!("crash bundle not written: {err:#}"),
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// Writes a crash bundle for a panic: the report, the session journal and a snapshot of the
+/// project, in a new directory under the data directory. Returns the directory. The snapshot
+/// and journal are best effort, as the model may be what broke.
+fn write_crash_bundle(
+    context: &CrashContext,
+    message: String,
+    backtrace: String,
+) -> Result<PathBuf> {
+    let CrashContext {
+        project,
+        project_path,
+        journal,
+    } = context;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let time = now.as_secs();
+    // Milliseconds, process and count keep bundles of crashes close together apart, and
+    // still sort oldest first.
+    let dir = crash_dir()
+        .context("no data directory available")?
+        .join(format!(
+            "{:016}-{}-{}",
+            now.as_millis(),
+            std::process::id(),
+            BUNDLES_WRITTEN.fetch_add(1, Ordering::Relaxed)
+        ));
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("create crash directory {}", dir.display()))?;
+
+    let skipped = journal.entries.len().saturating_sub(REPORTED_STEPS);
+    let report = CrashReport {
+        time,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        message,
+        backtrace,
+        project: project_path.clone(),
+        last_steps: journal.entries[skipped..]
+            .iter()
+            .map(|entry| entry.label.clone())
+            .collect(),
+    };
+    std::fs::write(
+        dir.join(REPORT_FILE),
+        serde_json::to_string_pretty(&report)?,
+    )
+    .with_context(|| format!("write crash report in {}", dir.display()))?;
+    let _ = save_project_as(project, dir.join(SNAPSHOT_FILE), ProjectFormat::Binary);
+    if !journal.is_empty() {
+        let _ = export_journal(journal, dir.join(format!("journal.{JOURNAL_EXTENSION}")));
+    }
+    std::fs::write(dir.join(PENDING_FILE), "")?;
+    Ok(dir)
+}
+
+/// Most recent crash bundle not yet shown in the recovery dialog.
+pub fn pending_crash() -> Option<PendingCrash> {
+    let entries = std::fs::read_dir(crash_dir()?).ok()?;
+    let mut pending: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|dir| dir.join(PENDING_FILE).exists())
+        .collect();
+    pending.sort();
+    let dir = pending.pop()?;
+    let report = std::fs::read_to_string(dir.join(REPORT_FILE)).ok()?;
+    let report = serde_json::from_str(&report).ok()?;
+    Some(PendingCrash { dir, report })
+}
+
+fn crash_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(CRASH_DIR))
+}
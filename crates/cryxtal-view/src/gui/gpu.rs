@@ -0,0 +1,131 @@
+use egui_wgpu::NativeAdapterSelectorMethod;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+
+use super::prefs::Preferences;
+
+pub type SharedDiagnostics = Arc<Mutex<GpuDiagnostics>>;
+
+#[derive(Clone, Debug, Default)]
+pub struct GpuDiagnostics {
+    pub adapters: Vec<wgpu::AdapterInfo>,
+    pub active: Option<wgpu::AdapterInfo>,
+    pub limits: Option<wgpu::Limits>,
+    pub failures: Vec<String>,
+    pub software_fallback: bool,
+}
+
+impl GpuDiagnostics {
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        match &self.active {
+            Some(info) => {
+                let _ = writeln!(out, "Active adapter: {}", describe_adapter(info));
+                if !info.driver.is_empty() {
+                    let _ = writeln!(out, "Driver: {} {}", info.driver, info.driver_info);
+                }
+            }
+            None => out.push_str("Active adapter: none\n"),
+        }
+        if self.software_fallback {
+            out.push_str("Running on the software fallback renderer\n");
+        }
+        if let Some(limits) = &self.limits {
+            let _ = writeln!(
+                out,
+                "Limits: texture 2D {} px, buffer {} MiB, bind groups {}, vertex buffers {}",
+                limits.max_texture_dimension_2d,
+                limits.max_buffer_size / (1024 * 1024),
+                limits.max_bind_groups,
+                limits.max_vertex_buffers,
+            );
+        }
+        if self.adapters.is_empty() {
+            out.push_str("Adapters: none enumerated\n");
+        } else {
+            out.push_str("Adapters:\n");
+            for info in &self.adapters {
+                let _ = writeln!(out, "  - {}", describe_adapter(info));
+            }
+        }
+        for failure in &self.failures {
+            let _ = writeln!(out, "Failure: {failure}");
+        }
+        out
+    }
+}
+
+/// Power preference from `CRYXTAL_POWER_PREF` (`high`, `default` or `low`); low power when
+/// unset.
+pub fn power_preference() -> wgpu::PowerPreference {
+    match std::env::var("CRYXTAL_POWER_PREF") {
+        Ok(value) => match value.trim().to_ascii_lowercase().as_str() {
+            "high" | "high_performance" | "high-performance" => {
+                wgpu::PowerPreference::HighPerformance
+            }
+            "default" => wgpu::PowerPreference::default(),
+            _ => wgpu::PowerPreference::LowPower,
+        },
+        Err(_) => wgpu::PowerPreference::LowPower,
+    }
+}
+
+pub fn describe_adapter(info: &wgpu::AdapterInfo) -> String {
+    format!("{} ({:?}, {:?})", info.name, info.backend, info.device_type)
+}
+
+pub fn adapter_selector(
+    preferences: &Preferences,
+    diagnostics: SharedDiagnostics,
+    power_preference: wgpu::PowerPreference,
+    software: bool,
+) -> NativeAdapterSelectorMethod {
+    let preferred = preferences.adapter.clone();
+    Arc::new(
+        move |adapters: &[wgpu::Adapter], surface: Option<&wgpu::Surface<'_>>| {
+            let candidates: Vec<&wgpu::Adapter> = adapters
+                .iter()
+                .filter(|adapter| {
+                    surface.is_none_or(|surface| adapter.is_surface_supported(surface))
+                })
+                .collect();
+            if let Ok(mut diagnostics) = diagnostics.lock() {
+                diagnostics.adapters = adapters.iter().map(|adapter| adapter.get_info()).collect();
+            }
+
+            if software {
+                return candidates
+                    .iter()
+                    .find(|adapter| adapter.get_info().device_type == wgpu::DeviceType::Cpu)
+                    .map(|adapter| (*adapter).clone())
+                    .ok_or_else(|| "no software (CPU) adapter is available".to_string());
+            }
+
+            if let Some(name) = &preferred
+                && let Some(adapter) = candidates
+                    .iter()
+                    .find(|adapter| &adapter.get_info().name == name)
+            {
+                return Ok((*adapter).clone());
+            }
+
+            candidates
+                .iter()
+                .min_by_key(|adapter| device_rank(adapter.get_info().device_type, power_preference))
+                .map(|adapter| (*adapter).clone())
+                .ok_or_else(|| "no adapter supports the window surface".to_string())
+        },
+    )
+}
+
+fn device_rank(device_type: wgpu::DeviceType, power_preference: wgpu::PowerPreference) -> u8 {
+    match (device_type, power_preference) {
+        (wgpu::DeviceType::DiscreteGpu, wgpu::PowerPreference::HighPerformance) => 0,
+        (wgpu::DeviceType::IntegratedGpu, wgpu::PowerPreference::HighPerformance) => 1,
+        (wgpu::DeviceType::IntegratedGpu, _) => 0,
+        (wgpu::DeviceType::DiscreteGpu, _) => 1,
+        (wgpu::DeviceType::VirtualGpu, _) => 2,
+        (wgpu::DeviceType::Other, _) => 3,
+        (wgpu::DeviceType::Cpu, _) => 4,
+    }
+}
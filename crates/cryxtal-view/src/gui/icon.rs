@@ -0,0 +1,37 @@
+use winit::window::Icon;
+
+const ICON_SIZE: u32 = 64;
+
+/// Procedural application icon: a faceted crystal on a dark rounded tile.
+pub fn app_icon() -> Option<Icon> {
+    let size = ICON_SIZE as f32;
+    let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let px = x as f32 + 0.5;
+            let py = y as f32 + 0.5;
+            let dx = (px - size * 0.5).abs() / (size * 0.30);
+            let dy = (py - size * 0.5).abs() / (size * 0.42);
+            let pixel = if dx + dy <= 1.0 {
+                let shade = if px < size * 0.5 { 1.0 } else { 0.78 };
+                let top = if py < size * 0.5 { 1.0 } else { 0.86 };
+                let k = shade * top;
+                [(95.0 * k) as u8, (190.0 * k) as u8, (242.0 * k) as u8, 255]
+            } else if rounded_tile_contains(px, py, size, size * 0.18) {
+                [24, 28, 36, 255]
+            } else {
+                [0, 0, 0, 0]
+            };
+            rgba.extend_from_slice(&pixel);
+        }
+    }
+    Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE).ok()
+}
+
+fn rounded_tile_contains(px: f32, py: f32, size: f32, radius: f32) -> bool {
+    let cx = px.clamp(radius, size - radius);
+    let cy = py.clamp(radius, size - radius);
+    let dx = px - cx;
+    let dy = py - cy;
+    dx * dx + dy * dy <= radius * radius
+}
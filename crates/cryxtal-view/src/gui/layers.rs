@@ -1,5 +0,0 @@
-#[derive(Clone, Debug)]
-pub struct Layer {
-    pub name: String,
-    pub color: crate::viewer::Color32,
-}
@@ -0,0 +1,173 @@
+use cryxtal_base::Guid;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+use tungstenite::Message;
+
+/// Address to listen on, e.g. `127.0.0.1:9101`. The link stays off when unset.
+pub const LIVE_LINK_ENV: &str = "CRYXTAL_LIVE_LINK";
+
+/// How long a client thread blocks on a read before flushing queued events.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Command sent by a client as a JSON text frame, tagged by `"command"`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum LiveCommand {
+    /// Selects the element with `guid`; `null` clears the selection.
+    Select { guid: Option<Guid> },
+    /// Moves the camera. `up` and `fov_deg` keep their current values when omitted.
+    Camera {
+        target: [f64; 3],
+        position: [f64; 3],
+        up: Option<[f64; 3]>,
+        fov_deg: Option<f64>,
+    },
+}
+
+/// Event broadcast to clients as a JSON text frame, tagged by `"event"`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LiveEvent {
+    /// The model changed; `edit` is the label of the last undoable edit.
+    Model {
+        revision: u64,
+        elements: usize,
+        edit: Option<String>,
+    },
+    Selection {
+        guid: Option<Guid>,
+    },
+    /// Reply to a command that could not be parsed or applied.
+    Error {
+        message: String,
+    },
+}
+
+/// Outgoing queue of one connected client.
+#[derive(Clone)]
+pub struct LiveClient(Sender<String>);
+
+impl LiveClient {
+    /// Queues `event`; returns `false` once the client has disconnected.
+    pub fn send(&self, event: &LiveEvent) -> bool {
+        match serde_json::to_string(event) {
+            Ok(text) => self.0.send(text).is_ok(),
+            Err(_) => true,
+        }
+    }
+}
+
+pub enum LiveMessage {
+    Connected(LiveClient),
+    Command(LiveClient, LiveCommand),
+    Invalid(LiveClient, String),
+}
+
+/// Local WebSocket server. Sockets are served on background threads; the app drains
+/// [`LiveLink::poll`] once per frame and pushes events with [`LiveLink::broadcast`].
+pub struct LiveLink {
+    address: SocketAddr,
+    incoming: Receiver<LiveMessage>,
+    clients: Vec<LiveClient>,
+}
+
+impl LiveLink {
+    /// Starts the server when [`LIVE_LINK_ENV`] is set.
+    pub fn from_env() -> Option<std::io::Result<Self>> {
+        let address = std::env::var(LIVE_LINK_ENV).ok()?;
+        Some(Self::bind(address.trim()))
+    }
+
+    pub fn bind(address: &str) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let (sender, incoming) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("live-link".to_string())
+            .spawn(move || accept_clients(listener, sender))?;
+        Ok(Self {
+            address,
+            incoming,
+            clients: Vec::new(),
+        })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Messages received since the last call. Newly connected clients join the broadcast list.
+    pub fn poll(&mut self) -> Vec<LiveMessage> {
+        let messages: Vec<LiveMessage> = self.incoming.try_iter().collect();
+        for message in &messages {
+            if let LiveMessage::Connected(client) = message {
+                self.clients.push(client.clone());
+            }
+        }
+        messages
+    }
+
+    pub fn broadcast(&mut self, event: &LiveEvent) {
+        self.clients.retain(|client| client.send(event));
+    }
+}
+
+fn accept_clients(listener: TcpListener, sender: Sender<LiveMessage>) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let sender = sender.clone();
+        let _ = std::thread::Builder::new()
+            .name("live-link-client".to_string())
+            .spawn(move || serve_client(stream, sender));
+    }
+}
+
+fn serve_client(stream: TcpStream, incoming: Sender<LiveMessage>) {
+    let Ok(mut socket) = tungstenite::accept(stream) else {
+        return;
+    };
+    if socket
+        .get_ref()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .is_err()
+    {
+        return;
+    }
+    let (sender, outgoing) = mpsc::channel();
+    let client = LiveClient(sender);
+    if incoming
+        .send(LiveMessage::Connected(client.clone()))
+        .is_err()
+    {
+        return;
+    }
+    loop {
+        while let Ok(text) = outgoing.try_recv() {
+            if socket.send(Message::text(text)).is_err() {
+                return;
+            }
+        }
+        let message = match socket.read() {
+            Ok(Message::Text(text)) => match serde_json::from_str(text.as_str()) {
+                Ok(command) => LiveMessage::Command(client.clone(), command),
+                Err(err) => LiveMessage::Invalid(client.clone(), err.to_string()),
+            },
+            Ok(Message::Close(_)) => return,
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(err))
+                if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+            {
+                continue;
+            }
+            Err(_) => return,
+        };
+        if incoming.send(message).is_err() {
+            return;
+        }
+    }
+}
@@ -1,6 +1,18 @@
 mod app;
-mod layers;
+#[cfg(not(target_arch = "wasm32"))]
+mod bench;
+mod crash;
+mod gpu;
+mod icon;
+#[cfg(feature = "live-link")]
+mod live_link;
 mod model;
 mod params;
+mod prefs;
+mod script;
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+mod web;
 
-pub use app::run_gui;
+pub use app::{run_gui, run_gui_with_model};
+#[cfg(not(target_arch = "wasm32"))]
+pub use bench::run_bench;
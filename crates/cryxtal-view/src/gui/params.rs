@@ -1,7 +1,29 @@
+use cryxtal_base::Guid;
+
+use crate::elements::{WallJoinStyle, WallOpeningMode};
+
 pub struct WallParams {
     pub thickness: f64,
     pub height: f64,
     pub name: String,
+    pub join_style: WallJoinStyle,
+    pub opening_mode: WallOpeningMode,
+    /// Wall type new walls are instances of; it fixes `thickness`.
+    pub type_id: Option<Guid>,
+}
+
+pub struct SlabParams {
+    pub thickness: f64,
+    pub name: String,
+}
+
+impl Default for SlabParams {
+    fn default() -> Self {
+        Self {
+            thickness: 250.0,
+            name: String::new(),
+        }
+    }
 }
 
 impl Default for WallParams {
@@ -10,6 +32,9 @@ impl Default for WallParams {
             thickness: 200.0,
             height: 3000.0,
             name: String::new(),
+            join_style: WallJoinStyle::default(),
+            opening_mode: WallOpeningMode::default(),
+            type_id: None,
         }
     }
 }
@@ -0,0 +1,96 @@
+use cryxtal_io::TessellationOptions;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+const PREFERENCES_FILE: &str = "preferences.json";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub adapter: Option<String>,
+    pub software_renderer: bool,
+    /// Name stamped on edited elements; the login name when unset.
+    pub author: Option<String>,
+    /// Chordal tolerance the viewport tessellates with, in model units; the default when
+    /// unset.
+    pub viewport_tolerance: Option<f64>,
+    /// Tighten the viewport tolerance of each solid to its most sharply curved face.
+    pub viewport_adaptive: bool,
+}
+
+impl Preferences {
+    pub fn load() -> Self {
+        let Some(path) = preferences_path() else {
+            return Self::default();
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_default(),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn author(&self) -> String {
+        self.author
+            .clone()
+            .filter(|author| !author.trim().is_empty())
+            .or_else(|| std::env::var("USER").ok())
+            .or_else(|| std::env::var("USERNAME").ok())
+            .unwrap_or_default()
+    }
+
+    /// Options the viewport tessellates elements with.
+    pub fn tessellation(&self) -> TessellationOptions {
+        let defaults = TessellationOptions::default();
+        TessellationOptions {
+            chordal_tol: self.viewport_tolerance.unwrap_or(defaults.chordal_tol),
+            curvature_adaptive: self.viewport_adaptive,
+            ..defaults
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = preferences_path() else {
+            anyhow::bail!("no configuration directory available");
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("CRYXTAL_CONFIG_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    base.map(|dir| dir.join("cryxtal"))
+}
+
+/// Directory for files the application writes for itself, such as crash bundles.
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("CRYXTAL_DATA_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    let base = if cfg!(windows) {
+        std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+            })
+    };
+    base.map(|dir| dir.join("cryxtal"))
+}
+
+fn preferences_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(PREFERENCES_FILE))
+}
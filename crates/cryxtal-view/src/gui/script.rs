@@ -0,0 +1,321 @@
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimModel, ParameterValue};
+use cryxtal_shapeops::{DEFAULT_SHAPEOPS_TOLERANCE, contains_point, intersects};
+use cryxtal_topology::Point3;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Rhai operations a run may take before it is stopped, so `loop {}` cannot hang the viewer.
+const MAX_OPERATIONS: u64 = 5_000_000;
+
+/// A model edit requested by a script. Edits are collected while the script runs and applied
+/// afterwards, each as its own undo step.
+#[derive(Clone, Debug)]
+pub enum ScriptAction {
+    AddWall { start: [f64; 3], end: [f64; 3] },
+    Delete(Guid),
+    SetLayer(Guid, String),
+    SetParameter(Guid, String, ParameterValue),
+    SetFormula(Guid, String, String),
+    Select(Guid),
+    Hide(Guid),
+    Isolate(Guid),
+    ShowAll,
+    Copy(Guid, [f64; 3]),
+    Paste([f64; 3]),
+    Undo,
+    Redo,
+}
+
+#[derive(Debug, Default)]
+pub struct ScriptRun {
+    /// Everything printed, including what was printed before an error.
+    pub output: Vec<String>,
+    pub actions: Vec<ScriptAction>,
+    /// Why the run stopped early; its actions are then dropped.
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct ScriptContext {
+    model: BimModel,
+    run: ScriptRun,
+}
+
+/// Rhai engine behind the console. Variables persist between runs; queries see the model as
+/// it was when the run started, since edits are only applied once it ends.
+pub struct ScriptEngine {
+    engine: Engine,
+    scope: Scope<'static>,
+    context: Rc<RefCell<ScriptContext>>,
+}
+
+impl ScriptEngine {
+    pub fn new() -> Self {
+        let context = Rc::new(RefCell::new(ScriptContext::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        let output = context.clone();
+        engine.on_print(move |text| output.borrow_mut().run.output.push(text.to_string()));
+        let output = context.clone();
+        engine.on_debug(move |text, _, _| output.borrow_mut().run.output.push(text.to_string()));
+
+        let queries = context.clone();
+        engine.register_fn("elements", move || -> Array {
+            let context = queries.borrow();
+            context
+                .model
+                .elements()
+                .iter()
+                .map(|element| {
+                    let mut map = Map::new();
+                    map.insert("guid".into(), element.guid.to_string().into());
+                    map.insert("name".into(), element.name.clone().into());
+                    map.insert("category".into(), format!("{:?}", element.category).into());
+                    let layer = match element.parameters.get("Layer") {
+                        Some(ParameterValue::Text(layer)) => layer.clone(),
+                        _ => String::new(),
+                    };
+                    map.insert("layer".into(), layer.into());
+                    Dynamic::from_map(map)
+                })
+                .collect()
+        });
+        let queries = context.clone();
+        engine.register_fn("count", move |category: &str| -> i64 {
+            let context = queries.borrow();
+            context
+                .model
+                .elements()
+                .iter()
+                .filter(|element| format!("{:?}", element.category).eq_ignore_ascii_case(category))
+                .count() as i64
+        });
+        let queries = context.clone();
+        engine.register_fn(
+            "param",
+            move |guid: &str, name: &str| -> ScriptResult<Dynamic> {
+                let guid = parse_guid(guid)?;
+                let context = queries.borrow();
+                let Some(element) = context.model.element(guid) else {
+                    return Err(format!("unknown element {guid}").into());
+                };
+                Ok(match element.parameters.get(name) {
+                    Some(ParameterValue::Integer(value)) => (*value).into(),
+                    Some(ParameterValue::Number(value)) => (*value).into(),
+                    Some(ParameterValue::Bool(value)) => (*value).into(),
+                    Some(ParameterValue::Text(value)) => value.clone().into(),
+                    None => Dynamic::UNIT,
+                })
+            },
+        );
+
+        let queries = context.clone();
+        engine.register_fn(
+            "contains",
+            move |guid: &str, at: Array| -> ScriptResult<bool> {
+                let guid = parse_guid(guid)?;
+                let [x, y, z] = point(at)?;
+                let context = queries.borrow();
+                let Some(element) = context.model.element(guid) else {
+                    return Err(format!("unknown element {guid}").into());
+                };
+                contains_point(
+                    &element.geometry,
+                    Point3::new(x, y, z),
+                    DEFAULT_SHAPEOPS_TOLERANCE,
+                )
+                .map_err(|err| err.to_string().into())
+            },
+        );
+        let queries = context.clone();
+        engine.register_fn(
+            "intersects",
+            move |a: &str, b: &str| -> ScriptResult<bool> {
+                let (a, b) = (parse_guid(a)?, parse_guid(b)?);
+                let context = queries.borrow();
+                let (Some(a), Some(b)) = (context.model.element(a), context.model.element(b))
+                else {
+                    return Err("unknown element".into());
+                };
+                // Reference meshes are context, not elements that can clash.
+                if a.is_reference_mesh() || b.is_reference_mesh() {
+                    return Ok(false);
+                }
+                intersects(&a.geometry, &b.geometry, DEFAULT_SHAPEOPS_TOLERANCE)
+                    .map_err(|err| err.to_string().into())
+            },
+        );
+
+        let actions = context.clone();
+        engine.register_fn(
+            "add_wall",
+            move |start: Array, end: Array| -> ScriptResult<()> {
+                let action = ScriptAction::AddWall {
+                    start: point(start)?,
+                    end: point(end)?,
+                };
+                actions.borrow_mut().run.actions.push(action);
+                Ok(())
+            },
+        );
+        let actions = context.clone();
+        engine.register_fn("delete", move |guid: &str| -> ScriptResult<()> {
+            let action = ScriptAction::Delete(parse_guid(guid)?);
+            actions.borrow_mut().run.actions.push(action);
+            Ok(())
+        });
+        let actions = context.clone();
+        engine.register_fn(
+            "set_layer",
+            move |guid: &str, layer: &str| -> ScriptResult<()> {
+                let action = ScriptAction::SetLayer(parse_guid(guid)?, layer.to_string());
+                actions.borrow_mut().run.actions.push(action);
+                Ok(())
+            },
+        );
+        let actions = context.clone();
+        engine.register_fn(
+            "set_param",
+            move |guid: &str, name: &str, value: Dynamic| -> ScriptResult<()> {
+                let action = ScriptAction::SetParameter(
+                    parse_guid(guid)?,
+                    name.to_string(),
+                    parameter(value)?,
+                );
+                actions.borrow_mut().run.actions.push(action);
+                Ok(())
+            },
+        );
+        let actions = context.clone();
+        engine.register_fn(
+            "set_formula",
+            move |guid: &str, name: &str, expression: &str| -> ScriptResult<()> {
+                let action = ScriptAction::SetFormula(
+                    parse_guid(guid)?,
+                    name.to_string(),
+                    expression.to_string(),
+                );
+                actions.borrow_mut().run.actions.push(action);
+                Ok(())
+            },
+        );
+        let actions = context.clone();
+        engine.register_fn("select", move |guid: &str| -> ScriptResult<()> {
+            let action = ScriptAction::Select(parse_guid(guid)?);
+            actions.borrow_mut().run.actions.push(action);
+            Ok(())
+        });
+        let actions = context.clone();
+        engine.register_fn("hide", move |guid: &str| -> ScriptResult<()> {
+            let action = ScriptAction::Hide(parse_guid(guid)?);
+            actions.borrow_mut().run.actions.push(action);
+            Ok(())
+        });
+        let actions = context.clone();
+        engine.register_fn("isolate", move |guid: &str| -> ScriptResult<()> {
+            let action = ScriptAction::Isolate(parse_guid(guid)?);
+            actions.borrow_mut().run.actions.push(action);
+            Ok(())
+        });
+        let actions = context.clone();
+        engine.register_fn("show_all", move || {
+            actions.borrow_mut().run.actions.push(ScriptAction::ShowAll);
+        });
+        let actions = context.clone();
+        engine.register_fn(
+            "copy",
+            move |guid: &str, anchor: Array| -> ScriptResult<()> {
+                let action = ScriptAction::Copy(parse_guid(guid)?, point(anchor)?);
+                actions.borrow_mut().run.actions.push(action);
+                Ok(())
+            },
+        );
+        let actions = context.clone();
+        engine.register_fn("paste", move |at: Array| -> ScriptResult<()> {
+            let action = ScriptAction::Paste(point(at)?);
+            actions.borrow_mut().run.actions.push(action);
+            Ok(())
+        });
+        let actions = context.clone();
+        engine.register_fn("undo", move || {
+            actions.borrow_mut().run.actions.push(ScriptAction::Undo);
+        });
+        let actions = context.clone();
+        engine.register_fn("redo", move || {
+            actions.borrow_mut().run.actions.push(ScriptAction::Redo);
+        });
+
+        Self {
+            engine,
+            scope: Scope::new(),
+            context,
+        }
+    }
+
+    /// Runs `code` against `model`, which is lent to the engine for the run and handed back
+    /// unchanged. A non-unit result is echoed as output.
+    pub fn run(&mut self, model: &mut BimModel, code: &str) -> ScriptRun {
+        self.context.borrow_mut().model = std::mem::take(model);
+        let result = self
+            .engine
+            .eval_with_scope::<Dynamic>(&mut self.scope, code);
+        let mut context = self.context.borrow_mut();
+        *model = std::mem::take(&mut context.model);
+        let mut run = std::mem::take(&mut context.run);
+        match result {
+            Ok(value) if !value.is_unit() => run.output.push(value.to_string()),
+            Ok(_) => {}
+            Err(err) => {
+                run.actions.clear();
+                run.error = Some(err.to_string());
+            }
+        }
+        run
+    }
+}
+
+/// Rhai literal for a point, as taken by `add_wall`, `copy` and `paste`.
+pub fn point_literal(point: [f64; 3]) -> String {
+    format!("[{:?}, {:?}, {:?}]", point[0], point[1], point[2])
+}
+
+fn parse_guid(text: &str) -> ScriptResult<Guid> {
+    text.parse()
+        .map_err(|err: cryxtal_base::Error| err.to_string().into())
+}
+
+fn number(value: &Dynamic) -> ScriptResult<f64> {
+    value
+        .as_float()
+        .or_else(|_| value.as_int().map(|value| value as f64))
+        .map_err(|type_name| format!("expected a number, got {type_name}").into())
+}
+
+fn point(values: Array) -> ScriptResult<[f64; 3]> {
+    match values.as_slice() {
+        [x, y] => Ok([number(x)?, number(y)?, 0.0]),
+        [x, y, z] => Ok([number(x)?, number(y)?, number(z)?]),
+        _ => Err("points are [x, y] or [x, y, z]".into()),
+    }
+}
+
+fn parameter(value: Dynamic) -> ScriptResult<ParameterValue> {
+    if let Ok(value) = value.as_int() {
+        return Ok(ParameterValue::Integer(value));
+    }
+    if let Ok(value) = value.as_float() {
+        return Ok(ParameterValue::Number(value));
+    }
+    if let Ok(value) = value.as_bool() {
+        return Ok(ParameterValue::Bool(value));
+    }
+    if value.is_string() {
+        return Ok(ParameterValue::Text(value.to_string()));
+    }
+    Err(format!("unsupported parameter value {}", value.type_name()).into())
+}
@@ -0,0 +1,106 @@
+use js_sys::Uint8Array;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{JsFuture, spawn_local};
+use web_sys::{DragEvent, HtmlCanvasElement, Response, UrlSearchParams};
+
+use super::app::run_web;
+
+/// Project file read by the page, or why it could not be read.
+pub(super) struct LoadedFile {
+    pub name: String,
+    pub bytes: Result<Vec<u8>, String>,
+}
+
+/// Files read by the page, waiting for the next frame to open them.
+pub(super) type PendingFiles = Rc<RefCell<Vec<LoadedFile>>>;
+
+/// Starts the viewer in the canvas with id `canvas_id`. The project at `model_url` is
+/// fetched and opened, or else the one named by the page's `?model=` query parameter;
+/// project files dropped on the canvas open as well.
+#[wasm_bindgen]
+pub fn start(canvas_id: &str, model_url: Option<String>) -> Result<(), JsValue> {
+    std::panic::set_hook(Box::new(|info| {
+        web_sys::console::error_1(&info.to_string().into());
+    }));
+    let window = web_sys::window().ok_or("no window")?;
+    let canvas: HtmlCanvasElement = window
+        .document()
+        .ok_or("no document")?
+        .get_element_by_id(canvas_id)
+        .ok_or_else(|| format!("no element with id `{canvas_id}`"))?
+        .dyn_into()
+        .map_err(|_| format!("`{canvas_id}` is not a canvas"))?;
+
+    let files = PendingFiles::default();
+    listen_for_drops(&canvas, &files)?;
+    let model_url = match model_url {
+        Some(url) => Some(url),
+        None => UrlSearchParams::new_with_str(&window.location().search()?)?.get("model"),
+    };
+    if let Some(url) = model_url {
+        let files = files.clone();
+        spawn_local(async move {
+            let bytes = fetch(&url).await.map_err(describe);
+            files.borrow_mut().push(LoadedFile { name: url, bytes });
+        });
+    }
+
+    spawn_local(async move {
+        if let Err(err) = run_web(canvas, files).await {
+            web_sys::console::error_1(&format!("CryXtal viewer failed: {err:#}").into());
+        }
+    });
+    Ok(())
+}
+
+fn listen_for_drops(canvas: &HtmlCanvasElement, files: &PendingFiles) -> Result<(), JsValue> {
+    // Without this the browser navigates to the dropped file instead of handing it over.
+    let on_drag_over = Closure::<dyn FnMut(DragEvent)>::new(|event: DragEvent| {
+        event.prevent_default();
+    });
+    canvas.add_event_listener_with_callback("dragover", on_drag_over.as_ref().unchecked_ref())?;
+    on_drag_over.forget();
+
+    let files = files.clone();
+    let on_drop = Closure::<dyn FnMut(DragEvent)>::new(move |event: DragEvent| {
+        event.prevent_default();
+        let Some(list) = event.data_transfer().and_then(|data| data.files()) else {
+            return;
+        };
+        for file in (0..list.length()).filter_map(|index| list.get(index)) {
+            let files = files.clone();
+            spawn_local(async move {
+                let bytes = JsFuture::from(file.array_buffer())
+                    .await
+                    .map(|buffer| Uint8Array::new(&buffer).to_vec())
+                    .map_err(describe);
+                files.borrow_mut().push(LoadedFile {
+                    name: file.name(),
+                    bytes,
+                });
+            });
+        }
+    });
+    canvas.add_event_listener_with_callback("drop", on_drop.as_ref().unchecked_ref())?;
+    on_drop.forget();
+    Ok(())
+}
+
+async fn fetch(url: &str) -> Result<Vec<u8>, JsValue> {
+    let window = web_sys::window().ok_or("no window")?;
+    let response: Response = JsFuture::from(window.fetch_with_str(url))
+        .await?
+        .dyn_into()?;
+    if !response.ok() {
+        return Err(format!("HTTP {} {}", response.status(), response.status_text()).into());
+    }
+    let buffer = JsFuture::from(response.array_buffer()?).await?;
+    Ok(Uint8Array::new(&buffer).to_vec())
+}
+
+fn describe(err: JsValue) -> String {
+    err.as_string().unwrap_or_else(|| format!("{err:?}"))
+}
@@ -1,8 +1,22 @@
 use anyhow::{Context, Result, bail};
-use cryxtal_io::{DEFAULT_TESSELLATION_TOLERANCE, export_obj, export_step};
+use cryxtal_base::{CoordinateInput, Guid, LengthUnit, parse_coordinate};
+use cryxtal_bim::{BimElement, BimModel, ModelCommand};
+use cryxtal_io::{
+    TessellationOptions, export_obj, export_step, export_step_tagged, load_project, save_project,
+};
+use cryxtal_topology::Point3;
 
-use crate::cli::{GenerateCommand, HeadlessCommand};
-use crate::elements::{build_box_element, build_plate_element};
+use crate::cli::{
+    BenchArgs, ColumnArgs, ConnectionArgs, FoundationRebarArgs, GenerateCommand, HeadlessCommand,
+    PileCapArgs, RegenerateArgs,
+};
+use crate::elements::{
+    BeamOptions, BoltedConnection, ColumnLevel, FoundationReinforcement, PileCap, PileLayout,
+    SectionProfile, build_beam_between_points, build_bolted_connection, build_box_element,
+    build_column_element, build_footing_element, build_level_column, build_mep_segment,
+    build_pile_cap, build_plate_element, build_ramp_element, build_roof_element,
+    build_strip_footing, regenerate_level_elements, steel_section,
+};
 
 pub fn run_headless(command: HeadlessCommand) -> Result<()> {
     match command {
@@ -29,11 +43,135 @@ pub fn run_headless(command: HeadlessCommand) -> Result<()> {
             export_obj(
                 element.geometry(),
                 &args.out,
-                DEFAULT_TESSELLATION_TOLERANCE,
+                &TessellationOptions::default(),
             )?;
             println!("OBJ exported: {}", args.out);
             Ok(())
         }
+        HeadlessCommand::Generate {
+            command: GenerateCommand::Column(args),
+        } => generate_column(args),
+        HeadlessCommand::Generate {
+            command: GenerateCommand::Beam(args),
+        } => {
+            let profile = match (args.profile.as_deref(), args.width, args.depth) {
+                (Some(name), None, None) => SectionProfile::Steel(
+                    steel_section(name)
+                        .with_context(|| format!("unknown steel profile `{name}`"))?,
+                ),
+                (None, Some(width), Some(depth)) => SectionProfile::Rectangular { width, depth },
+                _ => bail!("beam expects either --profile, or --width and --depth"),
+            };
+            let start = parse_point(&args.start, "--start")?;
+            let end = parse_point(&args.end, "--end")?;
+            let options = BeamOptions {
+                roll_deg: args.roll,
+                start_offset: args.start_offset,
+                end_offset: args.end_offset,
+            };
+            let mut element =
+                build_beam_between_points(start, end, profile, options, args.name.as_deref())?;
+            if args.out.is_none() && args.project.is_none() {
+                bail!("beam expects --out or --project");
+            }
+            if let Some(out) = args.out.as_deref() {
+                export_element_step(&element, out)?;
+            }
+            if let Some(project_path) = args.project.as_deref() {
+                let mut project = load_project(project_path)?;
+                let storey = project
+                    .model
+                    .default_storey()
+                    .context("project has no storey")?;
+                // Beams are built in millimetres.
+                element.convert_length(LengthUnit::Millimeter, project.model.units.length);
+                project.model.add_element(element, storey)?;
+                save_project(&project, project_path)?;
+                println!("Beam added to {project_path}");
+            }
+            Ok(())
+        }
+        HeadlessCommand::Generate {
+            command: GenerateCommand::Footing(args),
+        } => {
+            let center = parse_point(&args.center, "--center")?;
+            let (width, depth, thickness) = parse_size(&args.size)?;
+            let element = build_footing_element(
+                center,
+                width,
+                depth,
+                thickness,
+                reinforcement(&args.rebar).as_ref(),
+                args.name.as_deref(),
+            )?;
+            export_element_step(&element, &args.out)
+        }
+        HeadlessCommand::Generate {
+            command: GenerateCommand::StripFooting(args),
+        } => {
+            let start = parse_point(&args.start, "--start")?;
+            let end = parse_point(&args.end, "--end")?;
+            let element = build_strip_footing(
+                start,
+                end,
+                args.width,
+                args.thickness,
+                reinforcement(&args.rebar).as_ref(),
+                args.name.as_deref(),
+            )?;
+            export_element_step(&element, &args.out)
+        }
+        HeadlessCommand::Generate {
+            command: GenerateCommand::PileCap(args),
+        } => generate_pile_cap(args),
+        HeadlessCommand::Generate {
+            command: GenerateCommand::Roof(args),
+        } => {
+            let outline = args
+                .outline
+                .split(';')
+                .map(|point| parse_point(point, "--outline"))
+                .collect::<Result<Vec<_>>>()?;
+            let element =
+                build_roof_element(&outline, args.thickness, args.slope, args.name.as_deref())?;
+            export_element_step(&element, &args.out)
+        }
+        HeadlessCommand::Generate {
+            command: GenerateCommand::Ramp(args),
+        } => {
+            let start = parse_edge(&args.start, "--start")?;
+            let end = parse_edge(&args.end, "--end")?;
+            let element = build_ramp_element(start, end, args.thickness, args.name.as_deref())?;
+            export_element_step(&element, &args.out)
+        }
+        HeadlessCommand::Generate {
+            command: GenerateCommand::Pipe(args),
+        } => {
+            let start = parse_point(&args.start, "--start")?;
+            let end = parse_point(&args.end, "--end")?;
+            let profile = SectionProfile::Circular {
+                diameter: args.diameter,
+            };
+            let element = build_mep_segment(start, end, profile, args.name.as_deref())?;
+            export_element_step(&element, &args.out)
+        }
+        HeadlessCommand::Generate {
+            command: GenerateCommand::Duct(args),
+        } => {
+            let start = parse_point(&args.start, "--start")?;
+            let end = parse_point(&args.end, "--end")?;
+            let profile = SectionProfile::Rectangular {
+                width: args.width,
+                depth: args.height,
+            };
+            let element = build_mep_segment(start, end, profile, args.name.as_deref())?;
+            export_element_step(&element, &args.out)
+        }
+        HeadlessCommand::Generate {
+            command: GenerateCommand::Connection(args),
+        } => generate_connection(args),
+        HeadlessCommand::Regenerate(args) => regenerate_columns(args),
+        HeadlessCommand::Bench(args) => run_bench(&args),
         HeadlessCommand::Triangulate(args) => {
             let _ = args.out;
             bail!(
@@ -44,14 +182,239 @@ pub fn run_headless(command: HeadlessCommand) -> Result<()> {
     }
 }
 
+#[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
+fn run_bench(args: &BenchArgs) -> Result<()> {
+    crate::gui::run_bench(args)
+}
+
+#[cfg(not(all(feature = "gui", not(target_arch = "wasm32"))))]
+fn run_bench(_args: &BenchArgs) -> Result<()> {
+    bail!("benchmarking needs the native GPU renderer. Rebuild with --features gui.")
+}
+
+fn generate_column(args: ColumnArgs) -> Result<()> {
+    let profile = match (
+        args.profile.as_deref(),
+        args.width,
+        args.depth,
+        args.diameter,
+    ) {
+        (Some(name), None, None, None) => SectionProfile::Steel(
+            steel_section(name).with_context(|| format!("unknown steel profile `{name}`"))?,
+        ),
+        (None, Some(width), Some(depth), None) => SectionProfile::Rectangular { width, depth },
+        (None, None, None, Some(diameter)) => SectionProfile::Circular { diameter },
+        _ => bail!("column expects either --profile, --width and --depth, or --diameter"),
+    };
+    let base = parse_point(&args.base, "--base")?;
+    let Some(project_path) = args.project.as_deref() else {
+        if args.base_storey.is_some() || args.top_storey.is_some() {
+            bail!("--base-storey and --top-storey need --project");
+        }
+        let height = args.height.context("column expects --height")?;
+        let out = args
+            .out
+            .as_deref()
+            .context("column expects --out or --project")?;
+        let element = build_column_element(base, profile, height, args.name.as_deref())?;
+        return export_element_step(&element, out);
+    };
+
+    let mut project = load_project(project_path)?;
+    let model = &project.model;
+    let base_level = match args.base_storey.as_deref() {
+        Some(name) => ColumnLevel::Storey {
+            storey: storey_by_name(model, name)?,
+            offset: args.base_offset,
+        },
+        None => ColumnLevel::Elevation(base.z),
+    };
+    let top_level = match (args.top_storey.as_deref(), args.height) {
+        (Some(name), None) => ColumnLevel::Storey {
+            storey: storey_by_name(model, name)?,
+            offset: args.top_offset,
+        },
+        (None, Some(height)) => ColumnLevel::Elevation(base_level.elevation(model)? + height),
+        _ => bail!("column expects either --height or --top-storey"),
+    };
+    let element = build_level_column(
+        model,
+        base,
+        profile,
+        base_level,
+        top_level,
+        args.name.as_deref(),
+    )?;
+    if let Some(out) = args.out.as_deref() {
+        export_element_step(&element, out)?;
+    }
+    let storey = match base_level {
+        ColumnLevel::Storey { storey, .. } => storey,
+        ColumnLevel::Elevation(_) => model.default_storey().context("project has no storey")?,
+    };
+    project.model.add_element(element, storey)?;
+    save_project(&project, project_path)?;
+    println!("Column added to {project_path}");
+    Ok(())
+}
+
+fn regenerate_columns(args: RegenerateArgs) -> Result<()> {
+    let mut project = load_project(&args.project)?;
+    for assignment in &args.storey {
+        let (name, elevation) = assignment
+            .split_once('=')
+            .with_context(|| format!("--storey expects NAME=ELEVATION, got `{assignment}`"))?;
+        let elevation: f64 = elevation
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid elevation in --storey `{assignment}`"))?;
+        let storey = project
+            .model
+            .site
+            .buildings
+            .iter_mut()
+            .flat_map(|building| building.storeys.iter_mut())
+            .find(|storey| storey.name == name.trim())
+            .with_context(|| format!("unknown storey `{}`", name.trim()))?;
+        storey.elevation = elevation;
+    }
+    let elements = regenerate_level_elements(&project.model)?;
+    let count = elements.len();
+    ModelCommand::ReplaceElements { elements }.apply(&mut project.model)?;
+    save_project(&project, &args.project)?;
+    println!("Regenerated {count} element(s) in {}", args.project);
+    Ok(())
+}
+
+fn generate_connection(args: ConnectionArgs) -> Result<()> {
+    let mut project = load_project(&args.project)?;
+    let model = &project.model;
+    let beam = element_by_name(model, &args.beam)?;
+    let column = element_by_name(model, &args.column)?;
+    let connection = BoltedConnection {
+        bolt_diameter: args.bolt_diameter,
+        gauge: args.gauge,
+        pitch: args.pitch,
+        rows: args.rows,
+        columns: args.bolt_columns,
+        plate_thickness: args.plate_thickness,
+        edge: args.edge,
+    };
+    let parts = build_bolted_connection(beam, column, connection, args.name.as_deref())?;
+    let storey = model
+        .storey_of(column.guid)
+        .map(|storey| storey.guid)
+        .or_else(|| model.default_storey())
+        .context("project has no storey")?;
+    ModelCommand::Batch(vec![
+        ModelCommand::ReplaceElements {
+            elements: vec![parts.column],
+        },
+        ModelCommand::AddElements {
+            storey,
+            elements: vec![parts.plate, parts.bolts],
+        },
+    ])
+    .apply(&mut project.model)?;
+    save_project(&project, &args.project)?;
+    println!("Connection added to {}", args.project);
+    Ok(())
+}
+
+fn generate_pile_cap(args: PileCapArgs) -> Result<()> {
+    let center = parse_point(&args.center, "--center")?;
+    let (width, depth, thickness) = parse_size(&args.size)?;
+    let cap = PileCap {
+        width,
+        depth,
+        thickness,
+        piles: args.pile_diameter.map(|diameter| PileLayout {
+            rows: args.pile_rows,
+            columns: args.pile_columns,
+            spacing: args.pile_spacing,
+            diameter,
+            length: args.pile_length,
+        }),
+    };
+    let parts = build_pile_cap(
+        center,
+        &cap,
+        reinforcement(&args.rebar).as_ref(),
+        args.name.as_deref(),
+    )?;
+    let mut project = load_project(&args.project)?;
+    let storey = project
+        .model
+        .default_storey()
+        .context("project has no storey")?;
+    let mut elements = vec![parts.cap];
+    elements.extend(parts.piles);
+    for element in &mut elements {
+        // Foundations are built in millimetres.
+        element.convert_length(LengthUnit::Millimeter, project.model.units.length);
+    }
+    let count = elements.len() - 1;
+    ModelCommand::AddElements { storey, elements }.apply(&mut project.model)?;
+    save_project(&project, &args.project)?;
+    println!("Pile cap with {count} pile(s) added to {}", args.project);
+    Ok(())
+}
+
+fn reinforcement(args: &FoundationRebarArgs) -> Option<FoundationReinforcement> {
+    args.rebar_diameter.map(|diameter| FoundationReinforcement {
+        diameter,
+        spacing: args.rebar_spacing,
+        cover: args.cover,
+        top_mat: args.top_mat,
+    })
+}
+
+fn element_by_name<'a>(model: &'a BimModel, name: &str) -> Result<&'a BimElement> {
+    model
+        .elements()
+        .iter()
+        .find(|element| element.name == name)
+        .with_context(|| format!("unknown element `{name}`"))
+}
+
+fn storey_by_name(model: &BimModel, name: &str) -> Result<Guid> {
+    model
+        .storeys()
+        .find(|storey| storey.name == name)
+        .map(|storey| storey.guid)
+        .with_context(|| format!("unknown storey `{name}`"))
+}
+
+fn export_element_step(element: &BimElement, out: &str) -> Result<()> {
+    export_step_tagged(element.geometry(), &element.tags, out)?;
+    println!("STEP exported: {out}");
+    Ok(())
+}
+
+fn parse_point(text: &str, flag: &str) -> Result<Point3> {
+    match parse_coordinate(text).with_context(|| format!("invalid {flag}"))? {
+        CoordinateInput::Absolute([x, y, z]) => Ok(Point3::new(x, y, z)),
+        CoordinateInput::Relative(_) => bail!("{flag} does not accept a relative `@` value"),
+    }
+}
+
+fn parse_edge(text: &str, flag: &str) -> Result<[Point3; 2]> {
+    let points = text
+        .split(';')
+        .map(|point| parse_point(point, flag))
+        .collect::<Result<Vec<_>>>()?;
+    match points.as_slice() {
+        [a, b] => Ok([*a, *b]),
+        _ => bail!("{flag} expects two points separated by `;`"),
+    }
+}
+
 fn parse_size(text: &str) -> Result<(f64, f64, f64)> {
-    let parts: Vec<&str> = text.split(',').collect();
-    if parts.len() != 3 {
+    if text.split(',').count() != 3 {
         bail!("--size expects three comma-separated numbers, e.g. 100,200,300");
     }
-
-    let width: f64 = parts[0].trim().parse().context("invalid width")?;
-    let height: f64 = parts[1].trim().parse().context("invalid height")?;
-    let depth: f64 = parts[2].trim().parse().context("invalid depth")?;
-    Ok((width, height, depth))
+    match parse_coordinate(text).context("invalid --size")? {
+        CoordinateInput::Absolute([width, height, depth]) => Ok((width, height, depth)),
+        CoordinateInput::Relative(_) => bail!("--size does not accept a relative `@` value"),
+    }
 }
@@ -1,10 +1,145 @@
-use cryxtal_base::{Error, Result};
+use anyhow::Result;
+use clap::Parser;
+use cryxtal_base::Guid;
+use cryxtal_bim::{BimCategory, BimElement, BimModel, ParameterSet};
+use cryxtal_io::{GltfOptions, export_gltf};
 use cryxtal_topology::Solid;
+use std::path::{Path, PathBuf};
 
-pub struct ViewerStub;
+mod cli;
+mod elements;
+mod headless;
+#[cfg(feature = "gui")]
+mod gui;
+#[cfg(feature = "gui")]
+mod viewer;
 
-impl ViewerStub {
-    pub fn open(_solid: &Solid) -> Result<()> {
-        Err(Error::NotImplemented("viewer is not implemented"))
+/// What a [`Viewer`] shows: a whole model, or loose elements put on a model of their own.
+pub enum ViewerContent {
+    Model(BimModel),
+    Elements(Vec<BimElement>),
+}
+
+impl From<BimModel> for ViewerContent {
+    fn from(model: BimModel) -> Self {
+        ViewerContent::Model(model)
+    }
+}
+
+impl From<Vec<BimElement>> for ViewerContent {
+    fn from(elements: Vec<BimElement>) -> Self {
+        ViewerContent::Elements(elements)
     }
 }
+
+impl From<BimElement> for ViewerContent {
+    fn from(element: BimElement) -> Self {
+        ViewerContent::Elements(vec![element])
+    }
+}
+
+impl From<Solid> for ViewerContent {
+    fn from(solid: Solid) -> Self {
+        let element = BimElement::new(
+            Guid::new(),
+            "Solid",
+            BimCategory::Generic,
+            ParameterSet::new(),
+            solid,
+        );
+        ViewerContent::Elements(vec![element])
+    }
+}
+
+/// Preview of a model or elements from library code. With the `gui` feature, [`Viewer::show`]
+/// opens the viewer window on it; without, the preview is rendered to a GLB file instead.
+pub struct Viewer {
+    model: BimModel,
+    #[cfg_attr(not(feature = "gui"), allow(dead_code))]
+    read_only: bool,
+}
+
+impl Viewer {
+    pub fn new(content: impl Into<ViewerContent>) -> Result<Self> {
+        let model = match content.into() {
+            ViewerContent::Model(model) => model,
+            ViewerContent::Elements(elements) => {
+                let mut model = BimModel::new("Preview");
+                let storey = model
+                    .default_storey()
+                    .ok_or_else(|| anyhow::anyhow!("preview model has no storey"))?;
+                for element in elements {
+                    model.add_element(element, storey)?;
+                }
+                model
+            }
+        };
+        Ok(Self {
+            model,
+            read_only: false,
+        })
+    }
+
+    /// Opens the preview for review only, refusing edits.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Shows `content` in a new viewer; see [`Viewer::show`].
+    pub fn open(content: impl Into<ViewerContent>) -> Result<Option<PathBuf>> {
+        Self::new(content)?.show()
+    }
+
+    /// Opens the viewer window and blocks until it is closed, returning `None`. The window
+    /// needs the main thread and can be opened once per process. Built without the `gui`
+    /// feature, renders the preview with [`Viewer::render_to`] into the temporary directory
+    /// instead and returns the file.
+    pub fn show(self) -> Result<Option<PathBuf>> {
+        #[cfg(feature = "gui")]
+        {
+            gui::run_gui_with_model(self.model, self.read_only)?;
+            Ok(None)
+        }
+        #[cfg(not(feature = "gui"))]
+        {
+            let path = std::env::temp_dir().join(format!("cryxtal-preview-{}.glb", Guid::new()));
+            self.render_to(&path)?;
+            Ok(Some(path))
+        }
+    }
+
+    /// Writes the preview as a binary glTF (`.glb`) with each element colored by its layer,
+    /// for any glTF viewer to display.
+    pub fn render_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let options = GltfOptions {
+            layers: self.model.layers.clone(),
+            ..GltfOptions::default()
+        };
+        export_gltf(self.model.elements(), path, &options)
+    }
+
+    pub fn model(&self) -> &BimModel {
+        &self.model
+    }
+}
+
+/// Entry point of the `cryxtal-view` binary: the GUI, or a headless command.
+#[doc(hidden)]
+pub fn run_cli() -> Result<()> {
+    let args = cli::CliArgs::parse();
+    match args.mode {
+        Some(cli::Mode::Headless { command }) => headless::run_headless(command),
+        None => run_gui(args.project, args.read_only),
+    }
+}
+
+#[cfg(feature = "gui")]
+fn run_gui(project: Option<PathBuf>, read_only: bool) -> Result<()> {
+    gui::run_gui(project, read_only)
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_gui(_project: Option<PathBuf>, _read_only: bool) -> Result<()> {
+    anyhow::bail!("GUI support disabled. Rebuild with --features gui.");
+}
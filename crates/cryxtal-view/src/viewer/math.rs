@@ -1,6 +1,6 @@
-use cryxtal_topology::Point3;
+use cryxtal_topology::{Point3, Vector3};
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Vec3 {
     pub x: f64,
     pub y: f64,
@@ -58,6 +58,12 @@ impl From<Point3> for Vec3 {
     }
 }
 
+impl From<Vector3> for Vec3 {
+    fn from(vector: Vector3) -> Self {
+        Self::new(vector.x, vector.y, vector.z)
+    }
+}
+
 impl std::ops::Add for Vec3 {
     type Output = Self;
 
@@ -6,15 +6,20 @@ mod mesh;
 mod input;
 mod pick;
 mod pivot;
+mod point_cloud;
 mod state;
+mod tracking;
 mod truck_renderer;
 mod overlay;
 mod ui;
 
 pub use mesh::ViewerMesh;
+pub use point_cloud::ViewerPointCloud;
 pub use input::{Modifiers, ViewerInput};
 pub use gizmo_renderer::GizmoRenderer;
-pub use state::{GizmoMode, ViewMode, ViewerState};
-pub use truck_renderer::TruckRenderer;
+pub use state::{GizmoMode, ReferenceShape, ViewMode, ViewerState};
+pub(crate) use state::dashed_line;
+pub use tracking::{PolarTracking, TRACKING_INCREMENTS, TrackingHit};
+pub use truck_renderer::{RenderStats, TruckRenderer};
 pub use overlay::{OverlayCollector, OverlayPainter, OverlayShape};
 pub use ui::{Align2, Color32, Point2, Rect, Stroke, Vec2};
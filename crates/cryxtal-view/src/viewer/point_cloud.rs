@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use cryxtal_topology::Point3;
+use truck_platform::{BufferHandler, DeviceHandler, RenderID, Rendered, SceneDescriptor};
+
+use super::ui::Color32;
+
+const POINT_SHADER: &str = include_str!("point_cloud.wgsl");
+/// A cloud vertex: position, then color, as `f32`s.
+type CloudVertex = [f32; 6];
+
+/// A point cloud drawn with the model as context, such as an as-built scan; see
+/// [`TruckRenderer::set_point_clouds`](super::TruckRenderer::set_point_clouds).
+#[derive(Clone, Debug, Default)]
+pub struct ViewerPointCloud {
+    pub points: Vec<Point3>,
+    /// Color of each point, in step with `points`; empty draws every point in `color`.
+    pub colors: Vec<Color32>,
+    pub color: Color32,
+}
+
+/// A cloud uploaded to the GPU, drawn as one-pixel points depth tested against the model.
+pub(super) struct PointCloudInstance {
+    id: RenderID,
+    vertices: Arc<BufferHandler>,
+    indices: Arc<BufferHandler>,
+}
+
+impl PointCloudInstance {
+    /// Uploads `cloud`, or returns `None` for a cloud without points.
+    pub(super) fn new(device: &wgpu::Device, cloud: &ViewerPointCloud) -> Option<Self> {
+        if cloud.points.is_empty() {
+            return None;
+        }
+        let vertices: Vec<CloudVertex> = cloud
+            .points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| {
+                let color = cloud.colors.get(index).copied().unwrap_or(cloud.color);
+                [
+                    point.x as f32,
+                    point.y as f32,
+                    point.z as f32,
+                    color.r as f32 / 255.0,
+                    color.g as f32 / 255.0,
+                    color.b as f32 / 255.0,
+                ]
+            })
+            .collect();
+        let indices: Vec<u32> = (0..vertices.len() as u32).collect();
+        Some(Self {
+            id: RenderID::r#gen(),
+            vertices: Arc::new(BufferHandler::from_slice(
+                &vertices,
+                device,
+                wgpu::BufferUsages::VERTEX,
+            )),
+            indices: Arc::new(BufferHandler::from_slice(
+                &indices,
+                device,
+                wgpu::BufferUsages::INDEX,
+            )),
+        })
+    }
+}
+
+impl Rendered for PointCloudInstance {
+    fn render_id(&self) -> RenderID {
+        self.id
+    }
+
+    fn vertex_buffer(&self, _: &DeviceHandler) -> (Arc<BufferHandler>, Option<Arc<BufferHandler>>) {
+        (self.vertices.clone(), Some(self.indices.clone()))
+    }
+
+    fn bind_group_layout(&self, handler: &DeviceHandler) -> Arc<wgpu::BindGroupLayout> {
+        Arc::new(
+            handler
+                .device()
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("point_cloud"),
+                    entries: &[],
+                }),
+        )
+    }
+
+    fn bind_group(
+        &self,
+        handler: &DeviceHandler,
+        layout: &wgpu::BindGroupLayout,
+    ) -> Arc<wgpu::BindGroup> {
+        Arc::new(
+            handler
+                .device()
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("point_cloud"),
+                    layout,
+                    entries: &[],
+                }),
+        )
+    }
+
+    fn pipeline(
+        &self,
+        handler: &DeviceHandler,
+        layout: &wgpu::PipelineLayout,
+        scene_desc: &SceneDescriptor,
+    ) -> Arc<wgpu::RenderPipeline> {
+        let device = handler.device();
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("point_cloud"),
+            source: wgpu::ShaderSource::Wgsl(POINT_SHADER.into()),
+        });
+        // Same depth buffer as the scene's meshes, so a scan behind a wall stays hidden.
+        let depth_stencil = scene_desc
+            .backend_buffer
+            .depth_test
+            .then(|| wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: Default::default(),
+                bias: Default::default(),
+            });
+        Arc::new(
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("point_cloud"),
+                layout: Some(layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    compilation_options: Default::default(),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: std::mem::size_of::<CloudVertex>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3],
+                    }],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: scene_desc.render_texture.format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::PointList,
+                    ..Default::default()
+                },
+                depth_stencil,
+                multisample: wgpu::MultisampleState {
+                    count: scene_desc.backend_buffer.sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            }),
+        )
+    }
+}
@@ -4,9 +4,11 @@ use super::math::{Vec3, rotate_around_axis};
 use super::mesh::ViewerMesh;
 use super::overlay::OverlayPainter;
 use super::pivot::PivotState;
+use super::tracking::TrackingHit;
 use super::ui::{Align2, Color32, Point2, Rect, Stroke, Vec2, pos2, vec2};
 use super::viewcube::{ViewBasis, draw as draw_viewcube, pick_target as pick_viewcube_target, view_direction_from_normal};
-use cryxtal_topology::Point3;
+use cryxtal_io::ProjectCamera;
+use cryxtal_topology::{Point3, Vector3};
 
 #[derive(Clone, Copy, Debug)]
 struct CameraBasis {
@@ -46,11 +48,22 @@ impl Default for GizmoMode {
     }
 }
 
+/// Reference geometry as the viewer draws and snaps to it. Lines and planes are unbounded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReferenceShape {
+    Point(Vec3),
+    Line { origin: Vec3, direction: Vec3 },
+    Plane { origin: Vec3, normal: Vec3 },
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum SnapKind {
     Vertex,
     EdgeMidpoint,
     FaceCenter,
+    ReferencePoint,
+    ReferenceLine,
+    ReferencePlane,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -84,6 +97,10 @@ pub struct ViewerState {
     fov_deg: f64,
     view_transition: Option<ViewTransition>,
     snap_cache: Option<SnapCache>,
+    references: Vec<ReferenceShape>,
+    /// Origin and unit normal of the plane points are picked on off the model, if not the
+    /// horizontal plane through the pivot.
+    work_plane: Option<(Vec3, Vec3)>,
     gizmo_mode: GizmoMode,
     gizmo_drag_active: bool,
     gizmo_drag_pos: Option<Point2>,
@@ -108,6 +125,8 @@ impl Default for ViewerState {
             fov_deg: 60.0,
             view_transition: None,
             snap_cache: None,
+            references: Vec::new(),
+            work_plane: None,
             gizmo_mode: GizmoMode::Cube,
             gizmo_drag_active: false,
             gizmo_drag_pos: None,
@@ -123,6 +142,27 @@ impl ViewerState {
         self.gizmo_mode = gizmo_mode;
     }
 
+    pub fn camera(&self) -> ProjectCamera {
+        let point = |v: Vec3| [v.x, v.y, v.z];
+        ProjectCamera {
+            target: point(self.target),
+            position: point(self.camera_pos),
+            up: point(self.camera_up),
+            fov_deg: self.fov_deg,
+        }
+    }
+
+    pub fn set_camera(&mut self, camera: &ProjectCamera) {
+        let vec = |[x, y, z]: [f64; 3]| Vec3::new(x, y, z);
+        self.view_transition = None;
+        self.target = vec(camera.target);
+        self.camera_pos = vec(camera.position);
+        self.camera_up = vec(camera.up);
+        self.fov_deg = camera.fov_deg;
+        self.pivot.set_position(self.target);
+        self.snap_cache = None;
+    }
+
     pub fn gizmo_mode(&self) -> GizmoMode {
         self.gizmo_mode
     }
@@ -147,6 +187,34 @@ impl ViewerState {
         self.camera_up = Self::default_up(forward);
     }
 
+    /// Turns the camera to look along `forward` at `center` from its current distance, such as
+    /// straight onto a picked face.
+    pub fn look_at(&mut self, center: Point3, forward: Vector3) {
+        let center = Vec3::from(center);
+        self.camera_pos = self.camera_pos + (center - self.target);
+        self.target = center;
+        self.pivot.set_position(center);
+        self.snap_cache = None;
+        self.begin_view_transition(forward.into());
+    }
+
+    pub fn work_plane(&self) -> Option<(Point3, Vector3)> {
+        self.work_plane.map(|(origin, normal)| {
+            (
+                Point3::new(origin.x, origin.y, origin.z),
+                Vector3::new(normal.x, normal.y, normal.z),
+            )
+        })
+    }
+
+    /// Sets the plane points are picked on where the pointer misses the model, such as a face
+    /// to sketch on; `None` goes back to the horizontal plane through the pivot.
+    pub fn set_work_plane(&mut self, plane: Option<(Point3, Vector3)>) {
+        self.work_plane =
+            plane.map(|(origin, normal)| (origin.into(), Vec3::from(normal).normalized()));
+        self.snap_cache = None;
+    }
+
     pub fn update(&mut self, dt: f64) -> bool {
         self.update_view_transition(dt);
         self.view_transition.is_some()
@@ -156,6 +224,14 @@ impl ViewerState {
         self.snap_cache = None;
     }
 
+    /// Replaces the reference geometry that is drawn and snapped to; pass an empty list to hide it.
+    pub fn set_references(&mut self, references: Vec<ReferenceShape>) {
+        if self.references != references {
+            self.references = references;
+            self.snap_cache = None;
+        }
+    }
+
     pub fn handle_input(&mut self, input: &ViewerInput, meshes: &[ViewerMesh]) -> bool {
         let basis = self.camera_basis();
         let ctrl = input.modifiers.ctrl;
@@ -332,6 +408,8 @@ impl ViewerState {
                 self.draw_selection_handles(painter, rect, &basis, scale, mesh);
             }
         }
+        self.draw_references(painter, rect, &basis, scale);
+        self.draw_work_plane(painter, rect, &basis, scale);
 
         self.pivot
             .draw(painter, |point| self.project(point, rect, &basis, scale));
@@ -389,6 +467,33 @@ impl ViewerState {
         );
     }
 
+    /// Dashed tracking ray from the last point through the cursor, with a distance/angle readout.
+    pub fn paint_tracking<P: OverlayPainter>(&self, painter: &mut P, rect: Rect, hit: &TrackingHit) {
+        let basis = self.camera_basis();
+        let scale = self.view_scale(rect);
+        let reach = hit.distance.max(0.0) + rect.width().max(rect.height()) as f64 / scale;
+        let Some((start, _)) = self.project(hit.origin, rect, &basis, scale) else {
+            return;
+        };
+        let Some((end, _)) = self.project(hit.at_distance(reach), rect, &basis, scale) else {
+            return;
+        };
+        let Some((point, _)) = self.project(hit.point(), rect, &basis, scale) else {
+            return;
+        };
+
+        let stroke = Stroke::new(1.0, Color32::from_rgb(120, 200, 255));
+        dashed_line(painter, start, end, stroke);
+        painter.circle_stroke(point, 4.0, stroke);
+        painter.text(
+            point + Vec2::new(12.0, 10.0),
+            Align2::LeftTop,
+            format!("{:.0} < {:.0}°", hit.distance, hit.angle_deg),
+            12.0,
+            Color32::from_rgb(170, 220, 255),
+        );
+    }
+
     pub fn distance(&self) -> f64 {
         self.distance_internal()
     }
@@ -618,9 +723,36 @@ impl ViewerState {
             return Some(point);
         }
 
+        if let Some((origin, normal)) = self.work_plane {
+            let (ray_origin, dir) = self.screen_ray(pos, rect, &basis, scale)?;
+            let denom = dir.dot(normal);
+            if denom.abs() <= 1.0e-6 {
+                return None;
+            }
+            let t = (origin - ray_origin).dot(normal) / denom;
+            return (t > 0.0).then(|| ray_origin + dir * t);
+        }
+
         self.pick_on_plane(pos, rect, &basis, scale, self.pivot.position().z)
     }
 
+    /// Snap target under `pos` with its label, sharing the overlay's snap cache.
+    pub fn snap_target(
+        &mut self,
+        pos: Point2,
+        rect: Rect,
+        meshes: &[ViewerMesh],
+    ) -> Option<(&'static str, Point3)> {
+        let basis = self.camera_basis();
+        let scale = self.view_scale(rect);
+        self.cached_snap(pos, rect, &basis, scale, meshes).map(|hit| {
+            (
+                snap_label(hit.kind),
+                Point3::new(hit.world.x, hit.world.y, hit.world.z),
+            )
+        })
+    }
+
     fn pick_on_plane(
         &self,
         pos: Point2,
@@ -753,9 +885,145 @@ impl ViewerState {
             }
         }
 
+        let ray = self.screen_ray(pos, rect, basis, scale);
+        for reference in &self.references {
+            let (kind, world) = match *reference {
+                ReferenceShape::Point(point) => (SnapKind::ReferencePoint, point),
+                ReferenceShape::Line { origin, direction } => {
+                    let Some((ray_origin, ray_dir)) = ray else {
+                        continue;
+                    };
+                    let point = closest_on_line(origin, direction, ray_origin, ray_dir);
+                    (SnapKind::ReferenceLine, point)
+                }
+                ReferenceShape::Plane { .. } => continue,
+            };
+            if let Some((screen, depth)) = self.project(world, rect, basis, scale) {
+                consider(kind, world, screen, depth);
+            }
+        }
+
+        // Planes cover the whole view, so they only catch the cursor when nothing else does.
+        if best.is_none() {
+            for reference in &self.references {
+                let ReferenceShape::Plane { origin, normal } = *reference else {
+                    continue;
+                };
+                let Some(world) =
+                    screen_point_on_plane(pos, rect, basis, scale, basis.pos, origin, normal)
+                else {
+                    continue;
+                };
+                let Some((screen, depth)) = self.project(world, rect, basis, scale) else {
+                    continue;
+                };
+                if best.is_none_or(|current| depth < current.depth) {
+                    best = Some(SnapHit {
+                        kind: SnapKind::ReferencePlane,
+                        world,
+                        screen,
+                        distance: 0.0,
+                        depth,
+                    });
+                }
+            }
+        }
+
         best
     }
 
+    fn draw_references(
+        &self,
+        painter: &mut impl OverlayPainter,
+        rect: Rect,
+        basis: &CameraBasis,
+        scale: f64,
+    ) {
+        if self.references.is_empty() {
+            return;
+        }
+        let stroke = Stroke::new(1.0, Color32::from_rgb(200, 130, 255));
+        let reach = rect.width().max(rect.height()) as f64 / scale;
+        let project = |point: Vec3| self.project(point, rect, basis, scale).map(|(p, _)| p);
+        for reference in &self.references {
+            match *reference {
+                ReferenceShape::Point(point) => {
+                    if let Some(center) = project(point) {
+                        let r = 5.0;
+                        painter.line_segment(
+                            center + Vec2::new(-r, -r),
+                            center + Vec2::new(r, r),
+                            stroke,
+                        );
+                        painter.line_segment(
+                            center + Vec2::new(-r, r),
+                            center + Vec2::new(r, -r),
+                            stroke,
+                        );
+                    }
+                }
+                ReferenceShape::Line { origin, direction } => {
+                    let middle = origin + direction * (self.target - origin).dot(direction);
+                    if let (Some(start), Some(end)) = (
+                        project(middle - direction * reach),
+                        project(middle + direction * reach),
+                    ) {
+                        dashed_line(painter, start, end, stroke);
+                    }
+                }
+                ReferenceShape::Plane { origin, normal } => {
+                    let center = self.target - normal * (self.target - origin).dot(normal);
+                    let u = normal.cross(Self::default_up(normal)).normalized();
+                    let v = normal.cross(u);
+                    let half = reach * 0.5;
+                    let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                        .map(|(a, b)| project(center + u * (a * half) + v * (b * half)));
+                    if corners.iter().all(Option::is_some) {
+                        painter.polygon(
+                            corners.into_iter().flatten().collect(),
+                            Color32::from_rgba_unmultiplied(200, 130, 255, 18),
+                            stroke,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// The work plane as a square around its origin with its two in-plane axes.
+    fn draw_work_plane(
+        &self,
+        painter: &mut impl OverlayPainter,
+        rect: Rect,
+        basis: &CameraBasis,
+        scale: f64,
+    ) {
+        let Some((origin, normal)) = self.work_plane else {
+            return;
+        };
+        let stroke = Stroke::new(1.0, Color32::from_rgb(90, 200, 230));
+        let half = rect.width().min(rect.height()) as f64 / scale * 0.2;
+        let u = normal.cross(Self::default_up(normal)).normalized();
+        let v = normal.cross(u);
+        let project = |point: Vec3| self.project(point, rect, basis, scale).map(|(p, _)| p);
+        let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .map(|(a, b)| project(origin + u * (a * half) + v * (b * half)));
+        if corners.iter().all(Option::is_some) {
+            painter.polygon(
+                corners.into_iter().flatten().collect(),
+                Color32::from_rgba_unmultiplied(90, 200, 230, 16),
+                stroke,
+            );
+        }
+        for axis in [u, v] {
+            if let (Some(start), Some(end)) =
+                (project(origin - axis * half), project(origin + axis * half))
+            {
+                dashed_line(painter, start, end, stroke);
+            }
+        }
+    }
+
     fn cached_snap(
         &mut self,
         pos: Point2,
@@ -922,13 +1190,13 @@ impl ViewerState {
         let outline = Stroke::new(4.2, Color32::from_rgba_unmultiplied(15, 12, 8, 140));
 
         match snap.kind {
-            SnapKind::Vertex => {
+            SnapKind::Vertex | SnapKind::ReferencePoint => {
                 let rect = Rect::from_center_size(center, vec2(size, size));
                 painter.rect_stroke(rect, 1.0, outline);
                 painter.rect_filled(rect, 1.0, fill);
                 painter.rect_stroke(rect, 1.0, stroke);
             }
-            SnapKind::EdgeMidpoint => {
+            SnapKind::EdgeMidpoint | SnapKind::ReferenceLine => {
                 let r = size * 0.6;
                 let points = vec![
                     center + Vec2::new(0.0, -r),
@@ -943,7 +1211,7 @@ impl ViewerState {
                 );
                 painter.polygon(points, fill, stroke);
             }
-            SnapKind::FaceCenter => {
+            SnapKind::FaceCenter | SnapKind::ReferencePlane => {
                 let r = size * 0.7;
                 let points = vec![
                     center + Vec2::new(0.0, -r),
@@ -1056,6 +1324,9 @@ fn snap_radius(kind: SnapKind) -> f32 {
         SnapKind::Vertex => 7.0,
         SnapKind::EdgeMidpoint => 7.0,
         SnapKind::FaceCenter => 9.0,
+        SnapKind::ReferencePoint => 7.0,
+        SnapKind::ReferenceLine => 6.0,
+        SnapKind::ReferencePlane => 0.0,
     }
 }
 
@@ -1065,9 +1336,11 @@ fn same_vec3(a: Vec3, b: Vec3) -> bool {
 
 fn snap_priority(kind: SnapKind) -> u8 {
     match kind {
-        SnapKind::Vertex => 0,
+        SnapKind::Vertex | SnapKind::ReferencePoint => 0,
         SnapKind::EdgeMidpoint => 1,
         SnapKind::FaceCenter => 2,
+        SnapKind::ReferenceLine => 3,
+        SnapKind::ReferencePlane => 4,
     }
 }
 
@@ -1076,6 +1349,38 @@ fn snap_label(kind: SnapKind) -> &'static str {
         SnapKind::Vertex => "Vertex",
         SnapKind::EdgeMidpoint => "Edge midpoint",
         SnapKind::FaceCenter => "Face center",
+        SnapKind::ReferencePoint => "Reference point",
+        SnapKind::ReferenceLine => "Reference line",
+        SnapKind::ReferencePlane => "Reference plane",
+    }
+}
+
+/// Point on the line `origin + t * direction` nearest to the view ray; the line's origin when
+/// the two are parallel.
+fn closest_on_line(origin: Vec3, direction: Vec3, ray_origin: Vec3, ray_dir: Vec3) -> Vec3 {
+    let w = ray_origin - origin;
+    let b = ray_dir.dot(direction);
+    let denom = ray_dir.dot(ray_dir) * direction.dot(direction) - b * b;
+    if denom.abs() <= 1.0e-9 {
+        return origin;
+    }
+    let t = (ray_dir.dot(ray_dir) * direction.dot(w) - b * ray_dir.dot(w)) / denom;
+    origin + direction * t
+}
+
+pub(crate) fn dashed_line(painter: &mut impl OverlayPainter, start: Point2, end: Point2, stroke: Stroke) {
+    let delta = end - start;
+    let length = delta.length();
+    if length <= 1.0 {
+        return;
+    }
+    let dash = 8.0;
+    let step = delta * (1.0 / length);
+    let mut along = 0.0;
+    while along < length {
+        let to = (along + dash).min(length);
+        painter.line_segment(start + step * along, start + step * to, stroke);
+        along += dash * 2.0;
     }
 }
 
@@ -0,0 +1,72 @@
+use super::math::Vec3;
+
+/// How far (in degrees) the cursor may stray from a tracking ray and still lock onto it.
+const TRACKING_TOLERANCE_DEG: f64 = 3.0;
+
+pub const TRACKING_INCREMENTS: [f64; 4] = [15.0, 30.0, 45.0, 90.0];
+
+/// Polar tracking settings: temporary rays from the last point at multiples of
+/// `increment_deg`, measured in the XY plane from +X.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PolarTracking {
+    pub enabled: bool,
+    pub increment_deg: f64,
+}
+
+impl Default for PolarTracking {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            increment_deg: 45.0,
+        }
+    }
+}
+
+/// The cursor locked onto a tracking ray.
+#[derive(Clone, Copy, Debug)]
+pub struct TrackingHit {
+    pub origin: Vec3,
+    /// Unit direction of the ray, horizontal.
+    pub direction: Vec3,
+    pub angle_deg: f64,
+    pub distance: f64,
+}
+
+impl TrackingHit {
+    pub fn point(&self) -> Vec3 {
+        self.at_distance(self.distance)
+    }
+
+    pub fn at_distance(&self, distance: f64) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+}
+
+impl PolarTracking {
+    /// Locks `cursor` onto the nearest tracking ray from `origin`, keeping the origin's
+    /// elevation; `None` when tracking is off or the cursor is not near any ray.
+    pub fn track(&self, origin: Vec3, cursor: Vec3) -> Option<TrackingHit> {
+        if !self.enabled || self.increment_deg <= 0.0 {
+            return None;
+        }
+        let dx = cursor.x - origin.x;
+        let dy = cursor.y - origin.y;
+        if dx.hypot(dy) <= 1.0e-9 {
+            return None;
+        }
+
+        let angle = dy.atan2(dx).to_degrees();
+        let snapped = (angle / self.increment_deg).round() * self.increment_deg;
+        if (angle - snapped).abs() > TRACKING_TOLERANCE_DEG {
+            return None;
+        }
+        let radians = snapped.to_radians();
+        let direction = Vec3::new(radians.cos(), radians.sin(), 0.0);
+        Some(TrackingHit {
+            origin,
+            direction,
+            angle_deg: snapped.rem_euclid(360.0),
+            distance: dx * direction.x + dy * direction.y,
+        })
+    }
+}
@@ -10,9 +10,16 @@ use truck_rendimpl::{
     WireFrameState,
 };
 
+use std::time::Duration;
+use web_time::Instant;
+
 use super::math::Vec3;
+use super::point_cloud::PointCloudInstance;
 use super::ui::{Color32, Rect};
-use super::{ViewMode, ViewerMesh, ViewerState};
+use super::{ViewMode, ViewerMesh, ViewerPointCloud, ViewerState};
+
+const AXIS_DRAW_CALLS: usize = 3;
+const GPU_VEC3_BYTES: usize = 3 * std::mem::size_of::<f32>();
 
 pub struct TruckRenderer {
     scene: Scene,
@@ -23,11 +30,23 @@ pub struct TruckRenderer {
     mesh_revision: u64,
     instances: Vec<ElementInstances>,
     axes: AxisInstances,
+    clouds: Vec<PointCloudInstance>,
+    cloud_revision: Option<u64>,
     last_view_mode: Option<ViewMode>,
     last_selected: Option<usize>,
     last_hovered: Option<usize>,
     last_colors_hash: u64,
     instances_dirty: bool,
+    stats: RenderStats,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RenderStats {
+    pub instances: usize,
+    pub draw_calls: usize,
+    pub triangles: usize,
+    pub buffer_bytes: usize,
+    pub render_time: Duration,
 }
 
 struct RenderTarget {
@@ -39,6 +58,8 @@ struct RenderTarget {
 struct ElementInstances {
     surface: PolygonInstance,
     wire: WireFrameInstance,
+    surface_visible: bool,
+    wire_visible: bool,
 }
 
 struct AxisInstances {
@@ -88,11 +109,14 @@ impl TruckRenderer {
             mesh_revision: 0,
             instances: Vec::new(),
             axes,
+            clouds: Vec::new(),
+            cloud_revision: None,
             last_view_mode: None,
             last_selected: None,
             last_hovered: None,
             last_colors_hash: 0,
             instances_dirty: true,
+            stats: RenderStats::default(),
         };
         renderer.axes.add_to_scene(&mut renderer.scene);
         renderer
@@ -133,10 +157,42 @@ impl TruckRenderer {
             selected,
         );
 
+        let started = Instant::now();
         self.scene.render(&self.target.view);
+        self.stats.render_time = started.elapsed();
+        self.stats.draw_calls = self.visible_draw_calls();
         true
     }
 
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    /// Uploads `clouds` to be drawn with the model from the next render, unless they were
+    /// already uploaded at `revision`.
+    pub fn set_point_clouds(&mut self, revision: u64, clouds: &[ViewerPointCloud]) {
+        if self.cloud_revision == Some(revision) {
+            return;
+        }
+        self.cloud_revision = Some(revision);
+        self.clouds = clouds
+            .iter()
+            .filter_map(|cloud| PointCloudInstance::new(&self.device, cloud))
+            .collect();
+        self.rebuild_draw_order();
+        // Re-adding the elements makes them all visible; the next render hides them again.
+        self.instances_dirty = true;
+    }
+
+    fn visible_draw_calls(&self) -> usize {
+        let elements: usize = self
+            .instances
+            .iter()
+            .map(|instance| instance.surface_visible as usize + instance.wire_visible as usize)
+            .sum();
+        elements + AXIS_DRAW_CALLS + self.clouds.len()
+    }
+
     fn ensure_target(&mut self, size: [u32; 2]) {
         if self.target.size != size {
             self.target = RenderTarget::new(&self.device, size);
@@ -164,6 +220,8 @@ impl TruckRenderer {
 
         let count = meshes.len().min(poly_meshes.len());
         let mut instances = Vec::new();
+        let mut triangles = 0usize;
+        let mut buffer_bytes = 0usize;
         for idx in 0..count {
             let mesh = &meshes[idx];
             let poly = &poly_meshes[idx];
@@ -183,8 +241,18 @@ impl TruckRenderer {
             };
             let edges = edge_segments(mesh);
             let wire = self.creator.create_instance(&edges, &wire_state);
-            instances.push(ElementInstances { surface, wire });
+            triangles += mesh.tri_faces.len();
+            buffer_bytes += estimate_buffer_bytes(poly, mesh, edges.len());
+            instances.push(ElementInstances {
+                surface,
+                wire,
+                surface_visible: true,
+                wire_visible: true,
+            });
         }
+        self.stats.instances = instances.len();
+        self.stats.triangles = triangles;
+        self.stats.buffer_bytes = buffer_bytes;
         self.instances = instances;
         self.rebuild_draw_order();
         self.instances_dirty = true;
@@ -259,7 +327,7 @@ impl TruckRenderer {
                     highlight,
                     default_color,
                     material_color,
-                    update_pipeline,
+                    update_pipeline || colors_changed,
                 );
             }
         } else {
@@ -380,6 +448,12 @@ impl TruckRenderer {
                 }
             };
 
+        // Translucent element colors, such as view filter transparency, blend on top of the mode.
+        if base.a < u8::MAX && view_mode != ViewMode::Material {
+            alpha *= base.a as f32 / 255.0;
+            alpha_blend = true;
+        }
+
         if !visible {
             surface_visible = false;
             wire_visible = Some(idx) == selected || Some(idx) == hovered;
@@ -399,6 +473,8 @@ impl TruckRenderer {
 
         self.scene.set_visibility(&instance.surface, surface_visible);
         self.scene.set_visibility(&instance.wire, wire_visible);
+        instance.surface_visible = surface_visible;
+        instance.wire_visible = wire_visible;
         self.scene.update_bind_group(&instance.surface);
         self.scene.update_bind_group(&instance.wire);
         if update_pipeline {
@@ -414,6 +490,9 @@ impl TruckRenderer {
         for instance in &self.instances {
             self.scene.add_object(&instance.wire);
         }
+        for cloud in &self.clouds {
+            self.scene.add_object(cloud);
+        }
         self.axes.add_to_scene(&mut self.scene);
     }
 }
@@ -618,6 +697,13 @@ fn edge_segments(mesh: &ViewerMesh) -> Vec<(Point3, Point3)> {
     segments
 }
 
+fn estimate_buffer_bytes(poly: &PolygonMesh, mesh: &ViewerMesh, segments: usize) -> usize {
+    let vertex_bytes = (poly.positions().len() + poly.normals().len()) * GPU_VEC3_BYTES;
+    let index_bytes = mesh.tri_faces.len() * 3 * std::mem::size_of::<u32>();
+    let wire_bytes = segments * 2 * GPU_VEC3_BYTES;
+    vertex_bytes + index_bytes + wire_bytes
+}
+
 fn flat_material(color: Color32, alpha: f32, alpha_blend: bool) -> Material {
     Material {
         albedo: color_to_vec4(color, alpha),